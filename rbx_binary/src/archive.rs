@@ -0,0 +1,388 @@
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    io::{self, Read, Seek, SeekFrom, Write},
+};
+
+use rbx_dom_weak::{types::Ref, WeakDom};
+use thiserror::Error;
+
+use crate::{
+    core::{RbxReadExt, RbxWriteExt},
+    deserializer::{Deserializer, Error as DecodeError},
+    serializer::{Error as EncodeError, Serializer},
+};
+
+/// The bytes at the start and end of every archive.
+const ARCHIVE_MAGIC: &[u8; 8] = b"<rbxarc!";
+
+/// The version of the archive format written by [`ArchiveWriter`].
+const ARCHIVE_VERSION: u16 = 0;
+
+/// The length of the magic bytes and version at the start of every archive.
+const ARCHIVE_HEADER_LEN: u64 = 10;
+
+/// The length of the manifest offset and magic bytes at the end of every
+/// archive.
+const ARCHIVE_TRAILER_LEN: u64 = 16;
+
+/// One model in an archive, as listed in the archive's manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ArchiveEntry {
+    /// The name the model was added to the archive with.
+    pub name: String,
+
+    /// The number of bytes the model takes up in the archive.
+    pub len: u64,
+
+    offset: u64,
+}
+
+/// Writes many models into one archive, which can be read back with
+/// [`ArchiveReader`].
+///
+/// Each model is stored as a complete binary model one after the other,
+/// followed by a manifest listing the name and position of every model, so
+/// archives can be written to any stream without seeking.
+///
+/// # Format
+///
+/// All integers are little-endian, and offsets count bytes from the start of
+/// the archive. An archive is made of:
+///
+/// 1. A header of 10 bytes:
+///    - 8 bytes: the magic bytes `<rbxarc!`.
+///    - `u16`: the format version, which is 0.
+/// 2. The bytes of every model, with nothing between them, in the order
+///    they were added. Each one is a complete binary model, like the
+///    contents of an `.rbxm` file.
+/// 3. The manifest:
+///    - `u32`: the number of entries.
+///    - For each entry, in the order the models were added:
+///      - `u32`: the length of the entry's name in bytes.
+///      - The entry's name, as UTF-8.
+///      - `u64`: the offset of the entry's model.
+///      - `u64`: the length of the entry's model in bytes.
+/// 4. A trailer of 16 bytes:
+///    - `u64`: the offset of the manifest.
+///    - 8 bytes: the magic bytes `<rbxarc!` again.
+///
+/// [`ArchiveReader`] rejects archives whose entries don't lie between the
+/// header and the manifest, or that have two entries with the same name.
+///
+/// ```
+/// use rbx_binary::{ArchiveReader, ArchiveWriter};
+/// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+///
+/// let mut writer = ArchiveWriter::new(Vec::new())?;
+/// for name in ["Tree", "Rock"] {
+///     let dom = WeakDom::new(InstanceBuilder::new("Model").with_name(name));
+///     writer.add(name, &dom, &[dom.root_ref()])?;
+/// }
+/// let archive = writer.finish()?;
+///
+/// let mut reader = ArchiveReader::new(std::io::Cursor::new(archive))?;
+/// assert_eq!(reader.entries().len(), 2);
+///
+/// let rock = reader.open("Rock")?;
+/// let model = rock.get_by_ref(rock.root().children()[0]).unwrap();
+/// assert_eq!(model.name, "Rock");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct ArchiveWriter<W> {
+    writer: W,
+    position: u64,
+    entries: Vec<ArchiveEntry>,
+    names: HashMap<String, usize>,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Starts writing an archive to `writer`.
+    pub fn new(mut writer: W) -> Result<Self, ArchiveError> {
+        writer.write_all(ARCHIVE_MAGIC)?;
+        writer.write_le_u16(ARCHIVE_VERSION)?;
+
+        Ok(Self {
+            writer,
+            position: ARCHIVE_HEADER_LEN,
+            entries: Vec::new(),
+            names: HashMap::new(),
+        })
+    }
+
+    /// Serializes `refs` in `dom` with the default settings and adds them to
+    /// the archive as the entry `name`.
+    pub fn add<N: Into<String>>(
+        &mut self,
+        name: N,
+        dom: &WeakDom,
+        refs: &[Ref],
+    ) -> Result<(), ArchiveError> {
+        self.add_with(name, dom, refs, &Serializer::new())
+    }
+
+    /// Serializes `refs` in `dom` with `serializer` and adds them to the
+    /// archive as the entry `name`.
+    pub fn add_with<N: Into<String>>(
+        &mut self,
+        name: N,
+        dom: &WeakDom,
+        refs: &[Ref],
+        serializer: &Serializer,
+    ) -> Result<(), ArchiveError> {
+        let mut buffer = Vec::new();
+        serializer.serialize(&mut buffer, dom, refs)?;
+        self.add_serialized(name, &buffer)
+    }
+
+    /// Adds a model that has already been serialized, like the contents of
+    /// an `.rbxm` file, to the archive as the entry `name`. The model isn't
+    /// checked, so it's up to the caller to make sure it's a valid binary
+    /// model.
+    pub fn add_serialized<N: Into<String>>(
+        &mut self,
+        name: N,
+        model: &[u8],
+    ) -> Result<(), ArchiveError> {
+        let name = name.into();
+        if self.names.contains_key(&name) {
+            return Err(ArchiveError::DuplicateEntry { name });
+        }
+
+        // The manifest stores the number of entries and the length of each
+        // name as a `u32`.
+        if u32::try_from(self.entries.len() + 1).is_err() {
+            return Err(ArchiveError::TooManyEntries);
+        }
+        if u32::try_from(name.len()).is_err() {
+            return Err(ArchiveError::NameTooLong { len: name.len() });
+        }
+
+        self.writer.write_all(model)?;
+
+        self.names.insert(name.clone(), self.entries.len());
+        self.entries.push(ArchiveEntry {
+            name,
+            len: model.len() as u64,
+            offset: self.position,
+        });
+        self.position += model.len() as u64;
+
+        Ok(())
+    }
+
+    /// Writes the archive's manifest and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, ArchiveError> {
+        log::trace!("Writing manifest of {} entries", self.entries.len());
+
+        // `add_serialized` never lets the number of entries overflow a `u32`.
+        self.writer
+            .write_le_u32(u32::try_from(self.entries.len()).unwrap())?;
+        for entry in &self.entries {
+            self.writer.write_string(&entry.name)?;
+            self.writer.write_all(&entry.offset.to_le_bytes())?;
+            self.writer.write_all(&entry.len.to_le_bytes())?;
+        }
+
+        self.writer.write_all(&self.position.to_le_bytes())?;
+        self.writer.write_all(ARCHIVE_MAGIC)?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Reads models out of an archive written by [`ArchiveWriter`].
+///
+/// Only the archive's manifest is read up front. Each model is read and
+/// decoded when it's opened, so opening one model from a large archive is
+/// cheap.
+#[derive(Debug)]
+pub struct ArchiveReader<R> {
+    reader: R,
+    entries: Vec<ArchiveEntry>,
+    names: HashMap<String, usize>,
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    /// Reads the manifest of the archive in `reader`.
+    pub fn new(mut reader: R) -> Result<Self, ArchiveError> {
+        let mut magic = [0; 8];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(ArchiveError::NotAnArchive);
+        }
+
+        let version = reader.read_le_u16()?;
+        if version != ARCHIVE_VERSION {
+            return Err(ArchiveError::UnknownVersion { version });
+        }
+
+        let end = reader.seek(SeekFrom::End(0))?;
+        if end < ARCHIVE_HEADER_LEN + ARCHIVE_TRAILER_LEN {
+            return Err(ArchiveError::NotAnArchive);
+        }
+
+        reader.seek(SeekFrom::Start(end - ARCHIVE_TRAILER_LEN))?;
+        let manifest_offset = read_u64(&mut reader)?;
+        reader.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(ArchiveError::NotAnArchive);
+        }
+
+        if !(ARCHIVE_HEADER_LEN..=end - ARCHIVE_TRAILER_LEN).contains(&manifest_offset) {
+            return Err(ArchiveError::MalformedManifest);
+        }
+
+        // The manifest is read from its own buffer so that a bad entry count
+        // can't read past the end of it.
+        let mut manifest = Vec::new();
+        reader.seek(SeekFrom::Start(manifest_offset))?;
+        (&mut reader)
+            .take(end - ARCHIVE_TRAILER_LEN - manifest_offset)
+            .read_to_end(&mut manifest)?;
+        let mut manifest = manifest.as_slice();
+
+        let count = manifest.read_le_u32().map_err(malformed)?;
+        let mut entries = Vec::new();
+        let mut names = HashMap::new();
+
+        for index in 0..count as usize {
+            let name = manifest.read_string().map_err(malformed)?;
+            let offset = read_u64(&mut manifest).map_err(malformed)?;
+            let len = read_u64(&mut manifest).map_err(malformed)?;
+
+            let in_bounds = offset >= ARCHIVE_HEADER_LEN
+                && offset
+                    .checked_add(len)
+                    .is_some_and(|entry_end| entry_end <= manifest_offset);
+
+            if !in_bounds || names.insert(name.clone(), index).is_some() {
+                return Err(ArchiveError::MalformedManifest);
+            }
+
+            entries.push(ArchiveEntry { name, len, offset });
+        }
+
+        Ok(Self {
+            reader,
+            entries,
+            names,
+        })
+    }
+
+    /// Returns every entry in the archive, in the order they were added.
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    /// Returns the entry named `name`, if there is one.
+    pub fn entry(&self, name: &str) -> Option<&ArchiveEntry> {
+        self.names.get(name).map(|&index| &self.entries[index])
+    }
+
+    /// Reads and decodes the entry named `name` with the default settings.
+    pub fn open(&mut self, name: &str) -> Result<WeakDom, ArchiveError> {
+        self.open_with(name, &Deserializer::new())
+    }
+
+    /// Reads and decodes the entry named `name` with `deserializer`.
+    pub fn open_with(
+        &mut self,
+        name: &str,
+        deserializer: &Deserializer,
+    ) -> Result<WeakDom, ArchiveError> {
+        let reader = self.open_serialized(name)?;
+        Ok(deserializer.deserialize(reader)?)
+    }
+
+    /// Returns a reader over the serialized bytes of the entry named `name`,
+    /// without decoding them.
+    pub fn open_serialized(&mut self, name: &str) -> Result<impl Read + '_, ArchiveError> {
+        let entry = self.entry(name).ok_or_else(|| ArchiveError::UnknownEntry {
+            name: name.to_owned(),
+        })?;
+        let (offset, len) = (entry.offset, entry.len);
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+        Ok((&mut self.reader).take(len))
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn malformed(_: io::Error) -> ArchiveError {
+    ArchiveError::MalformedManifest
+}
+
+/// An error that happened while writing or reading an archive.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ArchiveError {
+    /// The file isn't an archive written by [`ArchiveWriter`].
+    #[error("File is not an rbx_binary archive")]
+    NotAnArchive,
+
+    /// The archive was written with a version of the format that isn't known.
+    #[error("Unknown archive version {version}. Known versions are: 0")]
+    UnknownVersion {
+        /// The version in the archive's header.
+        version: u16,
+    },
+
+    /// The archive's manifest couldn't be read, or listed entries that don't
+    /// fit in the archive.
+    #[error("Archive manifest is malformed")]
+    MalformedManifest,
+
+    /// An entry was added with the same name as one already in the archive.
+    #[error("Archive already has an entry named {name:?}")]
+    DuplicateEntry {
+        /// The name of the entry.
+        name: String,
+    },
+
+    /// An entry was added to an archive that already has `u32::MAX` entries.
+    #[error("Archive cannot hold more than {} entries", u32::MAX)]
+    TooManyEntries,
+
+    /// An entry was added with a name longer than `u32::MAX` bytes.
+    #[error(
+        "Archive entry name is {len} bytes long, which is more than {} bytes",
+        u32::MAX
+    )]
+    NameTooLong {
+        /// The length of the name in bytes.
+        len: usize,
+    },
+
+    /// No entry in the archive has the requested name.
+    #[error("Archive has no entry named {name:?}")]
+    UnknownEntry {
+        /// The requested name.
+        name: String,
+    },
+
+    /// A model couldn't be serialized.
+    #[error(transparent)]
+    Encode(#[from] EncodeError),
+
+    /// A model couldn't be decoded.
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+
+    /// Reading or writing the archive failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
@@ -2,59 +2,216 @@ use std::{
     fmt,
     io::{self, Read, Write},
     str,
+    time::{Duration, Instant},
 };
 
 use crate::{
     core::{RbxReadExt, RbxWriteExt},
+    deserializer::{ChunkInfo, DecodeCache, DecodeLimits},
     serializer::CompressionType,
 };
 
-const ZSTD_MAGIC_NUMBER: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+pub(crate) const ZSTD_MAGIC_NUMBER: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
 
 /// Represents one chunk from a binary model file.
 #[derive(Debug)]
 pub struct Chunk {
     pub name: [u8; 4],
     pub data: Vec<u8>,
+
+    /// The compression the chunk was stored with.
+    pub compression: CompressionType,
+
+    /// The number of bytes the chunk took up in the file, not including its
+    /// header.
+    pub stored_len: u32,
+
+    /// How long it took to decompress the chunk's data.
+    pub decompress_time: Duration,
 }
 
 impl Chunk {
     /// Reads and decodes a `Chunk` from the given reader.
-    pub fn decode<R: Read>(mut reader: R) -> io::Result<Chunk> {
+    pub fn decode<R: Read>(reader: R) -> io::Result<Chunk> {
+        Self::decode_limited(reader, &DecodeLimits::new())
+    }
+
+    /// Reads and decodes a `Chunk` from the given reader, failing before its
+    /// data is read if it's longer than `limits` allows.
+    pub fn decode_limited<R: Read>(reader: R, limits: &DecodeLimits) -> io::Result<Chunk> {
+        Self::decode_cached(reader, limits, &mut DecodeCache::default(), false)
+    }
+
+    /// Reads and decodes a `Chunk` like [`Chunk::decode_limited`], reading
+    /// and decompressing it into buffers from `cache`. Decompression is only
+    /// timed if `timed` is set.
+    pub(crate) fn decode_cached<R: Read>(
+        mut reader: R,
+        limits: &DecodeLimits,
+        cache: &mut DecodeCache,
+        timed: bool,
+    ) -> io::Result<Chunk> {
         let header = decode_chunk_header(&mut reader)?;
 
         log::trace!("{header}");
 
-        let data = if header.compressed_len == 0 {
+        limits.check_chunk_len(header.len, header.compressed_len)?;
+
+        let mut decompress_time = Duration::ZERO;
+        let (data, compression) = if header.compressed_len == 0 {
             log::trace!("No compression");
-            let mut data = Vec::with_capacity(header.len as usize);
+            let mut data = cache.buffer();
+            data.reserve(header.len as usize);
             reader.take(header.len as u64).read_to_end(&mut data)?;
-            data
+            (data, CompressionType::None)
         } else {
-            let mut compressed_data = Vec::with_capacity(header.compressed_len as usize);
-            reader
+            let mut compressed_data = cache.take_compressed();
+            compressed_data.reserve(header.compressed_len as usize);
+            let read = reader
                 .take(header.compressed_len as u64)
-                .read_to_end(&mut compressed_data)?;
-
-            if &compressed_data[0..4] == ZSTD_MAGIC_NUMBER {
-                log::trace!("ZSTD compression");
-                zstd::bulk::decompress(&compressed_data, header.len as usize)?
-            } else {
-                log::trace!("LZ4 compression");
-                lz4_flex::block::decompress(&compressed_data, header.len as usize)
-                    .map_err(io::Error::other)?
+                .read_to_end(&mut compressed_data);
+
+            let started = start_timer(timed);
+            let decompressed = read.and_then(|_| {
+                let mut data = cache.buffer();
+                let len = header.len as usize;
+
+                if compressed_data.starts_with(ZSTD_MAGIC_NUMBER) {
+                    log::trace!("ZSTD compression");
+                    cache.decompress_zstd(&compressed_data, len, &mut data)?;
+                    Ok((data, CompressionType::Zstd))
+                } else {
+                    log::trace!("LZ4 compression");
+                    data.resize(len, 0);
+                    let written = lz4_flex::block::decompress_into(&compressed_data, &mut data)
+                        .map_err(io::Error::other)?;
+                    data.truncate(written);
+                    Ok((data, CompressionType::Lz4))
+                }
+            });
+            if let Some(started) = started {
+                decompress_time = started.elapsed();
             }
+            cache.restore_compressed(compressed_data);
+
+            decompressed?
         };
 
-        assert_eq!(data.len(), header.len as usize);
+        if data.len() != header.len as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Chunk was {} bytes long, but its header said it would be {}",
+                    data.len(),
+                    header.len
+                ),
+            ));
+        }
+
+        let stored_len = if header.compressed_len == 0 {
+            header.len
+        } else {
+            header.compressed_len
+        };
 
         Ok(Chunk {
             name: header.name,
             data,
+            compression,
+            stored_len,
+            decompress_time,
         })
     }
 }
 
+/// Compresses `data` with Zstandard.
+#[cfg(feature = "zstd")]
+fn zstd_compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    zstd::bulk::compress(data, 0)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_compress(_data: &[u8]) -> io::Result<Vec<u8>> {
+    Err(zstd_unsupported())
+}
+
+/// Decompresses Zstandard-compressed `data`, which is expected to hold at
+/// most `len` bytes.
+#[cfg(feature = "zstd")]
+pub(crate) fn zstd_decompress(data: &[u8], len: usize) -> io::Result<Vec<u8>> {
+    zstd::bulk::decompress(data, len)
+}
+
+#[cfg(not(feature = "zstd"))]
+pub(crate) fn zstd_decompress(_data: &[u8], _len: usize) -> io::Result<Vec<u8>> {
+    Err(zstd_unsupported())
+}
+
+/// The error for reading or writing a Zstandard-compressed chunk when
+/// rbx_binary was built without the `zstd` feature.
+#[cfg(not(feature = "zstd"))]
+pub(crate) fn zstd_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Zstandard-compressed chunks need rbx_binary's zstd feature",
+    )
+}
+
+/// Starts timing something if `enabled`. `Instant::now` panics on
+/// wasm32-unknown-unknown, which has no clock, so nothing is timed there.
+pub(crate) fn start_timer(enabled: bool) -> Option<Instant> {
+    if enabled && !cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        Some(Instant::now())
+    } else {
+        None
+    }
+}
+
+/// Reads a chunk's header and skips over its data without decompressing
+/// it, returning a description of the chunk.
+pub fn skip_chunk<R: Read>(mut reader: R) -> io::Result<ChunkInfo> {
+    let header = decode_chunk_header(&mut reader)?;
+
+    log::trace!("Skipping {header}");
+
+    let stored_len = if header.compressed_len == 0 {
+        header.len
+    } else {
+        header.compressed_len
+    };
+
+    // Only the first few bytes are needed to tell which compression was used.
+    let mut magic = Vec::with_capacity(ZSTD_MAGIC_NUMBER.len());
+    let mut reader = reader.take(stored_len as u64);
+    reader
+        .by_ref()
+        .take(ZSTD_MAGIC_NUMBER.len() as u64)
+        .read_to_end(&mut magic)?;
+
+    let compression = if header.compressed_len == 0 {
+        CompressionType::None
+    } else if magic == ZSTD_MAGIC_NUMBER {
+        CompressionType::Zstd
+    } else {
+        CompressionType::Lz4
+    };
+
+    let skipped = magic.len() as u64 + io::copy(&mut reader, &mut io::sink())?;
+    if skipped != stored_len as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("Chunk was {skipped} bytes long, but its header said it would be {stored_len}"),
+        ));
+    }
+
+    Ok(ChunkInfo::new(
+        &header.name,
+        compression,
+        stored_len,
+        header.len,
+    ))
+}
+
 /// Holds a chunk that is currently being written.
 ///
 /// This type intended to be written into via io::Write and then dumped into the
@@ -62,7 +219,7 @@ impl Chunk {
 /// automatically.
 #[must_use]
 pub struct ChunkBuilder {
-    chunk_name: &'static [u8],
+    chunk_name: [u8; 4],
     compression: CompressionType,
     buffer: Vec<u8>,
 }
@@ -70,19 +227,27 @@ pub struct ChunkBuilder {
 impl ChunkBuilder {
     /// Creates a new `ChunkBuilder` with the given name and compression
     /// setting.
-    pub fn new(chunk_name: &'static [u8], compression: CompressionType) -> Self {
+    pub fn new(chunk_name: &[u8; 4], compression: CompressionType) -> Self {
         ChunkBuilder {
-            chunk_name,
+            chunk_name: *chunk_name,
             compression,
             buffer: Vec::new(),
         }
     }
 
-    /// Consume the chunk and write it to the given writer.
-    pub fn dump<W: Write>(self, mut writer: W) -> io::Result<()> {
-        writer.write_all(self.chunk_name)?;
+    /// Consume the chunk and write it to the given writer, returning a
+    /// description of what was written.
+    pub fn dump<W: Write>(self, mut writer: W) -> io::Result<ChunkInfo> {
+        trace_span!(
+            "chunk",
+            name = %String::from_utf8_lossy(&self.chunk_name),
+            len = self.buffer.len(),
+            compression = ?self.compression,
+        );
+
+        writer.write_all(&self.chunk_name)?;
 
-        match self.compression {
+        let stored_len = match self.compression {
             CompressionType::Lz4 => {
                 let compressed = lz4_flex::block::compress(&self.buffer);
 
@@ -91,6 +256,7 @@ impl ChunkBuilder {
                 writer.write_le_u32(0)?;
 
                 writer.write_all(&compressed)?;
+                compressed.len()
             }
             CompressionType::None => {
                 writer.write_le_u32(0)?;
@@ -98,9 +264,10 @@ impl ChunkBuilder {
                 writer.write_le_u32(0)?;
 
                 writer.write_all(&self.buffer)?;
+                self.buffer.len()
             }
             CompressionType::Zstd => {
-                let compressed = zstd::bulk::compress(&self.buffer, 0)?;
+                let compressed = zstd_compress(&self.buffer)?;
 
                 writer.write_le_u32(compressed.len() as u32)?;
                 writer.write_le_u32(self.buffer.len() as u32)?;
@@ -109,10 +276,16 @@ impl ChunkBuilder {
                 // ZSTD includes the magic number when compressing so we don't
                 // have to write it manually
                 writer.write_all(&compressed)?;
+                compressed.len()
             }
-        }
+        };
 
-        Ok(())
+        Ok(ChunkInfo::new(
+            &self.chunk_name,
+            self.compression,
+            stored_len as u32,
+            self.buffer.len() as u32,
+        ))
     }
 }
 
@@ -126,6 +299,135 @@ impl Write for ChunkBuilder {
     }
 }
 
+/// Reads the header of the chunk at the start of `source` without reading its
+/// data, returning the chunk's name and how many bytes it takes up, including
+/// its header.
+pub(crate) fn chunk_extent(source: &[u8]) -> io::Result<([u8; 4], usize)> {
+    let mut reader = source;
+    let header = decode_chunk_header(&mut reader)?;
+
+    let stored_len = if header.compressed_len == 0 {
+        header.len
+    } else {
+        header.compressed_len
+    } as usize;
+
+    if reader.len() < stored_len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "Chunk was {} bytes long, but its header said it would be {stored_len}",
+                reader.len()
+            ),
+        ));
+    }
+
+    Ok((header.name, source.len() - reader.len() + stored_len))
+}
+
+/// Returns the first `N` bytes of the data of the chunk at the start of
+/// `source`, decompressing no more of it than is needed to find them. The
+/// type ID at the start of `INST` and `PROP` chunks can be found this way
+/// without decompressing the rest of the chunk.
+pub(crate) fn peek_chunk_data<const N: usize>(source: &[u8]) -> io::Result<[u8; N]> {
+    let mut reader = source;
+    let header = decode_chunk_header(&mut reader)?;
+    let mut output = [0; N];
+
+    if header.compressed_len == 0 {
+        reader.read_exact(&mut output)?;
+    } else {
+        let data = reader
+            .get(..header.compressed_len as usize)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+
+        if data.starts_with(ZSTD_MAGIC_NUMBER) {
+            zstd_decompress_prefix(data, &mut output)?;
+        } else {
+            lz4_decompress_prefix(data, &mut output)?;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Decompresses the start of Zstandard-compressed `data` into `output`.
+#[cfg(feature = "zstd")]
+fn zstd_decompress_prefix(data: &[u8], output: &mut [u8]) -> io::Result<()> {
+    zstd::stream::read::Decoder::with_buffer(data)?.read_exact(output)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress_prefix(_data: &[u8], _output: &mut [u8]) -> io::Result<()> {
+    Err(zstd_unsupported())
+}
+
+/// Decompresses the start of an LZ4 block into `output`, stopping as soon as
+/// it's full.
+///
+/// A block is a series of sequences, each of which is a token, some literal
+/// bytes, and a match that copies bytes from earlier in the output. The high
+/// four bits of the token are the number of literals, and the low four bits
+/// are the length of the match minus 4. Either is followed by more bytes that
+/// are added to it if it's 15. The match is stored as a 2 byte offset back
+/// from the end of the output, followed by the rest of its length.
+fn lz4_decompress_prefix(mut data: &[u8], output: &mut [u8]) -> io::Result<()> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid LZ4 block");
+    let mut decoded = Vec::with_capacity(output.len());
+
+    while decoded.len() < output.len() {
+        let token = data.read_u8()?;
+
+        let literal_len = lz4_length(&mut data, token >> 4)?;
+        let wanted = literal_len.min(output.len() - decoded.len());
+        let literals = data
+            .get(..wanted)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        decoded.extend_from_slice(literals);
+
+        if decoded.len() == output.len() {
+            break;
+        }
+
+        data = data
+            .get(literal_len..)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+
+        let offset = data.read_le_u16()? as usize;
+        if offset == 0 || offset > decoded.len() {
+            return Err(invalid());
+        }
+
+        // A match can overlap the bytes it writes, so it's copied a byte at
+        // a time.
+        let match_len = lz4_length(&mut data, token & 0x0f)? + 4;
+        for _ in 0..match_len.min(output.len() - decoded.len()) {
+            decoded.push(decoded[decoded.len() - offset]);
+        }
+    }
+
+    output.copy_from_slice(&decoded);
+    Ok(())
+}
+
+/// Reads the rest of a length from an LZ4 sequence that starts with `nibble`.
+fn lz4_length(data: &mut &[u8], nibble: u8) -> io::Result<usize> {
+    let mut len = nibble as usize;
+
+    if nibble == 0x0f {
+        loop {
+            let byte = data.read_u8()?;
+            len += byte as usize;
+
+            if byte != 0xff {
+                break;
+            }
+        }
+    }
+
+    Ok(len)
+}
+
 #[derive(Debug)]
 struct ChunkHeader {
     /// 4-byte short name for the chunk, like "INST" or "PRNT"
@@ -168,10 +470,10 @@ fn decode_chunk_header<R: Read>(source: &mut R) -> io::Result<ChunkHeader> {
     let reserved = source.read_le_u32()?;
 
     if reserved != 0 {
-        panic!(
-            "Chunk reserved space was not zero, it was {}. This chunk may be malformed.",
-            reserved
-        );
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Chunk reserved space was not zero, it was {reserved}. This chunk may be malformed."),
+        ));
     }
 
     Ok(ChunkHeader {
@@ -3,10 +3,9 @@ use std::{
     mem,
 };
 
-use rbx_dom_weak::Ustr;
-use rbx_reflection::{
-    ClassDescriptor, PropertyDescriptor, PropertyKind, PropertySerialization, ReflectionDatabase,
-};
+use rbx_reflection::{PropertyDescriptors, ReflectionDatabase};
+
+use crate::deserializer::LimitExceeded;
 
 pub static FILE_MAGIC_HEADER: &[u8] = b"<roblox!";
 pub static FILE_SIGNATURE: &[u8] = b"\x89\xff\x0d\x0a\x1a\x0a";
@@ -75,10 +74,18 @@ pub trait RbxReadExt: Read {
     /// no guarantees about encoding of things it calls strings. rbx_binary
     /// makes a semantic differentiation between strings and binary buffers,
     /// which makes it more strict than Roblox but more likely to be correct.
+    #[cfg(any(test, feature = "unstable_text_format"))]
     fn read_binary_string(&mut self) -> io::Result<Vec<u8>> {
-        let length = self.read_le_u32()?;
+        self.read_binary_string_limited(None)
+    }
+
+    /// Read a binary "string" like `read_binary_string`, failing with
+    /// `LimitExceeded::StringLength` before anything is allocated if it's
+    /// longer than `limit`.
+    fn read_binary_string_limited(&mut self, limit: Option<usize>) -> io::Result<Vec<u8>> {
+        let length = check_string_len(self.read_le_u32()?, limit)?;
 
-        let mut value = Vec::with_capacity(length as usize);
+        let mut value = Vec::with_capacity(length);
         self.take(length as u64).read_to_end(&mut value)?;
 
         Ok(value)
@@ -88,8 +95,16 @@ pub trait RbxReadExt: Read {
     /// strings. This function isn't always appropriate because Roblox's formats
     /// generally aren't dilligent about data being valid Unicode.
     fn read_string(&mut self) -> io::Result<String> {
-        let length = self.read_le_u32()?;
-        let mut value = String::with_capacity(length as usize);
+        self.read_string_limited(None)
+    }
+
+    /// Read a UTF-8 encoded string like `read_string`, failing with
+    /// `LimitExceeded::StringLength` before anything is allocated if it's
+    /// longer than `limit`.
+    fn read_string_limited(&mut self, limit: Option<usize>) -> io::Result<String> {
+        let length = check_string_len(self.read_le_u32()?, limit)?;
+
+        let mut value = String::with_capacity(length);
         self.take(length as u64).read_to_string(&mut value)?;
 
         Ok(value)
@@ -187,6 +202,16 @@ pub trait RbxReadExt: Read {
 
 impl<R> RbxReadExt for R where R: Read {}
 
+/// Checks the length of a string against `limit`.
+fn check_string_len(length: u32, limit: Option<usize>) -> io::Result<usize> {
+    let length = length as usize;
+
+    match limit {
+        Some(limit) if length > limit => Err(LimitExceeded::StringLength { limit }.into()),
+        _ => Ok(length),
+    }
+}
+
 pub trait RbxWriteExt: Write {
     fn write_le_u32(&mut self, value: u32) -> io::Result<()> {
         self.write_all(&value.to_le_bytes())?;
@@ -337,136 +362,12 @@ pub fn untransform_i64(value: i64) -> i64 {
     ((value as u64) >> 1) as i64 ^ -(value & 1)
 }
 
-pub struct PropertyDescriptors<'db> {
-    pub canonical: &'db PropertyDescriptor<'db>,
-    pub serialized: Option<&'db PropertyDescriptor<'db>>,
-}
-
 /// Find both the canonical and serialized property descriptors for a given
 /// class and property name pair. These might be the same descriptor!
 pub fn find_property_descriptors<'db>(
     database: &'db ReflectionDatabase<'db>,
-    class_name: Ustr,
-    property_name: Ustr,
+    class_name: &str,
+    property_name: &str,
 ) -> Option<PropertyDescriptors<'db>> {
-    let mut class_descriptor = database.classes.get(class_name.as_str())?;
-
-    // We need to find the canonical property descriptor associated with
-    // the property we're working with.
-    //
-    // At each step of the loop, we're checking a new class descriptor to see if
-    // it has an entry for the property name we're looking for. If that class
-    // doesn't have the property, we'll check its superclass until we reach the
-    // root.
-    loop {
-        // If this class descriptor knows about this property name, we're pretty
-        // much done!
-        if let Some(property_descriptor) = class_descriptor.properties.get(property_name.as_str()) {
-            match &property_descriptor.kind {
-                // This property descriptor is the canonical form of this
-                // logical property. That means we've found one of the two
-                // descriptors we're looking for!
-                PropertyKind::Canonical { serialization } => {
-                    let serialized = find_serialized_from_canonical(
-                        class_descriptor,
-                        property_descriptor,
-                        serialization,
-                    );
-
-                    return Some(PropertyDescriptors {
-                        canonical: property_descriptor,
-                        serialized,
-                    });
-                }
-
-                // This descriptor is an alias for another property. While this
-                // descriptor might be one of the two descriptors we need to
-                // return, it's possible that both the canonical and serialized
-                // forms are different.
-                PropertyKind::Alias { alias_for } => {
-                    let canonical = class_descriptor.properties.get(alias_for.as_ref()).unwrap();
-
-                    if let PropertyKind::Canonical { serialization } = &canonical.kind {
-                        let serialized = find_serialized_from_canonical(
-                            class_descriptor,
-                            canonical,
-                            serialization,
-                        );
-
-                        return Some(PropertyDescriptors {
-                            canonical,
-                            serialized,
-                        });
-                    } else {
-                        // If one property in the database calls itself an alias
-                        // of another property, that property must be canonical.
-                        log::error!(
-                            "Property {}.{} is marked as an alias for {}.{}, but the latter is not canonical.",
-                            class_descriptor.name,
-                            property_descriptor.name,
-                            class_descriptor.name,
-                            alias_for
-                        );
-
-                        return None;
-                    }
-                }
-
-                // This descriptor is of an unknown kind and we don't know how
-                // to deal with it -- maybe rbx_binary is out of date?
-                _ => return None,
-            }
-        }
-
-        if let Some(superclass_name) = &class_descriptor.superclass {
-            // If a property descriptor isn't found in our class, check our
-            // superclass.
-
-            class_descriptor = database
-                .classes
-                .get(superclass_name)
-                .expect("Superclass in reflection database didn't exist");
-        } else {
-            // This property isn't known by any class in the reflection
-            // database.
-
-            return None;
-        }
-    }
-}
-
-/// Given the canonical property descriptor for a logical property along with
-/// its serialization, returns the serialized form of the logical property if
-/// this property is serializable.
-fn find_serialized_from_canonical<'db>(
-    class: &'db ClassDescriptor<'db>,
-    canonical: &'db PropertyDescriptor<'db>,
-    serialization: &'db PropertySerialization<'db>,
-) -> Option<&'db PropertyDescriptor<'db>> {
-    match serialization {
-        // This property serializes as-is. This is the happiest path: both the
-        // canonical and serialized descriptors are the same!
-        PropertySerialization::Serializes | PropertySerialization::Migrate { .. } => {
-            Some(canonical)
-        }
-
-        // This property serializes under an alias. That property should have a
-        // corresponding property descriptor within the same class descriptor.
-        PropertySerialization::SerializesAs(serialized_name) => {
-            let serialized_descriptor = class.properties.get(serialized_name.as_ref()).unwrap();
-
-            Some(serialized_descriptor)
-        }
-
-        // If this property does not serialize, there is no serialized
-        // descriptor to use.
-        PropertySerialization::DoesNotSerialize => None,
-
-        // This case will be hit if a new form of property serialization is
-        // introduced to the reflection database. This might happen if the
-        // database starts including more complex aliasing rules, like for the
-        // Grip properties of Tool, or the Rotation and Orientation properties
-        // of BasePart.
-        _ => None,
-    }
+    database.find_property_descriptors(class_name, property_name)
 }
@@ -0,0 +1,89 @@
+use std::io::Read;
+
+use rbx_dom_weak::WeakDom;
+
+use super::{cache::DecodeCache, Deserializer, Error, FileInfo};
+
+/// Decodes many binary models or places one after another, reusing work
+/// between them.
+///
+/// Each file is decoded exactly as [`Deserializer::deserialize`] would decode
+/// it, but a `BatchDecoder` keeps a few things around from one file to the
+/// next:
+///
+/// - The buffers chunks are read and decompressed into, and the Zstandard
+///   decompression context
+/// - The class and property names and SharedStrings of the last file, so that
+///   files which share data with the file before them, like successive
+///   versions of the same place, share the same names and SharedStrings
+///
+/// If a file fails to decode, the caches are dropped and the next file starts
+/// with empty ones.
+///
+/// ## Example
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// use rbx_binary::BatchDecoder;
+///
+/// let mut decoder = BatchDecoder::new();
+///
+/// for path in ["Version1.rbxl", "Version2.rbxl", "Version3.rbxl"] {
+///     let dom = decoder.decode(BufReader::new(File::open(path)?))?;
+///     println!("{path} has {} instances", dom.descendants().count());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct BatchDecoder<'db> {
+    deserializer: Deserializer<'db>,
+    cache: DecodeCache,
+}
+
+impl<'db> BatchDecoder<'db> {
+    /// Creates a new `BatchDecoder` that decodes files with a `Deserializer`
+    /// with the default settings.
+    pub fn new() -> Self {
+        Self::with_deserializer(Deserializer::new())
+    }
+
+    /// Creates a new `BatchDecoder` that decodes files with the given
+    /// `Deserializer`, so that its settings apply to every file.
+    pub fn with_deserializer(deserializer: Deserializer<'db>) -> Self {
+        Self {
+            deserializer,
+            cache: DecodeCache::default(),
+        }
+    }
+
+    /// Returns the `Deserializer` files are decoded with.
+    pub fn deserializer(&self) -> &Deserializer<'db> {
+        &self.deserializer
+    }
+
+    /// Decodes the next file from the given stream.
+    pub fn decode<R: Read>(&mut self, reader: R) -> Result<WeakDom, Error> {
+        let (dom, _) = self.decode_with_info(reader)?;
+        Ok(dom)
+    }
+
+    /// Decodes the next file from the given stream, also returning
+    /// information about how it was stored, like
+    /// [`Deserializer::deserialize_with_info`].
+    pub fn decode_with_info<R: Read>(&mut self, reader: R) -> Result<(WeakDom, FileInfo), Error> {
+        self.deserializer
+            .deserialize_chunks(reader, None, &mut self.cache)
+    }
+
+    /// Drops everything kept from the files decoded so far, freeing its
+    /// memory. The next file is decoded as if it were the first.
+    pub fn clear_caches(&mut self) {
+        self.cache.clear();
+    }
+}
+
+impl Default for BatchDecoder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,147 @@
+use std::io;
+
+use ahash::{HashMap, RandomState};
+use rbx_dom_weak::{types::SharedString, Symbol};
+
+/// Most chunks are decoded and dropped one at a time, so only a few buffers
+/// are ever needed at once.
+const MAX_BUFFERS: usize = 4;
+
+/// Allocations and lookups that can be reused from one file to the next.
+///
+/// [`BatchDecoder`][crate::BatchDecoder] keeps a cache between the files it
+/// decodes. Every other way of deserializing a file starts with an empty one,
+/// which still saves work between the chunks of that file.
+#[derive(Default)]
+pub(crate) struct DecodeCache {
+    /// A Zstandard decompression context, which is expensive to set up.
+    #[cfg(feature = "zstd")]
+    zstd: Option<zstd::bulk::Decompressor<'static>>,
+
+    /// Buffers that decoded chunks are done with, which the next chunks are
+    /// decompressed into.
+    buffers: Vec<Vec<u8>>,
+
+    /// The buffer compressed chunk data is read into.
+    compressed: Vec<u8>,
+
+    /// The class and property names from the last file that was finished,
+    /// which files that use the same names share instead of each allocating
+    /// their own.
+    names: HashMap<String, Symbol>,
+
+    /// The class and property names from the file being decoded.
+    next_names: HashMap<String, Symbol>,
+
+    /// The SharedStrings from the last file that was finished, keyed by a
+    /// hash of their data. The hashes files store in their `SSTR` chunks
+    /// aren't used, since some serializers leave them empty.
+    shared_strings: HashMap<u64, SharedString>,
+
+    /// The SharedStrings from the file being decoded.
+    next_shared_strings: HashMap<u64, SharedString>,
+
+    /// The hasher SharedStrings are keyed with, which is much faster than
+    /// the one SharedStrings are deduplicated with.
+    hasher: RandomState,
+}
+
+impl DecodeCache {
+    /// Returns an empty buffer to read or decompress a chunk into.
+    pub(crate) fn buffer(&mut self) -> Vec<u8> {
+        self.buffers.pop().unwrap_or_default()
+    }
+
+    /// Gives back a buffer that was returned by [`DecodeCache::buffer`] once
+    /// whatever was decoded into it isn't needed anymore.
+    pub(crate) fn recycle(&mut self, mut buffer: Vec<u8>) {
+        if self.buffers.len() < MAX_BUFFERS {
+            buffer.clear();
+            self.buffers.push(buffer);
+        }
+    }
+
+    /// Takes the buffer compressed data is read into, which should be given
+    /// back with [`DecodeCache::restore_compressed`].
+    pub(crate) fn take_compressed(&mut self) -> Vec<u8> {
+        let mut compressed = std::mem::take(&mut self.compressed);
+        compressed.clear();
+        compressed
+    }
+
+    pub(crate) fn restore_compressed(&mut self, compressed: Vec<u8>) {
+        self.compressed = compressed;
+    }
+
+    /// Decompresses Zstandard-compressed `source` into `destination`, which
+    /// is expected to hold at most `len` bytes.
+    #[cfg(feature = "zstd")]
+    pub(crate) fn decompress_zstd(
+        &mut self,
+        source: &[u8],
+        len: usize,
+        destination: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        if self.zstd.is_none() {
+            self.zstd = Some(zstd::bulk::Decompressor::new()?);
+        }
+        let decompressor = self.zstd.as_mut().unwrap();
+
+        destination.reserve(len);
+        decompressor.decompress_to_buffer(source, destination)?;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    pub(crate) fn decompress_zstd(
+        &mut self,
+        _source: &[u8],
+        _len: usize,
+        _destination: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        Err(crate::chunk::zstd_unsupported())
+    }
+
+    /// Returns a Symbol for `name`, reusing the one from this file or the
+    /// last one if either used the same name.
+    pub(crate) fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(interned) = self.next_names.get(name) {
+            return interned.clone();
+        }
+
+        let interned = match self.names.get(name) {
+            Some(cached) => cached.clone(),
+            None => Symbol::from(name),
+        };
+
+        self.next_names.insert(name.to_owned(), interned.clone());
+        interned
+    }
+
+    /// Returns a SharedString holding `data`, reusing the one from the last
+    /// file if it had the same data, which is quicker than creating a new
+    /// one.
+    pub(crate) fn shared_string(&mut self, data: Vec<u8>) -> SharedString {
+        let hash = self.hasher.hash_one(&data);
+        let shared_string = match self.shared_strings.get(&hash) {
+            Some(cached) if cached.data() == data.as_slice() => cached.clone(),
+            _ => SharedString::new(data),
+        };
+
+        self.next_shared_strings.insert(hash, shared_string.clone());
+        shared_string
+    }
+
+    /// Marks the file being decoded as finished, so that its names and
+    /// SharedStrings replace the ones kept from the file before it.
+    pub(crate) fn finish_file(&mut self) {
+        self.names = std::mem::take(&mut self.next_names);
+        self.shared_strings = std::mem::take(&mut self.next_shared_strings);
+    }
+
+    /// Drops everything in the cache, freeing its memory.
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,143 @@
+/// How strictly a file is checked as it's decoded, set with
+/// [`Deserializer::config`][crate::Deserializer::config].
+///
+/// By default, the deserializer is permissive: it reads anything Roblox
+/// Studio would, and files it doesn't fully understand are read as well as
+/// they can be. Each strictness toggle turns one of these cases into an
+/// error instead, for consumers that would rather reject a file than work
+/// with part of it.
+///
+/// ```
+/// use rbx_binary::{DecodeConfig, Deserializer};
+///
+/// // Reject anything out of the ordinary, except classes that are newer than
+/// // the reflection database.
+/// let deserializer = Deserializer::new()
+///     .config(DecodeConfig::strict().with_reject_unknown_classes(false));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeConfig {
+    pub(crate) reject_unknown_types: bool,
+    pub(crate) reject_unknown_classes: bool,
+    pub(crate) reject_trailing_data: bool,
+    pub(crate) reject_duplicate_referents: bool,
+    pub(crate) repair_parents: bool,
+    pub(crate) allow_truncated: bool,
+}
+
+impl DecodeConfig {
+    /// Creates a permissive `DecodeConfig`, which is what the deserializer
+    /// uses by default:
+    ///
+    /// - Properties with unknown types are skipped
+    /// - Instances of classes that aren't in the reflection database are read
+    /// - Data after the `END` chunk is ignored
+    /// - Instances that reuse a referent replace the instance that used it
+    ///   first
+    /// - Problems with the file's parents are repaired, and listed in
+    ///   [`FileInfo::parent_repairs`][crate::FileInfo::parent_repairs]
+    /// - Files that end before their `END` chunk are errors
+    pub fn new() -> Self {
+        Self {
+            reject_unknown_types: false,
+            reject_unknown_classes: false,
+            reject_trailing_data: false,
+            reject_duplicate_referents: false,
+            repair_parents: true,
+            allow_truncated: false,
+        }
+    }
+
+    /// Creates a `DecodeConfig` that rejects every file the permissive
+    /// config would have to skip over or guess about, including files with
+    /// problems with their parents. Files that end early are errors, as they
+    /// are by default.
+    pub fn strict() -> Self {
+        Self {
+            reject_unknown_types: true,
+            reject_unknown_classes: true,
+            reject_trailing_data: true,
+            reject_duplicate_referents: true,
+            repair_parents: false,
+            allow_truncated: false,
+        }
+    }
+
+    /// Sets whether files with properties of a type rbx_binary doesn't know
+    /// about fail to deserialize, rather than those properties being skipped.
+    pub fn with_reject_unknown_types(mut self, reject: bool) -> Self {
+        self.reject_unknown_types = reject;
+        self
+    }
+
+    /// Sets whether files with instances of classes that aren't in the
+    /// deserializer's reflection database fail to deserialize. Classes
+    /// skipped with [`Deserializer::skip_classes`][crate::Deserializer::skip_classes]
+    /// are never checked.
+    pub fn with_reject_unknown_classes(mut self, reject: bool) -> Self {
+        self.reject_unknown_classes = reject;
+        self
+    }
+
+    /// Sets whether files with data after their `END` chunk fail to
+    /// deserialize, rather than it being ignored.
+    pub fn with_reject_trailing_data(mut self, reject: bool) -> Self {
+        self.reject_trailing_data = reject;
+        self
+    }
+
+    /// Sets whether files that declare more than one instance with the same
+    /// referent fail to deserialize, rather than the last of them being kept.
+    pub fn with_reject_duplicate_referents(mut self, reject: bool) -> Self {
+        self.reject_duplicate_referents = reject;
+        self
+    }
+
+    /// Sets whether to repair files with broken parents rather than failing
+    /// to deserialize them, which is on by default. See
+    /// [`Deserializer::repair_parents`][crate::Deserializer::repair_parents].
+    pub fn with_repair_parents(mut self, repair: bool) -> Self {
+        self.repair_parents = repair;
+        self
+    }
+
+    /// Sets whether to read files that end before their `END` chunk, like
+    /// downloads that were cut off, rather than failing to deserialize them.
+    ///
+    /// A truncated file is only read if every instance it declares and its
+    /// `PRNT` chunk were read before it ended, since everything after those
+    /// is only there to mark the end of the file. The file is then read as if
+    /// the rest of it were there, a warning is logged, and
+    /// [`FileInfo::truncated`][crate::FileInfo::truncated] is set. Files that
+    /// end any earlier still fail to deserialize.
+    ///
+    /// ```
+    /// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    /// use rbx_binary::{DecodeConfig, Deserializer};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Folder"));
+    /// let mut buffer = Vec::new();
+    /// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+    ///
+    /// // Cut off the END chunk partway through.
+    /// let truncated = &buffer[..buffer.len() - 4];
+    /// assert!(Deserializer::new().deserialize(truncated).is_err());
+    ///
+    /// let (decoded, info) = Deserializer::new()
+    ///     .config(DecodeConfig::new().with_allow_truncated(true))
+    ///     .deserialize_with_info(truncated)?;
+    /// assert!(info.truncated);
+    /// assert_eq!(decoded.root().children().len(), 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_allow_truncated(mut self, allow: bool) -> Self {
+        self.allow_truncated = allow;
+        self
+    }
+}
+
+impl Default for DecodeConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
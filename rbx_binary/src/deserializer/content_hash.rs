@@ -0,0 +1,105 @@
+//! Content hashes of decoded instances.
+//!
+//! An instance's hash is the first 8 bytes, read as a little-endian `u64`, of
+//! the [BLAKE3] hash of this encoding of the instance, where strings are
+//! written as their length as a little-endian `u64` followed by their bytes:
+//!
+//! 1. The instance's class, as a string.
+//! 2. The instance's name, as a string.
+//! 3. For each property, sorted by name: the property's name, as a string,
+//!    followed by its value in [bincode] 1.3's default encoding of
+//!    [`Variant`]. Floats are written as their bits (`to_bits`) in
+//!    little-endian order, so every NaN payload and both zeroes hash
+//!    differently.
+//!
+//! Two kinds of values are written differently, since their bincode encoding
+//! isn't the same every time a file is read or is needlessly large:
+//!
+//! - `Ref` values are written as the variant tag of `Variant::Ref` followed by
+//!   the referent their target had in the file as a little-endian `i32`, or
+//!   -1 if they don't point at anything from the file.
+//! - `SharedString` values are written as the variant tag of
+//!   `Variant::SharedString` followed by the 32 byte BLAKE3 hash of their
+//!   contents.
+//!
+//! None of this depends on the Rust version or platform, so hashes can be
+//! stored and compared later. The encoding only changes in a release of
+//! rbx_binary whose changelog says so.
+//!
+//! [BLAKE3]: https://github.com/BLAKE3-team/BLAKE3-specs
+//! [bincode]: https://github.com/bincode-org/bincode/blob/v1.3.3/spec.md
+
+use ahash::{HashMap, HashMapExt};
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    Instance, WeakDom,
+};
+
+/// The bincode variant tags of `Variant::Ref` and `Variant::SharedString`,
+/// which are their positions in `Variant`.
+const REF_TAG: u32 = 20;
+const SHARED_STRING_TAG: u32 = 23;
+
+/// Computes the content hash of every instance in `dom` that was read from
+/// the file, keyed by the referent it had in the file.
+///
+/// Hashes cover an instance's class, name, and properties, but not its
+/// children, so that changing an instance doesn't change the hash of its
+/// ancestors. `Ref` properties are hashed by the referent their target had in
+/// the file, since the `Ref`s in the dom are different every time a file is
+/// read.
+pub(super) fn instance_content_hashes(dom: &WeakDom) -> HashMap<i32, u64> {
+    let mut hashes = HashMap::new();
+
+    for instance in dom.descendants() {
+        if let Some(referent) = instance.binary_referent() {
+            hashes.insert(referent, content_hash(dom, instance));
+        }
+    }
+
+    hashes
+}
+
+fn content_hash(dom: &WeakDom, instance: &Instance) -> u64 {
+    let mut hasher = blake3::Hasher::new();
+
+    write_bytes(&mut hasher, instance.class.as_bytes());
+    write_bytes(&mut hasher, instance.name.as_bytes());
+
+    for (name, value) in instance.sorted_properties_iter() {
+        write_bytes(&mut hasher, name.as_bytes());
+
+        match value {
+            Variant::Ref(target) => {
+                hasher.update(&REF_TAG.to_le_bytes());
+                hasher.update(&file_referent(dom, *target).to_le_bytes());
+            }
+            Variant::SharedString(value) => {
+                hasher.update(&SHARED_STRING_TAG.to_le_bytes());
+                hasher.update(value.hash().as_bytes());
+            }
+            // Writing to a hasher can't fail, and every variant can be
+            // encoded by bincode.
+            _ => bincode::serialize_into(&mut hasher, value).unwrap(),
+        }
+    }
+
+    let hash = hasher.finalize();
+    let mut first = [0; 8];
+    first.copy_from_slice(&hash.as_bytes()[..8]);
+
+    u64::from_le_bytes(first)
+}
+
+/// Returns the referent the target of a `Ref` property had in the file, or -1
+/// if the property doesn't point at anything from the file.
+fn file_referent(dom: &WeakDom, target: Ref) -> i32 {
+    dom.binary_referent_of(target).unwrap_or(-1)
+}
+
+/// Writes `value` with its length in front of it, so that values next to
+/// each other in the hash can't run into each other.
+fn write_bytes(hasher: &mut blake3::Hasher, value: &[u8]) {
+    hasher.update(&(value.len() as u64).to_le_bytes());
+    hasher.update(value);
+}
@@ -4,6 +4,8 @@ use thiserror::Error;
 
 use crate::types::InvalidTypeError;
 
+use super::{file_info::ParentProblem, limits::LimitExceeded};
+
 /// Represents an error that occurred during deserialization.
 #[derive(Debug, Error)]
 #[error(transparent)]
@@ -11,6 +13,18 @@ pub struct Error {
     source: Box<InnerError>,
 }
 
+impl Error {
+    /// Returns which of the deserializer's
+    /// [`DecodeLimits`][crate::DecodeLimits] the file went over, if that's why
+    /// it failed to deserialize.
+    pub fn limit_exceeded(&self) -> Option<LimitExceeded> {
+        match *self.source {
+            InnerError::LimitExceeded { limit } => Some(limit),
+            _ => None,
+        }
+    }
+}
+
 impl From<InnerError> for Error {
     fn from(inner: InnerError) -> Self {
         Self {
@@ -22,10 +36,7 @@ impl From<InnerError> for Error {
 #[derive(Debug, Error)]
 pub(crate) enum InnerError {
     #[error(transparent)]
-    Io {
-        #[from]
-        source: io::Error,
-    },
+    Io { source: io::Error },
 
     #[error("Invalid file header")]
     BadHeader,
@@ -82,4 +93,48 @@ pub(crate) enum InnerError {
 
     #[error("'Content' type {0} is not implemented")]
     BadContentType(i32),
+
+    #[error("File contained more than {limit} distinct class and property names")]
+    InternLimitExceeded { limit: usize },
+
+    #[error("Invalid PRNT chunk: {problem}")]
+    InvalidParent { problem: ParentProblem },
+
+    #[error(transparent)]
+    LimitExceeded { limit: LimitExceeded },
+
+    #[error("Property {type_name}.{prop_name} has unknown type ID {type_id:#04x}")]
+    UnknownPropType {
+        type_name: String,
+        prop_name: String,
+        type_id: u8,
+    },
+
+    #[error("Class {class_name} is not in the reflection database")]
+    UnknownClass { class_name: String },
+
+    #[error("File has data after its END chunk")]
+    TrailingData,
+
+    #[error("Referent {referent} was declared by more than one instance")]
+    DuplicateReferent { referent: i32 },
+}
+
+impl From<io::Error> for InnerError {
+    fn from(source: io::Error) -> Self {
+        // Limits checked while reading are carried through I/O errors.
+        match source
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<LimitExceeded>())
+        {
+            Some(limit) => InnerError::LimitExceeded { limit: *limit },
+            None => InnerError::Io { source },
+        }
+    }
+}
+
+impl From<LimitExceeded> for InnerError {
+    fn from(limit: LimitExceeded) -> Self {
+        InnerError::LimitExceeded { limit }
+    }
 }
@@ -0,0 +1,251 @@
+use std::{fmt, time::Duration};
+
+use crate::{chunk::Chunk, serializer::CompressionType};
+
+/// Information about how a binary file was stored, returned by
+/// [`Deserializer::deserialize_with_info`][crate::Deserializer::deserialize_with_info]
+/// and [`peek_stats`][crate::peek_stats].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileInfo {
+    /// The version of the binary format the file was written with.
+    pub version: u16,
+
+    /// The number of classes the file's header declared.
+    pub class_count: u32,
+
+    /// The number of instances the file's header declared.
+    pub instance_count: u32,
+
+    /// Every chunk in the file, in the order they appeared.
+    pub chunks: Vec<ChunkInfo>,
+
+    /// Every problem with the file's `PRNT` chunks that was repaired because
+    /// of [`Deserializer::repair_parents`][crate::Deserializer::repair_parents],
+    /// in the order they were found.
+    pub parent_repairs: Vec<ParentProblem>,
+
+    /// Whether the file ended before its `END` chunk and was read anyway
+    /// because of [`DecodeConfig::with_allow_truncated`][crate::DecodeConfig::with_allow_truncated].
+    pub truncated: bool,
+}
+
+/// Information about one chunk of a binary file, as part of a [`FileInfo`] or
+/// a [`SizeEstimate`][crate::SizeEstimate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChunkInfo {
+    /// The name of the chunk, like `INST` or `PROP`. Trailing null bytes are
+    /// removed, so the last chunk is named `END`.
+    pub name: String,
+
+    /// The compression the chunk was stored with.
+    pub compression: CompressionType,
+
+    /// The number of bytes the chunk took up in the file, not including its
+    /// header.
+    pub stored_len: u32,
+
+    /// The number of bytes in the chunk after it was decompressed.
+    pub len: u32,
+}
+
+impl ChunkInfo {
+    pub(crate) fn new(
+        name: &[u8],
+        compression: CompressionType,
+        stored_len: u32,
+        len: u32,
+    ) -> Self {
+        let name = String::from_utf8_lossy(name);
+
+        Self {
+            name: name.trim_end_matches('\0').to_owned(),
+            compression,
+            stored_len,
+            len,
+        }
+    }
+
+    /// Returns how many times smaller the chunk was in the file than after it
+    /// was decompressed. Uncompressed and empty chunks have a ratio of 1.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.stored_len == 0 {
+            1.0
+        } else {
+            self.len as f64 / self.stored_len as f64
+        }
+    }
+}
+
+/// How one chunk of a binary file was stored and how long it took to read,
+/// returned by
+/// [`Deserializer::deserialize_with_chunk_stats`][crate::Deserializer::deserialize_with_chunk_stats].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChunkStats {
+    /// The name, compression, and lengths of the chunk.
+    pub info: ChunkInfo,
+
+    /// How long it took to decompress the chunk, which is zero for
+    /// uncompressed chunks.
+    ///
+    /// This is always zero on wasm32-unknown-unknown, which has no clock.
+    pub decompress_time: Duration,
+
+    /// How long it took to decode the decompressed chunk into the dom.
+    ///
+    /// This is always zero on wasm32-unknown-unknown, which has no clock.
+    pub decode_time: Duration,
+}
+
+impl ChunkStats {
+    pub(crate) fn new(chunk: &Chunk, decode_time: Duration) -> Self {
+        Self {
+            info: ChunkInfo::new(
+                &chunk.name,
+                chunk.compression,
+                chunk.stored_len,
+                chunk.data.len() as u32,
+            ),
+            decompress_time: chunk.decompress_time,
+            decode_time,
+        }
+    }
+}
+
+/// A problem with the parents a file's `PRNT` chunks give its instances.
+/// Referents are the ones used by the file.
+///
+/// By default, each of these problems is repaired as described and listed in
+/// [`FileInfo::parent_repairs`]. Files with them fail to deserialize if
+/// [`Deserializer::repair_parents`][crate::Deserializer::repair_parents] is
+/// turned off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParentProblem {
+    /// A parent was given to an instance that no `INST` chunk declared. It's
+    /// repaired by ignoring the parent.
+    UnknownInstance {
+        /// The referent of the instance.
+        referent: i32,
+    },
+
+    /// An instance was parented to an instance that no `INST` chunk declared.
+    /// It's repaired by parenting the instance to the root instead.
+    UnknownParent {
+        /// The referent of the instance.
+        referent: i32,
+
+        /// The referent of the parent that doesn't exist.
+        parent: i32,
+    },
+
+    /// An instance was given more than one parent. It's repaired by keeping
+    /// the parent it was given first.
+    MultipleParents {
+        /// The referent of the instance.
+        referent: i32,
+
+        /// The referent of the parent that was given after the first.
+        parent: i32,
+    },
+
+    /// An instance is its own ancestor. It's repaired by parenting the
+    /// instance to the root, which keeps its descendants under it.
+    Cycle {
+        /// The referent of the instance.
+        referent: i32,
+    },
+
+    /// An instance was never given a parent. It's repaired by parenting the
+    /// instance to the root.
+    Unparented {
+        /// The referent of the instance.
+        referent: i32,
+    },
+}
+
+impl fmt::Display for ParentProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParentProblem::UnknownInstance { referent } => {
+                write!(
+                    f,
+                    "instance {referent}, which was not declared, was given a parent"
+                )
+            }
+            ParentProblem::UnknownParent { referent, parent } => write!(
+                f,
+                "instance {referent} was parented to instance {parent}, which was not declared"
+            ),
+            ParentProblem::MultipleParents { referent, parent } => write!(
+                f,
+                "instance {referent} was given another parent, instance {parent}"
+            ),
+            ParentProblem::Cycle { referent } => {
+                write!(f, "instance {referent} is its own ancestor")
+            }
+            ParentProblem::Unparented { referent } => {
+                write!(f, "instance {referent} was never given a parent")
+            }
+        }
+    }
+}
+
+impl FileInfo {
+    pub(crate) fn new(version: u16, class_count: u32, instance_count: u32) -> Self {
+        Self {
+            version,
+            class_count,
+            instance_count,
+            chunks: Vec::new(),
+            parent_repairs: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    pub(crate) fn push_chunk(&mut self, chunk: &Chunk) {
+        self.chunks.push(ChunkInfo::new(
+            &chunk.name,
+            chunk.compression,
+            chunk.stored_len,
+            chunk.data.len() as u32,
+        ));
+    }
+
+    /// Returns the total number of bytes in every chunk after they were
+    /// decompressed.
+    pub fn decompressed_size(&self) -> u64 {
+        self.chunks.iter().map(|chunk| chunk.len as u64).sum()
+    }
+
+    /// Returns the compression that was used by the most chunks, which is
+    /// usually the compression that the file should be written back out with.
+    /// The `END` chunk is ignored, since it's never compressed.
+    ///
+    /// Returns `None` if the file had no chunks other than `END`.
+    pub fn compression(&self) -> Option<CompressionType> {
+        let mut counts = [
+            (CompressionType::Lz4, 0),
+            (CompressionType::Zstd, 0),
+            (CompressionType::None, 0),
+        ];
+
+        for chunk in self.chunks.iter().filter(|chunk| chunk.name != "END") {
+            for (compression, count) in &mut counts {
+                if *compression == chunk.compression {
+                    *count += 1;
+                }
+            }
+        }
+
+        // Ties are broken in favor of compression Roblox uses by default.
+        counts
+            .iter()
+            .rev()
+            .filter(|(_, count)| *count > 0)
+            .max_by_key(|(_, count)| *count)
+            .map(|(compression, _)| *compression)
+    }
+}
@@ -7,6 +7,9 @@ use super::error::InnerError;
 /// All the information contained in the header before any chunks are read from
 /// the file.
 pub struct FileHeader {
+    /// The version of the binary format the file was written with.
+    pub(crate) version: u16,
+
     /// The number of instance types (represented for us as `TypeInfo`) that are
     /// in this file. Generally useful to pre-size some containers before
     /// reading the file.
@@ -49,6 +52,7 @@ impl FileHeader {
         }
 
         Ok(Self {
+            version,
             num_types,
             num_instances,
         })
@@ -0,0 +1,16 @@
+use rbx_dom_weak::InstanceBuilder;
+
+/// What a hook registered with
+/// [`Deserializer::instance_hook`][crate::Deserializer::instance_hook] does
+/// with an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceAction {
+    /// Keep the instance, with any changes the hook made to it.
+    Keep,
+
+    /// Discard the instance along with all of its descendants.
+    Skip,
+}
+
+pub(crate) type InstanceHook<'db> =
+    Box<dyn Fn(&mut InstanceBuilder) -> InstanceAction + Send + Sync + 'db>;
@@ -0,0 +1,267 @@
+use std::{convert::TryInto, io::Cursor};
+
+use ahash::{HashMap, HashMapExt, HashSet};
+use rbx_dom_weak::{types::Ref, Instance, Symbol, SymbolMap, WeakDom};
+
+use crate::chunk::{chunk_extent, peek_chunk_data};
+
+use super::{
+    cache::DecodeCache, decode_chunk, error::Error, state::DeserializerState, Deserializer,
+    InnerError, FILE_HEADER_LEN,
+};
+
+/// A binary model or place whose instances are only decoded once they're
+/// looked at, created by [`Deserializer::deserialize_lazy`].
+///
+/// Opening a file only decodes its metadata, SharedStrings, and the classes
+/// and parents of its instances, which are a small part of most files. The
+/// properties of an instance are decoded the first time it's accessed with
+/// [`LazyDom::get_by_ref`], along with those of every other instance of its
+/// class, since the file stores the properties of each class together. The
+/// shape of the tree can be walked with [`LazyDom::children_of`] and
+/// [`LazyDom::class_of`] without decoding anything, so a browser for a large
+/// place only decodes the parts of it that are looked at.
+///
+/// The file is read from anything that can be viewed as bytes, like a
+/// `Vec<u8>` or a memory-mapped file, which is never copied. Opening it only
+/// decompresses the first few bytes of each `PROP` chunk, which say which
+/// class the chunk belongs to; the rest of it is decompressed when an
+/// instance of that class is first accessed.
+///
+/// The deserializer's [`instance_hook`][Deserializer::instance_hook]s and
+/// [`content_hashes`][Deserializer::content_hashes] aren't used, and
+/// instances don't have byte sizes. The rest of the deserializer's settings
+/// apply as usual, including whether truncated files are read.
+///
+/// ## Example
+/// ```
+/// use rbx_binary::Deserializer;
+/// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+///
+/// let dom = WeakDom::new(
+///     InstanceBuilder::new("Model")
+///         .with_child(InstanceBuilder::new("Part").with_name("Door"))
+///         .with_child(InstanceBuilder::new("Folder")),
+/// );
+/// let mut buffer = Vec::new();
+/// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+///
+/// let deserializer = Deserializer::new();
+/// let mut lazy = deserializer.deserialize_lazy(buffer)?;
+///
+/// let model = lazy.children_of(lazy.root_ref())[0];
+/// let part = lazy.children_of(model)[0];
+/// assert_eq!(lazy.class_of(part).unwrap(), "Part");
+/// assert!(!lazy.is_materialized(part));
+///
+/// assert_eq!(lazy.get_by_ref(part)?.unwrap().name, "Door");
+/// assert!(lazy.is_materialized(part));
+/// assert!(!lazy.is_materialized(lazy.children_of(model)[1]));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct LazyDom<'db, B> {
+    data: B,
+    state: DeserializerState<'db, Cursor<[u8; FILE_HEADER_LEN]>>,
+
+    /// Every instance, with the properties of the ones that have been
+    /// materialized.
+    dom: WeakDom,
+
+    /// The type IDs used by each class in the file. Files almost always use
+    /// a single ID for each class.
+    type_ids: SymbolMap<Vec<u32>>,
+
+    /// The type IDs whose instances haven't been materialized yet.
+    pending: HashSet<u32>,
+
+    /// The offsets of the `PROP` chunks of each type that hasn't been
+    /// materialized yet.
+    prop_chunks: HashMap<u32, Vec<usize>>,
+}
+
+impl<'db> Deserializer<'db> {
+    /// Opens a Roblox binary model or place without decoding the properties
+    /// of its instances, which are decoded a class at a time as they're
+    /// needed. See [`LazyDom`].
+    pub fn deserialize_lazy<B: AsRef<[u8]>>(&'db self, data: B) -> Result<LazyDom<'db, B>, Error> {
+        profiling::scope!("rbx_binary::deserialize_lazy");
+        trace_span!("rbx_binary::deserialize_lazy");
+
+        let bytes = data.as_ref();
+        let header: [u8; FILE_HEADER_LEN] = bytes
+            .get(..FILE_HEADER_LEN)
+            .and_then(|header| header.try_into().ok())
+            .ok_or_else(|| {
+                InnerError::from(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))
+            })?;
+
+        let mut state = DeserializerState::new(self, Cursor::new(header), DecodeCache::default())?;
+        let mut prop_chunks: HashMap<u32, Vec<usize>> = HashMap::new();
+        let mut offset = FILE_HEADER_LEN;
+        let mut truncated = false;
+
+        loop {
+            let (name, len) = match chunk_extent(&bytes[offset..]) {
+                Ok(extent) => extent,
+                Err(error) => {
+                    state.missing_end(error.into())?;
+                    truncated = true;
+                    break;
+                }
+            };
+
+            if &name == b"PROP" {
+                let type_id = peek_chunk_data(&bytes[offset..]).map_err(InnerError::from)?;
+                prop_chunks
+                    .entry(u32::from_le_bytes(type_id))
+                    .or_default()
+                    .push(offset);
+            } else {
+                let chunk = match state.read_chunk(&bytes[offset..]) {
+                    Ok(chunk) => chunk,
+                    Err(error) => {
+                        state.missing_end(error)?;
+                        truncated = true;
+                        break;
+                    }
+                };
+                let last = decode_chunk(&mut state, &chunk)?;
+                state.recycle_chunk(chunk);
+
+                if last {
+                    offset += len;
+                    break;
+                }
+            }
+
+            offset += len;
+        }
+
+        if self.config.reject_trailing_data && !truncated && offset != bytes.len() {
+            return Err(InnerError::TrailingData.into());
+        }
+
+        let mut type_ids: SymbolMap<Vec<u32>> = SymbolMap::default();
+        for (type_id, class) in state.type_ids() {
+            type_ids.entry(class).or_default().push(type_id);
+        }
+        let pending = type_ids.values().flatten().copied().collect();
+        let dom = state.skeleton()?;
+
+        Ok(LazyDom {
+            data,
+            state,
+            dom,
+            type_ids,
+            pending,
+            prop_chunks,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>> LazyDom<'_, B> {
+    /// Returns the referent of the root of the dom, a `DataModel` whose
+    /// children are the top-level instances in the file.
+    pub fn root_ref(&self) -> Ref {
+        self.dom.root_ref()
+    }
+
+    /// Returns the referents of the children of an instance without
+    /// materializing anything, or an empty slice if there's no instance with
+    /// the given referent.
+    pub fn children_of(&self, referent: Ref) -> &[Ref] {
+        self.dom
+            .get_by_ref(referent)
+            .map(|instance| instance.children())
+            .unwrap_or_default()
+    }
+
+    /// Returns the referent of the parent of an instance without
+    /// materializing anything, or `None` if there's no instance with the
+    /// given referent. The parent of the root is `Ref::none()`.
+    pub fn parent_of(&self, referent: Ref) -> Option<Ref> {
+        self.dom
+            .get_by_ref(referent)
+            .map(|instance| instance.parent())
+    }
+
+    /// Returns the class of an instance without materializing anything, or
+    /// `None` if there's no instance with the given referent.
+    pub fn class_of(&self, referent: Ref) -> Option<Symbol> {
+        self.dom
+            .get_by_ref(referent)
+            .map(|instance| instance.class.clone())
+    }
+
+    /// Returns whether the name and properties of an instance have been
+    /// decoded. The root is always materialized.
+    pub fn is_materialized(&self, referent: Ref) -> bool {
+        match self.class_of(referent) {
+            Some(class) => self
+                .type_ids
+                .get(&class)
+                .is_none_or(|ids| ids.iter().all(|id| !self.pending.contains(id))),
+            None => false,
+        }
+    }
+
+    /// Returns the instance with the given referent, first decoding the
+    /// properties of every instance of its class if they haven't been
+    /// decoded yet. Returns `Ok(None)` if there's no instance with the given
+    /// referent.
+    pub fn get_by_ref(&mut self, referent: Ref) -> Result<Option<&Instance>, Error> {
+        let class = match self.class_of(referent) {
+            Some(class) => class,
+            None => return Ok(None),
+        };
+
+        self.materialize_class(class)?;
+        Ok(self.dom.get_by_ref(referent))
+    }
+
+    /// Decodes every instance that hasn't been decoded yet and returns the
+    /// whole dom, which is the same as deserializing the file in one go.
+    pub fn into_dom(mut self) -> Result<WeakDom, Error> {
+        let classes: Vec<Symbol> = self.type_ids.keys().cloned().collect();
+        for class in classes {
+            self.materialize_class(class)?;
+        }
+
+        Ok(self.dom)
+    }
+
+    /// Decodes the properties of every instance of `class`, unless they've
+    /// been decoded already.
+    fn materialize_class(&mut self, class: Symbol) -> Result<(), Error> {
+        let type_ids = match self.type_ids.get(&class) {
+            Some(type_ids) => type_ids.clone(),
+            None => return Ok(()),
+        };
+
+        for type_id in type_ids {
+            if !self.pending.contains(&type_id) {
+                continue;
+            }
+
+            profiling::scope!("rbx_binary::LazyDom::materialize");
+            trace_span!("rbx_binary::LazyDom::materialize", class = %class, type_id);
+            log::trace!("Materializing instances of {class} (type ID {type_id})");
+
+            let offsets = self.prop_chunks.get(&type_id).cloned().unwrap_or_default();
+
+            for offset in offsets {
+                let chunk = self.state.read_chunk(&self.data.as_ref()[offset..])?;
+                self.state.decode_prop_chunk(&chunk.data)?;
+                self.state.recycle_chunk(chunk);
+            }
+
+            // The type is only marked as done once all of its chunks have
+            // decoded, so a failure is reported again on the next access.
+            self.state.fill_in(type_id, &mut self.dom);
+            self.pending.remove(&type_id);
+            self.prop_chunks.remove(&type_id);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::{error, fmt, io};
+
+/// Limits on how large the parts of a file may be, set with
+/// [`Deserializer::limits`][crate::Deserializer::limits].
+///
+/// Files that go over a limit fail to deserialize with an error whose
+/// [`limit_exceeded`][crate::DecodeError::limit_exceeded] says which limit it
+/// was. Limits are checked before the memory for each part is allocated, so
+/// files that lie about their sizes, like decompression bombs, are rejected
+/// before they can use up memory.
+///
+/// ```
+/// use rbx_binary::{DecodeLimits, Deserializer};
+///
+/// let deserializer = Deserializer::new().limits(
+///     DecodeLimits::untrusted()
+///         .with_max_instances(10_000)
+///         .with_max_depth(64),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecodeLimits {
+    pub(crate) max_instances: Option<usize>,
+    pub(crate) max_string_len: Option<usize>,
+    pub(crate) max_chunk_len: Option<usize>,
+    pub(crate) max_depth: Option<usize>,
+}
+
+impl DecodeLimits {
+    /// Creates `DecodeLimits` with no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates `DecodeLimits` meant for files from untrusted sources, which
+    /// are still high enough for large places:
+    ///
+    /// - 1,000,000 instances
+    /// - 16 MiB strings
+    /// - 256 MiB chunks, after decompression
+    /// - 1,000 levels of instances
+    pub fn untrusted() -> Self {
+        Self {
+            max_instances: Some(1_000_000),
+            max_string_len: Some(16 * 1024 * 1024),
+            max_chunk_len: Some(256 * 1024 * 1024),
+            max_depth: Some(1_000),
+        }
+    }
+
+    /// Sets the maximum number of instances a file may contain.
+    pub fn with_max_instances(mut self, max_instances: usize) -> Self {
+        self.max_instances = Some(max_instances);
+        self
+    }
+
+    /// Sets the maximum length of any string in a file, in bytes. This
+    /// includes string property values like `Source`, the names of classes
+    /// and properties, shared strings, and metadata.
+    pub fn with_max_string_len(mut self, max_string_len: usize) -> Self {
+        self.max_string_len = Some(max_string_len);
+        self
+    }
+
+    /// Sets the maximum length of any chunk in a file, in bytes, both as it's
+    /// stored and after it's decompressed.
+    pub fn with_max_chunk_len(mut self, max_chunk_len: usize) -> Self {
+        self.max_chunk_len = Some(max_chunk_len);
+        self
+    }
+
+    /// Sets the maximum depth of a file's tree of instances. Instances at
+    /// the top of the file have a depth of 1.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub(crate) fn check_chunk_len(&self, len: u32, compressed_len: u32) -> io::Result<()> {
+        match self.max_chunk_len {
+            Some(limit) if len.max(compressed_len) as usize > limit => {
+                Err(LimitExceeded::ChunkLength { limit }.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Which of a [`DecodeLimits`] a file went over, returned by
+/// [`DecodeError::limit_exceeded`][crate::DecodeError::limit_exceeded].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LimitExceeded {
+    /// The file contained more instances than
+    /// [`DecodeLimits::with_max_instances`] allows.
+    Instances {
+        /// The maximum number of instances.
+        limit: usize,
+    },
+
+    /// The file contained a string longer than
+    /// [`DecodeLimits::with_max_string_len`] allows.
+    StringLength {
+        /// The maximum length of a string.
+        limit: usize,
+    },
+
+    /// The file contained a chunk longer than
+    /// [`DecodeLimits::with_max_chunk_len`] allows.
+    ChunkLength {
+        /// The maximum length of a chunk.
+        limit: usize,
+    },
+
+    /// The file's instances were nested deeper than
+    /// [`DecodeLimits::with_max_depth`] allows.
+    Depth {
+        /// The maximum depth.
+        limit: usize,
+    },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::Instances { limit } => {
+                write!(f, "File contained more than {limit} instances")
+            }
+            LimitExceeded::StringLength { limit } => {
+                write!(f, "File contained a string longer than {limit} bytes")
+            }
+            LimitExceeded::ChunkLength { limit } => {
+                write!(f, "File contained a chunk longer than {limit} bytes")
+            }
+            LimitExceeded::Depth { limit } => {
+                write!(f, "File contained instances nested more than {limit} deep")
+            }
+        }
+    }
+}
+
+impl error::Error for LimitExceeded {}
+
+// Limits are checked while reading, so they're carried through `io::Error`
+// and turned back into `LimitExceeded` when they reach the deserializer.
+impl From<LimitExceeded> for io::Error {
+    fn from(limit: LimitExceeded) -> Self {
+        io::Error::other(limit)
+    }
+}
@@ -1,16 +1,55 @@
+mod batch;
+mod cache;
+mod config;
+mod content_hash;
 mod error;
+mod file_info;
 pub mod header;
+mod hooks;
+mod lazy;
+mod limits;
+mod peek;
+mod schema;
 mod state;
+mod verify;
 
 use std::io::Read;
 use std::str;
+use std::time::Duration;
 
-use rbx_dom_weak::WeakDom;
+#[cfg(feature = "tokio")]
+use std::convert::TryInto;
+
+use ahash::{HashSet, HashSetExt};
+use rbx_dom_weak::{InstanceBuilder, Symbol, WeakDom};
 use rbx_reflection::ReflectionDatabase;
 
-use self::state::DeserializerState;
+use crate::chunk::{start_timer, Chunk};
+
+use self::{hooks::InstanceHook, state::DeserializerState};
+
+pub use self::{
+    batch::BatchDecoder,
+    config::DecodeConfig,
+    error::Error,
+    file_info::{ChunkInfo, ChunkStats, FileInfo, ParentProblem},
+    hooks::InstanceAction,
+    lazy::LazyDom,
+    limits::{DecodeLimits, LimitExceeded},
+    schema::{ClassSchema, FileSchema, PropertySchema},
+    verify::{VerifyIssue, VerifyReport},
+};
+
+pub(crate) use self::{
+    cache::DecodeCache, error::InnerError, peek::peek_stats, schema::scan_schema, verify::verify,
+};
 
-pub use self::error::Error;
+/// The length of the header at the start of every binary file.
+const FILE_HEADER_LEN: usize = 32;
+
+/// The length of the header at the start of every chunk.
+#[cfg(feature = "tokio")]
+const CHUNK_HEADER_LEN: usize = 16;
 
 /// A configurable deserializer for Roblox binary models and places.
 ///
@@ -43,10 +82,47 @@ pub use self::error::Error;
 /// A custom [`ReflectionDatabase`][ReflectionDatabase] can be specified via
 /// [`reflection_database`][reflection_database].
 ///
+/// The number of distinct class and property names a file may contain can be
+/// limited via [`intern_limit`][intern_limit].
+///
+/// Instances of classes that aren't needed can be discarded while decoding
+/// via [`skip_classes`][skip_classes], and instances can be changed or
+/// discarded once their properties are decoded via
+/// [`instance_hook`][instance_hook].
+///
+/// Whether files with broken parent data are repaired or fail to deserialize
+/// can be set via [`repair_parents`][repair_parents], and how strictly
+/// other unusual files are treated can be set via [`config`][config].
+///
+/// A hash of each instance's contents can be computed once the file is
+/// decoded via [`content_hashes`][content_hashes], for finding what changed between two
+/// versions of a file.
+///
+/// Each instance's name and class can also be put in its properties as
+/// `Name` and `ClassName` via [`identity_properties`][identity_properties].
+///
+/// Limits on the number of instances and the sizes of strings and chunks in
+/// files from untrusted sources can be set via [`limits`][limits].
+///
 /// [ReflectionDatabase]: rbx_reflection::ReflectionDatabase
 /// [reflection_database]: Deserializer#method.reflection_database
+/// [intern_limit]: Deserializer#method.intern_limit
+/// [skip_classes]: Deserializer#method.skip_classes
+/// [instance_hook]: Deserializer#method.instance_hook
+/// [repair_parents]: Deserializer#method.repair_parents
+/// [config]: Deserializer#method.config
+/// [content_hashes]: Deserializer#method.content_hashes
+/// [identity_properties]: Deserializer#method.identity_properties
+/// [limits]: Deserializer#method.limits
 pub struct Deserializer<'db> {
     database: &'db ReflectionDatabase<'db>,
+    intern_limit: Option<usize>,
+    skip_classes: HashSet<String>,
+    config: DecodeConfig,
+    content_hashes: bool,
+    identity_properties: bool,
+    limits: DecodeLimits,
+    hooks: Vec<(Symbol, InstanceHook<'db>)>,
 }
 
 impl<'db> Deserializer<'db> {
@@ -54,48 +130,558 @@ impl<'db> Deserializer<'db> {
     pub fn new() -> Self {
         Self {
             database: rbx_reflection_database::get().unwrap(),
+            intern_limit: None,
+            skip_classes: HashSet::new(),
+            config: DecodeConfig::new(),
+            content_hashes: false,
+            identity_properties: false,
+            limits: DecodeLimits::new(),
+            hooks: Vec::new(),
         }
     }
 
     /// Sets what reflection database for the deserializer to use.
     #[inline]
     pub fn reflection_database(self, database: &'db ReflectionDatabase<'db>) -> Self {
-        Self { database }
+        Self { database, ..self }
+    }
+
+    /// Sets the maximum number of distinct class and property names that each
+    /// file may contain. Files that go over the limit fail to deserialize. By
+    /// default, there is no limit.
+    ///
+    /// Names are interned by the dom each file is read into and freed along
+    /// with it, so they can't build up over many files. Services that read
+    /// untrusted files can use this to turn away files full of made up names
+    /// before they're decoded.
+    #[inline]
+    pub fn intern_limit(self, limit: usize) -> Self {
+        Self {
+            intern_limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Sets classes whose instances should be discarded while the file is
+    /// decoded, along with all of their descendants. The property values of
+    /// these instances are never decoded, which makes skipping large
+    /// instances like `Terrain` much cheaper than removing them afterwards.
+    ///
+    /// Only exact class names are matched, so skipping a class doesn't skip
+    /// its subclasses. `Ref` properties that point to discarded instances are
+    /// set to `nil`.
+    ///
+    /// ```
+    /// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    /// # use rbx_binary::Deserializer;
+    /// let dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+    ///     InstanceBuilder::new("Script").with_child(InstanceBuilder::new("Folder")),
+    ///     InstanceBuilder::new("Part"),
+    /// ]));
+    /// let mut buffer = Vec::new();
+    /// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+    ///
+    /// let decoded = Deserializer::new()
+    ///     .skip_classes(["Script"])
+    ///     .deserialize(buffer.as_slice())?;
+    /// assert_eq!(decoded.descendants().count(), 3);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn skip_classes<I, S>(self, classes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            skip_classes: classes.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    /// Sets limits on the size of files, which the deserializer checks before
+    /// allocating memory for each part of a file. Files that go over a limit
+    /// fail to deserialize, and [`DecodeError::limit_exceeded`] on the error
+    /// says which limit it was. By default, there are no limits.
+    ///
+    /// [`DecodeError::limit_exceeded`]: Error::limit_exceeded
+    ///
+    /// ```
+    /// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    /// use rbx_binary::{DecodeLimits, Deserializer, LimitExceeded};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+    ///     InstanceBuilder::new("Folder"),
+    ///     InstanceBuilder::new("Folder"),
+    /// ]));
+    /// let mut buffer = Vec::new();
+    /// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+    ///
+    /// let error = Deserializer::new()
+    ///     .limits(DecodeLimits::untrusted().with_max_instances(2))
+    ///     .deserialize(buffer.as_slice())
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     error.limit_exceeded(),
+    ///     Some(LimitExceeded::Instances { limit: 2 })
+    /// );
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[inline]
+    pub fn limits(self, limits: DecodeLimits) -> Self {
+        Self { limits, ..self }
+    }
+
+    /// Sets whether to repair files whose `PRNT` chunks give instances
+    /// parents that don't exist, more than one parent, or no parent at all,
+    /// or that parent instances to their own descendants, rather than failing
+    /// to deserialize them. By default, these files are repaired.
+    ///
+    /// When repairing, instances without a usable parent are parented to the
+    /// root, and every repair is listed in [`FileInfo::parent_repairs`]. See
+    /// [`ParentProblem`] for how each problem is repaired.
+    ///
+    /// Instances may be parented to instances from `INST` chunks that appear
+    /// after the `PRNT` chunk whether or not this is turned on, since parents
+    /// are only resolved once the whole file has been read.
+    ///
+    /// This is the same as setting
+    /// [`DecodeConfig::with_repair_parents`] on the deserializer's
+    /// [`config`][Deserializer::config].
+    #[inline]
+    pub fn repair_parents(self, repair: bool) -> Self {
+        Self {
+            config: self.config.with_repair_parents(repair),
+            ..self
+        }
+    }
+
+    /// Sets how strictly files are checked as they're decoded, replacing the
+    /// config set before, including whether to
+    /// [`repair_parents`][Deserializer::repair_parents]. By default, the
+    /// deserializer is permissive. See [`DecodeConfig`].
+    ///
+    /// ```
+    /// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    /// use rbx_binary::{DecodeConfig, Deserializer};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Folder"));
+    /// let mut buffer = Vec::new();
+    /// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+    /// buffer.extend_from_slice(b"garbage");
+    ///
+    /// assert!(Deserializer::new().deserialize(buffer.as_slice()).is_ok());
+    /// assert!(Deserializer::new()
+    ///     .config(DecodeConfig::strict())
+    ///     .deserialize(buffer.as_slice())
+    ///     .is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[inline]
+    pub fn config(self, config: DecodeConfig) -> Self {
+        Self { config, ..self }
+    }
+
+    /// Sets whether to compute a hash of each instance's class, name, and
+    /// properties, which is stored in
+    /// [`WeakDom::instance_content_hashes`][rbx_dom_weak::WeakDom::instance_content_hashes]
+    /// alongside the instance's byte size. This is off by default.
+    ///
+    /// Hashes are computed in a pass over the dom after the file is decoded,
+    /// which takes about as long as hashing every property once.
+    ///
+    /// Hashes are the same every time the same instance is read, so
+    /// comparing them is a quick way to find the instances that changed
+    /// between two versions of a file, without comparing every property of
+    /// every instance. Hashes don't include children, and `Ref` properties
+    /// are hashed by the referent of their target in the file, so an
+    /// instance's hash also changes if the file renumbers the instance it
+    /// points at.
+    ///
+    /// Hashes are truncated [BLAKE3] hashes of an encoding of the instance
+    /// that's written out in the source of `deserializer::content_hash`.
+    /// They don't depend on the Rust version or platform, so they can be
+    /// stored and compared with hashes computed later. The encoding only
+    /// changes in a release whose changelog says so.
+    ///
+    /// [BLAKE3]: https://github.com/BLAKE3-team/BLAKE3-specs
+    ///
+    /// ```
+    /// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    /// use rbx_binary::Deserializer;
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Part").with_property("Anchored", true));
+    /// let mut buffer = Vec::new();
+    /// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+    ///
+    /// let decoded = Deserializer::new()
+    ///     .content_hashes(true)
+    ///     .deserialize(buffer.as_slice())?;
+    /// let part = decoded.root().children()[0];
+    /// assert!(decoded.get_instance_content_hash(part).is_some());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[inline]
+    pub fn content_hashes(self, content_hashes: bool) -> Self {
+        Self {
+            content_hashes,
+            ..self
+        }
+    }
+
+    /// Sets whether to copy each instance's name and class into its
+    /// properties as the `String` properties `Name` and `ClassName`, for
+    /// tools that want to treat every property the same way. This is off by
+    /// default. See
+    /// [`WeakDom::expose_identity_properties`][rbx_dom_weak::WeakDom::expose_identity_properties].
+    ///
+    /// The copies are never written back out by the serializer, which always
+    /// uses [`Instance::name`][rbx_dom_weak::Instance::name] and
+    /// [`Instance::class`][rbx_dom_weak::Instance::class].
+    ///
+    /// ```
+    /// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    /// use rbx_binary::Deserializer;
+    /// use rbx_dom_weak::types::Variant;
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Part").with_name("Door"));
+    /// let mut buffer = Vec::new();
+    /// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+    ///
+    /// let decoded = Deserializer::new()
+    ///     .identity_properties(true)
+    ///     .deserialize(buffer.as_slice())?;
+    /// let part = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    /// assert_eq!(part.properties.get("Name"), Some(&Variant::from("Door")));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[inline]
+    pub fn identity_properties(self, identity_properties: bool) -> Self {
+        Self {
+            identity_properties,
+            ..self
+        }
+    }
+
+    /// Registers a hook that's called with every instance of `class` once all
+    /// of its properties have been decoded, before it's added to the tree.
+    ///
+    /// Hooks can rename the instance and set or remove its properties, which
+    /// makes them a good place to migrate old property formats as a file is
+    /// loaded. If a hook returns [`InstanceAction::Skip`], the instance and
+    /// all of its descendants are discarded, as if their classes were passed
+    /// to [`skip_classes`][Deserializer::skip_classes], except that their
+    /// properties have already been decoded.
+    ///
+    /// Only exact class names are matched. Hooks for the same class are run
+    /// in the order they were registered, and once one skips an instance, the
+    /// rest aren't called for it. Instances are visited in the order they're
+    /// declared in the file, grouped by class.
+    ///
+    /// ```
+    /// use rbx_binary::{Deserializer, InstanceAction};
+    /// use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+    ///     InstanceBuilder::new("Part").with_property("Transparency", 2.0f32),
+    ///     InstanceBuilder::new("Part").with_name("Debug"),
+    /// ]));
+    /// let mut buffer = Vec::new();
+    /// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+    ///
+    /// let decoded = Deserializer::new()
+    ///     .instance_hook("Part", |part| {
+    ///         if part.name() == "Debug" {
+    ///             return InstanceAction::Skip;
+    ///         }
+    ///
+    ///         if let Some(Variant::Float32(value)) = part.property("Transparency") {
+    ///             let value = value.clamp(0.0, 1.0);
+    ///             part.set_property("Transparency", value);
+    ///         }
+    ///
+    ///         InstanceAction::Keep
+    ///     })
+    ///     .deserialize(buffer.as_slice())?;
+    /// assert_eq!(decoded.descendants().count(), 3);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn instance_hook<S, F>(mut self, class: S, hook: F) -> Self
+    where
+        S: Into<Symbol>,
+        F: Fn(&mut InstanceBuilder) -> InstanceAction + Send + Sync + 'db,
+    {
+        self.hooks.push((class.into(), Box::new(hook)));
+        self
     }
 
     /// Deserialize a Roblox binary model or place from the given stream using
     /// this deserializer.
     pub fn deserialize<R: Read>(&self, reader: R) -> Result<WeakDom, Error> {
+        let (dom, _) = self.deserialize_with_info(reader)?;
+        Ok(dom)
+    }
+
+    /// Deserialize a Roblox binary model or place from the given stream using
+    /// this deserializer, also returning information about how the file was
+    /// stored, like its version and the compression used by each chunk.
+    pub fn deserialize_with_info<R: Read>(&self, reader: R) -> Result<(WeakDom, FileInfo), Error> {
+        self.deserialize_chunks(reader, None, &mut DecodeCache::default())
+    }
+
+    /// Deserialize a Roblox binary model or place from the given stream using
+    /// this deserializer, also returning how every chunk in the file was
+    /// stored and how long it took to decompress and decode, in the order
+    /// they appeared.
+    ///
+    /// ```
+    /// use rbx_binary::Deserializer;
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Folder"));
+    /// let mut buffer = Vec::new();
+    /// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+    ///
+    /// let (_, stats) = Deserializer::new().deserialize_with_chunk_stats(buffer.as_slice())?;
+    /// for chunk in &stats {
+    ///     println!(
+    ///         "{}: {} -> {} bytes ({:.2}x), decoded in {:?}",
+    ///         chunk.info.name,
+    ///         chunk.info.stored_len,
+    ///         chunk.info.len,
+    ///         chunk.info.compression_ratio(),
+    ///         chunk.decompress_time + chunk.decode_time,
+    ///     );
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn deserialize_with_chunk_stats<R: Read>(
+        &self,
+        reader: R,
+    ) -> Result<(WeakDom, Vec<ChunkStats>), Error> {
+        let mut stats = Vec::new();
+        let (dom, _) =
+            self.deserialize_chunks(reader, Some(&mut stats), &mut DecodeCache::default())?;
+
+        Ok((dom, stats))
+    }
+
+    /// Decodes every chunk of a file, timing each one into `stats` if it's
+    /// given. `cache` is used while decoding and is left holding what can be
+    /// reused by the next file.
+    fn deserialize_chunks<R: Read>(
+        &self,
+        reader: R,
+        mut stats: Option<&mut Vec<ChunkStats>>,
+        cache: &mut DecodeCache,
+    ) -> Result<(WeakDom, FileInfo), Error> {
         profiling::scope!("rbx_binary::deserialize");
+        trace_span!("rbx_binary::deserialize");
 
-        let mut deserializer = DeserializerState::new(self, reader)?;
+        let mut deserializer = DeserializerState::new(self, reader, std::mem::take(cache))?;
 
         loop {
-            let chunk = deserializer.next_chunk()?;
-
-            match &chunk.name {
-                b"META" => deserializer.decode_meta_chunk(&chunk.data)?,
-                b"SSTR" => deserializer.decode_sstr_chunk(&chunk.data)?,
-                b"INST" => deserializer.decode_inst_chunk(&chunk.data)?,
-                b"PROP" => deserializer.decode_prop_chunk(&chunk.data)?,
-                b"PRNT" => deserializer.decode_prnt_chunk(&chunk.data)?,
-                b"END\0" => {
-                    deserializer.decode_end_chunk(&chunk.data)?;
+            let chunk = match deserializer.next_chunk(stats.is_some()) {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    deserializer.missing_end(error)?;
                     break;
                 }
-                _ =>
-                    match str::from_utf8(&chunk.name) {
-                        Ok(name) => log::info!("Unknown binary chunk name {name}"),
-                        Err(_) => log::info!("Unknown binary chunk name {:?}", chunk.name),
-                    }
+            };
+
+            let started = start_timer(stats.is_some());
+            let last = decode_chunk(&mut deserializer, &chunk)?;
+            if let Some(stats) = &mut stats {
+                let decode_time = started.map_or(Duration::ZERO, |started| started.elapsed());
+                stats.push(ChunkStats::new(&chunk, decode_time));
             }
+            deserializer.recycle_chunk(chunk);
+
+            if last {
+                break;
+            }
+        }
+
+        if self.config.reject_trailing_data {
+            deserializer.check_trailing_data()?;
         }
 
+        *cache = deserializer.take_cache();
+        Ok(self.finish(deserializer)?)
+    }
+
+    /// Deserialize a Roblox binary model or place from the given async stream
+    /// using this deserializer.
+    ///
+    /// Each chunk is read from `reader` without blocking, and the task yields
+    /// to the executor after decoding each one so that large files don't
+    /// starve other tasks.
+    #[cfg(feature = "tokio")]
+    pub async fn deserialize_async<R>(&self, mut reader: R) -> Result<WeakDom, Error>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let mut header = [0; FILE_HEADER_LEN];
+        reader
+            .read_exact(&mut header)
+            .await
+            .map_err(InnerError::from)?;
+
+        // The span is only entered while chunks are being decoded, since an
+        // entered span can't be held across an await.
+        let span = file_span!("rbx_binary::deserialize");
+
+        // The header has already been read in full, so the state never needs
+        // to read from its input again.
+        let mut deserializer =
+            span.in_scope(|| DeserializerState::new(self, &header[..], DecodeCache::default()))?;
+        let mut raw_chunk = Vec::new();
+
+        loop {
+            let chunk = match read_chunk_async(&mut reader, &self.limits, &mut raw_chunk).await {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    deserializer.missing_end(error)?;
+                    break;
+                }
+            };
+
+            if span.in_scope(|| decode_chunk(&mut deserializer, &chunk))? {
+                break;
+            }
+
+            tokio::task::yield_now().await;
+        }
+
+        if self.config.reject_trailing_data {
+            let mut byte = [0];
+            if reader.read(&mut byte).await.map_err(InnerError::from)? != 0 {
+                return Err(InnerError::TrailingData.into());
+            }
+        }
+
+        let (dom, _) = span.in_scope(|| self.finish(deserializer))?;
+        Ok(dom)
+    }
+}
+
+/// Reads the next chunk of a file from an async stream, using `raw_chunk` as
+/// the buffer the chunk is read into.
+#[cfg(feature = "tokio")]
+async fn read_chunk_async<R>(
+    reader: &mut R,
+    limits: &DecodeLimits,
+    raw_chunk: &mut Vec<u8>,
+) -> Result<Chunk, InnerError>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    raw_chunk.clear();
+    raw_chunk.resize(CHUNK_HEADER_LEN, 0);
+    reader.read_exact(raw_chunk).await?;
+
+    // Chunks are stored as a 4 byte name, followed by their compressed and
+    // uncompressed lengths. Uncompressed chunks have a compressed length of 0.
+    let compressed_len = u32::from_le_bytes(raw_chunk[4..8].try_into().unwrap());
+    let len = u32::from_le_bytes(raw_chunk[8..12].try_into().unwrap());
+    limits.check_chunk_len(len, compressed_len)?;
+    let data_len = if compressed_len == 0 {
+        len
+    } else {
+        compressed_len
+    };
+
+    let read = reader.take(data_len as u64).read_to_end(raw_chunk).await?;
+
+    if read != data_len as usize {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(Chunk::decode(raw_chunk.as_slice())?)
+}
+
+/// Decodes a single chunk into the deserializer, returning whether it was the
+/// last chunk in the file.
+fn decode_chunk<R: Read>(
+    deserializer: &mut DeserializerState<'_, R>,
+    chunk: &Chunk,
+) -> Result<bool, InnerError> {
+    trace_span!(
+        "chunk",
+        name = %String::from_utf8_lossy(&chunk.name),
+        len = chunk.data.len(),
+        stored_len = chunk.stored_len,
+    );
+
+    let result = decode_chunk_contents(deserializer, chunk);
+
+    #[cfg(feature = "tracing")]
+    if let Err(error) = &result {
+        tracing::error!(%error, "Could not decode chunk");
+    }
+
+    result
+}
+
+fn decode_chunk_contents<R: Read>(
+    deserializer: &mut DeserializerState<'_, R>,
+    chunk: &Chunk,
+) -> Result<bool, InnerError> {
+    deserializer.record_chunk(chunk);
+
+    match &chunk.name {
+        b"META" => deserializer.decode_meta_chunk(&chunk.data)?,
+        b"SSTR" => deserializer.decode_sstr_chunk(&chunk.data)?,
+        b"INST" => deserializer.decode_inst_chunk(&chunk.data)?,
+        b"PROP" => deserializer.decode_prop_chunk(&chunk.data)?,
+        b"PRNT" => deserializer.decode_prnt_chunk(&chunk.data)?,
+        b"END\0" => {
+            deserializer.decode_end_chunk(&chunk.data)?;
+            return Ok(true);
+        }
+        _ => match str::from_utf8(&chunk.name) {
+            Ok(name) => log::info!("Unknown binary chunk name {name}"),
+            Err(_) => log::info!("Unknown binary chunk name {:?}", chunk.name),
+        },
+    }
+
+    Ok(false)
+}
+
+impl Deserializer<'_> {
+    /// Builds the dom from a deserializer that has decoded every chunk of a
+    /// file.
+    fn finish<R: Read>(
+        &self,
+        deserializer: DeserializerState<'_, R>,
+    ) -> Result<(WeakDom, FileInfo), InnerError> {
         let instance_byte_sizes = deserializer.get_all_instance_byte_sizes();
-        let mut dom = deserializer.finish();
+        let instance_refs = deserializer.get_all_instance_refs();
+        let (mut dom, file_info) = deserializer.finish()?;
         dom.instance_byte_sizes = Some(instance_byte_sizes);
+        dom.instance_binary_referents = Some(instance_refs);
 
-        Ok(dom)
+        if self.content_hashes {
+            dom.instance_content_hashes = Some(content_hash::instance_content_hashes(&dom));
+        }
+
+        if self.identity_properties {
+            dom.expose_identity_properties();
+        }
+
+        Ok((dom, file_info))
+    }
+
+    /// Checks that a file with `count` instances is within the deserializer's
+    /// limits.
+    fn check_instance_limit(&self, count: usize) -> Result<(), InnerError> {
+        match self.limits.max_instances {
+            Some(limit) if count > limit => Err(LimitExceeded::Instances { limit }.into()),
+            _ => Ok(()),
+        }
     }
 }
 
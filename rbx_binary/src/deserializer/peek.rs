@@ -0,0 +1,24 @@
+use std::io::Read;
+
+use crate::chunk::skip_chunk;
+
+use super::{error::Error, file_info::FileInfo, header::FileHeader, InnerError};
+
+pub(crate) fn peek_stats<R: Read>(mut reader: R) -> Result<FileInfo, Error> {
+    profiling::scope!("rbx_binary::peek_stats");
+
+    let header = FileHeader::decode(&mut reader)?;
+    let mut info = FileInfo::new(header.version, header.num_types, header.num_instances);
+
+    loop {
+        let chunk = skip_chunk(&mut reader).map_err(InnerError::from)?;
+        let end = chunk.name == "END";
+        info.chunks.push(chunk);
+
+        if end {
+            break;
+        }
+    }
+
+    Ok(info)
+}
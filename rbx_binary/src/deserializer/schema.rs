@@ -0,0 +1,131 @@
+use std::{convert::TryFrom, io::Read};
+
+use ahash::{HashMap, HashMapExt};
+use rbx_dom_weak::types::VariantType;
+
+use crate::{chunk::Chunk, core::RbxReadExt, types::Type};
+
+use super::{error::InnerError, file_info::FileInfo, header::FileHeader, Error};
+
+/// A summary of the classes and properties used by a binary file, returned by
+/// [`scan_schema`][crate::scan_schema].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileSchema {
+    /// Information about how the file was stored.
+    pub info: FileInfo,
+
+    /// Every class in the file, in the order their `INST` chunks appeared.
+    pub classes: Vec<ClassSchema>,
+}
+
+impl FileSchema {
+    /// Returns the class with the given name, if the file has any instances
+    /// of it.
+    pub fn class(&self, name: &str) -> Option<&ClassSchema> {
+        self.classes.iter().find(|class| class.name == name)
+    }
+}
+
+/// A class used by a binary file, as part of a [`FileSchema`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ClassSchema {
+    /// The name of the class.
+    pub name: String,
+
+    /// The number of instances of the class in the file.
+    pub instance_count: usize,
+
+    /// Every property the file stores for the class, in the order their
+    /// `PROP` chunks appeared.
+    pub properties: Vec<PropertySchema>,
+}
+
+impl ClassSchema {
+    /// Returns the property with the given name, if the file stores it for
+    /// this class.
+    pub fn property(&self, name: &str) -> Option<&PropertySchema> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+
+    /// Returns the number of bytes the values of every property of the class
+    /// took up after they were decompressed.
+    pub fn size(&self) -> usize {
+        self.properties.iter().map(|property| property.size).sum()
+    }
+}
+
+/// A property stored by a binary file for one class, as part of a
+/// [`ClassSchema`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PropertySchema {
+    /// The name the property was stored under, which may be different from
+    /// its name in the reflection database.
+    pub name: String,
+
+    /// The type that values of the property would be decoded as, if nothing
+    /// else was known about the property. This is `None` if the type isn't
+    /// known to rbx_binary or the chunk had no values.
+    pub ty: Option<VariantType>,
+
+    /// The number of bytes the values of the property took up after they were
+    /// decompressed, for every instance of the class together.
+    pub size: usize,
+}
+
+pub(crate) fn scan_schema<R: Read>(mut reader: R) -> Result<FileSchema, Error> {
+    profiling::scope!("rbx_binary::scan_schema");
+
+    let header = FileHeader::decode(&mut reader)?;
+    let mut info = FileInfo::new(header.version, header.num_types, header.num_instances);
+    let mut classes = Vec::new();
+    let mut class_indices = HashMap::new();
+
+    loop {
+        let chunk = Chunk::decode(&mut reader).map_err(InnerError::from)?;
+        info.push_chunk(&chunk);
+        let mut data = chunk.data.as_slice();
+
+        match &chunk.name {
+            b"INST" => {
+                let type_id = data.read_le_u32().map_err(InnerError::from)?;
+                let name = data.read_string().map_err(InnerError::from)?;
+                let _object_format = data.read_u8().map_err(InnerError::from)?;
+                let instance_count = data.read_le_u32().map_err(InnerError::from)?;
+
+                class_indices.insert(type_id, classes.len());
+                classes.push(ClassSchema {
+                    name,
+                    instance_count: instance_count as usize,
+                    properties: Vec::new(),
+                });
+            }
+            b"PROP" => {
+                let type_id = data.read_le_u32().map_err(InnerError::from)?;
+                let name = data.read_string().map_err(InnerError::from)?;
+                let index = class_indices
+                    .get(&type_id)
+                    .ok_or(InnerError::InvalidTypeId { type_id })?;
+
+                let ty = data
+                    .first()
+                    .and_then(|byte| Type::try_from(*byte).ok())
+                    .and_then(Type::to_default_rbx_type);
+
+                classes[*index].properties.push(PropertySchema {
+                    name,
+                    ty,
+                    size: data.len().saturating_sub(1),
+                });
+            }
+            b"END\0" => break,
+            _ => {}
+        }
+    }
+
+    Ok(FileSchema { info, classes })
+}
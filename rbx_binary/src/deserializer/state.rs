@@ -42,14 +42,22 @@ use rbx_dom_weak::{
         Vector3int16,
     },
     InstanceBuilder,
-    Ustr,
+    Symbol,
     WeakDom,
 };
 use rbx_reflection::{ DataType, PropertyKind, PropertySerialization, ReflectionDatabase };
 
 use crate::{ chunk::Chunk, core::{ find_property_descriptors, RbxReadExt }, types::Type };
 
-use super::{ error::InnerError, header::FileHeader, Deserializer };
+use super::{
+    cache::DecodeCache,
+    error::InnerError,
+    file_info::{ FileInfo, ParentProblem },
+    header::FileHeader,
+    hooks::InstanceAction,
+    limits::LimitExceeded,
+    Deserializer,
+};
 
 pub(super) struct DeserializerState<'db, R> {
     /// The user-provided configuration that we should use.
@@ -80,6 +88,12 @@ pub(super) struct DeserializerState<'db, R> {
     /// in the file.
     root_instance_refs: Vec<i32>,
 
+    /// Every pair of instance and parent referents from the file's `PRNT`
+    /// chunks, in the order they appear. These are only resolved once the
+    /// whole file has been read, since they may refer to instances that are
+    /// declared later on.
+    parents: Vec<(i32, i32)>,
+
     /// Contains a set of unknown type IDs that we've encountered so far while
     /// deserializing this file. We use this map in order to ensure we only
     /// print one warning per unknown type ID when deserializing a file.
@@ -87,6 +101,30 @@ pub(super) struct DeserializerState<'db, R> {
 
     /// Tracks which shared strings are actually used by instances for byte attribution.
     used_shared_strings: HashSet<usize>,
+
+    /// The distinct class and property names this file has used so far,
+    /// which count towards the deserializer's intern limit. This is only
+    /// filled in when there is a limit.
+    names: HashSet<String>,
+
+    /// Information about how the file was stored, which is built up as each
+    /// chunk is read.
+    file_info: FileInfo,
+
+    /// The type IDs of classes being skipped because of
+    /// [`Deserializer::skip_classes`], whose `PROP` chunks are ignored.
+    skipped_type_ids: HashSet<u32>,
+
+    /// The referents of every instance being skipped because of its class or
+    /// because a hook skipped it. Their descendants are skipped by never
+    /// being parented into the tree.
+    skipped_referents: HashSet<i32>,
+
+    /// The number of instances declared by the file's `INST` chunks so far.
+    instance_count: usize,
+
+    /// Buffers and lookups shared with the files decoded before this one.
+    cache: DecodeCache,
 }
 
 /// Represents a unique instance class. Binary models define all their instance
@@ -97,7 +135,7 @@ struct TypeInfo {
     type_id: u32,
 
     /// The common name for this type like `Folder` or `UserInputService`.
-    type_name: Ustr,
+    type_name: Symbol,
 
     /// A list of the instances described by this file that are this type.
     referents: Vec<i32>,
@@ -125,7 +163,7 @@ struct Instance {
 /// others (like Font, which has been superceded by FontFace).
 #[derive(Debug)]
 struct CanonicalProperty<'db> {
-    name: Ustr,
+    name: Symbol,
     ty: VariantType,
     migration: Option<&'db PropertySerialization<'db>>,
 }
@@ -133,10 +171,10 @@ struct CanonicalProperty<'db> {
 fn find_canonical_property<'de>(
     database: &'de ReflectionDatabase,
     binary_type: Type,
-    class_name: Ustr,
-    prop_name: Ustr
+    class_name: &str,
+    prop_name: Symbol
 ) -> Option<CanonicalProperty<'de>> {
-    match find_property_descriptors(database, class_name, prop_name) {
+    match find_property_descriptors(database, class_name, &prop_name) {
         Some(descriptors) => {
             // If this descriptor is known but wasn't supposed to be
             // serialized, we should skip it.
@@ -213,7 +251,7 @@ fn find_canonical_property<'de>(
 fn add_property(instance: &mut Instance, canonical_property: &CanonicalProperty, value: Variant) {
     if let Some(PropertySerialization::Migrate(migration)) = canonical_property.migration {
         let new_property_name = &migration.new_property_name;
-        let old_property_name = canonical_property.name;
+        let old_property_name = &canonical_property.name;
 
         if !instance.builder.has_property(new_property_name) {
             log::trace!(
@@ -234,19 +272,24 @@ fn add_property(instance: &mut Instance, canonical_property: &CanonicalProperty,
             };
         }
     } else {
-        instance.builder.add_property(canonical_property.name, value)
+        instance.builder.add_property(canonical_property.name.clone(), value)
     }
 }
 
 impl<'db, R: Read> DeserializerState<'db, R> {
     pub(super) fn new(
         deserializer: &'db Deserializer<'db>,
-        mut input: R
+        mut input: R,
+        cache: DecodeCache
     ) -> Result<Self, InnerError> {
         let mut tree = WeakDom::new(InstanceBuilder::new("DataModel"));
 
         let header = FileHeader::decode(&mut input)?;
 
+        // The header's counts are used to reserve memory, so they're checked
+        // before anything is allocated.
+        deserializer.check_instance_limit(header.num_instances as usize)?;
+
         let type_infos = HashMap::with_capacity(header.num_types as usize);
         let instances_by_ref = HashMap::with_capacity(1 + (header.num_instances as usize));
 
@@ -261,31 +304,122 @@ impl<'db, R: Read> DeserializerState<'db, R> {
             type_infos,
             instances_by_ref,
             root_instance_refs: Vec::new(),
+            parents: Vec::new(),
             unknown_type_ids: HashSet::new(),
             used_shared_strings: HashSet::new(),
+            names: HashSet::new(),
+            file_info: FileInfo::new(header.version, header.num_types, header.num_instances),
+            skipped_type_ids: HashSet::new(),
+            skipped_referents: HashSet::new(),
+            instance_count: 0,
+            cache,
         })
     }
 
-    pub(super) fn next_chunk(&mut self) -> Result<Chunk, InnerError> {
-        Ok(Chunk::decode(&mut self.input)?)
+    /// Takes back the cache the state was created with, once the file has
+    /// been decoded.
+    pub(super) fn take_cache(&mut self) -> DecodeCache {
+        let mut cache = std::mem::take(&mut self.cache);
+        cache.finish_file();
+        cache
+    }
+
+    /// Interns a class or property name from the file, checking the intern
+    /// limit if it hasn't been seen before.
+    fn intern_name(&mut self, name: &str) -> Result<Symbol, InnerError> {
+        self.check_intern_limit(name)?;
+        Ok(self.cache.intern(name))
+    }
+
+    /// Checks that `name` won't take this file over the deserializer's
+    /// intern limit.
+    fn check_intern_limit(&mut self, name: &str) -> Result<(), InnerError> {
+        if let Some(limit) = self.deserializer.intern_limit {
+            if !self.names.contains(name) {
+                if self.names.len() >= limit {
+                    return Err(InnerError::InternLimitExceeded { limit });
+                }
+
+                self.names.insert(name.to_owned());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn next_chunk(&mut self, timed: bool) -> Result<Chunk, InnerError> {
+        let limits = &self.deserializer.limits;
+        Ok(Chunk::decode_cached(&mut self.input, limits, &mut self.cache, timed)?)
+    }
+
+    /// Reads a chunk from somewhere other than the state's input, like the
+    /// file a [`LazyDom`][super::LazyDom] was opened from.
+    pub(super) fn read_chunk<T: Read>(&mut self, reader: T) -> Result<Chunk, InnerError> {
+        Ok(Chunk::decode_cached(reader, &self.deserializer.limits, &mut self.cache, false)?)
+    }
+
+    /// Handles `error`, which was returned while reading the next chunk of the
+    /// file. If the file has ended after its structure was complete and
+    /// truncated files are allowed, the file is marked as truncated and
+    /// `Ok` is returned, so that it can be finished as if its `END` chunk
+    /// had been read.
+    pub(super) fn missing_end(&mut self, error: InnerError) -> Result<(), InnerError> {
+        let structure_complete =
+            self.instance_count >= (self.file_info.instance_count as usize) &&
+            self.file_info.chunks.iter().any(|chunk| chunk.name == "PRNT");
+
+        if !self.deserializer.config.allow_truncated || !structure_complete {
+            return Err(error);
+        }
+
+        if let InnerError::Io { source } = &error {
+            log::warn!("File ended before its END chunk and may be incomplete: {source}");
+            self.file_info.truncated = true;
+            return Ok(());
+        }
+
+        Err(error)
+    }
+
+    /// Gives a chunk's buffer back to the cache once it has been decoded.
+    pub(super) fn recycle_chunk(&mut self, chunk: Chunk) {
+        self.cache.recycle(chunk.data);
+    }
+
+    /// Returns an error if there's anything left in the input after the
+    /// `END` chunk.
+    pub(super) fn check_trailing_data(&mut self) -> Result<(), InnerError> {
+        let mut byte = [0];
+        if self.input.read(&mut byte)? != 0 {
+            return Err(InnerError::TrailingData);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn record_chunk(&mut self, chunk: &Chunk) {
+        self.file_info.push_chunk(chunk);
     }
 
     #[profiling::function]
     pub(super) fn decode_meta_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
+        let max_string_len = self.deserializer.limits.max_string_len;
         let len = chunk.read_le_u32()?;
-        self.metadata.reserve(len as usize);
+
+        // Every entry takes up at least 8 bytes, so a file can't make us
+        // reserve more entries than its chunk could hold.
+        self.metadata.reserve((len as usize).min(chunk.len() / 8));
 
         for _ in 0..len {
-            let key = chunk.read_string()?;
-            let value = chunk.read_string()?;
+            let key = chunk.read_string_limited(max_string_len)?;
+            let value = chunk.read_string_limited(max_string_len)?;
 
             self.metadata.insert(key, value);
         }
 
         let meta_chunk_size = chunk.len();
         let total_instances = self.instances_by_ref.len();
-        if total_instances > 0 {
-            let meta_bytes_per_instance = meta_chunk_size / total_instances;
+        if let Some(meta_bytes_per_instance) = meta_chunk_size.checked_div(total_instances) {
             for instance in self.instances_by_ref.values_mut() {
                 instance.byte_size += meta_bytes_per_instance;
             }
@@ -296,6 +430,7 @@ impl<'db, R: Read> DeserializerState<'db, R> {
 
     #[profiling::function]
     pub(super) fn decode_sstr_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
+        let max_string_len = self.deserializer.limits.max_string_len;
         let version = chunk.read_le_u32()?;
 
         if version != 0 {
@@ -309,8 +444,8 @@ impl<'db, R: Read> DeserializerState<'db, R> {
 
         for _ in 0..num_entries {
             chunk.read_exact(&mut [0; 16])?; // We don't do anything with the hash.
-            let data = chunk.read_binary_string()?;
-            self.shared_strings.push(SharedString::new(data));
+            let data = chunk.read_binary_string_limited(max_string_len)?;
+            self.shared_strings.push(self.cache.shared_string(data));
         }
 
         Ok(())
@@ -318,28 +453,56 @@ impl<'db, R: Read> DeserializerState<'db, R> {
 
     #[profiling::function]
     pub(super) fn decode_inst_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
+        let max_string_len = self.deserializer.limits.max_string_len;
         let type_id = chunk.read_le_u32()?;
-        let type_name = chunk.read_string()?;
+        let type_name = chunk.read_string_limited(max_string_len)?;
         let object_format = chunk.read_u8()?;
         let number_instances = chunk.read_le_u32()?;
 
         log::trace!(
             "INST chunk (type ID {type_id}, type name {type_name}, format {object_format}, {number_instances} instances)"
         );
+        trace_span!("class", class = %type_name, type_id, instances = number_instances);
+
+        self.instance_count += number_instances as usize;
+        self.deserializer.check_instance_limit(self.instance_count)?;
 
         let mut referents = vec![0; number_instances as usize];
         chunk.read_referent_array(&mut referents)?;
 
-        let prop_capacity = self.deserializer.database.classes
-            .get(type_name.as_str())
+        // Skipped classes are checked before the class name is interned, so
+        // they don't count towards the intern limit.
+        if self.deserializer.skip_classes.contains(&type_name) {
+            log::trace!("Skipping {number_instances} instances of {type_name}");
+
+            self.skipped_type_ids.insert(type_id);
+            self.skipped_referents.extend(referents);
+            return Ok(());
+        }
+
+        let class_name = self.intern_name(&type_name)?;
+
+        let class = self.deserializer.database.classes.get(type_name.as_str());
+        if class.is_none() && self.deserializer.config.reject_unknown_classes {
+            return Err(InnerError::UnknownClass { class_name: type_name });
+        }
+
+        let prop_capacity = class
             .map(|class| class.default_properties.len())
             .unwrap_or(0);
 
         // TODO: Check object_format and check for service markers if it's 1?
 
         for &referent in &referents {
+            if self.deserializer.config.reject_duplicate_referents
+                && (self.instances_by_ref.contains_key(&referent)
+                    || self.skipped_referents.contains(&referent))
+            {
+                return Err(InnerError::DuplicateReferent { referent });
+            }
+
             self.instances_by_ref.insert(referent, Instance {
-                builder: InstanceBuilder::with_property_capacity(type_name.as_str(), prop_capacity)
+                builder: InstanceBuilder::with_property_capacity(class_name.clone(), prop_capacity)
                     .with_binary_referent(referent),
                 children: Vec::new(),
                 byte_size: 4,
@@ -348,7 +511,7 @@ impl<'db, R: Read> DeserializerState<'db, R> {
 
         self.type_infos.insert(type_id, TypeInfo {
             type_id,
-            type_name: type_name.into(),
+            type_name: class_name,
             referents,
         });
 
@@ -357,10 +520,18 @@ impl<'db, R: Read> DeserializerState<'db, R> {
 
     #[profiling::function]
     pub(super) fn decode_prop_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
+        let max_string_len = self.deserializer.limits.max_string_len;
         let type_id = chunk.read_le_u32()?;
-        let prop_name = chunk.read_string()?;
+        let prop_name = chunk.read_string_limited(max_string_len)?;
+
+        if self.skipped_type_ids.contains(&type_id) {
+            return Ok(());
+        }
+
+        let interned_prop_name = self.intern_name(&prop_name)?;
 
         let type_info = self.type_infos.get(&type_id).ok_or(InnerError::InvalidTypeId { type_id })?;
+        trace_span!("property", class = %type_info.type_name, property = %prop_name);
 
         // PROP chunks that contain no type byte are ignored by Roblox. This can
         // happen when a new type is introduced.
@@ -379,6 +550,14 @@ impl<'db, R: Read> DeserializerState<'db, R> {
         let binary_type: Type = match binary_type_byte.try_into() {
             Ok(ty) => ty,
             Err(_) => {
+                if self.deserializer.config.reject_unknown_types {
+                    return Err(InnerError::UnknownPropType {
+                        type_name: type_info.type_name.to_string(),
+                        prop_name,
+                        type_id: binary_type_byte,
+                    });
+                }
+
                 if self.unknown_type_ids.insert(binary_type_byte) {
                     log::warn!(
                         "Unknown value type ID {byte:#04x} ({byte}) in Roblox \
@@ -411,7 +590,7 @@ impl<'db, R: Read> DeserializerState<'db, R> {
             for referent in &type_info.referents {
                 let instance = self.instances_by_ref.get_mut(referent).unwrap();
                 let name_start_size = chunk.len();
-                let binary_string = chunk.read_binary_string()?;
+                let binary_string = chunk.read_binary_string_limited(max_string_len)?;
                 let value = match std::str::from_utf8(&binary_string) {
                     Ok(value) => Cow::Borrowed(value),
                     Err(_) => {
@@ -438,8 +617,8 @@ This may cause unexpected or broken behavior in your final results if you rely o
             let Some(property) = find_canonical_property(
                 self.deserializer.database,
                 binary_type,
-                type_info.type_name,
-                prop_name.as_str().into()
+                &type_info.type_name,
+                interned_prop_name
             )
         {
             property
@@ -456,7 +635,7 @@ This may cause unexpected or broken behavior in your final results if you rely o
                         for referent in &type_info.referents {
                             let instance = self.instances_by_ref.get_mut(referent).unwrap();
                             let string_start_size = chunk.len();
-                            let binary_string = chunk.read_binary_string()?;
+                            let binary_string = chunk.read_binary_string_limited(max_string_len)?;
                             let value = match std::str::from_utf8(&binary_string) {
                                 Ok(value) => Cow::Borrowed(value),
                                 Err(_) => {
@@ -481,7 +660,7 @@ This may cause unexpected or broken behavior in your final results if you rely o
                         for referent in &type_info.referents {
                             let instance = self.instances_by_ref.get_mut(referent).unwrap();
                             let content_id_start_size = chunk.len();
-                            let value = chunk.read_string()?;
+                            let value = chunk.read_string_limited(max_string_len)?;
                             add_property(instance, &property, ContentId::from(value).into());
 
                             let bytes_consumed = content_id_start_size - chunk.len();
@@ -492,7 +671,7 @@ This may cause unexpected or broken behavior in your final results if you rely o
                         for referent in &type_info.referents {
                             let instance = self.instances_by_ref.get_mut(referent).unwrap();
                             let start_size = chunk.len();
-                            let value: BinaryString = chunk.read_binary_string()?.into();
+                            let value: BinaryString = chunk.read_binary_string_limited(max_string_len)?.into();
                             let bytes_consumed = start_size - chunk.len();
                             add_property(instance, &property, value.into());
                             instance.byte_size += bytes_consumed;
@@ -501,7 +680,7 @@ This may cause unexpected or broken behavior in your final results if you rely o
                     VariantType::Tags => {
                         for referent in &type_info.referents {
                             let instance = self.instances_by_ref.get_mut(referent).unwrap();
-                            let buffer = chunk.read_binary_string()?;
+                            let buffer = chunk.read_binary_string_limited(max_string_len)?;
 
                             let value = Tags::decode(buffer.as_ref()).map_err(|_| {
                                 InnerError::InvalidPropData {
@@ -513,14 +692,14 @@ This may cause unexpected or broken behavior in your final results if you rely o
                             })?;
 
                             add_property(instance, &property, value.into());
-                            instance.byte_size += buffer.len();
+                            instance.byte_size += 4 + buffer.len();
                         }
                     }
                     VariantType::Attributes => {
                         for referent in &type_info.referents {
                             let instance = self.instances_by_ref.get_mut(referent).unwrap();
-                            let buffer = chunk.read_binary_string()?;
-                            let buffer_len = buffer.len();
+                            let buffer = chunk.read_binary_string_limited(max_string_len)?;
+                            let buffer_len = 4 + buffer.len();
 
                             match Attributes::from_reader(buffer.as_slice()) {
                                 Ok(value) => {
@@ -548,8 +727,8 @@ rbx-dom may require changes to fully support this property. Please open an issue
                     VariantType::MaterialColors => {
                         for referent in &type_info.referents {
                             let instance = self.instances_by_ref.get_mut(referent).unwrap();
-                            let buffer = chunk.read_binary_string()?;
-                            let buffer_len = buffer.len();
+                            let buffer = chunk.read_binary_string_limited(max_string_len)?;
+                            let buffer_len = 4 + buffer.len();
                             match MaterialColors::decode(&buffer) {
                                 Ok(value) => add_property(instance, &property, value.into()),
                                 Err(err) => {
@@ -1133,12 +1312,12 @@ rbx-dom may require changes to fully support this property. Please open an issue
                         for referent in &type_info.referents {
                             let instance = self.instances_by_ref.get_mut(referent).unwrap();
 
-                            let family = chunk.read_string()?;
+                            let family = chunk.read_string_limited(max_string_len)?;
                             let weight = FontWeight::from_u16(
                                 chunk.read_le_u16()?
                             ).unwrap_or_default();
                             let style = FontStyle::from_u8(chunk.read_u8()?).unwrap_or_default();
-                            let cached_face_id = chunk.read_string()?;
+                            let cached_face_id = chunk.read_string_limited(max_string_len)?;
 
                             let cached_face_id = if cached_face_id.is_empty() {
                                 None
@@ -1148,9 +1327,11 @@ rbx-dom may require changes to fully support this property. Please open an issue
 
                             // Calculate byte size before moving values
                             let byte_size =
+                                4 +
                                 family.len() +
                                 2 +
                                 1 +
+                                4 +
                                 cached_face_id.as_ref().map_or(0, |s| s.len());
 
                             add_property(
@@ -1604,7 +1785,7 @@ rbx-dom may require changes to fully support this property. Please open an issue
                         let uri_count = chunk.read_le_u32()? as usize;
                         let mut uris = VecDeque::with_capacity(uri_count);
                         for _ in 0..uri_count {
-                            uris.push_front(chunk.read_string()?);
+                            uris.push_front(chunk.read_string_limited(max_string_len)?);
                         }
 
                         let object_count = chunk.read_le_u32()? as usize;
@@ -1619,10 +1800,18 @@ rbx-dom may require changes to fully support this property. Please open an issue
                         chunk.read_to_end(&mut bytes)?;
 
                         for (referent, ty) in type_info.referents.iter().zip(source_types) {
+                            // Every value has a source type, plus either a
+                            // length-prefixed URI or an object referent.
+                            let mut content_bytes = 4;
                             let value = match ty {
                                 0 => Content::none(),
-                                1 => Content::from_uri(uris.pop_back().unwrap()),
+                                1 => {
+                                    let uri = uris.pop_back().unwrap();
+                                    content_bytes += 4 + uri.len();
+                                    Content::from_uri(uri)
+                                }
                                 2 => {
+                                    content_bytes += 4;
                                     let read_value = objects.pop_back().unwrap();
                                     Content::from_referent(
                                         if
@@ -1642,14 +1831,7 @@ rbx-dom may require changes to fully support this property. Please open an issue
                             };
                             let instance = self.instances_by_ref.get_mut(referent).unwrap();
                             add_property(instance, &property, value.into());
-
-                            let content_bytes = match ty {
-                                0 => 0, // Content::none() - no additional bytes
-                                1 => 4, // URI index - 4 bytes for the URI reference
-                                2 => 4, // Object referent - 4 bytes for the object reference
-                                _ => 0, // Should not happen due to error handling above
-                            };
-                            instance.byte_size += 4 + content_bytes;
+                            instance.byte_size += content_bytes;
                         }
                     }
                     invalid_type => {
@@ -1666,6 +1848,45 @@ rbx-dom may require changes to fully support this property. Please open an issue
         Ok(())
     }
 
+    /// Calls the hooks registered with [`Deserializer::instance_hook`] on
+    /// every instance of their class. Instances that are skipped stay in
+    /// `instances_by_ref`, so that `Ref` properties pointing to them are set
+    /// to `nil` when the tree is built.
+    fn run_instance_hooks(&mut self) {
+        if self.deserializer.hooks.is_empty() {
+            return;
+        }
+
+        let mut type_ids: Vec<u32> = self.type_infos.keys().copied().collect();
+        type_ids.sort_unstable();
+
+        for type_id in type_ids {
+            let type_info = &self.type_infos[&type_id];
+            let hooks: Vec<_> = self.deserializer.hooks
+                .iter()
+                .filter(|(class, _)| *class == type_info.type_name)
+                .map(|(_, hook)| hook)
+                .collect();
+
+            if hooks.is_empty() {
+                continue;
+            }
+
+            for referent in &type_info.referents {
+                let instance = self.instances_by_ref.get_mut(referent).unwrap();
+
+                for hook in &hooks {
+                    if hook(&mut instance.builder) == InstanceAction::Skip {
+                        log::trace!("Hook skipped an instance of {}", type_info.type_name);
+
+                        self.skipped_referents.insert(*referent);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
     #[profiling::function]
     pub(super) fn decode_prnt_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
         let version = chunk.read_u8()?;
@@ -1678,6 +1899,7 @@ rbx-dom may require changes to fully support this property. Please open an issue
         }
 
         let number_objects = chunk.read_le_u32()?;
+        self.deserializer.check_instance_limit(number_objects as usize)?;
 
         log::trace!("PRNT chunk ({number_objects} instances)");
 
@@ -1687,19 +1909,11 @@ rbx-dom may require changes to fully support this property. Please open an issue
         chunk.read_referent_array(&mut subjects)?;
         chunk.read_referent_array(&mut parents)?;
 
-        for (id, parent_ref) in subjects.iter().copied().zip(parents.iter().copied()) {
-            if parent_ref == -1 {
-                self.root_instance_refs.push(id);
-            } else {
-                let instance = self.instances_by_ref.get_mut(&parent_ref).unwrap();
-                instance.children.push(id);
-            }
-        }
+        self.parents.extend(subjects.iter().copied().zip(parents.iter().copied()));
 
         let prnt_chunk_size = chunk.len();
         let total_instances = self.instances_by_ref.len();
-        if total_instances > 0 {
-            let prnt_bytes_per_instance = prnt_chunk_size / total_instances;
+        if let Some(prnt_bytes_per_instance) = prnt_chunk_size.checked_div(total_instances) {
             for instance in self.instances_by_ref.values_mut() {
                 instance.byte_size += prnt_bytes_per_instance;
             }
@@ -1728,10 +1942,235 @@ rbx-dom may require changes to fully support this property. Please open an issue
         byte_sizes
     }
 
+    /// Get the `Ref` of every instance in the file, keyed by its referent
+    pub(super) fn get_all_instance_refs(&self) -> HashMap<i32, Ref> {
+        let mut refs = HashMap::new();
+        for (referent, instance) in &self.instances_by_ref {
+            refs.insert(*referent, instance.builder.referent());
+        }
+        refs
+    }
+
+    /// Reports a problem with the file's parents, which is an error unless
+    /// the deserializer is repairing parents.
+    fn parent_problem(&mut self, problem: ParentProblem) -> Result<(), InnerError> {
+        if !self.deserializer.config.repair_parents {
+            return Err(InnerError::InvalidParent { problem });
+        }
+
+        log::warn!("Repairing PRNT chunk: {problem}");
+        self.file_info.parent_repairs.push(problem);
+
+        Ok(())
+    }
+
+    /// Resolves the parents read from the file's `PRNT` chunks into the
+    /// children of each instance and the list of root instances, checking
+    /// that every instance ends up with exactly one parent that leads to the
+    /// root.
+    fn link_parents(&mut self) -> Result<(), InnerError> {
+        let mut parent_of = HashMap::with_capacity(self.parents.len());
+
+        // Instances are linked in the order they're first given a parent, so
+        // that children keep the order they had in the file.
+        let mut order = Vec::with_capacity(self.parents.len());
+
+        for (referent, parent) in std::mem::take(&mut self.parents) {
+            let skipped = self.skipped_referents.contains(&referent) ||
+                self.skipped_referents.contains(&parent);
+
+            if !skipped && !self.instances_by_ref.contains_key(&referent) {
+                self.parent_problem(ParentProblem::UnknownInstance { referent })?;
+                continue;
+            }
+
+            if parent_of.contains_key(&referent) {
+                self.parent_problem(ParentProblem::MultipleParents { referent, parent })?;
+                continue;
+            }
+
+            let parent = if skipped || parent == -1 || self.instances_by_ref.contains_key(&parent) {
+                parent
+            } else {
+                self.parent_problem(ParentProblem::UnknownParent { referent, parent })?;
+                -1
+            };
+
+            parent_of.insert(referent, parent);
+            order.push(referent);
+        }
+
+        // Every instance has to lead to the root or to a skipped instance by
+        // following its parents. Whether each one does is remembered, so that
+        // every instance is only walked over once.
+        let mut referents: Vec<i32> = self.instances_by_ref.keys().copied().collect();
+        referents.sort_unstable();
+
+        let mut attached = HashMap::with_capacity(referents.len());
+        let mut path = Vec::new();
+        let mut on_path = HashSet::new();
+
+        for referent in referents {
+            let mut current = referent;
+
+            let is_attached = loop {
+                if let Some(&is_attached) = attached.get(&current) {
+                    break is_attached;
+                }
+
+                if self.skipped_referents.contains(&current) {
+                    break false;
+                }
+
+                if !on_path.insert(current) {
+                    self.parent_problem(ParentProblem::Cycle { referent: current })?;
+                    parent_of.insert(current, -1);
+                    break true;
+                }
+
+                path.push(current);
+
+                match parent_of.get(&current) {
+                    Some(-1) => {
+                        break true;
+                    }
+                    Some(&parent) => {
+                        current = parent;
+                    }
+                    None => {
+                        self.parent_problem(ParentProblem::Unparented { referent: current })?;
+                        parent_of.insert(current, -1);
+                        order.push(current);
+                        break true;
+                    }
+                }
+            };
+
+            for referent in path.drain(..) {
+                attached.insert(referent, is_attached);
+            }
+            on_path.clear();
+        }
+
+        for referent in order {
+            let parent = parent_of[&referent];
+
+            if self.skipped_referents.contains(&referent) || self.skipped_referents.contains(&parent) {
+                continue;
+            }
+
+            if parent == -1 {
+                self.root_instance_refs.push(referent);
+            } else {
+                let instance = self.instances_by_ref.get_mut(&parent).unwrap();
+                instance.children.push(referent);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the ID and class name of every type declared so far that isn't
+    /// being skipped.
+    pub(super) fn type_ids(&self) -> impl Iterator<Item = (u32, Symbol)> + '_ {
+        self.type_infos.values().map(|type_info| (type_info.type_id, type_info.type_name.clone()))
+    }
+
+    /// Builds a tree out of the instances declared so far, which has the
+    /// class and referent of every instance and where it is in the tree, but
+    /// none of its properties. Names are left as the instance's class until
+    /// [`DeserializerState::fill_in`] is called for its type.
+    #[profiling::function]
+    pub(super) fn skeleton(&mut self) -> Result<WeakDom, InnerError> {
+        self.link_parents()?;
+
+        let mut classes = HashMap::with_capacity(self.instances_by_ref.len());
+        for type_info in self.type_infos.values() {
+            for &referent in &type_info.referents {
+                classes.insert(referent, type_info.type_name.clone());
+            }
+        }
+
+        let mut tree = std::mem::replace(
+            &mut self.tree,
+            WeakDom::new(InstanceBuilder::new("DataModel"))
+        );
+        let mut instances_to_construct = VecDeque::new();
+
+        let root_ref = tree.root_ref();
+        for &referent in &self.root_instance_refs {
+            instances_to_construct.push_back((referent, root_ref, 1));
+        }
+
+        let max_depth = self.deserializer.limits.max_depth;
+
+        while let Some((referent, parent_ref, depth)) = instances_to_construct.pop_front() {
+            if let Some(limit) = max_depth {
+                if depth > limit {
+                    return Err(LimitExceeded::Depth { limit }.into());
+                }
+            }
+
+            let instance = self.instances_by_ref.get_mut(&referent).unwrap();
+            let builder = InstanceBuilder::new(classes[&referent].clone())
+                .with_referent(instance.builder.referent())
+                .with_binary_referent(referent);
+            let id = tree.insert(parent_ref, builder);
+
+            for referent in std::mem::take(&mut instance.children) {
+                instances_to_construct.push_back((referent, id, depth + 1));
+            }
+        }
+
+        tree.set_metadata(self.metadata.clone().into_iter().collect());
+
+        Ok(tree)
+    }
+
+    /// Moves the names and properties decoded for every instance of a type
+    /// into `tree`, which was built by [`DeserializerState::skeleton`].
+    /// `Ref` properties that point to instances that aren't in the tree are
+    /// set to `nil`, like they are when a whole file is deserialized.
+    pub(super) fn fill_in(&mut self, type_id: u32, tree: &mut WeakDom) {
+        let type_info = match self.type_infos.get(&type_id) {
+            Some(type_info) => type_info,
+            None => {
+                return;
+            }
+        };
+
+        for referent in &type_info.referents {
+            let instance = self.instances_by_ref.get_mut(referent).unwrap();
+            let dom_ref = instance.builder.referent();
+
+            // Descendants of skipped instances aren't in the tree.
+            if tree.get_by_ref(dom_ref).is_none() {
+                continue;
+            }
+
+            tree.set_name(dom_ref, instance.builder.name());
+
+            for (key, mut value) in instance.builder.take_properties() {
+                if let Variant::Ref(target) = &mut value {
+                    if tree.get_by_ref(*target).is_none() {
+                        *target = Ref::none();
+                    }
+                }
+
+                tree.set_property(dom_ref, key, value);
+            }
+        }
+    }
+
     /// Combines together all the decoded information to build and emplace
     /// instances in our tree.
     #[profiling::function]
-    pub(super) fn finish(mut self) -> WeakDom {
+    pub(super) fn finish(mut self) -> Result<(WeakDom, FileInfo), InnerError> {
+        // Properties are stored a class at a time, so instances only have all
+        // of their properties once the whole file has been read.
+        self.run_instance_hooks();
+        self.link_parents()?;
+
         log::trace!("Constructing tree from deserialized data");
 
         // Track all the instances we need to construct. Order of construction
@@ -1744,18 +2183,54 @@ rbx-dom may require changes to fully support this property. Please open an issue
         // start at the top of the tree to begin construction.
         let root_ref = self.tree.root_ref();
         for &referent in &self.root_instance_refs {
-            instances_to_construct.push_back((referent, root_ref));
+            instances_to_construct.push_back((referent, root_ref, 1));
         }
 
-        while let Some((referent, parent_ref)) = instances_to_construct.pop_front() {
+        let max_depth = self.deserializer.limits.max_depth;
+
+        while let Some((referent, parent_ref, depth)) = instances_to_construct.pop_front() {
+            if let Some(limit) = max_depth {
+                if depth > limit {
+                    return Err(LimitExceeded::Depth { limit }.into());
+                }
+            }
+
             let instance = self.instances_by_ref.remove(&referent).unwrap();
-            let id = self.tree.insert(parent_ref, instance.builder);
+            let builder = instance.builder.with_byte_size(instance.byte_size);
+            let id = self.tree.insert(parent_ref, builder);
 
             for referent in instance.children {
-                instances_to_construct.push_back((referent, id));
+                instances_to_construct.push_back((referent, id, depth + 1));
             }
         }
 
-        self.tree
+        // Anything left over is a descendant of a skipped instance, so any
+        // properties that refer to it would be left pointing at nothing.
+        if !self.instances_by_ref.is_empty() {
+            let discarded: HashSet<Ref> = self.instances_by_ref
+                .values()
+                .map(|instance| instance.builder.referent())
+                .collect();
+            let referents: Vec<Ref> = self.tree
+                .descendants()
+                .map(|instance| instance.referent())
+                .collect();
+
+            for referent in referents {
+                let instance = self.tree.get_by_ref_mut(referent).unwrap();
+
+                for (_, value) in instance.properties.iter_mut() {
+                    if let Variant::Ref(target) = value {
+                        if discarded.contains(target) {
+                            *target = Ref::none();
+                        }
+                    }
+                }
+            }
+        }
+
+        self.tree.set_metadata(self.metadata.into_iter().collect());
+
+        Ok((self.tree, self.file_info))
     }
 }
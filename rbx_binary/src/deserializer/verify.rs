@@ -0,0 +1,515 @@
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    fmt,
+    io::{self, Read},
+};
+
+use crate::{
+    chunk::{zstd_decompress, ZSTD_MAGIC_NUMBER},
+    core::RbxReadExt,
+    serializer::CompressionType,
+};
+
+use super::{
+    error::InnerError,
+    file_info::{ChunkInfo, FileInfo},
+    header::FileHeader,
+};
+
+/// The result of checking a binary file with [`verify`][crate::verify].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VerifyReport {
+    /// The file's header and every chunk that could be read, or `None` if
+    /// the header couldn't be read.
+    pub file_info: Option<FileInfo>,
+
+    /// Every problem that was found, in the order they were found.
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl VerifyReport {
+    /// Returns whether no problems were found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// A problem found in a binary file by [`verify`][crate::verify].
+///
+/// Chunks are identified by their position in the file, starting from zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyIssue {
+    /// The file doesn't start with the header of a binary model or place.
+    BadHeader,
+
+    /// The file was written with a version of the format that isn't known.
+    UnknownFileVersion {
+        /// The version in the file's header.
+        version: u16,
+    },
+
+    /// The file ended before the end of its header or one of its chunks.
+    Truncated {
+        /// The chunk that was cut off, or `None` if the header was.
+        chunk: Option<usize>,
+    },
+
+    /// Reading from the source failed for a reason other than it ending.
+    Io {
+        /// A description of the error.
+        message: String,
+    },
+
+    /// A chunk header had reserved bytes that weren't zero.
+    ReservedNotZero {
+        /// The chunk with the bad header.
+        chunk: usize,
+    },
+
+    /// A compressed chunk couldn't be decompressed.
+    Decompression {
+        /// The chunk that couldn't be decompressed.
+        chunk: usize,
+        /// A description of the error.
+        message: String,
+    },
+
+    /// A chunk decompressed to a different number of bytes than its header
+    /// said it would.
+    LengthMismatch {
+        /// The chunk with the wrong length.
+        chunk: usize,
+        /// The length in the chunk's header.
+        expected: u32,
+        /// The number of bytes the chunk decompressed to.
+        actual: usize,
+    },
+
+    /// A chunk's contents ended before everything it declared was read.
+    MalformedChunk {
+        /// The malformed chunk.
+        chunk: usize,
+        /// The name of the chunk, like `INST`.
+        name: String,
+    },
+
+    /// A chunk was written with a version of its format that isn't known.
+    UnknownChunkVersion {
+        /// The chunk with the unknown version.
+        chunk: usize,
+        /// The name of the chunk, like `PRNT`.
+        name: String,
+        /// The version the chunk declared.
+        version: u8,
+    },
+
+    /// More than one `INST` chunk declared the same type ID.
+    DuplicateTypeId {
+        /// The second chunk that declared the type ID.
+        chunk: usize,
+        /// The type ID.
+        type_id: u32,
+    },
+
+    /// A `PROP` chunk referred to a type ID no earlier `INST` chunk declared.
+    UnknownTypeId {
+        /// The chunk with the unknown type ID.
+        chunk: usize,
+        /// The type ID.
+        type_id: u32,
+    },
+
+    /// More than one instance was declared with the same referent.
+    DuplicateReferent {
+        /// The chunk that declared the referent a second time.
+        chunk: usize,
+        /// The referent.
+        referent: i32,
+    },
+
+    /// A `PRNT` chunk referred to a referent no `INST` chunk declared.
+    UnknownReferent {
+        /// The chunk with the unknown referent.
+        chunk: usize,
+        /// The referent.
+        referent: i32,
+    },
+
+    /// The number of instances or classes declared by `INST` chunks didn't
+    /// match the number in the file's header.
+    CountMismatch {
+        /// The number of classes in the header.
+        expected_classes: u32,
+        /// The number of classes declared by `INST` chunks.
+        actual_classes: usize,
+        /// The number of instances in the header.
+        expected_instances: u32,
+        /// The number of instances declared by `INST` chunks.
+        actual_instances: usize,
+    },
+
+    /// The file ended without an `END` chunk.
+    MissingEnd,
+}
+
+impl fmt::Display for VerifyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadHeader => write!(f, "invalid file header"),
+            Self::UnknownFileVersion { version } => {
+                write!(f, "unknown file version {version}. Known versions are: 0")
+            }
+            Self::Truncated { chunk: None } => write!(f, "the file ended inside its header"),
+            Self::Truncated { chunk: Some(chunk) } => {
+                write!(f, "the file ended inside chunk {chunk}")
+            }
+            Self::Io { message } => write!(f, "{message}"),
+            Self::ReservedNotZero { chunk } => {
+                write!(f, "chunk {chunk} has reserved bytes that are not zero")
+            }
+            Self::Decompression { chunk, message } => {
+                write!(f, "chunk {chunk} could not be decompressed: {message}")
+            }
+            Self::LengthMismatch {
+                chunk,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "chunk {chunk} should be {expected} bytes long, but it was {actual} bytes long"
+            ),
+            Self::MalformedChunk { chunk, name } => {
+                write!(f, "{name} chunk {chunk} ended before its contents did")
+            }
+            Self::UnknownChunkVersion {
+                chunk,
+                name,
+                version,
+            } => write!(f, "{name} chunk {chunk} has unknown version {version}"),
+            Self::DuplicateTypeId { chunk, type_id } => {
+                write!(f, "chunk {chunk} declares type ID {type_id} a second time")
+            }
+            Self::UnknownTypeId { chunk, type_id } => {
+                write!(f, "chunk {chunk} refers to undeclared type ID {type_id}")
+            }
+            Self::DuplicateReferent { chunk, referent } => {
+                write!(
+                    f,
+                    "chunk {chunk} declares referent {referent} a second time"
+                )
+            }
+            Self::UnknownReferent { chunk, referent } => {
+                write!(f, "chunk {chunk} refers to undeclared referent {referent}")
+            }
+            Self::CountMismatch {
+                expected_classes,
+                actual_classes,
+                expected_instances,
+                actual_instances,
+            } => write!(
+                f,
+                "the header declares {expected_classes} classes and {expected_instances} \
+                 instances, but the file has {actual_classes} classes and {actual_instances} \
+                 instances"
+            ),
+            Self::MissingEnd => write!(f, "the file has no END chunk"),
+        }
+    }
+}
+
+/// The state kept while checking the chunks of a file.
+#[derive(Default)]
+struct Verifier {
+    /// The number of instances each declared type ID has.
+    types: HashMap<u32, usize>,
+    referents: HashSet<i32>,
+    issues: Vec<VerifyIssue>,
+}
+
+pub(crate) fn verify<R: Read>(mut reader: R) -> VerifyReport {
+    let mut verifier = Verifier::default();
+
+    let header = match FileHeader::decode(&mut reader) {
+        Ok(header) => header,
+        Err(err) => {
+            let issue = match err {
+                InnerError::UnknownFileVersion { version } => {
+                    VerifyIssue::UnknownFileVersion { version }
+                }
+                InnerError::Io { source } => io_issue(source, None),
+                _ => VerifyIssue::BadHeader,
+            };
+
+            return VerifyReport {
+                file_info: None,
+                issues: vec![issue],
+            };
+        }
+    };
+
+    let mut file_info = FileInfo::new(header.version, header.num_types, header.num_instances);
+    let mut found_end = false;
+    let mut readable = true;
+
+    for index in 0.. {
+        let (info, data) = match read_chunk(&mut reader, index) {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(issue) => {
+                verifier.issues.push(issue);
+                readable = false;
+                break;
+            }
+        };
+
+        let name = info.name.clone();
+        file_info.chunks.push(info);
+
+        if let Some(issue) = verifier.check_chunk(index, &name, &data) {
+            verifier.issues.push(issue);
+        }
+
+        if name == "END" {
+            found_end = true;
+            break;
+        }
+    }
+
+    if found_end {
+        let actual_instances = verifier.types.values().sum();
+        if verifier.types.len() != header.num_types as usize
+            || actual_instances != header.num_instances as usize
+        {
+            verifier.issues.push(VerifyIssue::CountMismatch {
+                expected_classes: header.num_types,
+                actual_classes: verifier.types.len(),
+                expected_instances: header.num_instances,
+                actual_instances,
+            });
+        }
+    } else if readable {
+        verifier.issues.push(VerifyIssue::MissingEnd);
+    }
+
+    VerifyReport {
+        file_info: Some(file_info),
+        issues: verifier.issues,
+    }
+}
+
+/// Reads one chunk, returning `None` if the reader ended cleanly before it.
+/// Unlike [`Chunk::decode`][crate::chunk::Chunk::decode], this never panics on
+/// malformed input.
+fn read_chunk<R: Read>(
+    reader: &mut R,
+    index: usize,
+) -> Result<Option<(ChunkInfo, Vec<u8>)>, VerifyIssue> {
+    let mut header = [0; 16];
+    let read = read_fully(reader, &mut header).map_err(|err| io_issue(err, Some(index)))?;
+    if read == 0 {
+        return Ok(None);
+    } else if read < header.len() {
+        return Err(VerifyIssue::Truncated { chunk: Some(index) });
+    }
+
+    let name = &header[0..4];
+    let compressed_len = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    let len = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    if header[12..16] != [0; 4] {
+        return Err(VerifyIssue::ReservedNotZero { chunk: index });
+    }
+
+    let stored_len = if compressed_len == 0 {
+        len
+    } else {
+        compressed_len
+    };
+
+    // The stored bytes are read before anything is allocated for them, so a
+    // header with a huge length can't cause a huge allocation on its own.
+    let mut stored = Vec::new();
+    reader
+        .take(stored_len as u64)
+        .read_to_end(&mut stored)
+        .map_err(|err| io_issue(err, Some(index)))?;
+    if stored.len() < stored_len as usize {
+        return Err(VerifyIssue::Truncated { chunk: Some(index) });
+    }
+
+    let (data, compression) = if compressed_len == 0 {
+        (stored, CompressionType::None)
+    } else if stored.starts_with(ZSTD_MAGIC_NUMBER) {
+        let data = zstd_decompress(&stored, len as usize).map_err(|err| {
+            VerifyIssue::Decompression {
+                chunk: index,
+                message: err.to_string(),
+            }
+        })?;
+        (data, CompressionType::Zstd)
+    } else {
+        let data = lz4_flex::block::decompress(&stored, len as usize).map_err(|err| {
+            VerifyIssue::Decompression {
+                chunk: index,
+                message: err.to_string(),
+            }
+        })?;
+        (data, CompressionType::Lz4)
+    };
+
+    if data.len() != len as usize {
+        return Err(VerifyIssue::LengthMismatch {
+            chunk: index,
+            expected: len,
+            actual: data.len(),
+        });
+    }
+
+    Ok(Some((
+        ChunkInfo::new(name, compression, stored_len, len),
+        data,
+    )))
+}
+
+impl Verifier {
+    fn check_chunk(&mut self, index: usize, name: &str, data: &[u8]) -> Option<VerifyIssue> {
+        let mut reader = data;
+        let result = match name {
+            "INST" => self.check_inst(index, &mut reader),
+            "PROP" => self.check_prop(index, &mut reader),
+            "PRNT" => self.check_prnt(index, &mut reader),
+            _ => Ok(None),
+        };
+
+        match result {
+            Ok(issue) => issue,
+            Err(_) => Some(VerifyIssue::MalformedChunk {
+                chunk: index,
+                name: name.to_owned(),
+            }),
+        }
+    }
+
+    fn check_inst(&mut self, index: usize, reader: &mut &[u8]) -> io::Result<Option<VerifyIssue>> {
+        let type_id = reader.read_le_u32()?;
+        let class_len = reader.read_le_u32()?;
+        skip(reader, class_len as usize)?;
+        let _object_format = reader.read_u8()?;
+        let count = reader.read_le_u32()? as usize;
+        let referents = read_referents(reader, count)?;
+
+        if self.types.insert(type_id, count).is_some() {
+            return Ok(Some(VerifyIssue::DuplicateTypeId {
+                chunk: index,
+                type_id,
+            }));
+        }
+
+        for referent in referents {
+            if !self.referents.insert(referent) {
+                return Ok(Some(VerifyIssue::DuplicateReferent {
+                    chunk: index,
+                    referent,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn check_prop(&mut self, index: usize, reader: &mut &[u8]) -> io::Result<Option<VerifyIssue>> {
+        let type_id = reader.read_le_u32()?;
+
+        if self.types.contains_key(&type_id) {
+            Ok(None)
+        } else {
+            Ok(Some(VerifyIssue::UnknownTypeId {
+                chunk: index,
+                type_id,
+            }))
+        }
+    }
+
+    fn check_prnt(&mut self, index: usize, reader: &mut &[u8]) -> io::Result<Option<VerifyIssue>> {
+        let version = reader.read_u8()?;
+        if version != 0 {
+            return Ok(Some(VerifyIssue::UnknownChunkVersion {
+                chunk: index,
+                name: "PRNT".to_owned(),
+                version,
+            }));
+        }
+
+        let count = reader.read_le_u32()? as usize;
+        let subjects = read_referents(reader, count)?;
+        let parents = read_referents(reader, count)?;
+
+        let unknown = subjects
+            .into_iter()
+            .chain(parents.into_iter().filter(|parent| *parent != -1))
+            .find(|referent| !self.referents.contains(referent));
+
+        Ok(unknown.map(|referent| VerifyIssue::UnknownReferent {
+            chunk: index,
+            referent,
+        }))
+    }
+}
+
+/// Reads a referent array, checking that the chunk is long enough to hold it
+/// before allocating space for it.
+fn read_referents(reader: &mut &[u8], count: usize) -> io::Result<Vec<i32>> {
+    if count.saturating_mul(4) > reader.len() {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let mut referents = vec![0; count];
+    reader.read_interleaved_i32_array(&mut referents)?;
+
+    // Unlike `read_referent_array`, this wraps instead of overflowing, since
+    // the deltas come from an untrusted file.
+    let mut last: i32 = 0;
+    for referent in &mut referents {
+        *referent = referent.wrapping_add(last);
+        last = *referent;
+    }
+
+    Ok(referents)
+}
+
+fn skip(reader: &mut &[u8], len: usize) -> io::Result<()> {
+    if len > reader.len() {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    *reader = &reader[len..];
+    Ok(())
+}
+
+/// Reads into `buffer` until it's full or the reader ends, returning how many
+/// bytes were read.
+fn read_fully<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(read)
+}
+
+fn io_issue(err: io::Error, chunk: Option<usize>) -> VerifyIssue {
+    if err.kind() == io::ErrorKind::UnexpectedEof {
+        VerifyIssue::Truncated { chunk }
+    } else {
+        VerifyIssue::Io {
+            message: err.to_string(),
+        }
+    }
+}
@@ -49,14 +49,83 @@ rbx_binary::to_writer(output, &dom, &[dom.root_ref()])?;
 
 # Ok::<(), Box<dyn std::error::Error>>(())
 ```
+
+## Tracing
+
+With the `tracing` feature enabled, decoding and encoding enter
+[`tracing`](https://docs.rs/tracing) spans that any subscriber can record:
+
+* `rbx_binary::deserialize` and `rbx_binary::serialize` around a whole file
+* `chunk` around each chunk, with its `name` and length
+* `class` around each `INST` chunk, with the `class` and its number of
+  `instances`
+* `property` around each `PROP` chunk, with its `class` and `property`
+
+A chunk that fails to decode also emits an error event inside its span. The
+async functions enter their whole-file span only while they're decoding or
+encoding, and leave it whenever they wait on their stream, so their futures
+stay `Send`.
+
+## Zstandard
+
+Chunks compressed with Zstandard are read and written with the `zstd`
+crate, which builds the C zstd library. That needs a C compiler for the
+target, so it's behind the `zstd` feature, which is on by default. Without
+it, rbx_binary is pure Rust and builds for targets like
+`wasm32-unknown-unknown`. Files with Zstandard-compressed chunks then fail
+to decode, and encoding with [`CompressionType::Zstd`] fails.
 */
 
 #![deny(missing_docs)]
 
+/// Enters a `tracing` span at the `INFO` level that lasts until the end of the
+/// enclosing block, if the `tracing` feature is enabled. Takes the same
+/// arguments as `tracing::info_span!`; they aren't evaluated otherwise.
+macro_rules! trace_span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($($args)*).entered();
+    };
+}
+
+/// Creates a [`FileSpan`] that can be entered around each part of decoding or
+/// encoding a file, for async functions that can't hold an entered span
+/// across an `.await`. Takes the same arguments as `trace_span!`.
+#[cfg(feature = "tokio")]
+macro_rules! file_span {
+    ($($args:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        let span = crate::FileSpan(tracing::info_span!($($args)*));
+        #[cfg(not(feature = "tracing"))]
+        let span = crate::FileSpan();
+        span
+    }};
+}
+
+/// A `tracing` span for a whole file, made by `file_span!`, which does nothing
+/// if the `tracing` feature is disabled.
+#[cfg(feature = "tokio")]
+pub(crate) struct FileSpan(#[cfg(feature = "tracing")] tracing::Span);
+
+#[cfg(feature = "tokio")]
+impl FileSpan {
+    /// Runs `f` inside of the span.
+    pub(crate) fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "tracing")]
+        return self.0.in_scope(f);
+
+        #[cfg(not(feature = "tracing"))]
+        f()
+    }
+}
+
+mod archive;
 mod chunk;
 mod core;
 mod deserializer;
+mod roundtrip;
 mod serializer;
+mod transcode;
 mod types;
 
 #[cfg(any(test, feature = "unstable_text_format"))]
@@ -76,8 +145,19 @@ pub mod text_format {
 }
 
 pub use crate::{
-    deserializer::{Deserializer, Error as DecodeError},
-    serializer::{CompressionType, Error as EncodeError, InnerError, Serializer, SerializerState},
+    archive::{ArchiveEntry, ArchiveError, ArchiveReader, ArchiveWriter},
+    deserializer::{
+        BatchDecoder, ChunkInfo, ChunkStats, ClassSchema, DecodeConfig, DecodeLimits, Deserializer,
+        Error as DecodeError, FileInfo, FileSchema, InstanceAction, LazyDom, LimitExceeded,
+        ParentProblem, PropertySchema, VerifyIssue, VerifyReport,
+    },
+    roundtrip::{Difference, DifferenceKind, RoundtripError, RoundtripReport},
+    serializer::{
+        ChildOrder, CompressionType, DanglingSharedStrings, Error as EncodeError, InnerError,
+        InstanceIssues, InstanceSize, PropertyAction, SerializedSize, Serializer, SerializerState,
+        SizeEstimate, SizeLimitExceeded, ValidationIssue,
+    },
+    transcode::{TranscodeError, Transcoder},
 };
 
 /// Deserialize a Roblox binary model or place from a stream.
@@ -85,8 +165,152 @@ pub fn from_reader<R: Read>(reader: R) -> Result<WeakDom, DecodeError> {
     Deserializer::new().deserialize(reader)
 }
 
+/// Decode a Roblox binary model or place from a stream, write it back out,
+/// decode the result, and report every way it differs from the original.
+///
+/// Instances are compared by their class, name, properties, and number of
+/// children. This is meant for checking that a corpus of files survives
+/// being rewritten by rbx_binary.
+///
+/// ```
+/// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+/// let dom = WeakDom::new(InstanceBuilder::new("Folder").with_property("Archivable", true));
+/// let mut buffer = Vec::new();
+/// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+///
+/// let report = rbx_binary::roundtrip(buffer.as_slice())?;
+/// for difference in &report.differences {
+///     println!("{difference}");
+/// }
+/// assert!(report.is_identical());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn roundtrip<R: Read>(reader: R) -> Result<RoundtripReport, RoundtripError> {
+    roundtrip::roundtrip(reader)
+}
+
+/// Compare two DOMs and report every way `after` differs from `before`, in
+/// the same way as [`roundtrip`].
+///
+/// Instances are paired up by their position in the tree, so this works best
+/// on two versions of the same file.
+pub fn compare(before: &WeakDom, after: &WeakDom) -> Vec<Difference> {
+    roundtrip::compare(before, after)
+}
+
+/// Read which classes and properties a Roblox binary model or place uses from
+/// a stream, along with how many instances of each class it has and how many
+/// bytes each property takes up, without decoding any property values.
+///
+/// Every chunk still has to be decompressed, but class and property names are
+/// only read as strings, so this is cheap to run on untrusted files.
+///
+/// ```
+/// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+/// let dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+///     InstanceBuilder::new("Part"),
+///     InstanceBuilder::new("Part"),
+/// ]));
+/// let mut buffer = Vec::new();
+/// rbx_binary::to_writer(&mut buffer, &dom, dom.root().children())?;
+///
+/// let schema = rbx_binary::scan_schema(buffer.as_slice())?;
+/// let part = schema.class("Part").unwrap();
+/// assert_eq!(part.instance_count, 2);
+/// assert!(part.property("Name").is_some());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn scan_schema<R: Read>(reader: R) -> Result<FileSchema, DecodeError> {
+    deserializer::scan_schema(reader)
+}
+
+/// Read how many classes and instances a Roblox binary model or place declares
+/// and how large each of its chunks is from a stream, without decompressing or
+/// decoding any of them.
+///
+/// Only the file's header and the header of each chunk are read, so this is
+/// cheap enough to run on every file before deciding whether to deserialize
+/// it. The counts are the ones declared by the file's header, and aren't
+/// checked against the file's contents.
+///
+/// ```
+/// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+/// let dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+///     InstanceBuilder::new("Part"),
+///     InstanceBuilder::new("Part"),
+/// ]));
+/// let mut buffer = Vec::new();
+/// rbx_binary::to_writer(&mut buffer, &dom, &[dom.root_ref()])?;
+///
+/// let info = rbx_binary::peek_stats(buffer.as_slice())?;
+/// assert_eq!(info.instance_count, 3);
+/// assert!(info.decompressed_size() < 64 * 1024 * 1024);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn peek_stats<R: Read>(reader: R) -> Result<FileInfo, DecodeError> {
+    deserializer::peek_stats(reader)
+}
+
+/// Check a Roblox binary model or place from a stream for corruption without
+/// decoding it into a dom.
+///
+/// Every chunk is read and decompressed, the `INST` and `PRNT` chunks are
+/// checked for referents that are duplicated or undeclared, and the file must
+/// end with an `END` chunk. Property values aren't decoded, so a file that
+/// passes can still fail to deserialize if they're malformed. Reading stops at
+/// the first problem that makes the rest of the file unreadable.
+///
+/// ```
+/// # use rbx_dom_weak::{InstanceBuilder, WeakDom};
+/// let dom = WeakDom::new(InstanceBuilder::new("Folder"));
+/// let mut buffer = Vec::new();
+/// rbx_binary::to_writer(&mut buffer, &dom, dom.root().children())?;
+///
+/// assert!(rbx_binary::verify(buffer.as_slice()).is_valid());
+///
+/// let report = rbx_binary::verify(&buffer[..buffer.len() - 4]);
+/// assert!(!report.is_valid());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn verify<R: Read>(reader: R) -> VerifyReport {
+    deserializer::verify(reader)
+}
+
+/// Deserialize a Roblox binary model or place from an async stream without
+/// blocking the executor.
+#[cfg(feature = "tokio")]
+pub async fn from_async_reader<R>(reader: R) -> Result<WeakDom, DecodeError>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    Deserializer::new().deserialize_async(reader).await
+}
+
 /// Serializes a subset of the given DOM to a binary format model or place,
 /// writing to something that implements the `std::io::Write` trait.
+///
+/// Each instance in `refs` is written along with its descendants, and can be
+/// anywhere in the DOM, like a single model inside of a place. Instances whose
+/// ancestor is also in `refs` are only written once, as part of that ancestor.
+/// `Ref` properties that point outside of the written instances are written as
+/// `nil`.
 pub fn to_writer<W: Write>(writer: W, dom: &WeakDom, refs: &[Ref]) -> Result<(), EncodeError> {
     Serializer::new().serialize(writer, dom, refs)
 }
+
+/// Computes the size of the binary format model or place that [`to_writer`]
+/// would write for a subset of the given DOM, without keeping any of the
+/// output. See [`Serializer::estimate_size`] for details.
+pub fn estimate_size(dom: &WeakDom, refs: &[Ref]) -> Result<SizeEstimate, EncodeError> {
+    Serializer::new().estimate_size(dom, refs)
+}
+
+/// Serializes a subset of the given DOM to a binary format model or place,
+/// writing each chunk to an async stream as soon as it's encoded.
+#[cfg(feature = "tokio")]
+pub async fn to_async_writer<W>(writer: W, dom: &WeakDom, refs: &[Ref]) -> Result<(), EncodeError>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    Serializer::new().serialize_async(writer, dom, refs).await
+}
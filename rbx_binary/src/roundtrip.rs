@@ -0,0 +1,308 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{self, Read},
+};
+
+use ahash::{HashMap, HashMapExt};
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    Instance, Symbol, WeakDom,
+};
+use thiserror::Error;
+
+use crate::{deserializer::Error as DecodeError, serializer::Error as EncodeError};
+
+/// The differences found by [`roundtrip`][crate::roundtrip] between a file
+/// and the same file after being written back out by rbx_binary.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct RoundtripReport {
+    /// Every difference that was found, ordered by where the instance they
+    /// were found on is in the file, top-down.
+    pub differences: Vec<Difference>,
+}
+
+impl RoundtripReport {
+    /// Returns whether the file survived being written back out unchanged.
+    pub fn is_identical(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// A difference between an instance in a file and the same instance after
+/// the file was written back out, as part of a [`RoundtripReport`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Difference {
+    /// The full name of the instance in the original file, from
+    /// [`WeakDom::full_name_of`].
+    pub instance: String,
+
+    /// How the instance changed.
+    pub kind: DifferenceKind,
+}
+
+/// How an instance changed, as part of a [`Difference`].
+///
+/// `Ref` properties are compared by the instance they point to rather than by
+/// their value, since referents are never the same between two decodes.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DifferenceKind {
+    /// The instance's class changed.
+    Class {
+        /// The class in the original file.
+        before: Symbol,
+        /// The class after the round trip.
+        after: Symbol,
+    },
+
+    /// The instance's name changed.
+    Name {
+        /// The name in the original file.
+        before: String,
+        /// The name after the round trip.
+        after: String,
+    },
+
+    /// A property's value changed.
+    Property {
+        /// The name of the property.
+        property: Symbol,
+        /// The value in the original file.
+        before: Variant,
+        /// The value after the round trip.
+        after: Variant,
+    },
+
+    /// A property was lost.
+    PropertyRemoved {
+        /// The name of the property.
+        property: Symbol,
+        /// The value in the original file.
+        value: Variant,
+    },
+
+    /// A property appeared that wasn't in the original file.
+    PropertyAdded {
+        /// The name of the property.
+        property: Symbol,
+        /// The value after the round trip.
+        value: Variant,
+    },
+
+    /// The instance has a different number of children. Only the children
+    /// that are in both files are compared.
+    Children {
+        /// The number of children in the original file.
+        before: usize,
+        /// The number of children after the round trip.
+        after: usize,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let instance = &self.instance;
+
+        match &self.kind {
+            DifferenceKind::Class { before, after } => {
+                write!(f, "{instance}: class changed from {before} to {after}")
+            }
+            DifferenceKind::Name { before, after } => {
+                write!(f, "{instance}: name changed from {before:?} to {after:?}")
+            }
+            DifferenceKind::Property {
+                property,
+                before,
+                after,
+            } => write!(
+                f,
+                "{instance}: {property} changed from {before:?} to {after:?}"
+            ),
+            DifferenceKind::PropertyRemoved { property, value } => {
+                write!(f, "{instance}: {property} ({value:?}) was removed")
+            }
+            DifferenceKind::PropertyAdded { property, value } => {
+                write!(f, "{instance}: {property} ({value:?}) was added")
+            }
+            DifferenceKind::Children { before, after } => write!(
+                f,
+                "{instance}: number of children changed from {before} to {after}"
+            ),
+        }
+    }
+}
+
+/// An error that stopped [`roundtrip`][crate::roundtrip] from comparing a
+/// file.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RoundtripError {
+    /// The original file couldn't be read.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The original file couldn't be decoded.
+    #[error("could not decode the original file: {0}")]
+    Decode(#[source] DecodeError),
+
+    /// The decoded file couldn't be written back out.
+    #[error("could not encode the decoded file: {0}")]
+    Encode(#[source] EncodeError),
+
+    /// The file that was written back out couldn't be decoded.
+    #[error("could not decode the re-encoded file: {0}")]
+    Redecode(#[source] DecodeError),
+}
+
+pub(crate) fn roundtrip<R: Read>(mut reader: R) -> Result<RoundtripReport, RoundtripError> {
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents)?;
+
+    let before = crate::from_reader(contents.as_slice()).map_err(RoundtripError::Decode)?;
+
+    let mut encoded = Vec::new();
+    crate::to_writer(&mut encoded, &before, before.root().children())
+        .map_err(RoundtripError::Encode)?;
+
+    let after = crate::from_reader(encoded.as_slice()).map_err(RoundtripError::Redecode)?;
+
+    Ok(RoundtripReport {
+        differences: compare(&before, &after),
+    })
+}
+
+/// Compares two doms, pairing up instances by their position in the tree.
+pub(crate) fn compare(before: &WeakDom, after: &WeakDom) -> Vec<Difference> {
+    // Every instance is paired up before any properties are compared, so that
+    // `Ref` properties can be compared by what they point to.
+    let mut pairs = Vec::new();
+    let mut paired = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back((before.root_ref(), after.root_ref()));
+
+    while let Some((before_ref, after_ref)) = queue.pop_front() {
+        let before_instance = before.get_by_ref(before_ref).unwrap();
+        let after_instance = after.get_by_ref(after_ref).unwrap();
+
+        pairs.push((before_instance, after_instance));
+        paired.insert(before_ref, after_ref);
+
+        for (before_child, after_child) in before_instance
+            .children()
+            .iter()
+            .zip(after_instance.children())
+        {
+            queue.push_back((*before_child, *after_child));
+        }
+    }
+
+    let mut differences = Vec::new();
+
+    for (before_instance, after_instance) in pairs {
+        let mut push = |kind| {
+            differences.push(Difference {
+                instance: before.full_name_of(before_instance.referent()),
+                kind,
+            })
+        };
+
+        if before_instance.class != after_instance.class {
+            push(DifferenceKind::Class {
+                before: before_instance.class.clone(),
+                after: after_instance.class.clone(),
+            });
+        }
+
+        if before_instance.name != after_instance.name {
+            push(DifferenceKind::Name {
+                before: before_instance.name.clone(),
+                after: after_instance.name.clone(),
+            });
+        }
+
+        for kind in compare_properties(before_instance, after_instance, &paired) {
+            push(kind);
+        }
+
+        let before_children = before_instance.children().len();
+        let after_children = after_instance.children().len();
+        if before_children != after_children {
+            push(DifferenceKind::Children {
+                before: before_children,
+                after: after_children,
+            });
+        }
+    }
+
+    differences
+}
+
+fn compare_properties(
+    before: &Instance,
+    after: &Instance,
+    paired: &HashMap<Ref, Ref>,
+) -> Vec<DifferenceKind> {
+    let mut keys: Vec<Symbol> = before
+        .properties
+        .keys()
+        .chain(after.properties.keys())
+        .cloned()
+        .collect();
+    keys.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+    keys.dedup();
+
+    let mut differences = Vec::new();
+
+    for property in keys {
+        match (
+            before.properties.get(&property),
+            after.properties.get(&property),
+        ) {
+            (Some(before), Some(after)) => {
+                if !same_value(before, after, paired) {
+                    differences.push(DifferenceKind::Property {
+                        property,
+                        before: before.clone(),
+                        after: after.clone(),
+                    });
+                }
+            }
+            (Some(value), None) => differences.push(DifferenceKind::PropertyRemoved {
+                property,
+                value: value.clone(),
+            }),
+            (None, Some(value)) => differences.push(DifferenceKind::PropertyAdded {
+                property,
+                value: value.clone(),
+            }),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    differences
+}
+
+fn same_value(before: &Variant, after: &Variant, paired: &HashMap<Ref, Ref>) -> bool {
+    match (before, after) {
+        (Variant::Ref(before), Variant::Ref(after)) => {
+            if before.is_none() {
+                after.is_none()
+            } else {
+                paired.get(before) == Some(after)
+            }
+        }
+
+        // NaN is written out unchanged, but never compares equal to itself.
+        (Variant::Float32(before), Variant::Float32(after)) => {
+            before == after || (before.is_nan() && after.is_nan())
+        }
+        (Variant::Float64(before), Variant::Float64(after)) => {
+            before == after || (before.is_nan() && after.is_nan())
+        }
+
+        _ => before == after,
+    }
+}
@@ -0,0 +1,96 @@
+use ahash::HashMap;
+use rbx_dom_weak::{types::Ref, WeakDom};
+use thiserror::Error;
+
+/// How many of the largest instances a [`SizeLimitExceeded`] lists.
+const LARGEST_INSTANCES: usize = 10;
+
+/// Details of a file that was larger than
+/// [`Serializer::max_size`][crate::Serializer::max_size] allows, returned by
+/// [`EncodeError::size_limit_exceeded`][crate::EncodeError::size_limit_exceeded].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "Output was {len} bytes, which is more than the limit of {limit} bytes{}",
+    largest_summary(.largest)
+)]
+#[non_exhaustive]
+pub struct SizeLimitExceeded {
+    /// The maximum number of bytes the file was allowed to take up.
+    pub limit: u64,
+
+    /// The number of bytes the file would have taken up.
+    pub len: u64,
+
+    /// The instances that contributed the most bytes to the file, largest
+    /// first.
+    pub largest: Vec<InstanceSize>,
+}
+
+/// The number of bytes one instance contributed to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InstanceSize {
+    /// The referent of the instance in the dom being serialized.
+    pub referent: Ref,
+
+    /// The full name of the instance, as returned by
+    /// [`WeakDom::full_name_of`].
+    pub full_name: String,
+
+    /// The number of bytes the instance's own properties contributed to the
+    /// file, not counting its descendants.
+    pub len: usize,
+}
+
+impl SizeLimitExceeded {
+    pub(crate) fn new(
+        dom: &WeakDom,
+        limit: u64,
+        len: u64,
+        byte_sizes: &HashMap<Ref, usize>,
+    ) -> Self {
+        let mut sizes: Vec<usize> = byte_sizes.values().copied().collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        let cutoff = sizes.get(LARGEST_INSTANCES - 1).copied().unwrap_or(0);
+
+        // Every instance tied with the last one that makes the list is looked
+        // at, and ties are broken by full name, so that which instances are
+        // listed doesn't depend on the order of the map.
+        let mut largest: Vec<InstanceSize> = byte_sizes
+            .iter()
+            .filter(|(_, len)| **len >= cutoff)
+            .map(|(referent, len)| InstanceSize {
+                referent: *referent,
+                full_name: dom.full_name_of(*referent),
+                len: *len,
+            })
+            .collect();
+        largest.sort_by(|a, b| {
+            b.len
+                .cmp(&a.len)
+                .then_with(|| a.full_name.cmp(&b.full_name))
+        });
+        largest.truncate(LARGEST_INSTANCES);
+
+        Self {
+            limit,
+            len,
+            largest,
+        }
+    }
+}
+
+fn largest_summary(largest: &[InstanceSize]) -> String {
+    let mut summary = String::new();
+
+    for (i, instance) in largest.iter().enumerate() {
+        summary.push_str(if i == 0 {
+            ". Largest instances: "
+        } else {
+            ", "
+        });
+        summary.push_str(&format!("{} ({} bytes)", instance.full_name, instance.len));
+    }
+
+    summary
+}
@@ -0,0 +1,29 @@
+use rbx_dom_weak::types::{SharedString, SharedStringHash};
+
+/// The SharedStrings that were found while collecting the properties of a
+/// dom, but that no property in the file refers to, returned by
+/// [`Serializer::dangling_shared_strings`][crate::Serializer::dangling_shared_strings].
+///
+/// These are the values of properties that aren't serialized or that a
+/// [property hook][crate::Serializer::property_hook] replaced, and the
+/// defaults of SharedString properties that every instance has a value for.
+/// The serializer always leaves them out of the SSTR chunk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DanglingSharedStrings {
+    /// The hashes of the SharedStrings that were left out, sorted.
+    pub dropped: Vec<SharedStringHash>,
+
+    /// The number of uncompressed bytes leaving them out saved in the SSTR
+    /// chunk, counting each one's hash and length as well as its data.
+    pub reclaimed_bytes: u64,
+}
+
+impl DanglingSharedStrings {
+    pub(crate) fn add(&mut self, shared_string: &SharedString) {
+        // Each entry is a 16-byte hash, followed by the length of the data
+        // and the data itself.
+        self.dropped.push(shared_string.hash());
+        self.reclaimed_bytes += 16 + 4 + shared_string.data().len() as u64;
+    }
+}
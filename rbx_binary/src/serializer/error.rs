@@ -3,6 +3,8 @@ use std::io;
 use rbx_dom_weak::types::Ref;
 use thiserror::Error;
 
+use super::budget::SizeLimitExceeded;
+
 /// Represents an error that occurred during serialization.
 #[derive(Debug, Error)]
 #[error(transparent)]
@@ -10,6 +12,17 @@ pub struct Error {
     source: Box<InnerError>,
 }
 
+impl Error {
+    /// Returns how far over [`Serializer::max_size`][crate::Serializer::max_size]
+    /// the output would have been, if that's why serialization failed.
+    pub fn size_limit_exceeded(&self) -> Option<&SizeLimitExceeded> {
+        match &*self.source {
+            InnerError::SizeLimitExceeded { exceeded } => Some(exceeded),
+            _ => None,
+        }
+    }
+}
+
 impl From<InnerError> for Error {
     fn from(inner: InnerError) -> Self {
         Self {
@@ -81,4 +94,12 @@ pub enum InnerError {
         /// The referent that was not found in the DOM.
         referent: Ref,
     },
+
+    /// The output was larger than the serializer's
+    /// [`max_size`][crate::Serializer::max_size].
+    #[error(transparent)]
+    SizeLimitExceeded {
+        /// How far over the limit the output was.
+        exceeded: SizeLimitExceeded,
+    },
 }
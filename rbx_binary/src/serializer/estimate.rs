@@ -0,0 +1,171 @@
+use std::io::{self, Write};
+
+use rbx_dom_weak::{
+    types::{ContentType, Matrix3, PhysicalProperties, Variant},
+    Instance,
+};
+
+use crate::{deserializer::ChunkInfo, types::Type};
+
+/// The size a binary file would have, returned by
+/// [`Serializer::estimate_size`][crate::Serializer::estimate_size] and
+/// [`estimate_size`][crate::estimate_size].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SizeEstimate {
+    /// The total number of bytes the file would take up, including its header
+    /// and the header of every chunk.
+    pub len: u64,
+
+    /// Every chunk the file would contain, in the order they'd be written.
+    pub chunks: Vec<ChunkInfo>,
+}
+
+/// A writer that discards everything written to it, only keeping track of how
+/// many bytes it was given.
+#[derive(Debug, Default)]
+pub(crate) struct ByteCounter {
+    pub len: u64,
+}
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.len += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Estimates of the number of bytes values and instances contribute to a
+/// binary file, before compression, without serializing anything.
+///
+/// Unlike [`Serializer::serialize_with_byte_sizes`][crate::Serializer::serialize_with_byte_sizes]
+/// and the sizes the deserializer records, these work on any dom, including
+/// ones that were never read from or written to a binary file. They're
+/// measured the same way those sizes are, but since the reflection database
+/// isn't consulted, properties the serializer would skip, rename, or add
+/// defaults for aren't accounted for.
+///
+/// ```
+/// use rbx_binary::SerializedSize;
+/// use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+///
+/// assert_eq!(Variant::from("hello").serialized_size_estimate(), 9);
+///
+/// let dom = WeakDom::new(InstanceBuilder::new("StringValue").with_property("Value", "hello"));
+/// println!("{} bytes", dom.root().serialized_size_estimate());
+/// ```
+pub trait SerializedSize {
+    /// Returns roughly how many bytes this takes up in a binary file, before
+    /// compression.
+    fn serialized_size_estimate(&self) -> usize;
+}
+
+impl SerializedSize for Variant {
+    /// Returns how many bytes the value takes up in a `PROP` chunk. Values of
+    /// types the binary format can't store are measured as zero.
+    ///
+    /// SharedStrings are counted in full, even though they're only stored
+    /// once per file, to match how their size is attributed to every
+    /// instance that uses them.
+    fn serialized_size_estimate(&self) -> usize {
+        match self {
+            Variant::String(value) => 4 + value.len(),
+            Variant::ContentId(value) => 4 + value.as_str().len(),
+            Variant::BinaryString(value) => 4 + AsRef::<[u8]>::as_ref(value).len(),
+            Variant::Tags(value) => 4 + value.encode().len(),
+            Variant::MaterialColors(value) => 4 + value.encode().len(),
+            Variant::Attributes(value) => {
+                let mut counter = ByteCounter::default();
+                match value.to_writer(&mut counter) {
+                    Ok(()) => 4 + counter.len as usize,
+                    Err(_) => 0,
+                }
+            }
+            _ => Type::from_rbx_type(self.ty())
+                .map_or(0, |prop_type| value_byte_size(prop_type, self)),
+        }
+    }
+}
+
+impl SerializedSize for Instance {
+    /// Returns how many bytes the instance's referent, name, and properties
+    /// take up, not counting its descendants.
+    fn serialized_size_estimate(&self) -> usize {
+        let name_size = Variant::from(self.name.as_str()).serialized_size_estimate();
+        let properties_size: usize = self
+            .properties
+            .values()
+            .map(SerializedSize::serialized_size_estimate)
+            .sum();
+
+        4 + name_size + properties_size
+    }
+}
+
+/// Computes the number of bytes a single value occupies in a PROP chunk of
+/// the given type. Values that don't match the type are measured as zero,
+/// since they'll be rejected when the chunk is written.
+pub(crate) fn value_byte_size(prop_type: Type, value: &Variant) -> usize {
+    fn rotation_size(rotation: &Matrix3) -> usize {
+        if rotation.to_basic_rotation_id().is_some() {
+            1
+        } else {
+            1 + 36
+        }
+    }
+
+    match (prop_type, value) {
+        (Type::String, _) => 0,
+        (Type::Bool, _) | (Type::Faces, _) | (Type::Axes, _) => 1,
+        (Type::Int32, _) | (Type::Float32, _) | (Type::BrickColor, _) => 4,
+        (Type::Enum, _) | (Type::Ref, _) => 4,
+        (Type::Float64, _) | (Type::UDim, _) | (Type::Vector2, _) => 8,
+        (Type::NumberRange, _) | (Type::Int64, _) => 8,
+        (Type::SecurityCapabilities, _) => 8,
+        (Type::Color3, _) | (Type::Vector3, _) => 12,
+        (Type::UDim2, _) | (Type::Rect, _) | (Type::UniqueId, _) => 16,
+        (Type::Ray, _) => 24,
+        (Type::Vector3int16, _) => 6,
+        (Type::Color3uint8, _) => 3,
+        (Type::Font, Variant::Font(font)) => {
+            4 + font.family.len()
+                + 2
+                + 1
+                + 4
+                + font.cached_face_id.as_ref().map_or(0, |id| id.len())
+        }
+        (Type::CFrame, Variant::CFrame(cframe)) => rotation_size(&cframe.orientation) + 12,
+        (Type::OptionalCFrame, Variant::OptionalCFrame(cframe)) => {
+            let rotation = cframe
+                .as_ref()
+                .map_or(1, |cframe| rotation_size(&cframe.orientation));
+            rotation + 12 + 1
+        }
+        (Type::NumberSequence, Variant::NumberSequence(sequence)) => {
+            4 + sequence.keypoints.len() * 12
+        }
+        (Type::ColorSequence, Variant::ColorSequence(sequence)) => {
+            4 + sequence.keypoints.len() * 20
+        }
+        (Type::PhysicalProperties, Variant::PhysicalProperties(properties)) => match properties {
+            PhysicalProperties::Custom(_) => 1 + 20,
+            _ => 1,
+        },
+        // SharedStrings are stored once in the SSTR chunk, but we attribute
+        // their data to every instance that refers to them, mirroring the
+        // deserializer.
+        (Type::SharedString, Variant::SharedString(shared_string)) => {
+            4 + shared_string.data().len()
+        }
+        (Type::Content, Variant::Content(content)) => match content.value() {
+            ContentType::Uri(uri) => 4 + 4 + uri.len(),
+            ContentType::Object(_) => 4 + 4,
+            _ => 4,
+        },
+        _ => 0,
+    }
+}
@@ -0,0 +1,47 @@
+use std::borrow::Cow;
+
+use rbx_dom_weak::{types::Variant, Instance, Symbol};
+
+/// What a hook registered with
+/// [`Serializer::property_hook`][crate::Serializer::property_hook] does with a
+/// property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyAction {
+    /// Write the property as it is.
+    Keep,
+
+    /// Write this value in place of the property's value.
+    Replace(Variant),
+
+    /// Don't write the property.
+    Drop,
+}
+
+pub(crate) type PropertyHook<'db> =
+    Box<dyn Fn(&Instance, &str, &Variant) -> PropertyAction + Send + Sync + 'db>;
+
+/// Runs every hook registered for the class of `instance` on one of its
+/// properties, in the order they were registered. Returns `None` if any of
+/// them dropped the property.
+pub(crate) fn apply<'a>(
+    hooks: &[(Symbol, PropertyHook<'_>)],
+    instance: &Instance,
+    name: &str,
+    value: &'a Variant,
+) -> Option<Cow<'a, Variant>> {
+    let mut value = Cow::Borrowed(value);
+
+    for (class, hook) in hooks {
+        if *class != instance.class {
+            continue;
+        }
+
+        match hook(instance, name, &value) {
+            PropertyAction::Keep => {}
+            PropertyAction::Replace(new_value) => value = Cow::Owned(new_value),
+            PropertyAction::Drop => return None,
+        }
+    }
+
+    Some(value)
+}
@@ -1,15 +1,31 @@
+mod budget;
+mod dangling;
 mod error;
+mod estimate;
+mod hooks;
 mod state;
+mod validation;
 
-use std::io::Write;
+use std::io::{self, Write};
 
-use rbx_dom_weak::{types::Ref, WeakDom};
+use ahash::HashMap;
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    Instance, Symbol, WeakDom,
+};
 use rbx_reflection::ReflectionDatabase;
 
+use self::{estimate::ByteCounter, hooks::PropertyHook};
+
 pub use self::state::SerializerState;
 
+pub use self::budget::{InstanceSize, SizeLimitExceeded};
+pub use self::dangling::DanglingSharedStrings;
 pub use self::error::Error;
 pub use self::error::InnerError;
+pub use self::estimate::{SerializedSize, SizeEstimate};
+pub use self::hooks::PropertyAction;
+pub use self::validation::{InstanceIssues, ValidationIssue};
 
 /// A configurable serializer for Roblox binary models and places.
 ///
@@ -38,9 +54,14 @@ pub use self::error::InnerError;
 /// By default, the Serializer uses LZ4 compression, mimicking Roblox. This can
 /// be changed via [`compression_type`][compression_type].
 ///
+/// By default, children are written in the order they appear in the dom. They
+/// can instead be sorted, which keeps output stable for version control, via
+/// [`child_order`][child_order].
+///
 /// [ReflectionDatabase]: rbx_reflection::ReflectionDatabase
 /// [reflection_database]: Serializer#method.reflection_database
 /// [compression_type]: Serializer#method.compression_type
+/// [child_order]: Serializer#method.child_order
 //
 // future settings:
 // * recursive: bool = true
@@ -48,6 +69,10 @@ pub use self::error::InnerError;
 pub struct Serializer<'db> {
     database: &'db ReflectionDatabase<'db>,
     compression: CompressionType,
+    child_order: ChildOrder,
+    preserve_binary_referents: bool,
+    max_size: Option<u64>,
+    hooks: Vec<(Symbol, PropertyHook<'db>)>,
 }
 
 impl<'db> Serializer<'db> {
@@ -56,6 +81,10 @@ impl<'db> Serializer<'db> {
         Serializer {
             database: rbx_reflection_database::get().unwrap(),
             compression: CompressionType::default(),
+            child_order: ChildOrder::default(),
+            preserve_binary_referents: false,
+            max_size: None,
+            hooks: Vec::new(),
         }
     }
 
@@ -74,22 +103,379 @@ impl<'db> Serializer<'db> {
         }
     }
 
+    /// Sets what order the serializer will write the children of each
+    /// instance in. The instances passed to the serializer are always written
+    /// in the order they were given.
+    #[inline]
+    pub fn child_order(self, child_order: ChildOrder) -> Self {
+        Self {
+            child_order,
+            ..self
+        }
+    }
+
+    /// Sets whether the serializer will write each instance with the referent
+    /// it had in the binary file it was read from, as returned by
+    /// [`Instance::binary_referent`], instead of numbering instances from 0
+    /// in the order they're written. This is off by default.
+    ///
+    /// Instances that weren't read from a binary file, or whose referent is
+    /// negative or already taken by an instance written before them, are given
+    /// the lowest referents that aren't otherwise used.
+    ///
+    /// ```
+    /// use rbx_binary::Serializer;
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Folder")
+    ///         .with_binary_referent(7)
+    ///         .with_child(InstanceBuilder::new("Part")),
+    /// );
+    ///
+    /// let mut buffer = Vec::new();
+    /// Serializer::new()
+    ///     .preserve_binary_referents(true)
+    ///     .serialize(&mut buffer, &dom, &[dom.root_ref()])?;
+    ///
+    /// let decoded = rbx_binary::from_reader(buffer.as_slice())?;
+    /// let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    /// let part = decoded.get_by_ref(folder.children()[0]).unwrap();
+    /// assert_eq!(folder.binary_referent(), Some(7));
+    /// assert_eq!(part.binary_referent(), Some(0));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[inline]
+    pub fn preserve_binary_referents(self, preserve_binary_referents: bool) -> Self {
+        Self {
+            preserve_binary_referents,
+            ..self
+        }
+    }
+
+    /// Sets the largest number of bytes the serializer may write. If the
+    /// output would be any larger, nothing is written and serializing fails
+    /// with an error whose [`Error::size_limit_exceeded`] lists the instances
+    /// that contributed the most bytes, so they can be looked at or left out.
+    ///
+    /// Checking the size means the whole output is held in memory until it's
+    /// known to fit, instead of being written as it's encoded.
+    ///
+    /// ```
+    /// use rbx_binary::Serializer;
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("StringValue").with_property("Value", "Hello, world!"),
+    /// );
+    ///
+    /// let mut buffer = Vec::new();
+    /// let error = Serializer::new()
+    ///     .max_size(100)
+    ///     .serialize(&mut buffer, &dom, &[dom.root_ref()])
+    ///     .unwrap_err();
+    ///
+    /// let exceeded = error.size_limit_exceeded().unwrap();
+    /// assert_eq!(exceeded.largest[0].full_name, "StringValue");
+    /// assert!(buffer.is_empty());
+    /// ```
+    #[inline]
+    pub fn max_size(self, max_size: u64) -> Self {
+        Self {
+            max_size: Some(max_size),
+            ..self
+        }
+    }
+
+    /// Registers a hook that's called with every property of every instance of
+    /// `class` just before it's written, which can keep the property, replace
+    /// its value, or drop it. Hooks for the same class run in the order they
+    /// were registered, each seeing the value left by the last.
+    ///
+    /// This lets properties be changed on the way out, like clamping values
+    /// or leaving out properties that only matter at runtime, without copying
+    /// and changing the dom first. Hooks may be called more than once for the
+    /// same property, so they should always return the same thing for it.
+    /// They aren't called for `Name`, or for properties an instance doesn't
+    /// have. If a property is dropped from some instances of a class but not
+    /// others, the ones it was dropped from are written with its default
+    /// value.
+    ///
+    /// ```
+    /// use rbx_binary::{PropertyAction, Serializer};
+    /// use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Part")
+    ///         .with_property("Transparency", 2.0f32)
+    ///         .with_property("RuntimeOnly", true),
+    /// );
+    ///
+    /// let serializer = Serializer::new().property_hook("Part", |_, name, value| {
+    ///     match (name, value) {
+    ///         ("Transparency", Variant::Float32(value)) => {
+    ///             PropertyAction::Replace(Variant::Float32(value.clamp(0.0, 1.0)))
+    ///         }
+    ///         ("RuntimeOnly", _) => PropertyAction::Drop,
+    ///         _ => PropertyAction::Keep,
+    ///     }
+    /// });
+    ///
+    /// let mut buffer = Vec::new();
+    /// serializer.serialize(&mut buffer, &dom, &[dom.root_ref()])?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn property_hook<S, F>(mut self, class: S, hook: F) -> Self
+    where
+        S: Into<Symbol>,
+        F: Fn(&Instance, &str, &Variant) -> PropertyAction + Send + Sync + 'db,
+    {
+        self.hooks.push((class.into(), Box::new(hook)));
+        self
+    }
+
+    /// Checks every instance in `refs` and their descendants against this
+    /// serializer's reflection database, returning every instance with a
+    /// property of the wrong type, an unknown class or property, or an enum
+    /// value that isn't an item of its enum.
+    ///
+    /// Unlike [`serialize`][Serializer::serialize], which stops at the first
+    /// value it can't write, this reports every problem at once. Referents
+    /// that aren't in `dom` are skipped.
+    ///
+    /// ```
+    /// use rbx_binary::{Serializer, ValidationIssue};
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Part").with_property("Anchored", 1i32));
+    ///
+    /// for instance in Serializer::new().validate_against_database(&dom, &[dom.root_ref()]) {
+    ///     for issue in &instance.issues {
+    ///         println!("{}: {}", instance.full_name, issue);
+    ///     }
+    /// }
+    /// ```
+    pub fn validate_against_database(&self, dom: &WeakDom, refs: &[Ref]) -> Vec<InstanceIssues> {
+        validation::validate(self.database, dom, refs)
+    }
+
     /// Serialize a Roblox binary model or place into the given stream using
     /// this serializer.
     pub fn serialize<W: Write>(&self, writer: W, dom: &WeakDom, refs: &[Ref]) -> Result<(), Error> {
+        self.serialize_with_byte_sizes(writer, dom, refs)?;
+
+        Ok(())
+    }
+
+    /// Serialize a Roblox binary model or place into the given stream using
+    /// this serializer, returning the number of bytes each serialized instance
+    /// contributed to the output.
+    ///
+    /// Sizes are measured the same way as
+    /// [`WeakDom::instance_byte_sizes`][rbx_dom_weak::WeakDom] is populated
+    /// when deserializing, so the two can be compared directly to find size
+    /// changes introduced between reading and writing a file.
+    pub fn serialize_with_byte_sizes<W: Write>(
+        &self,
+        mut writer: W,
+        dom: &WeakDom,
+        refs: &[Ref],
+    ) -> Result<HashMap<Ref, usize>, Error> {
         profiling::scope!("rbx_binary::seserialize");
 
-        let mut serializer = SerializerState::new(self, dom, writer);
+        if let Some(max_size) = self.max_size {
+            let (output, byte_sizes) = self.serialize_within(max_size, dom, refs)?;
+            writer.write_all(&output).map_err(InnerError::from)?;
+
+            return Ok(byte_sizes);
+        }
+
+        let serializer = self.serialize_state(writer, dom, refs)?;
+
+        Ok(serializer.into_instance_byte_sizes())
+    }
+
+    /// Computes the size of the Roblox binary model or place that this
+    /// serializer would write, without keeping any of the output.
+    ///
+    /// Every chunk is still encoded and compressed, so the result is exact,
+    /// but only one chunk is held in memory at a time. This can be used to
+    /// check a file against a size limit before uploading it, or to compare
+    /// how well different [`CompressionType`]s do on a particular dom.
+    ///
+    /// ```
+    /// use rbx_binary::{CompressionType, Serializer};
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Folder"));
+    ///
+    /// for compression in [CompressionType::Lz4, CompressionType::None] {
+    ///     let estimate = Serializer::new()
+    ///         .compression_type(compression)
+    ///         .estimate_size(&dom, &[dom.root_ref()])?;
+    ///
+    ///     println!("{:?}: {} bytes", compression, estimate.len);
+    /// }
+    /// # Ok::<(), rbx_binary::EncodeError>(())
+    /// ```
+    pub fn estimate_size(&self, dom: &WeakDom, refs: &[Ref]) -> Result<SizeEstimate, Error> {
+        profiling::scope!("rbx_binary::estimate_size");
 
+        let mut counter = ByteCounter::default();
+        let serializer = self.serialize_state(&mut counter, dom, refs)?;
+        let chunks = serializer.chunks().to_vec();
+
+        Ok(SizeEstimate {
+            len: counter.len,
+            chunks,
+        })
+    }
+
+    /// Returns the SharedStrings in `dom` that a file containing `refs` would
+    /// not refer to, and that [`serialize`][Serializer::serialize] leaves out
+    /// of it, along with how many bytes that saves. See
+    /// [`DanglingSharedStrings`].
+    ///
+    /// Only the instances being serialized are looked at, and nothing is
+    /// encoded, so this is much cheaper than serializing the dom.
+    ///
+    /// ```
+    /// use rbx_binary::Serializer;
+    /// use rbx_dom_weak::{types::SharedString, InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Model")
+    ///         .with_property("ModelMeshData", SharedString::new(b"mesh".to_vec())),
+    /// );
+    ///
+    /// let dangling = Serializer::new().dangling_shared_strings(&dom, &[dom.root_ref()])?;
+    /// println!("Reclaimed {} bytes", dangling.reclaimed_bytes);
+    /// # Ok::<(), rbx_binary::EncodeError>(())
+    /// ```
+    pub fn dangling_shared_strings(
+        &self,
+        dom: &WeakDom,
+        refs: &[Ref],
+    ) -> Result<DanglingSharedStrings, Error> {
+        let mut serializer = SerializerState::new(self, dom, io::sink());
         serializer.add_instances(refs)?;
-        serializer.generate_referents();
-        serializer.write_header()?;
-        serializer.serialize_metadata()?;
-        serializer.serialize_shared_strings()?;
-        serializer.serialize_instances()?;
+
+        Ok(serializer.dangling_shared_strings().clone())
+    }
+
+    /// Returns the value of a property after running the hooks registered
+    /// for the instance's class, or `None` if a hook dropped it.
+    pub(crate) fn hooked_property<'a>(
+        &self,
+        instance: &Instance,
+        name: &str,
+        value: &'a Variant,
+    ) -> Option<std::borrow::Cow<'a, Variant>> {
+        hooks::apply(&self.hooks, instance, name, value)
+    }
+
+    /// Serializes a Roblox binary model or place into memory, failing if it's
+    /// larger than `max_size` bytes.
+    fn serialize_within(
+        &self,
+        max_size: u64,
+        dom: &WeakDom,
+        refs: &[Ref],
+    ) -> Result<(Vec<u8>, HashMap<Ref, usize>), InnerError> {
+        let mut output = Vec::new();
+        let serializer = self.serialize_state(&mut output, dom, refs)?;
+        let byte_sizes = serializer.into_instance_byte_sizes();
+
+        let len = output.len() as u64;
+        if len > max_size {
+            return Err(InnerError::SizeLimitExceeded {
+                exceeded: SizeLimitExceeded::new(dom, max_size, len, &byte_sizes),
+            });
+        }
+
+        Ok((output, byte_sizes))
+    }
+
+    /// Writes every chunk of a Roblox binary model or place into `writer`,
+    /// returning the state used to do so.
+    fn serialize_state<'a, W: Write>(
+        &'a self,
+        writer: W,
+        dom: &'a WeakDom,
+        refs: &[Ref],
+    ) -> Result<SerializerState<'a, 'a, W>, InnerError> {
+        trace_span!("rbx_binary::serialize", roots = refs.len());
+
+        let mut serializer = SerializerState::new(self, dom, writer);
+
+        serializer.serialize_start(refs)?;
         serializer.serialize_properties()?;
-        serializer.serialize_parents()?;
-        serializer.serialize_end()?;
+        serializer.serialize_finish()?;
+
+        Ok(serializer)
+    }
+
+    /// Serialize a Roblox binary model or place into the given async stream
+    /// using this serializer.
+    ///
+    /// Each chunk is written to `writer` as soon as it's been encoded, so the
+    /// whole file is never held in memory at once, unless a
+    /// [`max_size`][Serializer::max_size] is set. The task yields to the
+    /// executor after each PROP chunk so that large files don't starve other
+    /// tasks.
+    #[cfg(feature = "tokio")]
+    pub async fn serialize_async<W>(
+        &self,
+        mut writer: W,
+        dom: &WeakDom,
+        refs: &[Ref],
+    ) -> Result<(), Error>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        async fn flush<W>(
+            serializer: &mut SerializerState<'_, '_, Vec<u8>>,
+            writer: &mut W,
+        ) -> Result<(), InnerError>
+        where
+            W: tokio::io::AsyncWrite + Unpin,
+        {
+            let output = serializer.output_mut();
+            writer.write_all(output).await?;
+            output.clear();
+
+            Ok(())
+        }
+
+        if let Some(max_size) = self.max_size {
+            let (output, _) = self.serialize_within(max_size, dom, refs)?;
+            writer.write_all(&output).await.map_err(InnerError::from)?;
+            writer.flush().await.map_err(InnerError::from)?;
+
+            return Ok(());
+        }
+
+        // The span is only entered while chunks are being encoded, since an
+        // entered span can't be held across an await.
+        let span = file_span!("rbx_binary::serialize", roots = refs.len());
+        let mut serializer = SerializerState::new(self, dom, Vec::new());
+
+        span.in_scope(|| serializer.serialize_start(refs))?;
+        flush(&mut serializer, &mut writer).await?;
+
+        for (type_name, prop_name) in serializer.property_chunks() {
+            span.in_scope(|| serializer.serialize_property(&type_name, &prop_name))?;
+            flush(&mut serializer, &mut writer).await?;
+
+            tokio::task::yield_now().await;
+        }
+
+        span.in_scope(|| serializer.serialize_finish())?;
+        flush(&mut serializer, &mut writer).await?;
+
+        writer.flush().await.map_err(InnerError::from)?;
 
         Ok(())
     }
@@ -112,3 +498,17 @@ pub enum CompressionType {
     /// ZSTD compression.
     Zstd,
 }
+
+/// Indicates the order that the children of each instance are written in.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ChildOrder {
+    /// Children are written in the order they appear in the dom.
+    #[default]
+    Dom,
+    /// Children are sorted by name. Children with the same name are sorted by
+    /// class name, and then keep the order they appear in the dom.
+    Name,
+    /// Children are sorted by class name. Children with the same class are
+    /// sorted by name, and then keep the order they appear in the dom.
+    Class,
+}
@@ -1,12 +1,13 @@
 use std::{
     borrow::{Borrow, Cow},
-    collections::{btree_map, BTreeMap},
+    collections::BTreeMap,
     convert::TryInto,
     io::Write,
 };
 
-use ahash::{HashMap, HashMapExt, HashSetExt};
+use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use rbx_dom_weak::{
+    is_identity_property,
     types::{
         Attributes, Axes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence,
         ColorSequenceKeypoint, Content, ContentId, ContentType, Enum, EnumItem, Faces, Font,
@@ -14,7 +15,7 @@ use rbx_dom_weak::{
         PhysicalProperties, Ray, Rect, Ref, SecurityCapabilities, SharedString, Tags, UDim, UDim2,
         UniqueId, Variant, VariantType, Vector2, Vector3, Vector3int16,
     },
-    Instance, Ustr, UstrSet, WeakDom,
+    Instance, Symbol, SymbolSet, WeakDom,
 };
 
 use rbx_reflection::{
@@ -27,12 +28,15 @@ use crate::{
     core::{
         find_property_descriptors, RbxWriteExt, FILE_MAGIC_HEADER, FILE_SIGNATURE, FILE_VERSION,
     },
+    deserializer::ChunkInfo,
     types::Type,
     Serializer,
 };
 
+use super::dangling::DanglingSharedStrings;
 use super::error::InnerError;
-use super::CompressionType;
+use super::estimate::value_byte_size;
+use super::{ChildOrder, CompressionType};
 
 static FILE_FOOTER: &[u8] = b"</roblox>";
 
@@ -67,6 +71,19 @@ pub struct SerializerState<'dom, 'db, W> {
     /// A map of SharedStrings to where it is in the SSTR chunk. This is used
     /// for writing PROP chunks.
     pub shared_string_ids: HashMap<SharedString, u32>,
+
+    /// The SharedStrings that were found in the DOM but that no PROP chunk
+    /// refers to, and so were left out of `shared_strings`.
+    dangling_shared_strings: DanglingSharedStrings,
+
+    /// The number of bytes each instance contributed to the output, keyed by
+    /// the instance's referent. This is measured the same way the deserializer
+    /// measures its input: the uncompressed bytes of each instance's entry in
+    /// its INST chunk and of its values in every PROP chunk.
+    instance_byte_sizes: HashMap<Ref, usize>,
+
+    /// Every chunk that has been written to the output so far, in order.
+    chunks: Vec<ChunkInfo>,
 }
 
 /// An instance class that our serializer knows about. We should have one struct
@@ -90,7 +107,7 @@ struct TypeInfo<'dom, 'db> {
     ///
     /// Stored in a sorted map to try to ensure that we write out properties in
     /// a deterministic order.
-    properties: BTreeMap<Ustr, PropInfo<'db>>,
+    properties: BTreeMap<Symbol, PropInfo<'db>>,
 
     /// A reference to the type's class descriptor from rbx_reflection, if this
     /// is a known class.
@@ -99,7 +116,7 @@ struct TypeInfo<'dom, 'db> {
     /// A set containing the properties that we have seen so far in the file and
     /// processed. This helps us avoid traversing the reflection database
     /// multiple times if there are many copies of the same kind of instance.
-    properties_visited: UstrSet,
+    properties_visited: SymbolSet,
 }
 
 /// A property on a specific class that our serializer knows about.
@@ -122,14 +139,14 @@ struct PropInfo<'db> {
     /// The serialized name for this property. This is the name that is actually
     /// written as part of the PROP chunk and may not line up with the canonical
     /// name for the property.
-    serialized_name: Ustr,
+    serialized_name: Symbol,
 
     /// A set containing the names of all aliases discovered while preparing to
     /// serialize this property. Ideally, this set will remain empty (and not
     /// allocate) in most cases. However, if an instance is missing a property
     /// from its canonical name, but does have another variant, we can use this
     /// set to recover and map those values.
-    aliases: UstrSet,
+    aliases: SymbolSet,
 
     /// The default value for this property that should be used if any instances
     /// are missing this property.
@@ -160,7 +177,7 @@ struct TypeInfos<'dom, 'db> {
     ///
     /// These are stored sorted so that we naturally iterate over them in order
     /// and improve our chances of being deterministic.
-    values: BTreeMap<Ustr, TypeInfo<'dom, 'db>>,
+    values: BTreeMap<Symbol, TypeInfo<'dom, 'db>>,
 
     /// The next type ID that should be assigned if a type is discovered and
     /// added to the serializer.
@@ -178,8 +195,11 @@ impl<'dom, 'db> TypeInfos<'dom, 'db> {
 
     /// Finds the type info from the given ClassName if it exists, or creates
     /// one and returns a reference to it if not.
-    fn get_or_create(&mut self, class: Ustr) -> &mut TypeInfo<'dom, 'db> {
-        if let btree_map::Entry::Vacant(entry) = self.values.entry(class) {
+    // Using the entry API here would clone the class name for every
+    // instance, rather than once for each class.
+    #[allow(clippy::map_entry)]
+    fn get_or_create(&mut self, class: &Symbol) -> &mut TypeInfo<'dom, 'db> {
+        if !self.values.contains_key(class) {
             let type_id = self.next_type_id;
             self.next_type_id += 1;
 
@@ -206,25 +226,28 @@ impl<'dom, 'db> TypeInfos<'dom, 'db> {
                 PropInfo {
                     prop_type: Type::String,
                     serialized_name: "Name".into(),
-                    aliases: UstrSet::new(),
+                    aliases: SymbolSet::new(),
                     default_value: Cow::Owned(Variant::String(String::new())),
                     migration: None,
                 },
             );
 
-            entry.insert(TypeInfo {
-                type_id,
-                is_service,
-                instances: Vec::new(),
-                properties,
-                class_descriptor,
-                properties_visited: UstrSet::new(),
-            });
+            self.values.insert(
+                class.clone(),
+                TypeInfo {
+                    type_id,
+                    is_service,
+                    instances: Vec::new(),
+                    properties,
+                    class_descriptor,
+                    properties_visited: SymbolSet::new(),
+                },
+            );
         }
 
         // This unwrap will not panic because we always insert this key into
         // type_infos in this function.
-        self.values.get_mut(&class).unwrap()
+        self.values.get_mut(class).unwrap()
     }
 }
 
@@ -240,7 +263,93 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
             type_infos: TypeInfos::new(serializer.database),
             shared_strings: Vec::new(),
             shared_string_ids: HashMap::new(),
+            dangling_shared_strings: DanglingSharedStrings::default(),
+            instance_byte_sizes: HashMap::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Returns the number of bytes each instance has contributed to the output
+    /// so far, keyed by the instance's referent.
+    pub fn instance_byte_sizes(&self) -> &HashMap<Ref, usize> {
+        &self.instance_byte_sizes
+    }
+
+    /// Returns the output that the serialized file is being written to.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn output_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+
+    /// Returns every chunk that has been written to the output so far.
+    pub(crate) fn chunks(&self) -> &[ChunkInfo] {
+        &self.chunks
+    }
+
+    /// Consumes the `SerializerState`, returning the number of bytes each
+    /// instance contributed to the output.
+    pub fn into_instance_byte_sizes(self) -> HashMap<Ref, usize> {
+        self.instance_byte_sizes
+    }
+
+    /// Returns the children of `instance` in the order the serializer's
+    /// [`ChildOrder`] says they should be written in.
+    fn ordered_children(&self, instance: &'dom Instance) -> Cow<'dom, [Ref]> {
+        if self.serializer.child_order == ChildOrder::Dom {
+            return Cow::Borrowed(instance.children());
+        }
+
+        let dom = self.dom;
+        let mut children = instance.children().to_vec();
+
+        // Children that don't exist are sorted first, and are reported as
+        // invalid once they're visited.
+        match self.serializer.child_order {
+            ChildOrder::Dom => {}
+            ChildOrder::Name => children.sort_by_key(|child| {
+                dom.get_by_ref(*child)
+                    .map(|child| (child.name.as_str(), child.class.as_str()))
+            }),
+            ChildOrder::Class => children.sort_by_key(|child| {
+                dom.get_by_ref(*child)
+                    .map(|child| (child.class.as_str(), child.name.as_str()))
+            }),
         }
+
+        Cow::Owned(children)
+    }
+
+    /// Returns the given instance IDs that aren't already included by one of
+    /// their ancestors also being given, so that every selected instance is
+    /// only serialized once. Any instance can be selected, not only children of
+    /// the root; its ancestors are left out of the file, and the instance is
+    /// written without a parent.
+    fn selected_roots(&self, referents: &[Ref]) -> Vec<Ref> {
+        let selected: HashSet<Ref> = referents.iter().copied().collect();
+        let mut seen = HashSet::with_capacity(referents.len());
+
+        referents
+            .iter()
+            .copied()
+            .filter(|referent| {
+                let mut ancestor = self
+                    .dom
+                    .get_by_ref(*referent)
+                    .map(|instance| instance.parent());
+
+                while let Some(parent) = ancestor.filter(|parent| parent.is_some()) {
+                    if selected.contains(&parent) {
+                        return false;
+                    }
+                    ancestor = self
+                        .dom
+                        .get_by_ref(parent)
+                        .map(|instance| instance.parent());
+                }
+
+                seen.insert(*referent)
+            })
+            .collect()
     }
 
     /// Mark the given instance IDs and all of their descendants as intended for
@@ -263,7 +372,7 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
         let mut to_visit = Vec::new();
         let mut last_visited_child = None;
 
-        to_visit.extend(referents.iter().rev());
+        to_visit.extend(self.selected_roots(referents).iter().rev());
 
         while let Some(referent) = to_visit.last() {
             let instance = self
@@ -273,7 +382,7 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
                     referent: *referent,
                 })?;
 
-            to_visit.extend(instance.children().iter().rev());
+            to_visit.extend(self.ordered_children(instance).iter().rev());
 
             while let Some(referent) = to_visit.last() {
                 let instance =
@@ -284,7 +393,7 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
                         })?;
 
                 if !instance.children().is_empty()
-                    && instance.children().last() != last_visited_child.as_ref()
+                    && self.ordered_children(instance).last() != last_visited_child.as_ref()
                 {
                     break;
                 }
@@ -298,6 +407,7 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
         // Sort shared_strings by their hash, to ensure they are deterministically added
         // into the SSTR chunk, then assign them corresponding ids
         self.shared_strings.sort_by_key(SharedString::hash);
+        self.drop_dangling_shared_strings();
         for (id, shared_string) in self.shared_strings.iter().cloned().enumerate() {
             self.shared_string_ids.insert(shared_string, id as u32);
         }
@@ -310,6 +420,59 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
         Ok(())
     }
 
+    /// Removes every SharedString from `shared_strings` that no PROP chunk
+    /// will refer to, recording them in `dangling_shared_strings`.
+    ///
+    /// SharedStrings are collected from every property as instances are
+    /// visited, including properties that turn out not to be serialized, and
+    /// the default value of each SharedString property is collected in case
+    /// an instance is missing it, so some of them may not end up being used.
+    fn drop_dangling_shared_strings(&mut self) {
+        let mut referenced = HashSet::new();
+
+        for type_info in self.type_infos.values.values() {
+            for (prop_name, prop_info) in &type_info.properties {
+                if prop_info.prop_type != Type::SharedString {
+                    continue;
+                }
+
+                let values =
+                    Self::property_values(self.serializer, type_info, prop_name, prop_info);
+                for value in values {
+                    if let Variant::SharedString(shared_string) = value.as_ref() {
+                        referenced.insert(shared_string.hash());
+                    }
+                }
+            }
+        }
+
+        let dangling = &mut self.dangling_shared_strings;
+        let shared_string_ids = &mut self.shared_string_ids;
+        self.shared_strings.retain(|shared_string| {
+            if referenced.contains(&shared_string.hash()) {
+                return true;
+            }
+
+            dangling.add(shared_string);
+            shared_string_ids.remove(shared_string);
+            false
+        });
+
+        if !dangling.dropped.is_empty() {
+            log::debug!(
+                "Dropped {} SharedStrings that nothing refers to, saving {} bytes",
+                dangling.dropped.len(),
+                dangling.reclaimed_bytes
+            );
+        }
+    }
+
+    /// Returns the SharedStrings that were found in the DOM but that no PROP
+    /// chunk refers to. See [`DanglingSharedStrings`].
+    pub fn dangling_shared_strings(&self) -> &DanglingSharedStrings {
+        &self.dangling_shared_strings
+    }
+
     /// Collect information about all the different types of instance and their
     /// properties.
     // Using the entry API here, as Clippy suggests, would require us to
@@ -317,12 +480,26 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
     #[allow(clippy::map_entry)]
     #[profiling::function]
     pub fn collect_type_info(&mut self, instance: &'dom Instance) -> Result<(), InnerError> {
-        let type_info = self.type_infos.get_or_create(instance.class);
+        let type_info = self.type_infos.get_or_create(&instance.class);
         type_info.instances.push(instance);
 
         for (prop_name, prop_value) in &instance.properties {
+            // Copies of the instance's name and class exposed as properties
+            // are never written; the fields themselves are used instead.
+            if is_identity_property(prop_name) {
+                continue;
+            }
+
+            let prop_value = match self
+                .serializer
+                .hooked_property(instance, prop_name, prop_value)
+            {
+                Some(prop_value) => prop_value,
+                None => continue,
+            };
+
             // Discover and track any shared strings we come across.
-            if let Variant::SharedString(shared_string) = prop_value {
+            if let Variant::SharedString(shared_string) = prop_value.as_ref() {
                 if !self.shared_string_ids.contains_key(shared_string) {
                     // We insert it with a dummy id of 0 so that we can check for contains_key.
                     // The actual id is set in `add_instances`
@@ -338,7 +515,7 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
 
             // ...but add it to the set of visited properties if we haven't seen
             // it.
-            type_info.properties_visited.insert(*prop_name);
+            type_info.properties_visited.insert(prop_name.clone());
 
             let canonical_name;
             let serialized_name;
@@ -346,7 +523,7 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
             let mut migration = None;
 
             let database = self.serializer.database;
-            match find_property_descriptors(database, instance.class, *prop_name) {
+            match find_property_descriptors(database, &instance.class, prop_name) {
                 Some(descriptors) => {
                     // For any properties that do not serialize, we can skip
                     // adding them to the set of type_infos.
@@ -363,8 +540,8 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
                                 // serialize
                                 let new_descriptors = find_property_descriptors(
                                     database,
-                                    instance.class,
-                                    prop_migration.new_property_name.as_str().into(),
+                                    &instance.class,
+                                    &prop_migration.new_property_name,
                                 );
 
                                 migration = Some(prop_migration);
@@ -407,8 +584,8 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
                 }
 
                 None => {
-                    canonical_name = *prop_name;
-                    serialized_name = *prop_name;
+                    canonical_name = prop_name.clone();
+                    serialized_name = prop_name.clone();
                     serialized_ty = prop_value.ty();
                 }
             }
@@ -454,11 +631,11 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
                 })?;
 
                 type_info.properties.insert(
-                    canonical_name,
+                    canonical_name.clone(),
                     PropInfo {
                         prop_type: ser_type,
                         serialized_name,
-                        aliases: UstrSet::new(),
+                        aliases: SymbolSet::new(),
                         default_value,
                         migration,
                     },
@@ -472,7 +649,7 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
                 let prop_info = type_info.properties.get_mut(&canonical_name).unwrap();
 
                 if !prop_info.aliases.contains(prop_name) {
-                    prop_info.aliases.insert(*prop_name);
+                    prop_info.aliases.insert(prop_name.clone());
                 }
 
                 prop_info.migration = migration;
@@ -488,9 +665,40 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
     pub fn generate_referents(&mut self) {
         self.id_to_referent.reserve(self.relevant_instances.len());
 
-        for (next_referent, id) in self.relevant_instances.iter().enumerate() {
-            self.id_to_referent
-                .insert(*id, next_referent.try_into().unwrap());
+        if !self.serializer.preserve_binary_referents {
+            for (next_referent, id) in self.relevant_instances.iter().enumerate() {
+                self.id_to_referent
+                    .insert(*id, next_referent.try_into().unwrap());
+            }
+
+            log::debug!("Collected {} referents", self.id_to_referent.len());
+            return;
+        }
+
+        // Instances keep their old referents first, so that an instance
+        // without one can't take the referent of an instance written after it.
+        let mut used = HashSet::new();
+        let mut unassigned = Vec::new();
+
+        for id in &self.relevant_instances {
+            let binary_referent = self.dom.get_by_ref(*id).unwrap().binary_referent();
+
+            match binary_referent {
+                Some(referent) if referent >= 0 && used.insert(referent) => {
+                    self.id_to_referent.insert(*id, referent);
+                }
+                _ => unassigned.push(*id),
+            }
+        }
+
+        let mut next_referent = 0;
+        for id in unassigned {
+            while used.contains(&next_referent) {
+                next_referent += 1;
+            }
+
+            self.id_to_referent.insert(id, next_referent);
+            next_referent += 1;
         }
 
         log::debug!("Collected {} referents", self.id_to_referent.len());
@@ -515,8 +723,24 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
 
     /// Write out any metadata about this file, stored in a chunk named META.
     pub fn serialize_metadata(&mut self) -> Result<(), InnerError> {
-        log::trace!("Writing metadata (currently no-op)");
-        // TODO: There is no concept of metadata in a dom yet.
+        log::trace!("Writing metadata");
+
+        let metadata = self.dom.metadata();
+        if metadata.is_empty() {
+            return Ok(());
+        }
+
+        let mut chunk = ChunkBuilder::new(b"META", self.serializer.compression);
+
+        chunk.write_le_u32(metadata.len() as u32)?;
+
+        for (key, value) in metadata.iter() {
+            chunk.write_string(key)?;
+            chunk.write_string(value)?;
+        }
+
+        self.chunks.push(chunk.dump(&mut self.output)?);
+
         Ok(())
     }
 
@@ -541,7 +765,7 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
             chunk.write_binary_string(shared_string.data())?;
         }
 
-        chunk.dump(&mut self.output)?;
+        self.chunks.push(chunk.dump(&mut self.output)?);
 
         Ok(())
     }
@@ -558,6 +782,12 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
                 type_name,
                 type_info.instances.len()
             );
+            trace_span!(
+                "class",
+                class = %type_name,
+                type_id = type_info.type_id,
+                instances = type_info.instances.len(),
+            );
 
             let mut chunk = ChunkBuilder::new(b"INST", self.serializer.compression);
 
@@ -581,6 +811,16 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
                     .map(|instance| self.id_to_referent[&instance.referent()]),
             )?;
 
+            // Each instance costs one referent, plus the service marker written
+            // below if this type is a service.
+            let instance_size = if type_info.is_service { 5 } else { 4 };
+            for instance in &type_info.instances {
+                *self
+                    .instance_byte_sizes
+                    .entry(instance.referent())
+                    .or_default() += instance_size;
+            }
+
             if type_info.is_service {
                 // It's unclear what this byte is used for, but when the type is
                 // a service (like Workspace, Lighting, etc), we need to write
@@ -594,12 +834,29 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
                 }
             }
 
-            chunk.dump(&mut self.output)?;
+            self.chunks.push(chunk.dump(&mut self.output)?);
         }
 
         Ok(())
     }
 
+    /// Adds the instances in `referents` and everything they depend on, then
+    /// writes every chunk that comes before the PROP chunks.
+    pub fn serialize_start(&mut self, referents: &[Ref]) -> Result<(), InnerError> {
+        self.add_instances(referents)?;
+        self.generate_referents();
+        self.write_header()?;
+        self.serialize_metadata()?;
+        self.serialize_shared_strings()?;
+        self.serialize_instances()
+    }
+
+    /// Writes every chunk that comes after the PROP chunks.
+    pub fn serialize_finish(&mut self) -> Result<(), InnerError> {
+        self.serialize_parents()?;
+        self.serialize_end()
+    }
+
     /// Write out batch declarations of property values for the instances
     /// previously defined in the INST chunks. Property data is contained in
     /// chunks named PROP.
@@ -607,692 +864,765 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
     pub fn serialize_properties(&mut self) -> Result<(), InnerError> {
         log::trace!("Writing properties");
 
-        for (type_name, type_info) in &self.type_infos.values {
-            for (prop_name, prop_info) in &type_info.properties {
-                profiling::scope!("serialize property", prop_name.borrow());
-                log::trace!(
-                    "Writing property {}.{} (type {:?})",
-                    type_name,
-                    prop_name,
-                    prop_info.prop_type
-                );
+        for (type_name, prop_name) in self.property_chunks() {
+            self.serialize_property(&type_name, &prop_name)?;
+        }
 
-                let mut chunk = ChunkBuilder::new(b"PROP", self.serializer.compression);
+        Ok(())
+    }
 
-                chunk.write_le_u32(type_info.type_id)?;
-                chunk.write_string(&prop_info.serialized_name)?;
-                chunk.write_u8(prop_info.prop_type as u8)?;
+    /// Returns the class and property name of every PROP chunk written by
+    /// `serialize_properties`, in the order they're written.
+    pub(crate) fn property_chunks(&self) -> Vec<(Symbol, Symbol)> {
+        self.type_infos
+            .values
+            .iter()
+            .flat_map(|(type_name, type_info)| {
+                type_info
+                    .properties
+                    .keys()
+                    .map(move |prop_name| (type_name.clone(), prop_name.clone()))
+            })
+            .collect()
+    }
 
-                let values = type_info
-                    .instances
+    /// Writes the PROP chunk for a single property of a single class.
+    pub(crate) fn serialize_property(
+        &mut self,
+        type_name: &Symbol,
+        prop_name: &Symbol,
+    ) -> Result<(), InnerError> {
+        let type_info = &self.type_infos.values[type_name];
+        let prop_info = &type_info.properties[prop_name];
+
+        profiling::scope!("serialize property", prop_name.borrow());
+        trace_span!("property", class = %type_name, property = %prop_name);
+        log::trace!(
+            "Writing property {}.{} (type {:?})",
+            type_name,
+            prop_name,
+            prop_info.prop_type
+        );
+
+        let mut chunk = ChunkBuilder::new(b"PROP", self.serializer.compression);
+
+        chunk.write_le_u32(type_info.type_id)?;
+        chunk.write_string(&prop_info.serialized_name)?;
+        chunk.write_u8(prop_info.prop_type as u8)?;
+
+        let values = Self::property_values(self.serializer, type_info, prop_name, prop_info);
+
+        // String values are measured as they're written, since some of
+        // them are only encoded at that point. Everything else can be
+        // measured up front.
+        let mut value_sizes = Vec::with_capacity(values.len());
+        if prop_info.prop_type != Type::String {
+            value_sizes.extend(
+                values
                     .iter()
-                    .map(|instance| {
-                        // We store the Name property in a different field for
-                        // convenience, but when serializing to the binary model
-                        // format we need to handle it just like other properties.
-                        if *prop_name == "Name" {
-                            return Cow::Owned(Variant::String(instance.name.clone()));
-                        }
+                    .map(|value| value_byte_size(prop_info.prop_type, value.as_ref())),
+            );
+        }
 
-                        // Most properties will be stored on instances using the
-                        // property's canonical name, so we'll try that first.
-                        if let Some(property) = instance.properties.get(prop_name) {
-                            return Cow::Borrowed(property);
+        let values = values.into_iter().enumerate();
+
+        // Helper to generate a type mismatch error with context from
+        // this chunk.
+        let type_mismatch = |i: usize, bad_value: &Variant, valid_type_names: &'static str| {
+            Err(InnerError::PropTypeMismatch {
+                type_name: type_name.to_string(),
+                prop_name: prop_name.to_string(),
+                valid_type_names,
+                actual_type_name: format!("{:?}", bad_value.ty()),
+                instance_full_name: self.dom.full_name_of(type_info.instances[i].referent()),
+            })
+        };
+
+        let invalid_value = |i: usize, bad_value: &Variant| InnerError::InvalidPropValue {
+            instance_full_name: self.dom.full_name_of(type_info.instances[i].referent()),
+            type_name: type_name.to_string(),
+            prop_name: prop_name.to_string(),
+            prop_type: format!("{:?}", bad_value.ty()),
+        };
+
+        match prop_info.prop_type {
+            Type::String => {
+                for (i, rbx_value) in values {
+                    match rbx_value.as_ref() {
+                        Variant::String(value) => {
+                            chunk.write_string(value)?;
+                            value_sizes.push(4 + value.len());
                         }
-
-                        // If there were any known aliases for this property
-                        // used as part of this file, we can check those next.
-                        for alias in &prop_info.aliases {
-                            if let Some(property) = instance.properties.get(alias) {
-                                return Cow::Borrowed(property);
-                            }
+                        Variant::ContentId(value) => {
+                            chunk.write_string(value.as_ref())?;
+                            value_sizes.push(4 + value.as_str().len());
                         }
-
-                        // Finally, we can fall back to the default value we
-                        // computed for this PropInfo. This is sourced from the
-                        // reflection database if available, or falls back to a
-                        // reasonable default.
-                        Cow::Borrowed(prop_info.default_value.borrow())
-                    })
-                    .map(|value| {
-                        if let Some(migration) = prop_info.migration {
-                            match migration.perform(&value) {
-                                Ok(new_value) => Cow::Owned(new_value),
-                                Err(_) => value,
-                            }
-                        } else {
-                            value
+                        Variant::BinaryString(value) => {
+                            let buf: &[u8] = value.as_ref();
+                            chunk.write_binary_string(buf)?;
+                            value_sizes.push(4 + buf.len());
                         }
-                    })
-                    .enumerate();
-
-                // Helper to generate a type mismatch error with context from
-                // this chunk.
-                let type_mismatch =
-                    |i: usize, bad_value: &Variant, valid_type_names: &'static str| {
-                        Err(InnerError::PropTypeMismatch {
-                            type_name: type_name.to_string(),
-                            prop_name: prop_name.to_string(),
-                            valid_type_names,
-                            actual_type_name: format!("{:?}", bad_value.ty()),
-                            instance_full_name: self
-                                .full_name_for(type_info.instances[i].referent()),
-                        })
-                    };
-
-                let invalid_value = |i: usize, bad_value: &Variant| InnerError::InvalidPropValue {
-                    instance_full_name: self.full_name_for(type_info.instances[i].referent()),
-                    type_name: type_name.to_string(),
-                    prop_name: prop_name.to_string(),
-                    prop_type: format!("{:?}", bad_value.ty()),
-                };
-
-                match prop_info.prop_type {
-                    Type::String => {
-                        for (i, rbx_value) in values {
-                            match rbx_value.as_ref() {
-                                Variant::String(value) => {
-                                    chunk.write_string(value)?;
-                                }
-                                Variant::ContentId(value) => {
-                                    chunk.write_string(value.as_ref())?;
-                                }
-                                Variant::BinaryString(value) => {
-                                    chunk.write_binary_string(value.as_ref())?;
-                                }
-                                Variant::Tags(value) => {
-                                    let buf = value.encode();
-                                    chunk.write_binary_string(&buf)?;
-                                }
-                                Variant::Attributes(value) => {
-                                    let mut buf = Vec::new();
+                        Variant::SharedString(value) => {
+                            // Shared strings are only deduplicated in the file
+                            // when the property itself is a SharedString.
+                            chunk.write_binary_string(value.data())?;
+                            value_sizes.push(4 + value.data().len());
+                        }
+                        Variant::Tags(value) => {
+                            let buf = value.encode();
+                            chunk.write_binary_string(&buf)?;
+                            value_sizes.push(4 + buf.len());
+                        }
+                        Variant::Attributes(value) => {
+                            let mut buf = Vec::new();
 
-                                    value
-                                        .to_writer(&mut buf)
-                                        .map_err(|_| invalid_value(i, &rbx_value))?;
+                            value
+                                .to_writer(&mut buf)
+                                .map_err(|_| invalid_value(i, &rbx_value))?;
 
-                                    chunk.write_binary_string(&buf)?;
-                                }
-                                Variant::MaterialColors(value) => {
-                                    chunk.write_binary_string(&value.encode())?;
-                                }
-                                _ => {
-                                    return type_mismatch(
-                                        i,
-                                        &rbx_value,
-                                        "String, ContentId, Tags, Attributes, MaterialColors, or BinaryString",
-                                    );
-                                }
-                            }
+                            chunk.write_binary_string(&buf)?;
+                            value_sizes.push(4 + buf.len());
                         }
-                    }
-                    Type::Bool => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Bool(value) = rbx_value.as_ref() {
-                                chunk.write_bool(*value)?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Bool");
-                            }
+                        Variant::MaterialColors(value) => {
+                            let buf = value.encode();
+                            chunk.write_binary_string(&buf)?;
+                            value_sizes.push(4 + buf.len());
                         }
-                    }
-                    Type::Int32 => {
-                        let mut buf = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Int32(value) = rbx_value.as_ref() {
-                                buf.push(*value);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Int32");
-                            }
+                        _ => {
+                            return type_mismatch(
+                                i,
+                                &rbx_value,
+                                "String, ContentId, Tags, Attributes, MaterialColors, BinaryString, or SharedString",
+                            );
                         }
+                    }
+                }
+            }
+            Type::Bool => {
+                for (i, rbx_value) in values {
+                    if let Variant::Bool(value) = rbx_value.as_ref() {
+                        chunk.write_bool(*value)?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Bool");
+                    }
+                }
+            }
+            Type::Int32 => {
+                let mut buf = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Int32(value) = rbx_value.as_ref() {
+                        buf.push(*value);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Int32");
+                    }
+                }
 
-                        chunk.write_interleaved_i32_array(buf.into_iter())?;
+                chunk.write_interleaved_i32_array(buf.into_iter())?;
+            }
+            Type::Float32 => {
+                let mut buf = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Float32(value) = rbx_value.as_ref() {
+                        buf.push(*value);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Float32");
                     }
-                    Type::Float32 => {
-                        let mut buf = Vec::with_capacity(values.len());
+                }
 
-                        for (i, rbx_value) in values {
-                            if let Variant::Float32(value) = rbx_value.as_ref() {
-                                buf.push(*value);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Float32");
-                            }
+                chunk.write_interleaved_f32_array(buf.into_iter())?;
+            }
+            Type::Float64 => {
+                for (i, rbx_value) in values {
+                    match rbx_value.as_ref() {
+                        Variant::Float64(value) => {
+                            chunk.write_le_f64(*value)?;
                         }
-
-                        chunk.write_interleaved_f32_array(buf.into_iter())?;
-                    }
-                    Type::Float64 => {
-                        for (i, rbx_value) in values {
-                            match rbx_value.as_ref() {
-                                Variant::Float64(value) => {
-                                    chunk.write_le_f64(*value)?;
-                                }
-                                Variant::Float32(value) => {
-                                    chunk.write_le_f64(*value as f64)?;
-                                }
-                                _ => return type_mismatch(i, &rbx_value, "Float64"),
-                            }
+                        Variant::Float32(value) => {
+                            chunk.write_le_f64(*value as f64)?;
                         }
+                        _ => return type_mismatch(i, &rbx_value, "Float64"),
                     }
-                    Type::UDim => {
-                        let mut scale = Vec::with_capacity(values.len());
-                        let mut offset = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::UDim(value) = rbx_value.as_ref() {
-                                scale.push(value.scale);
-                                offset.push(value.offset);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "UDim");
-                            }
-                        }
+                }
+            }
+            Type::UDim => {
+                let mut scale = Vec::with_capacity(values.len());
+                let mut offset = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::UDim(value) = rbx_value.as_ref() {
+                        scale.push(value.scale);
+                        offset.push(value.offset);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "UDim");
+                    }
+                }
 
-                        chunk.write_interleaved_f32_array(scale.into_iter())?;
-                        chunk.write_interleaved_i32_array(offset.into_iter())?;
+                chunk.write_interleaved_f32_array(scale.into_iter())?;
+                chunk.write_interleaved_i32_array(offset.into_iter())?;
+            }
+            Type::UDim2 => {
+                let mut scale_x = Vec::with_capacity(values.len());
+                let mut scale_y = Vec::with_capacity(values.len());
+                let mut offset_x = Vec::with_capacity(values.len());
+                let mut offset_y = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::UDim2(value) = rbx_value.as_ref() {
+                        scale_x.push(value.x.scale);
+                        scale_y.push(value.y.scale);
+                        offset_x.push(value.x.offset);
+                        offset_y.push(value.y.offset);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "UDim2");
                     }
-                    Type::UDim2 => {
-                        let mut scale_x = Vec::with_capacity(values.len());
-                        let mut scale_y = Vec::with_capacity(values.len());
-                        let mut offset_x = Vec::with_capacity(values.len());
-                        let mut offset_y = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::UDim2(value) = rbx_value.as_ref() {
-                                scale_x.push(value.x.scale);
-                                scale_y.push(value.y.scale);
-                                offset_x.push(value.x.offset);
-                                offset_y.push(value.y.offset);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "UDim2");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(scale_x.into_iter())?;
-                        chunk.write_interleaved_f32_array(scale_y.into_iter())?;
-                        chunk.write_interleaved_i32_array(offset_x.into_iter())?;
-                        chunk.write_interleaved_i32_array(offset_y.into_iter())?;
+                chunk.write_interleaved_f32_array(scale_x.into_iter())?;
+                chunk.write_interleaved_f32_array(scale_y.into_iter())?;
+                chunk.write_interleaved_i32_array(offset_x.into_iter())?;
+                chunk.write_interleaved_i32_array(offset_y.into_iter())?;
+            }
+            Type::Font => {
+                for (i, rbx_value) in values {
+                    if let Variant::Font(value) = rbx_value.as_ref() {
+                        chunk.write_string(&value.family)?;
+                        chunk.write_le_u16(value.weight.as_u16())?;
+                        chunk.write_u8(value.style.as_u8())?;
+                        chunk.write_string(value.cached_face_id.as_deref().unwrap_or_default())?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Font");
                     }
-                    Type::Font => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Font(value) = rbx_value.as_ref() {
-                                chunk.write_string(&value.family)?;
-                                chunk.write_le_u16(value.weight.as_u16())?;
-                                chunk.write_u8(value.style.as_u8())?;
-                                chunk.write_string(
-                                    value.cached_face_id.as_deref().unwrap_or_default(),
-                                )?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Font");
-                            }
-                        }
+                }
+            }
+            Type::Ray => {
+                for (i, rbx_value) in values {
+                    if let Variant::Ray(value) = rbx_value.as_ref() {
+                        chunk.write_le_f32(value.origin.x)?;
+                        chunk.write_le_f32(value.origin.y)?;
+                        chunk.write_le_f32(value.origin.z)?;
+                        chunk.write_le_f32(value.direction.x)?;
+                        chunk.write_le_f32(value.direction.y)?;
+                        chunk.write_le_f32(value.direction.x)?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Ray");
                     }
-                    Type::Ray => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Ray(value) = rbx_value.as_ref() {
-                                chunk.write_le_f32(value.origin.x)?;
-                                chunk.write_le_f32(value.origin.y)?;
-                                chunk.write_le_f32(value.origin.z)?;
-                                chunk.write_le_f32(value.direction.x)?;
-                                chunk.write_le_f32(value.direction.y)?;
-                                chunk.write_le_f32(value.direction.x)?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Ray");
-                            }
-                        }
+                }
+            }
+            Type::Faces => {
+                for (i, rbx_value) in values {
+                    if let Variant::Faces(value) = rbx_value.as_ref() {
+                        chunk.write_u8(value.bits())?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Faces");
                     }
-                    Type::Faces => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Faces(value) = rbx_value.as_ref() {
-                                chunk.write_u8(value.bits())?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Faces");
-                            }
-                        }
+                }
+            }
+            Type::Axes => {
+                for (i, rbx_value) in values {
+                    if let Variant::Axes(value) = rbx_value.as_ref() {
+                        chunk.write_u8(value.bits())?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Axes");
                     }
-                    Type::Axes => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Axes(value) = rbx_value.as_ref() {
-                                chunk.write_u8(value.bits())?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Axes");
-                            }
-                        }
+                }
+            }
+            Type::BrickColor => {
+                let mut numbers = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::BrickColor(value) = rbx_value.as_ref() {
+                        numbers.push(*value as u32);
+                    } else if let Variant::Int32(value) = rbx_value.as_ref() {
+                        numbers.push(*value as u32);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "BrickColor");
                     }
-                    Type::BrickColor => {
-                        let mut numbers = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::BrickColor(value) = rbx_value.as_ref() {
-                                numbers.push(*value as u32);
-                            } else if let Variant::Int32(value) = rbx_value.as_ref() {
-                                numbers.push(*value as u32);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "BrickColor");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_u32_array(&numbers)?;
+                chunk.write_interleaved_u32_array(&numbers)?;
+            }
+            Type::Color3 => {
+                let mut r = Vec::with_capacity(values.len());
+                let mut g = Vec::with_capacity(values.len());
+                let mut b = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Color3(value) = rbx_value.as_ref() {
+                        r.push(value.r);
+                        g.push(value.g);
+                        b.push(value.b);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Color3");
                     }
-                    Type::Color3 => {
-                        let mut r = Vec::with_capacity(values.len());
-                        let mut g = Vec::with_capacity(values.len());
-                        let mut b = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Color3(value) = rbx_value.as_ref() {
-                                r.push(value.r);
-                                g.push(value.g);
-                                b.push(value.b);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Color3");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(r.into_iter())?;
-                        chunk.write_interleaved_f32_array(g.into_iter())?;
-                        chunk.write_interleaved_f32_array(b.into_iter())?;
+                chunk.write_interleaved_f32_array(r.into_iter())?;
+                chunk.write_interleaved_f32_array(g.into_iter())?;
+                chunk.write_interleaved_f32_array(b.into_iter())?;
+            }
+            Type::Vector2 => {
+                let mut x = Vec::with_capacity(values.len());
+                let mut y = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Vector2(value) = rbx_value.as_ref() {
+                        x.push(value.x);
+                        y.push(value.y)
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Vector2");
                     }
-                    Type::Vector2 => {
-                        let mut x = Vec::with_capacity(values.len());
-                        let mut y = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Vector2(value) = rbx_value.as_ref() {
-                                x.push(value.x);
-                                y.push(value.y)
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Vector2");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(x.into_iter())?;
-                        chunk.write_interleaved_f32_array(y.into_iter())?;
+                chunk.write_interleaved_f32_array(x.into_iter())?;
+                chunk.write_interleaved_f32_array(y.into_iter())?;
+            }
+            Type::Vector3 => {
+                let mut x = Vec::with_capacity(values.len());
+                let mut y = Vec::with_capacity(values.len());
+                let mut z = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Vector3(value) = rbx_value.as_ref() {
+                        x.push(value.x);
+                        y.push(value.y);
+                        z.push(value.z)
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Vector3");
                     }
-                    Type::Vector3 => {
-                        let mut x = Vec::with_capacity(values.len());
-                        let mut y = Vec::with_capacity(values.len());
-                        let mut z = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Vector3(value) = rbx_value.as_ref() {
-                                x.push(value.x);
-                                y.push(value.y);
-                                z.push(value.z)
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Vector3");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(x.into_iter())?;
-                        chunk.write_interleaved_f32_array(y.into_iter())?;
-                        chunk.write_interleaved_f32_array(z.into_iter())?;
+                chunk.write_interleaved_f32_array(x.into_iter())?;
+                chunk.write_interleaved_f32_array(y.into_iter())?;
+                chunk.write_interleaved_f32_array(z.into_iter())?;
+            }
+            Type::CFrame => {
+                let mut rotations = Vec::with_capacity(values.len());
+                let mut x = Vec::with_capacity(values.len());
+                let mut y = Vec::with_capacity(values.len());
+                let mut z = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::CFrame(value) = rbx_value.as_ref() {
+                        rotations.push(value.orientation);
+                        x.push(value.position.x);
+                        y.push(value.position.y);
+                        z.push(value.position.z);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "CFrame");
                     }
-                    Type::CFrame => {
-                        let mut rotations = Vec::with_capacity(values.len());
-                        let mut x = Vec::with_capacity(values.len());
-                        let mut y = Vec::with_capacity(values.len());
-                        let mut z = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::CFrame(value) = rbx_value.as_ref() {
-                                rotations.push(value.orientation);
-                                x.push(value.position.x);
-                                y.push(value.position.y);
-                                z.push(value.position.z);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "CFrame");
-                            }
-                        }
-
-                        for matrix in rotations {
-                            if let Some(id) = matrix.to_basic_rotation_id() {
-                                chunk.write_u8(id)?;
-                            } else {
-                                chunk.write_u8(0x00)?;
+                }
 
-                                chunk.write_le_f32(matrix.x.x)?;
-                                chunk.write_le_f32(matrix.x.y)?;
-                                chunk.write_le_f32(matrix.x.z)?;
+                for matrix in rotations {
+                    if let Some(id) = matrix.to_basic_rotation_id() {
+                        chunk.write_u8(id)?;
+                    } else {
+                        chunk.write_u8(0x00)?;
 
-                                chunk.write_le_f32(matrix.y.x)?;
-                                chunk.write_le_f32(matrix.y.y)?;
-                                chunk.write_le_f32(matrix.y.z)?;
+                        chunk.write_le_f32(matrix.x.x)?;
+                        chunk.write_le_f32(matrix.x.y)?;
+                        chunk.write_le_f32(matrix.x.z)?;
 
-                                chunk.write_le_f32(matrix.z.x)?;
-                                chunk.write_le_f32(matrix.z.y)?;
-                                chunk.write_le_f32(matrix.z.z)?;
-                            }
-                        }
+                        chunk.write_le_f32(matrix.y.x)?;
+                        chunk.write_le_f32(matrix.y.y)?;
+                        chunk.write_le_f32(matrix.y.z)?;
 
-                        chunk.write_interleaved_f32_array(x.into_iter())?;
-                        chunk.write_interleaved_f32_array(y.into_iter())?;
-                        chunk.write_interleaved_f32_array(z.into_iter())?;
+                        chunk.write_le_f32(matrix.z.x)?;
+                        chunk.write_le_f32(matrix.z.y)?;
+                        chunk.write_le_f32(matrix.z.z)?;
                     }
-                    Type::Enum => {
-                        let mut buf = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            match rbx_value.as_ref() {
-                                Variant::Enum(value) => buf.push(value.to_u32()),
-                                Variant::EnumItem(EnumItem { value, .. }) => buf.push(*value),
-                                _ => return type_mismatch(i, &rbx_value, "Enum or EnumItem"),
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_u32_array(&buf)?;
+                chunk.write_interleaved_f32_array(x.into_iter())?;
+                chunk.write_interleaved_f32_array(y.into_iter())?;
+                chunk.write_interleaved_f32_array(z.into_iter())?;
+            }
+            Type::Enum => {
+                let mut buf = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    match rbx_value.as_ref() {
+                        Variant::Enum(value) => buf.push(value.to_u32()),
+                        Variant::EnumItem(EnumItem { value, .. }) => buf.push(*value),
+                        _ => return type_mismatch(i, &rbx_value, "Enum or EnumItem"),
                     }
-                    Type::Ref => {
-                        let mut buf = Vec::with_capacity(values.len());
+                }
 
-                        for (i, rbx_value) in values {
-                            if let Variant::Ref(value) = rbx_value.as_ref() {
-                                if let Some(id) = self.id_to_referent.get(value) {
-                                    buf.push(*id);
-                                } else {
-                                    buf.push(-1);
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Ref");
-                            }
+                chunk.write_interleaved_u32_array(&buf)?;
+            }
+            Type::Ref => {
+                let mut buf = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Ref(value) = rbx_value.as_ref() {
+                        if let Some(id) = self.id_to_referent.get(value) {
+                            buf.push(*id);
+                        } else {
+                            buf.push(-1);
                         }
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Ref");
+                    }
+                }
 
-                        chunk.write_referent_array(buf.into_iter())?;
+                chunk.write_referent_array(buf.into_iter())?;
+            }
+            Type::Vector3int16 => {
+                for (i, rbx_value) in values {
+                    if let Variant::Vector3int16(value) = rbx_value.as_ref() {
+                        chunk.write_le_i16(value.x)?;
+                        chunk.write_le_i16(value.y)?;
+                        chunk.write_le_i16(value.z)?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Vector3int16");
                     }
-                    Type::Vector3int16 => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Vector3int16(value) = rbx_value.as_ref() {
-                                chunk.write_le_i16(value.x)?;
-                                chunk.write_le_i16(value.y)?;
-                                chunk.write_le_i16(value.z)?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Vector3int16");
-                            }
+                }
+            }
+            Type::NumberSequence => {
+                for (i, rbx_value) in values {
+                    if let Variant::NumberSequence(value) = rbx_value.as_ref() {
+                        chunk.write_le_u32(value.keypoints.len() as u32)?;
+
+                        for keypoint in &value.keypoints {
+                            chunk.write_le_f32(keypoint.time)?;
+                            chunk.write_le_f32(keypoint.value)?;
+                            chunk.write_le_f32(keypoint.envelope)?;
                         }
+                    } else {
+                        return type_mismatch(i, &rbx_value, "NumberSequence");
                     }
-                    Type::NumberSequence => {
-                        for (i, rbx_value) in values {
-                            if let Variant::NumberSequence(value) = rbx_value.as_ref() {
-                                chunk.write_le_u32(value.keypoints.len() as u32)?;
-
-                                for keypoint in &value.keypoints {
-                                    chunk.write_le_f32(keypoint.time)?;
-                                    chunk.write_le_f32(keypoint.value)?;
-                                    chunk.write_le_f32(keypoint.envelope)?;
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "NumberSequence");
-                            }
+                }
+            }
+            Type::ColorSequence => {
+                for (i, rbx_value) in values {
+                    if let Variant::ColorSequence(value) = rbx_value.as_ref() {
+                        chunk.write_le_u32(value.keypoints.len() as u32)?;
+
+                        for keypoint in &value.keypoints {
+                            chunk.write_le_f32(keypoint.time)?;
+                            chunk.write_le_f32(keypoint.color.r)?;
+                            chunk.write_le_f32(keypoint.color.g)?;
+                            chunk.write_le_f32(keypoint.color.b)?;
+
+                            // write out a dummy value for envelope, which is serialized but doesn't do anything
+                            chunk.write_le_f32(0.0)?;
                         }
+                    } else {
+                        return type_mismatch(i, &rbx_value, "ColorSequence");
                     }
-                    Type::ColorSequence => {
-                        for (i, rbx_value) in values {
-                            if let Variant::ColorSequence(value) = rbx_value.as_ref() {
-                                chunk.write_le_u32(value.keypoints.len() as u32)?;
-
-                                for keypoint in &value.keypoints {
-                                    chunk.write_le_f32(keypoint.time)?;
-                                    chunk.write_le_f32(keypoint.color.r)?;
-                                    chunk.write_le_f32(keypoint.color.g)?;
-                                    chunk.write_le_f32(keypoint.color.b)?;
-
-                                    // write out a dummy value for envelope, which is serialized but doesn't do anything
-                                    chunk.write_le_f32(0.0)?;
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "ColorSequence");
-                            }
-                        }
+                }
+            }
+            Type::NumberRange => {
+                for (i, rbx_value) in values {
+                    if let Variant::NumberRange(value) = rbx_value.as_ref() {
+                        chunk.write_le_f32(value.min)?;
+                        chunk.write_le_f32(value.max)?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "NumberRange");
                     }
-                    Type::NumberRange => {
-                        for (i, rbx_value) in values {
-                            if let Variant::NumberRange(value) = rbx_value.as_ref() {
-                                chunk.write_le_f32(value.min)?;
-                                chunk.write_le_f32(value.max)?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "NumberRange");
-                            }
+                }
+            }
+            Type::Rect => {
+                let mut x_min = Vec::with_capacity(values.len());
+                let mut y_min = Vec::with_capacity(values.len());
+                let mut x_max = Vec::with_capacity(values.len());
+                let mut y_max = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Rect(value) = rbx_value.as_ref() {
+                        x_min.push(value.min.x);
+                        y_min.push(value.min.y);
+                        x_max.push(value.max.x);
+                        y_max.push(value.max.y);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Rect");
+                    }
+                }
+
+                chunk.write_interleaved_f32_array(x_min.into_iter())?;
+                chunk.write_interleaved_f32_array(y_min.into_iter())?;
+                chunk.write_interleaved_f32_array(x_max.into_iter())?;
+                chunk.write_interleaved_f32_array(y_max.into_iter())?;
+            }
+            Type::PhysicalProperties => {
+                for (i, rbx_value) in values {
+                    if let Variant::PhysicalProperties(value) = rbx_value.as_ref() {
+                        if let PhysicalProperties::Custom(props) = value {
+                            chunk.write_u8(1)?;
+                            chunk.write_le_f32(props.density)?;
+                            chunk.write_le_f32(props.friction)?;
+                            chunk.write_le_f32(props.elasticity)?;
+                            chunk.write_le_f32(props.friction_weight)?;
+                            chunk.write_le_f32(props.elasticity_weight)?;
+                        } else {
+                            chunk.write_u8(0)?;
                         }
+                    } else {
+                        return type_mismatch(i, &rbx_value, "PhysicalProperties");
                     }
-                    Type::Rect => {
-                        let mut x_min = Vec::with_capacity(values.len());
-                        let mut y_min = Vec::with_capacity(values.len());
-                        let mut x_max = Vec::with_capacity(values.len());
-                        let mut y_max = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Rect(value) = rbx_value.as_ref() {
-                                x_min.push(value.min.x);
-                                y_min.push(value.min.y);
-                                x_max.push(value.max.x);
-                                y_max.push(value.max.y);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Rect");
-                            }
+                }
+            }
+            Type::Color3uint8 => {
+                let mut r = Vec::with_capacity(values.len());
+                let mut g = Vec::with_capacity(values.len());
+                let mut b = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    match rbx_value.as_ref() {
+                        Variant::Color3uint8(value) => {
+                            r.push(value.r);
+                            g.push(value.g);
+                            b.push(value.b);
                         }
+                        Variant::Color3(value) => {
+                            let color: Color3uint8 = (*value).into();
 
-                        chunk.write_interleaved_f32_array(x_min.into_iter())?;
-                        chunk.write_interleaved_f32_array(y_min.into_iter())?;
-                        chunk.write_interleaved_f32_array(x_max.into_iter())?;
-                        chunk.write_interleaved_f32_array(y_max.into_iter())?;
-                    }
-                    Type::PhysicalProperties => {
-                        for (i, rbx_value) in values {
-                            if let Variant::PhysicalProperties(value) = rbx_value.as_ref() {
-                                if let PhysicalProperties::Custom(props) = value {
-                                    chunk.write_u8(1)?;
-                                    chunk.write_le_f32(props.density)?;
-                                    chunk.write_le_f32(props.friction)?;
-                                    chunk.write_le_f32(props.elasticity)?;
-                                    chunk.write_le_f32(props.friction_weight)?;
-                                    chunk.write_le_f32(props.elasticity_weight)?;
-                                } else {
-                                    chunk.write_u8(0)?;
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "PhysicalProperties");
-                            }
+                            r.push(color.r);
+                            g.push(color.g);
+                            b.push(color.b);
                         }
+                        _ => return type_mismatch(i, &rbx_value, "Color3uint8 or Color3"),
                     }
-                    Type::Color3uint8 => {
-                        let mut r = Vec::with_capacity(values.len());
-                        let mut g = Vec::with_capacity(values.len());
-                        let mut b = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            match rbx_value.as_ref() {
-                                Variant::Color3uint8(value) => {
-                                    r.push(value.r);
-                                    g.push(value.g);
-                                    b.push(value.b);
-                                }
-                                Variant::Color3(value) => {
-                                    let color: Color3uint8 = (*value).into();
+                }
 
-                                    r.push(color.r);
-                                    g.push(color.g);
-                                    b.push(color.b);
-                                }
-                                _ => return type_mismatch(i, &rbx_value, "Color3uint8 or Color3"),
-                            }
-                        }
+                chunk.write_all(r.as_slice())?;
+                chunk.write_all(g.as_slice())?;
+                chunk.write_all(b.as_slice())?;
+            }
+            Type::Int64 => {
+                let mut buf = Vec::with_capacity(values.len());
 
-                        chunk.write_all(r.as_slice())?;
-                        chunk.write_all(g.as_slice())?;
-                        chunk.write_all(b.as_slice())?;
+                for (i, rbx_value) in values {
+                    match rbx_value.as_ref() {
+                        Variant::Int64(value) => {
+                            buf.push(*value);
+                        }
+                        Variant::Int32(value) => {
+                            buf.push(*value as i64);
+                        }
+                        _ => return type_mismatch(i, &rbx_value, "Int64"),
                     }
-                    Type::Int64 => {
-                        let mut buf = Vec::with_capacity(values.len());
+                }
 
-                        for (i, rbx_value) in values {
-                            match rbx_value.as_ref() {
-                                Variant::Int64(value) => {
-                                    buf.push(*value);
-                                }
-                                Variant::Int32(value) => {
-                                    buf.push(*value as i64);
-                                }
-                                _ => return type_mismatch(i, &rbx_value, "Int64"),
-                            }
-                        }
+                chunk.write_interleaved_i64_array(buf.into_iter())?;
+            }
+            Type::SharedString => {
+                let mut entries = Vec::with_capacity(values.len());
 
-                        chunk.write_interleaved_i64_array(buf.into_iter())?;
+                for (i, rbx_value) in values {
+                    if let Variant::SharedString(value) = rbx_value.as_ref() {
+                        if let Some(id) = self.shared_string_ids.get(value) {
+                            entries.push(*id);
+                        } else {
+                            panic!(
+                                "SharedString {} was not found during type collection",
+                                value.hash()
+                            )
+                        }
+                    } else {
+                        return type_mismatch(i, &rbx_value, "SharedString");
                     }
-                    Type::SharedString => {
-                        let mut entries = Vec::with_capacity(values.len());
+                }
 
-                        for (i, rbx_value) in values {
-                            if let Variant::SharedString(value) = rbx_value.as_ref() {
-                                if let Some(id) = self.shared_string_ids.get(value) {
-                                    entries.push(*id);
-                                } else {
-                                    panic!(
-                                        "SharedString {} was not found during type collection",
-                                        value.hash()
-                                    )
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "SharedString");
-                            }
+                chunk.write_interleaved_u32_array(&entries)?;
+            }
+            Type::OptionalCFrame => {
+                let mut rotations = Vec::with_capacity(values.len());
+                let mut bools = Vec::with_capacity(values.len());
+                let mut x = Vec::with_capacity(values.len());
+                let mut y = Vec::with_capacity(values.len());
+                let mut z = Vec::with_capacity(values.len());
+
+                chunk.write_u8(Type::CFrame as u8)?;
+
+                for (i, rbx_value) in values {
+                    if let Variant::OptionalCFrame(value) = rbx_value.as_ref() {
+                        if let Some(value) = value {
+                            rotations.push(value.orientation);
+                            x.push(value.position.x);
+                            y.push(value.position.y);
+                            z.push(value.position.z);
+                            bools.push(0x01);
+                        } else {
+                            rotations.push(Matrix3::identity());
+                            x.push(0.0);
+                            y.push(0.0);
+                            z.push(0.0);
+                            bools.push(0x00);
                         }
-
-                        chunk.write_interleaved_u32_array(&entries)?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "OptionalCFrame");
                     }
-                    Type::OptionalCFrame => {
-                        let mut rotations = Vec::with_capacity(values.len());
-                        let mut bools = Vec::with_capacity(values.len());
-                        let mut x = Vec::with_capacity(values.len());
-                        let mut y = Vec::with_capacity(values.len());
-                        let mut z = Vec::with_capacity(values.len());
-
-                        chunk.write_u8(Type::CFrame as u8)?;
-
-                        for (i, rbx_value) in values {
-                            if let Variant::OptionalCFrame(value) = rbx_value.as_ref() {
-                                if let Some(value) = value {
-                                    rotations.push(value.orientation);
-                                    x.push(value.position.x);
-                                    y.push(value.position.y);
-                                    z.push(value.position.z);
-                                    bools.push(0x01);
-                                } else {
-                                    rotations.push(Matrix3::identity());
-                                    x.push(0.0);
-                                    y.push(0.0);
-                                    z.push(0.0);
-                                    bools.push(0x00);
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "OptionalCFrame");
-                            }
-                        }
+                }
 
-                        for matrix in rotations {
-                            if let Some(id) = matrix.to_basic_rotation_id() {
-                                chunk.write_u8(id)?;
-                            } else {
-                                chunk.write_u8(0x00)?;
+                for matrix in rotations {
+                    if let Some(id) = matrix.to_basic_rotation_id() {
+                        chunk.write_u8(id)?;
+                    } else {
+                        chunk.write_u8(0x00)?;
 
-                                chunk.write_le_f32(matrix.x.x)?;
-                                chunk.write_le_f32(matrix.x.y)?;
-                                chunk.write_le_f32(matrix.x.z)?;
+                        chunk.write_le_f32(matrix.x.x)?;
+                        chunk.write_le_f32(matrix.x.y)?;
+                        chunk.write_le_f32(matrix.x.z)?;
 
-                                chunk.write_le_f32(matrix.y.x)?;
-                                chunk.write_le_f32(matrix.y.y)?;
-                                chunk.write_le_f32(matrix.y.z)?;
+                        chunk.write_le_f32(matrix.y.x)?;
+                        chunk.write_le_f32(matrix.y.y)?;
+                        chunk.write_le_f32(matrix.y.z)?;
 
-                                chunk.write_le_f32(matrix.z.x)?;
-                                chunk.write_le_f32(matrix.z.y)?;
-                                chunk.write_le_f32(matrix.z.z)?;
-                            }
-                        }
+                        chunk.write_le_f32(matrix.z.x)?;
+                        chunk.write_le_f32(matrix.z.y)?;
+                        chunk.write_le_f32(matrix.z.z)?;
+                    }
+                }
 
-                        chunk.write_interleaved_f32_array(x.into_iter())?;
-                        chunk.write_interleaved_f32_array(y.into_iter())?;
-                        chunk.write_interleaved_f32_array(z.into_iter())?;
+                chunk.write_interleaved_f32_array(x.into_iter())?;
+                chunk.write_interleaved_f32_array(y.into_iter())?;
+                chunk.write_interleaved_f32_array(z.into_iter())?;
 
-                        chunk.write_u8(Type::Bool as u8)?;
-                        chunk.write_all(bools.as_slice())?;
+                chunk.write_u8(Type::Bool as u8)?;
+                chunk.write_all(bools.as_slice())?;
+            }
+            Type::UniqueId => {
+                let mut blobs = Vec::with_capacity(values.len());
+                for (i, rbx_value) in values {
+                    if let Variant::UniqueId(value) = rbx_value.as_ref() {
+                        let mut blob = [0; 16];
+                        // This is maybe not the best solution to this
+                        // but we can always change it.
+                        blob[0..4].copy_from_slice(&value.index().to_be_bytes());
+                        blob[4..8].copy_from_slice(&value.time().to_be_bytes());
+                        blob[8..].copy_from_slice(&value.random().rotate_left(1).to_be_bytes());
+                        blobs.push(blob);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "UniqueId");
                     }
-                    Type::UniqueId => {
-                        let mut blobs = Vec::with_capacity(values.len());
-                        for (i, rbx_value) in values {
-                            if let Variant::UniqueId(value) = rbx_value.as_ref() {
-                                let mut blob = [0; 16];
-                                // This is maybe not the best solution to this
-                                // but we can always change it.
-                                blob[0..4].copy_from_slice(&value.index().to_be_bytes());
-                                blob[4..8].copy_from_slice(&value.time().to_be_bytes());
-                                blob[8..]
-                                    .copy_from_slice(&value.random().rotate_left(1).to_be_bytes());
-                                blobs.push(blob);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "UniqueId");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_bytes::<16>(&blobs)?;
+                chunk.write_interleaved_bytes::<16>(&blobs)?;
+            }
+            Type::SecurityCapabilities => {
+                let mut capabilities = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::SecurityCapabilities(value) = rbx_value.as_ref() {
+                        capabilities.push(value.bits() as i64)
+                    } else {
+                        return type_mismatch(i, &rbx_value, "SecurityCapabilities");
                     }
-                    Type::SecurityCapabilities => {
-                        let mut capabilities = Vec::with_capacity(values.len());
+                }
 
-                        for (i, rbx_value) in values {
-                            if let Variant::SecurityCapabilities(value) = rbx_value.as_ref() {
-                                capabilities.push(value.bits() as i64)
-                            } else {
-                                return type_mismatch(i, &rbx_value, "SecurityCapabilities");
+                chunk.write_interleaved_i64_array(capabilities.into_iter())?;
+            }
+            Type::Content => {
+                let mut source_types = Vec::with_capacity(values.len());
+                let mut uris = Vec::with_capacity(values.len());
+                let mut objects = Vec::new();
+                for (i, rbx_value) in values {
+                    if let Variant::Content(content) = rbx_value.as_ref() {
+                        source_types.push(match content.value() {
+                            ContentType::None => 0,
+                            ContentType::Uri(uri) => {
+                                uris.push(uri.clone());
+                                1
                             }
-                        }
-
-                        chunk.write_interleaved_i64_array(capabilities.into_iter())?;
-                    }
-                    Type::Content => {
-                        let mut source_types = Vec::with_capacity(values.len());
-                        let mut uris = Vec::with_capacity(values.len());
-                        let mut objects = Vec::new();
-                        for (i, rbx_value) in values {
-                            if let Variant::Content(content) = rbx_value.as_ref() {
-                                source_types.push(match content.value() {
-                                    ContentType::None => 0,
-                                    ContentType::Uri(uri) => {
-                                        uris.push(uri.clone());
-                                        1
-                                    }
-                                    ContentType::Object(referent) => {
-                                        if let Some(id) = self.id_to_referent.get(referent) {
-                                            objects.push(*id);
-                                        } else {
-                                            objects.push(-1);
-                                        }
-                                        2
-                                    }
-                                    _ => return Err(invalid_value(i, &rbx_value)),
-                                });
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Content");
+                            ContentType::Object(referent) => {
+                                if let Some(id) = self.id_to_referent.get(referent) {
+                                    objects.push(*id);
+                                } else {
+                                    objects.push(-1);
+                                }
+                                2
                             }
-                        }
-                        chunk.write_interleaved_i32_array(source_types.into_iter())?;
-
-                        chunk.write_le_u32(uris.len() as u32)?;
-                        for uri in uris {
-                            chunk.write_string(&uri)?;
-                        }
-                        chunk.write_le_u32(objects.len() as u32)?;
-                        chunk.write_referent_array(objects.into_iter())?;
-
-                        // If we ever need to support the external referents,
-                        // we will need to add it here.
-                        chunk.write_le_u32(0)?;
+                            _ => return Err(invalid_value(i, &rbx_value)),
+                        });
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Content");
                     }
                 }
+                chunk.write_interleaved_i32_array(source_types.into_iter())?;
+
+                chunk.write_le_u32(uris.len() as u32)?;
+                for uri in uris {
+                    chunk.write_string(&uri)?;
+                }
+                chunk.write_le_u32(objects.len() as u32)?;
+                chunk.write_referent_array(objects.into_iter())?;
 
-                chunk.dump(&mut self.output)?;
+                // If we ever need to support the external referents,
+                // we will need to add it here.
+                chunk.write_le_u32(0)?;
             }
         }
 
+        for (instance, size) in type_info.instances.iter().zip(value_sizes) {
+            *self
+                .instance_byte_sizes
+                .entry(instance.referent())
+                .or_default() += size;
+        }
+
+        self.chunks.push(chunk.dump(&mut self.output)?);
+
         Ok(())
     }
 
+    /// Returns the value each instance of a class has for one of its
+    /// properties, in the order the instances are written, after running
+    /// property hooks and migrations.
+    fn property_values<'a>(
+        serializer: &Serializer,
+        type_info: &'a TypeInfo<'dom, 'db>,
+        prop_name: &Symbol,
+        prop_info: &'a PropInfo<'db>,
+    ) -> Vec<Cow<'a, Variant>> {
+        type_info
+            .instances
+            .iter()
+            .map(|instance| {
+                // We store the Name property in a different field for
+                // convenience, but when serializing to the binary model
+                // format we need to handle it just like other properties.
+                if *prop_name == "Name" {
+                    return Cow::Owned(Variant::String(instance.name.clone()));
+                }
+
+                // Most properties will be stored on instances using the
+                // property's canonical name, so we'll try that first. If
+                // there were any known aliases for this property used as
+                // part of this file, we can check those next.
+                let property = instance.properties.get_key_value(prop_name).or_else(|| {
+                    prop_info
+                        .aliases
+                        .iter()
+                        .find_map(|alias| instance.properties.get_key_value(alias))
+                });
+
+                if let Some((name, property)) = property {
+                    if let Some(property) = serializer.hooked_property(instance, name, property) {
+                        return property;
+                    }
+                }
+
+                // Finally, we can fall back to the default value we
+                // computed for this PropInfo. This is sourced from the
+                // reflection database if available, or falls back to a
+                // reasonable default.
+                Cow::Borrowed(prop_info.default_value.borrow())
+            })
+            .map(|value| {
+                if let Some(migration) = prop_info.migration {
+                    match migration.perform(&value) {
+                        Ok(new_value) => Cow::Owned(new_value),
+                        Err(_) => value,
+                    }
+                } else {
+                    value
+                }
+            })
+            .collect()
+    }
+
     /// Write out the hierarchical relations between instances, stored in a
     /// chunk named PRNT.
     #[profiling::function]
@@ -1328,7 +1658,7 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
         chunk.write_referent_array(object_referents)?;
         chunk.write_referent_array(parent_referents)?;
 
-        chunk.dump(&mut self.output)?;
+        self.chunks.push(chunk.dump(&mut self.output)?);
 
         Ok(())
     }
@@ -1342,32 +1672,11 @@ impl<'dom, 'db, W: Write> SerializerState<'dom, 'db, W> {
 
         let mut end = ChunkBuilder::new(b"END\0", CompressionType::None);
         end.write_all(FILE_FOOTER)?;
-        end.dump(&mut self.output)?;
+        self.chunks.push(end.dump(&mut self.output)?);
 
         Ok(())
     }
 
-    /// Equivalent to Instance:GetFullName() from Roblox.
-    fn full_name_for(&self, subject_ref: Ref) -> String {
-        let mut components = Vec::new();
-        let mut current_id = subject_ref;
-
-        while current_id.is_some() {
-            let instance = self.dom.get_by_ref(current_id).unwrap();
-            components.push(instance.name.as_str());
-            current_id = instance.parent();
-        }
-
-        let mut name = String::new();
-        for component in components.iter().rev() {
-            name.push_str(component);
-            name.push('.');
-        }
-        name.pop();
-
-        name
-    }
-
     fn fallback_default_value(rbx_type: VariantType) -> Option<Variant> {
         Some(match rbx_type {
             VariantType::String => Variant::String(String::new()),
@@ -0,0 +1,248 @@
+use std::{borrow::Cow, fmt};
+
+use rbx_dom_weak::{
+    types::{EnumItem, Ref, Variant, VariantType},
+    Instance, Symbol, WeakDom,
+};
+use rbx_reflection::{DataType, PropertyKind, PropertySerialization, ReflectionDatabase};
+
+use crate::{core::find_property_descriptors, types::Type};
+
+/// The problems found with a single instance by
+/// [`Serializer::validate_against_database`][crate::Serializer::validate_against_database].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InstanceIssues {
+    /// The referent of the instance.
+    pub referent: Ref,
+
+    /// The full name of the instance, as returned by
+    /// [`WeakDom::full_name_of`].
+    pub full_name: String,
+
+    /// Every problem found with the instance, sorted by property name.
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// A problem found with an instance when comparing it against a reflection
+/// database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationIssue {
+    /// The instance's class isn't in the database. Its properties are written
+    /// as-is, so they aren't checked.
+    UnknownClass,
+
+    /// The property isn't defined on the instance's class or any of its
+    /// superclasses. It will be written as-is.
+    UnknownProperty {
+        /// The name of the property.
+        property: Symbol,
+    },
+
+    /// The property's value has a type that can't be written as the type the
+    /// database says it has. Serializing the instance will fail.
+    WrongType {
+        /// The name of the property.
+        property: Symbol,
+        /// The type the database says the property has.
+        expected: VariantType,
+        /// The type of the property's value.
+        actual: VariantType,
+    },
+
+    /// The database says the property has a type that rbx_binary doesn't know
+    /// how to write. Serializing the instance will fail.
+    UnsupportedType {
+        /// The name of the property.
+        property: Symbol,
+        /// The type the database says the property has.
+        ty: VariantType,
+    },
+
+    /// The property's value isn't an item of its enum.
+    EnumOutOfRange {
+        /// The name of the property.
+        property: Symbol,
+        /// The name of the enum the property uses.
+        enum_name: String,
+        /// The value that isn't in the enum.
+        value: u32,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownClass => write!(f, "the class is not in the reflection database"),
+            Self::UnknownProperty { property } => {
+                write!(f, "{property} is not in the reflection database")
+            }
+            Self::WrongType {
+                property,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{property} should be of type {expected:?}, but it was of type {actual:?}"
+            ),
+            Self::UnsupportedType { property, ty } => {
+                write!(
+                    f,
+                    "{property} is of type {ty:?}, which cannot be serialized"
+                )
+            }
+            Self::EnumOutOfRange {
+                property,
+                enum_name,
+                value,
+            } => write!(
+                f,
+                "{property} has value {value}, which is not in Enum.{enum_name}"
+            ),
+        }
+    }
+}
+
+/// Checks every instance in `refs` and their descendants against `database`,
+/// returning the instances that have problems in the order they're visited.
+pub fn validate(database: &ReflectionDatabase, dom: &WeakDom, refs: &[Ref]) -> Vec<InstanceIssues> {
+    let mut report = Vec::new();
+
+    for referent in refs {
+        if dom.get_by_ref(*referent).is_none() {
+            continue;
+        }
+
+        for instance in dom.descendants_of(*referent) {
+            let issues = validate_instance(database, instance);
+
+            if !issues.is_empty() {
+                report.push(InstanceIssues {
+                    referent: instance.referent(),
+                    full_name: dom.full_name_of(instance.referent()),
+                    issues,
+                });
+            }
+        }
+    }
+
+    report
+}
+
+fn validate_instance(database: &ReflectionDatabase, instance: &Instance) -> Vec<ValidationIssue> {
+    if !database.classes.contains_key(instance.class.as_str()) {
+        return vec![ValidationIssue::UnknownClass];
+    }
+
+    // Properties are stored in a hash map, so they're sorted to keep the
+    // report stable.
+    let mut properties: Vec<(&Symbol, &Variant)> = instance.properties.iter().collect();
+    properties.sort_unstable_by_key(|(property, _)| property.as_str());
+
+    let mut issues = Vec::new();
+
+    for (property, value) in properties {
+        let property = property.clone();
+        let descriptors = match find_property_descriptors(database, &instance.class, &property) {
+            Some(descriptors) => descriptors,
+            None => {
+                issues.push(ValidationIssue::UnknownProperty { property });
+                continue;
+            }
+        };
+
+        // Properties that don't serialize are never written, so their values
+        // don't matter.
+        let mut serialized = match descriptors.serialized {
+            Some(serialized) => serialized,
+            None => continue,
+        };
+        let mut value = Cow::Borrowed(value);
+
+        // Migrated properties are written as the property they migrate to, the
+        // same way the serializer handles them.
+        if let PropertyKind::Canonical {
+            serialization: PropertySerialization::Migrate(migration),
+        } = &serialized.kind
+        {
+            let new_descriptors =
+                find_property_descriptors(database, &instance.class, &migration.new_property_name);
+
+            serialized = match new_descriptors.and_then(|descriptors| descriptors.serialized) {
+                Some(serialized) => serialized,
+                None => continue,
+            };
+
+            if let Ok(new_value) = migration.perform(&value) {
+                value = Cow::Owned(new_value);
+            }
+        }
+
+        let (expected, enum_name) = match &serialized.data_type {
+            DataType::Value(ty) => (*ty, None),
+            DataType::Enum(enum_name) => (VariantType::Enum, Some(enum_name)),
+            _ => continue,
+        };
+
+        let ty = match Type::from_rbx_type(expected) {
+            Some(ty) => ty,
+            None => {
+                issues.push(ValidationIssue::UnsupportedType {
+                    property,
+                    ty: expected,
+                });
+                continue;
+            }
+        };
+
+        if !can_write_as(ty, &value) {
+            issues.push(ValidationIssue::WrongType {
+                property,
+                expected,
+                actual: value.ty(),
+            });
+            continue;
+        }
+
+        let enum_value = match value.as_ref() {
+            Variant::Enum(value) => value.to_u32(),
+            Variant::EnumItem(EnumItem { value, .. }) => *value,
+            _ => continue,
+        };
+
+        // Enums missing from the database can't be checked.
+        let enum_descriptor = match enum_name.and_then(|name| database.enums.get(name.as_ref())) {
+            Some(descriptor) => descriptor,
+            None => continue,
+        };
+
+        if !enum_descriptor
+            .items
+            .values()
+            .any(|item| *item == enum_value)
+        {
+            issues.push(ValidationIssue::EnumOutOfRange {
+                property,
+                enum_name: enum_descriptor.name.to_string(),
+                value: enum_value,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Returns whether the serializer can write `value` into a property of type
+/// `ty`. Besides values of the same type, the serializer also converts a few
+/// types into ones that are stored the same way.
+fn can_write_as(ty: Type, value: &Variant) -> bool {
+    match (ty, value) {
+        (Type::String, Variant::Attributes(_) | Variant::SharedString(_))
+        | (Type::Float64, Variant::Float32(_))
+        | (Type::Enum, Variant::EnumItem(_))
+        | (Type::Color3uint8, Variant::Color3(_))
+        | (Type::Int64, Variant::Int32(_)) => true,
+        _ => Type::from_rbx_type(value.ty()) == Some(ty),
+    }
+}
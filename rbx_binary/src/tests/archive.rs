@@ -0,0 +1,106 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::to_writer;
+
+/// Ensures that models written to an archive can be listed and opened in any
+/// order, and that malformed archives are rejected.
+#[test]
+fn archive() {
+    use std::io::{Cursor, Read};
+
+    use crate::{ArchiveError, ArchiveReader, ArchiveWriter};
+
+    let models: Vec<_> = ["Tree", "Rock", "Bush"]
+        .iter()
+        .map(|&name| {
+            WeakDom::new(
+                InstanceBuilder::new("Model")
+                    .with_name(name)
+                    .with_child(InstanceBuilder::new("Part")),
+            )
+        })
+        .collect();
+
+    let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+    for model in &models {
+        let name = model.root().name.as_str();
+        writer.add(name, model, &[model.root_ref()]).unwrap();
+    }
+
+    let mut serialized = Vec::new();
+    to_writer(&mut serialized, &models[0], &[models[0].root_ref()]).unwrap();
+    writer.add_serialized("Copy", &serialized).unwrap();
+
+    assert!(matches!(
+        writer.add("Rock", &models[1], &[models[1].root_ref()]),
+        Err(ArchiveError::DuplicateEntry { name }) if name == "Rock"
+    ));
+
+    let archive = writer.finish().unwrap();
+    let mut reader = ArchiveReader::new(Cursor::new(archive.as_slice())).unwrap();
+
+    let names: Vec<_> = reader.entries().iter().map(|entry| &entry.name).collect();
+    assert_eq!(names, ["Tree", "Rock", "Bush", "Copy"]);
+    assert_eq!(reader.entry("Copy").unwrap().len, serialized.len() as u64);
+
+    for name in ["Bush", "Tree", "Rock"] {
+        let dom = reader.open(name).unwrap();
+        let model = dom.get_by_ref(dom.root().children()[0]).unwrap();
+        assert_eq!(model.name, name);
+        assert_eq!(model.children().len(), 1);
+    }
+
+    let mut copy = Vec::new();
+    reader
+        .open_serialized("Copy")
+        .unwrap()
+        .read_to_end(&mut copy)
+        .unwrap();
+    assert_eq!(copy, serialized);
+
+    assert!(matches!(
+        reader.open("Missing"),
+        Err(ArchiveError::UnknownEntry { .. })
+    ));
+
+    assert!(matches!(
+        ArchiveReader::new(Cursor::new(serialized.as_slice())),
+        Err(ArchiveError::NotAnArchive)
+    ));
+
+    // A manifest that points past its own start is rejected.
+    let mut bad_offset = archive.clone();
+    let trailer = bad_offset.len() - 16;
+    bad_offset[trailer..trailer + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(matches!(
+        ArchiveReader::new(Cursor::new(bad_offset.as_slice())),
+        Err(ArchiveError::MalformedManifest)
+    ));
+}
+
+/// Ensures that archives are laid out exactly as `ArchiveWriter`'s docs
+/// describe.
+#[test]
+fn archive_layout() {
+    use crate::ArchiveWriter;
+
+    let model = b"model bytes";
+    let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+    writer.add_serialized("A", model).unwrap();
+    let archive = writer.finish().unwrap();
+
+    let mut expected = Vec::new();
+    expected.extend_from_slice(b"<rbxarc!");
+    expected.extend_from_slice(&0u16.to_le_bytes());
+    expected.extend_from_slice(model);
+    let manifest_offset = expected.len() as u64;
+    expected.extend_from_slice(&1u32.to_le_bytes());
+    expected.extend_from_slice(&1u32.to_le_bytes());
+    expected.extend_from_slice(b"A");
+    expected.extend_from_slice(&10u64.to_le_bytes());
+    expected.extend_from_slice(&(model.len() as u64).to_le_bytes());
+    expected.extend_from_slice(&manifest_offset.to_le_bytes());
+    expected.extend_from_slice(b"<rbxarc!");
+
+    assert_eq!(archive, expected);
+}
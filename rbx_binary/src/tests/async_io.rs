@@ -0,0 +1,78 @@
+use rbx_dom_weak::{DomViewer, InstanceBuilder, WeakDom};
+
+use crate::{
+    from_async_reader, from_reader, to_async_writer, to_writer, DecodeConfig, Deserializer,
+};
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+fn test_dom() -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("Folder").with_children([
+            InstanceBuilder::new("StringValue")
+                .with_name("Greeting")
+                .with_property("Value", "Hello, world!"),
+            InstanceBuilder::new("Folder")
+                .with_child(InstanceBuilder::new("IntValue").with_property("Value", 5i64)),
+        ]),
+    )
+}
+
+/// Decoding from an `AsyncRead` should give the same result as decoding
+/// from a `Read`.
+#[test]
+fn async_read_matches_sync() {
+    let dom = test_dom();
+    let mut encoded = Vec::new();
+    to_writer(&mut encoded, &dom, &[dom.root_ref()]).unwrap();
+
+    let sync_decoded = from_reader(encoded.as_slice()).unwrap();
+    let async_decoded = block_on(from_async_reader(encoded.as_slice())).unwrap();
+
+    assert_eq!(
+        format!("{:?}", DomViewer::new().view_children(&async_decoded)),
+        format!("{:?}", DomViewer::new().view_children(&sync_decoded)),
+    );
+    assert_eq!(
+        async_decoded.instance_byte_sizes,
+        sync_decoded.instance_byte_sizes
+    );
+}
+
+#[test]
+fn async_read_truncated() {
+    let dom = test_dom();
+    let mut encoded = Vec::new();
+    to_writer(&mut encoded, &dom, &[dom.root_ref()]).unwrap();
+
+    for len in [0, 20, 40, encoded.len() - 1] {
+        let result = block_on(from_async_reader(&encoded[..len]));
+        assert!(result.is_err(), "decoding {} bytes should fail", len);
+    }
+
+    // Files missing only part of their END chunk can be read anyway.
+    let deserializer = Deserializer::new().config(DecodeConfig::new().with_allow_truncated(true));
+    assert!(block_on(deserializer.deserialize_async(&encoded[..40])).is_err());
+    let decoded = block_on(deserializer.deserialize_async(&encoded[..encoded.len() - 1])).unwrap();
+    assert_eq!(decoded.descendants().count(), 5);
+}
+
+/// Encoding to an `AsyncWrite` should produce exactly the same bytes as
+/// encoding to a `Write`.
+#[test]
+fn async_write_matches_sync() {
+    let dom = test_dom();
+
+    let mut sync_encoded = Vec::new();
+    to_writer(&mut sync_encoded, &dom, &[dom.root_ref()]).unwrap();
+
+    let mut async_encoded = Vec::new();
+    block_on(to_async_writer(&mut async_encoded, &dom, &[dom.root_ref()])).unwrap();
+
+    assert_eq!(async_encoded, sync_encoded);
+}
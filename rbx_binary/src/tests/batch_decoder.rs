@@ -0,0 +1,69 @@
+use rbx_dom_weak::{types::SharedString, InstanceBuilder, WeakDom};
+
+use crate::{CompressionType, Deserializer, Serializer};
+
+/// Ensures that files decoded one after another by a `BatchDecoder` come out
+/// the same as files decoded on their own, including after a file fails to
+/// decode and after the decoder's caches are cleared.
+#[test]
+fn batch_decoder() {
+    let mesh = SharedString::new(b"shared mesh data".to_vec());
+    let files: Vec<Vec<u8>> = [
+        CompressionType::Lz4,
+        CompressionType::Zstd,
+        CompressionType::None,
+        CompressionType::Zstd,
+    ]
+    .iter()
+    .enumerate()
+    .map(|(version, &compression)| {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder").with_children([
+                InstanceBuilder::new("Model").with_property("ModelMeshData", mesh.clone()),
+                InstanceBuilder::new("Model").with_property(
+                    "ModelMeshData",
+                    SharedString::new(format!("version {version}").into_bytes()),
+                ),
+                InstanceBuilder::new("StringValue")
+                    .with_name(format!("Value{version}"))
+                    .with_property("Value", "a".repeat(100 * version)),
+            ]),
+        );
+
+        let mut buffer = Vec::new();
+        Serializer::new()
+            .compression_type(compression)
+            .serialize(&mut buffer, &dom, &[dom.root_ref()])
+            .unwrap();
+        buffer
+    })
+    .collect();
+
+    let contents = |dom: &WeakDom| -> Vec<_> {
+        dom.descendants()
+            .map(|instance| {
+                let properties: Vec<_> = instance
+                    .sorted_properties_iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                (instance.class.clone(), instance.name.clone(), properties)
+            })
+            .collect()
+    };
+
+    let mut decoder = crate::BatchDecoder::new();
+    for (i, file) in files.iter().enumerate() {
+        let expected = Deserializer::new().deserialize(file.as_slice()).unwrap();
+        let decoded = decoder.decode(file.as_slice()).unwrap();
+        assert_eq!(contents(&decoded), contents(&expected));
+
+        if i == 1 {
+            assert!(decoder.decode(&file[..file.len() - 20]).is_err());
+        } else if i == 2 {
+            decoder.clear_caches();
+        }
+    }
+
+    let (_, info) = decoder.decode_with_info(files[1].as_slice()).unwrap();
+    assert_eq!(info.compression(), Some(CompressionType::Zstd));
+}
@@ -0,0 +1,115 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+use crate::{from_reader, to_writer, Serializer};
+
+/// Ensures that decoding fills in the map `WeakDom::get_by_binary_referent`
+/// looks instances up in.
+#[test]
+fn binary_referent_lookup() {
+    let dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+        InstanceBuilder::new("Part").with_name("A"),
+        InstanceBuilder::new("Part").with_name("B"),
+    ]));
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &dom, &[dom.root_ref()]).unwrap();
+    let decoded = from_reader(buffer.as_slice()).unwrap();
+
+    let referents = decoded.instance_binary_referents.as_ref().unwrap();
+    assert_eq!(referents.len(), 3);
+
+    for instance in decoded.descendants().skip(1) {
+        let binary_referent = instance.binary_referent().unwrap();
+        assert_eq!(referents[&binary_referent], instance.referent());
+        assert_eq!(
+            decoded
+                .get_by_binary_referent(binary_referent)
+                .map(|found| found.referent()),
+            Some(instance.referent())
+        );
+    }
+}
+
+/// Ensures that instances keep the referents they were read with when
+/// `preserve_binary_referents` is set, and that new instances fill the gaps.
+#[test]
+fn preserve_binary_referents() {
+    let referents = |dom: &WeakDom| -> Vec<(String, Option<i32>)> {
+        dom.descendants()
+            .skip(1)
+            .map(|instance| (instance.name.clone(), instance.binary_referent()))
+            .collect()
+    };
+    let encode = |dom: &WeakDom| {
+        let mut buffer = Vec::new();
+        Serializer::new()
+            .preserve_binary_referents(true)
+            .serialize(&mut buffer, dom, dom.root().children())
+            .expect("failed to encode model");
+        from_reader(buffer.as_slice()).unwrap()
+    };
+
+    let children = ["A", "B", "C", "D"]
+        .iter()
+        .map(|name| InstanceBuilder::new("Folder").with_name(*name));
+    let tree = WeakDom::new(InstanceBuilder::new("Model").with_children(children));
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+    let mut decoded = from_reader(buffer.as_slice()).unwrap();
+    let model_ref = decoded.root().children()[0];
+    let model = decoded.get_by_ref(model_ref).unwrap();
+    let original: Vec<i32> = model
+        .children()
+        .iter()
+        .map(|child| {
+            decoded
+                .get_by_ref(*child)
+                .unwrap()
+                .binary_referent()
+                .unwrap()
+        })
+        .collect();
+    let b_ref = model.children()[1];
+    let b_referent = original[1];
+
+    decoded.destroy(b_ref);
+    decoded.insert(
+        model_ref,
+        InstanceBuilder::new("WeldConstraint")
+            .with_name("New")
+            .with_property("Part0", model_ref),
+    );
+    // A referent that's already taken is replaced too.
+    decoded.insert(
+        model_ref,
+        InstanceBuilder::new("Folder")
+            .with_name("Clash")
+            .with_binary_referent(original[0]),
+    );
+
+    let model_referent = decoded.binary_referent_of(model_ref);
+    let reencoded = encode(&decoded);
+    let mut expected = vec![
+        ("Model".to_owned(), model_referent),
+        ("A".to_owned(), Some(original[0])),
+        ("C".to_owned(), Some(original[2])),
+        ("D".to_owned(), Some(original[3])),
+    ];
+    let mut fill =
+        (0..).filter(|referent| !expected.iter().any(|(_, taken)| *taken == Some(*referent)));
+    let new_referent = fill.next();
+    let clash_referent = fill.next();
+    assert_eq!(new_referent, Some(b_referent));
+    expected.push(("New".to_owned(), new_referent));
+    expected.push(("Clash".to_owned(), clash_referent));
+    assert_eq!(referents(&reencoded), expected);
+
+    // Refs still point at the right instances after being renumbered.
+    let new_model = reencoded.root().children()[0];
+    let weld = reencoded
+        .descendants()
+        .find(|instance| instance.name == "New")
+        .unwrap();
+    assert_eq!(weld.properties.get("Part0"), Some(&Variant::Ref(new_model)));
+}
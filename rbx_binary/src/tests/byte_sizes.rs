@@ -0,0 +1,108 @@
+use rbx_dom_weak::{
+    types::{Attributes, CFrame, Color3uint8, Font, Matrix3, Tags, Variant, Vector3},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{from_reader, SerializedSize, Serializer};
+
+/// Ensures that the byte sizes reported by the serializer line up with the
+/// sizes the deserializer measures when reading the same file back.
+#[test]
+fn byte_sizes_match_deserializer() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children([
+            InstanceBuilder::new("StringValue")
+                .with_name("Short")
+                .with_property("Value", "Hi"),
+            InstanceBuilder::new("StringValue")
+                .with_name("Long")
+                .with_property("Value", "a".repeat(1000)),
+            InstanceBuilder::new("Part")
+                .with_name("Part")
+                .with_property("Size", Vector3::new(1.0, 2.0, 3.0))
+                .with_property("Tags", Tags::from(vec!["One".to_owned(), "Two".to_owned()]))
+                .with_property("FontFace", Font::default()),
+        ]),
+    );
+
+    let mut buffer = Vec::new();
+    let sizes = Serializer::new()
+        .serialize_with_byte_sizes(&mut buffer, &tree, &[tree.root_ref()])
+        .expect("failed to encode model");
+
+    assert_eq!(sizes.len(), 4);
+
+    let size_of = |name: &str| {
+        let instance = tree.descendants().find(|inst| inst.name == name).unwrap();
+        sizes[&instance.referent()]
+    };
+    assert!(size_of("Long") >= size_of("Short") + 990);
+
+    let decoded = from_reader(buffer.as_slice()).expect("failed to decode model");
+    let decoded_sizes = decoded.instance_byte_sizes.as_ref().unwrap();
+
+    for instance in decoded.descendants().skip(1) {
+        assert_eq!(
+            instance.byte_size(decoded_sizes),
+            size_of(&instance.name),
+            "byte size mismatch for {}",
+            instance.name
+        );
+        assert_eq!(
+            instance.decoded_byte_size(),
+            Some(size_of(&instance.name)),
+            "stored byte size mismatch for {}",
+            instance.name
+        );
+    }
+}
+
+/// Ensures that the sizes estimated for instances that were never serialized
+/// match the byte sizes the serializer reports once they are.
+#[test]
+fn serialized_size_estimate() {
+    let mut attributes = Attributes::new();
+    attributes.insert("Health".to_owned(), Variant::Float64(100.0));
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_property("Attributes", attributes)
+            .with_children([
+                InstanceBuilder::new("StringValue")
+                    .with_name("Value")
+                    .with_property("Value", "a".repeat(100)),
+                InstanceBuilder::new("Part")
+                    .with_name("Part")
+                    .with_property(
+                        "CFrame",
+                        CFrame::new(Vector3::new(1.0, 2.0, 3.0), Matrix3::identity()),
+                    )
+                    .with_property("Color3uint8", Color3uint8::new(1, 2, 3))
+                    .with_property("Tags", Tags::from(vec!["One".to_owned(), "Two".to_owned()]))
+                    .with_property("FontFace", Font::default()),
+            ]),
+    );
+
+    let sizes = Serializer::new()
+        .serialize_with_byte_sizes(Vec::new(), &tree, &[tree.root_ref()])
+        .unwrap();
+
+    for instance in tree.descendants() {
+        assert_eq!(
+            instance.serialized_size_estimate(),
+            sizes[&instance.referent()],
+            "estimated size mismatch for {}",
+            instance.name
+        );
+    }
+
+    assert_eq!(Variant::Bool(true).serialized_size_estimate(), 1);
+    assert_eq!(
+        Variant::CFrame(CFrame::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Matrix3::identity()
+        ))
+        .serialized_size_estimate(),
+        13
+    );
+}
@@ -0,0 +1,55 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{from_reader, ChildOrder, Serializer};
+
+/// Ensures that sorting children makes the output independent of the order
+/// children were inserted in.
+#[test]
+fn child_order() {
+    let build = |names: &[&str]| {
+        let children = names.iter().map(|name| {
+            InstanceBuilder::new("Folder")
+                .with_name(*name)
+                .with_child(InstanceBuilder::new("StringValue").with_name(*name))
+                .with_child(InstanceBuilder::new("BoolValue").with_name(*name))
+        });
+        WeakDom::new(InstanceBuilder::new("Folder").with_children(children))
+    };
+    let encode = |tree: &WeakDom, child_order: ChildOrder| {
+        let mut buffer = Vec::new();
+        Serializer::new()
+            .child_order(child_order)
+            .serialize(&mut buffer, tree, &[tree.root_ref()])
+            .expect("failed to encode model");
+        buffer
+    };
+
+    let first = build(&["B", "C", "A"]);
+    let second = build(&["C", "A", "B"]);
+    assert_ne!(
+        encode(&first, ChildOrder::Dom),
+        encode(&second, ChildOrder::Dom)
+    );
+    assert_eq!(
+        encode(&first, ChildOrder::Name),
+        encode(&second, ChildOrder::Name)
+    );
+
+    let decoded = from_reader(encode(&first, ChildOrder::Class).as_slice()).unwrap();
+    let root = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let folders: Vec<&str> = root
+        .children()
+        .iter()
+        .map(|child| decoded.get_by_ref(*child).unwrap().name.as_str())
+        .collect();
+    assert_eq!(folders, ["A", "B", "C"]);
+
+    let values: Vec<&str> = decoded
+        .get_by_ref(root.children()[0])
+        .unwrap()
+        .children()
+        .iter()
+        .map(|child| decoded.get_by_ref(*child).unwrap().class.as_str())
+        .collect();
+    assert_eq!(values, ["BoolValue", "StringValue"]);
+}
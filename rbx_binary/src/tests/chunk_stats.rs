@@ -0,0 +1,43 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{CompressionType, Deserializer, Serializer};
+
+/// Ensures that the chunk stats returned alongside a dom describe the same
+/// chunks as `FileInfo`, and only time decompression for compressed chunks.
+#[test]
+fn chunk_stats() {
+    let tree =
+        WeakDom::new(InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("StringValue").with_property("Value", "a".repeat(4096)),
+        ));
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression_type(CompressionType::Zstd)
+        .serialize(&mut buffer, &tree, &[tree.root_ref()])
+        .unwrap();
+
+    let (dom, stats) = Deserializer::new()
+        .deserialize_with_chunk_stats(buffer.as_slice())
+        .unwrap();
+    let (_, info) = Deserializer::new()
+        .deserialize_with_info(buffer.as_slice())
+        .unwrap();
+
+    assert_eq!(dom.descendants().count(), 3);
+    let chunks: Vec<_> = stats.iter().map(|chunk| chunk.info.clone()).collect();
+    assert_eq!(chunks, info.chunks);
+
+    let end = stats.last().unwrap();
+    assert_eq!(end.info.name, "END");
+    assert_eq!(end.info.compression_ratio(), 1.0);
+    assert_eq!(end.decompress_time, std::time::Duration::ZERO);
+
+    // The repeated string compresses well.
+    let value = stats
+        .iter()
+        .filter(|chunk| chunk.info.name == "PROP")
+        .max_by_key(|chunk| chunk.info.len)
+        .unwrap();
+    assert_eq!(value.info.compression, CompressionType::Zstd);
+    assert!(value.info.compression_ratio() > 10.0);
+}
@@ -0,0 +1,113 @@
+use rbx_dom_weak::{
+    types::{Ref, SharedString, Variant, Vector3},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{to_writer, Deserializer};
+
+/// Ensures that content hashes are only computed when asked for, stay the
+/// same when a file is read again, and change only for instances that were
+/// changed.
+#[test]
+fn content_hashes() {
+    let target = InstanceBuilder::new("Part").with_name("Target");
+    let target_ref = target.referent();
+    let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+        target,
+        InstanceBuilder::new("ObjectValue").with_ref_property("Value", target_ref),
+        InstanceBuilder::new("StringValue").with_property("Value", "before"),
+    ]));
+    let refs = dom.root().children().to_vec();
+
+    let hashes = |dom: &WeakDom| {
+        let mut buffer = Vec::new();
+        to_writer(&mut buffer, dom, &[dom.root_ref()]).unwrap();
+
+        let decoded = Deserializer::new()
+            .content_hashes(true)
+            .deserialize(buffer.as_slice())
+            .unwrap();
+        let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+        let hashes: Vec<u64> = folder
+            .children()
+            .iter()
+            .map(|child| decoded.get_instance_content_hash(*child).unwrap())
+            .collect();
+
+        (buffer, hashes)
+    };
+
+    let (buffer, before) = hashes(&dom);
+    assert_eq!(hashes(&dom).1, before);
+    assert_eq!(
+        Deserializer::new()
+            .deserialize(buffer.as_slice())
+            .unwrap()
+            .instance_content_hashes,
+        None
+    );
+
+    dom.get_by_ref_mut(refs[2])
+        .unwrap()
+        .properties
+        .insert("Value".into(), Variant::String("after".into()));
+    let (_, after) = hashes(&dom);
+
+    assert_eq!(after[0], before[0]);
+    assert_eq!(after[1], before[1]);
+    assert_ne!(after[2], before[2]);
+}
+
+/// Ensures that content hashes follow the encoding documented in
+/// `deserializer::content_hash`, which is meant to stay the same between
+/// Rust versions, and that they tell apart floats that only differ in their
+/// bits.
+#[test]
+fn content_hash_stability() {
+    let hash = |dom: &WeakDom| {
+        let mut buffer = Vec::new();
+        to_writer(&mut buffer, dom, dom.root().children()).unwrap();
+
+        let decoded = Deserializer::new()
+            .content_hashes(true)
+            .deserialize(buffer.as_slice())
+            .unwrap();
+        decoded
+            .get_instance_content_hash(decoded.root().children()[0])
+            .unwrap()
+    };
+
+    // `Ref` and `SharedString` values are hashed with their bincode tags,
+    // which must match their positions in `Variant`.
+    assert_eq!(
+        &bincode::serialize(&Variant::Ref(Ref::none())).unwrap()[..4],
+        &20u32.to_le_bytes()
+    );
+    assert_eq!(
+        &bincode::serialize(&Variant::SharedString(SharedString::new(Vec::new()))).unwrap()[..4],
+        &23u32.to_le_bytes()
+    );
+
+    let part = WeakDom::new(
+        InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("Part")
+                .with_name("Pinned")
+                .with_property("Anchored", true)
+                .with_property("Size", Vector3::new(1.0, 2.0, 3.0))
+                .with_property("Tag", SharedString::new(b"shared".to_vec()))
+                .with_property("Weight", 0.5f64),
+        ),
+    );
+    assert_eq!(hash(&part), 0x054a_53f0_8527_102a);
+
+    let number = |value: f64| {
+        WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_child(InstanceBuilder::new("NumberValue").with_property("Value", value)),
+        )
+    };
+    let nan = f64::NAN;
+    let other_nan = f64::from_bits(nan.to_bits() | 1);
+    assert_ne!(hash(&number(nan)), hash(&number(other_nan)));
+    assert_ne!(hash(&number(0.0)), hash(&number(-0.0)));
+}
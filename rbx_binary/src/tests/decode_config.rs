@@ -0,0 +1,177 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{from_reader, roundtrip::compare, to_writer, CompressionType, Deserializer};
+
+use super::repair_parents::folders_with_parents;
+
+/// Ensures that each of `DecodeConfig`'s strictness toggles rejects the files
+/// it's meant to, and that the default config still reads them.
+#[test]
+fn decode_config() {
+    use std::io::Write;
+
+    use crate::{
+        chunk::ChunkBuilder,
+        core::{RbxWriteExt, FILE_MAGIC_HEADER, FILE_SIGNATURE},
+        DecodeConfig,
+    };
+
+    // Builds a file of folders with the given referents, all at the top of
+    // the tree, with a property of type `0xfe` if `unknown_type` is set.
+    let folders = |referents: &[i32], unknown_type: bool| {
+        let mut buffer = Vec::new();
+        buffer.write_all(FILE_MAGIC_HEADER).unwrap();
+        buffer.write_all(FILE_SIGNATURE).unwrap();
+        buffer.write_le_u16(0).unwrap();
+        buffer.write_le_u32(1).unwrap();
+        buffer.write_le_u32(referents.len() as u32).unwrap();
+        buffer.write_all(&[0; 8]).unwrap();
+
+        let mut inst = ChunkBuilder::new(b"INST", CompressionType::None);
+        inst.write_le_u32(0).unwrap();
+        inst.write_string("Folder").unwrap();
+        inst.write_u8(0).unwrap();
+        inst.write_le_u32(referents.len() as u32).unwrap();
+        inst.write_referent_array(referents.iter().copied())
+            .unwrap();
+        inst.dump(&mut buffer).unwrap();
+
+        if unknown_type {
+            let mut prop = ChunkBuilder::new(b"PROP", CompressionType::None);
+            prop.write_le_u32(0).unwrap();
+            prop.write_string("Mystery").unwrap();
+            prop.write_u8(0xfe).unwrap();
+            prop.write_all(&[1, 2, 3, 4]).unwrap();
+            prop.dump(&mut buffer).unwrap();
+        }
+
+        let mut prnt = ChunkBuilder::new(b"PRNT", CompressionType::None);
+        prnt.write_u8(0).unwrap();
+        let mut unique = referents.to_vec();
+        unique.dedup();
+        prnt.write_le_u32(unique.len() as u32).unwrap();
+        prnt.write_referent_array(unique.iter().copied()).unwrap();
+        prnt.write_referent_array(unique.iter().map(|_| -1))
+            .unwrap();
+        prnt.dump(&mut buffer).unwrap();
+
+        let mut end = ChunkBuilder::new(b"END\0", CompressionType::None);
+        end.write_all(b"</roblox>").unwrap();
+        end.dump(&mut buffer).unwrap();
+
+        buffer
+    };
+
+    let unknown_class = {
+        let dom = WeakDom::new(InstanceBuilder::new("NotARealClass"));
+        let mut buffer = Vec::new();
+        to_writer(&mut buffer, &dom, &[dom.root_ref()]).unwrap();
+        buffer
+    };
+    let trailing_data = {
+        let dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let mut buffer = Vec::new();
+        to_writer(&mut buffer, &dom, &[dom.root_ref()]).unwrap();
+        buffer.extend_from_slice(b"extra");
+        buffer
+    };
+
+    let cases = [
+        (
+            folders(&[0], true),
+            DecodeConfig::new().with_reject_unknown_types(true),
+            "Property Folder.Mystery has unknown type ID 0xfe",
+        ),
+        (
+            unknown_class,
+            DecodeConfig::new().with_reject_unknown_classes(true),
+            "Class NotARealClass is not in the reflection database",
+        ),
+        (
+            trailing_data,
+            DecodeConfig::new().with_reject_trailing_data(true),
+            "File has data after its END chunk",
+        ),
+        (
+            folders(&[0, 0], false),
+            DecodeConfig::new().with_reject_duplicate_referents(true),
+            "Referent 0 was declared by more than one instance",
+        ),
+        (
+            folders_with_parents(2, &[(0, -1), (1, 5)], false),
+            DecodeConfig::new().with_repair_parents(false),
+            "Invalid PRNT chunk: instance 1 was parented to instance 5, which was not declared",
+        ),
+    ];
+
+    for (file, config, message) in &cases {
+        Deserializer::new()
+            .deserialize(file.as_slice())
+            .unwrap_or_else(|error| panic!("{} with the default config: {}", message, error));
+
+        for config in [*config, DecodeConfig::strict()].iter() {
+            let error = Deserializer::new()
+                .config(*config)
+                .deserialize(file.as_slice())
+                .unwrap_err();
+            assert_eq!(error.to_string(), *message);
+        }
+    }
+
+    // Files without any of these problems are read in strict mode too.
+    let dom = Deserializer::new()
+        .config(DecodeConfig::strict())
+        .deserialize(folders(&[0, 1], false).as_slice())
+        .unwrap();
+    assert_eq!(dom.root().children().len(), 2);
+}
+
+/// Ensures that files cut off after their `PRNT` chunk can be read when
+/// truncated files are allowed, and that files cut off any earlier can't.
+#[test]
+fn allow_truncated() {
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("StringValue").with_property("Value", "kept")),
+    );
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &dom, &[dom.root_ref()]).unwrap();
+
+    let expected = from_reader(buffer.as_slice()).unwrap();
+
+    let info = crate::peek_stats(buffer.as_slice()).unwrap();
+    let mut offsets = Vec::new();
+    let mut offset = 32;
+    for chunk in &info.chunks {
+        offsets.push((chunk.name.clone(), offset));
+        offset += 16 + chunk.stored_len as usize;
+    }
+    let start_of = |name: &str| offsets.iter().find(|(n, _)| n == name).unwrap().1;
+    let prnt = start_of("PRNT");
+    let end = start_of("END");
+
+    let deserializer =
+        Deserializer::new().config(crate::DecodeConfig::new().with_allow_truncated(true));
+
+    let (_, info) = deserializer
+        .deserialize_with_info(buffer.as_slice())
+        .unwrap();
+    assert!(!info.truncated);
+
+    for len in [end, end + 5, buffer.len() - 1] {
+        let truncated = &buffer[..len];
+        assert!(Deserializer::new().deserialize(truncated).is_err());
+
+        let (decoded, info) = deserializer.deserialize_with_info(truncated).unwrap();
+        assert!(info.truncated, "{} bytes should be read as truncated", len);
+        assert!(compare(&expected, &decoded).is_empty());
+
+        let lazy = deserializer.deserialize_lazy(truncated).unwrap();
+        assert!(compare(&expected, &lazy.into_dom().unwrap()).is_empty());
+    }
+
+    for len in [prnt, prnt + 20] {
+        assert!(deserializer.deserialize(&buffer[..len]).is_err());
+        assert!(deserializer.deserialize_lazy(&buffer[..len]).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{from_reader, to_writer, Deserializer};
+
+#[test]
+fn decode_limits() {
+    use crate::{DecodeLimits, LimitExceeded};
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_child(
+        InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("StringValue").with_property("Value", "x".repeat(100)),
+        )),
+    ));
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+
+    let limit_exceeded = |limits: DecodeLimits| {
+        Deserializer::new()
+            .limits(limits)
+            .deserialize(buffer.as_slice())
+            .err()
+            .and_then(|err| err.limit_exceeded())
+    };
+
+    assert_eq!(limit_exceeded(DecodeLimits::untrusted()), None);
+    assert_eq!(
+        limit_exceeded(DecodeLimits::new().with_max_instances(3)),
+        Some(LimitExceeded::Instances { limit: 3 })
+    );
+    assert_eq!(
+        limit_exceeded(DecodeLimits::new().with_max_string_len(50)),
+        Some(LimitExceeded::StringLength { limit: 50 })
+    );
+    assert_eq!(
+        limit_exceeded(DecodeLimits::new().with_max_chunk_len(16)),
+        Some(LimitExceeded::ChunkLength { limit: 16 })
+    );
+    assert_eq!(
+        limit_exceeded(DecodeLimits::new().with_max_depth(3)),
+        Some(LimitExceeded::Depth { limit: 3 })
+    );
+    assert_eq!(limit_exceeded(DecodeLimits::new().with_max_depth(4)), None);
+
+    // A chunk that claims to decompress to almost 2 GiB is rejected before
+    // anything is allocated for it.
+    let mut bomb = buffer[..32].to_vec();
+    bomb.extend_from_slice(b"INST");
+    bomb.extend_from_slice(&16u32.to_le_bytes());
+    bomb.extend_from_slice(&i32::MAX.to_le_bytes());
+    bomb.extend_from_slice(&0u32.to_le_bytes());
+    bomb.extend_from_slice(&[0; 16]);
+
+    let error = Deserializer::new()
+        .limits(DecodeLimits::untrusted())
+        .deserialize(bomb.as_slice())
+        .unwrap_err();
+    assert_eq!(
+        error.limit_exceeded(),
+        Some(LimitExceeded::ChunkLength {
+            limit: 256 * 1024 * 1024
+        })
+    );
+
+    // Malformed chunk headers are errors rather than panics.
+    let mut reserved = bomb.clone();
+    reserved[32 + 12] = 1;
+    assert!(from_reader(reserved.as_slice()).is_err());
+}
@@ -0,0 +1,45 @@
+use rbx_dom_weak::{types::Vector3, InstanceBuilder, WeakDom};
+
+use crate::{CompressionType, Deserializer, Serializer};
+
+/// Ensures that estimating the size of a file matches what is actually
+/// written, for every type of compression.
+#[test]
+fn estimate_size() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("StringValue").with_property("Value", "a".repeat(500)))
+            .with_child(
+                InstanceBuilder::new("Part").with_property("Size", Vector3::new(1.0, 2.0, 3.0)),
+            ),
+    );
+
+    for compression in [
+        CompressionType::Lz4,
+        CompressionType::Zstd,
+        CompressionType::None,
+    ] {
+        let serializer = Serializer::new().compression_type(compression);
+        let estimate = serializer
+            .estimate_size(&tree, &[tree.root_ref()])
+            .expect("failed to estimate size");
+
+        let mut buffer = Vec::new();
+        serializer
+            .serialize(&mut buffer, &tree, &[tree.root_ref()])
+            .expect("failed to encode model");
+        assert_eq!(estimate.len, buffer.len() as u64);
+
+        let (_, info) = Deserializer::new()
+            .deserialize_with_info(buffer.as_slice())
+            .unwrap();
+        assert_eq!(estimate.chunks, info.chunks);
+    }
+
+    assert_eq!(
+        crate::estimate_size(&tree, &[tree.root_ref()]).unwrap(),
+        Serializer::new()
+            .estimate_size(&tree, &[tree.root_ref()])
+            .unwrap()
+    );
+}
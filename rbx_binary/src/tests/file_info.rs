@@ -0,0 +1,46 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{CompressionType, Deserializer, Serializer};
+
+/// Ensures that the deserializer reports how a file was stored.
+#[test]
+fn file_info() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("StringValue")),
+    );
+    let mut buffer = Vec::new();
+
+    Serializer::new()
+        .compression_type(CompressionType::Zstd)
+        .serialize(&mut buffer, &tree, &[tree.root_ref()])
+        .expect("failed to encode model");
+
+    let (_, info) = Deserializer::new()
+        .deserialize_with_info(buffer.as_slice())
+        .unwrap();
+
+    assert_eq!(info.version, 0);
+    assert_eq!(info.class_count, 2);
+    assert_eq!(info.instance_count, 2);
+    assert_eq!(info.compression(), Some(CompressionType::Zstd));
+
+    let names: Vec<&str> = info
+        .chunks
+        .iter()
+        .map(|chunk| chunk.name.as_str())
+        .collect();
+    assert_eq!(names.first(), Some(&"INST"));
+    assert_eq!(names.last(), Some(&"END"));
+    assert!(names.contains(&"PRNT"));
+
+    let end = info.chunks.last().unwrap();
+    assert_eq!(end.compression, CompressionType::None);
+    assert_eq!(
+        info.decompressed_size(),
+        info.chunks
+            .iter()
+            .map(|chunk| chunk.len as u64)
+            .sum::<u64>()
+    );
+    assert!(info.decompressed_size() > 0);
+}
@@ -0,0 +1,137 @@
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{from_reader, to_writer, Deserializer, InstanceAction, PropertyAction, Serializer};
+
+#[test]
+fn property_hook() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children([
+            InstanceBuilder::new("Part")
+                .with_property("Transparency", 2.0f32)
+                .with_property("Anchored", true)
+                .with_property("RuntimeOnly", true),
+            InstanceBuilder::new("Part")
+                .with_property("Transparency", 0.5f32)
+                .with_property("Anchored", false),
+            InstanceBuilder::new("NumberValue").with_property("Value", 2.0f64),
+        ]),
+    );
+
+    let serializer = Serializer::new()
+        .property_hook("Part", |_, name, value| match (name, value) {
+            ("Transparency", Variant::Float32(value)) => {
+                PropertyAction::Replace(Variant::Float32(value.clamp(0.0, 1.0)))
+            }
+            ("RuntimeOnly", _) => PropertyAction::Drop,
+            _ => PropertyAction::Keep,
+        })
+        .property_hook("Part", |instance, name, _| {
+            if name == "Anchored"
+                && instance.properties.get("Anchored") == Some(&Variant::Bool(true))
+            {
+                PropertyAction::Drop
+            } else {
+                PropertyAction::Keep
+            }
+        });
+
+    let mut buffer = Vec::new();
+    serializer
+        .serialize(&mut buffer, &tree, &[tree.root_ref()])
+        .unwrap();
+
+    let decoded = from_reader(buffer.as_slice()).unwrap();
+    let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let children: Vec<_> = folder
+        .children()
+        .iter()
+        .map(|referent| decoded.get_by_ref(*referent).unwrap())
+        .collect();
+
+    assert_eq!(
+        children[0].properties.get("Transparency"),
+        Some(&Variant::Float32(1.0))
+    );
+    assert_eq!(children[0].properties.get("RuntimeOnly"), None);
+    // Anchored was dropped from the first part but not the second, so the
+    // first is written with the default value.
+    assert_eq!(
+        children[0].properties.get("Anchored"),
+        Some(&Variant::Bool(false))
+    );
+    assert_eq!(
+        children[1].properties.get("Transparency"),
+        Some(&Variant::Float32(0.5))
+    );
+    assert_eq!(
+        children[2].properties.get("Value"),
+        Some(&Variant::Float64(2.0))
+    );
+}
+
+#[test]
+fn instance_hook() {
+    let debug = InstanceBuilder::new("Part")
+        .with_name("Debug")
+        .with_child(InstanceBuilder::new("Folder"));
+    let debug_ref = debug.referent();
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children([
+            InstanceBuilder::new("Part")
+                .with_property("Transparency", 2.0f32)
+                .with_property("RuntimeOnly", true),
+            debug,
+            InstanceBuilder::new("ObjectValue").with_property("Value", debug_ref),
+        ]),
+    );
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+
+    let decoded = Deserializer::new()
+        .instance_hook("Part", |part| {
+            if let Some(Variant::Float32(value)) = part.property("Transparency") {
+                let value = value.clamp(0.0, 1.0);
+                part.set_property("Transparency", value);
+            }
+            part.remove_property("RuntimeOnly");
+
+            if part.name() == "Debug" {
+                InstanceAction::Skip
+            } else {
+                InstanceAction::Keep
+            }
+        })
+        .instance_hook("Part", |part| {
+            // Skipped instances aren't passed to later hooks.
+            assert_ne!(part.name(), "Debug");
+            part.set_name("Renamed");
+            InstanceAction::Keep
+        })
+        .deserialize(buffer.as_slice())
+        .unwrap();
+
+    let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let children: Vec<_> = folder
+        .children()
+        .iter()
+        .map(|referent| decoded.get_by_ref(*referent).unwrap())
+        .collect();
+    assert_eq!(children.len(), 2);
+    assert_eq!(decoded.descendants().count(), 4);
+
+    assert_eq!(children[0].name, "Renamed");
+    assert_eq!(
+        children[0].properties.get("Transparency"),
+        Some(&Variant::Float32(1.0))
+    );
+    assert_eq!(children[0].properties.get("RuntimeOnly"), None);
+    assert_eq!(
+        children[1].properties.get("Value"),
+        Some(&Variant::Ref(Ref::none()))
+    );
+}
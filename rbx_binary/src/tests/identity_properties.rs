@@ -0,0 +1,40 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+use crate::{from_reader, to_writer, Deserializer};
+
+/// Ensures that Name and ClassName properties exposed by the deserializer are
+/// never written as properties, and that the instances' fields are written
+/// instead.
+#[test]
+fn identity_properties() {
+    let dom = WeakDom::new(InstanceBuilder::new("Folder").with_name("Original"));
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &dom, &[dom.root_ref()]).unwrap();
+
+    let mut decoded = Deserializer::new()
+        .identity_properties(true)
+        .deserialize(buffer.as_slice())
+        .unwrap();
+    let folder_ref = decoded.root().children()[0];
+    let folder = decoded.get_by_ref(folder_ref).unwrap();
+    assert_eq!(
+        folder.properties.get("ClassName"),
+        Some(&Variant::from("Folder"))
+    );
+
+    // Changing the copies doesn't change what's written.
+    decoded.set_property(folder_ref, "Name", "Changed");
+    decoded.set_property(folder_ref, "ClassName", "Model");
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &decoded, &[folder_ref]).unwrap();
+
+    let reencoded = from_reader(buffer.as_slice()).unwrap();
+    let folder = reencoded
+        .get_by_ref(reencoded.root().children()[0])
+        .unwrap();
+    assert_eq!(folder.name, "Original");
+    assert_eq!(folder.class, "Folder");
+    assert!(!folder.properties.contains_key("Name"));
+    assert!(!folder.properties.contains_key("ClassName"));
+}
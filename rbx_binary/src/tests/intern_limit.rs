@@ -0,0 +1,45 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{Deserializer, Serializer};
+
+/// Ensures that the deserializer's intern limit counts each distinct class
+/// and property name in a file once, and only for that file.
+#[test]
+fn intern_limit() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("InternLimitClass")
+                .with_property("InternLimitProperty", "Hello")
+                .with_child(
+                    InstanceBuilder::new("InternLimitClass")
+                        .with_property("InternLimitProperty", "Hi"),
+                ),
+        ),
+    );
+    let mut buffer = Vec::new();
+
+    Serializer::new()
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .expect("failed to encode model");
+
+    // The file holds the class name and two property names: Name and
+    // InternLimitProperty.
+    let result = Deserializer::new()
+        .intern_limit(2)
+        .deserialize(buffer.as_slice());
+    assert!(result.is_err());
+
+    let dom = Deserializer::new()
+        .intern_limit(3)
+        .deserialize(buffer.as_slice())
+        .unwrap();
+    let instance = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    assert_eq!(instance.class, "InternLimitClass");
+
+    // The limit is for each file, so reading the same file again doesn't
+    // change what counts towards it.
+    let result = Deserializer::new()
+        .intern_limit(2)
+        .deserialize(buffer.as_slice());
+    assert!(result.is_err());
+}
@@ -0,0 +1,115 @@
+use std::io::Write;
+
+use rbx_dom_weak::{
+    types::{Ref, SharedString},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{
+    chunk::{chunk_extent, peek_chunk_data, Chunk, ChunkBuilder},
+    from_reader,
+    roundtrip::compare,
+    CompressionType, Deserializer, Serializer,
+};
+
+/// Ensures that a `LazyDom` only decodes the classes that are accessed, and
+/// that once everything is decoded it's the same as deserializing the file in
+/// one go, including `Ref` properties that point between classes.
+#[test]
+fn lazy_dom() {
+    let part = InstanceBuilder::new("Part")
+        .with_name("Door")
+        .with_property("Anchored", true);
+    let part_ref = part.referent();
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Model")
+            .with_ref_property("PrimaryPart", part_ref)
+            .with_children([
+                part,
+                InstanceBuilder::new("Folder").with_child(
+                    InstanceBuilder::new("Model")
+                        .with_name("Inner")
+                        .with_property("ModelMeshData", SharedString::new(b"mesh".to_vec())),
+                ),
+            ]),
+    );
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression_type(CompressionType::Zstd)
+        .serialize(&mut buffer, &dom, &[dom.root_ref()])
+        .unwrap();
+    let decoded = from_reader(buffer.as_slice()).unwrap();
+
+    let deserializer = Deserializer::new();
+    let mut lazy = deserializer.deserialize_lazy(buffer.as_slice()).unwrap();
+    assert!(lazy.is_materialized(lazy.root_ref()));
+
+    let model = lazy.children_of(lazy.root_ref())[0];
+    let children = lazy.children_of(model).to_vec();
+    let (part, folder) = (children[0], children[1]);
+    assert_eq!(lazy.class_of(folder).unwrap(), "Folder");
+    assert_eq!(lazy.parent_of(part), Some(model));
+    assert!(lazy.get_by_ref(Ref::new()).unwrap().is_none());
+
+    let model_instance = lazy.get_by_ref(model).unwrap().unwrap();
+    assert_eq!(model_instance.get_ref("PrimaryPart"), Some(part));
+    assert!(lazy.is_materialized(lazy.children_of(folder)[0]));
+    assert!(!lazy.is_materialized(part));
+    assert!(!lazy.is_materialized(folder));
+
+    let lazy = lazy.into_dom().unwrap();
+    assert!(compare(&decoded, &lazy).is_empty());
+}
+
+/// Ensures that the type ID of each `PROP` chunk, which is all a `LazyDom`
+/// decompresses of it when the file is opened, is the same as the start of
+/// the fully decompressed chunk.
+#[test]
+fn peek_type_ids() {
+    let dom = WeakDom::new(InstanceBuilder::new("Model").with_children([
+        InstanceBuilder::new("Part").with_property("Anchored", true),
+        InstanceBuilder::new("Folder"),
+    ]));
+
+    let mut chunks = Vec::new();
+    for compression in [
+        CompressionType::None,
+        CompressionType::Lz4,
+        CompressionType::Zstd,
+    ] {
+        let mut buffer = Vec::new();
+        Serializer::new()
+            .compression_type(compression)
+            .serialize(&mut buffer, &dom, &[dom.root_ref()])
+            .unwrap();
+
+        // Chunks start after the 32 byte file header.
+        let mut offset = 32;
+        loop {
+            let (name, len) = chunk_extent(&buffer[offset..]).unwrap();
+            chunks.push(buffer[offset..offset + len].to_vec());
+            offset += len;
+
+            if &name == b"END\0" {
+                break;
+            }
+        }
+    }
+
+    // LZ4 stores a run of the same byte as one literal followed by a match
+    // that overlaps itself.
+    for data in [vec![0; 64], vec![1, 2, 1, 2, 1, 2, 1, 2, 3, 4, 5, 6, 7, 8]] {
+        let mut builder = ChunkBuilder::new(b"PROP", CompressionType::Lz4);
+        builder.write_all(&data).unwrap();
+        let mut chunk = Vec::new();
+        builder.dump(&mut chunk).unwrap();
+        chunks.push(chunk);
+    }
+
+    for chunk in chunks {
+        let decoded = Chunk::decode(chunk.as_slice()).unwrap();
+        let peeked: [u8; 4] = peek_chunk_data(&chunk).unwrap();
+        assert_eq!(&peeked[..], &decoded.data[..4]);
+    }
+}
@@ -0,0 +1,54 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{CompressionType, Serializer};
+
+/// Ensures that serializing with a `max_size` fails without writing anything
+/// when the output is too large, and names the largest instances.
+#[test]
+fn max_size() {
+    let big = InstanceBuilder::new("StringValue")
+        .with_name("Big")
+        .with_property("Value", "x".repeat(5000));
+    let big_ref = big.referent();
+    let medium = InstanceBuilder::new("StringValue")
+        .with_name("Medium")
+        .with_property("Value", "x".repeat(1000));
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(medium)
+            .with_child(InstanceBuilder::new("Folder").with_child(big)),
+    );
+
+    let serializer = || Serializer::new().compression_type(CompressionType::None);
+
+    let mut unlimited = Vec::new();
+    serializer()
+        .serialize(&mut unlimited, &tree, &[tree.root_ref()])
+        .unwrap();
+    let len = unlimited.len() as u64;
+
+    let mut buffer = Vec::new();
+    let error = serializer()
+        .max_size(len - 1)
+        .serialize(&mut buffer, &tree, &[tree.root_ref()])
+        .unwrap_err();
+    assert!(buffer.is_empty());
+
+    let exceeded = error.size_limit_exceeded().unwrap();
+    assert_eq!(exceeded.limit, len - 1);
+    assert_eq!(exceeded.len, len);
+    assert_eq!(exceeded.largest.len(), 4);
+    assert_eq!(exceeded.largest[0].referent, big_ref);
+    assert_eq!(exceeded.largest[0].full_name, "Folder.Folder.Big");
+    assert_eq!(exceeded.largest[1].full_name, "Folder.Medium");
+    assert!(exceeded.largest[0].len > 5000);
+    assert!(error.to_string().contains("Folder.Folder.Big ("));
+
+    // Output that fits is written as usual.
+    let mut buffer = Vec::new();
+    serializer()
+        .max_size(len)
+        .serialize(&mut buffer, &tree, &[tree.root_ref()])
+        .unwrap();
+    assert_eq!(buffer, unlimited);
+}
@@ -0,0 +1,19 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{from_reader, to_writer};
+
+/// Ensures that the metadata of a dom is written to the META chunk and read
+/// back out.
+#[test]
+fn metadata_round_trip() {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder"));
+    tree.metadata_mut().set_explicit_auto_joints(true);
+    tree.metadata_mut().insert("SomethingElse", "Hello");
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, &[tree.root_ref()]).expect("failed to encode model");
+
+    let decoded = from_reader(buffer.as_slice()).unwrap();
+    assert_eq!(decoded.metadata(), tree.metadata());
+    assert_eq!(decoded.metadata().explicit_auto_joints(), Some(true));
+}
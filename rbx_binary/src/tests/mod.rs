@@ -1,5 +1,43 @@
+mod archive;
+#[cfg(feature = "tokio")]
+mod async_io;
+#[cfg(feature = "zstd")]
+mod batch_decoder;
+mod binary_referents;
+mod byte_sizes;
+mod child_order;
+#[cfg(feature = "zstd")]
+mod chunk_stats;
+mod content_hashes;
 mod core_read_write;
+mod decode_config;
+mod decode_limits;
+#[cfg(feature = "zstd")]
+mod estimate_size;
+#[cfg(feature = "zstd")]
+mod file_info;
+mod hooks;
+mod identity_properties;
+mod intern_limit;
+#[cfg(feature = "zstd")]
+mod lazy_dom;
+mod max_size;
+mod metadata;
 mod models;
+#[cfg(feature = "zstd")]
+mod peek_stats;
 mod places;
+mod repair_parents;
+mod roundtrip;
+mod scan_schema;
 mod serializer;
+mod shared_strings;
+mod skip_classes;
+#[cfg(feature = "tracing")]
+mod spans;
+mod stable_ids;
+#[cfg(feature = "zstd")]
+mod transcode;
 mod util;
+mod validate;
+mod verify;
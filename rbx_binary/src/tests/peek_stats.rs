@@ -0,0 +1,36 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{CompressionType, Deserializer, Serializer};
+
+/// Ensures that peeking at a file reports the same chunks as decoding it, for
+/// every type of compression, and that a truncated file is an error.
+#[test]
+fn peek_stats() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("StringValue").with_property("Value", "a".repeat(500)))
+            .with_child(InstanceBuilder::new("Part")),
+    );
+
+    for compression in [
+        CompressionType::Lz4,
+        CompressionType::Zstd,
+        CompressionType::None,
+    ] {
+        let mut buffer = Vec::new();
+        Serializer::new()
+            .compression_type(compression)
+            .serialize(&mut buffer, &tree, &[tree.root_ref()])
+            .unwrap();
+
+        let peeked = crate::peek_stats(buffer.as_slice()).unwrap();
+        let (_, info) = Deserializer::new()
+            .deserialize_with_info(buffer.as_slice())
+            .unwrap();
+        assert_eq!(peeked, info);
+        assert_eq!(peeked.instance_count, 3);
+        assert_eq!(peeked.compression(), Some(compression));
+
+        assert!(crate::peek_stats(&buffer[..buffer.len() - 20]).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use rbx_dom_weak::WeakDom;
+
+use crate::{CompressionType, Deserializer};
+
+/// Builds a file made of `count` folders with referents `0..count`, parented
+/// according to `parents`. If `parents_first` is set, the `PRNT` chunk is
+/// written before the `INST` chunk that declares the folders.
+pub(super) fn folders_with_parents(
+    count: i32,
+    parents: &[(i32, i32)],
+    parents_first: bool,
+) -> Vec<u8> {
+    use std::io::Write;
+
+    use crate::{
+        chunk::ChunkBuilder,
+        core::{RbxWriteExt, FILE_MAGIC_HEADER, FILE_SIGNATURE},
+    };
+
+    let mut buffer = Vec::new();
+    buffer.write_all(FILE_MAGIC_HEADER).unwrap();
+    buffer.write_all(FILE_SIGNATURE).unwrap();
+    buffer.write_le_u16(0).unwrap();
+    buffer.write_le_u32(1).unwrap();
+    buffer.write_le_u32(count as u32).unwrap();
+    buffer.write_all(&[0; 8]).unwrap();
+
+    let mut inst = ChunkBuilder::new(b"INST", CompressionType::None);
+    inst.write_le_u32(0).unwrap();
+    inst.write_string("Folder").unwrap();
+    inst.write_u8(0).unwrap();
+    inst.write_le_u32(count as u32).unwrap();
+    inst.write_referent_array(0..count).unwrap();
+
+    let mut prnt = ChunkBuilder::new(b"PRNT", CompressionType::None);
+    prnt.write_u8(0).unwrap();
+    prnt.write_le_u32(parents.len() as u32).unwrap();
+    prnt.write_referent_array(parents.iter().map(|(referent, _)| *referent))
+        .unwrap();
+    prnt.write_referent_array(parents.iter().map(|(_, parent)| *parent))
+        .unwrap();
+
+    if parents_first {
+        prnt.dump(&mut buffer).unwrap();
+        inst.dump(&mut buffer).unwrap();
+    } else {
+        inst.dump(&mut buffer).unwrap();
+        prnt.dump(&mut buffer).unwrap();
+    }
+
+    let mut end = ChunkBuilder::new(b"END\0", CompressionType::None);
+    end.write_all(b"</roblox>").unwrap();
+    end.dump(&mut buffer).unwrap();
+
+    buffer
+}
+
+/// Returns the referents from the file of every instance in `dom`, along with
+/// the referent of its parent, or -1 for instances at the top of the file.
+fn binary_parents(dom: &WeakDom) -> Vec<(i32, i32)> {
+    let mut parents: Vec<(i32, i32)> = dom
+        .descendants()
+        .filter(|instance| instance.referent() != dom.root_ref())
+        .map(|instance| {
+            let parent = dom.get_by_ref(instance.parent()).unwrap();
+            (
+                instance.binary_referent().unwrap(),
+                parent.binary_referent().unwrap_or(-1),
+            )
+        })
+        .collect();
+    parents.sort();
+    parents
+}
+
+#[test]
+fn repair_parents() {
+    use crate::ParentProblem;
+
+    // The number of folders, the parents given to them, the parents they
+    // should have after being repaired, and the problems that were repaired.
+    type Parents = &'static [(i32, i32)];
+    let cases: &[(i32, Parents, Parents, &[ParentProblem])] = &[
+        (
+            2,
+            &[(0, -1), (1, 5)],
+            &[(0, -1), (1, -1)],
+            &[ParentProblem::UnknownParent {
+                referent: 1,
+                parent: 5,
+            }],
+        ),
+        (
+            2,
+            &[(0, -1), (1, 0), (1, -1)],
+            &[(0, -1), (1, 0)],
+            &[ParentProblem::MultipleParents {
+                referent: 1,
+                parent: -1,
+            }],
+        ),
+        (
+            3,
+            &[(0, 1), (1, 0), (2, 1)],
+            &[(0, -1), (1, 0), (2, 1)],
+            &[ParentProblem::Cycle { referent: 0 }],
+        ),
+        (
+            2,
+            &[(0, -1), (1, 1)],
+            &[(0, -1), (1, -1)],
+            &[ParentProblem::Cycle { referent: 1 }],
+        ),
+        (
+            2,
+            &[(0, -1)],
+            &[(0, -1), (1, -1)],
+            &[ParentProblem::Unparented { referent: 1 }],
+        ),
+        (
+            1,
+            &[(0, -1), (7, 0)],
+            &[(0, -1)],
+            &[ParentProblem::UnknownInstance { referent: 7 }],
+        ),
+    ];
+
+    for (count, parents, expected, problems) in cases {
+        let buffer = folders_with_parents(*count, parents, false);
+
+        assert!(
+            Deserializer::new()
+                .repair_parents(false)
+                .deserialize(buffer.as_slice())
+                .is_err(),
+            "{:?} should not deserialize without repairs",
+            parents
+        );
+
+        // Files are repaired by default.
+        let (dom, info) = Deserializer::new()
+            .deserialize_with_info(buffer.as_slice())
+            .unwrap();
+        assert_eq!(binary_parents(&dom), *expected, "repairing {:?}", parents);
+        assert_eq!(info.parent_repairs, *problems, "repairing {:?}", parents);
+    }
+
+    // Parents may be given before the instances are declared.
+    let buffer = folders_with_parents(2, &[(1, 0), (0, -1)], true);
+    let dom = Deserializer::new().deserialize(buffer.as_slice()).unwrap();
+    assert_eq!(binary_parents(&dom), [(0, -1), (1, 0)]);
+}
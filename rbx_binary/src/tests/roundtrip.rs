@@ -0,0 +1,63 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+use crate::{roundtrip::compare, to_writer, DifferenceKind};
+
+#[test]
+fn roundtrip() {
+    let part = InstanceBuilder::new("Part").with_property("Transparency", 0.5f32);
+    let part_ref = part.referent();
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Model")
+            .with_property("PrimaryPart", part_ref)
+            .with_property("Value", f64::NAN)
+            .with_child(part),
+    );
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+    let report = crate::roundtrip(buffer.as_slice()).unwrap();
+    assert!(report.is_identical(), "{:?}", report.differences);
+
+    let other_part = InstanceBuilder::new("Part").with_property("Transparency", 1.0f32);
+    let other = WeakDom::new(
+        InstanceBuilder::new("Model")
+            .with_name("Renamed")
+            .with_property("Value", f64::NAN)
+            .with_property("Locked", true)
+            .with_children([other_part, InstanceBuilder::new("Part")]),
+    );
+    let other_part_ref = other.root().children()[0];
+    let mut other = other;
+    other
+        .get_by_ref_mut(other.root_ref())
+        .unwrap()
+        .properties
+        .insert("PrimaryPart".into(), Variant::Ref(other_part_ref));
+
+    let kinds: Vec<DifferenceKind> = compare(&tree, &other)
+        .into_iter()
+        .map(|difference| difference.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            DifferenceKind::Name {
+                before: "Model".to_owned(),
+                after: "Renamed".to_owned(),
+            },
+            DifferenceKind::PropertyAdded {
+                property: "Locked".into(),
+                value: Variant::Bool(true),
+            },
+            DifferenceKind::Children {
+                before: 1,
+                after: 2,
+            },
+            DifferenceKind::Property {
+                property: "Transparency".into(),
+                before: Variant::Float32(0.5),
+                after: Variant::Float32(1.0),
+            },
+        ]
+    );
+}
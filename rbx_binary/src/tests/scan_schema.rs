@@ -0,0 +1,39 @@
+use rbx_dom_weak::{types::VariantType, InstanceBuilder, WeakDom};
+
+use crate::to_writer;
+
+#[test]
+fn scan_schema() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+        InstanceBuilder::new("Part").with_property("Anchored", true),
+        InstanceBuilder::new("Part").with_property("Anchored", false),
+        InstanceBuilder::new("StringValue").with_property("Value", "x".repeat(100)),
+    ]));
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, tree.root().children()).unwrap();
+    let schema = crate::scan_schema(buffer.as_slice()).unwrap();
+
+    let mut classes: Vec<(&str, usize)> = schema
+        .classes
+        .iter()
+        .map(|class| (class.name.as_str(), class.instance_count))
+        .collect();
+    classes.sort_unstable();
+    assert_eq!(classes, [("Part", 2), ("StringValue", 1)]);
+    assert_eq!(schema.info.instance_count, 3);
+
+    let anchored = schema.class("Part").unwrap().property("Anchored").unwrap();
+    assert_eq!(anchored.ty, Some(VariantType::Bool));
+    assert_eq!(anchored.size, 2);
+
+    let value = schema
+        .class("StringValue")
+        .unwrap()
+        .property("Value")
+        .unwrap();
+    assert_eq!(value.ty, Some(VariantType::BinaryString));
+    assert_eq!(value.size, 104);
+    assert!(schema.class("StringValue").unwrap().size() > 104);
+    assert!(schema.class("Folder").is_none());
+}
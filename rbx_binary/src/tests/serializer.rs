@@ -1,11 +1,12 @@
 use rbx_dom_weak::{
     types::{
-        BrickColor, CFrame, Color3, Color3uint8, Enum, Font, Ref, Region3, SharedString, Vector3,
+        BrickColor, CFrame, Color3, Color3uint8, Enum, Font, FontStyle, FontWeight, Matrix3, Ref,
+        Region3, SecurityCapabilities, SharedString, UniqueId, Variant, Vector3,
     },
     InstanceBuilder, WeakDom,
 };
 
-use crate::{text_deserializer::DecodedModel, to_writer};
+use crate::{from_reader, text_deserializer::DecodedModel, to_writer};
 
 /// A basic test to make sure we can serialize the simplest instance: a Folder.
 #[test]
@@ -229,3 +230,121 @@ fn does_not_serialize() {
     let decoded = DecodedModel::from_reader(buf.as_slice());
     insta::assert_yaml_snapshot!(decoded);
 }
+
+/// Ensures that serializing an instance deep inside of a dom leaves out its
+/// ancestors, writes it once even if its descendants are also selected, and
+/// drops Ref properties that point outside of it.
+#[test]
+fn interior_subtree() {
+    let outside = InstanceBuilder::new("Part").with_name("Outside");
+    let outside_ref = outside.referent();
+    let base = InstanceBuilder::new("Part")
+        .with_name("Base")
+        .with_property("Anchored", true);
+    let base_ref = base.referent();
+    let model = InstanceBuilder::new("Model")
+        .with_property("PrimaryPart", base_ref)
+        .with_child(base)
+        .with_child(InstanceBuilder::new("ObjectValue").with_property("Value", outside_ref));
+    let model_ref = model.referent();
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("DataModel").with_child(
+            InstanceBuilder::new("Workspace")
+                .with_child(outside)
+                .with_child(model),
+        ),
+    );
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, &[model_ref, base_ref]).expect("failed to encode model");
+
+    let decoded = from_reader(buffer.as_slice()).unwrap();
+    assert_eq!(decoded.root().children().len(), 1);
+
+    let model = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    assert_eq!(model.class, "Model");
+    assert_eq!(model.children().len(), 2);
+    assert_eq!(decoded.descendants().count(), 4);
+
+    let base = decoded.get_by_ref(model.children()[0]).unwrap();
+    assert_eq!(
+        model.properties.get("PrimaryPart"),
+        Some(&Variant::Ref(base.referent()))
+    );
+
+    let value = decoded.get_by_ref(model.children()[1]).unwrap();
+    assert_eq!(
+        value.properties.get("Value"),
+        Some(&Variant::Ref(Ref::none()))
+    );
+}
+
+/// Ensures that the newer property types that Studio writes survive a round
+/// trip with their values intact.
+#[test]
+fn newer_property_types() {
+    let font = Font {
+        family: "rbxasset://fonts/families/GothamSSm.json".to_owned(),
+        weight: FontWeight::Bold,
+        style: FontStyle::Italic,
+        cached_face_id: Some("rbxasset://fonts/GothamSSm-BoldItalic.otf".to_owned()),
+    };
+    let pivot = CFrame::new(
+        Vector3::new(1.0, 2.0, 3.0),
+        Matrix3::new(
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ),
+    );
+    let unique_id = UniqueId::new(0x0048_15fc, 0x02e9_c68d, 0x44b1_88da_ce63_2b47);
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Model")
+            .with_property("WorldPivotData", Variant::OptionalCFrame(Some(pivot)))
+            .with_property("UniqueId", unique_id)
+            .with_property("Capabilities", SecurityCapabilities::from_bits(0b1011))
+            .with_child(
+                InstanceBuilder::new("TextLabel")
+                    .with_property("FontFace", font.clone())
+                    .with_property("UniqueId", UniqueId::nil()),
+            )
+            .with_child(
+                InstanceBuilder::new("Model")
+                    .with_property("WorldPivotData", Variant::OptionalCFrame(None)),
+            ),
+    );
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, &[tree.root_ref()]).expect("failed to encode model");
+    let decoded = from_reader(buffer.as_slice()).unwrap();
+
+    let model = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let label = decoded.get_by_ref(model.children()[0]).unwrap();
+    let empty_model = decoded.get_by_ref(model.children()[1]).unwrap();
+
+    assert_eq!(
+        model.properties.get("WorldPivotData"),
+        Some(&Variant::OptionalCFrame(Some(pivot)))
+    );
+    assert_eq!(
+        model.properties.get("UniqueId"),
+        Some(&Variant::UniqueId(unique_id))
+    );
+    assert_eq!(
+        model.properties.get("Capabilities"),
+        Some(&Variant::SecurityCapabilities(
+            SecurityCapabilities::from_bits(0b1011)
+        ))
+    );
+    assert_eq!(label.properties.get("FontFace"), Some(&Variant::Font(font)));
+    assert_eq!(
+        label.properties.get("UniqueId"),
+        Some(&Variant::UniqueId(UniqueId::nil()))
+    );
+    assert_eq!(
+        empty_model.properties.get("WorldPivotData"),
+        Some(&Variant::OptionalCFrame(None))
+    );
+}
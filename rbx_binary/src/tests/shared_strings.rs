@@ -0,0 +1,111 @@
+use rbx_dom_weak::{
+    types::{BinaryString, SharedString, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{
+    from_reader,
+    text_deserializer::{DecodedChunk, DecodedModel},
+    to_writer, CompressionType, Serializer,
+};
+
+/// Ensures that promoting repeated BinaryStrings to SharedStrings shrinks the
+/// output, and that promoted properties which aren't SharedStrings in the
+/// reflection database can still be written.
+#[test]
+fn promoted_shared_strings() {
+    let blob: Vec<u8> = (0..4096).map(|i| (i * 7 % 251) as u8).collect();
+    let build = || {
+        let parts = (0..20).map(|_| {
+            InstanceBuilder::new("Part").with_property("MeshBlob", BinaryString::from(blob.clone()))
+        });
+        let value = InstanceBuilder::new("BinaryStringValue")
+            .with_property("Value", BinaryString::from(blob.clone()));
+
+        WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_children(parts)
+                .with_child(value),
+        )
+    };
+    let encode = |tree: &WeakDom| {
+        let mut buffer = Vec::new();
+        Serializer::new()
+            .compression_type(CompressionType::None)
+            .serialize(&mut buffer, tree, &[tree.root_ref()])
+            .expect("failed to encode model");
+        buffer
+    };
+
+    let plain = build();
+    let mut promoted = build();
+    assert_eq!(promoted.promote_shared_strings(|_, _, _| true), 21);
+
+    let plain_buffer = encode(&plain);
+    let promoted_buffer = encode(&promoted);
+    assert!(promoted_buffer.len() * 5 < plain_buffer.len());
+
+    let decoded = from_reader(promoted_buffer.as_slice()).unwrap();
+    for instance in decoded.descendants() {
+        match instance.class.as_str() {
+            "Part" => assert!(matches!(
+                instance.properties.get("MeshBlob"),
+                Some(Variant::SharedString(value)) if value.data() == blob.as_slice()
+            )),
+            "BinaryStringValue" => assert_eq!(
+                instance.properties.get("Value"),
+                Some(&Variant::BinaryString(blob.clone().into()))
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Ensures that SharedStrings that no PROP chunk refers to are left out of the
+/// SSTR chunk, and that they're reported by `dangling_shared_strings`.
+#[test]
+fn dangling_shared_strings() {
+    let kept = SharedString::new(b"kept mesh data".to_vec());
+    // Source is serialized as a String, so a SharedString in it is written
+    // inline rather than referring to the SSTR chunk.
+    let inline = SharedString::new(vec![b'a'; 4096]);
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+        InstanceBuilder::new("ModuleScript").with_property("Source", inline.clone()),
+        InstanceBuilder::new("Model").with_property("ModelMeshData", kept.clone()),
+    ]));
+
+    let dangling = Serializer::new()
+        .dangling_shared_strings(&tree, tree.root().children())
+        .unwrap();
+
+    // The default value of ModelMeshData is dropped too, since every Model
+    // has a value for it.
+    let default = SharedString::new(Vec::new());
+    let mut expected = vec![inline.hash(), default.hash()];
+    expected.sort();
+    assert_eq!(dangling.dropped, expected);
+    assert_eq!(dangling.reclaimed_bytes, (20 + 4096) + 20);
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, tree.root().children()).unwrap();
+
+    let decoded = DecodedModel::from_reader(buffer.as_slice());
+    let shared_strings: Vec<_> = decoded
+        .chunks
+        .iter()
+        .filter_map(|chunk| match chunk {
+            DecodedChunk::Sstr { entries, .. } => Some(entries),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    assert_eq!(shared_strings, [&kept]);
+
+    let dom = from_reader(buffer.as_slice()).unwrap();
+    let script = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    assert_eq!(
+        script.properties.get("Source"),
+        Some(&Variant::String("a".repeat(4096)))
+    );
+}
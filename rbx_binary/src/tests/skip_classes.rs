@@ -0,0 +1,52 @@
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{to_writer, Deserializer};
+
+#[test]
+fn skip_classes() {
+    let handle = InstanceBuilder::new("Part").with_name("Handle");
+    let handle_ref = handle.referent();
+    let part = InstanceBuilder::new("Part").with_name("Kept");
+    let part_ref = part.referent();
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children([
+            InstanceBuilder::new("Script")
+                .with_property("Source", "print('hi')")
+                .with_child(handle),
+            part,
+            InstanceBuilder::new("ObjectValue").with_property("Value", handle_ref),
+            InstanceBuilder::new("ObjectValue").with_property("Value", part_ref),
+        ]),
+    );
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+
+    let decoded = Deserializer::new()
+        .skip_classes(["Script", "NotInTheFile"])
+        .deserialize(buffer.as_slice())
+        .unwrap();
+
+    let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let children: Vec<_> = folder
+        .children()
+        .iter()
+        .map(|referent| decoded.get_by_ref(*referent).unwrap())
+        .collect();
+    let classes: Vec<&str> = children.iter().map(|child| child.class.as_str()).collect();
+    assert_eq!(classes, ["Part", "ObjectValue", "ObjectValue"]);
+    assert_eq!(decoded.descendants().count(), 5);
+
+    assert_eq!(
+        children[1].properties.get("Value"),
+        Some(&Variant::Ref(Ref::none()))
+    );
+    assert_eq!(
+        children[2].properties.get("Value"),
+        Some(&Variant::Ref(children[0].referent()))
+    );
+}
@@ -0,0 +1,129 @@
+use std::{
+    fmt::{self, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+use crate::{from_reader, to_writer};
+
+/// A subscriber that records the name and fields of every span created while
+/// it's active, like `class class=Part instances=1`.
+#[derive(Default)]
+struct SpanRecorder {
+    spans: Arc<Mutex<Vec<String>>>,
+    next_id: AtomicU64,
+}
+
+struct FieldWriter<'a>(&'a mut String);
+
+impl Visit for FieldWriter<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        write!(self.0, " {}={:?}", field.name(), value).unwrap();
+    }
+}
+
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let mut description = span.metadata().name().to_owned();
+        span.record(&mut FieldWriter(&mut description));
+        self.spans.lock().unwrap().push(description);
+
+        span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+/// Encoding and decoding should enter a span for the whole file, every
+/// chunk, and every class and property.
+#[test]
+fn spans_per_chunk_and_class() {
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Part").with_property("Anchored", true)),
+    );
+
+    let recorder = SpanRecorder::default();
+    let spans = Arc::clone(&recorder.spans);
+
+    tracing::subscriber::with_default(recorder, || {
+        let mut buffer = Vec::new();
+        to_writer(&mut buffer, &dom, &[dom.root_ref()]).unwrap();
+        from_reader(buffer.as_slice()).unwrap();
+    });
+
+    let spans = spans.lock().unwrap();
+    let count = |prefix: &str| spans.iter().filter(|span| span.starts_with(prefix)).count();
+
+    assert_eq!(count("rbx_binary::serialize roots=1"), 1);
+    assert_eq!(count("rbx_binary::deserialize"), 1);
+    assert_eq!(count("chunk name=INST"), 4);
+    assert_eq!(count("chunk name=PRNT"), 2);
+    assert_eq!(count("class class=Part"), 2);
+    assert_eq!(count("property class=Part property=Anchored"), 2);
+}
+
+/// The async functions should enter the same whole-file spans as the sync
+/// ones, while keeping their futures `Send`.
+#[cfg(feature = "tokio")]
+#[test]
+fn async_spans() {
+    use crate::{from_async_reader, to_async_writer};
+
+    fn assert_send<T: Send>(value: T) -> T {
+        value
+    }
+
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Part").with_property("Anchored", true)),
+    );
+
+    let recorder = SpanRecorder::default();
+    let spans = Arc::clone(&recorder.spans);
+
+    tracing::subscriber::with_default(recorder, || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let mut buffer = Vec::new();
+        runtime
+            .block_on(assert_send(to_async_writer(
+                &mut buffer,
+                &dom,
+                &[dom.root_ref()],
+            )))
+            .unwrap();
+        runtime
+            .block_on(assert_send(from_async_reader(buffer.as_slice())))
+            .unwrap();
+    });
+
+    let spans = spans.lock().unwrap();
+    let count = |prefix: &str| spans.iter().filter(|span| span.starts_with(prefix)).count();
+
+    assert_eq!(count("rbx_binary::serialize roots=1"), 1);
+    assert_eq!(count("rbx_binary::deserialize"), 1);
+    assert_eq!(count("property class=Part property=Anchored"), 2);
+}
@@ -0,0 +1,23 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{from_reader, to_writer};
+
+/// Ensures that stable IDs given to instances are read back from the file
+/// they were written to.
+#[test]
+fn stable_ids() {
+    let part = InstanceBuilder::new("Part");
+    let part_ref = part.referent();
+    let mut tree = WeakDom::new(InstanceBuilder::new("Model").with_child(part));
+    tree.set_stable_id(tree.root_ref(), "model");
+    tree.set_stable_id(part_ref, "part");
+
+    let mut buffer = Vec::new();
+    to_writer(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+
+    let decoded = from_reader(buffer.as_slice()).unwrap();
+    let model = decoded.root().children()[0];
+    let part = decoded.get_by_ref(model).unwrap().children()[0];
+    assert_eq!(decoded.stable_id_of(model), Some("model"));
+    assert_eq!(decoded.find_by_stable_id("part"), Some(part));
+}
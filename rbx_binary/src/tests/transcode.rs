@@ -0,0 +1,403 @@
+use rbx_dom_weak::{
+    types::{
+        Axes, BrickColor, CFrame, Color3, ColorSequence, ColorSequenceKeypoint, Content,
+        CustomPhysicalProperties, Enum, Faces, Font, Matrix3, NumberRange, NumberSequence,
+        NumberSequenceKeypoint, PhysicalProperties, Rect, Ref, SecurityCapabilities, SharedString,
+        UDim, UDim2, UniqueId, Variant, VariantType, Vector2, Vector3, Vector3int16,
+    },
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{from_reader, to_writer, CompressionType, DecodeLimits, LimitExceeded, TranscodeError};
+
+#[test]
+fn transcode() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children([
+            InstanceBuilder::new("Script")
+                .with_property("Source", "print('one')")
+                .with_property("Disabled", true),
+            InstanceBuilder::new("Script").with_property("Source", "print('two')"),
+            InstanceBuilder::new("StringValue").with_property("Value", "kept"),
+        ]),
+    );
+
+    let mut input = Vec::new();
+    to_writer(&mut input, &tree, &[tree.root_ref()]).unwrap();
+
+    let mut output = Vec::new();
+    crate::Transcoder::new()
+        .compression_type(CompressionType::Zstd)
+        .remove_property("Script", "Disabled")
+        .map_strings("Script", "Source", |source| source.make_ascii_uppercase())
+        .transcode(input.as_slice(), &mut output)
+        .unwrap();
+
+    let report = crate::verify(output.as_slice());
+    assert!(report.is_valid(), "{:?}", report);
+
+    let decoded = from_reader(output.as_slice()).unwrap();
+    let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let children: Vec<_> = folder
+        .children()
+        .iter()
+        .map(|referent| decoded.get_by_ref(*referent).unwrap())
+        .collect();
+
+    assert_eq!(
+        children[0].properties.get("Source"),
+        Some(&Variant::String("PRINT('ONE')".to_owned()))
+    );
+    assert_eq!(children[0].properties.get("Disabled"), None);
+    assert_eq!(
+        children[1].properties.get("Source"),
+        Some(&Variant::String("PRINT('TWO')".to_owned()))
+    );
+    assert_eq!(
+        children[2].properties.get("Value"),
+        Some(&Variant::String("kept".to_owned()))
+    );
+
+    let mut unchanged = Vec::new();
+    crate::Transcoder::new()
+        .transcode(input.as_slice(), &mut unchanged)
+        .unwrap();
+    assert_eq!(unchanged, input);
+}
+
+/// Every type of value, under names the reflection database doesn't know, so
+/// each is stored as the type it's given as.
+fn values_of_every_type(target: Ref) -> Vec<(&'static str, Variant)> {
+    vec![
+        ("String", "text".into()),
+        ("Bool", true.into()),
+        ("Int32", 7i32.into()),
+        ("Float32", 0.5f32.into()),
+        ("Float64", 0.25f64.into()),
+        ("UDim", UDim::new(0.5, 3).into()),
+        (
+            "UDim2",
+            UDim2::new(UDim::new(0.5, 3), UDim::new(0.25, -4)).into(),
+        ),
+        ("Faces", Faces::from_bits(5).unwrap().into()),
+        ("Axes", Axes::from_bits(3).unwrap().into()),
+        ("BrickColor", BrickColor::BrightRed.into()),
+        ("Color3", Color3::new(0.1, 0.2, 0.3).into()),
+        ("Vector2", Vector2::new(1.0, 2.0).into()),
+        ("Vector3", Vector3::new(1.0, 2.0, 3.0).into()),
+        (
+            "CFrame",
+            CFrame::new(Vector3::new(1.0, 2.0, 3.0), Matrix3::identity()).into(),
+        ),
+        ("Enum", Enum::from_u32(4).into()),
+        ("Ref", target.into()),
+        ("Vector3int16", Vector3int16::new(1, -2, 3).into()),
+        (
+            "NumberSequence",
+            NumberSequence {
+                keypoints: vec![
+                    NumberSequenceKeypoint::new(0.0, 1.0, 0.0),
+                    NumberSequenceKeypoint::new(1.0, 2.0, 0.5),
+                ],
+            }
+            .into(),
+        ),
+        (
+            "ColorSequence",
+            ColorSequence {
+                keypoints: vec![
+                    ColorSequenceKeypoint::new(0.0, Color3::new(1.0, 0.0, 0.0)),
+                    ColorSequenceKeypoint::new(1.0, Color3::new(0.0, 0.0, 1.0)),
+                ],
+            }
+            .into(),
+        ),
+        ("NumberRange", NumberRange::new(1.0, 2.0).into()),
+        (
+            "Rect",
+            Rect::new(Vector2::new(0.0, 1.0), Vector2::new(2.0, 3.0)).into(),
+        ),
+        (
+            "PhysicalProperties",
+            PhysicalProperties::Custom(CustomPhysicalProperties {
+                density: 1.0,
+                friction: 0.5,
+                elasticity: 0.25,
+                friction_weight: 2.0,
+                elasticity_weight: 3.0,
+            })
+            .into(),
+        ),
+        ("Int64", 1i64.into()),
+        ("SharedString", SharedString::new(b"shared".to_vec()).into()),
+        (
+            "OptionalCFrame",
+            Variant::OptionalCFrame(Some(CFrame::new(
+                Vector3::new(4.0, 5.0, 6.0),
+                Matrix3::identity(),
+            ))),
+        ),
+        ("UniqueIdValue", UniqueId::new(1, 2, 3).into()),
+        ("Font", Font::default().into()),
+        (
+            "SecurityCapabilities",
+            SecurityCapabilities::from_bits(6).into(),
+        ),
+        ("Content", Content::from_uri("rbxassetid://1").into()),
+    ]
+}
+
+#[test]
+fn transcode_retain_instances() {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder"));
+    let target = tree.insert(
+        tree.root_ref(),
+        InstanceBuilder::new("Model").with_name("Target"),
+    );
+    let removed_model = tree.insert(
+        tree.root_ref(),
+        InstanceBuilder::new("Model").with_name("Removed"),
+    );
+    tree.insert(
+        removed_model,
+        InstanceBuilder::new("Part").with_name("Orphan"),
+    );
+    tree.insert(
+        tree.root_ref(),
+        InstanceBuilder::new("ObjectValue")
+            .with_name("Pointer")
+            .with_property("Value", removed_model),
+    );
+    tree.insert(tree.root_ref(), InstanceBuilder::new("Sky"));
+
+    // Both `Thing`s have every value, so removing one of them rewrites each
+    // of their PROP chunks.
+    for _ in 0..2 {
+        tree.insert(
+            tree.root_ref(),
+            InstanceBuilder::new("Thing").with_properties(values_of_every_type(target)),
+        );
+    }
+
+    let mut input = Vec::new();
+    to_writer(&mut input, &tree, &[tree.root_ref()]).unwrap();
+
+    let mut things = 0;
+    let mut output = Vec::new();
+    crate::Transcoder::new()
+        .retain_instances(|class, _| class != "Sky")
+        .retain_instances(|class, _| {
+            if class == "Thing" {
+                things += 1;
+                things == 1
+            } else {
+                true
+            }
+        })
+        .transcode(input.as_slice(), &mut output)
+        .unwrap();
+
+    let report = crate::verify(output.as_slice());
+    assert!(report.is_valid(), "{:?}", report);
+
+    let original = from_reader(input.as_slice()).unwrap();
+    let decoded = from_reader(output.as_slice()).unwrap();
+    let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let children: Vec<_> = folder
+        .children()
+        .iter()
+        .map(|referent| decoded.get_by_ref(*referent).unwrap())
+        .collect();
+
+    let names: Vec<_> = children.iter().map(|child| child.name.as_str()).collect();
+    assert_eq!(names.len(), 4);
+    for name in ["Target", "Removed", "Pointer", "Thing"] {
+        assert!(names.contains(&name), "{} should be kept", name);
+    }
+    assert!(!children.iter().any(|child| child.class == "Sky"));
+
+    let original_thing = original
+        .descendants()
+        .find(|instance| instance.class == "Thing")
+        .unwrap();
+    let thing = children
+        .iter()
+        .find(|child| child.class == "Thing")
+        .unwrap();
+
+    for (name, value) in &original_thing.properties {
+        if name == "Ref" {
+            continue;
+        }
+
+        assert_eq!(thing.properties.get(name), Some(value), "{} changed", name);
+    }
+
+    let target = match thing.properties.get("Ref") {
+        Some(Variant::Ref(target)) => decoded.get_by_ref(*target).unwrap(),
+        other => panic!("Ref was {:?}", other),
+    };
+    assert_eq!(target.name, "Target");
+}
+
+#[test]
+fn transcode_remove_referenced() {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder"));
+    let removed = tree.insert(tree.root_ref(), InstanceBuilder::new("Model"));
+    tree.insert(removed, InstanceBuilder::new("Part"));
+    tree.insert(
+        tree.root_ref(),
+        InstanceBuilder::new("ObjectValue").with_property("Value", removed),
+    );
+
+    let mut input = Vec::new();
+    to_writer(&mut input, &tree, &[tree.root_ref()]).unwrap();
+
+    let mut output = Vec::new();
+    crate::Transcoder::new()
+        .retain_instances(|class, _| class != "Model")
+        .transcode(input.as_slice(), &mut output)
+        .unwrap();
+
+    let report = crate::verify(output.as_slice());
+    assert!(report.is_valid(), "{:?}", report);
+
+    let decoded = from_reader(output.as_slice()).unwrap();
+    let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let classes: Vec<_> = folder
+        .children()
+        .iter()
+        .map(|referent| decoded.get_by_ref(*referent).unwrap().class.as_str())
+        .collect();
+    assert_eq!(classes.len(), 2);
+    assert!(classes.contains(&"Part"));
+    assert!(classes.contains(&"ObjectValue"));
+
+    let object_value = decoded
+        .descendants()
+        .find(|instance| instance.class == "ObjectValue")
+        .unwrap();
+    assert_eq!(
+        object_value.properties.get("Value"),
+        Some(&Variant::Ref(Ref::none()))
+    );
+}
+
+#[test]
+fn transcode_map_values() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children([
+            InstanceBuilder::new("NumberValue").with_property("Value", 1.5f64),
+            InstanceBuilder::new("Part")
+                .with_property("Anchored", false)
+                .with_property("Size", Vector3::new(1.0, 2.0, 3.0)),
+        ]),
+    );
+
+    let mut input = Vec::new();
+    to_writer(&mut input, &tree, &[tree.root_ref()]).unwrap();
+
+    let mut output = Vec::new();
+    crate::Transcoder::new()
+        .map_values(|class, property, value| match (class, property, value) {
+            ("NumberValue", "Value", Variant::Float64(number)) => *number *= 2.0,
+            ("Part", "Anchored", Variant::Bool(anchored)) => *anchored = true,
+            (_, "Name", Variant::String(name)) => name.push('!'),
+            _ => {}
+        })
+        .transcode(input.as_slice(), &mut output)
+        .unwrap();
+
+    let decoded = from_reader(output.as_slice()).unwrap();
+    let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    assert_eq!(folder.name, "Folder!");
+
+    let children: Vec<_> = folder
+        .children()
+        .iter()
+        .map(|referent| decoded.get_by_ref(*referent).unwrap())
+        .collect();
+    assert_eq!(
+        children[0].properties.get("Value"),
+        Some(&Variant::Float64(3.0))
+    );
+    assert_eq!(
+        children[1].properties.get("Anchored"),
+        Some(&Variant::Bool(true))
+    );
+    assert_eq!(
+        children[1].properties.get("Size"),
+        Some(&Variant::Vector3(Vector3::new(1.0, 2.0, 3.0)))
+    );
+
+    // Transforms that don't change anything leave the file as it was.
+    let mut unchanged = Vec::new();
+    crate::Transcoder::new()
+        .map_values(|_, _, _| {})
+        .retain_instances(|_, _| true)
+        .transcode(input.as_slice(), &mut unchanged)
+        .unwrap();
+    assert_eq!(unchanged, input);
+}
+
+#[test]
+fn transcode_invalid_value() {
+    let tree = WeakDom::new(InstanceBuilder::new("BoolValue").with_property("Value", true));
+
+    let mut input = Vec::new();
+    to_writer(&mut input, &tree, &[tree.root_ref()]).unwrap();
+
+    let error = crate::Transcoder::new()
+        .map_values(|_, property, value| {
+            if property == "Value" {
+                *value = Variant::Int32(1);
+            }
+        })
+        .transcode(input.as_slice(), Vec::new())
+        .unwrap_err();
+
+    match error {
+        TranscodeError::InvalidValue {
+            class,
+            property,
+            value_type,
+        } => {
+            assert_eq!(class, "BoolValue");
+            assert_eq!(property, "Value");
+            assert_eq!(value_type, VariantType::Int32);
+        }
+        other => panic!("expected InvalidValue, got {:?}", other),
+    }
+}
+
+#[test]
+fn transcode_limits() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+        InstanceBuilder::new("Script").with_property("Source", "print('long enough')"),
+        InstanceBuilder::new("Folder"),
+    ]));
+
+    let mut input = Vec::new();
+    to_writer(&mut input, &tree, &[tree.root_ref()]).unwrap();
+
+    let limit_exceeded = |limits| match crate::Transcoder::new()
+        .limits(limits)
+        .transcode(input.as_slice(), Vec::new())
+    {
+        Err(TranscodeError::Decode(error)) => error.limit_exceeded(),
+        other => panic!("expected a limit to be exceeded, got {:?}", other),
+    };
+
+    assert_eq!(
+        limit_exceeded(DecodeLimits::new().with_max_string_len(4)),
+        Some(LimitExceeded::StringLength { limit: 4 })
+    );
+    assert_eq!(
+        limit_exceeded(DecodeLimits::new().with_max_instances(2)),
+        Some(LimitExceeded::Instances { limit: 2 })
+    );
+    assert_eq!(
+        limit_exceeded(DecodeLimits::new().with_max_chunk_len(16)),
+        Some(LimitExceeded::ChunkLength { limit: 16 })
+    );
+}
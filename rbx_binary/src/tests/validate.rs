@@ -0,0 +1,61 @@
+use rbx_dom_weak::{
+    types::{Color3, Enum, VariantType, Vector3},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{Serializer, ValidationIssue};
+
+/// Ensures that validating against the reflection database reports every
+/// problem, instead of only the first one the serializer runs into.
+#[test]
+fn validate_against_database() {
+    let part = InstanceBuilder::new("Part")
+        .with_property("Anchored", 1i32)
+        .with_property("Material", Enum::from_u32(9999))
+        .with_property("Color", Color3::new(1.0, 0.0, 0.0))
+        .with_property("Size", Vector3::new(1.0, 2.0, 3.0))
+        .with_property("NotARealProperty", true);
+    let part_ref = part.referent();
+    let unknown = InstanceBuilder::new("NotARealClass").with_property("Anchored", 1i32);
+    let unknown_ref = unknown.referent();
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(part)
+            .with_child(unknown)
+            .with_child(InstanceBuilder::new("Folder")),
+    );
+
+    let serializer = Serializer::new();
+    let report = serializer.validate_against_database(&tree, &[tree.root_ref()]);
+
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].referent, part_ref);
+    assert_eq!(report[0].full_name, "Folder.Part");
+    assert_eq!(
+        report[0].issues,
+        [
+            ValidationIssue::WrongType {
+                property: "Anchored".into(),
+                expected: VariantType::Bool,
+                actual: VariantType::Int32,
+            },
+            ValidationIssue::EnumOutOfRange {
+                property: "Material".into(),
+                enum_name: "Material".to_owned(),
+                value: 9999,
+            },
+            ValidationIssue::UnknownProperty {
+                property: "NotARealProperty".into(),
+            },
+        ]
+    );
+    assert_eq!(report[1].referent, unknown_ref);
+    assert_eq!(report[1].issues, [ValidationIssue::UnknownClass]);
+
+    // The serializer agrees that the wrong type can't be written.
+    let mut buffer = Vec::new();
+    assert!(serializer
+        .serialize(&mut buffer, &tree, &[tree.root_ref()])
+        .is_err());
+}
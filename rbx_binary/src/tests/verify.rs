@@ -0,0 +1,49 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{to_writer, CompressionType, Serializer, VerifyIssue};
+
+#[test]
+fn verify() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Part"))
+            .with_child(InstanceBuilder::new("Part")),
+    );
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression_type(CompressionType::None)
+        .serialize(&mut buffer, &tree, &[tree.root_ref()])
+        .unwrap();
+
+    let report = crate::verify(buffer.as_slice());
+    assert!(report.is_valid(), "{:?}", report.issues);
+    let info = report.file_info.unwrap();
+    assert_eq!(info.instance_count, 3);
+    assert_eq!(info.chunks.last().unwrap().name, "END");
+
+    let report = crate::verify(&buffer[..buffer.len() - 4]);
+    assert_eq!(
+        report.issues,
+        [VerifyIssue::Truncated {
+            chunk: Some(info.chunks.len() - 1)
+        }]
+    );
+
+    // END is a 16 byte header followed by `</roblox>`.
+    let report = crate::verify(&buffer[..buffer.len() - 25]);
+    assert_eq!(report.issues, [VerifyIssue::MissingEnd]);
+
+    let mut corrupted = buffer.clone();
+    corrupted[32 + 12] = 1;
+    let report = crate::verify(corrupted.as_slice());
+    assert_eq!(report.issues, [VerifyIssue::ReservedNotZero { chunk: 0 }]);
+
+    let report = crate::verify(&buffer[..20]);
+    assert_eq!(report.issues, [VerifyIssue::Truncated { chunk: None }]);
+    assert!(report.file_info.is_none());
+
+    let mut compressed = Vec::new();
+    to_writer(&mut compressed, &tree, &[tree.root_ref()]).unwrap();
+    assert!(crate::verify(compressed.as_slice()).is_valid());
+}
@@ -0,0 +1,729 @@
+mod values;
+
+use std::{
+    convert::TryFrom,
+    fmt,
+    io::{self, Read, Write},
+};
+
+use ahash::{HashMap, HashSet};
+use rbx_dom_weak::types::{Ref, SharedString, Variant, VariantType};
+use thiserror::Error;
+
+use crate::{
+    chunk::{Chunk, ChunkBuilder},
+    core::{RbxReadExt, RbxWriteExt, FILE_MAGIC_HEADER, FILE_SIGNATURE},
+    deserializer::{
+        header::FileHeader, DecodeLimits, Error as DecodeError, InnerError, LimitExceeded,
+    },
+    serializer::CompressionType,
+    types::Type,
+};
+
+use self::values::{Codec, Referents, SharedStrings};
+
+type StringTransform<'a> = Box<dyn Fn(&mut Vec<u8>) + Send + Sync + 'a>;
+type ValueTransform<'a> = Box<dyn FnMut(&str, &str, &mut Variant) + Send + 'a>;
+type InstanceFilter<'a> = Box<dyn FnMut(&str, Ref) -> bool + Send + 'a>;
+
+/// Rewrites a Roblox binary model or place one chunk at a time, removing or
+/// changing properties and instances along the way, without decoding the file
+/// into a `WeakDom`.
+///
+/// Only one chunk is held in memory at a time, so this is suited to bulk
+/// operations over very large files, like stripping the source out of every
+/// script. [`map_values`][Transcoder::map_values] and
+/// [`retain_instances`][Transcoder::retain_instances] also keep a referent
+/// for each instance and the file's shared strings, but never a property
+/// value from outside the chunk being rewritten. Everything the transcoder
+/// isn't told to change is written back out as-is.
+///
+/// ```
+/// use rbx_binary::Transcoder;
+/// use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+///
+/// let dom = WeakDom::new(
+///     InstanceBuilder::new("Script").with_property("Source", "print('secret')"),
+/// );
+/// let mut input = Vec::new();
+/// rbx_binary::to_writer(&mut input, &dom, &[dom.root_ref()])?;
+///
+/// let mut output = Vec::new();
+/// Transcoder::new()
+///     .map_strings("Script", "Source", |source| {
+///         source.clear();
+///         source.extend_from_slice(b"-- removed");
+///     })
+///     .transcode(input.as_slice(), &mut output)?;
+///
+/// let dom = rbx_binary::from_reader(output.as_slice())?;
+/// let script = dom.get_by_ref(dom.root().children()[0]).unwrap();
+/// assert_eq!(
+///     script.properties.get("Source"),
+///     Some(&Variant::String("-- removed".to_owned())),
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Default)]
+pub struct Transcoder<'a> {
+    compression: Option<CompressionType>,
+    limits: DecodeLimits,
+    removed: HashSet<(String, String)>,
+    string_transforms: HashMap<(String, String), StringTransform<'a>>,
+    value_transforms: Vec<ValueTransform<'a>>,
+    instance_filters: Vec<InstanceFilter<'a>>,
+}
+
+impl<'a> Transcoder<'a> {
+    /// Creates a `Transcoder` that writes files back out unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the compression every chunk is written with. By default, each
+    /// chunk is written with the compression it was read with.
+    pub fn compression_type(self, compression: CompressionType) -> Self {
+        Self {
+            compression: Some(compression),
+            ..self
+        }
+    }
+
+    /// Sets limits on the size of the files being read, like
+    /// [`Deserializer::limits`][crate::Deserializer::limits]. Files that go
+    /// over a limit fail with a [`TranscodeError::Decode`]. Strings are only
+    /// checked when they're read, which values in chunks that are copied
+    /// as-is aren't, and the transcoder never builds the tree of instances,
+    /// so the maximum depth isn't checked. By default, there are no limits.
+    pub fn limits(self, limits: DecodeLimits) -> Self {
+        Self { limits, ..self }
+    }
+
+    /// Removes the property `property` from every instance of `class`. The
+    /// names are the ones stored in the file, which may be different from the
+    /// ones in the reflection database.
+    pub fn remove_property<C: Into<String>, P: Into<String>>(
+        mut self,
+        class: C,
+        property: P,
+    ) -> Self {
+        self.removed.insert((class.into(), property.into()));
+        self
+    }
+
+    /// Calls `transform` with the value of the property `property` on every
+    /// instance of `class`, and writes out whatever it changes the value to.
+    ///
+    /// This only applies to properties that are stored as strings, like
+    /// `Script.Source` or `StringValue.Value`. Other properties with the name
+    /// are left alone.
+    pub fn map_strings<C, P, F>(mut self, class: C, property: P, transform: F) -> Self
+    where
+        C: Into<String>,
+        P: Into<String>,
+        F: Fn(&mut Vec<u8>) + Send + Sync + 'a,
+    {
+        self.string_transforms
+            .insert((class.into(), property.into()), Box::new(transform));
+        self
+    }
+
+    /// Calls `transform` with the class name, property name, and value of
+    /// every property of every instance, and writes out whatever it changes
+    /// the value to. Transforms run in the order they're added, after
+    /// [`map_strings`][Transcoder::map_strings].
+    ///
+    /// Values are decoded by the type they're stored as, so strings are given
+    /// as `Variant::String`, or `Variant::BinaryString` if they aren't UTF-8,
+    /// and enums as `Variant::Enum`. A value may only be changed to one that
+    /// can be stored the same way, and `Ref` and `SharedString` values only to
+    /// ones already in the file; anything else fails with
+    /// [`TranscodeError::InvalidValue`]. Chunks whose values don't change are
+    /// written back out as-is.
+    ///
+    /// ```
+    /// use rbx_binary::Transcoder;
+    /// use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("IntValue").with_property("Value", 1i64));
+    /// let mut input = Vec::new();
+    /// rbx_binary::to_writer(&mut input, &dom, &[dom.root_ref()])?;
+    ///
+    /// let mut output = Vec::new();
+    /// Transcoder::new()
+    ///     .map_values(|_class, _property, value| {
+    ///         if let Variant::Int64(number) = value {
+    ///             *number *= 10;
+    ///         }
+    ///     })
+    ///     .transcode(input.as_slice(), &mut output)?;
+    ///
+    /// let dom = rbx_binary::from_reader(output.as_slice())?;
+    /// let value = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    /// assert_eq!(value.properties.get("Value"), Some(&Variant::Int64(10)));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn map_values<F>(mut self, transform: F) -> Self
+    where
+        F: FnMut(&str, &str, &mut Variant) + Send + 'a,
+    {
+        self.value_transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Calls `filter` with the class name of every instance and a `Ref` made
+    /// up for it, and removes the instances it returns `false` for.
+    ///
+    /// The `Ref` is the same one `map_values` is given for `Ref` properties
+    /// that point to the instance. Properties that pointed to a removed
+    /// instance are set to `Ref::none()`, and the children of a removed
+    /// instance are moved to its closest ancestor that's kept.
+    pub fn retain_instances<F>(mut self, filter: F) -> Self
+    where
+        F: FnMut(&str, Ref) -> bool + Send + 'a,
+    {
+        self.instance_filters.push(Box::new(filter));
+        self
+    }
+
+    /// Reads a binary file from `reader` and writes it to `writer` with the
+    /// configured changes applied.
+    pub fn transcode<R: Read, W: Write>(
+        &mut self,
+        mut reader: R,
+        mut writer: W,
+    ) -> Result<(), TranscodeError> {
+        profiling::scope!("rbx_binary::transcode");
+
+        let header = FileHeader::decode(&mut reader).map_err(DecodeError::from)?;
+        let mut state = TranscodeState::default();
+
+        // The header counts the file's classes and instances, so it's only
+        // written once every INST chunk has been read. Roblox writes those
+        // before any PROP chunk, so only the chunks before that are held back.
+        let mut held = Some(Vec::new());
+
+        loop {
+            let chunk = Chunk::decode_limited(&mut reader, &self.limits).map_err(inner)?;
+
+            if matches!(&chunk.name, b"PROP" | b"PRNT" | b"END\0") {
+                if let Some(held) = held.take() {
+                    state.renumber_classes();
+
+                    writer.write_all(FILE_MAGIC_HEADER)?;
+                    writer.write_all(FILE_SIGNATURE)?;
+                    writer.write_le_u16(header.version)?;
+                    writer.write_le_u32(
+                        header
+                            .num_types
+                            .saturating_sub(state.removed_classes as u32),
+                    )?;
+                    writer.write_le_u32(
+                        header
+                            .num_instances
+                            .saturating_sub(state.removed.len() as u32),
+                    )?;
+                    writer.write_all(&[0; 8])?;
+
+                    for (name, compression, mut data) in held {
+                        if &name == b"INST" {
+                            data = state.with_type_id(data);
+                        }
+
+                        write_chunk(&mut writer, &name, compression, &data)?;
+                    }
+                }
+            }
+
+            let data = match &chunk.name {
+                b"SSTR" => {
+                    if self.decodes_values() {
+                        self.read_shared_strings(&mut state, &chunk.data)?;
+                    }
+
+                    Some(chunk.data)
+                }
+                b"INST" => self.transcode_inst(&mut state, chunk.data)?,
+                b"PROP" => self.transcode_prop(&state, chunk.data)?,
+                b"PRNT" => self.transcode_prnt(&state, chunk.data)?,
+                _ => Some(chunk.data),
+            };
+
+            let compression = match &chunk.name {
+                b"END\0" => CompressionType::None,
+                _ => self.compression.unwrap_or(chunk.compression),
+            };
+
+            if let Some(data) = data {
+                match &mut held {
+                    Some(held) => held.push((chunk.name, compression, data)),
+                    None => write_chunk(&mut writer, &chunk.name, compression, &data)?,
+                }
+            }
+
+            if &chunk.name == b"END\0" {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether property values have to be decoded, rather than just copied.
+    fn decodes_values(&self) -> bool {
+        !self.value_transforms.is_empty() || !self.instance_filters.is_empty()
+    }
+
+    fn check_instance_limit(&self, count: usize) -> Result<(), TranscodeError> {
+        match self.limits.max_instances {
+            Some(limit) if count > limit => Err(inner(LimitExceeded::Instances { limit })),
+            _ => Ok(()),
+        }
+    }
+
+    /// Keeps the contents of an SSTR chunk, which SharedString values refer
+    /// to by index.
+    fn read_shared_strings(
+        &self,
+        state: &mut TranscodeState,
+        mut data: &[u8],
+    ) -> Result<(), TranscodeError> {
+        let version = data.read_le_u32().map_err(inner)?;
+        if version != 0 {
+            return Err(inner(InnerError::UnknownChunkVersion {
+                chunk_name: "SSTR",
+                version,
+            }));
+        }
+
+        let num_entries = data.read_le_u32().map_err(inner)?;
+        for _ in 0..num_entries {
+            data.read_exact(&mut [0; 16]).map_err(inner)?;
+            let value = data
+                .read_binary_string_limited(self.limits.max_string_len)
+                .map_err(inner)?;
+            state.shared_strings.push(SharedString::new(value));
+        }
+
+        Ok(())
+    }
+
+    /// Records the class declared by an INST chunk and removes the instances
+    /// that are filtered out from it, returning `None` if none are left.
+    fn transcode_inst(
+        &mut self,
+        state: &mut TranscodeState,
+        data: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, TranscodeError> {
+        let mut reader = data.as_slice();
+        let type_id = reader.read_le_u32().map_err(inner)?;
+        let name = reader
+            .read_string_limited(self.limits.max_string_len)
+            .map_err(inner)?;
+        let object_format = reader.read_u8().map_err(inner)?;
+        let count = reader.read_le_u32().map_err(inner)? as usize;
+
+        state.instance_count += count;
+        self.check_instance_limit(state.instance_count)?;
+
+        if !self.decodes_values() {
+            state.classes.insert(
+                type_id,
+                Class {
+                    name,
+                    type_id,
+                    kept: Vec::new(),
+                },
+            );
+            return Ok(Some(data));
+        }
+
+        let mut referents = vec![0; count];
+        reader.read_referent_array(&mut referents).map_err(inner)?;
+
+        let mut kept = Vec::with_capacity(count);
+        for &referent in &referents {
+            let id = Ref::new();
+            let keep = self
+                .instance_filters
+                .iter_mut()
+                .all(|filter| filter(&name, id));
+
+            if keep {
+                state.referents.insert(referent, id);
+            } else {
+                state.removed.insert(referent);
+            }
+
+            kept.push(keep);
+        }
+
+        let class = Class {
+            name,
+            type_id,
+            kept,
+        };
+
+        let output = if !class.any_removed() {
+            Some(data)
+        } else if class.kept.contains(&true) {
+            log::trace!("Removing instances of {}", class.name);
+
+            let mut output = Vec::new();
+            output.write_le_u32(class.type_id)?;
+            output.write_string(&class.name)?;
+            output.write_u8(object_format)?;
+            output.write_le_u32(class.kept.iter().filter(|keep| **keep).count() as u32)?;
+            output.write_referent_array(class.retained(referents).into_iter())?;
+
+            // Services have a marker for each instance after their referents.
+            if object_format == 1 {
+                let mut markers = vec![0; count];
+                reader.read_exact(&mut markers).map_err(inner)?;
+                output.extend(class.retained(markers));
+            }
+
+            Some(output)
+        } else {
+            log::trace!("Removing every instance of {}", class.name);
+            state.removed_classes += 1;
+            None
+        };
+
+        state.classes.insert(type_id, class);
+        Ok(output)
+    }
+
+    /// Applies the configured changes to the contents of a PROP chunk,
+    /// returning `None` if the chunk should be left out.
+    fn transcode_prop(
+        &mut self,
+        state: &TranscodeState,
+        data: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, TranscodeError> {
+        let max_string_len = self.limits.max_string_len;
+
+        let mut reader = data.as_slice();
+        let type_id = reader.read_le_u32().map_err(inner)?;
+        let property = reader.read_string_limited(max_string_len).map_err(inner)?;
+
+        let class = state
+            .classes
+            .get(&type_id)
+            .ok_or(InnerError::InvalidTypeId { type_id })
+            .map_err(inner)?;
+        let key = (class.name.clone(), property);
+
+        if !class.kept.is_empty() && !class.kept.contains(&true) {
+            return Ok(None);
+        }
+
+        if self.removed.contains(&key) {
+            log::trace!("Removing property {}.{}", key.0, key.1);
+            return Ok(None);
+        }
+
+        // Everything up to and including the type byte is kept, and the
+        // values after it may be replaced.
+        let values_start = data.len() - reader.len() + 1;
+        let type_byte = match reader.first() {
+            Some(byte) => *byte,
+            None => return Ok(Some(class.with_type_id(data))),
+        };
+
+        let data = match self.string_transforms.get(&key) {
+            Some(transform) if type_byte == Type::String as u8 => {
+                let mut values = &data[values_start..];
+                let mut output = data[..values_start].to_vec();
+
+                while !values.is_empty() {
+                    let mut value = values
+                        .read_binary_string_limited(max_string_len)
+                        .map_err(inner)?;
+                    transform(&mut value);
+                    output.write_binary_string(&value)?;
+                }
+
+                output
+            }
+            Some(_) => {
+                log::trace!("Property {}.{} isn't stored as a string", key.0, key.1);
+                data
+            }
+            None => data,
+        };
+
+        if !self.decodes_values() {
+            return Ok(Some(class.with_type_id(data)));
+        }
+
+        let ty = match Type::try_from(type_byte) {
+            Ok(ty) => ty,
+            Err(_) if !class.any_removed() => return Ok(Some(class.with_type_id(data))),
+            Err(_) => {
+                return Err(inner(InnerError::UnknownPropType {
+                    type_name: key.0,
+                    prop_name: key.1,
+                    type_id: type_byte,
+                }))
+            }
+        };
+
+        let codec = Codec {
+            class: &key.0,
+            property: &key.1,
+            referents: &state.referents,
+            shared_strings: &state.shared_strings,
+            max_string_len,
+        };
+
+        let values = codec
+            .decode(&mut &data[values_start..], ty, class.kept.len())
+            .map_err(inner)?;
+        let mut values = class.retained(values);
+
+        let mut changed = false;
+        if !self.value_transforms.is_empty() {
+            for value in &mut values {
+                let original = value.clone();
+
+                for transform in &mut self.value_transforms {
+                    transform(&key.0, &key.1, value);
+                }
+
+                changed |= *value != original;
+            }
+        }
+
+        // Values that pointed to a removed instance have to be written again,
+        // even if nothing else about them changed.
+        let refers_to_removed =
+            !state.removed.is_empty() && matches!(ty, Type::Ref | Type::Content);
+
+        if !changed && !class.any_removed() && !refers_to_removed {
+            return Ok(Some(class.with_type_id(data)));
+        }
+
+        let mut output = data[..values_start].to_vec();
+        output[..4].copy_from_slice(&class.type_id.to_le_bytes());
+        codec.encode(&mut output, ty, &values)?;
+
+        Ok(Some(output))
+    }
+
+    /// Removes the instances that are filtered out from a PRNT chunk, giving
+    /// their children the closest ancestor that's kept.
+    fn transcode_prnt(
+        &self,
+        state: &TranscodeState,
+        data: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, TranscodeError> {
+        if state.removed.is_empty() {
+            return Ok(Some(data));
+        }
+
+        let mut reader = data.as_slice();
+        let version = reader.read_u8().map_err(inner)?;
+        if version != 0 {
+            return Err(inner(InnerError::UnknownChunkVersion {
+                chunk_name: "PRNT",
+                version: version as u32,
+            }));
+        }
+
+        let count = reader.read_le_u32().map_err(inner)? as usize;
+        self.check_instance_limit(count)?;
+
+        let mut subjects = vec![0; count];
+        let mut parents = vec![0; count];
+        reader.read_referent_array(&mut subjects).map_err(inner)?;
+        reader.read_referent_array(&mut parents).map_err(inner)?;
+
+        let parent_of: HashMap<i32, i32> = subjects
+            .iter()
+            .copied()
+            .zip(parents.iter().copied())
+            .collect();
+
+        let mut kept_subjects = Vec::with_capacity(count);
+        let mut kept_parents = Vec::with_capacity(count);
+
+        for (subject, mut parent) in subjects.into_iter().zip(parents) {
+            if state.removed.contains(&subject) {
+                continue;
+            }
+
+            // Walking up at most `count` times stops at cycles between
+            // removed instances, which leave the instance without a parent.
+            let mut steps = 0;
+            while state.removed.contains(&parent) && steps < count {
+                parent = parent_of.get(&parent).copied().unwrap_or(-1);
+                steps += 1;
+            }
+
+            if state.removed.contains(&parent) {
+                parent = -1;
+            }
+
+            kept_subjects.push(subject);
+            kept_parents.push(parent);
+        }
+
+        let mut output = Vec::new();
+        output.write_u8(0)?;
+        output.write_le_u32(kept_subjects.len() as u32)?;
+        output.write_referent_array(kept_subjects.into_iter())?;
+        output.write_referent_array(kept_parents.into_iter())?;
+
+        Ok(Some(output))
+    }
+}
+
+impl fmt::Debug for Transcoder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transcoder")
+            .field("compression", &self.compression)
+            .field("limits", &self.limits)
+            .field("removed", &self.removed)
+            .field(
+                "string_transforms",
+                &self.string_transforms.keys().collect::<Vec<_>>(),
+            )
+            .field("value_transforms", &self.value_transforms.len())
+            .field("instance_filters", &self.instance_filters.len())
+            .finish()
+    }
+}
+
+/// What the transcoder knows about a file while it's being rewritten.
+#[derive(Default)]
+struct TranscodeState {
+    /// Each class, by the type ID the file declared it with.
+    classes: HashMap<u32, Class>,
+
+    /// The number of classes that had every instance removed.
+    removed_classes: usize,
+
+    /// The referents of removed instances.
+    removed: HashSet<i32>,
+
+    /// The number of instances declared so far.
+    instance_count: usize,
+
+    referents: Referents,
+    shared_strings: SharedStrings,
+}
+
+impl TranscodeState {
+    /// Numbers the classes that are kept from 0, in the order of the type IDs
+    /// they were declared with, if any classes were removed.
+    fn renumber_classes(&mut self) {
+        if self.removed_classes == 0 {
+            return;
+        }
+
+        let mut classes: Vec<_> = self
+            .classes
+            .iter_mut()
+            .filter(|(_, class)| class.kept.contains(&true))
+            .collect();
+        classes.sort_by_key(|(type_id, _)| **type_id);
+
+        for (type_id, (_, class)) in classes.into_iter().enumerate() {
+            class.type_id = type_id as u32;
+        }
+    }
+
+    /// Replaces the type ID at the start of an INST chunk with the one its
+    /// class is written with.
+    fn with_type_id(&self, data: Vec<u8>) -> Vec<u8> {
+        let type_id = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+
+        match self.classes.get(&type_id) {
+            Some(class) => class.with_type_id(data),
+            None => data,
+        }
+    }
+}
+
+/// A class declared by an INST chunk.
+struct Class {
+    name: String,
+
+    /// The type ID the class is written with, which is different from the one
+    /// it was read with if a class before it was removed.
+    type_id: u32,
+
+    /// Whether each instance of the class is kept, in the order the file
+    /// declares them. This is only filled in when values are decoded.
+    kept: Vec<bool>,
+}
+
+impl Class {
+    fn any_removed(&self) -> bool {
+        self.kept.contains(&false)
+    }
+
+    /// Keeps the items in `items` that belong to kept instances.
+    fn retained<T>(&self, items: Vec<T>) -> Vec<T> {
+        if !self.any_removed() {
+            return items;
+        }
+
+        items
+            .into_iter()
+            .zip(&self.kept)
+            .filter(|(_, keep)| **keep)
+            .map(|(item, _)| item)
+            .collect()
+    }
+
+    /// Replaces the type ID at the start of a PROP chunk, or an INST chunk,
+    /// with the one the class is written with.
+    fn with_type_id(&self, mut data: Vec<u8>) -> Vec<u8> {
+        data[..4].copy_from_slice(&self.type_id.to_le_bytes());
+        data
+    }
+}
+
+/// An error that stopped a [`Transcoder`] from rewriting a file.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TranscodeError {
+    /// The input file couldn't be read.
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+
+    /// The output couldn't be written.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// A transform changed a value to one that can't be written in place of
+    /// the original, like a value of a different type or a `Ref` to an
+    /// instance that isn't in the file.
+    #[error("Property {class}.{property} can't be written with a {value_type:?} value")]
+    InvalidValue {
+        /// The class the property belongs to.
+        class: String,
+        /// The name of the property.
+        property: String,
+        /// The type of the value that couldn't be written.
+        value_type: VariantType,
+    },
+}
+
+fn write_chunk<W: Write>(
+    writer: W,
+    name: &[u8; 4],
+    compression: CompressionType,
+    data: &[u8],
+) -> io::Result<()> {
+    let mut builder = ChunkBuilder::new(name, compression);
+    builder.write_all(data)?;
+    builder.dump(writer)?;
+    Ok(())
+}
+
+fn inner<E: Into<InnerError>>(err: E) -> TranscodeError {
+    TranscodeError::Decode(err.into().into())
+}
@@ -0,0 +1,1031 @@
+//! Decodes the values stored in a PROP chunk into `Variant`s and encodes them
+//! back, so the transcoder can hand them to callbacks without decoding the
+//! rest of the file.
+//!
+//! Values are decoded by the type stored in the chunk, not the type the
+//! reflection database gives the property, since the transcoder doesn't look
+//! properties up.
+
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Write},
+};
+
+use ahash::HashMap;
+use rbx_dom_weak::types::{
+    Axes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence,
+    ColorSequenceKeypoint, Content, ContentType, CustomPhysicalProperties, Enum, EnumItem, Faces,
+    Font, FontStyle, FontWeight, Matrix3, NumberRange, NumberSequence, NumberSequenceKeypoint,
+    PhysicalProperties, Ray, Rect, Ref, SecurityCapabilities, SharedString, UDim, UDim2, UniqueId,
+    Variant, Vector2, Vector3, Vector3int16,
+};
+
+use crate::{
+    core::{RbxReadExt, RbxWriteExt},
+    deserializer::InnerError,
+    types::Type,
+};
+
+use super::TranscodeError;
+
+/// Gives each instance kept in a file a `Ref`, so `Ref` values can be decoded
+/// and written back as the same referents.
+#[derive(Default)]
+pub(super) struct Referents {
+    by_referent: HashMap<i32, Ref>,
+    by_ref: HashMap<Ref, i32>,
+}
+
+impl Referents {
+    /// Gives the instance with the referent `referent` the `Ref` `id`.
+    pub fn insert(&mut self, referent: i32, id: Ref) {
+        self.by_referent.insert(referent, id);
+        self.by_ref.insert(id, referent);
+    }
+
+    /// Returns the `Ref` for `referent`, or `Ref::none()` if no instance kept
+    /// in the file has it.
+    fn get_ref(&self, referent: i32) -> Ref {
+        self.by_referent
+            .get(&referent)
+            .copied()
+            .unwrap_or_else(Ref::none)
+    }
+
+    /// Returns the referent to write for `id`, which is -1 for `Ref::none()`.
+    fn get_referent(&self, id: Ref) -> Option<i32> {
+        if id.is_none() {
+            Some(-1)
+        } else {
+            self.by_ref.get(&id).copied()
+        }
+    }
+}
+
+/// The shared strings from a file's SSTR chunk, which SharedString values
+/// are stored as indices into.
+#[derive(Default)]
+pub(super) struct SharedStrings {
+    values: Vec<SharedString>,
+    ids: HashMap<SharedString, u32>,
+}
+
+impl SharedStrings {
+    pub fn push(&mut self, value: SharedString) {
+        self.ids
+            .entry(value.clone())
+            .or_insert(self.values.len() as u32);
+        self.values.push(value);
+    }
+}
+
+/// Decodes and encodes the values of one property of one class.
+pub(super) struct Codec<'a> {
+    pub class: &'a str,
+    pub property: &'a str,
+    pub referents: &'a Referents,
+    pub shared_strings: &'a SharedStrings,
+    pub max_string_len: Option<usize>,
+}
+
+impl Codec<'_> {
+    /// Reads `count` values of type `ty` from `reader`, which should hold
+    /// everything in the chunk after its type byte.
+    pub fn decode(
+        &self,
+        reader: &mut &[u8],
+        ty: Type,
+        count: usize,
+    ) -> Result<Vec<Variant>, InnerError> {
+        let max_string_len = self.max_string_len;
+
+        let values = match ty {
+            Type::String => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let value = reader.read_binary_string_limited(max_string_len)?;
+                    values.push(match String::from_utf8(value) {
+                        Ok(value) => Variant::String(value),
+                        Err(err) => Variant::BinaryString(BinaryString::from(err.into_bytes())),
+                    });
+                }
+
+                values
+            }
+            Type::Bool => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    values.push(Variant::Bool(reader.read_bool()?));
+                }
+
+                values
+            }
+            Type::Int32 => read_i32s(reader, count)?
+                .into_iter()
+                .map(Variant::Int32)
+                .collect(),
+            Type::Float32 => read_f32s(reader, count)?
+                .into_iter()
+                .map(Variant::Float32)
+                .collect(),
+            Type::Float64 => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    values.push(Variant::Float64(reader.read_le_f64()?));
+                }
+
+                values
+            }
+            Type::UDim => {
+                let scale = read_f32s(reader, count)?;
+                let offset = read_i32s(reader, count)?;
+
+                scale
+                    .into_iter()
+                    .zip(offset)
+                    .map(|(scale, offset)| UDim::new(scale, offset).into())
+                    .collect()
+            }
+            Type::UDim2 => {
+                let scale_x = read_f32s(reader, count)?;
+                let scale_y = read_f32s(reader, count)?;
+                let offset_x = read_i32s(reader, count)?;
+                let offset_y = read_i32s(reader, count)?;
+
+                scale_x
+                    .into_iter()
+                    .zip(scale_y)
+                    .zip(offset_x.into_iter().zip(offset_y))
+                    .map(|((scale_x, scale_y), (offset_x, offset_y))| {
+                        UDim2::new(UDim::new(scale_x, offset_x), UDim::new(scale_y, offset_y))
+                            .into()
+                    })
+                    .collect()
+            }
+            Type::Font => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let family = reader.read_string_limited(max_string_len)?;
+                    let weight = FontWeight::from_u16(reader.read_le_u16()?).unwrap_or_default();
+                    let style = FontStyle::from_u8(reader.read_u8()?).unwrap_or_default();
+                    let cached_face_id = reader.read_string_limited(max_string_len)?;
+
+                    values.push(Variant::Font(Font {
+                        family,
+                        weight,
+                        style,
+                        cached_face_id: Some(cached_face_id).filter(|id| !id.is_empty()),
+                    }));
+                }
+
+                values
+            }
+            Type::Ray => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let origin = read_vector3(reader)?;
+                    let direction = read_vector3(reader)?;
+                    values.push(Variant::Ray(Ray::new(origin, direction)));
+                }
+
+                values
+            }
+            Type::Faces => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let value = reader.read_u8()?;
+                    let faces = Faces::from_bits(value)
+                        .ok_or_else(|| self.invalid_data("less than 63", value))?;
+                    values.push(Variant::Faces(faces));
+                }
+
+                values
+            }
+            Type::Axes => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let value = reader.read_u8()?;
+                    let axes = Axes::from_bits(value)
+                        .ok_or_else(|| self.invalid_data("less than 7", value))?;
+                    values.push(Variant::Axes(axes));
+                }
+
+                values
+            }
+            Type::BrickColor => {
+                let mut values = Vec::with_capacity(count);
+
+                for value in read_u32s(reader, count)? {
+                    let color = u16::try_from(value)
+                        .ok()
+                        .and_then(BrickColor::from_number)
+                        .ok_or_else(|| self.invalid_data("a valid BrickColor", value))?;
+                    values.push(Variant::BrickColor(color));
+                }
+
+                values
+            }
+            Type::Color3 => {
+                let r = read_f32s(reader, count)?;
+                let g = read_f32s(reader, count)?;
+                let b = read_f32s(reader, count)?;
+
+                r.into_iter()
+                    .zip(g)
+                    .zip(b)
+                    .map(|((r, g), b)| Color3::new(r, g, b).into())
+                    .collect()
+            }
+            Type::Vector2 => {
+                let x = read_f32s(reader, count)?;
+                let y = read_f32s(reader, count)?;
+
+                x.into_iter()
+                    .zip(y)
+                    .map(|(x, y)| Vector2::new(x, y).into())
+                    .collect()
+            }
+            Type::Vector3 => read_vector3s(reader, count)?
+                .into_iter()
+                .map(Variant::Vector3)
+                .collect(),
+            Type::CFrame => {
+                let rotations = self.read_rotations(reader, count)?;
+                let positions = read_vector3s(reader, count)?;
+
+                positions
+                    .into_iter()
+                    .zip(rotations)
+                    .map(|(position, rotation)| CFrame::new(position, rotation).into())
+                    .collect()
+            }
+            Type::Enum => read_u32s(reader, count)?
+                .into_iter()
+                .map(|value| Enum::from_u32(value).into())
+                .collect(),
+            Type::Ref => read_referents(reader, count)?
+                .into_iter()
+                .map(|referent| Variant::Ref(self.referents.get_ref(referent)))
+                .collect(),
+            Type::Vector3int16 => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    values.push(Variant::Vector3int16(Vector3int16::new(
+                        reader.read_le_i16()?,
+                        reader.read_le_i16()?,
+                        reader.read_le_i16()?,
+                    )));
+                }
+
+                values
+            }
+            Type::NumberSequence => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let keypoint_count = reader.read_le_u32()?;
+                    let mut keypoints = Vec::new();
+
+                    for _ in 0..keypoint_count {
+                        keypoints.push(NumberSequenceKeypoint::new(
+                            reader.read_le_f32()?,
+                            reader.read_le_f32()?,
+                            reader.read_le_f32()?,
+                        ));
+                    }
+
+                    values.push(Variant::NumberSequence(NumberSequence { keypoints }));
+                }
+
+                values
+            }
+            Type::ColorSequence => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let keypoint_count = reader.read_le_u32()?;
+                    let mut keypoints = Vec::new();
+
+                    for _ in 0..keypoint_count {
+                        let time = reader.read_le_f32()?;
+                        let color = Color3::new(
+                            reader.read_le_f32()?,
+                            reader.read_le_f32()?,
+                            reader.read_le_f32()?,
+                        );
+
+                        // The envelope is stored, but doesn't do anything.
+                        reader.read_le_f32()?;
+
+                        keypoints.push(ColorSequenceKeypoint::new(time, color));
+                    }
+
+                    values.push(Variant::ColorSequence(ColorSequence { keypoints }));
+                }
+
+                values
+            }
+            Type::NumberRange => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    values.push(Variant::NumberRange(NumberRange::new(
+                        reader.read_le_f32()?,
+                        reader.read_le_f32()?,
+                    )));
+                }
+
+                values
+            }
+            Type::Rect => {
+                let x_min = read_f32s(reader, count)?;
+                let y_min = read_f32s(reader, count)?;
+                let x_max = read_f32s(reader, count)?;
+                let y_max = read_f32s(reader, count)?;
+
+                x_min
+                    .into_iter()
+                    .zip(y_min)
+                    .zip(x_max.into_iter().zip(y_max))
+                    .map(|((x_min, y_min), (x_max, y_max))| {
+                        Rect::new(Vector2::new(x_min, y_min), Vector2::new(x_max, y_max)).into()
+                    })
+                    .collect()
+            }
+            Type::PhysicalProperties => {
+                let mut values = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let value = if reader.read_u8()? == 1 {
+                        PhysicalProperties::Custom(CustomPhysicalProperties {
+                            density: reader.read_le_f32()?,
+                            friction: reader.read_le_f32()?,
+                            elasticity: reader.read_le_f32()?,
+                            friction_weight: reader.read_le_f32()?,
+                            elasticity_weight: reader.read_le_f32()?,
+                        })
+                    } else {
+                        PhysicalProperties::Default
+                    };
+
+                    values.push(Variant::PhysicalProperties(value));
+                }
+
+                values
+            }
+            Type::Color3uint8 => {
+                let mut r = vec![0; count];
+                let mut g = vec![0; count];
+                let mut b = vec![0; count];
+
+                reader.read_exact(&mut r)?;
+                reader.read_exact(&mut g)?;
+                reader.read_exact(&mut b)?;
+
+                r.into_iter()
+                    .zip(g)
+                    .zip(b)
+                    .map(|((r, g), b)| Color3uint8::new(r, g, b).into())
+                    .collect()
+            }
+            Type::Int64 => read_i64s(reader, count)?
+                .into_iter()
+                .map(Variant::Int64)
+                .collect(),
+            Type::SharedString => {
+                let mut values = Vec::with_capacity(count);
+
+                for index in read_u32s(reader, count)? {
+                    let value = self
+                        .shared_strings
+                        .values
+                        .get(index as usize)
+                        .ok_or_else(|| self.invalid_data("a shared string in the file", index))?;
+                    values.push(Variant::SharedString(value.clone()));
+                }
+
+                values
+            }
+            Type::OptionalCFrame => {
+                self.check_type_byte(reader, Type::CFrame)?;
+                let rotations = self.read_rotations(reader, count)?;
+                let positions = read_vector3s(reader, count)?;
+
+                self.check_type_byte(reader, Type::Bool)?;
+                let mut present = Vec::with_capacity(count);
+                for _ in 0..count {
+                    present.push(reader.read_bool()?);
+                }
+
+                positions
+                    .into_iter()
+                    .zip(rotations)
+                    .zip(present)
+                    .map(|((position, rotation), present)| {
+                        Variant::OptionalCFrame(
+                            Some(CFrame::new(position, rotation)).filter(|_| present),
+                        )
+                    })
+                    .collect()
+            }
+            Type::UniqueId => {
+                let mut blobs = vec![[0; 16]; count];
+                reader.read_interleaved_bytes::<16>(&mut blobs)?;
+
+                let mut values = Vec::with_capacity(count);
+                for blob in blobs {
+                    let mut blob = blob.as_slice();
+                    values.push(Variant::UniqueId(UniqueId::new(
+                        blob.read_be_u32()?,
+                        blob.read_be_u32()?,
+                        blob.read_be_i64()?.rotate_right(1),
+                    )));
+                }
+
+                values
+            }
+            Type::SecurityCapabilities => read_i64s(reader, count)?
+                .into_iter()
+                .map(|value| SecurityCapabilities::from_bits(value as u64).into())
+                .collect(),
+            Type::Content => {
+                let source_types = read_i32s(reader, count)?;
+
+                let uri_count = reader.read_le_u32()?;
+                let mut uris = Vec::new();
+                for _ in 0..uri_count {
+                    uris.push(reader.read_string_limited(max_string_len)?);
+                }
+
+                let object_count = reader.read_le_u32()? as usize;
+                let objects = read_referents(reader, object_count)?;
+
+                // External referents are for Roblox's own use and aren't
+                // decoded, just like in the deserializer.
+                let external_count = reader.read_le_u32()? as usize;
+                read_referents(reader, external_count)?;
+
+                let mut uris = uris.into_iter();
+                let mut objects = objects.into_iter();
+                let mut values = Vec::with_capacity(count);
+
+                for source_type in source_types {
+                    let value = match source_type {
+                        0 => Content::none(),
+                        1 => Content::from_uri(uris.next().unwrap_or_default()),
+                        2 => Content::from_referent(
+                            self.referents.get_ref(objects.next().unwrap_or(-1)),
+                        ),
+                        n => return Err(InnerError::BadContentType(n)),
+                    };
+
+                    values.push(Variant::Content(value));
+                }
+
+                values
+            }
+        };
+
+        Ok(values)
+    }
+
+    /// Writes `values` to `output` as the type `ty`, in the same layout
+    /// [`Codec::decode`] reads.
+    pub fn encode(
+        &self,
+        output: &mut Vec<u8>,
+        ty: Type,
+        values: &[Variant],
+    ) -> Result<(), TranscodeError> {
+        match ty {
+            Type::String => {
+                for rbx_value in values {
+                    match rbx_value {
+                        Variant::String(value) => output.write_string(value)?,
+                        Variant::BinaryString(value) => {
+                            let buffer: &[u8] = value.as_ref();
+                            output.write_binary_string(buffer)?
+                        }
+                        Variant::ContentId(value) => output.write_string(value.as_str())?,
+                        Variant::SharedString(value) => output.write_binary_string(value.data())?,
+                        Variant::Tags(value) => output.write_binary_string(&value.encode())?,
+                        Variant::MaterialColors(value) => {
+                            output.write_binary_string(&value.encode())?
+                        }
+                        Variant::Attributes(value) => {
+                            let mut buffer = Vec::new();
+                            value
+                                .to_writer(&mut buffer)
+                                .map_err(|_| self.invalid_value(rbx_value))?;
+                            output.write_binary_string(&buffer)?;
+                        }
+                        _ => return Err(self.invalid_value(rbx_value)),
+                    }
+                }
+            }
+            Type::Bool => {
+                for value in values {
+                    match value {
+                        Variant::Bool(value) => output.write_bool(*value)?,
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::Int32 => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Int32(value) => Some(*value),
+                    _ => None,
+                })?;
+                output.write_interleaved_i32_array(values.into_iter())?;
+            }
+            Type::Float32 => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Float32(value) => Some(*value),
+                    _ => None,
+                })?;
+                output.write_interleaved_f32_array(values.into_iter())?;
+            }
+            Type::Float64 => {
+                for value in values {
+                    match value {
+                        Variant::Float64(value) => output.write_le_f64(*value)?,
+                        Variant::Float32(value) => output.write_le_f64(*value as f64)?,
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::UDim => {
+                let values = self.collect(values, |value| match value {
+                    Variant::UDim(value) => Some(*value),
+                    _ => None,
+                })?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.scale))?;
+                output.write_interleaved_i32_array(values.iter().map(|value| value.offset))?;
+            }
+            Type::UDim2 => {
+                let values = self.collect(values, |value| match value {
+                    Variant::UDim2(value) => Some(*value),
+                    _ => None,
+                })?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.x.scale))?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.y.scale))?;
+                output.write_interleaved_i32_array(values.iter().map(|value| value.x.offset))?;
+                output.write_interleaved_i32_array(values.iter().map(|value| value.y.offset))?;
+            }
+            Type::Font => {
+                for value in values {
+                    match value {
+                        Variant::Font(value) => {
+                            output.write_string(&value.family)?;
+                            output.write_le_u16(value.weight.as_u16())?;
+                            output.write_u8(value.style.as_u8())?;
+                            output.write_string(
+                                value.cached_face_id.as_deref().unwrap_or_default(),
+                            )?;
+                        }
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::Ray => {
+                for value in values {
+                    match value {
+                        Variant::Ray(value) => {
+                            write_vector3(output, value.origin)?;
+                            write_vector3(output, value.direction)?;
+                        }
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::Faces => {
+                for value in values {
+                    match value {
+                        Variant::Faces(value) => output.write_u8(value.bits())?,
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::Axes => {
+                for value in values {
+                    match value {
+                        Variant::Axes(value) => output.write_u8(value.bits())?,
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::BrickColor => {
+                let values = self.collect(values, |value| match value {
+                    Variant::BrickColor(value) => Some(*value as u32),
+                    Variant::Int32(value) => Some(*value as u32),
+                    _ => None,
+                })?;
+                output.write_interleaved_u32_array(&values)?;
+            }
+            Type::Color3 => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Color3(value) => Some(*value),
+                    _ => None,
+                })?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.r))?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.g))?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.b))?;
+            }
+            Type::Vector2 => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Vector2(value) => Some(*value),
+                    _ => None,
+                })?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.x))?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.y))?;
+            }
+            Type::Vector3 => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Vector3(value) => Some(*value),
+                    _ => None,
+                })?;
+                write_vector3s(output, &values)?;
+            }
+            Type::CFrame => {
+                let values = self.collect(values, |value| match value {
+                    Variant::CFrame(value) => Some(*value),
+                    _ => None,
+                })?;
+                write_rotations(output, values.iter().map(|value| value.orientation))?;
+                let positions: Vec<_> = values.iter().map(|value| value.position).collect();
+                write_vector3s(output, &positions)?;
+            }
+            Type::Enum => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Enum(value) => Some(value.to_u32()),
+                    Variant::EnumItem(EnumItem { value, .. }) => Some(*value),
+                    _ => None,
+                })?;
+                output.write_interleaved_u32_array(&values)?;
+            }
+            Type::Ref => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Ref(value) => self.referents.get_referent(*value),
+                    _ => None,
+                })?;
+                output.write_referent_array(values.into_iter())?;
+            }
+            Type::Vector3int16 => {
+                for value in values {
+                    match value {
+                        Variant::Vector3int16(value) => {
+                            output.write_le_i16(value.x)?;
+                            output.write_le_i16(value.y)?;
+                            output.write_le_i16(value.z)?;
+                        }
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::NumberSequence => {
+                for value in values {
+                    match value {
+                        Variant::NumberSequence(value) => {
+                            output.write_le_u32(value.keypoints.len() as u32)?;
+
+                            for keypoint in &value.keypoints {
+                                output.write_le_f32(keypoint.time)?;
+                                output.write_le_f32(keypoint.value)?;
+                                output.write_le_f32(keypoint.envelope)?;
+                            }
+                        }
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::ColorSequence => {
+                for value in values {
+                    match value {
+                        Variant::ColorSequence(value) => {
+                            output.write_le_u32(value.keypoints.len() as u32)?;
+
+                            for keypoint in &value.keypoints {
+                                output.write_le_f32(keypoint.time)?;
+                                output.write_le_f32(keypoint.color.r)?;
+                                output.write_le_f32(keypoint.color.g)?;
+                                output.write_le_f32(keypoint.color.b)?;
+                                output.write_le_f32(0.0)?;
+                            }
+                        }
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::NumberRange => {
+                for value in values {
+                    match value {
+                        Variant::NumberRange(value) => {
+                            output.write_le_f32(value.min)?;
+                            output.write_le_f32(value.max)?;
+                        }
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::Rect => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Rect(value) => Some(*value),
+                    _ => None,
+                })?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.min.x))?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.min.y))?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.max.x))?;
+                output.write_interleaved_f32_array(values.iter().map(|value| value.max.y))?;
+            }
+            Type::PhysicalProperties => {
+                for value in values {
+                    match value {
+                        Variant::PhysicalProperties(PhysicalProperties::Custom(props)) => {
+                            output.write_u8(1)?;
+                            output.write_le_f32(props.density)?;
+                            output.write_le_f32(props.friction)?;
+                            output.write_le_f32(props.elasticity)?;
+                            output.write_le_f32(props.friction_weight)?;
+                            output.write_le_f32(props.elasticity_weight)?;
+                        }
+                        Variant::PhysicalProperties(PhysicalProperties::Default) => {
+                            output.write_u8(0)?;
+                        }
+                        _ => return Err(self.invalid_value(value)),
+                    }
+                }
+            }
+            Type::Color3uint8 => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Color3uint8(value) => Some(*value),
+                    Variant::Color3(value) => Some((*value).into()),
+                    _ => None,
+                })?;
+                output.extend(values.iter().map(|value| value.r));
+                output.extend(values.iter().map(|value| value.g));
+                output.extend(values.iter().map(|value| value.b));
+            }
+            Type::Int64 => {
+                let values = self.collect(values, |value| match value {
+                    Variant::Int64(value) => Some(*value),
+                    Variant::Int32(value) => Some(*value as i64),
+                    _ => None,
+                })?;
+                output.write_interleaved_i64_array(values.into_iter())?;
+            }
+            Type::SharedString => {
+                let values = self.collect(values, |value| match value {
+                    Variant::SharedString(value) => self.shared_strings.ids.get(value).copied(),
+                    _ => None,
+                })?;
+                output.write_interleaved_u32_array(&values)?;
+            }
+            Type::OptionalCFrame => {
+                let values = self.collect(values, |value| match value {
+                    Variant::OptionalCFrame(value) => Some(*value),
+                    _ => None,
+                })?;
+                let cframes: Vec<_> = values
+                    .iter()
+                    .map(|value| {
+                        value.unwrap_or_else(|| {
+                            CFrame::new(Vector3::new(0.0, 0.0, 0.0), Matrix3::identity())
+                        })
+                    })
+                    .collect();
+
+                output.write_u8(Type::CFrame as u8)?;
+                write_rotations(output, cframes.iter().map(|value| value.orientation))?;
+                let positions: Vec<_> = cframes.iter().map(|value| value.position).collect();
+                write_vector3s(output, &positions)?;
+
+                output.write_u8(Type::Bool as u8)?;
+                for value in &values {
+                    output.write_bool(value.is_some())?;
+                }
+            }
+            Type::UniqueId => {
+                let values = self.collect(values, |value| match value {
+                    Variant::UniqueId(value) => Some(*value),
+                    _ => None,
+                })?;
+                let blobs: Vec<[u8; 16]> = values
+                    .iter()
+                    .map(|value| {
+                        let mut blob = [0; 16];
+                        blob[0..4].copy_from_slice(&value.index().to_be_bytes());
+                        blob[4..8].copy_from_slice(&value.time().to_be_bytes());
+                        blob[8..].copy_from_slice(&value.random().rotate_left(1).to_be_bytes());
+                        blob
+                    })
+                    .collect();
+                output.write_interleaved_bytes::<16>(&blobs)?;
+            }
+            Type::SecurityCapabilities => {
+                let values = self.collect(values, |value| match value {
+                    Variant::SecurityCapabilities(value) => Some(value.bits() as i64),
+                    _ => None,
+                })?;
+                output.write_interleaved_i64_array(values.into_iter())?;
+            }
+            Type::Content => {
+                let mut source_types = Vec::with_capacity(values.len());
+                let mut uris = Vec::new();
+                let mut objects = Vec::new();
+
+                for value in values {
+                    let source_type = match value {
+                        Variant::Content(content) => match content.value() {
+                            ContentType::None => 0,
+                            ContentType::Uri(uri) => {
+                                uris.push(uri.as_str());
+                                1
+                            }
+                            ContentType::Object(referent) => {
+                                match self.referents.get_referent(*referent) {
+                                    Some(referent) => objects.push(referent),
+                                    None => return Err(self.invalid_value(value)),
+                                }
+                                2
+                            }
+                            _ => return Err(self.invalid_value(value)),
+                        },
+                        _ => return Err(self.invalid_value(value)),
+                    };
+
+                    source_types.push(source_type);
+                }
+
+                output.write_interleaved_i32_array(source_types.into_iter())?;
+                output.write_le_u32(uris.len() as u32)?;
+                for uri in uris {
+                    output.write_string(uri)?;
+                }
+                output.write_le_u32(objects.len() as u32)?;
+                output.write_referent_array(objects.into_iter())?;
+                output.write_le_u32(0)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Maps every value with `convert`, failing on the first value it returns
+    /// `None` for.
+    fn collect<T, F>(&self, values: &[Variant], mut convert: F) -> Result<Vec<T>, TranscodeError>
+    where
+        F: FnMut(&Variant) -> Option<T>,
+    {
+        values
+            .iter()
+            .map(|value| convert(value).ok_or_else(|| self.invalid_value(value)))
+            .collect()
+    }
+
+    fn read_rotations(&self, reader: &mut &[u8], count: usize) -> Result<Vec<Matrix3>, InnerError> {
+        let mut rotations = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let id = reader.read_u8()?;
+
+            let rotation = if id == 0 {
+                Matrix3::new(
+                    read_vector3(reader)?,
+                    read_vector3(reader)?,
+                    read_vector3(reader)?,
+                )
+            } else {
+                Matrix3::from_basic_rotation_id(id).map_err(|_| InnerError::BadRotationId {
+                    type_name: self.class.to_owned(),
+                    prop_name: self.property.to_owned(),
+                    id,
+                })?
+            };
+
+            rotations.push(rotation);
+        }
+
+        Ok(rotations)
+    }
+
+    /// Reads one of the type bytes OptionalCFrame values are stored with.
+    fn check_type_byte(&self, reader: &mut &[u8], expected: Type) -> Result<(), InnerError> {
+        let actual_type_id = reader.read_u8()?;
+
+        if actual_type_id == expected as u8 {
+            Ok(())
+        } else {
+            Err(InnerError::BadOptionalCFrameFormat {
+                expected_type_name: format!("{expected:?}"),
+                expected_type_id: expected as u8,
+                actual_type_id,
+            })
+        }
+    }
+
+    fn invalid_data<T: ToString>(&self, valid_value: &'static str, actual_value: T) -> InnerError {
+        InnerError::InvalidPropData {
+            type_name: self.class.to_owned(),
+            prop_name: self.property.to_owned(),
+            valid_value,
+            actual_value: actual_value.to_string(),
+        }
+    }
+
+    fn invalid_value(&self, value: &Variant) -> TranscodeError {
+        TranscodeError::InvalidValue {
+            class: self.class.to_owned(),
+            property: self.property.to_owned(),
+            value_type: value.ty(),
+        }
+    }
+}
+
+fn read_i32s(reader: &mut &[u8], count: usize) -> io::Result<Vec<i32>> {
+    let mut values = vec![0; count];
+    reader.read_interleaved_i32_array(&mut values)?;
+    Ok(values)
+}
+
+fn read_u32s(reader: &mut &[u8], count: usize) -> io::Result<Vec<u32>> {
+    let mut values = vec![0; count];
+    reader.read_interleaved_u32_array(&mut values)?;
+    Ok(values)
+}
+
+fn read_f32s(reader: &mut &[u8], count: usize) -> io::Result<Vec<f32>> {
+    let mut values = vec![0.0; count];
+    reader.read_interleaved_f32_array(&mut values)?;
+    Ok(values)
+}
+
+fn read_i64s(reader: &mut &[u8], count: usize) -> io::Result<Vec<i64>> {
+    let mut values = vec![0; count];
+    reader.read_interleaved_i64_array(&mut values)?;
+    Ok(values)
+}
+
+fn read_referents(reader: &mut &[u8], count: usize) -> io::Result<Vec<i32>> {
+    let mut values = vec![0; count];
+    reader.read_referent_array(&mut values)?;
+    Ok(values)
+}
+
+fn read_vector3(reader: &mut &[u8]) -> io::Result<Vector3> {
+    Ok(Vector3::new(
+        reader.read_le_f32()?,
+        reader.read_le_f32()?,
+        reader.read_le_f32()?,
+    ))
+}
+
+fn read_vector3s(reader: &mut &[u8], count: usize) -> io::Result<Vec<Vector3>> {
+    let x = read_f32s(reader, count)?;
+    let y = read_f32s(reader, count)?;
+    let z = read_f32s(reader, count)?;
+
+    Ok(x.into_iter()
+        .zip(y)
+        .zip(z)
+        .map(|((x, y), z)| Vector3::new(x, y, z))
+        .collect())
+}
+
+fn write_vector3<W: Write>(output: &mut W, value: Vector3) -> io::Result<()> {
+    output.write_le_f32(value.x)?;
+    output.write_le_f32(value.y)?;
+    output.write_le_f32(value.z)
+}
+
+fn write_vector3s<W: Write>(output: &mut W, values: &[Vector3]) -> io::Result<()> {
+    output.write_interleaved_f32_array(values.iter().map(|value| value.x))?;
+    output.write_interleaved_f32_array(values.iter().map(|value| value.y))?;
+    output.write_interleaved_f32_array(values.iter().map(|value| value.z))
+}
+
+fn write_rotations<W, I>(output: &mut W, rotations: I) -> io::Result<()>
+where
+    W: Write,
+    I: Iterator<Item = Matrix3>,
+{
+    for matrix in rotations {
+        if let Some(id) = matrix.to_basic_rotation_id() {
+            output.write_u8(id)?;
+        } else {
+            output.write_u8(0x00)?;
+            write_vector3(output, matrix.x)?;
+            write_vector3(output, matrix.y)?;
+            write_vector3(output, matrix.z)?;
+        }
+    }
+
+    Ok(())
+}
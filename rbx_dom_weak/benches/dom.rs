@@ -0,0 +1,85 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rbx_dom_weak::{types::Ref, InstanceBuilder, WeakDom};
+
+const INSTANCES: usize = 10_000;
+
+/// Builds a dom of `INSTANCES` folders split between 100 parent folders, so
+/// that every benchmark works on the same shape of tree.
+fn build_dom() -> (WeakDom, Vec<Ref>) {
+    let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+    let root_ref = dom.root_ref();
+    let mut referents = Vec::with_capacity(INSTANCES);
+
+    for _ in 0..INSTANCES / 100 {
+        let parent = dom.insert(root_ref, InstanceBuilder::new("Folder"));
+        referents.push(parent);
+
+        for _ in 1..100 {
+            referents.push(dom.insert(parent, InstanceBuilder::new("Folder")));
+        }
+    }
+
+    (dom, referents)
+}
+
+pub fn weak_dom(c: &mut Criterion) {
+    let mut group = c.benchmark_group("10,000 Folders");
+    group.throughput(Throughput::Elements(INSTANCES as u64));
+
+    group.bench_function("Insert", |b| b.iter(build_dom));
+
+    group.bench_function("Mutate", |b| {
+        b.iter_batched_ref(
+            build_dom,
+            |(dom, referents)| {
+                for &referent in referents.iter() {
+                    dom.get_by_ref_mut(referent).unwrap().name.push('!');
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    let (dom, referents) = build_dom();
+
+    group.bench_function("Read", |b| {
+        b.iter(|| {
+            referents
+                .iter()
+                .map(|&referent| dom.get_by_ref(referent).unwrap().name.len())
+                .sum::<usize>()
+        })
+    });
+
+    group.bench_function("Traverse", |b| b.iter(|| dom.descendants().count()));
+
+    group.bench_function("Destroy", |b| {
+        b.iter_batched_ref(
+            build_dom,
+            |(dom, referents)| {
+                for &referent in referents.iter().step_by(100) {
+                    dom.destroy(referent);
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    // Copying the whole dom is what `freeze` is meant to replace, so it's
+    // here to compare against.
+    group.bench_function("Copy", |b| {
+        b.iter(|| {
+            let mut copy = WeakDom::new(InstanceBuilder::new("Folder"));
+            dom.clone_multiple_into_external(dom.root().children(), &mut copy);
+            copy
+        })
+    });
+
+    #[cfg(feature = "snapshots")]
+    group.bench_function("Freeze", |b| b.iter(|| dom.freeze()));
+
+    group.finish();
+}
+
+criterion_group!(bench_suite, weak_dom);
+criterion_main!(bench_suite);
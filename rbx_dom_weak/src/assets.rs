@@ -0,0 +1,350 @@
+use ahash::AHashMap;
+use serde::Serialize;
+
+use crate::{
+    instance::Instance,
+    types::{Content, ContentId, Ref, Variant},
+    Symbol, WeakDom,
+};
+
+/// Every asset referred to by the `Content` and `ContentId` properties of a
+/// [`WeakDom`], like `MeshId`, `TextureID`, and `SoundId`.
+///
+/// URLs are normalized with [`normalize_asset_url`] before being compared, so
+/// an asset referred to as both `rbxassetid://123` and
+/// `http://www.roblox.com/asset/?id=123` is only listed once. Because
+/// `AssetManifest` implements `Serialize`, it can be written out as JSON or
+/// any other format supported by serde.
+///
+/// ```
+/// use rbx_dom_weak::{types::ContentId, AssetManifest, InstanceBuilder, WeakDom};
+///
+/// let dom = WeakDom::new(
+///     InstanceBuilder::new("Sound")
+///         .with_property("SoundId", ContentId::from("rbxassetid://123")),
+/// );
+///
+/// let manifest = AssetManifest::new(&dom);
+///
+/// for asset in &manifest.assets {
+///     println!("{} is used {} times", asset.url, asset.references.len());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetManifest {
+    /// Every distinct asset, in the order they first appear in the dom.
+    pub assets: Vec<Asset>,
+}
+
+/// An asset listed in an [`AssetManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Asset {
+    /// The normalized URL of the asset.
+    pub url: String,
+
+    /// The numeric ID of the asset, if it's hosted on Roblox. Assets that
+    /// ship with the client, like `rbxasset://textures/face.png`, have no ID.
+    pub id: Option<u64>,
+
+    /// Every property that refers to the asset, in dom order.
+    pub references: Vec<AssetReference>,
+}
+
+/// A property that refers to an [`Asset`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetReference {
+    /// The referent of the instance that has the property.
+    pub referent: Ref,
+
+    /// The name of the property.
+    pub property: Symbol,
+}
+
+impl AssetManifest {
+    /// Collects every asset referred to by the instances in `dom`. Empty
+    /// `Content` and `ContentId` values are skipped.
+    pub fn new(dom: &WeakDom) -> Self {
+        let mut assets: Vec<Asset> = Vec::new();
+        let mut indices: AHashMap<String, usize> = AHashMap::new();
+
+        for instance in dom.descendants() {
+            // Properties are stored in a hash map, so they're sorted to keep
+            // the manifest stable.
+            let properties = instance
+                .sorted_properties()
+                .into_iter()
+                .filter_map(|(property, value)| Some((property, content_url(value)?)));
+
+            for (property, url) in properties {
+                let url = normalize_asset_url(url);
+                let index = *indices.entry(url).or_insert_with_key(|url| {
+                    assets.push(Asset {
+                        url: url.clone(),
+                        id: asset_id(url),
+                        references: Vec::new(),
+                    });
+                    assets.len() - 1
+                });
+
+                assets[index].references.push(AssetReference {
+                    referent: instance.referent(),
+                    property,
+                });
+            }
+        }
+
+        Self { assets }
+    }
+}
+
+impl WeakDom {
+    /// Replaces the URL of every non-empty `Content` and `ContentId` property
+    /// in the dom with the result of calling `rewrite` on it, like when moving
+    /// assets between universes. URLs are passed to `rewrite` as they are
+    /// stored; use [`normalize_asset_url`] or [`asset_id`] to handle the
+    /// different formats Roblox accepts. Returns the number of properties that
+    /// were changed.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{asset_id, types::ContentId, InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(
+    ///     InstanceBuilder::new("Sound")
+    ///         .with_property("SoundId", ContentId::from("rbxassetid://123")),
+    /// );
+    ///
+    /// let changed = dom.rewrite_content(|url| match asset_id(url) {
+    ///     Some(123) => "rbxassetid://456".to_owned(),
+    ///     _ => url.to_owned(),
+    /// });
+    /// assert_eq!(changed, 1);
+    /// ```
+    pub fn rewrite_content<F>(&mut self, mut rewrite: F) -> usize
+    where
+        F: FnMut(&str) -> String,
+    {
+        let referents: Vec<Ref> = self.descendants().map(Instance::referent).collect();
+        let mut changed = 0;
+
+        for referent in referents {
+            let instance = self.get_by_ref_mut(referent).unwrap();
+
+            for value in instance.properties.values_mut() {
+                let url = match content_url(value) {
+                    Some(url) => url,
+                    None => continue,
+                };
+
+                let new_url = rewrite(url);
+                if new_url == url {
+                    continue;
+                }
+
+                *value = match value {
+                    Variant::Content(_) => Content::from_uri(new_url).into(),
+                    _ => ContentId::from(new_url).into(),
+                };
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+}
+
+/// Returns the URL stored in a `Content` or `ContentId` value, unless it's
+/// empty.
+pub(crate) fn content_url(value: &Variant) -> Option<&str> {
+    let url = match value {
+        Variant::Content(content) => content.as_uri()?,
+        Variant::ContentId(content_id) => content_id.as_str(),
+        _ => return None,
+    };
+
+    if url.is_empty() {
+        None
+    } else {
+        Some(url)
+    }
+}
+
+/// Returns the numeric ID of an asset hosted on Roblox from any of the URL
+/// formats Roblox accepts, like `rbxassetid://123`,
+/// `http://www.roblox.com/asset/?id=123`, or
+/// `https://assetdelivery.roblox.com/v1/asset/?id=123`.
+pub fn asset_id(url: &str) -> Option<u64> {
+    let url = url.trim().to_ascii_lowercase();
+
+    if let Some(id) = url.strip_prefix("rbxassetid://") {
+        return id.parse().ok();
+    }
+
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let (host, path) = rest.split_once('/')?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    if host != "roblox.com" && host != "assetdelivery.roblox.com" {
+        return None;
+    }
+
+    let (path, query) = path.split_once('?')?;
+    if !matches!(path.trim_end_matches('/'), "asset" | "v1/asset") {
+        return None;
+    }
+
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("id="))?
+        .parse()
+        .ok()
+}
+
+/// Converts a URL referring to an asset hosted on Roblox into the
+/// `rbxassetid://` format. Other URLs are returned without surrounding
+/// whitespace, but are otherwise unchanged.
+pub fn normalize_asset_url(url: &str) -> String {
+    match asset_id(url) {
+        Some(id) => format!("rbxassetid://{}", id),
+        None => url.trim().to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn normalize() {
+        for url in [
+            "rbxassetid://123",
+            " rbxassetid://123 ",
+            "http://www.roblox.com/asset/?id=123",
+            "https://www.roblox.com/asset?id=123",
+            "https://assetdelivery.roblox.com/v1/asset/?id=123&version=2",
+            "HTTP://WWW.ROBLOX.COM/ASSET/?ID=123",
+        ] {
+            assert_eq!(asset_id(url), Some(123), "{}", url);
+            assert_eq!(normalize_asset_url(url), "rbxassetid://123");
+        }
+
+        for url in [
+            "rbxasset://textures/face.png",
+            "https://example.com/asset/?id=123",
+            "rbxassetid://not-a-number",
+        ] {
+            assert_eq!(asset_id(url), None, "{}", url);
+            assert_eq!(normalize_asset_url(url), url);
+        }
+    }
+
+    #[test]
+    fn manifest() {
+        let mesh = InstanceBuilder::new("MeshPart")
+            .with_property("MeshId", ContentId::from("rbxassetid://1"))
+            .with_property("TextureID", ContentId::from("rbxasset://textures/face.png"))
+            .with_property(
+                "MeshContent",
+                Content::from("http://www.roblox.com/asset/?id=1"),
+            );
+        let mesh_ref = mesh.referent();
+        let sound = InstanceBuilder::new("Sound")
+            .with_property("SoundId", ContentId::from("rbxassetid://2"))
+            .with_property("EmptyId", ContentId::new());
+        let sound_ref = sound.referent();
+
+        let dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([mesh, sound]));
+        let manifest = AssetManifest::new(&dom);
+
+        assert_eq!(
+            manifest.assets,
+            [
+                Asset {
+                    url: "rbxassetid://1".to_owned(),
+                    id: Some(1),
+                    references: vec![
+                        AssetReference {
+                            referent: mesh_ref,
+                            property: "MeshContent".into(),
+                        },
+                        AssetReference {
+                            referent: mesh_ref,
+                            property: "MeshId".into(),
+                        },
+                    ],
+                },
+                Asset {
+                    url: "rbxasset://textures/face.png".to_owned(),
+                    id: None,
+                    references: vec![AssetReference {
+                        referent: mesh_ref,
+                        property: "TextureID".into(),
+                    }],
+                },
+                Asset {
+                    url: "rbxassetid://2".to_owned(),
+                    id: Some(2),
+                    references: vec![AssetReference {
+                        referent: sound_ref,
+                        property: "SoundId".into(),
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rewrite_content() {
+        let mesh = InstanceBuilder::new("MeshPart")
+            .with_property(
+                "MeshId",
+                ContentId::from("http://www.roblox.com/asset/?id=1"),
+            )
+            .with_property("MeshContent", Content::from("rbxassetid://1"))
+            .with_property("TextureID", ContentId::from("rbxasset://textures/face.png"))
+            .with_property("EmptyId", ContentId::new());
+        let mesh_ref = mesh.referent();
+
+        let mut dom = WeakDom::new(mesh);
+        let mut seen = Vec::new();
+        let changed = dom.rewrite_content(|url| {
+            seen.push(url.to_owned());
+
+            match asset_id(url) {
+                Some(1) => "rbxassetid://2".to_owned(),
+                _ => url.to_owned(),
+            }
+        });
+
+        seen.sort();
+        assert_eq!(
+            seen,
+            [
+                "http://www.roblox.com/asset/?id=1",
+                "rbxasset://textures/face.png",
+                "rbxassetid://1",
+            ]
+        );
+        assert_eq!(changed, 2);
+
+        let properties = &dom.get_by_ref(mesh_ref).unwrap().properties;
+        assert_eq!(
+            properties.get("MeshId"),
+            Some(&ContentId::from("rbxassetid://2").into())
+        );
+        assert_eq!(
+            properties.get("MeshContent"),
+            Some(&Content::from("rbxassetid://2").into())
+        );
+        assert_eq!(
+            properties.get("TextureID"),
+            Some(&ContentId::from("rbxasset://textures/face.png").into())
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt;
+
+use ahash::AHashMap;
+
+use crate::{
+    symbol,
+    types::{Ref, Variant, VariantType},
+    Symbol, SymbolMap, WeakDom,
+};
+
+/// The attributes that instances of some class, or with some tag, are
+/// expected to have, used by [`WeakDom::validate_attributes`].
+///
+/// ```
+/// use rbx_dom_weak::{types::VariantType, AttributeSchema};
+///
+/// let enemy = AttributeSchema::new()
+///     .with_required("Health", VariantType::Float64)
+///     .with_optional("Team", VariantType::String);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttributeSchema {
+    attributes: Vec<(String, VariantType, bool)>,
+}
+
+impl AttributeSchema {
+    /// Creates an `AttributeSchema` that doesn't expect any attributes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects instances to have the attribute `name`, holding a value of
+    /// type `ty`.
+    pub fn with_required<S: Into<String>>(mut self, name: S, ty: VariantType) -> Self {
+        self.attributes.push((name.into(), ty, true));
+        self
+    }
+
+    /// Allows instances to leave out the attribute `name`, but expects it to
+    /// hold a value of type `ty` if they have it.
+    pub fn with_optional<S: Into<String>>(mut self, name: S, ty: VariantType) -> Self {
+        self.attributes.push((name.into(), ty, false));
+        self
+    }
+}
+
+/// The [`AttributeSchema`]s that each class and tag should follow, used by
+/// [`WeakDom::validate_attributes`].
+///
+/// An instance is checked against the schema of its class as well as the
+/// schema of each of its tags. Classes are matched exactly, so a schema for
+/// `BasePart` isn't used for a `Part`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttributeSchemas {
+    classes: SymbolMap<AttributeSchema>,
+    tags: AHashMap<String, AttributeSchema>,
+}
+
+impl AttributeSchemas {
+    /// Creates an empty `AttributeSchemas`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the schema that instances of `class` should follow, replacing
+    /// any schema set for it before.
+    pub fn with_class<S: Into<Symbol>>(mut self, class: S, schema: AttributeSchema) -> Self {
+        self.classes.insert(class.into(), schema);
+        self
+    }
+
+    /// Sets the schema that instances with the tag `tag` should follow,
+    /// replacing any schema set for it before.
+    pub fn with_tag<S: Into<String>>(mut self, tag: S, schema: AttributeSchema) -> Self {
+        self.tags.insert(tag.into(), schema);
+        self
+    }
+}
+
+/// An attribute of an instance that doesn't follow its [`AttributeSchema`],
+/// returned by [`WeakDom::validate_attributes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeMismatch {
+    /// The referent of the instance.
+    pub referent: Ref,
+
+    /// The name of the attribute.
+    pub attribute: String,
+
+    /// What is wrong with the attribute.
+    pub problem: AttributeProblem,
+}
+
+/// What is wrong with an attribute, as part of an [`AttributeMismatch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AttributeProblem {
+    /// The attribute is required, but the instance doesn't have it.
+    Missing {
+        /// The type the attribute should have.
+        expected: VariantType,
+    },
+
+    /// The attribute holds a value of the wrong type.
+    WrongType {
+        /// The type the attribute should have.
+        expected: VariantType,
+
+        /// The type the attribute has.
+        actual: VariantType,
+    },
+}
+
+impl fmt::Display for AttributeProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeProblem::Missing { expected } => {
+                write!(f, "is missing, but should be a {:?}", expected)
+            }
+            AttributeProblem::WrongType { expected, actual } => {
+                write!(f, "is a {:?}, but should be a {:?}", actual, expected)
+            }
+        }
+    }
+}
+
+impl WeakDom {
+    /// Checks the attributes of every instance in the dom against the schemas
+    /// for its class and tags, returning every attribute that's missing or
+    /// has the wrong type, in the order the instances appear in the dom.
+    ///
+    /// Attributes that aren't in any schema are allowed. String attributes
+    /// read from binary files as `BinaryString`s count as `String`s.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{
+    ///     types::{Attributes, Tags, VariantType},
+    ///     AttributeProblem, AttributeSchema, AttributeSchemas, InstanceBuilder, WeakDom,
+    /// };
+    ///
+    /// let mut tags = Tags::new();
+    /// tags.push("Enemy");
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Model")
+    ///         .with_property("Tags", tags)
+    ///         .with_property("Attributes", Attributes::new().with("Health", "lots")),
+    /// );
+    ///
+    /// let schemas = AttributeSchemas::new().with_tag(
+    ///     "Enemy",
+    ///     AttributeSchema::new().with_required("Health", VariantType::Float64),
+    /// );
+    ///
+    /// let mismatches = dom.validate_attributes(&schemas);
+    /// assert_eq!(mismatches.len(), 1);
+    /// assert_eq!(
+    ///     mismatches[0].problem,
+    ///     AttributeProblem::WrongType {
+    ///         expected: VariantType::Float64,
+    ///         actual: VariantType::String,
+    ///     }
+    /// );
+    /// ```
+    pub fn validate_attributes(&self, schemas: &AttributeSchemas) -> Vec<AttributeMismatch> {
+        let tags_key = symbol("Tags");
+        let attributes_key = symbol("Attributes");
+        let mut mismatches = Vec::new();
+
+        for instance in self.descendants() {
+            let mut applicable: Vec<&AttributeSchema> =
+                schemas.classes.get(&instance.class).into_iter().collect();
+
+            if let Some(Variant::Tags(tags)) = instance.properties.get(&tags_key) {
+                applicable.extend(tags.iter().filter_map(|tag| schemas.tags.get(tag)));
+            }
+
+            if applicable.is_empty() {
+                continue;
+            }
+
+            let attributes = match instance.properties.get(&attributes_key) {
+                Some(Variant::Attributes(attributes)) => Some(attributes),
+                _ => None,
+            };
+
+            for schema in applicable {
+                for (name, expected, required) in &schema.attributes {
+                    let expected = *expected;
+                    let problem = match attributes
+                        .and_then(|attributes| attributes.get(name.as_str()))
+                    {
+                        None if *required => AttributeProblem::Missing { expected },
+                        None => continue,
+                        Some(value) => match (value.ty(), expected) {
+                            (VariantType::BinaryString, VariantType::String) => continue,
+                            (actual, expected) if actual == expected => continue,
+                            (actual, expected) => AttributeProblem::WrongType { expected, actual },
+                        },
+                    };
+
+                    mismatches.push(AttributeMismatch {
+                        referent: instance.referent(),
+                        attribute: name.clone(),
+                        problem,
+                    });
+                }
+            }
+        }
+
+        mismatches
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{
+        types::{Attributes, BinaryString, Tags},
+        InstanceBuilder,
+    };
+
+    #[test]
+    fn validate_attributes() {
+        let mut tags = Tags::new();
+        tags.push("Spawner");
+        tags.push("Untracked");
+
+        let good = InstanceBuilder::new("Part").with_property(
+            "Attributes",
+            Attributes::new()
+                .with("Health", 100.0f64)
+                .with("Team", BinaryString::from(b"Red".to_vec()))
+                .with("Extra", true),
+        );
+        let bad = InstanceBuilder::new("Part")
+            .with_property("Tags", tags)
+            .with_property(
+                "Attributes",
+                Attributes::new().with("Team", 1.0f64).with("Rate", 2.0f32),
+            );
+        let bad_ref = bad.referent();
+        let unchecked = InstanceBuilder::new("Folder");
+
+        let dom =
+            WeakDom::new(InstanceBuilder::new("Workspace").with_children([good, bad, unchecked]));
+
+        let schemas = AttributeSchemas::new()
+            .with_class(
+                "Part",
+                AttributeSchema::new()
+                    .with_required("Health", VariantType::Float64)
+                    .with_optional("Team", VariantType::String),
+            )
+            .with_tag(
+                "Spawner",
+                AttributeSchema::new()
+                    .with_required("Rate", VariantType::Float64)
+                    .with_optional("Limit", VariantType::Int32),
+            );
+
+        let mismatches = dom.validate_attributes(&schemas);
+        let found: Vec<_> = mismatches
+            .iter()
+            .map(|mismatch| {
+                assert_eq!(mismatch.referent, bad_ref);
+                (mismatch.attribute.as_str(), mismatch.problem)
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                (
+                    "Health",
+                    AttributeProblem::Missing {
+                        expected: VariantType::Float64
+                    }
+                ),
+                (
+                    "Team",
+                    AttributeProblem::WrongType {
+                        expected: VariantType::String,
+                        actual: VariantType::Float64
+                    }
+                ),
+                (
+                    "Rate",
+                    AttributeProblem::WrongType {
+                        expected: VariantType::Float64,
+                        actual: VariantType::Float32
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            found[1].1.to_string(),
+            "is a Float64, but should be a String"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use std::fmt;
+
+use rbx_types::{Ref, Variant};
+
+use crate::{instance::InstanceBuilder, Symbol, WeakDom};
+
+/// Constructs a [`WeakDom`] piece by piece, which is faster than inserting
+/// into a `WeakDom` directly when building large doms programmatically.
+///
+/// Instances inserted with a `WeakDomBuilder` are never looked up after
+/// they've been inserted, and space for them can be reserved up front. `Ref`
+/// properties can also be linked to instances by their path, which is
+/// resolved once the whole dom has been built.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, WeakDomBuilder};
+///
+/// let mut builder = WeakDomBuilder::with_capacity(InstanceBuilder::new("DataModel"), 3);
+///
+/// let model = InstanceBuilder::new("Model");
+/// let model_ref = model.referent();
+///
+/// builder.insert(builder.root_ref(), InstanceBuilder::new("Workspace"));
+/// builder.insert_many(builder.root_ref(), vec![
+///     model.with_child(InstanceBuilder::new("Part").with_name("Base")),
+/// ]);
+/// builder.link_path(model_ref, "PrimaryPart", "Model/Base");
+///
+/// let dom = builder.build()?;
+/// # Ok::<(), rbx_dom_weak::UnresolvedPathError>(())
+/// ```
+#[derive(Debug)]
+pub struct WeakDomBuilder {
+    dom: WeakDom,
+
+    /// Every link that needs to be resolved, along with the referent of the
+    /// instance its path starts from.
+    links: Vec<(Ref, UnresolvedPath)>,
+}
+
+impl WeakDomBuilder {
+    /// Creates a `WeakDomBuilder` whose dom has `root` at its root.
+    pub fn new(root: InstanceBuilder) -> Self {
+        Self {
+            dom: WeakDom::new(root),
+            links: Vec::new(),
+        }
+    }
+
+    /// Creates a `WeakDomBuilder` whose dom has `root` at its root, with space
+    /// reserved for at least `capacity` more instances.
+    pub fn with_capacity(root: InstanceBuilder, capacity: usize) -> Self {
+        let mut builder = Self::new(root);
+        builder.reserve(capacity);
+        builder
+    }
+
+    /// Reserves space for at least `additional` more instances.
+    pub fn reserve(&mut self, additional: usize) {
+        self.dom.reserve(additional);
+    }
+
+    /// Returns the referent of the root instance of the dom being built.
+    pub fn root_ref(&self) -> Ref {
+        self.dom.root_ref()
+    }
+
+    /// Inserts `builder` and its descendants as a child of `parent_ref`,
+    /// returning the referent of `builder`.
+    ///
+    /// ## Panics
+    /// Panics if `parent_ref` does not refer to an instance in the dom.
+    pub fn insert(&mut self, parent_ref: Ref, builder: InstanceBuilder) -> Ref {
+        let referent = builder.referent();
+        self.insert_many(parent_ref, std::iter::once(builder));
+        referent
+    }
+
+    /// Inserts every builder in `builders`, along with their descendants, as
+    /// children of `parent_ref`. `parent_ref` is only looked up once, no
+    /// matter how many builders there are.
+    ///
+    /// ## Panics
+    /// Panics if `parent_ref` does not refer to an instance in the dom.
+    pub fn insert_many<I>(&mut self, parent_ref: Ref, builders: I)
+    where
+        I: IntoIterator<Item = InstanceBuilder>,
+    {
+        if self.dom.get_by_ref(parent_ref).is_none() {
+            panic!("cannot insert into parent that does not exist");
+        }
+
+        let mut children = Vec::new();
+        let mut links = Vec::new();
+        for builder in builders {
+            children.push(builder.referent());
+            self.dom.insert_detached(parent_ref, builder, &mut links);
+        }
+
+        for (referent, property, path) in links {
+            self.links.push((
+                referent,
+                UnresolvedPath {
+                    referent,
+                    property,
+                    path,
+                },
+            ));
+        }
+
+        self.dom
+            .get_by_ref_mut(parent_ref)
+            .unwrap()
+            .children
+            .extend(children);
+    }
+
+    /// Sets the `Ref` property `property` of the instance `referent` to the
+    /// instance at `path` once the dom is built. Paths work like the ones
+    /// given to [`InstanceBuilder::with_ref_property`], but start from the
+    /// root of the dom, like `Workspace/Model/Part`.
+    ///
+    /// `referent` does not need to have been inserted yet, as long as it has
+    /// been by the time [`WeakDomBuilder::build`] is called.
+    pub fn link_path<K, P>(&mut self, referent: Ref, property: K, path: P)
+    where
+        K: Into<Symbol>,
+        P: Into<String>,
+    {
+        self.links.push((
+            self.dom.root_ref(),
+            UnresolvedPath {
+                referent,
+                property: property.into(),
+                path: path.into(),
+            },
+        ));
+    }
+
+    /// Resolves every path linked with [`WeakDomBuilder::link_path`] or
+    /// [`InstanceBuilder::with_ref_property`] and returns the finished dom.
+    /// Unlike [`WeakDom::insert`], paths added with `with_ref_property` can
+    /// refer to instances that were inserted after them.
+    ///
+    /// Returns an error listing every link that couldn't be resolved, either
+    /// because nothing exists at its path or because its instance was never
+    /// inserted.
+    pub fn build(mut self) -> Result<WeakDom, UnresolvedPathError> {
+        let mut unresolved = Vec::new();
+
+        for (from, link) in self.links {
+            let target = resolve_path(&self.dom, from, &link.path);
+
+            match (target, self.dom.get_by_ref_mut(link.referent)) {
+                (Some(target), Some(instance)) => {
+                    instance
+                        .properties
+                        .insert(link.property, Variant::Ref(target));
+                }
+                _ => unresolved.push(link),
+            }
+        }
+
+        if unresolved.is_empty() {
+            Ok(self.dom)
+        } else {
+            Err(UnresolvedPathError { links: unresolved })
+        }
+    }
+}
+
+/// Finds the instance at `path` relative to `from`, as described by
+/// [`InstanceBuilder::with_ref_property`]. An empty path refers to `from`
+/// itself.
+pub(crate) fn resolve_path(dom: &WeakDom, from: Ref, path: &str) -> Option<Ref> {
+    let mut current = dom.get_by_ref(from)?;
+
+    for name in path.split('/').filter(|name| !name.is_empty()) {
+        current = match name {
+            ".." => dom.get_by_ref(current.parent())?,
+            _ => current
+                .children()
+                .iter()
+                .map(|child| dom.get_by_ref(*child).unwrap())
+                .find(|child| child.name == name)?,
+        };
+    }
+
+    Some(current.referent())
+}
+
+/// A `Ref` property that was linked to a path which could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedPath {
+    /// The referent of the instance that has the property.
+    pub referent: Ref,
+
+    /// The name of the property.
+    pub property: Symbol,
+
+    /// The path the property was linked to.
+    pub path: String,
+}
+
+/// Returned by [`WeakDomBuilder::build`] when some of the paths linked with
+/// [`WeakDomBuilder::link_path`] or [`InstanceBuilder::with_ref_property`]
+/// could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedPathError {
+    links: Vec<UnresolvedPath>,
+}
+
+impl UnresolvedPathError {
+    /// Returns every link that could not be resolved.
+    pub fn links(&self) -> &[UnresolvedPath] {
+        &self.links
+    }
+}
+
+impl fmt::Display for UnresolvedPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} Ref properties were linked to paths that could not be resolved",
+            self.links.len()
+        )
+    }
+}
+
+impl std::error::Error for UnresolvedPathError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::symbol;
+
+    #[test]
+    fn build() {
+        let mut builder = WeakDomBuilder::with_capacity(InstanceBuilder::new("DataModel"), 4);
+        let root_ref = builder.root_ref();
+
+        let model = InstanceBuilder::new("Model").with_child(
+            InstanceBuilder::new("Folder")
+                .with_name("Parts")
+                .with_child(InstanceBuilder::new("Part").with_name("Base")),
+        );
+        let model_ref = model.referent();
+        let value = InstanceBuilder::new("ObjectValue");
+        let value_ref = value.referent();
+
+        // Links can be made before their instances are inserted.
+        builder.link_path(model_ref, "PrimaryPart", "Workspace/Model/Parts/Base");
+        builder.link_path(value_ref, "Value", "");
+
+        let workspace_ref = builder.insert(root_ref, InstanceBuilder::new("Workspace"));
+        builder.insert_many(workspace_ref, vec![model, value]);
+
+        let dom = builder.build().unwrap();
+        let workspace = dom.get_by_ref(workspace_ref).unwrap();
+        assert_eq!(dom.root().children(), [workspace_ref]);
+        assert_eq!(workspace.children(), [model_ref, value_ref]);
+        assert_eq!(dom.get_by_ref(model_ref).unwrap().parent(), workspace_ref);
+
+        let base_ref = resolve_path(&dom, model_ref, "Parts/Base").unwrap();
+        assert_eq!(dom.get_by_ref(base_ref).unwrap().name, "Base");
+        assert_eq!(
+            dom.get_by_ref(model_ref)
+                .unwrap()
+                .properties
+                .get("PrimaryPart"),
+            Some(&Variant::Ref(base_ref))
+        );
+        assert_eq!(
+            dom.get_by_ref(value_ref).unwrap().properties.get("Value"),
+            Some(&Variant::Ref(root_ref))
+        );
+    }
+
+    #[test]
+    fn ref_properties_inserted_later() {
+        let mut builder = WeakDomBuilder::new(InstanceBuilder::new("DataModel"));
+        let root_ref = builder.root_ref();
+
+        // The target doesn't exist yet when the ObjectValue is inserted.
+        let value_ref = builder.insert(
+            root_ref,
+            InstanceBuilder::new("ObjectValue").with_ref_property("Value", "../Target"),
+        );
+        let target_ref =
+            builder.insert(root_ref, InstanceBuilder::new("Folder").with_name("Target"));
+
+        let dom = builder.build().unwrap();
+        assert_eq!(
+            dom.get_by_ref(value_ref).unwrap().properties.get("Value"),
+            Some(&Variant::Ref(target_ref))
+        );
+    }
+
+    #[test]
+    fn unresolved() {
+        let mut builder = WeakDomBuilder::new(InstanceBuilder::new("DataModel"));
+        let root_ref = builder.root_ref();
+        let never_inserted = Ref::new();
+
+        let value_ref = builder.insert(
+            root_ref,
+            InstanceBuilder::new("ObjectValue").with_ref_property("Value", "Missing"),
+        );
+        builder.link_path(root_ref, "Missing", "Workspace");
+        builder.link_path(never_inserted, "Value", "");
+
+        let error = builder.build().unwrap_err();
+        assert_eq!(
+            error.links(),
+            [
+                UnresolvedPath {
+                    referent: value_ref,
+                    property: symbol("Value"),
+                    path: "Missing".to_owned(),
+                },
+                UnresolvedPath {
+                    referent: root_ref,
+                    property: symbol("Missing"),
+                    path: "Workspace".to_owned(),
+                },
+                UnresolvedPath {
+                    referent: never_inserted,
+                    property: symbol("Value"),
+                    path: String::new(),
+                },
+            ]
+        );
+    }
+}
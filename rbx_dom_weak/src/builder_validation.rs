@@ -0,0 +1,265 @@
+use std::fmt;
+
+use rbx_reflection::{DataType, ReflectionDatabase};
+
+use crate::{
+    instance::InstanceBuilder,
+    types::{Ref, VariantType},
+    Symbol, SymbolSet,
+};
+
+/// A problem with an [`InstanceBuilder`] or one of its children, returned by
+/// [`InstanceBuilder::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuilderIssue {
+    /// The referent the builder with the problem will have once it's
+    /// inserted.
+    pub referent: Ref,
+
+    /// The names of the builder with the problem and its ancestors, starting
+    /// with the builder that was validated.
+    pub path: Vec<String>,
+
+    /// What is wrong with the builder.
+    pub problem: BuilderProblem,
+}
+
+/// What is wrong with a builder, as part of a [`BuilderIssue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuilderProblem {
+    /// The builder's class isn't in the reflection database. Its property
+    /// types aren't checked.
+    UnknownClass {
+        /// The name of the class.
+        class: Symbol,
+    },
+
+    /// The property holds a value of a different type than the reflection
+    /// database says it has.
+    WrongType {
+        /// The name of the property.
+        property: Symbol,
+        /// The type the database says the property has.
+        expected: VariantType,
+        /// The type of the property's value.
+        actual: VariantType,
+    },
+
+    /// The property was added more than once, so every value but the last
+    /// will be dropped when the builder is inserted.
+    DuplicateProperty {
+        /// The name of the property.
+        property: Symbol,
+    },
+}
+
+impl fmt::Display for BuilderProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuilderProblem::UnknownClass { class } => {
+                write!(f, "class {} is not in the reflection database", class)
+            }
+            BuilderProblem::WrongType {
+                property,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} is a {:?}, but should be a {:?}",
+                property, actual, expected
+            ),
+            BuilderProblem::DuplicateProperty { property } => {
+                write!(f, "{} was added more than once", property)
+            }
+        }
+    }
+}
+
+impl InstanceBuilder {
+    /// Checks the builder and its children against `database` before they're
+    /// inserted into a dom, returning every problem found in the order the
+    /// builders are visited. Catching these here points at the builder that
+    /// caused them, rather than at an instance that fails to serialize later.
+    ///
+    /// Properties that aren't in the database are allowed, since serializers
+    /// write them as-is. Values are allowed to have the type of the form of
+    /// the property written to files, like a `Color3uint8` for `Color`, and
+    /// strings can be either `String`s or `BinaryString`s.
+    ///
+    /// Requires the `reflection` feature.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::VariantType, BuilderProblem, InstanceBuilder};
+    ///
+    /// let database = rbx_reflection_database::get().unwrap();
+    /// let model = InstanceBuilder::new("Model").with_child(
+    ///     InstanceBuilder::new("Part")
+    ///         .with_name("Door")
+    ///         .with_property("Anchored", "yes"),
+    /// );
+    ///
+    /// let issues = model.validate(database);
+    /// assert_eq!(issues.len(), 1);
+    /// assert_eq!(issues[0].path, ["Model", "Door"]);
+    /// assert_eq!(
+    ///     issues[0].problem,
+    ///     BuilderProblem::WrongType {
+    ///         property: "Anchored".into(),
+    ///         expected: VariantType::Bool,
+    ///         actual: VariantType::String,
+    ///     }
+    /// );
+    /// ```
+    pub fn validate(&self, database: &ReflectionDatabase) -> Vec<BuilderIssue> {
+        let mut issues = Vec::new();
+        let mut path = Vec::new();
+        validate_builder(database, self, &mut path, &mut issues);
+
+        issues
+    }
+}
+
+fn validate_builder(
+    database: &ReflectionDatabase,
+    builder: &InstanceBuilder,
+    path: &mut Vec<String>,
+    issues: &mut Vec<BuilderIssue>,
+) {
+    path.push(builder.name.clone());
+
+    let mut push = |problem| {
+        issues.push(BuilderIssue {
+            referent: builder.referent,
+            path: path.clone(),
+            problem,
+        })
+    };
+
+    let known_class = database.classes.contains_key(builder.class.as_str());
+    if !known_class {
+        push(BuilderProblem::UnknownClass {
+            class: builder.class.clone(),
+        });
+    }
+
+    let mut seen = SymbolSet::default();
+    let mut duplicates = SymbolSet::default();
+
+    for (property, value) in &builder.properties {
+        if !seen.insert(property.clone()) {
+            if duplicates.insert(property.clone()) {
+                push(BuilderProblem::DuplicateProperty {
+                    property: property.clone(),
+                });
+            }
+            continue;
+        }
+
+        if !known_class {
+            continue;
+        }
+
+        let descriptors = match database.find_property_descriptors(&builder.class, property) {
+            Some(descriptors) => descriptors,
+            None => continue,
+        };
+
+        let actual = value.ty();
+        let accepted = |data_type: &DataType| match (data_type, actual) {
+            (DataType::Value(ty), actual) if *ty == actual => true,
+            (DataType::Value(VariantType::String), VariantType::BinaryString) => true,
+            (DataType::Enum(_), VariantType::Enum | VariantType::EnumItem) => true,
+            _ => false,
+        };
+
+        let canonical = &descriptors.canonical.data_type;
+        let serialized = descriptors
+            .serialized
+            .map(|descriptor| &descriptor.data_type);
+        if accepted(canonical) || serialized.is_some_and(accepted) {
+            continue;
+        }
+
+        let expected = match canonical {
+            DataType::Value(ty) => *ty,
+            DataType::Enum(_) => VariantType::Enum,
+            // Types the database doesn't describe can't be checked.
+            _ => continue,
+        };
+
+        push(BuilderProblem::WrongType {
+            property: property.clone(),
+            expected,
+            actual,
+        });
+    }
+
+    for child in &builder.children {
+        validate_builder(database, child, path, issues);
+    }
+
+    path.pop();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::types::Color3uint8;
+
+    #[test]
+    fn validate() {
+        let database = rbx_reflection_database::get().unwrap();
+
+        let mut part = InstanceBuilder::new("Part")
+            .with_property("Color", Color3uint8::new(255, 0, 0))
+            .with_property("Anchored", true);
+        part.add_property("Anchored", false);
+        part.add_property("Anchored", true);
+        let part_ref = part.referent();
+
+        let folder = InstanceBuilder::new("NotARealClass")
+            .with_name("Mystery")
+            .with_property("Anything", 1.0f32);
+        let folder_ref = folder.referent();
+
+        let model = InstanceBuilder::new("Model")
+            .with_property("Custom", true)
+            .with_property("Name", 5i32)
+            .with_children([part, folder]);
+        let model_ref = model.referent();
+
+        let issues = model.validate(database);
+        assert_eq!(
+            issues,
+            [
+                BuilderIssue {
+                    referent: model_ref,
+                    path: vec!["Model".to_owned()],
+                    problem: BuilderProblem::WrongType {
+                        property: "Name".into(),
+                        expected: VariantType::String,
+                        actual: VariantType::Int32,
+                    },
+                },
+                BuilderIssue {
+                    referent: part_ref,
+                    path: vec!["Model".to_owned(), "Part".to_owned()],
+                    problem: BuilderProblem::DuplicateProperty {
+                        property: "Anchored".into(),
+                    },
+                },
+                BuilderIssue {
+                    referent: folder_ref,
+                    path: vec!["Model".to_owned(), "Mystery".to_owned()],
+                    problem: BuilderProblem::UnknownClass {
+                        class: "NotARealClass".into(),
+                    },
+                },
+            ]
+        );
+
+        assert!(InstanceBuilder::new("Folder").validate(database).is_empty());
+    }
+}
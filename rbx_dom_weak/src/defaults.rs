@@ -0,0 +1,124 @@
+use rbx_reflection::ReflectionDatabase;
+
+use crate::{
+    types::{Ref, Variant},
+    Symbol, WeakDom,
+};
+
+impl WeakDom {
+    /// Sets the property `key` of the instance with the given referent to
+    /// `value` like [`WeakDom::set_property`], except that the property is
+    /// removed instead if `value` is its default value according to
+    /// `database`. Returns the property's previous value.
+    ///
+    /// Serializers write missing properties with their default values, so
+    /// leaving out properties that are set to their defaults makes files
+    /// smaller without changing what they contain. Aliases like `size` are
+    /// stored under the canonical name of the property, and properties that
+    /// aren't in the database are always set.
+    ///
+    /// Requires the `reflection` feature.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+    ///
+    /// let database = rbx_reflection_database::get().unwrap();
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("Part").with_property("Anchored", true));
+    /// let part = dom.root_ref();
+    ///
+    /// dom.set_property_unless_default(part, "Anchored", false, database);
+    /// assert_eq!(dom.root().properties.get("Anchored"), None);
+    ///
+    /// dom.set_property_unless_default(part, "Transparency", 0.5f32, database);
+    /// assert_eq!(
+    ///     dom.root().properties.get("Transparency"),
+    ///     Some(&Variant::Float32(0.5))
+    /// );
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn set_property_unless_default<K, V>(
+        &mut self,
+        referent: Ref,
+        key: K,
+        value: V,
+        database: &ReflectionDatabase,
+    ) -> Option<Variant>
+    where
+        K: Into<Symbol>,
+        V: Into<Variant>,
+    {
+        let class = self
+            .get_by_ref(referent)
+            .unwrap_or_else(|| panic!("cannot set a property of an instance that does not exist"))
+            .class
+            .clone();
+
+        let mut key = key.into();
+        let value = value.into();
+
+        let default = database
+            .find_property_descriptors(&class, &key)
+            .and_then(|descriptors| {
+                key = Symbol::from(descriptors.canonical.name.as_ref());
+
+                let class = database.classes.get(class.as_str())?;
+                database.find_default_property(class, &key)
+            });
+
+        if default == Some(&value) {
+            self.replace_property(referent, key, None)
+        } else {
+            self.replace_property(referent, key, Some(value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn set_property_unless_default() {
+        let database = rbx_reflection_database::get().unwrap();
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Part")
+                .with_property("Anchored", true)
+                .with_property("Made Up", 1.0f32),
+        );
+        let part = dom.root_ref();
+
+        let get = |dom: &WeakDom, name: &str| dom.root().properties.get(name).cloned();
+        let set = |dom: &mut WeakDom, name: &str, value: Variant| {
+            dom.set_property_unless_default(part, name, value, database)
+        };
+
+        assert_eq!(
+            set(&mut dom, "Anchored", Variant::Bool(false)),
+            Some(Variant::Bool(true))
+        );
+        assert_eq!(get(&dom, "Anchored"), None);
+
+        // A default of the wrong type isn't the default.
+        set(&mut dom, "Anchored", Variant::Int32(0));
+        assert_eq!(get(&dom, "Anchored"), Some(Variant::Int32(0)));
+
+        set(&mut dom, "Transparency", Variant::Float32(0.5));
+        assert_eq!(get(&dom, "Transparency"), Some(Variant::Float32(0.5)));
+        set(&mut dom, "Transparency", Variant::Float32(0.0));
+        assert_eq!(get(&dom, "Transparency"), None);
+
+        set(&mut dom, "Made Up", Variant::Float32(0.0));
+        assert_eq!(get(&dom, "Made Up"), Some(Variant::Float32(0.0)));
+
+        // Defaults are found on superclasses, and aliases are stored under
+        // their canonical names.
+        assert_eq!(set(&mut dom, "Archivable", Variant::Bool(true)), None);
+        assert_eq!(get(&dom, "Archivable"), None);
+        set(&mut dom, "archivable", Variant::Bool(false));
+        assert_eq!(get(&dom, "Archivable"), Some(Variant::Bool(false)));
+    }
+}
@@ -1,10 +1,20 @@
-use std::collections::VecDeque;
+use std::{cmp::Ordering, collections::VecDeque, fmt};
 
 use ahash::{AHashMap, AHashSet};
 use rbx_types::{Ref, UniqueId, Variant};
-use ustr::ustr;
 
-use crate::instance::{Instance, InstanceBuilder};
+use crate::{
+    builder::resolve_path,
+    index::InstanceIndex,
+    instance::{Instance, InstanceBuilder},
+    interner::{Interner, InternerStats},
+    journal::{InstanceSnapshot, Journal, JournalEntry},
+    metadata::Metadata,
+    slot::{self, Slot},
+    symbol,
+    transaction::History,
+    Symbol,
+};
 
 /// Represents a DOM containing one or more Roblox instances.
 ///
@@ -15,12 +25,29 @@ use crate::instance::{Instance, InstanceBuilder};
 /// objects and insert them into the tree.
 #[derive(Debug)]
 pub struct WeakDom {
-    instances: AHashMap<Ref, Instance>,
+    /// Instances are only kept behind an `Arc` with the `snapshots` feature.
+    /// See the `slot` module.
+    pub(crate) instances: AHashMap<Ref, Slot>,
     root_ref: Ref,
-    unique_ids: AHashSet<UniqueId>,
+    pub(crate) unique_ids: AHashSet<UniqueId>,
     /// Map from instance referent to the number of bytes dedicated to that instance
     /// This is populated during binary deserialization
     pub instance_byte_sizes: Option<ahash::HashMap<i32, usize>>,
+    /// Map from instance referent to a hash of that instance's class, name,
+    /// and properties, which is populated during binary deserialization if
+    /// the deserializer was asked to compute content hashes
+    pub instance_content_hashes: Option<ahash::HashMap<i32, u64>>,
+    /// Map from instance referent in the binary file to the instance's `Ref`,
+    /// which is populated during binary deserialization and used by
+    /// [`WeakDom::get_by_binary_referent`]
+    pub instance_binary_referents: Option<ahash::HashMap<i32, Ref>>,
+    metadata: Metadata,
+    pub(crate) journal: Option<Journal>,
+    pub(crate) history: History,
+    pub(crate) index: InstanceIndex,
+    /// The class and property names of the dom's instances. See the
+    /// `interner` module.
+    pub(crate) interner: Interner,
 }
 
 impl WeakDom {
@@ -31,6 +58,13 @@ impl WeakDom {
             root_ref: builder.referent,
             unique_ids: AHashSet::new(),
             instance_byte_sizes: None,
+            instance_content_hashes: None,
+            instance_binary_referents: None,
+            metadata: Metadata::new(),
+            journal: None,
+            history: History::default(),
+            index: InstanceIndex::default(),
+            interner: Interner::default(),
         };
 
         dom.insert(Ref::none(), builder);
@@ -51,23 +85,21 @@ impl WeakDom {
     /// If an Instance has a `UniqueId` property that is either duplicated in
     /// `instances` or not a `Variant::UniqueId`, this function will panic.
     #[must_use]
-    pub fn from_raw(root_ref: Ref, instances: AHashMap<Ref, Instance>) -> WeakDom {
+    pub fn from_raw(root_ref: Ref, mut instances: AHashMap<Ref, Instance>) -> WeakDom {
         assert!(
             instances.contains_key(&root_ref),
             "the provided `instances` map does not contain the `root_ref`"
         );
         let mut unique_ids = AHashSet::with_capacity(instances.len());
         for inst in instances.values() {
-            match inst.properties.get(&ustr("UniqueId")) {
-                Some(Variant::UniqueId(id)) => {
-                    if !unique_ids.insert(*id) {
-                        panic!(
-                            "UniqueId {} is duplicated in the provided `instances` map",
-                            id
-                        );
-                    }
+            match inst.properties.get("UniqueId") {
+                Some(Variant::UniqueId(id)) if !unique_ids.insert(*id) => {
+                    panic!(
+                        "UniqueId {} is duplicated in the provided `instances` map",
+                        id
+                    );
                 }
-                None => {}
+                Some(Variant::UniqueId(_)) | None => {}
                 Some(val) => panic!(
                     "expected property UniqueId to be a UniqueId but it was instead a {:?}",
                     val.ty()
@@ -75,11 +107,52 @@ impl WeakDom {
             }
         }
 
+        let mut interner = Interner::default();
+        for instance in instances.values_mut() {
+            interner.intern_instance(instance);
+        }
+
+        WeakDom {
+            instances: instances
+                .into_iter()
+                .map(|(referent, instance)| (referent, slot::new(instance)))
+                .collect(),
+            root_ref,
+            unique_ids,
+            instance_byte_sizes: None,
+            instance_content_hashes: None,
+            instance_binary_referents: None,
+            metadata: Metadata::new(),
+            journal: None,
+            history: History::default(),
+            index: InstanceIndex::default(),
+            interner,
+        }
+    }
+
+    /// Creates a dom from instances that may be shared with other doms, like
+    /// a [`FrozenDom`][crate::FrozenDom]. `instances` must already form a
+    /// valid tree under `root_ref`, and `unique_ids` must hold their UniqueIds.
+    #[cfg(feature = "snapshots")]
+    pub(crate) fn from_shared(
+        root_ref: Ref,
+        instances: AHashMap<Ref, Slot>,
+        unique_ids: AHashSet<UniqueId>,
+        metadata: Metadata,
+        interner: Interner,
+    ) -> WeakDom {
         WeakDom {
             instances,
             root_ref,
             unique_ids,
             instance_byte_sizes: None,
+            instance_content_hashes: None,
+            instance_binary_referents: None,
+            metadata,
+            journal: None,
+            history: History::default(),
+            index: InstanceIndex::default(),
+            interner,
         }
     }
 
@@ -89,11 +162,43 @@ impl WeakDom {
         self.instances.reserve(additional);
     }
 
+    /// Returns how many distinct class and property names the dom has
+    /// interned, and how much memory they use.
+    pub fn interner_stats(&self) -> InternerStats {
+        self.interner.stats()
+    }
+
+    /// Forgets the class and property names that the dom interned but that
+    /// nothing uses anymore, like the names of instances that have since been
+    /// destroyed, and returns how many were forgotten. Names are otherwise
+    /// kept until the dom is dropped.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+    /// let part = dom.insert(dom.root_ref(), InstanceBuilder::new("Part"));
+    /// assert_eq!(dom.interner_stats().entries, 2);
+    ///
+    /// dom.destroy(part);
+    /// assert_eq!(dom.shrink_interner(), 1);
+    /// assert_eq!(dom.interner_stats().entries, 1);
+    /// ```
+    pub fn shrink_interner(&mut self) -> usize {
+        self.interner.shrink()
+    }
+
     /// Consumes the WeakDom, returning its underlying root ref and backing
     /// storage. This method is useful when tree-preserving operations are too
     /// slow.
     pub fn into_raw(self) -> (Ref, AHashMap<Ref, Instance>) {
-        (self.root_ref, self.instances)
+        let instances = self
+            .instances
+            .into_iter()
+            .map(|(referent, instance)| (referent, slot::into_inner(instance)))
+            .collect();
+
+        (self.root_ref, instances)
     }
 
     /// Returns the referent of the root instance of the `WeakDom`.
@@ -103,24 +208,66 @@ impl WeakDom {
 
     /// Returns a reference to the root instance of the `WeakDom`.
     pub fn root(&self) -> &Instance {
-        self.instances.get(&self.root_ref).unwrap()
+        self.get_by_ref(self.root_ref).unwrap()
     }
 
     /// Returns a _mutable_ reference to the root instance of the `WeakDom`.
     pub fn root_mut(&mut self) -> &mut Instance {
-        self.instances.get_mut(&self.root_ref).unwrap()
+        self.instances
+            .get_mut(&self.root_ref)
+            .map(slot::make_mut)
+            .unwrap()
     }
 
     /// Returns a reference to an instance by referent, or `None` if it is not
     /// found.
     pub fn get_by_ref(&self, referent: Ref) -> Option<&Instance> {
-        self.instances.get(&referent)
+        self.instances.get(&referent).map(slot::get)
     }
 
     /// Returns a _mutable_ reference to an instance by referent, or `None` if
     /// it is not found.
+    ///
+    /// Changes made through this reference can't be seen by the dom, so they
+    /// aren't recorded in its [`Journal`] or reflected in its indices. Prefer
+    /// [`WeakDom::set_name`], [`WeakDom::set_class`], and
+    /// [`WeakDom::set_property`] when either of those are in use.
     pub fn get_by_ref_mut(&mut self, referent: Ref) -> Option<&mut Instance> {
-        self.instances.get_mut(&referent)
+        self.instances.get_mut(&referent).map(slot::make_mut)
+    }
+
+    /// Returns _mutable_ references to several instances at once, which makes
+    /// it possible to modify two instances based on each other without
+    /// cloning either of them. Each reference is `None` if its instance is not
+    /// found.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+    /// let a = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder").with_name("A"));
+    /// let b = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder").with_name("B"));
+    ///
+    /// if let [Some(a), Some(b)] = dom.get_many_mut([a, b]) {
+    ///     std::mem::swap(&mut a.name, &mut b.name);
+    /// }
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if the same referent is passed more than once.
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        referents: [Ref; N],
+    ) -> [Option<&mut Instance>; N] {
+        for (i, referent) in referents.iter().enumerate() {
+            if referents[..i].contains(referent) {
+                panic!("cannot borrow instance {} mutably more than once", referent);
+            }
+        }
+
+        self.instances
+            .get_disjoint_mut(referents.each_ref())
+            .map(|instance| instance.map(slot::make_mut))
     }
 
     /// Returns the byte size of an instance if byte tracking is available.
@@ -141,23 +288,148 @@ impl WeakDom {
         }
     }
 
+    /// Returns the content hash of an instance if content hashes were
+    /// computed when the dom was read, or `None` otherwise. See
+    /// [`WeakDom::instance_content_hashes`].
+    ///
+    /// Two instances with the same hash almost certainly have the same class,
+    /// name, and properties, so comparing the hashes of instances in two
+    /// versions of a file finds the ones that changed without comparing
+    /// their properties. Children aren't part of the hash.
+    pub fn get_instance_content_hash(&self, referent: Ref) -> Option<u64> {
+        let binary_referent = self.get_by_ref(referent)?.binary_referent()?;
+        self.instance_content_hashes
+            .as_ref()?
+            .get(&binary_referent)
+            .copied()
+    }
+
+    /// Returns the referent the instance had in the binary file it was read
+    /// from, or `None` if the instance doesn't exist or wasn't read from a
+    /// binary file. See [`Instance::binary_referent`].
+    pub fn binary_referent_of(&self, referent: Ref) -> Option<i32> {
+        self.get_by_ref(referent)?.binary_referent()
+    }
+
+    /// Returns the instance that had `binary_referent` in the binary file it
+    /// was read from, if there is one. This is useful for finding instances
+    /// from referents logged by Roblox or other tools.
+    ///
+    /// Doms read by rbx_binary look instances up in
+    /// [`WeakDom::instance_binary_referents`], which takes constant time.
+    /// Every instance in the dom is checked instead if the dom doesn't have
+    /// that map, or if the map doesn't lead to a matching instance, like for
+    /// instances that were added after the dom was read. If several instances
+    /// have the same binary referent, like after instances read from
+    /// different files are put in the same dom, any one of them may be
+    /// returned.
+    pub fn get_by_binary_referent(&self, binary_referent: i32) -> Option<&Instance> {
+        let indexed = self
+            .instance_binary_referents
+            .as_ref()
+            .and_then(|referents| referents.get(&binary_referent))
+            .and_then(|referent| self.get_by_ref(*referent))
+            .filter(|instance| instance.binary_referent == Some(binary_referent));
+
+        indexed.or_else(|| {
+            self.instances
+                .values()
+                .find(|instance| instance.binary_referent == Some(binary_referent))
+                .map(slot::get)
+        })
+    }
+
     /// Returns the [`UniqueId`] for the Instance with the provided referent, if it
     /// exists.
     pub fn get_unique_id(&self, referent: Ref) -> Option<UniqueId> {
         let inst = self.instances.get(&referent)?;
-        match inst.properties.get(&ustr("UniqueId")) {
+        match inst.properties.get("UniqueId") {
             Some(Variant::UniqueId(id)) => Some(*id),
             _ => None,
         }
     }
 
+    /// Gives every instance in the subtree rooted at `referent` that has a
+    /// non-nil `UniqueId` property a newly generated one, returning the number
+    /// of instances that changed. Copies of instances, like ones made with
+    /// [`WeakDom::clone_into_external`], keep their `UniqueId`s, so this is
+    /// useful for making a copy that Roblox treats as distinct from the
+    /// original.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::UniqueId, InstanceBuilder, WeakDom};
+    ///
+    /// let unique_id = UniqueId::generate();
+    /// let source = WeakDom::new(InstanceBuilder::new("Part").with_property("UniqueId", unique_id));
+    /// let mut dest = WeakDom::new(InstanceBuilder::new("Folder"));
+    ///
+    /// let copy_ref = source.clone_into_external(source.root_ref(), &mut dest);
+    /// assert_eq!(dest.get_unique_id(copy_ref), Some(unique_id));
+    ///
+    /// assert_eq!(dest.regenerate_unique_ids(copy_ref), 1);
+    /// assert_ne!(dest.get_unique_id(copy_ref), Some(unique_id));
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn regenerate_unique_ids(&mut self, referent: Ref) -> usize {
+        if !self.instances.contains_key(&referent) {
+            panic!("cannot regenerate the UniqueIds of an instance that does not exist");
+        }
+
+        let referents: Vec<Ref> = self
+            .descendants_of(referent)
+            .map(Instance::referent)
+            .collect();
+        let key = symbol("UniqueId");
+        let mut changed = 0;
+
+        for referent in referents {
+            let instance = self
+                .instances
+                .get_mut(&referent)
+                .map(slot::make_mut)
+                .unwrap();
+
+            if let Some(Variant::UniqueId(unique_id)) = instance.properties.get_mut(&key) {
+                if unique_id.is_nil() {
+                    continue;
+                }
+
+                let new_unique_id = UniqueId::generate();
+                self.unique_ids.remove(unique_id);
+                self.unique_ids.insert(new_unique_id);
+                *unique_id = new_unique_id;
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+
+    /// Returns the metadata of the file the `WeakDom` was read from, or that
+    /// will be written along with it.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns a _mutable_ reference to the metadata of the `WeakDom`.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Replaces the metadata of the `WeakDom`.
+    pub fn set_metadata(&mut self, metadata: Metadata) {
+        self.metadata = metadata;
+    }
+
     /// Returns an iterator that goes through every descendant Instance of the
     /// root referent.
     ///
     /// The descendants are guaranteed to be top-down such that children come
     /// after their parents.
     #[inline]
-    pub fn descendants(&self) -> WeakDomDescendants {
+    pub fn descendants(&self) -> WeakDomDescendants<'_> {
         self.descendants_of(self.root_ref)
     }
 
@@ -168,7 +440,7 @@ impl WeakDom {
     ///
     /// Panics if `referent` is not a member of this DOM.
     #[inline]
-    pub fn descendants_of(&self, referent: Ref) -> WeakDomDescendants {
+    pub fn descendants_of(&self, referent: Ref) -> WeakDomDescendants<'_> {
         if !self.instances.contains_key(&referent) {
             panic!("the referent provided to `descendants_of` must be a part of the DOM")
         }
@@ -181,6 +453,10 @@ impl WeakDom {
     /// Returns an iterator that goes through the ancestors of a particular
     /// [`Ref`]. The passed `Ref` *must* be a part of this `WeakDom`.
     ///
+    /// The first item is the instance itself, followed by its parent, and so
+    /// on up to the topmost ancestor. Use `.skip(1)` to only go through its
+    /// parents.
+    ///
     /// ## Panics
     ///
     /// Panics if `referent` is not a member of this DOM.
@@ -195,9 +471,96 @@ impl WeakDom {
         })
     }
 
+    /// Returns whether `referent` is a descendant of `ancestor`, like
+    /// `Instance:IsDescendantOf()`. An instance isn't a descendant of itself.
+    ///
+    /// Returns `false` if either `Ref` isn't a part of this `WeakDom`.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let part = InstanceBuilder::new("Part");
+    /// let part_ref = part.referent();
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Model").with_child(InstanceBuilder::new("Folder").with_child(part)),
+    /// );
+    ///
+    /// assert!(dom.is_descendant_of(part_ref, dom.root_ref()));
+    /// assert!(!dom.is_descendant_of(dom.root_ref(), part_ref));
+    /// assert!(!dom.is_descendant_of(part_ref, part_ref));
+    /// ```
+    pub fn is_descendant_of(&self, referent: Ref, ancestor: Ref) -> bool {
+        let mut current = match self.get_by_ref(referent) {
+            Some(instance) => instance.parent(),
+            None => return false,
+        };
+
+        while let Some(instance) = self.get_by_ref(current) {
+            if current == ancestor {
+                return true;
+            }
+            current = instance.parent();
+        }
+
+        false
+    }
+
+    /// Returns the full name of an instance, made up of the names of it and
+    /// its ancestors, like `game.Workspace.Model.Part`. If the topmost
+    /// ancestor is a `DataModel`, it is written as `game`.
+    ///
+    /// Unlike `Instance:GetFullName()` in Roblox, names that aren't valid Luau
+    /// identifiers are quoted and escaped, like `game.Workspace["My Model"]`,
+    /// so that the full name is never ambiguous.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `referent` is not a member of this DOM.
+    pub fn full_name_of(&self, referent: Ref) -> String {
+        let mut ancestors: Vec<&Instance> = self.ancestors_of(referent).collect();
+        ancestors.reverse();
+
+        let mut full_name = String::new();
+
+        for (i, instance) in ancestors.iter().enumerate() {
+            let name = instance.name.as_str();
+
+            if i == 0 && instance.class == "DataModel" {
+                full_name.push_str("game");
+            } else if is_identifier(name) {
+                if i > 0 {
+                    full_name.push('.');
+                }
+                full_name.push_str(name);
+            } else {
+                full_name.push_str("[\"");
+                for c in name.chars() {
+                    match c {
+                        '"' => full_name.push_str("\\\""),
+                        '\\' => full_name.push_str("\\\\"),
+                        '\n' => full_name.push_str("\\n"),
+                        '\r' => full_name.push_str("\\r"),
+                        '\t' => full_name.push_str("\\t"),
+                        c if c.is_control() => {
+                            full_name.push_str(&format!("\\u{{{:x}}}", c as u32))
+                        }
+                        c => full_name.push(c),
+                    }
+                }
+                full_name.push_str("\"]");
+            }
+        }
+
+        full_name
+    }
+
     /// Insert a new instance into the DOM with the given parent. The parent is allowed to
     /// be the none Ref.
     ///
+    /// Any `Ref` properties that were added to the builders with a path, using
+    /// [`InstanceBuilder::with_ref_property`], are resolved once every builder
+    /// has been inserted.
+    ///
     /// ## Panics
     /// Panics if `parent_ref` is some and does not refer to an instance in the DOM.
     pub fn insert(&mut self, parent_ref: Ref, root_builder: InstanceBuilder) -> Ref {
@@ -206,23 +569,37 @@ impl WeakDom {
             builder: InstanceBuilder,
             parent: Ref,
             queue: Option<&mut VecDeque<(Ref, InstanceBuilder)>>,
+            links: &mut Vec<(Ref, Symbol, String)>,
         ) {
+            for (property, path) in builder.ref_links {
+                links.push((builder.referent, property, path));
+            }
+
+            let class = dom.interner.intern(builder.class);
+            let properties = builder
+                .properties
+                .into_iter()
+                .map(|(key, value)| (dom.interner.intern(key), value))
+                .collect();
+
             dom.inner_insert(
                 builder.referent,
-                Instance {
+                slot::new(Instance {
                     referent: builder.referent,
                     children: Vec::with_capacity(builder.children.len()),
                     parent,
                     name: builder.name,
-                    class: builder.class,
-                    properties: builder.properties.into_iter().collect(),
+                    class,
+                    properties,
                     binary_referent: builder.binary_referent,
-                },
+                    byte_size: builder.byte_size,
+                }),
             );
 
             if parent.is_some() {
                 dom.instances
                     .get_mut(&parent)
+                    .map(slot::make_mut)
                     .unwrap_or_else(|| panic!("cannot insert into parent that does not exist"))
                     .children
                     .push(builder.referent);
@@ -236,12 +613,13 @@ impl WeakDom {
         }
 
         let root_referent = root_builder.referent;
+        let mut links = Vec::new();
 
         // Fast path: if the builder does not have any children, then we don't have to
         // construct a queue to keep track of descendants for insertion, avoiding a heap
         // allocation.
         if root_builder.children.is_empty() {
-            insert(self, root_builder, parent_ref, None);
+            insert(self, root_builder, parent_ref, None, &mut links);
         } else {
             // Rather than performing this movement recursively, we instead use a
             // queue that we load the children of each `InstanceBuilder` into.
@@ -250,13 +628,159 @@ impl WeakDom {
             queue.push_back((parent_ref, root_builder));
 
             while let Some((parent, builder)) = queue.pop_front() {
-                insert(self, builder, parent, Some(&mut queue));
+                insert(self, builder, parent, Some(&mut queue), &mut links);
             }
         }
 
+        for (referent, property, path) in links {
+            let target = resolve_path(self, referent, &path).unwrap_or_else(Ref::none);
+            let instance = self
+                .instances
+                .get_mut(&referent)
+                .map(slot::make_mut)
+                .unwrap();
+            let value = Variant::Ref(target);
+            let property = self.interner.intern(property);
+            let previous = instance.properties.insert(property.clone(), value.clone());
+            self.index
+                .change_property(referent, &property, previous.as_ref(), Some(&value));
+        }
+
+        if self.is_journaling() {
+            let index = self.child_index(parent_ref, root_referent);
+            let instance = InstanceSnapshot::new(self, root_referent);
+            self.record(JournalEntry::Insert {
+                parent: parent_ref,
+                index,
+                instance,
+            });
+        }
+
         root_referent
     }
 
+    /// Insert a new instance into the DOM with the given parent, like
+    /// [`WeakDom::insert`], but without ever replacing an existing instance.
+    ///
+    /// Builders keep their referents unless another instance in the DOM, or an
+    /// earlier builder in `root_builder`, already has the same one. Those
+    /// builders are given new referents instead, and Ref properties within
+    /// `root_builder` that pointed to them are rewritten to match. Returns the
+    /// referent of the root instance along with a map from every referent that
+    /// was replaced to its replacement, so that referents tracked outside of
+    /// the DOM can be updated.
+    ///
+    /// ## Panics
+    /// Panics if `parent_ref` is some and does not refer to an instance in the DOM.
+    pub fn insert_remapped(
+        &mut self,
+        parent_ref: Ref,
+        mut root_builder: InstanceBuilder,
+    ) -> (Ref, AHashMap<Ref, Ref>) {
+        let mut remap = AHashMap::new();
+        let mut seen = AHashSet::new();
+
+        let mut to_visit = vec![&mut root_builder];
+        while let Some(builder) = to_visit.pop() {
+            let referent = builder.referent;
+
+            if self.instances.contains_key(&referent) || !seen.insert(referent) {
+                let new_referent = Ref::new();
+                remap.entry(referent).or_insert(new_referent);
+                builder.referent = new_referent;
+            }
+
+            to_visit.extend(builder.children.iter_mut().rev());
+        }
+
+        if !remap.is_empty() {
+            let mut to_visit = vec![&mut root_builder];
+            while let Some(builder) = to_visit.pop() {
+                for (_, value) in &mut builder.properties {
+                    if let Variant::Ref(target) = value {
+                        if let Some(new_target) = remap.get(target) {
+                            *target = *new_target;
+                        }
+                    }
+                }
+
+                to_visit.extend(builder.children.iter_mut());
+            }
+        }
+
+        (self.insert(parent_ref, root_builder), remap)
+    }
+
+    /// Moves every instance in `other` into this DOM as a descendant of
+    /// `parent_ref`, with the root of `other` as a child of `parent_ref`.
+    ///
+    /// Referents are kept and replaced the same way as
+    /// [`WeakDom::insert_remapped`], which is described there. Returns the
+    /// referent of the root of `other` in this DOM, along with a map from
+    /// every referent that was replaced to its replacement.
+    ///
+    /// ## Panics
+    /// Panics if `parent_ref` is some and does not refer to an instance in the DOM.
+    pub fn merge(&mut self, parent_ref: Ref, other: WeakDom) -> (Ref, AHashMap<Ref, Ref>) {
+        let root_ref = other.root_ref;
+        self.insert_remapped(parent_ref, other.into_builder(root_ref, true))
+    }
+
+    /// Inserts `root_builder` and its descendants with `parent_ref` as their
+    /// parent, but without adding `root_builder` to the children of
+    /// `parent_ref`, which is left to the caller. Because the children of each
+    /// builder are known up front, none of the new instances need to be looked
+    /// up again after they've been inserted.
+    ///
+    /// The path links of each builder are added to `links` along with the
+    /// referent of their instance, rather than being resolved.
+    pub(crate) fn insert_detached(
+        &mut self,
+        parent_ref: Ref,
+        root_builder: InstanceBuilder,
+        links: &mut Vec<(Ref, Symbol, String)>,
+    ) {
+        let mut queue = VecDeque::new();
+        queue.push_back((parent_ref, root_builder));
+
+        while let Some((parent, builder)) = queue.pop_front() {
+            let referent = builder.referent;
+
+            for (property, path) in builder.ref_links {
+                links.push((referent, property, path));
+            }
+
+            let class = self.interner.intern(builder.class);
+            let properties = builder
+                .properties
+                .into_iter()
+                .map(|(key, value)| (self.interner.intern(key), value))
+                .collect();
+
+            self.inner_insert(
+                referent,
+                slot::new(Instance {
+                    referent,
+                    children: builder
+                        .children
+                        .iter()
+                        .map(|child| child.referent)
+                        .collect(),
+                    parent,
+                    name: builder.name,
+                    class,
+                    properties,
+                    binary_referent: builder.binary_referent,
+                    byte_size: builder.byte_size,
+                }),
+            );
+
+            for child in builder.children {
+                queue.push_back((referent, child));
+            }
+        }
+    }
+
     /// Destroy the instance with the given referent.
     ///
     /// ## Panics
@@ -275,17 +799,394 @@ impl WeakDom {
             .unwrap_or_else(|| panic!("cannot destroy an instance that does not exist"));
 
         let parent_ref = instance.parent;
+
+        if self.is_journaling() {
+            let index = self.child_index(parent_ref, referent);
+            let instance = InstanceSnapshot::new(self, referent);
+            self.record(JournalEntry::Destroy {
+                parent: parent_ref,
+                index,
+                instance,
+            });
+        }
+
+        if parent_ref.is_some() {
+            let parent = self
+                .instances
+                .get_mut(&parent_ref)
+                .map(slot::make_mut)
+                .unwrap();
+            parent.children.retain(|&child| child != referent);
+        }
+
+        let mut to_remove = VecDeque::new();
+        to_remove.push_back(referent);
+
+        while let Some(referent) = to_remove.pop_front() {
+            let instance = self.inner_remove(referent);
+            to_remove.extend(instance.children.iter().copied());
+        }
+    }
+
+    /// Removes the instance with the given referent and all of its
+    /// descendants, returning them as a new `WeakDom` with the instance at its
+    /// root. This works like [`WeakDom::destroy`], but keeps the removed
+    /// instances instead of dropping them, which makes cutting instances out
+    /// of one dom and pasting them into another cheap.
+    ///
+    /// Instances keep their referents. Ref properties that point outside of
+    /// the removed subtree are left as they are, so they will dangle in both
+    /// doms; see [`WeakDom::fix_dangling_refs`].
+    ///
+    /// The dom's [`Journal`] records this like [`WeakDom::destroy`], so
+    /// undoing it or rolling it back restores the subtree to this dom. The
+    /// returned dom keeps its own copy of the instances either way.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let model = InstanceBuilder::new("Model");
+    /// let model_ref = model.referent();
+    /// let mut source = WeakDom::new(InstanceBuilder::new("Folder").with_child(model));
+    /// let mut dest = WeakDom::new(InstanceBuilder::new("Folder"));
+    ///
+    /// let detached = source.detach(model_ref);
+    /// dest.insert(dest.root_ref(), detached.into_builder(model_ref, true));
+    ///
+    /// assert!(source.get_by_ref(model_ref).is_none());
+    /// assert_eq!(dest.root().children(), [model_ref]);
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    ///
+    /// Will also panic if `referent` refers to the root instance in this
+    /// `WeakDom`.
+    pub fn detach(&mut self, referent: Ref) -> WeakDom {
+        if referent == self.root_ref {
+            panic!("cannot detach the root instance of a WeakDom");
+        }
+
+        let instance = self
+            .instances
+            .get(&referent)
+            .unwrap_or_else(|| panic!("cannot detach an instance that does not exist"));
+
+        let parent_ref = instance.parent;
+
+        if self.is_journaling() {
+            let index = self.child_index(parent_ref, referent);
+            let instance = InstanceSnapshot::new(self, referent);
+            self.record(JournalEntry::Destroy {
+                parent: parent_ref,
+                index,
+                instance,
+            });
+        }
+
         if parent_ref.is_some() {
-            let parent = self.instances.get_mut(&parent_ref).unwrap();
+            let parent = self
+                .instances
+                .get_mut(&parent_ref)
+                .map(slot::make_mut)
+                .unwrap();
             parent.children.retain(|&child| child != referent);
         }
 
+        let mut instances = AHashMap::new();
+        let mut unique_ids = AHashSet::new();
         let mut to_remove = VecDeque::new();
         to_remove.push_back(referent);
 
         while let Some(referent) = to_remove.pop_front() {
             let instance = self.inner_remove(referent);
-            to_remove.extend(instance.children);
+            to_remove.extend(instance.children.iter().copied());
+
+            if let Some(Variant::UniqueId(unique_id)) = instance.properties.get("UniqueId") {
+                unique_ids.insert(*unique_id);
+            }
+
+            instances.insert(referent, instance);
+        }
+
+        slot::make_mut(instances.get_mut(&referent).unwrap()).parent = Ref::none();
+
+        WeakDom {
+            instances,
+            root_ref: referent,
+            unique_ids,
+            instance_byte_sizes: None,
+            instance_content_hashes: None,
+            instance_binary_referents: None,
+            metadata: Metadata::new(),
+            journal: None,
+            history: History::default(),
+            index: InstanceIndex::default(),
+            // The detached instances keep the names they were interned with
+            // here, so the new dom starts out with all of them.
+            interner: self.interner.clone(),
+        }
+    }
+
+    /// Removes every instance that does not satisfy `predicate`, along with all
+    /// of its descendants. Any `Ref` properties on the remaining instances that
+    /// pointed to a removed instance are set to `Ref::none()`.
+    ///
+    /// `predicate` is called on each descendant of the root, parents before
+    /// children. It is not called on the root itself, which is always kept, or
+    /// on the descendants of instances that have already been rejected.
+    ///
+    /// The rewritten properties and removed instances are recorded in the
+    /// dom's [`Journal`] while it's journaling, so a rolled back transaction
+    /// or [`WeakDom::undo`] restores both.
+    ///
+    /// To fail instead of rewriting `Ref` properties, use
+    /// [`WeakDom::try_retain`].
+    pub fn retain<F>(&mut self, predicate: F)
+    where
+        F: FnMut(&Instance) -> bool,
+    {
+        let (to_destroy, removed) = self.find_rejected(predicate);
+
+        for dangling in self.find_refs_into(&removed) {
+            self.replace_property(
+                dangling.referent,
+                dangling.property,
+                Some(Variant::Ref(Ref::none())),
+            );
+        }
+
+        for referent in to_destroy {
+            self.destroy(referent);
+        }
+    }
+
+    /// Like [`WeakDom::retain`], but leaves the `WeakDom` untouched and returns
+    /// an error if any of the remaining instances have a `Ref` property that
+    /// points to an instance that would be removed.
+    pub fn try_retain<F>(&mut self, predicate: F) -> Result<(), DanglingRefError>
+    where
+        F: FnMut(&Instance) -> bool,
+    {
+        let (to_destroy, removed) = self.find_rejected(predicate);
+
+        let dangling = self.find_refs_into(&removed);
+        if !dangling.is_empty() {
+            return Err(DanglingRefError { refs: dangling });
+        }
+
+        for referent in to_destroy {
+            self.destroy(referent);
+        }
+
+        Ok(())
+    }
+
+    /// Walks the tree from the root, returning the topmost instances rejected
+    /// by `predicate` and the set of every instance that removing them would
+    /// remove.
+    fn find_rejected<F>(&self, mut predicate: F) -> (Vec<Ref>, AHashSet<Ref>)
+    where
+        F: FnMut(&Instance) -> bool,
+    {
+        let mut to_destroy = Vec::new();
+        let mut removed = AHashSet::new();
+        let mut queue: VecDeque<Ref> = self.root().children.iter().copied().collect();
+
+        while let Some(referent) = queue.pop_front() {
+            let instance = &self.instances[&referent];
+
+            if predicate(instance) {
+                queue.extend(instance.children.iter().copied());
+            } else {
+                to_destroy.push(referent);
+                removed.extend(self.descendants_of(referent).map(Instance::referent));
+            }
+        }
+
+        (to_destroy, removed)
+    }
+
+    /// Finds every `Ref` property on an instance outside of `removed` that
+    /// points to an instance inside of it.
+    fn find_refs_into(&self, removed: &AHashSet<Ref>) -> Vec<DanglingRef> {
+        let mut dangling = Vec::new();
+
+        if removed.is_empty() {
+            return dangling;
+        }
+
+        for (referent, instance) in &self.instances {
+            if removed.contains(referent) {
+                continue;
+            }
+
+            for (property, value) in &instance.properties {
+                if let Variant::Ref(target) = value {
+                    if removed.contains(target) {
+                        dangling.push(DanglingRef {
+                            referent: *referent,
+                            property: property.clone(),
+                            target: *target,
+                        });
+                    }
+                }
+            }
+        }
+
+        dangling
+    }
+
+    /// Finds every `Ref` property that points to an instance that is not part
+    /// of the `WeakDom`, then handles them according to `policy`. Properties
+    /// set to `Ref::none()` are not considered dangling.
+    ///
+    /// Every dangling property that was found is returned, in no particular
+    /// order, regardless of the policy. Properties that are changed are
+    /// recorded in the dom's [`Journal`] while it's journaling.
+    pub fn fix_dangling_refs(&mut self, policy: DanglingRefPolicy) -> Vec<DanglingRef> {
+        let mut dangling = Vec::new();
+
+        for (referent, instance) in &self.instances {
+            for (property, value) in &instance.properties {
+                if let Variant::Ref(target) = value {
+                    if target.is_some() && !self.instances.contains_key(target) {
+                        dangling.push(DanglingRef {
+                            referent: *referent,
+                            property: property.clone(),
+                            target: *target,
+                        });
+                    }
+                }
+            }
+        }
+
+        let value = match policy {
+            DanglingRefPolicy::SetToNone => Some(Variant::Ref(Ref::none())),
+            DanglingRefPolicy::Remove => None,
+            DanglingRefPolicy::Report => return dangling,
+        };
+
+        for dangling in &dangling {
+            self.replace_property(dangling.referent, dangling.property.clone(), value.clone());
+        }
+
+        dangling
+    }
+
+    /// Checks that the `WeakDom` is internally consistent, returning every
+    /// problem that was found. This is useful after mutating instances by
+    /// hand (for example, through [`WeakDom::from_raw`]) to catch corruption
+    /// before the dom is serialized.
+    ///
+    /// The following are checked:
+    /// - The root referent is not null and is part of the dom.
+    /// - Every instance is stored under its own referent.
+    /// - Every child of an instance exists, is listed only once, and has that
+    ///   instance as its parent.
+    /// - Every instance with a parent is listed among that parent's children.
+    /// - Every `Ref` property points to an instance in the dom or is null.
+    /// - No two instances share a `UniqueId`.
+    ///
+    /// Instances without a parent, like the result of
+    /// [`WeakDom::clone_within`], are not considered an error.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut violations = Vec::new();
+
+        if self.root_ref.is_none() {
+            violations.push(Violation::NullRoot);
+        } else if let Some(root) = self.instances.get(&self.root_ref) {
+            if root.parent.is_some() {
+                violations.push(Violation::RootHasParent {
+                    parent: root.parent,
+                });
+            }
+        } else {
+            violations.push(Violation::MissingRoot {
+                root: self.root_ref,
+            });
+        }
+
+        let mut unique_ids = AHashMap::new();
+
+        for (referent, instance) in &self.instances {
+            if instance.referent != *referent {
+                violations.push(Violation::MismatchedReferent {
+                    key: *referent,
+                    referent: instance.referent,
+                });
+            }
+
+            let mut seen_children = AHashSet::new();
+            for child_ref in &instance.children {
+                if !seen_children.insert(*child_ref) {
+                    violations.push(Violation::DuplicateChild {
+                        parent: *referent,
+                        child: *child_ref,
+                    });
+                    continue;
+                }
+
+                match self.instances.get(child_ref) {
+                    Some(child) if child.parent != *referent => {
+                        violations.push(Violation::WrongParent {
+                            child: *child_ref,
+                            listed_under: *referent,
+                            parent: child.parent,
+                        });
+                    }
+                    Some(_) => {}
+                    None => violations.push(Violation::MissingChild {
+                        parent: *referent,
+                        child: *child_ref,
+                    }),
+                }
+            }
+
+            if instance.parent.is_some() {
+                match self.instances.get(&instance.parent) {
+                    Some(parent) if !parent.children.contains(referent) => {
+                        violations.push(Violation::NotAChild {
+                            child: *referent,
+                            parent: instance.parent,
+                        });
+                    }
+                    Some(_) => {}
+                    None => violations.push(Violation::MissingParent {
+                        child: *referent,
+                        parent: instance.parent,
+                    }),
+                }
+            }
+
+            for (property, value) in &instance.properties {
+                match value {
+                    Variant::Ref(target)
+                        if target.is_some() && !self.instances.contains_key(target) =>
+                    {
+                        violations.push(Violation::DanglingRef(DanglingRef {
+                            referent: *referent,
+                            property: property.clone(),
+                            target: *target,
+                        }));
+                    }
+                    Variant::UniqueId(id) if *property == "UniqueId" => {
+                        if let Some(other) = unique_ids.insert(*id, *referent) {
+                            violations.push(Violation::DuplicateUniqueId {
+                                id: *id,
+                                referents: [other, *referent],
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { violations })
         }
     }
 
@@ -313,7 +1214,11 @@ impl WeakDom {
         // important to ensure this link is the one severed first.
         let parent_ref = instance.parent;
         if parent_ref.is_some() {
-            let parent = self.instances.get_mut(&parent_ref).unwrap();
+            let parent = self
+                .instances
+                .get_mut(&parent_ref)
+                .map(slot::make_mut)
+                .unwrap();
             parent.children.retain(|&child| child != referent);
         }
 
@@ -325,7 +1230,8 @@ impl WeakDom {
 
         // Instance was released.
         // Bye-bye, instance!
-        instance.parent = dest_parent_ref;
+        slot::make_mut(&mut instance).parent = dest_parent_ref;
+        dest.interner.adopt(&instance);
         dest.inner_insert(referent, instance);
 
         // Transfer all of the descendants of the moving instance breadth-first.
@@ -333,14 +1239,19 @@ impl WeakDom {
             let instance = self.inner_remove(referent);
 
             to_move.extend(instance.children.iter().copied());
+            dest.interner.adopt(&instance);
             dest.inner_insert(referent, instance);
         }
 
         // Finally, notify the new parent instance that their adoption is
         // complete. Enjoy!
-        let dest_parent = dest.instances.get_mut(&dest_parent_ref).unwrap_or_else(|| {
-            panic!("cannot move an instance into an instance that does not exist")
-        });
+        let dest_parent = dest
+            .instances
+            .get_mut(&dest_parent_ref)
+            .map(slot::make_mut)
+            .unwrap_or_else(|| {
+                panic!("cannot move an instance into an instance that does not exist")
+            });
         dest_parent.children.push(referent);
     }
 
@@ -364,15 +1275,26 @@ impl WeakDom {
         let instance = self
             .instances
             .get_mut(&referent)
+            .map(slot::make_mut)
             .unwrap_or_else(|| panic!("cannot move an instance that does not exist"));
 
         // Tell the instance who its new parent is.
         let parent_ref = instance.parent;
         instance.parent = dest_parent_ref;
 
+        let previous_index = if self.is_journaling() {
+            self.child_index(parent_ref, referent)
+        } else {
+            0
+        };
+
         // Remove the instance's referent from its parent's list of children.
         if parent_ref.is_some() {
-            let parent = self.instances.get_mut(&parent_ref).unwrap();
+            let parent = self
+                .instances
+                .get_mut(&parent_ref)
+                .map(slot::make_mut)
+                .unwrap();
             parent.children.retain(|&child| child != referent);
         }
 
@@ -380,8 +1302,236 @@ impl WeakDom {
         let dest_parent = self
             .instances
             .get_mut(&dest_parent_ref)
+            .map(slot::make_mut)
             .unwrap_or_else(|| panic!("cannot move into an instance that does not exist"));
         dest_parent.children.push(referent);
+
+        if self.is_journaling() {
+            let index = self.child_index(dest_parent_ref, referent);
+            self.record(JournalEntry::Reparent {
+                referent,
+                previous_parent: parent_ref,
+                previous_index,
+                parent: dest_parent_ref,
+                index,
+            });
+        }
+    }
+
+    /// Sets the property `key` of the instance with the given referent to
+    /// `value`, returning the property's previous value. Unlike changing
+    /// [`Instance::properties`] directly, this is recorded in the dom's
+    /// [`Journal`] while it's journaling.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn set_property<K, V>(&mut self, referent: Ref, key: K, value: V) -> Option<Variant>
+    where
+        K: Into<Symbol>,
+        V: Into<Variant>,
+    {
+        self.replace_property(referent, key.into(), Some(value.into()))
+    }
+
+    /// Renames the instance with the given referent, returning its previous
+    /// name. Unlike changing [`Instance::name`] directly, this keeps the index
+    /// enabled by [`WeakDom::enable_name_index`] up to date, and is recorded
+    /// in the dom's [`Journal`] while it's journaling.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn set_name<S: Into<String>>(&mut self, referent: Ref, name: S) -> String {
+        let name = name.into();
+        let instance = self
+            .instances
+            .get_mut(&referent)
+            .map(slot::make_mut)
+            .unwrap_or_else(|| panic!("cannot rename an instance that does not exist"));
+
+        self.index.change_name(referent, &instance.name, &name);
+        let previous = std::mem::replace(&mut instance.name, name);
+        instance.sync_identity_properties();
+
+        if self.is_journaling() {
+            let name = self.instances[&referent].name.clone();
+            self.record(JournalEntry::SetName {
+                referent,
+                previous: previous.clone(),
+                name,
+            });
+        }
+
+        previous
+    }
+
+    /// Changes the class of the instance with the given referent, returning its
+    /// previous class. Unlike changing [`Instance::class`] directly, this keeps
+    /// the index enabled by [`WeakDom::enable_class_index`] up to date, and is
+    /// recorded in the dom's [`Journal`] while it's journaling.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn set_class<S: Into<Symbol>>(&mut self, referent: Ref, class: S) -> Symbol {
+        let class = self.interner.intern(class.into());
+        let instance = self
+            .instances
+            .get_mut(&referent)
+            .map(slot::make_mut)
+            .unwrap_or_else(|| panic!("cannot set the class of an instance that does not exist"));
+
+        let previous = std::mem::replace(&mut instance.class, class.clone());
+        instance.sync_identity_properties();
+        self.index.change_class(referent, &previous, &class);
+        self.record(JournalEntry::SetClass {
+            referent,
+            previous: previous.clone(),
+            class,
+        });
+        previous
+    }
+
+    /// Removes the property `key` from the instance with the given referent,
+    /// returning its value. Like [`WeakDom::set_property`], this is recorded
+    /// in the dom's [`Journal`] while it's journaling.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn remove_property<K: Into<Symbol>>(&mut self, referent: Ref, key: K) -> Option<Variant> {
+        self.replace_property(referent, key.into(), None)
+    }
+
+    pub(crate) fn replace_property(
+        &mut self,
+        referent: Ref,
+        key: Symbol,
+        value: Option<Variant>,
+    ) -> Option<Variant> {
+        let instance = self
+            .instances
+            .get_mut(&referent)
+            .map(slot::make_mut)
+            .unwrap_or_else(|| panic!("cannot set a property of an instance that does not exist"));
+
+        let previous = match &value {
+            Some(value) => {
+                let key = self.interner.intern(key.clone());
+                instance.properties.insert(key, value.clone())
+            }
+            None => instance.properties.remove(&key),
+        };
+        self.index
+            .change_property(referent, &key, previous.as_ref(), value.as_ref());
+
+        if self.is_journaling() {
+            self.record(JournalEntry::SetProperty {
+                referent,
+                name: key,
+                previous: previous.clone(),
+                value,
+            });
+        }
+
+        previous
+    }
+
+    /// Reorders the children of the instance with the given referent using
+    /// `compare`. The sort is stable, so children that compare as equal keep
+    /// their current order.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+    ///     InstanceBuilder::new("Part").with_name("B"),
+    ///     InstanceBuilder::new("Part").with_name("A"),
+    /// ]));
+    ///
+    /// dom.sort_children(dom.root_ref(), |a, b| a.name.cmp(&b.name));
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in `self`.
+    pub fn sort_children<F>(&mut self, referent: Ref, mut compare: F)
+    where
+        F: FnMut(&Instance, &Instance) -> Ordering,
+    {
+        let instance = self
+            .instances
+            .get_mut(&referent)
+            .map(slot::make_mut)
+            .unwrap_or_else(|| {
+                panic!("cannot sort the children of an instance that does not exist")
+            });
+
+        // The children are taken out of the instance while sorting, since
+        // comparing them needs to borrow the rest of the dom.
+        let mut children = std::mem::take(&mut instance.children);
+        children.sort_by(|a, b| compare(&self.instances[a], &self.instances[b]));
+
+        self.instances
+            .get_mut(&referent)
+            .map(slot::make_mut)
+            .unwrap()
+            .children = children;
+    }
+
+    /// Converts every `BinaryString` property for which `predicate` returns
+    /// true into a `SharedString`, returning the number of properties that
+    /// were converted. `predicate` is given the instance, the name of the
+    /// property, and the property's contents.
+    ///
+    /// `SharedString` values with the same contents share one buffer, and
+    /// serializers write each distinct buffer once. Promoting large values
+    /// that are repeated across many instances, like mesh data, can make both
+    /// the dom and the files written from it much smaller.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::BinaryString, InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(
+    ///     InstanceBuilder::new("Folder")
+    ///         .with_property("Data", BinaryString::from(vec![0; 4096])),
+    /// );
+    ///
+    /// let promoted = dom.promote_shared_strings(|_, _, data| data.len() >= 1024);
+    /// assert_eq!(promoted, 1);
+    /// ```
+    pub fn promote_shared_strings<F>(&mut self, mut predicate: F) -> usize
+    where
+        F: FnMut(&Instance, &Symbol, &[u8]) -> bool,
+    {
+        let mut promoted = 0;
+
+        for instance in self.instances.values_mut() {
+            let names: Vec<Symbol> = instance
+                .properties
+                .iter()
+                .filter_map(|(name, value)| match value {
+                    Variant::BinaryString(value) if predicate(instance, name, value.as_ref()) => {
+                        Some(name.clone())
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            // Instances shared with a frozen dom are only copied if they
+            // actually have something to promote.
+            if names.is_empty() {
+                continue;
+            }
+
+            let instance = slot::make_mut(instance);
+            for name in names {
+                if let Some(Variant::BinaryString(value)) = instance.properties.remove(&name) {
+                    instance
+                        .properties
+                        .insert(name, Variant::SharedString(value.into()));
+                    promoted += 1;
+                }
+            }
+        }
+
+        promoted
     }
 
     /// Clone the instance with the given `referent` and all its descendants
@@ -405,6 +1555,46 @@ impl WeakDom {
         root_ref
     }
 
+    /// Clone the instance with the given `referent` and all its descendants
+    /// (i.e. the entire subtree) into the same WeakDom, only copying the
+    /// properties for which `filter` returns true.
+    ///
+    /// Like [`WeakDom::clone_within`], the root of the cloned subtree has no
+    /// parent, and Ref properties that point to instances contained in the
+    /// subtree are rewritten to point to the cloned instances. Properties that
+    /// are filtered out are never copied, so this uses less memory than
+    /// cloning a subtree and then removing properties from it.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(
+    ///     InstanceBuilder::new("Script").with_property("Source", "print('hi')"),
+    /// );
+    ///
+    /// let clone_ref = dom.clone_filtered(dom.root_ref(), |name, _| name != "Source");
+    /// assert!(dom.get_by_ref(clone_ref).unwrap().properties.is_empty());
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn clone_filtered<F>(&mut self, referent: Ref, filter: F) -> Ref
+    where
+        F: Fn(&Symbol, &Variant) -> bool,
+    {
+        let mut ctx = CloneContext::default();
+        let root_builder = ctx.clone_filtered_ref_as_builder(self, referent, &filter);
+        let root_ref = self.insert(Ref::none(), root_builder);
+
+        while let Some((cloned_parent, uncloned_child)) = ctx.queue.pop_front() {
+            let builder = ctx.clone_filtered_ref_as_builder(self, uncloned_child, &filter);
+            self.insert(cloned_parent, builder);
+        }
+
+        ctx.rewrite_refs(self);
+        root_ref
+    }
+
     /// Clone the instance with the given `referent` and all its descendants (i.e. the
     /// entire subtree) into the given WeakDom.
     ///
@@ -451,43 +1641,151 @@ impl WeakDom {
         root_refs
     }
 
-    fn inner_insert(&mut self, referent: Ref, instance: Instance) {
-        self.instances.insert(referent, instance);
+    /// Consumes the `WeakDom`, converting the instance with the given
+    /// `referent` and all of its descendants back into an [`InstanceBuilder`].
+    /// Passing [`WeakDom::root_ref`] converts the whole dom.
+    ///
+    /// If `preserve_referents` is true, every builder keeps the referent of the
+    /// instance it was made from. Otherwise, every builder gets a new referent
+    /// and Ref properties that point within the subtree are rewritten to match.
+    /// In both cases, Ref properties that point outside of the subtree are
+    /// rewritten to be `Ref::none()`, since they would be invalid in any dom the
+    /// builder is inserted into.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn into_builder(mut self, referent: Ref, preserve_referents: bool) -> InstanceBuilder {
+        if !self.instances.contains_key(&referent) {
+            panic!("cannot convert an instance that does not exist into a builder");
+        }
+
+        // Descendants are ordered such that parents come before their
+        // children, so building them in reverse ensures that every child is
+        // finished before it's added to its parent.
+        let order: Vec<Ref> = self
+            .descendants_of(referent)
+            .map(Instance::referent)
+            .collect();
+
+        let new_refs: AHashMap<Ref, Ref> = order
+            .iter()
+            .map(|old| (*old, if preserve_referents { *old } else { Ref::new() }))
+            .collect();
+
+        let mut finished: AHashMap<Ref, InstanceBuilder> = AHashMap::with_capacity(order.len());
+
+        for old_ref in order.iter().rev() {
+            let instance = slot::into_inner(self.instances.remove(old_ref).unwrap());
+            let property_count = instance.properties.len();
+
+            let properties = instance
+                .properties
+                .into_iter()
+                .map(|(key, value)| match value {
+                    Variant::Ref(target) => (
+                        key,
+                        Variant::Ref(new_refs.get(&target).copied().unwrap_or_else(Ref::none)),
+                    ),
+                    value => (key, value),
+                });
+
+            let mut builder =
+                InstanceBuilder::with_property_capacity(instance.class, property_count)
+                    .with_referent(new_refs[old_ref])
+                    .with_name(instance.name)
+                    .with_properties(properties)
+                    .with_children(
+                        instance
+                            .children
+                            .iter()
+                            .map(|child| finished.remove(child).unwrap()),
+                    );
+            builder.binary_referent = instance.binary_referent;
+            builder.byte_size = instance.byte_size;
+
+            finished.insert(*old_ref, builder);
+        }
+
+        finished.remove(&referent).unwrap()
+    }
+
+    /// Moves the instance with the given referent to `index` in the children
+    /// of `parent_ref`, or to the end if `index` is past it. Unlike
+    /// [`WeakDom::transfer_within`], `parent_ref` can be none, leaving the
+    /// instance without a parent.
+    pub(crate) fn move_instance(&mut self, referent: Ref, parent_ref: Ref, index: usize) {
+        let instance = self
+            .instances
+            .get_mut(&referent)
+            .map(slot::make_mut)
+            .unwrap_or_else(|| panic!("cannot move an instance that does not exist"));
+        let previous_parent_ref = std::mem::replace(&mut instance.parent, parent_ref);
+
+        if let Some(previous_parent) = self
+            .instances
+            .get_mut(&previous_parent_ref)
+            .map(slot::make_mut)
+        {
+            previous_parent.children.retain(|&child| child != referent);
+        }
+
+        if parent_ref.is_some() {
+            let parent = self
+                .instances
+                .get_mut(&parent_ref)
+                .map(slot::make_mut)
+                .unwrap_or_else(|| panic!("cannot move into an instance that does not exist"));
+            parent
+                .children
+                .insert(index.min(parent.children.len()), referent);
+        }
+    }
 
+    /// Returns the position of `referent` in the children of `parent_ref`, or
+    /// zero if it has no parent.
+    fn child_index(&self, parent_ref: Ref, referent: Ref) -> usize {
+        self.instances
+            .get(&parent_ref)
+            .and_then(|parent| parent.children.iter().position(|&child| child == referent))
+            .unwrap_or(0)
+    }
+
+    fn inner_insert(&mut self, referent: Ref, mut instance: Slot) {
         // We need to ensure that the value of the Instance.UniqueId property does
         // not collide with another instance. If it does, we must regenerate
         // it. If we *don't* do this, it's possible to use WeakDom::insert to
         // insert UniqueId properties that collide with other instances in the
         // dom, violating the invariant that every UniqueId is unique.
-
-        // Unwrap is safe because we just inserted this referent into the instance map
-        let instance = self.instances.get_mut(&referent).unwrap();
-        if let Some(Variant::UniqueId(unique_id)) = instance.properties.get(&ustr("UniqueId")) {
-            if self.unique_ids.contains(unique_id) {
-                // We found a collision! We need to replace the UniqueId property with
-                // a new value.
-
-                // Unwrap is probably ok. Likely not worth making this method fallible
-                // just because the system clock might be out whack, so panicking is fine
-                let new_unique_id = UniqueId::now().unwrap();
-
-                self.unique_ids.insert(new_unique_id);
-                instance
-                    .properties
-                    .insert(ustr("UniqueId"), Variant::UniqueId(new_unique_id));
-            } else {
-                self.unique_ids.insert(*unique_id);
-            };
+        //
+        // The instance is only borrowed mutably when it has to change, so an
+        // instance shared with a snapshot isn't copied just to be inserted.
+        let collides = match instance.properties.get("UniqueId") {
+            Some(Variant::UniqueId(unique_id)) => !self.unique_ids.insert(*unique_id),
+            _ => false,
+        };
+        if collides {
+            // We found a collision! We need to replace the UniqueId property with
+            // a new value.
+            let new_unique_id = UniqueId::generate();
+
+            self.unique_ids.insert(new_unique_id);
+            slot::make_mut(&mut instance)
+                .properties
+                .insert(symbol("UniqueId"), Variant::UniqueId(new_unique_id));
         }
+
+        self.index.insert(&instance);
+        self.instances.insert(referent, instance);
     }
 
-    fn inner_remove(&mut self, referent: Ref) -> Instance {
+    fn inner_remove(&mut self, referent: Ref) -> Slot {
         let instance = self
             .instances
             .remove(&referent)
             .unwrap_or_else(|| panic!("cannot remove an instance that does not exist"));
+        self.index.remove(&instance);
 
-        if let Some(Variant::UniqueId(unique_id)) = instance.properties.get(&ustr("UniqueId")) {
+        if let Some(Variant::UniqueId(unique_id)) = instance.properties.get("UniqueId") {
             self.unique_ids.remove(unique_id);
         }
 
@@ -505,19 +1803,239 @@ pub struct WeakDomDescendants<'a> {
     queue: VecDeque<Ref>,
 }
 
-impl<'a> Iterator for WeakDomDescendants<'a> {
-    type Item = &'a Instance;
+impl<'a> Iterator for WeakDomDescendants<'a> {
+    type Item = &'a Instance;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let instance = self
+            .queue
+            .pop_front()
+            .and_then(|r| self.dom.get_by_ref(r))?;
+        self.queue.extend(instance.children());
+        Some(instance)
+    }
+}
+
+/// A `Ref` property that points to an instance that is not, or would not be,
+/// part of a [`WeakDom`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingRef {
+    /// The referent of the instance that has the property.
+    pub referent: Ref,
+
+    /// The name of the property.
+    pub property: Symbol,
+
+    /// The referent of the instance the property points to.
+    pub target: Ref,
+}
+
+/// Describes what [`WeakDom::fix_dangling_refs`] should do with `Ref`
+/// properties that point to instances that are not part of the dom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DanglingRefPolicy {
+    /// Set the properties to `Ref::none()`.
+    SetToNone,
+
+    /// Remove the properties from their instances.
+    Remove,
+
+    /// Leave the properties alone, only reporting them.
+    Report,
+}
+
+/// Returned by [`WeakDom::try_retain`] when removing instances would leave
+/// `Ref` properties pointing to instances that no longer exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingRefError {
+    refs: Vec<DanglingRef>,
+}
+
+impl DanglingRefError {
+    /// Returns every property that would have been left dangling.
+    pub fn refs(&self) -> &[DanglingRef] {
+        &self.refs
+    }
+}
+
+impl fmt::Display for DanglingRefError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} Ref properties point to instances that would be removed",
+            self.refs.len()
+        )
+    }
+}
+
+impl std::error::Error for DanglingRefError {}
+
+/// A problem found by [`WeakDom::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Violation {
+    /// The root referent is null.
+    NullRoot,
+
+    /// The root referent does not refer to an instance in the dom.
+    MissingRoot {
+        /// The referent of the root.
+        root: Ref,
+    },
+
+    /// The root instance has a parent.
+    RootHasParent {
+        /// The referent the root instance's parent is set to.
+        parent: Ref,
+    },
+
+    /// An instance is stored under a referent that is not its own.
+    MismatchedReferent {
+        /// The referent the instance is stored under.
+        key: Ref,
+        /// The referent of the instance itself.
+        referent: Ref,
+    },
+
+    /// An instance lists the same child more than once.
+    DuplicateChild {
+        /// The referent of the instance listing the child.
+        parent: Ref,
+        /// The referent of the child.
+        child: Ref,
+    },
+
+    /// An instance lists a child that does not exist.
+    MissingChild {
+        /// The referent of the instance listing the child.
+        parent: Ref,
+        /// The referent of the missing child.
+        child: Ref,
+    },
+
+    /// An instance is listed as a child of an instance that is not its
+    /// parent.
+    WrongParent {
+        /// The referent of the child.
+        child: Ref,
+        /// The referent of the instance that lists it as a child.
+        listed_under: Ref,
+        /// The referent the child's parent is set to.
+        parent: Ref,
+    },
+
+    /// An instance's parent does not exist.
+    MissingParent {
+        /// The referent of the instance.
+        child: Ref,
+        /// The referent of the missing parent.
+        parent: Ref,
+    },
+
+    /// An instance's parent does not list it as a child.
+    NotAChild {
+        /// The referent of the instance.
+        child: Ref,
+        /// The referent of its parent.
+        parent: Ref,
+    },
+
+    /// A `Ref` property points to an instance that does not exist.
+    DanglingRef(DanglingRef),
+
+    /// Two instances have the same `UniqueId`.
+    DuplicateUniqueId {
+        /// The shared `UniqueId`.
+        id: UniqueId,
+        /// The referents of the two instances.
+        referents: [Ref; 2],
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::NullRoot => write!(f, "the root referent is null"),
+            Violation::MissingRoot { root } => {
+                write!(f, "the root {root} is not part of the dom")
+            }
+            Violation::RootHasParent { parent } => {
+                write!(f, "the root instance has parent {parent}")
+            }
+            Violation::MismatchedReferent { key, referent } => {
+                write!(f, "instance {referent} is stored under referent {key}")
+            }
+            Violation::DuplicateChild { parent, child } => {
+                write!(f, "instance {parent} lists child {child} more than once")
+            }
+            Violation::MissingChild { parent, child } => {
+                write!(
+                    f,
+                    "instance {parent} lists child {child}, which does not exist"
+                )
+            }
+            Violation::WrongParent {
+                child,
+                listed_under,
+                parent,
+            } => write!(
+                f,
+                "instance {child} is a child of {listed_under}, but its parent is {parent}"
+            ),
+            Violation::MissingParent { child, parent } => {
+                write!(
+                    f,
+                    "instance {child} has parent {parent}, which does not exist"
+                )
+            }
+            Violation::NotAChild { child, parent } => {
+                write!(
+                    f,
+                    "instance {child} is not listed as a child of its parent {parent}"
+                )
+            }
+            Violation::DanglingRef(dangling) => write!(
+                f,
+                "property {} of instance {} points to {}, which does not exist",
+                dangling.property, dangling.referent, dangling.target
+            ),
+            Violation::DuplicateUniqueId { id, referents } => write!(
+                f,
+                "instances {} and {} share UniqueId {id}",
+                referents[0], referents[1]
+            ),
+        }
+    }
+}
+
+/// Returned by [`WeakDom::validate`] when a `WeakDom` is not internally
+/// consistent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    violations: Vec<Violation>,
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let instance = self
-            .queue
-            .pop_front()
-            .and_then(|r| self.dom.get_by_ref(r))?;
-        self.queue.extend(instance.children());
-        Some(instance)
+impl ValidationError {
+    /// Returns every problem that was found.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the dom is invalid:")?;
+
+        for violation in &self.violations {
+            write!(f, "\n- {violation}")?;
+        }
+
+        Ok(())
     }
 }
 
+impl std::error::Error for ValidationError {}
+
 impl Default for WeakDom {
     fn default() -> WeakDom {
         WeakDom {
@@ -525,10 +2043,32 @@ impl Default for WeakDom {
             root_ref: Ref::none(),
             unique_ids: AHashSet::new(),
             instance_byte_sizes: None,
+            instance_content_hashes: None,
+            instance_binary_referents: None,
+            metadata: Metadata::new(),
+            journal: None,
+            history: History::default(),
+            index: InstanceIndex::default(),
+            interner: Interner::default(),
         }
     }
 }
 
+/// Returns whether `name` can be used to index an instance with `.` in Luau.
+fn is_identifier(name: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "if", "in",
+        "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+    ];
+
+    let mut chars = name.chars();
+    let starts_ok = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+
+    starts_ok && chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !KEYWORDS.contains(&name)
+}
+
 #[derive(Debug, Default)]
 struct CloneContext {
     queue: VecDeque<(Ref, Ref)>,
@@ -557,8 +2097,11 @@ impl CloneContext {
 
         for (_, new_ref) in self.ref_rewrites.iter() {
             let instance = dest
-                .get_by_ref_mut(*new_ref)
+                .instances
+                .get_mut(new_ref)
+                .map(slot::make_mut)
                 .expect("Cannot rewrite refs on an instance that does not exist");
+            dest.index.remove_refs(instance);
 
             for prop_value in instance.properties.values_mut() {
                 if let Variant::Ref(original_ref) = prop_value {
@@ -573,6 +2116,8 @@ impl CloneContext {
                     }
                 }
             }
+
+            dest.index.insert_refs(instance);
         }
     }
 
@@ -587,7 +2132,7 @@ impl CloneContext {
             .get_by_ref(original_ref)
             .expect("Cannot clone an instance that does not exist");
 
-        let builder = InstanceBuilder::new(instance.class)
+        let builder = InstanceBuilder::new(instance.class.clone())
             .with_name(instance.name.to_string())
             .with_properties(instance.properties.clone());
 
@@ -600,6 +2145,41 @@ impl CloneContext {
         self.ref_rewrites.insert(original_ref, new_ref);
         builder
     }
+
+    /// Like [`CloneContext::clone_ref_as_builder`], but only clones the
+    /// properties for which `filter` returns true.
+    fn clone_filtered_ref_as_builder<F>(
+        &mut self,
+        source: &WeakDom,
+        original_ref: Ref,
+        filter: &F,
+    ) -> InstanceBuilder
+    where
+        F: Fn(&Symbol, &Variant) -> bool,
+    {
+        let instance = source
+            .get_by_ref(original_ref)
+            .expect("Cannot clone an instance that does not exist");
+
+        let builder = InstanceBuilder::new(instance.class.clone())
+            .with_name(instance.name.to_string())
+            .with_properties(
+                instance
+                    .properties
+                    .iter()
+                    .filter(|(name, value)| filter(name, value))
+                    .map(|(name, value)| (name.clone(), value.clone())),
+            );
+
+        let new_ref = builder.referent;
+
+        for uncloned_child in instance.children.iter() {
+            self.queue.push_back((new_ref, *uncloned_child))
+        }
+
+        self.ref_rewrites.insert(original_ref, new_ref);
+        builder
+    }
 }
 
 #[cfg(test)]
@@ -607,7 +2187,7 @@ mod test {
     use super::*;
 
     use crate::DomViewer;
-    use rbx_types::{UniqueId, Variant};
+    use rbx_types::{BinaryString, UniqueId, Variant};
 
     #[test]
     fn transfer() {
@@ -666,6 +2246,34 @@ mod test {
         insta::assert_yaml_snapshot!(viewer.view_children(&dom));
     }
 
+    #[test]
+    fn sort_children() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+            InstanceBuilder::new("Part").with_name("B"),
+            InstanceBuilder::new("Model").with_name("A"),
+            InstanceBuilder::new("Folder").with_name("B"),
+            InstanceBuilder::new("Part").with_name("A"),
+        ]));
+
+        let describe = |dom: &WeakDom| -> Vec<String> {
+            dom.root()
+                .children()
+                .iter()
+                .map(|child| {
+                    let child = dom.get_by_ref(*child).unwrap();
+                    format!("{}:{}", child.class, child.name)
+                })
+                .collect()
+        };
+
+        // Children with the same name should keep their relative order.
+        dom.sort_children(dom.root_ref(), |a, b| a.name.cmp(&b.name));
+        assert_eq!(describe(&dom), ["Model:A", "Part:A", "Part:B", "Folder:B"]);
+
+        dom.sort_children(dom.root_ref(), |a, b| a.class.cmp(&b.class));
+        assert_eq!(describe(&dom), ["Folder:B", "Model:A", "Part:A", "Part:B"]);
+    }
+
     #[test]
     fn clone_within() {
         let mut child1 = InstanceBuilder::new("Part").with_name("Child1");
@@ -697,6 +2305,225 @@ mod test {
         insta::assert_yaml_snapshot!(viewer.view(&dom));
     }
 
+    #[test]
+    fn promote_shared_strings() {
+        let mesh_data = BinaryString::from(vec![7; 64]);
+        let parts: Vec<InstanceBuilder> = (0..3)
+            .map(|_| {
+                InstanceBuilder::new("MeshPart")
+                    .with_property("MeshData", mesh_data.clone())
+                    .with_property("Tiny", BinaryString::from(vec![1]))
+            })
+            .collect();
+        let part_refs: Vec<Ref> = parts.iter().map(|part| part.referent).collect();
+
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_children(parts));
+        let promoted = dom.promote_shared_strings(|instance, _, data| {
+            instance.class == "MeshPart" && data.len() > 1
+        });
+        assert_eq!(promoted, 3);
+
+        let values: Vec<&Variant> = part_refs
+            .iter()
+            .map(|referent| {
+                let part = dom.get_by_ref(*referent).unwrap();
+                assert!(matches!(
+                    part.properties.get("Tiny"),
+                    Some(Variant::BinaryString(_))
+                ));
+                part.properties.get("MeshData").unwrap()
+            })
+            .collect();
+
+        match values[0] {
+            Variant::SharedString(value) => assert_eq!(value.data(), &vec![7; 64][..]),
+            other => panic!("expected a SharedString, got {:?}", other),
+        }
+        assert!(values.iter().all(|value| *value == values[0]));
+    }
+
+    #[test]
+    fn detach() {
+        let unique_id = UniqueId::now().unwrap();
+        let child = InstanceBuilder::new("Part").with_property("UniqueId", unique_id);
+        let child_ref = child.referent;
+        let model = InstanceBuilder::new("Model")
+            .with_child(child)
+            .with_property("PrimaryPart", child_ref);
+        let model_ref = model.referent;
+        let sibling = InstanceBuilder::new("Folder");
+        let sibling_ref = sibling.referent;
+
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([model, sibling]));
+        let detached = dom.detach(model_ref);
+
+        assert_eq!(dom.root().children(), [sibling_ref]);
+        assert!(dom.get_by_ref(model_ref).is_none());
+        assert!(dom.get_by_ref(child_ref).is_none());
+        assert!(!dom.unique_ids.contains(&unique_id));
+
+        assert_eq!(detached.root_ref(), model_ref);
+        assert!(detached.root().parent().is_none());
+        assert_eq!(detached.root().children(), [child_ref]);
+        assert_eq!(
+            detached.root().properties.get("PrimaryPart"),
+            Some(&Variant::Ref(child_ref))
+        );
+        assert!(detached.unique_ids.contains(&unique_id));
+    }
+
+    #[test]
+    fn detach_rollback() {
+        let child = InstanceBuilder::new("Part");
+        let child_ref = child.referent;
+        let model = InstanceBuilder::new("Model").with_child(child);
+        let model_ref = model.referent;
+        let sibling = InstanceBuilder::new("Folder");
+        let sibling_ref = sibling.referent;
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([model, sibling]));
+
+        let result: Result<(), ()> = dom.transaction(|tx| {
+            assert_eq!(tx.detach(model_ref).root_ref(), model_ref);
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(dom.root().children(), [model_ref, sibling_ref]);
+        assert_eq!(dom.get_by_ref(child_ref).unwrap().parent(), model_ref);
+
+        dom.transaction(|tx| {
+            tx.detach(model_ref);
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+        assert_eq!(dom.root().children(), [sibling_ref]);
+
+        dom.undo();
+        assert_eq!(dom.root().children(), [model_ref, sibling_ref]);
+        assert_eq!(dom.get_by_ref(model_ref).unwrap().children(), [child_ref]);
+    }
+
+    #[test]
+    fn insert_remapped() {
+        let existing = InstanceBuilder::new("Folder").with_name("Existing");
+        let existing_ref = existing.referent;
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel").with_child(existing));
+
+        // The incoming model reuses the referent of an instance that's already
+        // in the dom, and one of its children points to it.
+        let kept = InstanceBuilder::new("ObjectValue").with_property("Value", existing_ref);
+        let kept_ref = kept.referent;
+        let model = InstanceBuilder::new("Model")
+            .with_referent(existing_ref)
+            .with_child(kept);
+
+        let (model_ref, remap) = dom.insert_remapped(dom.root_ref(), model);
+
+        assert_ne!(model_ref, existing_ref);
+        assert_eq!(remap.len(), 1);
+        assert_eq!(remap.get(&existing_ref), Some(&model_ref));
+
+        assert_eq!(dom.get_by_ref(existing_ref).unwrap().name, "Existing");
+        let kept = dom.get_by_ref(kept_ref).unwrap();
+        assert_eq!(kept.parent, model_ref);
+        assert_eq!(kept.properties.get("Value"), Some(&Variant::Ref(model_ref)));
+    }
+
+    #[test]
+    fn interner() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let first = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("Part").with_property("Anchored", true),
+        );
+        let second = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("Part").with_property("Anchored", false),
+        );
+        dom.set_property(dom.root_ref(), "Anchored", true);
+
+        let [first, second, root] = [first, second, dom.root_ref()].map(|referent| {
+            let instance = dom.get_by_ref(referent).unwrap();
+            let (key, _) = instance.properties.get_key_value("Anchored").unwrap();
+            (instance.class.clone(), key.clone())
+        });
+        assert!(first.0.ptr_eq(&second.0));
+        assert!(first.1.ptr_eq(&second.1));
+        assert!(first.1.ptr_eq(&root.1));
+        assert_eq!(dom.interner_stats().entries, 3);
+
+        // Transferred instances keep their names, which the new dom starts
+        // sharing with anything inserted into it later.
+        let mut other = WeakDom::new(InstanceBuilder::new("Folder"));
+        let other_root = other.root_ref();
+        dom.transfer(dom.root().children()[0], &mut other, other_root);
+        let inserted = other.insert(other.root_ref(), InstanceBuilder::new("Part"));
+        assert!(other.get_by_ref(inserted).unwrap().class.ptr_eq(&first.0));
+
+        // Part is still used by the other dom until it's dropped.
+        dom.destroy(dom.root().children()[0]);
+        drop((first, second, root));
+        assert_eq!(dom.shrink_interner(), 0);
+
+        drop(other);
+        assert_eq!(dom.shrink_interner(), 1);
+        assert_eq!(dom.interner_stats().entries, 2);
+    }
+
+    #[test]
+    fn merge() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let other = WeakDom::new(InstanceBuilder::new("Folder").with_referent(dom.root_ref()));
+        let other_root_ref = other.root_ref();
+
+        let (folder_ref, remap) = dom.merge(dom.root_ref(), other);
+        assert_eq!(remap.get(&other_root_ref), Some(&folder_ref));
+        assert_eq!(dom.root().children(), [folder_ref]);
+        assert_eq!(dom.get_by_ref(folder_ref).unwrap().class, "Folder");
+
+        let other = WeakDom::new(InstanceBuilder::new("Model"));
+        let other_root_ref = other.root_ref();
+        let (model_ref, remap) = dom.merge(folder_ref, other);
+        assert_eq!(model_ref, other_root_ref);
+        assert!(remap.is_empty());
+    }
+
+    #[test]
+    fn clone_filtered() {
+        let script = InstanceBuilder::new("Script")
+            .with_property("Source", "print('hello')")
+            .with_property("Disabled", false);
+        let script_ref = script.referent;
+        let value = InstanceBuilder::new("ObjectValue").with_property("Value", script_ref);
+
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_property("Source", "not a script")
+                .with_children([script, value]),
+        );
+
+        let clone_ref = dom.clone_filtered(dom.root_ref, |name, _| name != "Source");
+        let clone = dom.get_by_ref(clone_ref).unwrap();
+        assert!(clone.parent.is_none());
+        assert!(clone.properties.is_empty());
+
+        let cloned_script = dom.get_by_ref(clone.children[0]).unwrap();
+        assert_eq!(cloned_script.properties.len(), 1);
+        assert_eq!(
+            cloned_script.properties.get("Disabled"),
+            Some(&Variant::Bool(false))
+        );
+
+        // Ref properties are still rewritten to point into the clone.
+        let cloned_value = dom.get_by_ref(clone.children[1]).unwrap();
+        assert_eq!(
+            cloned_value.properties.get("Value"),
+            Some(&Variant::Ref(cloned_script.referent))
+        );
+
+        // The original instances are untouched.
+        assert_eq!(dom.get_by_ref(script_ref).unwrap().properties.len(), 2);
+    }
+
     #[test]
     fn clone_into_external() {
         let dom = {
@@ -777,6 +2604,475 @@ mod test {
         insta::assert_yaml_snapshot!(viewer.view(&other_dom));
     }
 
+    #[test]
+    fn retain() {
+        let script = InstanceBuilder::new("Script").with_child(InstanceBuilder::new("Folder"));
+        let script_ref = script.referent;
+        let script_child_ref = script.children[0].referent;
+
+        let value = InstanceBuilder::new("ObjectValue").with_property("Value", script_ref);
+        let value_ref = value.referent;
+
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(script)
+                .with_child(value),
+        );
+
+        let mut visited = Vec::new();
+        dom.retain(|instance| {
+            visited.push(instance.referent());
+            instance.class != "Script"
+        });
+
+        assert_eq!(
+            visited,
+            [script_ref, value_ref],
+            "descendants of rejected instances should not be visited"
+        );
+        assert!(dom.get_by_ref(script_ref).is_none());
+        assert!(dom.get_by_ref(script_child_ref).is_none());
+        assert_eq!(dom.root().children(), [value_ref]);
+        assert_eq!(
+            dom.get_by_ref(value_ref).unwrap().properties["Value"],
+            Variant::Ref(Ref::none()),
+            "Ref properties pointing to removed instances should be nulled"
+        );
+    }
+
+    #[test]
+    fn retain_rollback() {
+        let script = InstanceBuilder::new("Script");
+        let script_ref = script.referent;
+        let value = InstanceBuilder::new("ObjectValue").with_property("Value", script_ref);
+        let value_ref = value.referent;
+        let mut dom =
+            WeakDom::new(InstanceBuilder::new("DataModel").with_children([script, value]));
+
+        let result: Result<(), ()> = dom.transaction(|tx| {
+            tx.retain(|instance| instance.class != "Script");
+            Err(())
+        });
+        assert!(result.is_err());
+        assert!(dom.get_by_ref(script_ref).is_some());
+        assert_eq!(
+            dom.get_by_ref(value_ref).unwrap().properties["Value"],
+            Variant::Ref(script_ref),
+            "rolling back retain should restore the Ref properties it nulled"
+        );
+
+        dom.transaction(|tx| {
+            tx.retain(|instance| instance.class != "Script");
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+        assert!(dom.get_by_ref(script_ref).is_none());
+
+        dom.undo();
+        assert!(dom.get_by_ref(script_ref).is_some());
+        assert_eq!(
+            dom.get_by_ref(value_ref).unwrap().properties["Value"],
+            Variant::Ref(script_ref)
+        );
+    }
+
+    #[test]
+    fn try_retain() {
+        let script = InstanceBuilder::new("Script");
+        let script_ref = script.referent;
+
+        let value = InstanceBuilder::new("ObjectValue").with_property("Value", script_ref);
+        let value_ref = value.referent;
+
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(script)
+                .with_child(value),
+        );
+
+        let err = dom
+            .try_retain(|instance| instance.class != "Script")
+            .unwrap_err();
+
+        assert_eq!(
+            err.refs(),
+            [DanglingRef {
+                referent: value_ref,
+                property: symbol("Value"),
+                target: script_ref,
+            }]
+        );
+        assert!(
+            dom.get_by_ref(script_ref).is_some(),
+            "a failed try_retain should not remove anything"
+        );
+
+        dom.try_retain(|instance| instance.class != "ObjectValue")
+            .unwrap();
+        assert_eq!(dom.root().children(), [script_ref]);
+    }
+
+    #[test]
+    fn validate() {
+        let child = InstanceBuilder::new("ObjectValue");
+        let child_ref = child.referent();
+
+        let dom = WeakDom::new(InstanceBuilder::new("DataModel").with_child(child));
+        assert_eq!(dom.validate(), Ok(()));
+
+        let (root_ref, mut instances) = dom.into_raw();
+        let missing = Ref::new();
+        instances
+            .get_mut(&child_ref)
+            .unwrap()
+            .properties
+            .insert(symbol("Value"), Variant::Ref(missing));
+        instances
+            .get_mut(&root_ref)
+            .unwrap()
+            .children
+            .push(child_ref);
+
+        let dom = WeakDom::from_raw(root_ref, instances);
+        let error = dom.validate().unwrap_err();
+        let mut violations = error.violations().to_vec();
+        violations.sort_by_key(|violation| matches!(violation, Violation::DanglingRef(_)));
+
+        assert_eq!(
+            violations,
+            [
+                Violation::DuplicateChild {
+                    parent: root_ref,
+                    child: child_ref,
+                },
+                Violation::DanglingRef(DanglingRef {
+                    referent: child_ref,
+                    property: symbol("Value"),
+                    target: missing,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn into_builder() {
+        let child = InstanceBuilder::new("ObjectValue").with_name("Child");
+        let child_ref = child.referent();
+        let folder = InstanceBuilder::new("Folder")
+            .with_name("Folder")
+            .with_child(child.with_property("Value", child_ref));
+        let folder_ref = folder.referent();
+
+        let outside = InstanceBuilder::new("Part").with_name("Outside");
+        let outside_ref = outside.referent();
+
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel").with_child(folder));
+        dom.insert(dom.root_ref(), outside);
+        dom.get_by_ref_mut(folder_ref)
+            .unwrap()
+            .properties
+            .insert(symbol("Target"), Variant::Ref(outside_ref));
+
+        let builder = dom.into_builder(folder_ref, true);
+        assert_eq!(builder.referent(), folder_ref);
+
+        let rebuilt = WeakDom::new(builder);
+        let folder = rebuilt.root();
+        assert_eq!(folder.name, "Folder");
+        assert_eq!(folder.children(), [child_ref]);
+        assert_eq!(
+            folder.properties.get("Target"),
+            Some(&Variant::Ref(Ref::none()))
+        );
+        assert_eq!(
+            rebuilt
+                .get_by_ref(child_ref)
+                .unwrap()
+                .properties
+                .get("Value"),
+            Some(&Variant::Ref(child_ref))
+        );
+
+        let root_ref = rebuilt.root_ref();
+        let rebuilt = WeakDom::new(rebuilt.into_builder(root_ref, false));
+        let new_child_ref = rebuilt.root().children()[0];
+        assert_ne!(rebuilt.root_ref(), folder_ref);
+        assert_ne!(new_child_ref, child_ref);
+        assert_eq!(
+            rebuilt
+                .get_by_ref(new_child_ref)
+                .unwrap()
+                .properties
+                .get("Value"),
+            Some(&Variant::Ref(new_child_ref))
+        );
+    }
+
+    #[test]
+    fn fix_dangling_refs() {
+        let missing = Ref::new();
+        let builder = InstanceBuilder::new("ObjectValue").with_property("Value", missing);
+        let value_ref = builder.referent();
+
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(builder)
+                .with_child(
+                    InstanceBuilder::new("ObjectValue").with_property("Value", Ref::none()),
+                ),
+        );
+        let expected = vec![DanglingRef {
+            referent: value_ref,
+            property: symbol("Value"),
+            target: missing,
+        }];
+
+        assert_eq!(dom.fix_dangling_refs(DanglingRefPolicy::Report), expected);
+        assert_eq!(dom.fix_dangling_refs(DanglingRefPolicy::Report), expected);
+
+        assert_eq!(
+            dom.fix_dangling_refs(DanglingRefPolicy::SetToNone),
+            expected
+        );
+        assert_eq!(
+            dom.get_by_ref(value_ref).unwrap().properties.get("Value"),
+            Some(&Variant::Ref(Ref::none()))
+        );
+        assert!(dom.fix_dangling_refs(DanglingRefPolicy::Report).is_empty());
+
+        dom.get_by_ref_mut(value_ref)
+            .unwrap()
+            .properties
+            .insert(symbol("Value"), Variant::Ref(missing));
+        assert_eq!(dom.fix_dangling_refs(DanglingRefPolicy::Remove), expected);
+        assert!(dom.get_by_ref(value_ref).unwrap().properties.is_empty());
+    }
+
+    #[test]
+    fn fix_dangling_refs_rollback() {
+        let missing = Ref::new();
+        let builder = InstanceBuilder::new("ObjectValue").with_property("Value", missing);
+        let value_ref = builder.referent();
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel").with_child(builder));
+
+        for policy in [DanglingRefPolicy::SetToNone, DanglingRefPolicy::Remove] {
+            let result: Result<(), ()> = dom.transaction(|tx| {
+                assert_eq!(tx.fix_dangling_refs(policy).len(), 1);
+                Err(())
+            });
+            assert!(result.is_err());
+            assert_eq!(
+                dom.get_by_ref(value_ref).unwrap().properties["Value"],
+                Variant::Ref(missing),
+                "rolling back {:?} should restore the dangling property",
+                policy
+            );
+        }
+    }
+
+    #[test]
+    fn insert_ref_properties() {
+        let base = InstanceBuilder::new("Part").with_name("Base");
+        let base_ref = base.referent();
+        let other = InstanceBuilder::new("Part");
+        let other_ref = other.referent();
+
+        let model = InstanceBuilder::new("Model")
+            .with_ref_property("PrimaryPart", "Base")
+            .with_ref_property("Other", &other)
+            .with_ref_property("Missing", "Nowhere/Base")
+            .with_child(base)
+            .with_child(other)
+            .with_child(InstanceBuilder::new("ObjectValue").with_ref_property("Value", "../Base"));
+        let model_ref = model.referent();
+
+        let dom = WeakDom::new(model);
+        let model = dom.get_by_ref(model_ref).unwrap();
+        let value = dom.get_by_ref(model.children()[2]).unwrap();
+
+        assert_eq!(
+            model.properties.get("PrimaryPart"),
+            Some(&Variant::Ref(base_ref))
+        );
+        assert_eq!(
+            model.properties.get("Other"),
+            Some(&Variant::Ref(other_ref))
+        );
+        assert_eq!(
+            model.properties.get("Missing"),
+            Some(&Variant::Ref(Ref::none()))
+        );
+        assert_eq!(value.properties.get("Value"), Some(&Variant::Ref(base_ref)));
+    }
+
+    #[test]
+    fn full_name_of() {
+        let part = InstanceBuilder::new("Part").with_name("Part");
+        let part_ref = part.referent();
+        let odd = InstanceBuilder::new("Folder").with_name("My \"Odd\"\nModel");
+        let odd_ref = odd.referent();
+        let keyword = InstanceBuilder::new("Folder").with_name("end");
+        let keyword_ref = keyword.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("DataModel").with_child(
+                InstanceBuilder::new("Workspace")
+                    .with_child(InstanceBuilder::new("Model").with_child(part))
+                    .with_child(odd)
+                    .with_child(keyword),
+            ),
+        );
+
+        assert_eq!(dom.full_name_of(dom.root_ref()), "game");
+        assert_eq!(dom.full_name_of(part_ref), "game.Workspace.Model.Part");
+        assert_eq!(
+            dom.full_name_of(odd_ref),
+            "game.Workspace[\"My \\\"Odd\\\"\\nModel\"]"
+        );
+        assert_eq!(dom.full_name_of(keyword_ref), "game.Workspace[\"end\"]");
+
+        let dom =
+            WeakDom::new(InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("Part")));
+        assert_eq!(dom.full_name_of(dom.root().children()[0]), "Folder.Part");
+    }
+
+    #[test]
+    fn is_descendant_of() {
+        let part = InstanceBuilder::new("Part");
+        let part_ref = part.referent();
+        let folder = InstanceBuilder::new("Folder").with_child(part);
+        let folder_ref = folder.referent();
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Model")
+                .with_child(folder)
+                .with_child(InstanceBuilder::new("Decal")),
+        );
+        let root_ref = dom.root_ref();
+        let decal_ref = dom.root().children()[1];
+
+        assert!(dom.is_descendant_of(part_ref, folder_ref));
+        assert!(dom.is_descendant_of(part_ref, root_ref));
+        assert!(!dom.is_descendant_of(folder_ref, part_ref));
+        assert!(!dom.is_descendant_of(part_ref, decal_ref));
+        assert!(!dom.is_descendant_of(root_ref, root_ref));
+        assert!(!dom.is_descendant_of(Ref::new(), root_ref));
+
+        let ancestors: Vec<Ref> = dom
+            .ancestors_of(part_ref)
+            .skip(1)
+            .map(Instance::referent)
+            .collect();
+        assert_eq!(ancestors, [folder_ref, root_ref]);
+
+        dom.transfer_within(part_ref, decal_ref);
+        assert!(!dom.is_descendant_of(part_ref, folder_ref));
+        assert!(dom.is_descendant_of(part_ref, decal_ref));
+    }
+
+    #[test]
+    fn binary_referents() {
+        let part = InstanceBuilder::new("Part").with_binary_referent(7);
+        let part_ref = part.referent();
+        let dom = WeakDom::new(InstanceBuilder::new("Folder").with_child(part));
+
+        assert_eq!(dom.binary_referent_of(part_ref), Some(7));
+        assert_eq!(dom.binary_referent_of(dom.root_ref()), None);
+        assert_eq!(dom.binary_referent_of(Ref::new()), None);
+
+        assert_eq!(
+            dom.get_by_binary_referent(7).map(Instance::referent),
+            Some(part_ref)
+        );
+        assert!(dom.get_by_binary_referent(8).is_none());
+    }
+
+    #[test]
+    fn indexed_binary_referents() {
+        let part = InstanceBuilder::new("Part").with_binary_referent(7);
+        let part_ref = part.referent();
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_child(part));
+        dom.instance_binary_referents = Some([(7, part_ref)].iter().copied().collect());
+
+        assert_eq!(
+            dom.get_by_binary_referent(7).map(Instance::referent),
+            Some(part_ref)
+        );
+
+        // Instances that aren't in the map, or that the map is wrong about,
+        // are still found.
+        let added = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("Part").with_binary_referent(8),
+        );
+        assert_eq!(
+            dom.get_by_binary_referent(8).map(Instance::referent),
+            Some(added)
+        );
+
+        dom.destroy(part_ref);
+        let replacement = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("Part").with_binary_referent(7),
+        );
+        assert_eq!(
+            dom.get_by_binary_referent(7).map(Instance::referent),
+            Some(replacement)
+        );
+    }
+
+    #[test]
+    fn decoded_byte_sizes() {
+        let part = InstanceBuilder::new("Part")
+            .with_binary_referent(0)
+            .with_byte_size(40);
+        let part_ref = part.referent();
+        let dom = WeakDom::new(InstanceBuilder::new("Folder").with_child(part));
+
+        assert_eq!(
+            dom.get_by_ref(part_ref).unwrap().decoded_byte_size(),
+            Some(40)
+        );
+        assert_eq!(dom.root().decoded_byte_size(), None);
+
+        // Sizes survive being turned back into a builder.
+        let copy = WeakDom::new(dom.into_builder(part_ref, false));
+        assert_eq!(copy.root().decoded_byte_size(), Some(40));
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let a = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("Folder").with_name("A"),
+        );
+        let b = dom.insert(dom.root_ref(), InstanceBuilder::new("Part").with_name("B"));
+
+        let [Some(a_instance), Some(b_instance), None] = dom.get_many_mut([a, b, Ref::new()])
+        else {
+            panic!("expected both instances to be found");
+        };
+        a_instance
+            .properties
+            .insert(symbol("Target"), Variant::Ref(b_instance.referent()));
+        b_instance.name = a_instance.name.clone();
+
+        assert_eq!(dom.get_by_ref(b).unwrap().name, "A");
+        assert_eq!(
+            dom.get_by_ref(a).unwrap().properties.get("Target"),
+            Some(&Variant::Ref(b))
+        );
+    }
+
+    #[test]
+    #[should_panic = "more than once"]
+    fn get_many_mut_overlapping() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let root_ref = dom.root_ref();
+
+        dom.get_many_mut([root_ref, root_ref]);
+    }
+
     #[test]
     fn large_depth_tree() {
         // We've had issues with stack overflows when creating WeakDoms with
@@ -811,7 +3107,7 @@ mod test {
         );
 
         let child = dom.get_by_ref(child_ref).unwrap();
-        if let Some(Variant::UniqueId(actual_unique_id)) = child.properties.get(&ustr("UniqueId")) {
+        if let Some(Variant::UniqueId(actual_unique_id)) = child.properties.get("UniqueId") {
             assert_ne!(
                 unique_id,
                 *actual_unique_id,
@@ -838,7 +3134,7 @@ mod test {
         );
 
         let child = dom.get_by_ref(child_ref).unwrap();
-        if let Some(Variant::UniqueId(actual_unique_id)) = child.properties.get(&ustr("UniqueId")) {
+        if let Some(Variant::UniqueId(actual_unique_id)) = child.properties.get("UniqueId") {
             assert_ne!(
                 unique_id,
                 *actual_unique_id,
@@ -860,7 +3156,7 @@ mod test {
         );
 
         let child = dom.get_by_ref(child_ref).unwrap();
-        if let Some(Variant::UniqueId(actual_unique_id)) = child.properties.get(&ustr("UniqueId")) {
+        if let Some(Variant::UniqueId(actual_unique_id)) = child.properties.get("UniqueId") {
             assert_eq!(
                 unique_id,
                 *actual_unique_id,
@@ -891,8 +3187,7 @@ mod test {
         dom.transfer(folder_ref, &mut other_dom, other_root_ref);
 
         let folder = other_dom.get_by_ref(folder_ref).unwrap();
-        if let Some(Variant::UniqueId(actual_unique_id)) = folder.properties.get(&ustr("UniqueId"))
-        {
+        if let Some(Variant::UniqueId(actual_unique_id)) = folder.properties.get("UniqueId") {
             assert_ne!(
                 unique_id, *actual_unique_id,
                 "WeakDom::transfer caused a UniqueId collision."
@@ -967,7 +3262,7 @@ mod test {
             .get_by_ref(child_1)
             .unwrap()
             .properties
-            .get(&ustr("Value"))
+            .get("Value")
             .unwrap()
         else {
             panic!("child_1.Value was not a Ref. How did this happen?")
@@ -976,7 +3271,7 @@ mod test {
             .get_by_ref(child_2)
             .unwrap()
             .properties
-            .get(&ustr("Value"))
+            .get("Value")
             .unwrap()
         else {
             panic!("child_1.Value was not a Ref. How did this happen?")
@@ -1006,11 +3301,11 @@ mod test {
         tree.get_mut(&inst_ref_1)
             .unwrap()
             .properties
-            .insert(ustr("UniqueId"), id.into());
+            .insert(symbol("UniqueId"), id.into());
         tree.get_mut(&inst_ref_2)
             .unwrap()
             .properties
-            .insert(ustr("UniqueId"), id.into());
+            .insert(symbol("UniqueId"), id.into());
 
         let _ = WeakDom::from_raw(root, tree);
     }
@@ -1026,11 +3321,11 @@ mod test {
         tree.get_mut(&inst_ref_1)
             .unwrap()
             .properties
-            .insert(ustr("UniqueId"), UniqueId::now().unwrap().into());
+            .insert(symbol("UniqueId"), UniqueId::now().unwrap().into());
         tree.get_mut(&inst_ref_2)
             .unwrap()
             .properties
-            .insert(ustr("UniqueId"), UniqueId::now().unwrap().into());
+            .insert(symbol("UniqueId"), UniqueId::now().unwrap().into());
 
         let _ = WeakDom::from_raw(root, tree);
     }
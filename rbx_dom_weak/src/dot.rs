@@ -0,0 +1,245 @@
+use std::io::{self, Write};
+
+use ahash::{HashMap, HashMapExt};
+
+use crate::{types::Variant, WeakDom};
+
+/// Options for [`WeakDom::to_dot`].
+///
+/// ```
+/// use rbx_dom_weak::DotOptions;
+///
+/// let options = DotOptions::new().with_ref_edges(false).with_max_depth(2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DotOptions {
+    ref_edges: bool,
+    max_depth: Option<usize>,
+}
+
+impl DotOptions {
+    /// Creates `DotOptions` that include every instance and every `Ref`
+    /// property between them.
+    pub fn new() -> Self {
+        Self {
+            ref_edges: true,
+            max_depth: None,
+        }
+    }
+
+    /// Sets whether to draw a dashed edge for every `Ref` property that points
+    /// to another instance in the graph, labeled with the property's name.
+    pub fn with_ref_edges(mut self, ref_edges: bool) -> Self {
+        self.ref_edges = ref_edges;
+        self
+    }
+
+    /// Stops including instances more than `max_depth` levels below the root.
+    /// `Ref` properties that point to instances that were left out aren't
+    /// drawn.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeakDom {
+    /// Writes the dom to `writer` as a [Graphviz] graph in the DOT language,
+    /// configured by `options`. Each instance is a node labeled with its class
+    /// and name, with a solid edge to each of its children and a dashed edge
+    /// for each of its `Ref` properties.
+    ///
+    /// The output can be rendered with `dot -Tsvg`, for example.
+    ///
+    /// [Graphviz]: https://graphviz.org/
+    ///
+    /// ```
+    /// use rbx_dom_weak::{DotOptions, InstanceBuilder, WeakDom};
+    ///
+    /// let part = InstanceBuilder::new("Part");
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Model")
+    ///         .with_property("PrimaryPart", part.referent())
+    ///         .with_child(part),
+    /// );
+    ///
+    /// let mut output = Vec::new();
+    /// dom.to_dot(&mut output, &DotOptions::new())?;
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(output).unwrap(),
+    ///     concat!(
+    ///         "digraph {\n",
+    ///         "  node [shape=box];\n",
+    ///         "  n0 [label=\"Model\\nModel\"];\n",
+    ///         "  n1 [label=\"Part\\nPart\"];\n",
+    ///         "  n0 -> n1;\n",
+    ///         "  n0 -> n1 [style=dashed, label=\"PrimaryPart\"];\n",
+    ///         "}\n",
+    ///     ),
+    /// );
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn to_dot<W: Write>(&self, mut writer: W, options: &DotOptions) -> io::Result<()> {
+        // Instances are numbered in the order they're visited, which keeps the
+        // output stable no matter what the referents are.
+        let mut nodes = Vec::new();
+        let mut ids = HashMap::new();
+        let mut stack = vec![(self.root_ref(), 0)];
+
+        while let Some((referent, depth)) = stack.pop() {
+            let instance = match self.get_by_ref(referent) {
+                Some(instance) => instance,
+                None => continue,
+            };
+
+            ids.insert(referent, nodes.len());
+            nodes.push(instance);
+
+            if options.max_depth.is_some_and(|max| depth >= max) {
+                continue;
+            }
+
+            for child in instance.children().iter().rev() {
+                stack.push((*child, depth + 1));
+            }
+        }
+
+        writeln!(writer, "digraph {{")?;
+        writeln!(writer, "  node [shape=box];")?;
+
+        for (id, instance) in nodes.iter().enumerate() {
+            writeln!(
+                writer,
+                "  n{} [label=\"{}\\n{}\"];",
+                id,
+                escape(&instance.class),
+                escape(&instance.name)
+            )?;
+        }
+
+        for (id, instance) in nodes.iter().enumerate() {
+            for child in instance.children() {
+                if let Some(child_id) = ids.get(child) {
+                    writeln!(writer, "  n{} -> n{};", id, child_id)?;
+                }
+            }
+        }
+
+        if options.ref_edges {
+            for (id, instance) in nodes.iter().enumerate() {
+                for (name, value) in instance.sorted_properties() {
+                    let target_id = match value {
+                        Variant::Ref(target) => ids.get(target),
+                        _ => None,
+                    };
+
+                    if let Some(target_id) = target_id {
+                        writeln!(
+                            writer,
+                            "  n{} -> n{} [style=dashed, label=\"{}\"];",
+                            id,
+                            target_id,
+                            escape(&name)
+                        )?;
+                    }
+                }
+            }
+        }
+
+        writeln!(writer, "}}")
+    }
+}
+
+/// Escapes a string to be written between quotes in the DOT language.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{types::Ref, InstanceBuilder};
+
+    fn dot(dom: &WeakDom, options: &DotOptions) -> String {
+        let mut output = Vec::new();
+        dom.to_dot(&mut output, options).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn to_dot() {
+        let part = InstanceBuilder::new("Part").with_name("Handle \"A\"");
+        let part_ref = part.referent();
+        let outside = Ref::new();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Root")
+                .with_child(
+                    InstanceBuilder::new("Tool")
+                        .with_child(part)
+                        .with_child(InstanceBuilder::new("Folder")),
+                )
+                .with_child(
+                    InstanceBuilder::new("ObjectValue")
+                        .with_property("Value", part_ref)
+                        .with_property("Other", outside),
+                ),
+        );
+
+        assert_eq!(
+            dot(&dom, &DotOptions::new()),
+            concat!(
+                "digraph {\n",
+                "  node [shape=box];\n",
+                "  n0 [label=\"Folder\\nRoot\"];\n",
+                "  n1 [label=\"Tool\\nTool\"];\n",
+                "  n2 [label=\"Part\\nHandle \\\"A\\\"\"];\n",
+                "  n3 [label=\"Folder\\nFolder\"];\n",
+                "  n4 [label=\"ObjectValue\\nObjectValue\"];\n",
+                "  n0 -> n1;\n",
+                "  n0 -> n4;\n",
+                "  n1 -> n2;\n",
+                "  n1 -> n3;\n",
+                "  n4 -> n2 [style=dashed, label=\"Value\"];\n",
+                "}\n",
+            )
+        );
+
+        assert_eq!(
+            dot(&dom, &DotOptions::new().with_max_depth(1)),
+            concat!(
+                "digraph {\n",
+                "  node [shape=box];\n",
+                "  n0 [label=\"Folder\\nRoot\"];\n",
+                "  n1 [label=\"Tool\\nTool\"];\n",
+                "  n2 [label=\"ObjectValue\\nObjectValue\"];\n",
+                "  n0 -> n1;\n",
+                "  n0 -> n2;\n",
+                "}\n",
+            )
+        );
+
+        assert!(!dot(&dom, &DotOptions::new().with_ref_edges(false)).contains("dashed"));
+    }
+}
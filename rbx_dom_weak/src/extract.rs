@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+
+use ahash::AHashSet;
+
+use crate::{
+    dom::DanglingRef,
+    instance::InstanceBuilder,
+    types::{Ref, Variant},
+    WeakDom,
+};
+
+/// Describes what [`WeakDom::extract`] should do with `Ref` properties that
+/// point outside of the subtree being extracted, like the `Adornee` of a
+/// `BillboardGui` or the `CurrentCamera` of a `ViewportFrame`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalRefPolicy {
+    /// Extract the instances the properties point to, along with their
+    /// descendants, and follow the `Ref` properties of those in turn.
+    Include,
+
+    /// Set the properties to `Ref::none()`.
+    SetToNone,
+}
+
+/// A self-contained copy of part of a dom, returned by [`WeakDom::extract`].
+#[derive(Debug)]
+pub struct Extraction {
+    /// The extracted instances, whose root is a `DataModel` with no
+    /// properties. Every `Ref` property in it points to an instance in it or
+    /// to nothing.
+    pub dom: WeakDom,
+
+    /// The referents in [`Extraction::dom`] of the top-level extracted
+    /// instances, which are the children of its root. These are what should
+    /// be written to a model file.
+    pub roots: Vec<Ref>,
+
+    /// Every `Ref` property of the extracted instances that pointed outside
+    /// of the subtree being extracted, with the referents they had in the
+    /// original dom. With [`ExternalRefPolicy::Include`], properties whose
+    /// targets were pulled in still point at them, and every other one now
+    /// points to nothing.
+    pub external_refs: Vec<DanglingRef>,
+}
+
+impl WeakDom {
+    /// Copies the instance with the given referent and its descendants into a
+    /// new dom that doesn't refer to anything outside of itself, so that it
+    /// can be written to a model file on its own.
+    ///
+    /// `Ref` properties that point outside of the subtree are handled
+    /// according to `policy`, and are listed in
+    /// [`Extraction::external_refs`] either way. Properties that point to
+    /// instances that aren't in this dom at all are always set to
+    /// `Ref::none()`.
+    ///
+    /// With [`ExternalRefPolicy::Include`], a pulled-in instance that is an
+    /// ancestor of instances already being extracted takes their place as a
+    /// root, so no instance is extracted twice.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{ExternalRefPolicy, InstanceBuilder, WeakDom};
+    ///
+    /// let part = InstanceBuilder::new("Part");
+    /// let part_ref = part.referent();
+    /// let gui = InstanceBuilder::new("BillboardGui").with_ref_property("Adornee", part_ref);
+    /// let gui_ref = gui.referent();
+    /// let dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([part, gui]));
+    ///
+    /// let extraction = dom.extract(gui_ref, ExternalRefPolicy::Include);
+    /// assert_eq!(extraction.roots.len(), 2);
+    /// assert_eq!(extraction.external_refs[0].target, part_ref);
+    ///
+    /// let extraction = dom.extract(gui_ref, ExternalRefPolicy::SetToNone);
+    /// assert_eq!(extraction.roots.len(), 1);
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn extract(&self, referent: Ref, policy: ExternalRefPolicy) -> Extraction {
+        if self.get_by_ref(referent).is_none() {
+            panic!("cannot extract an instance that does not exist");
+        }
+
+        let mut to_scan: VecDeque<Ref> = self
+            .descendants_of(referent)
+            .map(|instance| instance.referent())
+            .collect();
+        let subtree: AHashSet<Ref> = to_scan.iter().copied().collect();
+
+        let mut roots = vec![referent];
+        let mut included = subtree.clone();
+        let mut external_refs = Vec::new();
+
+        while let Some(scanned) = to_scan.pop_front() {
+            for (property, value) in &self.instances[&scanned].properties {
+                let target = match value {
+                    Variant::Ref(target) if target.is_some() && !subtree.contains(target) => {
+                        *target
+                    }
+                    _ => continue,
+                };
+
+                external_refs.push(DanglingRef {
+                    referent: scanned,
+                    property: property.clone(),
+                    target,
+                });
+
+                if policy != ExternalRefPolicy::Include
+                    || included.contains(&target)
+                    || !self.instances.contains_key(&target)
+                {
+                    continue;
+                }
+
+                roots.retain(|&root| {
+                    !self
+                        .ancestors_of(root)
+                        .any(|ancestor| ancestor.referent() == target)
+                });
+                roots.push(target);
+
+                for pulled in self.descendants_of(target) {
+                    if included.insert(pulled.referent()) {
+                        to_scan.push_back(pulled.referent());
+                    }
+                }
+            }
+        }
+
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let roots = self.clone_multiple_into_external(&roots, &mut dom);
+        let root_ref = dom.root_ref();
+        for &root in &roots {
+            dom.transfer_within(root, root_ref);
+        }
+
+        Extraction {
+            dom,
+            roots,
+            external_refs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extract() {
+        let camera = InstanceBuilder::new("Camera");
+        let camera_ref = camera.referent();
+        let model = InstanceBuilder::new("Model").with_child(InstanceBuilder::new("Part"));
+        let model_ref = model.referent();
+        let frame = InstanceBuilder::new("ViewportFrame")
+            .with_ref_property("CurrentCamera", camera_ref)
+            .with_child(InstanceBuilder::new("Folder").with_ref_property("Value", model_ref));
+        let frame_ref = frame.referent();
+        let gui = InstanceBuilder::new("ScreenGui").with_child(frame);
+        let gui_ref = gui.referent();
+
+        // The camera points back at the gui that contains the frame, which
+        // pulls the whole gui in.
+        let camera = camera.with_ref_property("Focus", gui_ref);
+        let dom =
+            WeakDom::new(InstanceBuilder::new("DataModel").with_children([gui, camera, model]));
+
+        let extraction = dom.extract(frame_ref, ExternalRefPolicy::SetToNone);
+        assert_eq!(extraction.roots.len(), 1);
+        assert_eq!(extraction.external_refs.len(), 2);
+        assert_eq!(
+            extraction.dom.root().children(),
+            extraction.roots.as_slice()
+        );
+        for instance in extraction.dom.descendants() {
+            for value in instance.properties.values() {
+                assert_eq!(value, &Variant::Ref(Ref::none()));
+            }
+        }
+
+        let extraction = dom.extract(frame_ref, ExternalRefPolicy::Include);
+        assert_eq!(extraction.external_refs.len(), 3);
+        let classes: Vec<_> = extraction
+            .roots
+            .iter()
+            .map(|root| extraction.dom.get_by_ref(*root).unwrap().class.clone())
+            .collect();
+        assert_eq!(classes, ["Camera", "Model", "ScreenGui"]);
+        assert_eq!(extraction.dom.descendants().count(), 7);
+
+        for instance in extraction.dom.descendants() {
+            for value in instance.properties.values() {
+                if let Variant::Ref(target) = value {
+                    assert!(extraction.dom.get_by_ref(*target).is_some());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::io::{self, Write};
+
+use crate::{
+    instance::Instance,
+    types::{Ref, Variant},
+    Symbol, WeakDom,
+};
+
+const CLASS_COLOR: &str = "\x1b[36m";
+const SIZE_COLOR: &str = "\x1b[33m";
+const PROPERTY_COLOR: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Options for [`WeakDom::write_tree`].
+///
+/// ```
+/// use rbx_dom_weak::TreeOptions;
+///
+/// let options = TreeOptions::new()
+///     .with_property("Size")
+///     .with_byte_sizes(true)
+///     .with_max_depth(3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TreeOptions {
+    properties: Vec<Symbol>,
+    byte_sizes: bool,
+    color: bool,
+    max_depth: Option<usize>,
+}
+
+impl TreeOptions {
+    /// Creates `TreeOptions` that only list the class and name of each
+    /// instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lists the value of the property `name` on every instance that has it.
+    pub fn with_property<K: Into<Symbol>>(mut self, name: K) -> Self {
+        self.properties.push(name.into());
+        self
+    }
+
+    /// Lists the values of the properties in `names` on every instance that
+    /// has them.
+    pub fn with_properties<K, I>(mut self, names: I) -> Self
+    where
+        K: Into<Symbol>,
+        I: IntoIterator<Item = K>,
+    {
+        self.properties.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets whether to list the number of bytes each instance takes up, as
+    /// returned by [`WeakDom::get_instance_byte_size`].
+    pub fn with_byte_sizes(mut self, byte_sizes: bool) -> Self {
+        self.byte_sizes = byte_sizes;
+        self
+    }
+
+    /// Sets whether to color the output with ANSI escape codes, for writing to
+    /// a terminal.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Stops listing instances more than `max_depth` levels below the root.
+    /// Instances whose children aren't listed are marked with how many they
+    /// have.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    fn paint<'a>(&self, color: &'a str) -> (&'a str, &'static str) {
+        if self.color {
+            (color, RESET)
+        } else {
+            ("", "")
+        }
+    }
+}
+
+impl WeakDom {
+    /// Writes an indented listing of every instance in the dom to `writer`,
+    /// one instance per line, configured by `options`.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, TreeOptions, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Folder")
+    ///         .with_child(InstanceBuilder::new("Part").with_property("Anchored", true)),
+    /// );
+    ///
+    /// let mut output = Vec::new();
+    /// dom.write_tree(&mut output, &TreeOptions::new().with_property("Anchored"))?;
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(output).unwrap(),
+    ///     "Folder \"Folder\"\n└─ Part \"Part\" [Anchored = true]\n",
+    /// );
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_tree<W: Write>(&self, mut writer: W, options: &TreeOptions) -> io::Result<()> {
+        // Instances are visited depth-first with an explicit stack, so that
+        // very deep doms can't overflow the call stack. Each entry holds the
+        // prefix drawn before the instance's line, whether it's the last child
+        // of its parent (the root is neither), and its depth.
+        let mut stack: Vec<(Ref, String, Option<bool>, usize)> =
+            vec![(self.root_ref(), String::new(), None, 0)];
+
+        while let Some((referent, prefix, is_last, depth)) = stack.pop() {
+            let instance = match self.get_by_ref(referent) {
+                Some(instance) => instance,
+                None => continue,
+            };
+
+            let (branch, child_prefix) = match is_last {
+                None => ("", String::new()),
+                Some(true) => ("└─ ", format!("{}   ", prefix)),
+                Some(false) => ("├─ ", format!("{}│  ", prefix)),
+            };
+
+            write!(writer, "{}{}", prefix, branch)?;
+            self.write_tree_line(&mut writer, instance, options)?;
+
+            let children = instance.children();
+            if options.max_depth.is_some_and(|max| depth >= max) {
+                if !children.is_empty() {
+                    let (start, end) = options.paint(PROPERTY_COLOR);
+                    write!(writer, " {}(+{} children){}", start, children.len(), end)?;
+                }
+                writeln!(writer)?;
+                continue;
+            }
+            writeln!(writer)?;
+
+            for (index, child) in children.iter().enumerate().rev() {
+                let is_last = index == children.len() - 1;
+                stack.push((*child, child_prefix.clone(), Some(is_last), depth + 1));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_tree_line<W: Write>(
+        &self,
+        writer: &mut W,
+        instance: &Instance,
+        options: &TreeOptions,
+    ) -> io::Result<()> {
+        let (start, end) = options.paint(CLASS_COLOR);
+        write!(
+            writer,
+            "{}{}{} {:?}",
+            start, instance.class, end, instance.name
+        )?;
+
+        if options.byte_sizes {
+            let (start, end) = options.paint(SIZE_COLOR);
+            let bytes = self.get_instance_byte_size(instance.referent());
+            write!(writer, " {}({} bytes){}", start, bytes, end)?;
+        }
+
+        let properties: Vec<(&Symbol, &Variant)> = options
+            .properties
+            .iter()
+            .filter_map(|name| Some((name, instance.properties.get(name)?)))
+            .collect();
+
+        if !properties.is_empty() {
+            let (start, end) = options.paint(PROPERTY_COLOR);
+            write!(writer, " {}[", start)?;
+
+            for (index, (name, value)) in properties.into_iter().enumerate() {
+                if index > 0 {
+                    write!(writer, ", ")?;
+                }
+                write!(writer, "{} = ", name)?;
+                self.write_tree_value(writer, value)?;
+            }
+
+            write!(writer, "]{}", end)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_tree_value<W: Write>(&self, writer: &mut W, value: &Variant) -> io::Result<()> {
+        match value {
+            Variant::String(value) => write!(writer, "{:?}", value),
+            Variant::ContentId(value) => write!(writer, "{:?}", value.as_str()),
+            Variant::Bool(value) => write!(writer, "{}", value),
+            Variant::Int32(value) => write!(writer, "{}", value),
+            Variant::Int64(value) => write!(writer, "{}", value),
+            Variant::Float32(value) => write!(writer, "{}", value),
+            Variant::Float64(value) => write!(writer, "{}", value),
+            Variant::Enum(value) => write!(writer, "{}", value.to_u32()),
+            Variant::Ref(referent) if referent.is_none() => write!(writer, "nil"),
+            Variant::Ref(referent) if self.get_by_ref(*referent).is_some() => {
+                write!(writer, "{}", self.full_name_of(*referent))
+            }
+            other => write!(writer, "{:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{types::Vector3, InstanceBuilder};
+
+    fn tree(dom: &WeakDom, options: &TreeOptions) -> String {
+        let mut output = Vec::new();
+        dom.write_tree(&mut output, options).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn write_tree() {
+        let base = InstanceBuilder::new("Part")
+            .with_name("Base")
+            .with_property("Anchored", true)
+            .with_property("Size", Vector3::new(1.0, 2.0, 3.0));
+        let base_ref = base.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Root")
+                .with_child(
+                    InstanceBuilder::new("Model")
+                        .with_property("PrimaryPart", base_ref)
+                        .with_child(base)
+                        .with_child(InstanceBuilder::new("Part")),
+                )
+                .with_child(
+                    InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("Part")),
+                ),
+        );
+
+        let options = TreeOptions::new().with_properties(["Anchored", "PrimaryPart"]);
+        assert_eq!(
+            tree(&dom, &options),
+            concat!(
+                "Folder \"Root\"\n",
+                "├─ Model \"Model\" [PrimaryPart = Root.Model.Base]\n",
+                "│  ├─ Part \"Base\" [Anchored = true]\n",
+                "│  └─ Part \"Part\"\n",
+                "└─ Folder \"Folder\"\n",
+                "   └─ Part \"Part\"\n",
+            )
+        );
+
+        let options = TreeOptions::new().with_max_depth(1).with_byte_sizes(true);
+        assert_eq!(
+            tree(&dom, &options),
+            concat!(
+                "Folder \"Root\" (0 bytes)\n",
+                "├─ Model \"Model\" (0 bytes) (+2 children)\n",
+                "└─ Folder \"Folder\" (0 bytes) (+1 children)\n",
+            )
+        );
+
+        let options = TreeOptions::new().with_color(true).with_max_depth(0);
+        assert_eq!(
+            tree(&dom, &options),
+            "\x1b[36mFolder\x1b[0m \"Root\" \x1b[2m(+2 children)\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use ahash::{AHashMap, AHashSet};
+
+use crate::{
+    instance::Instance,
+    interner::Interner,
+    types::{Ref, UniqueId},
+    Metadata, WeakDom,
+};
+
+/// An immutable snapshot of a [`WeakDom`], returned by [`WeakDom::freeze`].
+///
+/// Instances are shared between the snapshot and the dom it was taken from
+/// until the dom changes them, so taking a snapshot doesn't copy any
+/// instances, and changing the dom afterwards only copies the instances that
+/// are changed. Cloning a `FrozenDom` is as cheap as cloning an [`Arc`], and
+/// it can be sent to and read from many threads at once.
+///
+/// ```
+/// use std::thread;
+///
+/// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+///
+/// let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+/// let snapshot = dom.freeze();
+///
+/// let reader = {
+///     let snapshot = snapshot.clone();
+///     thread::spawn(move || snapshot.descendants().count())
+/// };
+///
+/// dom.insert(dom.root_ref(), InstanceBuilder::new("Part"));
+///
+/// assert_eq!(reader.join().unwrap(), 1);
+/// assert_eq!(dom.descendants().count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrozenDom {
+    instances: Arc<AHashMap<Ref, Arc<Instance>>>,
+    root_ref: Ref,
+    unique_ids: Arc<AHashSet<UniqueId>>,
+    metadata: Arc<Metadata>,
+    interner: Arc<Interner>,
+}
+
+impl FrozenDom {
+    /// Returns the referent of the root instance of the snapshot.
+    pub fn root_ref(&self) -> Ref {
+        self.root_ref
+    }
+
+    /// Returns a reference to the root instance of the snapshot.
+    pub fn root(&self) -> &Instance {
+        self.get_by_ref(self.root_ref).unwrap()
+    }
+
+    /// Returns a reference to an instance by referent, or `None` if it is not
+    /// found.
+    pub fn get_by_ref(&self, referent: Ref) -> Option<&Instance> {
+        self.instances.get(&referent).map(|instance| &**instance)
+    }
+
+    /// Returns the metadata the dom had when the snapshot was taken.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the number of instances in the snapshot.
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// Returns whether the snapshot has no instances. A snapshot always has a
+    /// root instance, so this is always `false`.
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Returns an iterator that goes through every instance in the snapshot,
+    /// in the same order as [`WeakDom::descendants`].
+    pub fn descendants(&self) -> impl Iterator<Item = &Instance> {
+        self.descendants_of(self.root_ref)
+    }
+
+    /// Returns an iterator that goes through the descendants of a particular
+    /// [`Ref`], including the instance itself, in the same order as
+    /// [`WeakDom::descendants_of`].
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `referent` is not a member of the snapshot.
+    pub fn descendants_of(&self, referent: Ref) -> impl Iterator<Item = &Instance> {
+        if !self.instances.contains_key(&referent) {
+            panic!("the referent provided to `descendants_of` must be a part of the DOM")
+        }
+
+        let mut queue = VecDeque::from([referent]);
+        std::iter::from_fn(move || {
+            let instance = self.get_by_ref(queue.pop_front()?)?;
+            queue.extend(instance.children());
+            Some(instance)
+        })
+    }
+
+    /// Returns an iterator that goes through the ancestors of a particular
+    /// [`Ref`], starting with the instance itself.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `referent` is not a member of the snapshot.
+    pub fn ancestors_of(&self, referent: Ref) -> impl Iterator<Item = &Instance> {
+        let initial_instance = self.get_by_ref(referent);
+        if initial_instance.is_none() {
+            panic!("the referent provided to `ancestors_of` must be a part of the DOM");
+        }
+        std::iter::successors(initial_instance, move |&instance| {
+            self.get_by_ref(instance.parent())
+        })
+    }
+
+    /// Creates a new [`WeakDom`] that starts out with the same instances as
+    /// the snapshot. See [`WeakDom::fork`].
+    pub fn fork(&self) -> WeakDom {
+        WeakDom::from_shared(
+            self.root_ref,
+            (*self.instances).clone(),
+            (*self.unique_ids).clone(),
+            (*self.metadata).clone(),
+            (*self.interner).clone(),
+        )
+    }
+}
+
+impl WeakDom {
+    /// Takes an immutable snapshot of the dom that can be shared between
+    /// threads while the dom keeps changing. See [`FrozenDom`].
+    ///
+    /// Instances aren't copied, so this is much cheaper than cloning the dom.
+    pub fn freeze(&self) -> FrozenDom {
+        FrozenDom {
+            instances: Arc::new(self.instances.clone()),
+            root_ref: self.root_ref(),
+            unique_ids: Arc::new(self.unique_ids.clone()),
+            metadata: Arc::new(self.metadata().clone()),
+            interner: Arc::new(self.interner.clone()),
+        }
+    }
+
+    /// Creates a new `WeakDom` that starts out with the same instances as
+    /// this one, to make changes to without affecting it.
+    ///
+    /// The two doms share their instances until one of them changes an
+    /// instance, which copies just that instance, so any number of forks can
+    /// be made from one dom and each only pays for the instances it changes.
+    /// Referents are kept, so the same instance has the same referent in
+    /// every fork. The fork starts with no journal, history, or indices.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let base = WeakDom::new(InstanceBuilder::new("Folder").with_name("Base"));
+    ///
+    /// let mut preview = base.fork();
+    /// preview.root_mut().name = "Preview".to_owned();
+    /// preview.insert(preview.root_ref(), InstanceBuilder::new("Part"));
+    ///
+    /// assert_eq!(base.root().name, "Base");
+    /// assert!(base.root().children().is_empty());
+    /// assert_eq!(preview.root_ref(), base.root_ref());
+    /// ```
+    pub fn fork(&self) -> WeakDom {
+        WeakDom::from_shared(
+            self.root_ref(),
+            self.instances.clone(),
+            self.unique_ids.clone(),
+            self.metadata().clone(),
+            self.interner.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{types::Variant, InstanceBuilder};
+
+    #[test]
+    fn freeze() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FrozenDom>();
+
+        let part = InstanceBuilder::new("Part").with_name("Before");
+        let part_ref = part.referent();
+        let model = InstanceBuilder::new("Model").with_child(part);
+        let model_ref = model.referent();
+        let mut dom = WeakDom::new(InstanceBuilder::new("Workspace").with_child(model));
+
+        let snapshot = dom.freeze();
+
+        dom.get_by_ref_mut(part_ref).unwrap().name = "After".to_owned();
+        dom.set_property(model_ref, "Tag", Variant::String("new".to_owned()));
+        dom.insert(part_ref, InstanceBuilder::new("Decal"));
+        dom.destroy(model_ref);
+
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(snapshot.root().class, "Workspace");
+        assert_eq!(snapshot.get_by_ref(part_ref).unwrap().name, "Before");
+        assert!(snapshot.get_by_ref(part_ref).unwrap().children().is_empty());
+        assert!(snapshot
+            .get_by_ref(model_ref)
+            .unwrap()
+            .properties
+            .is_empty());
+
+        let classes: Vec<_> = snapshot
+            .descendants()
+            .map(|instance| instance.class.as_str())
+            .collect();
+        assert_eq!(classes, ["Workspace", "Model", "Part"]);
+
+        let ancestors: Vec<_> = snapshot
+            .ancestors_of(part_ref)
+            .map(|instance| instance.referent())
+            .collect();
+        assert_eq!(ancestors, [part_ref, model_ref, snapshot.root_ref()]);
+
+        assert!(dom.get_by_ref(model_ref).is_none());
+    }
+
+    #[test]
+    fn fork() {
+        let part = InstanceBuilder::new("Part").with_name("Base");
+        let part_ref = part.referent();
+        let untouched = InstanceBuilder::new("Model");
+        let untouched_ref = untouched.referent();
+        let base = WeakDom::new(InstanceBuilder::new("Workspace").with_children([part, untouched]));
+
+        let mut first = base.fork();
+        let mut second = base.freeze().fork();
+
+        first.get_by_ref_mut(part_ref).unwrap().name = "First".to_owned();
+        second.destroy(part_ref);
+        let decal_ref = second.insert(untouched_ref, InstanceBuilder::new("Decal"));
+
+        assert_eq!(base.get_by_ref(part_ref).unwrap().name, "Base");
+        assert_eq!(first.get_by_ref(part_ref).unwrap().name, "First");
+        assert!(second.get_by_ref(part_ref).is_none());
+        assert_eq!(base.root().children().len(), 2);
+        assert!(base.get_by_ref(decal_ref).is_none());
+
+        // Instances that no fork has changed are still shared with the base.
+        assert!(Arc::ptr_eq(
+            &base.instances[&base.root_ref()],
+            &first.instances[&first.root_ref()]
+        ));
+        assert!(Arc::ptr_eq(
+            &base.instances[&untouched_ref],
+            &first.instances[&untouched_ref]
+        ));
+        assert!(!Arc::ptr_eq(
+            &base.instances[&part_ref],
+            &first.instances[&part_ref]
+        ));
+    }
+
+    #[test]
+    fn transfer_keeps_sharing() {
+        let part = InstanceBuilder::new("Part");
+        let part_ref = part.referent();
+        let model = InstanceBuilder::new("Model").with_child(part);
+        let model_ref = model.referent();
+        let mut source = WeakDom::new(InstanceBuilder::new("Workspace").with_child(model));
+        let mut dest = WeakDom::new(InstanceBuilder::new("Workspace"));
+        let snapshot = source.freeze();
+
+        let dest_root = dest.root_ref();
+        source.transfer(model_ref, &mut dest, dest_root);
+
+        // The model gets a new parent, so it has to be copied, but the part
+        // inside of it moves without changing and stays shared.
+        assert!(!Arc::ptr_eq(
+            &snapshot.instances[&model_ref],
+            &dest.instances[&model_ref]
+        ));
+        assert!(Arc::ptr_eq(
+            &snapshot.instances[&part_ref],
+            &dest.instances[&part_ref]
+        ));
+        assert_eq!(dest.get_by_ref(model_ref).unwrap().parent(), dest_root);
+    }
+}
@@ -0,0 +1,134 @@
+use std::borrow::Cow;
+
+use crate::{instance::Instance, slot, symbol, types::Variant, Symbol, WeakDom};
+
+/// The names of the two fields of an [`Instance`] that Roblox treats as
+/// properties: [`Instance::name`] and [`Instance::class`].
+///
+/// These aren't stored in [`Instance::properties`] unless they're exposed
+/// there with [`WeakDom::expose_identity_properties`]. rbx_binary and rbx_xml
+/// never write them from the properties map; the fields are always used
+/// instead.
+pub const IDENTITY_PROPERTIES: [&str; 2] = ["Name", "ClassName"];
+
+/// Returns whether `name` is one of the [`IDENTITY_PROPERTIES`].
+pub fn is_identity_property(name: &str) -> bool {
+    IDENTITY_PROPERTIES.contains(&name)
+}
+
+impl Instance {
+    /// Returns every property of the instance, with its name and class first
+    /// as the `String` properties `Name` and `ClassName`, so that code that
+    /// looks at properties doesn't need to treat them specially. Copies of
+    /// them in [`Instance::properties`] are skipped.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Part").with_property("Anchored", true));
+    ///
+    /// let names: Vec<_> = dom
+    ///     .root()
+    ///     .properties_with_identity()
+    ///     .map(|(name, _)| name)
+    ///     .collect();
+    /// assert_eq!(names, ["Name", "ClassName", "Anchored"]);
+    /// ```
+    pub fn properties_with_identity(&self) -> impl Iterator<Item = (Symbol, Cow<'_, Variant>)> {
+        let identity = [
+            (symbol("Name"), Variant::String(self.name.clone())),
+            (symbol("ClassName"), Variant::String(self.class.to_string())),
+        ];
+
+        IntoIterator::into_iter(identity)
+            .map(|(name, value)| (name, Cow::Owned(value)))
+            .chain(
+                self.properties
+                    .iter()
+                    .filter(|(name, _)| !is_identity_property(name))
+                    .map(|(name, value)| (name.clone(), Cow::Borrowed(value))),
+            )
+    }
+
+    /// Updates the copies of the instance's name and class in its properties,
+    /// if they've been exposed there.
+    pub(crate) fn sync_identity_properties(&mut self) {
+        if let Some(Variant::String(name)) = self.properties.get_mut("Name") {
+            name.clone_from(&self.name);
+        }
+
+        if let Some(Variant::String(class)) = self.properties.get_mut("ClassName") {
+            *class = self.class.to_string();
+        }
+    }
+}
+
+impl WeakDom {
+    /// Copies the name and class of every instance in the dom into its
+    /// properties, as the `String` properties `Name` and `ClassName`, for
+    /// code that wants to treat every property the same way.
+    ///
+    /// [`WeakDom::set_name`] and [`WeakDom::set_class`] keep the copies up to
+    /// date, but instances inserted afterwards don't get them. Changing the
+    /// copies doesn't rename an instance or change its class.
+    pub fn expose_identity_properties(&mut self) {
+        for instance in self.instances.values_mut() {
+            let instance = slot::make_mut(instance);
+            let name = Variant::String(instance.name.clone());
+            let class = Variant::String(instance.class.to_string());
+
+            instance.properties.insert(symbol("Name"), name);
+            instance.properties.insert(symbol("ClassName"), class);
+        }
+    }
+
+    /// Removes the `Name` and `ClassName` properties added by
+    /// [`WeakDom::expose_identity_properties`] from every instance.
+    pub fn hide_identity_properties(&mut self) {
+        for instance in self.instances.values_mut() {
+            if IDENTITY_PROPERTIES
+                .iter()
+                .any(|name| instance.properties.contains_key(&symbol(name)))
+            {
+                let instance = slot::make_mut(instance);
+                for name in IDENTITY_PROPERTIES {
+                    instance.properties.remove(&symbol(name));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn identity_properties() {
+        let part = InstanceBuilder::new("Part").with_name("Door");
+        let part_ref = part.referent();
+        let mut dom = WeakDom::new(InstanceBuilder::new("Model").with_child(part));
+
+        dom.expose_identity_properties();
+        let properties = &dom.get_by_ref(part_ref).unwrap().properties;
+        assert_eq!(properties.get("Name"), Some(&Variant::from("Door")));
+        assert_eq!(properties.get("ClassName"), Some(&Variant::from("Part")));
+
+        dom.set_name(part_ref, "Window");
+        dom.set_class(part_ref, "WedgePart");
+        let part = dom.get_by_ref(part_ref).unwrap();
+        assert_eq!(part.properties.get("Name"), Some(&Variant::from("Window")));
+        assert_eq!(
+            part.properties.get("ClassName"),
+            Some(&Variant::from("WedgePart"))
+        );
+        assert_eq!(part.properties_with_identity().count(), 2);
+
+        dom.hide_identity_properties();
+        assert!(dom
+            .descendants()
+            .all(|instance| instance.properties.is_empty()));
+    }
+}
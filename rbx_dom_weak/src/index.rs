@@ -0,0 +1,481 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use ahash::{AHashMap, AHashSet};
+
+use crate::{
+    instance::Instance,
+    types::{Ref, Variant},
+    Symbol, WeakDom,
+};
+
+/// Secondary indices a [`WeakDom`] keeps up to date as instances are inserted,
+/// removed, and changed. Each one is only maintained once it's enabled.
+#[derive(Debug, Default)]
+pub(crate) struct InstanceIndex {
+    by_class: Option<AHashMap<Symbol, AHashSet<Ref>>>,
+    by_name: Option<AHashMap<String, AHashSet<Ref>>>,
+    by_ref_target: Option<AHashMap<Ref, AHashSet<(Ref, Symbol)>>>,
+}
+
+impl InstanceIndex {
+    pub(crate) fn insert(&mut self, instance: &Instance) {
+        if let Some(by_class) = &mut self.by_class {
+            by_class
+                .entry(instance.class.clone())
+                .or_default()
+                .insert(instance.referent());
+        }
+
+        if let Some(by_name) = &mut self.by_name {
+            insert_entry(by_name, &instance.name, instance.referent());
+        }
+
+        self.insert_refs(instance);
+    }
+
+    pub(crate) fn remove(&mut self, instance: &Instance) {
+        if let Some(by_class) = &mut self.by_class {
+            remove_entry(by_class, &instance.class, instance.referent());
+        }
+
+        if let Some(by_name) = &mut self.by_name {
+            remove_entry(by_name, instance.name.as_str(), instance.referent());
+        }
+
+        self.remove_refs(instance);
+    }
+
+    /// Adds every `Ref` property of `instance` to the reference index.
+    pub(crate) fn insert_refs(&mut self, instance: &Instance) {
+        if let Some(by_ref_target) = &mut self.by_ref_target {
+            for (property, target) in ref_properties(instance) {
+                by_ref_target
+                    .entry(target)
+                    .or_default()
+                    .insert((instance.referent(), property));
+            }
+        }
+    }
+
+    /// Removes every `Ref` property of `instance` from the reference index.
+    pub(crate) fn remove_refs(&mut self, instance: &Instance) {
+        if let Some(by_ref_target) = &mut self.by_ref_target {
+            for (property, target) in ref_properties(instance) {
+                remove_entry(by_ref_target, &target, (instance.referent(), property));
+            }
+        }
+    }
+
+    pub(crate) fn change_property(
+        &mut self,
+        referent: Ref,
+        property: &Symbol,
+        previous: Option<&Variant>,
+        value: Option<&Variant>,
+    ) {
+        if let Some(by_ref_target) = &mut self.by_ref_target {
+            if let Some(Variant::Ref(target)) = previous {
+                remove_entry(by_ref_target, target, (referent, property.clone()));
+            }
+
+            if let Some(Variant::Ref(target)) = value {
+                if target.is_some() {
+                    by_ref_target
+                        .entry(*target)
+                        .or_default()
+                        .insert((referent, property.clone()));
+                }
+            }
+        }
+    }
+
+    pub(crate) fn change_class(&mut self, referent: Ref, old_class: &Symbol, new_class: &Symbol) {
+        if let Some(by_class) = &mut self.by_class {
+            remove_entry(by_class, old_class, referent);
+            by_class
+                .entry(new_class.clone())
+                .or_default()
+                .insert(referent);
+        }
+    }
+
+    pub(crate) fn change_name(&mut self, referent: Ref, old_name: &str, new_name: &str) {
+        if let Some(by_name) = &mut self.by_name {
+            remove_entry(by_name, old_name, referent);
+            insert_entry(by_name, new_name, referent);
+        }
+    }
+}
+
+fn insert_entry(index: &mut AHashMap<String, AHashSet<Ref>>, name: &str, referent: Ref) {
+    match index.get_mut(name) {
+        Some(referents) => {
+            referents.insert(referent);
+        }
+        None => {
+            index.insert(name.to_owned(), std::iter::once(referent).collect());
+        }
+    }
+}
+
+/// Returns the name and target of every `Ref` property of `instance` that
+/// points at something.
+fn ref_properties(instance: &Instance) -> impl Iterator<Item = (Symbol, Ref)> + '_ {
+    instance
+        .properties
+        .iter()
+        .filter_map(|(property, value)| match value {
+            Variant::Ref(target) if target.is_some() => Some((property.clone(), *target)),
+            _ => None,
+        })
+}
+
+fn remove_entry<K, Q, V>(index: &mut AHashMap<K, AHashSet<V>>, key: &Q, value: V)
+where
+    K: Borrow<Q> + Eq + Hash,
+    Q: Eq + Hash + ?Sized,
+    V: Eq + Hash,
+{
+    if let Some(referents) = index.get_mut(key) {
+        referents.remove(&value);
+
+        if referents.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+impl WeakDom {
+    /// Starts keeping an index of every instance in the dom by class, which
+    /// makes [`WeakDom::instances_of_class`] take time proportional to the
+    /// number of instances it returns, rather than to the size of the dom.
+    ///
+    /// The index is kept up to date as instances are inserted and removed,
+    /// and as their classes are changed with [`WeakDom::set_class`]. Classes
+    /// changed directly through [`WeakDom::get_by_ref_mut`] aren't seen by the
+    /// index. If the index is already enabled, this does nothing.
+    pub fn enable_class_index(&mut self) {
+        if self.index.by_class.is_some() {
+            return;
+        }
+
+        let mut by_class: AHashMap<Symbol, AHashSet<Ref>> = AHashMap::new();
+        for (referent, instance) in &self.instances {
+            by_class
+                .entry(instance.class.clone())
+                .or_default()
+                .insert(*referent);
+        }
+
+        self.index.by_class = Some(by_class);
+    }
+
+    /// Stops keeping an index of instances by class, freeing the memory it
+    /// used.
+    pub fn disable_class_index(&mut self) {
+        self.index.by_class = None;
+    }
+
+    /// Returns whether the dom is keeping an index of instances by class.
+    pub fn has_class_index(&self) -> bool {
+        self.index.by_class.is_some()
+    }
+
+    /// Returns the referents of every instance in the dom whose class is
+    /// exactly `class`, in no particular order. Instances of subclasses aren't
+    /// included.
+    ///
+    /// If the class index is enabled with [`WeakDom::enable_class_index`], this
+    /// is a lookup; otherwise, every instance in the dom is checked.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+    ///     InstanceBuilder::new("Part"),
+    ///     InstanceBuilder::new("Part"),
+    ///     InstanceBuilder::new("Model"),
+    /// ]));
+    /// dom.enable_class_index();
+    ///
+    /// assert_eq!(dom.instances_of_class("Part").len(), 2);
+    /// ```
+    pub fn instances_of_class(&self, class: &str) -> Vec<Ref> {
+        match &self.index.by_class {
+            Some(by_class) => by_class
+                .get(class)
+                .map(|referents| referents.iter().copied().collect())
+                .unwrap_or_default(),
+            None => self
+                .instances
+                .values()
+                .filter(|instance| instance.class == class)
+                .map(|instance| instance.referent())
+                .collect(),
+        }
+    }
+
+    /// Starts keeping an index of every instance in the dom by name, which
+    /// makes [`WeakDom::instances_named`] take time proportional to the number
+    /// of instances it returns, rather than to the size of the dom.
+    ///
+    /// The index is kept up to date as instances are inserted and removed,
+    /// and as they're renamed with [`WeakDom::set_name`]. Names changed
+    /// directly through [`WeakDom::get_by_ref_mut`] aren't seen by the index.
+    /// If the index is already enabled, this does nothing.
+    pub fn enable_name_index(&mut self) {
+        if self.index.by_name.is_some() {
+            return;
+        }
+
+        let mut by_name = AHashMap::new();
+        for (referent, instance) in &self.instances {
+            insert_entry(&mut by_name, &instance.name, *referent);
+        }
+
+        self.index.by_name = Some(by_name);
+    }
+
+    /// Stops keeping an index of instances by name, freeing the memory it
+    /// used.
+    pub fn disable_name_index(&mut self) {
+        self.index.by_name = None;
+    }
+
+    /// Returns whether the dom is keeping an index of instances by name.
+    pub fn has_name_index(&self) -> bool {
+        self.index.by_name.is_some()
+    }
+
+    /// Returns the referents of every instance in the dom named exactly
+    /// `name`, in no particular order.
+    ///
+    /// If the name index is enabled with [`WeakDom::enable_name_index`], this
+    /// is a lookup; otherwise, every instance in the dom is checked.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("Model").with_children([
+    ///     InstanceBuilder::new("Part").with_name("HumanoidRootPart"),
+    ///     InstanceBuilder::new("Part").with_name("Head"),
+    /// ]));
+    /// dom.enable_name_index();
+    ///
+    /// assert_eq!(dom.instances_named("HumanoidRootPart").len(), 1);
+    /// ```
+    pub fn instances_named(&self, name: &str) -> Vec<Ref> {
+        match &self.index.by_name {
+            Some(by_name) => by_name
+                .get(name)
+                .map(|referents| referents.iter().copied().collect())
+                .unwrap_or_default(),
+            None => self
+                .instances
+                .values()
+                .filter(|instance| instance.name == name)
+                .map(|instance| instance.referent())
+                .collect(),
+        }
+    }
+
+    /// Starts keeping an index of every `Ref` property in the dom by the
+    /// instance it points at, which makes [`WeakDom::referenced_by`] take time
+    /// proportional to the number of references it returns, rather than to
+    /// the size of the dom.
+    ///
+    /// The index is kept up to date as instances are inserted and removed,
+    /// and as their properties are changed with [`WeakDom::set_property`] and
+    /// [`WeakDom::remove_property`], or by other methods of `WeakDom`.
+    /// Properties changed directly through [`WeakDom::get_by_ref_mut`] aren't
+    /// seen by the index. If the index is already enabled, this does nothing.
+    pub fn enable_ref_index(&mut self) {
+        if self.index.by_ref_target.is_some() {
+            return;
+        }
+
+        self.index.by_ref_target = Some(AHashMap::new());
+        for instance in self.instances.values() {
+            self.index.insert_refs(instance);
+        }
+    }
+
+    /// Stops keeping an index of `Ref` properties, freeing the memory it used.
+    pub fn disable_ref_index(&mut self) {
+        self.index.by_ref_target = None;
+    }
+
+    /// Returns whether the dom is keeping an index of `Ref` properties.
+    pub fn has_ref_index(&self) -> bool {
+        self.index.by_ref_target.is_some()
+    }
+
+    /// Returns the referent and property name of every `Ref` property in the
+    /// dom that points at the instance with the given referent, in no
+    /// particular order. This is everything that would be left pointing at
+    /// nothing if that instance were destroyed.
+    ///
+    /// If the reference index is enabled with [`WeakDom::enable_ref_index`],
+    /// this is a lookup; otherwise, every instance in the dom is checked.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let part = InstanceBuilder::new("Part");
+    /// let part_ref = part.referent();
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("Model").with_children([
+    ///     part,
+    ///     InstanceBuilder::new("ObjectValue").with_ref_property("Value", part_ref),
+    /// ]));
+    /// dom.enable_ref_index();
+    ///
+    /// let references = dom.referenced_by(part_ref);
+    /// assert_eq!(references.len(), 1);
+    /// assert_eq!(references[0].1, "Value");
+    /// ```
+    pub fn referenced_by(&self, target: Ref) -> Vec<(Ref, Symbol)> {
+        if target.is_none() {
+            return Vec::new();
+        }
+
+        match &self.index.by_ref_target {
+            Some(by_ref_target) => by_ref_target
+                .get(&target)
+                .map(|references| references.iter().cloned().collect())
+                .unwrap_or_default(),
+            None => self
+                .instances
+                .values()
+                .flat_map(|instance| {
+                    ref_properties(instance)
+                        .filter(|(_, value)| *value == target)
+                        .map(move |(property, _)| (instance.referent(), property))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{DanglingRefPolicy, InstanceBuilder};
+
+    fn set(referents: Vec<Ref>) -> AHashSet<Ref> {
+        referents.into_iter().collect()
+    }
+
+    #[test]
+    fn class_index() {
+        let part = InstanceBuilder::new("Part");
+        let part_ref = part.referent();
+        let model = InstanceBuilder::new("Model").with_child(InstanceBuilder::new("Part"));
+        let model_ref = model.referent();
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([part, model]));
+
+        let unindexed = set(dom.instances_of_class("Part"));
+        dom.enable_class_index();
+        assert!(dom.has_class_index());
+        assert_eq!(set(dom.instances_of_class("Part")), unindexed);
+        assert_eq!(unindexed.len(), 2);
+
+        dom.destroy(model_ref);
+        assert_eq!(dom.instances_of_class("Part"), [part_ref]);
+        assert!(dom.instances_of_class("Model").is_empty());
+
+        let wedge_ref = dom.insert(dom.root_ref(), InstanceBuilder::new("WedgePart"));
+        dom.set_class(part_ref, "WedgePart");
+        assert!(dom.instances_of_class("Part").is_empty());
+        assert_eq!(
+            set(dom.instances_of_class("WedgePart")),
+            set(vec![part_ref, wedge_ref])
+        );
+        assert!(dom.instances_of_class("NotAClassAnywhere").is_empty());
+
+        let mut other = WeakDom::new(InstanceBuilder::new("Folder"));
+        other.enable_class_index();
+        let other_root = other.root_ref();
+        dom.transfer(wedge_ref, &mut other, other_root);
+        assert_eq!(dom.instances_of_class("WedgePart"), [part_ref]);
+        assert_eq!(other.instances_of_class("WedgePart"), [wedge_ref]);
+
+        dom.disable_class_index();
+        assert!(!dom.has_class_index());
+        assert_eq!(dom.instances_of_class("WedgePart"), [part_ref]);
+    }
+
+    #[test]
+    fn name_index() {
+        let head = InstanceBuilder::new("Part").with_name("Head");
+        let head_ref = head.referent();
+        let mut dom = WeakDom::new(InstanceBuilder::new("Model").with_children([
+            head,
+            InstanceBuilder::new("Part").with_name("HumanoidRootPart"),
+        ]));
+
+        dom.enable_name_index();
+        assert!(dom.has_name_index());
+        assert_eq!(dom.instances_named("Head"), [head_ref]);
+        assert_eq!(dom.instances_named("HumanoidRootPart").len(), 1);
+
+        let previous = dom.set_name(head_ref, "HumanoidRootPart");
+        assert_eq!(previous, "Head");
+        assert!(dom.instances_named("Head").is_empty());
+        assert_eq!(dom.instances_named("HumanoidRootPart").len(), 2);
+
+        dom.destroy(head_ref);
+        assert_eq!(dom.instances_named("HumanoidRootPart").len(), 1);
+
+        dom.disable_name_index();
+        assert!(!dom.has_name_index());
+        assert_eq!(dom.instances_named("HumanoidRootPart").len(), 1);
+        assert_eq!(dom.instances_named("Model"), [dom.root_ref()]);
+    }
+
+    #[test]
+    fn ref_index() {
+        let part = InstanceBuilder::new("Part");
+        let part_ref = part.referent();
+        let value = InstanceBuilder::new("ObjectValue").with_ref_property("Value", part_ref);
+        let value_ref = value.referent();
+        let weld = InstanceBuilder::new("Weld")
+            .with_ref_property("Part0", part_ref)
+            .with_ref_property("Part1", part_ref);
+        let weld_ref = weld.referent();
+        let mut dom =
+            WeakDom::new(InstanceBuilder::new("Model").with_children([part, value, weld]));
+
+        let sorted = |mut references: Vec<(Ref, Symbol)>| {
+            references.sort_by_key(|(referent, property)| (referent.to_string(), property.clone()));
+            references
+        };
+
+        let unindexed = sorted(dom.referenced_by(part_ref));
+        dom.enable_ref_index();
+        assert!(dom.has_ref_index());
+        assert_eq!(sorted(dom.referenced_by(part_ref)), unindexed);
+        assert_eq!(unindexed.len(), 3);
+        assert!(dom.referenced_by(Ref::none()).is_empty());
+
+        dom.set_property(weld_ref, "Part1", Ref::none());
+        dom.remove_property(weld_ref, "Part0");
+        assert_eq!(dom.referenced_by(part_ref), [(value_ref, "Value".into())]);
+
+        dom.set_property(weld_ref, "Part0", value_ref);
+        assert_eq!(dom.referenced_by(value_ref), [(weld_ref, "Part0".into())]);
+
+        // References from destroyed instances go away, but references to them
+        // stay, since those are what's left broken.
+        dom.destroy(weld_ref);
+        assert!(dom.referenced_by(value_ref).is_empty());
+        dom.destroy(part_ref);
+        assert_eq!(dom.referenced_by(part_ref), [(value_ref, "Value".into())]);
+
+        dom.fix_dangling_refs(DanglingRefPolicy::SetToNone);
+        assert!(dom.referenced_by(part_ref).is_empty());
+
+        dom.disable_ref_index();
+        assert!(!dom.has_ref_index());
+    }
+}
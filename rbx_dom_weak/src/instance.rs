@@ -1,6 +1,9 @@
-use rbx_types::{Ref, Variant};
-use ustr::{Ustr, UstrMap};
+use std::{any::Any, fmt};
+
 use ahash::HashMap;
+use rbx_types::{FromVariant, Ref, Variant, VariantType};
+
+use crate::{symbol, Symbol, SymbolMap};
 
 /**
 Represents an instance that can be turned into a new
@@ -35,16 +38,18 @@ let dom = WeakDom::new(data_model);
 pub struct InstanceBuilder {
     pub(crate) referent: Ref,
     pub(crate) name: String,
-    pub(crate) class: Ustr,
-    pub(crate) properties: Vec<(Ustr, Variant)>,
+    pub(crate) class: Symbol,
+    pub(crate) properties: Vec<(Symbol, Variant)>,
     pub(crate) children: Vec<InstanceBuilder>,
     pub(crate) binary_referent: Option<i32>,
+    pub(crate) byte_size: Option<usize>,
+    pub(crate) ref_links: Vec<(Symbol, String)>,
 }
 
 impl InstanceBuilder {
     /// Create a new `InstanceBuilder` with the given ClassName. This is also
     /// used as the instance's Name, unless overwritten later.
-    pub fn new<S: Into<Ustr>>(class: S) -> Self {
+    pub fn new<S: Into<Symbol>>(class: S) -> Self {
         let class = class.into();
         let name = class.to_string();
 
@@ -55,12 +60,14 @@ impl InstanceBuilder {
             properties: Vec::new(),
             children: Vec::new(),
             binary_referent: None,
+            byte_size: None,
+            ref_links: Vec::new(),
         }
     }
 
     /// Create a new `InstanceBuilder` with the given ClassName and with a
     /// property table with at least enough space for the given capacity.
-    pub fn with_property_capacity<S: Into<Ustr>>(class: S, capacity: usize) -> Self {
+    pub fn with_property_capacity<S: Into<Symbol>>(class: S, capacity: usize) -> Self {
         let class = class.into();
         let name = class.to_string();
 
@@ -71,6 +78,8 @@ impl InstanceBuilder {
             properties: Vec::with_capacity(capacity),
             children: Vec::new(),
             binary_referent: None,
+            byte_size: None,
+            ref_links: Vec::new(),
         }
     }
 
@@ -79,10 +88,12 @@ impl InstanceBuilder {
         InstanceBuilder {
             referent: Ref::new(),
             name: String::new(),
-            class: Ustr::default(),
+            class: Symbol::default(),
             properties: Vec::new(),
             children: Vec::new(),
             binary_referent: None,
+            byte_size: None,
+            ref_links: Vec::new(),
         }
     }
 
@@ -105,6 +116,19 @@ impl InstanceBuilder {
         self
     }
 
+    /// Sets the number of bytes dedicated to the instance in the binary file
+    /// it was read from, which is returned by [`Instance::decoded_byte_size`].
+    /// This is set by binary deserializers.
+    pub fn with_byte_size(mut self, byte_size: usize) -> Self {
+        self.byte_size = Some(byte_size);
+        self
+    }
+
+    /// Return the name of the instance that the `InstanceBuilder` refers to.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Change the name of the `InstanceBuilder`.
     pub fn with_name<S: Into<String>>(self, name: S) -> Self {
         Self {
@@ -119,7 +143,7 @@ impl InstanceBuilder {
     }
 
     /// Change the class of the `InstanceBuilder`.
-    pub fn with_class<S: Into<Ustr>>(self, class: S) -> Self {
+    pub fn with_class<S: Into<Symbol>>(self, class: S) -> Self {
         Self {
             class: class.into(),
             ..self
@@ -127,31 +151,138 @@ impl InstanceBuilder {
     }
 
     /// Change the class of the `InstanceBuilder`.
-    pub fn set_class<S: Into<Ustr>>(&mut self, class: S) {
+    pub fn set_class<S: Into<Symbol>>(&mut self, class: S) {
         self.class = class.into();
     }
 
     /// Add a new property to the `InstanceBuilder`.
-    pub fn with_property<K: Into<Ustr>, V: Into<Variant>>(mut self, key: K, value: V) -> Self {
+    pub fn with_property<K: Into<Symbol>, V: Into<Variant>>(mut self, key: K, value: V) -> Self {
         self.properties.push((key.into(), value.into()));
         self
     }
 
     /// Add a new property to the `InstanceBuilder`.
-    pub fn add_property<K: Into<Ustr>, V: Into<Variant>>(&mut self, key: K, value: V) {
+    ///
+    /// If the builder already has a property with this key, both values are
+    /// kept until the builder is inserted into a [`WeakDom`][crate::WeakDom],
+    /// where the one added last is used. Use
+    /// [`InstanceBuilder::set_property`] to replace the old value instead.
+    pub fn add_property<K: Into<Symbol>, V: Into<Variant>>(&mut self, key: K, value: V) {
         self.properties.push((key.into(), value.into()));
     }
 
+    /// Set a property on the `InstanceBuilder`, replacing the value it
+    /// already has for the same key and returning it, if there was one.
+    ///
+    /// Unlike [`InstanceBuilder::add_property`], this has to look through
+    /// every property the builder already has, so it's slower for builders
+    /// with many properties.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::Variant, InstanceBuilder};
+    ///
+    /// let mut part = InstanceBuilder::new("Part").with_property("Anchored", false);
+    /// let previous = part.set_property("Anchored", true);
+    ///
+    /// assert_eq!(previous, Some(Variant::Bool(false)));
+    /// ```
+    pub fn set_property<K: Into<Symbol>, V: Into<Variant>>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> Option<Variant> {
+        let key = key.into();
+
+        // Duplicates added before with `add_property` are removed, so that the
+        // new value is the only one left.
+        let previous = self.remove_property(&key);
+        self.properties.push((key, value.into()));
+
+        previous
+    }
+
+    /// Add a new `Ref` property to the `InstanceBuilder` that points to
+    /// `target`, which can be a referent, an `InstanceBuilder`, or a path.
+    ///
+    /// Paths are resolved when the builder is inserted into a
+    /// [`WeakDom`][crate::WeakDom]. They're made of instance names separated
+    /// by `/`, starting from this instance, and can use `..` to refer to a
+    /// parent. If several siblings share a name, the first one is used. If
+    /// nothing exists at the path, the property is set to `Ref::none()`.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let model = InstanceBuilder::new("Model")
+    ///     .with_ref_property("PrimaryPart", "Base")
+    ///     .with_child(InstanceBuilder::new("Part").with_name("Base"))
+    ///     .with_child(InstanceBuilder::new("ObjectValue").with_ref_property("Value", ".."));
+    ///
+    /// let dom = WeakDom::new(model);
+    /// ```
+    pub fn with_ref_property<K: Into<Symbol>, T: Into<RefTarget>>(
+        mut self,
+        key: K,
+        target: T,
+    ) -> Self {
+        self.add_ref_property(key, target);
+        self
+    }
+
+    /// Add a new `Ref` property to the `InstanceBuilder` that points to
+    /// `target`. See [`InstanceBuilder::with_ref_property`].
+    pub fn add_ref_property<K: Into<Symbol>, T: Into<RefTarget>>(&mut self, key: K, target: T) {
+        match target.into() {
+            RefTarget::Ref(referent) => self.add_property(key, referent),
+            RefTarget::Path(path) => self.ref_links.push((key.into(), path)),
+        }
+    }
+
     /// Check if the `InstanceBuilder` already has a property with the given key.
-    pub fn has_property<K: Into<Ustr>>(&self, key: K) -> bool {
+    pub fn has_property<K: Into<Symbol>>(&self, key: K) -> bool {
         let key = key.into();
         self.properties.iter().any(|(k, _)| *k == key)
     }
 
+    /// Return the value of a property on the `InstanceBuilder`. If it was
+    /// added more than once, the value added last is returned, since that's
+    /// the one the instance will have.
+    pub fn property<K: Into<Symbol>>(&self, key: K) -> Option<&Variant> {
+        let key = key.into();
+        self.properties
+            .iter()
+            .rev()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Remove a property from the `InstanceBuilder`, returning the value the
+    /// instance would have had for it, if there was one.
+    pub fn remove_property<K: Into<Symbol>>(&mut self, key: K) -> Option<Variant> {
+        let key = key.into();
+
+        let index = self.properties.iter().rposition(|(k, _)| *k == key)?;
+        let removed = self.properties.remove(index).1;
+
+        // Values added earlier with `add_property` would have been replaced
+        // by the removed one, so they go too.
+        self.properties.retain(|(k, _)| *k != key);
+
+        Some(removed)
+    }
+
+    /// Remove every property from the `InstanceBuilder`, returning them in
+    /// the order they were added. This includes any duplicates added with
+    /// [`InstanceBuilder::add_property`], of which the last one is the value
+    /// the instance would have had.
+    pub fn take_properties(&mut self) -> Vec<(Symbol, Variant)> {
+        std::mem::take(&mut self.properties)
+    }
+
     /// Add multiple properties to the `InstanceBuilder` at once.
     pub fn with_properties<K, V, I>(mut self, props: I) -> Self
     where
-        K: Into<Ustr>,
+        K: Into<Symbol>,
         V: Into<Variant>,
         I: IntoIterator<Item = (K, V)>,
     {
@@ -164,7 +295,7 @@ impl InstanceBuilder {
     /// Add multiple properties to the `InstanceBuilder` at once.
     pub fn add_properties<K, V, I>(&mut self, props: I)
     where
-        K: Into<Ustr>,
+        K: Into<Symbol>,
         V: Into<Variant>,
         I: IntoIterator<Item = (K, V)>,
     {
@@ -205,11 +336,47 @@ impl InstanceBuilder {
     }
 }
 
+/// The instance a `Ref` property added with
+/// [`InstanceBuilder::with_ref_property`] points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefTarget {
+    /// The instance with this referent.
+    Ref(Ref),
+
+    /// The instance at this path, which is resolved when the builder is
+    /// inserted into a [`WeakDom`][crate::WeakDom].
+    Path(String),
+}
+
+impl From<Ref> for RefTarget {
+    fn from(referent: Ref) -> Self {
+        RefTarget::Ref(referent)
+    }
+}
+
+impl From<&InstanceBuilder> for RefTarget {
+    fn from(builder: &InstanceBuilder) -> Self {
+        RefTarget::Ref(builder.referent)
+    }
+}
+
+impl From<&str> for RefTarget {
+    fn from(path: &str) -> Self {
+        RefTarget::Path(path.to_owned())
+    }
+}
+
+impl From<String> for RefTarget {
+    fn from(path: String) -> Self {
+        RefTarget::Path(path)
+    }
+}
+
 /// An instance contained inside of a [`WeakDom`][crate::WeakDom].
 ///
 /// Operations that could affect other instances contained in the
 /// [`WeakDom`][crate::WeakDom] cannot be performed on an `Instance` correctly.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Instance {
     pub(crate) referent: Ref,
     pub(crate) children: Vec<Ref>,
@@ -219,14 +386,19 @@ pub struct Instance {
     pub name: String,
 
     /// The instance's class, corresponding to the `ClassName` property.
-    pub class: Ustr,
+    pub class: Symbol,
 
     /// Any properties stored on the object that are not `Name` or `ClassName`.
-    pub properties: UstrMap<Variant>,
+    pub properties: SymbolMap<Variant>,
 
     /// The binary referent from the file, used for byte size tracking.
     /// This is `None` for instances not loaded from binary files.
     pub(crate) binary_referent: Option<i32>,
+
+    /// The number of bytes dedicated to the instance in the binary file it
+    /// was read from. This is `None` for instances not loaded from binary
+    /// files.
+    pub(crate) byte_size: Option<usize>,
 }
 
 impl Instance {
@@ -252,11 +424,170 @@ impl Instance {
         self.parent
     }
 
+    /// Returns the referent this instance had in the binary file it was read
+    /// from, or `None` if it wasn't read from a binary file.
+    ///
+    /// Binary referents are only unique within the file they came from.
+    #[inline]
+    pub fn binary_referent(&self) -> Option<i32> {
+        self.binary_referent
+    }
+
+    /// Returns the value of the property `name` if the instance has it and it
+    /// holds a `T`, or `None` otherwise. `Name` returns the instance's
+    /// [`name`][Instance::name].
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::Vector3, InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Part").with_property("Size", Vector3::new(4.0, 1.0, 2.0)),
+    /// );
+    ///
+    /// assert_eq!(dom.root().get::<Vector3>("Size"), Some(&Vector3::new(4.0, 1.0, 2.0)));
+    /// assert_eq!(dom.root().get::<bool>("Size"), None);
+    /// assert_eq!(dom.root().get::<bool>("Anchored"), None);
+    /// ```
+    pub fn get<T: FromVariant + 'static>(&self, name: &str) -> Option<&T> {
+        self.try_get(name).ok().flatten()
+    }
+
+    /// Returns the value of the property `name` if the instance has it, or
+    /// `Ok(None)` if it doesn't. Unlike [`Instance::get`], a property that
+    /// holds something other than a `T` is an error.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::VariantType, InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Part").with_property("Anchored", true));
+    ///
+    /// assert_eq!(dom.root().try_get::<bool>("Anchored"), Ok(Some(&true)));
+    /// assert_eq!(dom.root().try_get::<bool>("Locked"), Ok(None));
+    ///
+    /// let error = dom.root().try_get::<f32>("Anchored").unwrap_err();
+    /// assert_eq!(error.actual, VariantType::Bool);
+    /// ```
+    pub fn try_get<T: FromVariant + 'static>(&self, name: &str) -> Result<Option<&T>, WrongType> {
+        if name == "Name" {
+            return match (&self.name as &dyn Any).downcast_ref() {
+                Some(value) => Ok(Some(value)),
+                None => Err(WrongType {
+                    property: symbol(name),
+                    expected: T::VARIANT_TYPE,
+                    actual: VariantType::String,
+                }),
+            };
+        }
+
+        let (property, value) = match self.properties.get_key_value(name) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        match T::from_variant(value) {
+            Some(value) => Ok(Some(value)),
+            None => Err(WrongType {
+                property: property.clone(),
+                expected: T::VARIANT_TYPE,
+                actual: value.ty(),
+            }),
+        }
+    }
+
+    /// Returns the value of the `String` property `name`, or `None` if the
+    /// instance doesn't have it or it isn't a `String`. `Name` returns the
+    /// instance's [`name`][Instance::name].
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.get::<String>(name).map(String::as_str)
+    }
+
+    /// Returns the value of the `Bool` property `name`, or `None` if the
+    /// instance doesn't have it or it isn't a `Bool`.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.get::<bool>(name).copied()
+    }
+
+    /// Returns the value of the `Ref` property `name`, or `None` if the
+    /// instance doesn't have it or it isn't a `Ref`. Properties set to `nil`
+    /// return `Some(Ref::none())`.
+    pub fn get_ref(&self, name: &str) -> Option<Ref> {
+        self.get::<Ref>(name).copied()
+    }
+
+    /// Returns every property on the instance, sorted by name.
+    ///
+    /// [`Instance::properties`] is a hash map, so iterating over it directly
+    /// visits properties in an order that can change between runs. This is
+    /// useful for output that needs to stay the same, like dumps and snapshot
+    /// tests.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, Symbol, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Part")
+    ///         .with_property("Transparency", 0.5f32)
+    ///         .with_property("Anchored", true),
+    /// );
+    ///
+    /// let names: Vec<Symbol> = dom
+    ///     .root()
+    ///     .sorted_properties()
+    ///     .into_iter()
+    ///     .map(|(name, _)| name)
+    ///     .collect();
+    /// assert_eq!(names, ["Anchored", "Transparency"]);
+    /// ```
+    pub fn sorted_properties(&self) -> Vec<(Symbol, &Variant)> {
+        self.sorted_properties_iter()
+            .map(|(name, value)| (name.clone(), value))
+            .collect()
+    }
+
+    /// Returns an iterator over every property on the instance, sorted by
+    /// name, that borrows the names instead of copying them.
+    ///
+    /// This is the same as [`Instance::sorted_properties`], for callers that
+    /// only walk the properties once, like formatters and diffs.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Part")
+    ///         .with_property("Transparency", 0.5f32)
+    ///         .with_property("Anchored", true),
+    /// );
+    ///
+    /// for (name, value) in dom.root().sorted_properties_iter() {
+    ///     println!("{} = {:?}", name, value);
+    /// }
+    /// ```
+    pub fn sorted_properties_iter(&self) -> impl Iterator<Item = (&Symbol, &Variant)> {
+        let mut properties: Vec<(&Symbol, &Variant)> = self.properties.iter().collect();
+        properties.sort_unstable_by_key(|(name, _)| name.as_str());
+
+        properties.into_iter()
+    }
+
+    /// Returns the number of bytes dedicated to this instance in the binary
+    /// file it was read from, or `None` if it wasn't read from a binary file.
+    ///
+    /// This includes the instance's entry in its `INST` chunk and all of its
+    /// property values, and is the same as the instance's entry in
+    /// [`WeakDom::instance_byte_sizes`][crate::WeakDom::instance_byte_sizes],
+    /// without needing the dom to look it up.
+    #[inline]
+    pub fn decoded_byte_size(&self) -> Option<usize> {
+        self.byte_size
+    }
+
     /// Returns the number of bytes dedicated to this instance in the binary file,
     /// or 0 if byte tracking is not available or this instance was not loaded from binary.
     ///
     /// This method requires the byte_sizes HashMap to look up the byte size information.
-    /// You can get this from `dom.instance_byte_sizes`.
+    /// You can get this from `dom.instance_byte_sizes`, or use
+    /// [`Instance::decoded_byte_size`] to get the size without it.
     pub fn byte_size(&self, byte_sizes: &HashMap<i32, usize>) -> usize {
         if let Some(binary_ref) = self.binary_referent {
             byte_sizes.get(&binary_ref).copied().unwrap_or(0)
@@ -265,3 +596,135 @@ impl Instance {
         }
     }
 }
+
+/// Returned by [`Instance::try_get`] when a property holds a different type
+/// than the one asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WrongType {
+    /// The name of the property.
+    pub property: Symbol,
+
+    /// The type that was asked for.
+    pub expected: VariantType,
+
+    /// The type the property holds.
+    pub actual: VariantType,
+}
+
+impl fmt::Display for WrongType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "property {} is a {:?}, not a {:?}",
+            self.property, self.actual, self.expected
+        )
+    }
+}
+
+impl std::error::Error for WrongType {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::WeakDom;
+
+    #[test]
+    fn set_property() {
+        let mut builder = InstanceBuilder::new("Part")
+            .with_property("Anchored", false)
+            .with_property("Locked", true)
+            .with_property("Anchored", true);
+
+        assert_eq!(
+            builder.set_property("Anchored", false),
+            Some(Variant::Bool(true))
+        );
+        assert_eq!(builder.set_property("CanCollide", false), None);
+        assert_eq!(builder.properties.len(), 3);
+
+        let dom = WeakDom::new(builder);
+        assert_eq!(
+            dom.root().properties.get("Anchored"),
+            Some(&Variant::Bool(false))
+        );
+    }
+
+    #[test]
+    fn remove_property() {
+        let mut builder = InstanceBuilder::new("Part")
+            .with_property("Anchored", false)
+            .with_property("Locked", true)
+            .with_property("Anchored", true);
+
+        assert_eq!(builder.property("Anchored"), Some(&Variant::Bool(true)));
+        assert_eq!(
+            builder.remove_property("Anchored"),
+            Some(Variant::Bool(true))
+        );
+        assert_eq!(builder.property("Anchored"), None);
+        assert_eq!(builder.remove_property("Anchored"), None);
+        assert_eq!(builder.properties.len(), 1);
+    }
+
+    #[test]
+    fn typed_getters() {
+        let target = Ref::new();
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Part")
+                .with_name("Door")
+                .with_property("Anchored", true)
+                .with_property("Transparency", 0.5f32)
+                .with_property("Material", "Plastic")
+                .with_property("Target", target),
+        );
+        let part = dom.root();
+
+        assert_eq!(part.get_bool("Anchored"), Some(true));
+        assert_eq!(part.get::<f32>("Transparency"), Some(&0.5));
+        assert_eq!(part.get_str("Material"), Some("Plastic"));
+        assert_eq!(part.get_str("Name"), Some("Door"));
+        assert_eq!(part.get_ref("Target"), Some(target));
+
+        assert_eq!(part.get_bool("Transparency"), None);
+        assert_eq!(part.get_bool("NeverInterned abc123"), None);
+        assert_eq!(part.try_get::<bool>("Locked"), Ok(None));
+        assert_eq!(
+            part.try_get::<bool>("Transparency"),
+            Err(WrongType {
+                property: symbol("Transparency"),
+                expected: VariantType::Bool,
+                actual: VariantType::Float32,
+            })
+        );
+        assert_eq!(
+            part.try_get::<f64>("Name").unwrap_err().actual,
+            VariantType::String
+        );
+    }
+
+    #[test]
+    fn sorted_properties() {
+        let names = ["Zeta", "Alpha", "Mu", "Beta", "Omega"];
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_properties(names.iter().map(|name| (*name, Variant::Bool(true)))),
+        );
+
+        let sorted: Vec<Symbol> = dom
+            .root()
+            .sorted_properties()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(sorted, ["Alpha", "Beta", "Mu", "Omega", "Zeta"]);
+
+        let borrowed: Vec<&str> = dom
+            .root()
+            .sorted_properties_iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(borrowed, sorted);
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    borrow::{Borrow, Cow},
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str::FromStr,
+    sync::Arc,
+};
+
+use ahash::RandomState;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::instance::Instance;
+
+/// The name of a class or property.
+///
+/// Symbols are reference counted, so cloning one is cheap, and their string
+/// is freed once the last clone of it is dropped. Each [`WeakDom`] interns
+/// the class and property names of the instances inserted into it, so that
+/// every instance in a dom shares one copy of each name, and the names go
+/// away with the dom.
+///
+/// Symbols deref to `str`, and can be compared against strings directly.
+///
+/// [`WeakDom`]: crate::WeakDom
+#[derive(Clone)]
+pub struct Symbol(Arc<str>);
+
+/// A `HashMap` with [`Symbol`] keys, which can be looked up by `&str`.
+pub type SymbolMap<V> = HashMap<Symbol, V, RandomState>;
+
+/// A `HashSet` of [`Symbol`]s, which can be looked up by `&str`.
+pub type SymbolSet = HashSet<Symbol, RandomState>;
+
+/// Creates a new [`Symbol`] from the given string.
+#[inline]
+pub fn symbol(name: &str) -> Symbol {
+    Symbol::new(name)
+}
+
+impl Symbol {
+    /// Creates a new `Symbol` from the given string.
+    #[inline]
+    pub fn new(name: &str) -> Self {
+        Self(Arc::from(name))
+    }
+
+    /// Returns this symbol's string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns whether `self` and `other` share the same string, rather than
+    /// only being equal.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Symbol) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Default for Symbol {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    #[inline]
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+// Symbols hash the same way as `str`, which lets maps with symbol keys be
+// looked up with a `&str`.
+impl Hash for Symbol {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialEq for Symbol {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || self.0 == other.0
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+macro_rules! impl_str_eq {
+    ($($ty:ty),*) => {
+        $(
+            impl PartialEq<$ty> for Symbol {
+                #[inline]
+                fn eq(&self, other: &$ty) -> bool {
+                    self.as_str() == &other[..]
+                }
+            }
+
+            impl PartialEq<Symbol> for $ty {
+                #[inline]
+                fn eq(&self, other: &Symbol) -> bool {
+                    &self[..] == other.as_str()
+                }
+            }
+        )*
+    };
+}
+
+impl_str_eq!(str, &str, String, &String, Box<str>, Cow<'_, str>);
+
+impl From<&str> for Symbol {
+    #[inline]
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<&String> for Symbol {
+    #[inline]
+    fn from(name: &String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Symbol {
+    #[inline]
+    fn from(name: String) -> Self {
+        Self(Arc::from(name))
+    }
+}
+
+impl From<Box<str>> for Symbol {
+    #[inline]
+    fn from(name: Box<str>) -> Self {
+        Self(Arc::from(name))
+    }
+}
+
+impl From<Cow<'_, str>> for Symbol {
+    #[inline]
+    fn from(name: Cow<'_, str>) -> Self {
+        Self(Arc::from(name))
+    }
+}
+
+impl From<Arc<str>> for Symbol {
+    #[inline]
+    fn from(name: Arc<str>) -> Self {
+        Self(name)
+    }
+}
+
+impl From<&Symbol> for Symbol {
+    #[inline]
+    fn from(name: &Symbol) -> Self {
+        name.clone()
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = std::convert::Infallible;
+
+    #[inline]
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(name))
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from(Cow::<str>::deserialize(deserializer)?))
+    }
+}
+
+/// The class and property names interned by a [`WeakDom`][crate::WeakDom].
+///
+/// Every name is kept until the dom is dropped or
+/// [`WeakDom::shrink_interner`][crate::WeakDom::shrink_interner] finds that
+/// no instance uses it anymore.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interner {
+    symbols: SymbolSet,
+}
+
+impl Interner {
+    /// Returns the interned copy of `name`, interning it first if this is the
+    /// first time it's been seen.
+    pub(crate) fn intern(&mut self, name: Symbol) -> Symbol {
+        match self.symbols.get(name.as_str()) {
+            Some(existing) => existing.clone(),
+            None => {
+                self.symbols.insert(name.clone());
+                name
+            }
+        }
+    }
+
+    /// Interns the class and property names of `instance`, replacing them
+    /// with their interned copies.
+    pub(crate) fn intern_instance(&mut self, instance: &mut Instance) {
+        instance.class = self.intern(std::mem::take(&mut instance.class));
+        instance.properties = std::mem::take(&mut instance.properties)
+            .into_iter()
+            .map(|(key, value)| (self.intern(key), value))
+            .collect();
+    }
+
+    /// Adds the class and property names of `instance` to the interner
+    /// without changing the instance, for instances that are shared with
+    /// other doms or that can't be changed cheaply.
+    pub(crate) fn adopt(&mut self, instance: &Instance) {
+        for name in std::iter::once(&instance.class).chain(instance.properties.keys()) {
+            if !self.symbols.contains(name) {
+                self.symbols.insert(name.clone());
+            }
+        }
+    }
+
+    /// Forgets every name that nothing but the interner is holding on to, and
+    /// returns how many were forgotten.
+    pub(crate) fn shrink(&mut self) -> usize {
+        let before = self.symbols.len();
+        self.symbols
+            .retain(|symbol| Arc::strong_count(&symbol.0) > 1);
+        self.symbols.shrink_to_fit();
+        before - self.symbols.len()
+    }
+
+    pub(crate) fn stats(&self) -> InternerStats {
+        InternerStats {
+            entries: self.symbols.len(),
+            allocated_bytes: self.symbols.iter().map(|symbol| symbol.len()).sum(),
+        }
+    }
+}
+
+/// A snapshot of how many class and property names a
+/// [`WeakDom`][crate::WeakDom] has interned, which is returned by
+/// [`WeakDom::interner_stats`][crate::WeakDom::interner_stats].
+///
+/// Names are interned per dom and freed along with it, so a long-running
+/// service only holds on to the names of the doms it still has open.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+///
+/// let dom = WeakDom::new(
+///     InstanceBuilder::new("Folder")
+///         .with_child(InstanceBuilder::new("SomeClassThatDoesNotExist"))
+///         .with_child(InstanceBuilder::new("SomeClassThatDoesNotExist")),
+/// );
+///
+/// let stats = dom.interner_stats();
+/// assert_eq!(stats.entries, 2);
+/// assert_eq!(stats.allocated_bytes, "Folder".len() + "SomeClassThatDoesNotExist".len());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InternerStats {
+    /// The number of distinct names that have been interned.
+    pub entries: usize,
+
+    /// The number of bytes used by the strings of the interned names.
+    pub allocated_bytes: usize,
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+
+use crate::{
+    symbol,
+    types::{Ref, Variant},
+    Instance, Symbol, WeakDom,
+};
+
+/// The `Ref` properties of each kind of joint, split into the ones a joint
+/// needs to do anything and the ones that may be left unset.
+const JOINTS: &[(&str, &[&str], &[&str])] = &[
+    ("Weld", &["Part0", "Part1"], &[]),
+    ("ManualWeld", &["Part0", "Part1"], &[]),
+    ("Snap", &["Part0", "Part1"], &[]),
+    ("Glue", &["Part0", "Part1"], &[]),
+    ("ManualGlue", &["Part0", "Part1"], &[]),
+    ("Motor", &["Part0", "Part1"], &[]),
+    ("Motor6D", &["Part0", "Part1"], &[]),
+    ("VelocityMotor", &["Part0", "Part1"], &[]),
+    ("Rotate", &["Part0", "Part1"], &[]),
+    ("RotateP", &["Part0", "Part1"], &[]),
+    ("RotateV", &["Part0", "Part1"], &[]),
+    ("WeldConstraint", &["Part0", "Part1"], &[]),
+    ("NoCollisionConstraint", &["Part0", "Part1"], &[]),
+    ("BallSocketConstraint", &["Attachment0", "Attachment1"], &[]),
+    (
+        "CylindricalConstraint",
+        &["Attachment0", "Attachment1"],
+        &[],
+    ),
+    ("HingeConstraint", &["Attachment0", "Attachment1"], &[]),
+    ("LineForce", &["Attachment0", "Attachment1"], &[]),
+    ("PlaneConstraint", &["Attachment0", "Attachment1"], &[]),
+    ("PrismaticConstraint", &["Attachment0", "Attachment1"], &[]),
+    ("RigidConstraint", &["Attachment0", "Attachment1"], &[]),
+    ("RodConstraint", &["Attachment0", "Attachment1"], &[]),
+    ("RopeConstraint", &["Attachment0", "Attachment1"], &[]),
+    ("SpringConstraint", &["Attachment0", "Attachment1"], &[]),
+    (
+        "TorsionSpringConstraint",
+        &["Attachment0", "Attachment1"],
+        &[],
+    ),
+    ("UniversalConstraint", &["Attachment0", "Attachment1"], &[]),
+    ("AlignOrientation", &["Attachment0"], &["Attachment1"]),
+    ("AlignPosition", &["Attachment0"], &["Attachment1"]),
+    ("AngularVelocity", &["Attachment0"], &["Attachment1"]),
+    ("LinearVelocity", &["Attachment0"], &["Attachment1"]),
+    ("Torque", &["Attachment0"], &["Attachment1"]),
+    ("VectorForce", &["Attachment0"], &["Attachment1"]),
+];
+
+/// The result of [`WeakDom::check_joints`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct JointReport {
+    /// The number of joints that were checked.
+    pub joints: usize,
+
+    /// Every joint property that was found to be broken, in the order the
+    /// joints appear in the dom.
+    pub broken: Vec<BrokenJoint>,
+}
+
+impl JointReport {
+    /// Returns whether every joint in the dom is connected to instances in
+    /// the dom.
+    pub fn is_ok(&self) -> bool {
+        self.broken.is_empty()
+    }
+}
+
+/// A property of a joint that doesn't connect it to anything, as part of a
+/// [`JointReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenJoint {
+    /// The referent of the joint.
+    pub referent: Ref,
+
+    /// The class of the joint.
+    pub class: Symbol,
+
+    /// The name of the property, like `Part0` or `Attachment1`.
+    pub property: Symbol,
+
+    /// What is wrong with the property.
+    pub problem: JointProblem,
+}
+
+/// What is wrong with a property of a joint, as part of a [`BrokenJoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JointProblem {
+    /// The property is missing or set to `nil`, but the joint needs it.
+    Unset,
+
+    /// The property points to an instance that is not part of the dom, which
+    /// usually means the joint was copied without the parts it connects.
+    OutsideDom {
+        /// The referent the property is set to.
+        target: Ref,
+    },
+}
+
+impl fmt::Display for JointProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JointProblem::Unset => write!(f, "is not set"),
+            JointProblem::OutsideDom { .. } => write!(f, "points outside of the dom"),
+        }
+    }
+}
+
+impl WeakDom {
+    /// Finds every weld, motor, and constraint in the dom whose `Part0`,
+    /// `Part1`, `Attachment0`, or `Attachment1` properties are unset or point
+    /// to instances that are not part of the dom.
+    ///
+    /// This is useful after extracting part of a place, since joints that
+    /// connect the extracted instances to the rest of the place are left
+    /// pointing at nothing. Only the properties a joint needs are required to
+    /// be set; `Attachment1` may be `nil` on constraints like `VectorForce`
+    /// that work with one attachment.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::Ref, InstanceBuilder, JointProblem, WeakDom};
+    ///
+    /// let part = InstanceBuilder::new("Part");
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Model").with_child(
+    ///         InstanceBuilder::new("Weld")
+    ///             .with_property("Part0", part.referent())
+    ///             .with_property("Part1", Ref::none()),
+    ///     ).with_child(part),
+    /// );
+    ///
+    /// let report = dom.check_joints();
+    /// assert_eq!(report.joints, 1);
+    /// assert_eq!(report.broken[0].property, "Part1");
+    /// assert_eq!(report.broken[0].problem, JointProblem::Unset);
+    /// ```
+    pub fn check_joints(&self) -> JointReport {
+        let mut joints = 0;
+        let mut broken = Vec::new();
+
+        for instance in self.descendants() {
+            let (required, optional) =
+                match JOINTS.iter().find(|(class, _, _)| instance.class == *class) {
+                    Some((_, required, optional)) => (*required, *optional),
+                    None => continue,
+                };
+
+            joints += 1;
+
+            for (property, is_required) in required
+                .iter()
+                .map(|property| (property, true))
+                .chain(optional.iter().map(|property| (property, false)))
+            {
+                let property = symbol(property);
+
+                if let Some(problem) = self.joint_problem(instance, &property, is_required) {
+                    broken.push(BrokenJoint {
+                        referent: instance.referent(),
+                        class: instance.class.clone(),
+                        property,
+                        problem,
+                    });
+                }
+            }
+        }
+
+        JointReport { joints, broken }
+    }
+
+    fn joint_problem(
+        &self,
+        instance: &Instance,
+        property: &str,
+        is_required: bool,
+    ) -> Option<JointProblem> {
+        let target = match instance.properties.get(property) {
+            Some(Variant::Ref(target)) => *target,
+            _ => Ref::none(),
+        };
+
+        if target.is_none() {
+            if is_required {
+                Some(JointProblem::Unset)
+            } else {
+                None
+            }
+        } else if self.get_by_ref(target).is_none() {
+            Some(JointProblem::OutsideDom { target })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn check_joints() {
+        let part = InstanceBuilder::new("Part");
+        let attachment = InstanceBuilder::new("Attachment");
+        let part_ref = part.referent();
+        let attachment_ref = attachment.referent();
+        let outside = Ref::new();
+
+        let fine_weld = InstanceBuilder::new("WeldConstraint")
+            .with_property("Part0", part_ref)
+            .with_property("Part1", part_ref);
+        let extracted_motor = InstanceBuilder::new("Motor6D")
+            .with_property("Part0", part_ref)
+            .with_property("Part1", outside);
+        let empty_hinge = InstanceBuilder::new("HingeConstraint");
+        let force = InstanceBuilder::new("VectorForce")
+            .with_property("Attachment0", attachment_ref)
+            .with_property("Attachment1", Ref::none());
+        let motor_ref = extracted_motor.referent();
+        let hinge_ref = empty_hinge.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Model")
+                .with_child(part.with_child(attachment))
+                .with_child(fine_weld)
+                .with_child(extracted_motor)
+                .with_child(empty_hinge)
+                .with_child(force)
+                .with_child(InstanceBuilder::new("ObjectValue").with_property("Value", outside)),
+        );
+
+        let report = dom.check_joints();
+        assert_eq!(report.joints, 4);
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.broken,
+            [
+                BrokenJoint {
+                    referent: motor_ref,
+                    class: symbol("Motor6D"),
+                    property: symbol("Part1"),
+                    problem: JointProblem::OutsideDom { target: outside },
+                },
+                BrokenJoint {
+                    referent: hinge_ref,
+                    class: symbol("HingeConstraint"),
+                    property: symbol("Attachment0"),
+                    problem: JointProblem::Unset,
+                },
+                BrokenJoint {
+                    referent: hinge_ref,
+                    class: symbol("HingeConstraint"),
+                    property: symbol("Attachment1"),
+                    problem: JointProblem::Unset,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,457 @@
+use ahash::AHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    instance::{Instance, InstanceBuilder},
+    types::{Ref, Variant},
+    Symbol, WeakDom,
+};
+
+/// An ordered log of changes made to a [`WeakDom`], recorded while journaling
+/// is turned on with [`WeakDom::start_journal`].
+///
+/// Only changes made through [`WeakDom::set_property`],
+/// [`WeakDom::remove_property`], [`WeakDom::set_name`],
+/// [`WeakDom::set_class`], [`WeakDom::transfer_within`], [`WeakDom::insert`],
+/// [`WeakDom::destroy`], and [`WeakDom::detach`] are recorded, along with
+/// methods built entirely on top of them like [`WeakDom::merge`],
+/// [`WeakDom::retain`], and [`WeakDom::fix_dangling_refs`]. Changes made
+/// any other way, like directly to an [`Instance`] through
+/// [`WeakDom::get_by_ref_mut`], aren't recorded, so a journal can only be
+/// replayed correctly if the dom is only changed through those methods.
+///
+/// Because `Journal` implements `Serialize` and `Deserialize`, it can be
+/// saved and later replayed onto a copy of the dom it was recorded from.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+///
+/// let part = InstanceBuilder::new("Part");
+/// let part_ref = part.referent();
+/// let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_child(part));
+///
+/// dom.start_journal();
+/// dom.set_property(part_ref, "Anchored", true);
+/// dom.insert(part_ref, InstanceBuilder::new("Decal"));
+///
+/// let journal = dom.stop_journal().unwrap();
+/// assert_eq!(journal.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+/// A single change recorded in a [`Journal`]. Every entry holds enough
+/// information to both apply and undo the change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JournalEntry {
+    /// A property was set or removed.
+    #[serde(rename_all = "camelCase")]
+    SetProperty {
+        /// The referent of the instance whose property changed.
+        referent: Ref,
+        /// The name of the property.
+        name: Symbol,
+        /// The value of the property before the change, or `None` if the
+        /// instance didn't have it.
+        previous: Option<Variant>,
+        /// The value of the property after the change, or `None` if it was
+        /// removed.
+        value: Option<Variant>,
+    },
+
+    /// An instance was renamed.
+    #[serde(rename_all = "camelCase")]
+    SetName {
+        /// The referent of the instance that was renamed.
+        referent: Ref,
+        /// The name of the instance before the change.
+        previous: String,
+        /// The name of the instance after the change.
+        name: String,
+    },
+
+    /// An instance's class was changed.
+    #[serde(rename_all = "camelCase")]
+    SetClass {
+        /// The referent of the instance whose class changed.
+        referent: Ref,
+        /// The class of the instance before the change.
+        previous: Symbol,
+        /// The class of the instance after the change.
+        class: Symbol,
+    },
+
+    /// An instance was moved to a new parent.
+    #[serde(rename_all = "camelCase")]
+    Reparent {
+        /// The referent of the instance that was moved.
+        referent: Ref,
+        /// The parent of the instance before it was moved.
+        previous_parent: Ref,
+        /// The position of the instance in its previous parent's children.
+        previous_index: usize,
+        /// The parent the instance was moved to.
+        parent: Ref,
+        /// The position of the instance in its new parent's children.
+        index: usize,
+    },
+
+    /// An instance and its descendants were inserted.
+    #[serde(rename_all = "camelCase")]
+    Insert {
+        /// The parent the instance was inserted into.
+        parent: Ref,
+        /// The position of the instance in its parent's children.
+        index: usize,
+        /// The inserted instances, as they were right after being inserted.
+        instance: InstanceSnapshot,
+    },
+
+    /// An instance and its descendants were destroyed.
+    #[serde(rename_all = "camelCase")]
+    Destroy {
+        /// The parent of the instance before it was destroyed.
+        parent: Ref,
+        /// The position of the instance in its parent's children.
+        index: usize,
+        /// The destroyed instances, as they were right before being destroyed.
+        instance: InstanceSnapshot,
+    },
+}
+
+/// A copy of an instance and its descendants, as recorded in a
+/// [`JournalEntry`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceSnapshot {
+    /// The referent of the instance.
+    pub referent: Ref,
+    /// The class of the instance.
+    pub class: Symbol,
+    /// The name of the instance.
+    pub name: String,
+    /// The properties of the instance, sorted by name.
+    pub properties: Vec<(Symbol, Variant)>,
+    /// Snapshots of the instance's children, in order.
+    pub children: Vec<InstanceSnapshot>,
+}
+
+impl Journal {
+    /// Creates an empty `Journal`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns every entry in the journal, oldest first.
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of entries in the journal.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the journal has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Applies every entry in the journal to `dom`, oldest first. `dom` should
+    /// be in the same state as the dom the journal was recorded from was when
+    /// recording started.
+    ///
+    /// ## Panics
+    /// Panics if an entry refers to an instance that isn't in `dom` when the
+    /// entry is applied.
+    pub fn replay(&self, dom: &mut WeakDom) {
+        for entry in &self.entries {
+            entry.apply(dom);
+        }
+    }
+
+    pub(crate) fn push(&mut self, entry: JournalEntry) {
+        self.entries.push(entry);
+    }
+}
+
+impl JournalEntry {
+    /// Makes the change described by this entry to `dom`. If `dom` is
+    /// journaling, the entry is recorded as-is.
+    ///
+    /// ## Panics
+    /// Panics if the entry refers to an instance that isn't in `dom`.
+    pub fn apply(&self, dom: &mut WeakDom) {
+        // The methods used here record entries of their own, which don't keep
+        // track of every detail this entry has, like the position an instance
+        // is moved to.
+        let journal = dom.journal.take();
+
+        match self {
+            Self::SetProperty {
+                referent,
+                name,
+                value,
+                ..
+            } => {
+                dom.replace_property(*referent, name.clone(), value.clone());
+            }
+            Self::SetName { referent, name, .. } => {
+                dom.set_name(*referent, name.clone());
+            }
+            Self::SetClass {
+                referent, class, ..
+            } => {
+                dom.set_class(*referent, class.clone());
+            }
+            Self::Reparent {
+                referent,
+                parent,
+                index,
+                ..
+            } => dom.move_instance(*referent, *parent, *index),
+            Self::Insert {
+                parent,
+                index,
+                instance,
+            } => {
+                let referent = dom.insert(*parent, instance.to_builder());
+                dom.move_instance(referent, *parent, *index);
+            }
+            Self::Destroy { instance, .. } => dom.destroy(instance.referent),
+        }
+
+        dom.journal = journal;
+        dom.record(self.clone());
+    }
+
+    /// Returns an entry that undoes the change described by this one.
+    pub fn inverse(&self) -> JournalEntry {
+        match self.clone() {
+            Self::SetProperty {
+                referent,
+                name,
+                previous,
+                value,
+            } => Self::SetProperty {
+                referent,
+                name,
+                previous: value,
+                value: previous,
+            },
+            Self::SetName {
+                referent,
+                previous,
+                name,
+            } => Self::SetName {
+                referent,
+                previous: name,
+                name: previous,
+            },
+            Self::SetClass {
+                referent,
+                previous,
+                class,
+            } => Self::SetClass {
+                referent,
+                previous: class,
+                class: previous,
+            },
+            Self::Reparent {
+                referent,
+                previous_parent,
+                previous_index,
+                parent,
+                index,
+            } => Self::Reparent {
+                referent,
+                previous_parent: parent,
+                previous_index: index,
+                parent: previous_parent,
+                index: previous_index,
+            },
+            Self::Insert {
+                parent,
+                index,
+                instance,
+            } => Self::Destroy {
+                parent,
+                index,
+                instance,
+            },
+            Self::Destroy {
+                parent,
+                index,
+                instance,
+            } => Self::Insert {
+                parent,
+                index,
+                instance,
+            },
+        }
+    }
+}
+
+impl InstanceSnapshot {
+    /// Copies the instance with the given referent and all of its descendants
+    /// out of `dom`.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in `dom`.
+    pub fn new(dom: &WeakDom, referent: Ref) -> Self {
+        if dom.get_by_ref(referent).is_none() {
+            panic!("cannot snapshot an instance that does not exist");
+        }
+
+        // Descendants are ordered such that parents come before their
+        // children, so building them in reverse ensures that every child is
+        // finished before it's added to its parent.
+        let order: Vec<&Instance> = dom.descendants_of(referent).collect();
+        let mut finished: AHashMap<Ref, InstanceSnapshot> = AHashMap::with_capacity(order.len());
+
+        for instance in order.into_iter().rev() {
+            let properties = instance
+                .sorted_properties()
+                .into_iter()
+                .map(|(name, value)| (name, value.clone()))
+                .collect();
+
+            let children = instance
+                .children()
+                .iter()
+                .map(|child| finished.remove(child).unwrap())
+                .collect();
+
+            finished.insert(
+                instance.referent(),
+                InstanceSnapshot {
+                    referent: instance.referent(),
+                    class: instance.class.clone(),
+                    name: instance.name.clone(),
+                    properties,
+                    children,
+                },
+            );
+        }
+
+        finished.remove(&referent).unwrap()
+    }
+
+    /// Creates an [`InstanceBuilder`] that inserts the instances in this
+    /// snapshot with their original referents.
+    pub fn to_builder(&self) -> InstanceBuilder {
+        InstanceBuilder::with_property_capacity(self.class.clone(), self.properties.len())
+            .with_referent(self.referent)
+            .with_name(self.name.clone())
+            .with_properties(self.properties.iter().cloned())
+            .with_children(self.children.iter().map(InstanceSnapshot::to_builder))
+    }
+}
+
+impl WeakDom {
+    /// Starts recording changes made to the dom into a [`Journal`]. If the dom
+    /// is already journaling, this does nothing.
+    pub fn start_journal(&mut self) {
+        if self.journal.is_none() {
+            self.journal = Some(Journal::new());
+        }
+    }
+
+    /// Returns the changes recorded since [`WeakDom::start_journal`] was
+    /// called, or `None` if the dom isn't journaling.
+    pub fn journal(&self) -> Option<&Journal> {
+        self.journal.as_ref()
+    }
+
+    /// Stops recording changes made to the dom, returning the changes that
+    /// were recorded, or `None` if the dom wasn't journaling.
+    pub fn stop_journal(&mut self) -> Option<Journal> {
+        self.journal.take()
+    }
+
+    pub(crate) fn is_journaling(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    pub(crate) fn record(&mut self, entry: JournalEntry) {
+        if let Some(journal) = &mut self.journal {
+            journal.push(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Builds a dom with the given referents, so that a journal recorded on
+    /// one copy can be replayed onto another.
+    fn build(root_ref: Ref, part_ref: Ref, folder_ref: Ref) -> WeakDom {
+        WeakDom::new(
+            InstanceBuilder::new("Model")
+                .with_referent(root_ref)
+                .with_child(
+                    InstanceBuilder::new("Part")
+                        .with_referent(part_ref)
+                        .with_property("Anchored", true),
+                )
+                .with_child(InstanceBuilder::new("Folder").with_referent(folder_ref)),
+        )
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let (root_ref, part_ref, folder_ref) = (Ref::new(), Ref::new(), Ref::new());
+        let mut dom = build(root_ref, part_ref, folder_ref);
+        let mut copy = build(root_ref, part_ref, folder_ref);
+
+        dom.start_journal();
+
+        let previous = dom.set_property(part_ref, "Anchored", false);
+        assert_eq!(previous, Some(Variant::Bool(true)));
+        dom.remove_property(part_ref, "Anchored");
+        let decal_ref = dom.insert(part_ref, InstanceBuilder::new("Decal"));
+        dom.transfer_within(part_ref, folder_ref);
+        dom.destroy(decal_ref);
+
+        let journal = dom.stop_journal().unwrap();
+        assert!(dom.journal().is_none());
+
+        assert_eq!(
+            journal.entries()[0],
+            JournalEntry::SetProperty {
+                referent: part_ref,
+                name: "Anchored".into(),
+                previous: Some(Variant::Bool(true)),
+                value: Some(Variant::Bool(false)),
+            }
+        );
+        assert_eq!(
+            journal.entries()[3],
+            JournalEntry::Reparent {
+                referent: part_ref,
+                previous_parent: root_ref,
+                previous_index: 0,
+                parent: folder_ref,
+                index: 0,
+            }
+        );
+        assert!(matches!(
+            &journal.entries()[4],
+            JournalEntry::Destroy { parent, index: 0, instance }
+                if *parent == part_ref && instance.class == "Decal"
+        ));
+        assert_eq!(journal.len(), 5);
+
+        journal.replay(&mut copy);
+
+        assert_eq!(copy.get_by_ref(folder_ref).unwrap().children(), [part_ref]);
+        let part = copy.get_by_ref(part_ref).unwrap();
+        assert!(part.children().is_empty());
+        assert!(part.properties.is_empty());
+        assert!(copy.get_by_ref(decal_ref).is_none());
+    }
+}
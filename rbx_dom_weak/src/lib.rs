@@ -38,25 +38,110 @@
 //! [`WeakDom::get_by_ref_mut`]. Note that it isn't possible to add or remove
 //! children through this method, use [`WeakDom::insert`] and
 //! [`WeakDom::destroy`] instead.
+//!
+//! ## Memory usage
+//! The names of classes and properties are stored as [`Symbol`] values, which
+//! are reference counted. Each `WeakDom` interns the names of the instances
+//! inserted into it, so every instance in a dom shares one copy of each name,
+//! and the names are freed once the dom and every other clone of them are
+//! dropped. Programs that read untrusted files, which can contain any number
+//! of made up class and property names, only hold on to the names of the doms
+//! they still have open. [`WeakDom::interner_stats`] reports how many names a
+//! dom has interned, and rbx_binary's `Deserializer::intern_limit` caps how
+//! many distinct names a file may contain.
+//!
+//! ## Features
+//! The `reflection` feature adds `WeakDom::set_property_unless_default`,
+//! which uses a reflection database from rbx_reflection to leave out
+//! properties that are set to their default values.
+//!
+//! The `snapshots` feature adds `WeakDom::freeze` and `WeakDom::fork`, which
+//! share instances between doms instead of copying them. To make that
+//! possible, every instance is kept behind an `Arc`, which makes looking up,
+//! changing, and destroying instances slower for every dom, so it's off by
+//! default.
 
 #![deny(missing_docs)]
 
+mod assets;
+mod attribute_schema;
+mod builder;
+#[cfg(feature = "reflection")]
+mod builder_validation;
+#[cfg(feature = "reflection")]
+mod defaults;
 mod dom;
+mod dot;
+mod extract;
+mod fmt;
+#[cfg(feature = "snapshots")]
+mod frozen;
+mod identity;
+mod index;
 mod instance;
+mod interner;
+mod joints;
+mod journal;
+mod localization;
+mod macros;
+mod matcher;
+mod metadata;
+mod rojo;
+mod roots;
+mod scripts;
+mod scrub;
+mod size_report;
+mod slice;
+mod slot;
+mod sourcemap;
+mod spatial;
+mod stable_ids;
+mod transaction;
+mod versions;
 mod viewer;
 
 pub use rbx_types as types;
 
 pub use ahash::AHashMap;
-pub use ustr::{ustr, Ustr, UstrMap, UstrSet};
+
+#[cfg(feature = "snapshots")]
+pub use crate::frozen::FrozenDom;
 
 pub use crate::{
-    dom::WeakDom,
-    instance::{Instance, InstanceBuilder},
+    assets::{asset_id, normalize_asset_url, Asset, AssetManifest, AssetReference},
+    attribute_schema::{AttributeMismatch, AttributeProblem, AttributeSchema, AttributeSchemas},
+    builder::{UnresolvedPath, UnresolvedPathError, WeakDomBuilder},
+    dom::{DanglingRef, DanglingRefError, DanglingRefPolicy, ValidationError, Violation, WeakDom},
+    dot::DotOptions,
+    extract::{ExternalRefPolicy, Extraction},
+    fmt::TreeOptions,
+    identity::{is_identity_property, IDENTITY_PROPERTIES},
+    instance::{Instance, InstanceBuilder, RefTarget, WrongType},
+    interner::{symbol, InternerStats, Symbol, SymbolMap, SymbolSet},
+    joints::{BrokenJoint, JointProblem, JointReport},
+    journal::{InstanceSnapshot, Journal, JournalEntry},
+    localization::{LocalizationEntry, LocalizationError, LocalizationTable},
+    matcher::Matcher,
+    metadata::Metadata,
+    rojo::RojoJsonError,
+    scripts::{ScriptSource, SCRIPT_CLASSES},
+    scrub::{Scrub, ScrubbedProperty},
+    size_report::{
+        AssetSizeResolver, ClassSize, ClassStats, ContentWeightReport, InstanceSize, SizeReport,
+        SubtreeWeight,
+    },
+    slice::DomSlice,
+    sourcemap::SourcemapNode,
+    spatial::{RegionQuery, PART_CLASSES},
+    stable_ids::STABLE_ID_ATTRIBUTE,
+    versions::{VersionLogError, VersionLogWriter, VersionedDom},
     viewer::{DomViewer, ViewedInstance},
 };
 
-/// Helper trait that provides convenience methods for `AHashMap` and `UstrMap`.
+#[cfg(feature = "reflection")]
+pub use crate::builder_validation::{BuilderIssue, BuilderProblem};
+
+/// Helper trait that provides convenience methods for `AHashMap` and `SymbolMap`.
 pub trait HashMapExt {
     /// Constructs an empty map.
     fn new() -> Self;
@@ -65,16 +150,16 @@ pub trait HashMapExt {
     fn with_capacity(capacity: usize) -> Self;
 }
 
-impl<V> HashMapExt for UstrMap<V> {
-    /// Creates an empty `UstrMap` using the default value for its hasher.
+impl<V> HashMapExt for SymbolMap<V> {
+    /// Creates an empty `SymbolMap` using the default value for its hasher.
     fn new() -> Self {
-        UstrMap::default()
+        SymbolMap::default()
     }
 
-    /// Creates an empty `UstrMap` with at least the specified capacity using
+    /// Creates an empty `SymbolMap` with at least the specified capacity using
     /// the default value for its hasher.
     fn with_capacity(capacity: usize) -> Self {
-        UstrMap::with_capacity_and_hasher(capacity, Default::default())
+        SymbolMap::with_capacity_and_hasher(capacity, Default::default())
     }
 }
 
@@ -0,0 +1,348 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    types::{Ref, Variant},
+    WeakDom,
+};
+
+/// One row of a [`LocalizationTable`]: a piece of text and its translations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct LocalizationEntry {
+    /// The key that scripts look the entry up with, which may be empty.
+    #[serde(default)]
+    pub key: String,
+
+    /// Where in the game the text appears, which tells apart entries with the
+    /// same source text.
+    #[serde(default)]
+    pub context: String,
+
+    /// Example text for translators.
+    #[serde(default)]
+    pub examples: String,
+
+    /// The text in the game's source language.
+    #[serde(default)]
+    pub source: String,
+
+    /// The translations of the text, keyed by locale ID like `es` or `pt-br`.
+    #[serde(default)]
+    pub values: BTreeMap<String, String>,
+
+    /// Fields this crate doesn't know about, which are kept so that
+    /// rewriting a table doesn't lose them.
+    #[serde(flatten)]
+    other: BTreeMap<String, serde_json::Value>,
+}
+
+impl LocalizationEntry {
+    /// Creates an entry with the given key and source text and no
+    /// translations.
+    pub fn new<K: Into<String>, S: Into<String>>(key: K, source: S) -> Self {
+        Self {
+            key: key.into(),
+            source: source.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the context of the entry.
+    pub fn with_context<S: Into<String>>(mut self, context: S) -> Self {
+        self.context = context.into();
+        self
+    }
+
+    /// Adds a translation of the entry into `locale`.
+    pub fn with_value<L: Into<String>, S: Into<String>>(mut self, locale: L, text: S) -> Self {
+        self.values.insert(locale.into(), text.into());
+        self
+    }
+
+    /// Returns whether `other` is the same entry as this one: entries with
+    /// keys are the same if their keys are, and entries without are the same
+    /// if their source text and context are, like in Roblox.
+    fn same_entry(&self, other: &LocalizationEntry) -> bool {
+        if self.key.is_empty() && other.key.is_empty() {
+            self.source == other.source && self.context == other.context
+        } else {
+            self.key == other.key
+        }
+    }
+}
+
+/// The contents of a `LocalizationTable`, which Roblox stores as JSON in its
+/// `Contents` property.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, LocalizationEntry, LocalizationTable, WeakDom};
+///
+/// let mut dom = WeakDom::new(InstanceBuilder::new("LocalizationTable"));
+/// let table_ref = dom.root_ref();
+///
+/// let mut table = dom.localization_table(table_ref)?;
+/// table.add(LocalizationEntry::new("Greeting", "Hello").with_value("es", "Hola"));
+/// dom.set_localization_table(table_ref, &table);
+///
+/// let table = dom.localization_table(table_ref)?;
+/// assert_eq!(table.lookup("Greeting", "es"), Some("Hola"));
+/// # Ok::<(), rbx_dom_weak::LocalizationError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LocalizationTable {
+    entries: Vec<LocalizationEntry>,
+}
+
+impl LocalizationTable {
+    /// Creates an empty `LocalizationTable`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a table from the JSON stored in a `LocalizationTable`'s
+    /// `Contents` property. An empty string is read as an empty table.
+    pub fn from_json(json: &str) -> Result<Self, LocalizationError> {
+        if json.trim().is_empty() {
+            return Ok(Self::new());
+        }
+
+        let entries = serde_json::from_str(json).map_err(LocalizationError::InvalidJson)?;
+        Ok(Self { entries })
+    }
+
+    /// Writes the table as JSON, in the form Roblox stores it in a
+    /// `LocalizationTable`'s `Contents` property.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.entries).unwrap()
+    }
+
+    /// Returns every entry in the table, in order.
+    pub fn entries(&self) -> &[LocalizationEntry] {
+        &self.entries
+    }
+
+    /// Returns the entry with the given key, if there is one.
+    pub fn get(&self, key: &str) -> Option<&LocalizationEntry> {
+        self.entries.iter().find(|entry| entry.key == key)
+    }
+
+    /// Returns the translation of the entry with the given key into `locale`,
+    /// if there is one.
+    pub fn lookup(&self, key: &str, locale: &str) -> Option<&str> {
+        self.get(key)?.values.get(locale).map(String::as_str)
+    }
+
+    /// Returns the translation of `source` into `locale` from the first entry
+    /// with that source text, for entries that scripts don't look up by key.
+    pub fn lookup_source(&self, source: &str, locale: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.source == source)
+            .find_map(|entry| entry.values.get(locale))
+            .map(String::as_str)
+    }
+
+    /// Adds `entry` to the end of the table, returning the entry it replaced
+    /// if the table already had the same entry. Entries with keys are the
+    /// same if their keys are, and entries without keys are the same if
+    /// their source text and context are.
+    pub fn add(&mut self, entry: LocalizationEntry) -> Option<LocalizationEntry> {
+        match self
+            .entries
+            .iter_mut()
+            .find(|existing| existing.same_entry(&entry))
+        {
+            Some(existing) => Some(std::mem::replace(existing, entry)),
+            None => {
+                self.entries.push(entry);
+                None
+            }
+        }
+    }
+
+    /// Removes the entry with the given key, returning it if there was one.
+    pub fn remove(&mut self, key: &str) -> Option<LocalizationEntry> {
+        let index = self.entries.iter().position(|entry| entry.key == key)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Merges the entries of `other` into the table. Translations from
+    /// `other` are added to matching entries, replacing translations into
+    /// the same locale, and entries that aren't in the table yet are added to
+    /// the end of it.
+    pub fn merge(&mut self, other: LocalizationTable) {
+        for entry in other.entries {
+            match self
+                .entries
+                .iter_mut()
+                .find(|existing| existing.same_entry(&entry))
+            {
+                Some(existing) => {
+                    existing.values.extend(entry.values);
+                    existing.other.extend(entry.other);
+
+                    for (field, value) in [
+                        (&mut existing.context, entry.context),
+                        (&mut existing.examples, entry.examples),
+                        (&mut existing.source, entry.source),
+                    ] {
+                        if !value.is_empty() {
+                            *field = value;
+                        }
+                    }
+                }
+                None => self.entries.push(entry),
+            }
+        }
+    }
+}
+
+impl WeakDom {
+    /// Reads the `Contents` of the `LocalizationTable` with the given
+    /// referent. An instance without `Contents` is read as an empty table.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn localization_table(
+        &self,
+        referent: Ref,
+    ) -> Result<LocalizationTable, LocalizationError> {
+        let instance = self.get_by_ref(referent).unwrap_or_else(|| {
+            panic!("cannot read the contents of an instance that does not exist")
+        });
+
+        match instance.properties.get("Contents") {
+            Some(Variant::String(json)) => LocalizationTable::from_json(json),
+            Some(Variant::BinaryString(json)) => match std::str::from_utf8(json.as_ref()) {
+                Ok(json) => LocalizationTable::from_json(json),
+                Err(_) => Err(LocalizationError::NotUtf8),
+            },
+            Some(_) => Err(LocalizationError::NotAString),
+            None => Ok(LocalizationTable::new()),
+        }
+    }
+
+    /// Writes `table` to the `Contents` of the `LocalizationTable` with the
+    /// given referent.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn set_localization_table(&mut self, referent: Ref, table: &LocalizationTable) {
+        self.set_property(referent, "Contents", Variant::String(table.to_json()));
+    }
+}
+
+/// Returned by [`WeakDom::localization_table`] and
+/// [`LocalizationTable::from_json`] when the contents of a table can't be
+/// read.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LocalizationError {
+    /// The `Contents` property isn't a string.
+    NotAString,
+
+    /// The `Contents` property isn't valid UTF-8.
+    NotUtf8,
+
+    /// The contents aren't a valid localization table.
+    InvalidJson(serde_json::Error),
+}
+
+impl fmt::Display for LocalizationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocalizationError::NotAString => write!(f, "Contents is not a string"),
+            LocalizationError::NotUtf8 => write!(f, "Contents is not valid UTF-8"),
+            LocalizationError::InvalidJson(error) => {
+                write!(f, "Contents is not a valid localization table: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LocalizationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LocalizationError::InvalidJson(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{types::BinaryString, InstanceBuilder};
+
+    #[test]
+    fn localization_table() {
+        let contents = r#"[
+            {"key":"Greeting","context":"","examples":"","source":"Hello","values":{"es":"Hola"}},
+            {"key":"","context":"Shop","examples":"","source":"Buy","values":{"fr":"Acheter"},"extra":1}
+        ]"#;
+        let dom = WeakDom::new(
+            InstanceBuilder::new("LocalizationTable")
+                .with_property("Contents", BinaryString::from(contents.as_bytes().to_vec())),
+        );
+        let mut table = dom.localization_table(dom.root_ref()).unwrap();
+
+        assert_eq!(table.entries().len(), 2);
+        assert_eq!(table.lookup("Greeting", "es"), Some("Hola"));
+        assert_eq!(table.lookup("Greeting", "fr"), None);
+        assert_eq!(table.lookup_source("Buy", "fr"), Some("Acheter"));
+
+        let mut update = LocalizationTable::new();
+        update.add(
+            LocalizationEntry::new("Greeting", "")
+                .with_value("es", "¡Hola!")
+                .with_value("de", "Hallo"),
+        );
+        update.add(
+            LocalizationEntry::new("", "Buy")
+                .with_context("Shop")
+                .with_value("de", "Kaufen"),
+        );
+        update.add(LocalizationEntry::new("Farewell", "Goodbye"));
+        table.merge(update);
+
+        assert_eq!(table.entries().len(), 3);
+        assert_eq!(table.lookup("Greeting", "es"), Some("¡Hola!"));
+        assert_eq!(table.lookup("Greeting", "de"), Some("Hallo"));
+        assert_eq!(table.get("Greeting").unwrap().source, "Hello");
+        assert_eq!(table.lookup_source("Buy", "de"), Some("Kaufen"));
+        assert_eq!(table.lookup_source("Buy", "fr"), Some("Acheter"));
+
+        // Entries without keys only match if their context does too.
+        let replaced = table.add(LocalizationEntry::new("", "Buy").with_context("Menu"));
+        assert_eq!(replaced, None);
+        assert_eq!(table.entries().len(), 4);
+
+        // Unknown fields survive being written back out.
+        let reread = LocalizationTable::from_json(&table.to_json()).unwrap();
+        assert_eq!(reread, table);
+        assert!(table.to_json().contains(r#""extra":1"#));
+
+        assert_eq!(table.remove("Farewell").unwrap().source, "Goodbye");
+        assert!(table.get("Farewell").is_none());
+    }
+
+    #[test]
+    fn localization_table_errors() {
+        assert_eq!(LocalizationTable::from_json("").unwrap().entries().len(), 0);
+        assert!(matches!(
+            LocalizationTable::from_json("{}"),
+            Err(LocalizationError::InvalidJson(_))
+        ));
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("LocalizationTable").with_property("Contents", 1.0f32),
+        );
+        assert!(matches!(
+            dom.localization_table(dom.root_ref()),
+            Err(LocalizationError::NotAString)
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+/// Builds a tree of [`InstanceBuilder`][crate::InstanceBuilder]s from a
+/// description of each instance's class, properties, and children.
+///
+/// Each instance is written as its class, optionally followed by `as` and its
+/// name, followed by braces holding its properties and children. Properties
+/// are written as `Name: value`, where `value` is anything that converts into
+/// a [`Variant`][crate::types::Variant], and are separated by commas.
+/// Properties whose names aren't identifiers can be written as strings.
+///
+/// ```
+/// use rbx_dom_weak::{instance_tree, types::Vector3, WeakDom};
+///
+/// let builder = instance_tree! {
+///     "DataModel" {
+///         "Workspace" {
+///             "Part" as "Baseplate" {
+///                 Anchored: true,
+///                 Size: Vector3::new(512.0, 20.0, 512.0),
+///             }
+///             "Model" {
+///                 "Made Up": 1.0f32,
+///                 "Part" {}
+///             }
+///         }
+///     }
+/// };
+///
+/// let dom = WeakDom::new(builder);
+/// let workspace = dom.get_by_ref(dom.root().children()[0]).unwrap();
+/// let baseplate = dom.get_by_ref(workspace.children()[0]).unwrap();
+/// assert_eq!(baseplate.name, "Baseplate");
+/// assert_eq!(baseplate.properties["Anchored"], true.into());
+/// ```
+#[macro_export]
+macro_rules! instance_tree {
+    ($class:literal $(as $name:literal)? { $($body:tt)* }) => {
+        $crate::instance_tree!(
+            @body $crate::InstanceBuilder::new($class)$(.with_name($name))?; $($body)*
+        )
+    };
+
+    ($class:literal $(as $name:literal)?) => {
+        $crate::InstanceBuilder::new($class)$(.with_name($name))?
+    };
+
+    (@body $builder:expr;) => {
+        $builder
+    };
+
+    (@body $builder:expr; $key:ident : $value:expr, $($rest:tt)*) => {
+        $crate::instance_tree!(@body $builder.with_property(stringify!($key), $value); $($rest)*)
+    };
+
+    (@body $builder:expr; $key:ident : $value:expr) => {
+        $builder.with_property(stringify!($key), $value)
+    };
+
+    (@body $builder:expr; $key:literal : $value:expr, $($rest:tt)*) => {
+        $crate::instance_tree!(@body $builder.with_property($key, $value); $($rest)*)
+    };
+
+    (@body $builder:expr; $key:literal : $value:expr) => {
+        $builder.with_property($key, $value)
+    };
+
+    (@body $builder:expr; $class:literal $(as $name:literal)? { $($child:tt)* }, $($rest:tt)*) => {
+        $crate::instance_tree!(
+            @body $builder.with_child($crate::instance_tree!($class $(as $name)? { $($child)* }));
+            $($rest)*
+        )
+    };
+
+    (@body $builder:expr; $class:literal $(as $name:literal)? { $($child:tt)* } $($rest:tt)*) => {
+        $crate::instance_tree!(
+            @body $builder.with_child($crate::instance_tree!($class $(as $name)? { $($child)* }));
+            $($rest)*
+        )
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        types::{Variant, Vector3},
+        InstanceBuilder, WeakDom,
+    };
+
+    #[test]
+    fn instance_tree() {
+        let size = Vector3::new(4.0, 1.0, 2.0);
+        let from_macro = WeakDom::new(instance_tree! {
+            "Folder" as "Map" {
+                "Part" { Anchored: true, Size: size },
+                "Model" as "Tree" {
+                    "Made Up": "value",
+                    "Part" as "Trunk" { CanCollide: false, }
+                    "MeshPart" {}
+                }
+                "Configuration" {}
+            }
+        });
+
+        let from_builders = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Map")
+                .with_child(
+                    InstanceBuilder::new("Part")
+                        .with_property("Anchored", true)
+                        .with_property("Size", size),
+                )
+                .with_child(
+                    InstanceBuilder::new("Model")
+                        .with_name("Tree")
+                        .with_property("Made Up", "value")
+                        .with_child(
+                            InstanceBuilder::new("Part")
+                                .with_name("Trunk")
+                                .with_property("CanCollide", false),
+                        )
+                        .with_child(InstanceBuilder::new("MeshPart")),
+                )
+                .with_child(InstanceBuilder::new("Configuration")),
+        );
+
+        let describe = |dom: &WeakDom| {
+            dom.descendants()
+                .map(|instance| {
+                    let mut properties: Vec<(String, Variant)> = instance
+                        .properties
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), value.clone()))
+                        .collect();
+                    properties.sort_by(|a, b| a.0.cmp(&b.0));
+
+                    (
+                        instance.class.clone(),
+                        instance.name.clone(),
+                        properties,
+                        instance.children().len(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(describe(&from_macro), describe(&from_builders));
+        assert_eq!(
+            describe(&WeakDom::new(instance_tree!("Folder"))),
+            describe(&WeakDom::new(InstanceBuilder::new("Folder")))
+        );
+    }
+}
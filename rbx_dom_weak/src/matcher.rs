@@ -0,0 +1,347 @@
+use std::{fmt, sync::Arc};
+
+use ahash::AHashSet;
+
+use crate::{
+    instance::Instance,
+    types::{Ref, Variant},
+    Symbol, SymbolSet, WeakDom,
+};
+
+type Predicate = Arc<dyn Fn(&Variant) -> bool + Send + Sync>;
+
+/// A set of criteria for finding instances, used with [`WeakDom::find_all`].
+///
+/// An instance matches if its class is one of the classes given to
+/// [`Matcher::with_class`], its name matches one of the patterns given to
+/// [`Matcher::with_name`], and every property check passes. Criteria that
+/// were never given match every instance.
+///
+/// Name patterns are parsed when they're added, so a `Matcher` can be built
+/// once and used on any number of doms. Matchers are cheap to clone and can be
+/// shared between threads.
+///
+/// ```
+/// use rbx_dom_weak::{types::Variant, Matcher};
+///
+/// let unanchored_doors = Matcher::new()
+///     .with_classes(["Part", "MeshPart"])
+///     .with_name("Door*")
+///     .with_property("Anchored", |value| *value == Variant::Bool(false));
+/// ```
+#[derive(Clone, Default)]
+pub struct Matcher {
+    classes: SymbolSet,
+    exact_names: AHashSet<String>,
+    name_patterns: Vec<NamePattern>,
+    properties: Vec<(Symbol, Predicate)>,
+}
+
+impl Matcher {
+    /// Creates a `Matcher` that matches every instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches instances whose class is exactly `class`, in addition to any
+    /// other classes that were already given. Subclasses aren't matched.
+    pub fn with_class<S: Into<Symbol>>(mut self, class: S) -> Self {
+        self.classes.insert(class.into());
+        self
+    }
+
+    /// Matches instances whose class is exactly one of `classes`, in addition
+    /// to any other classes that were already given.
+    pub fn with_classes<S, I>(mut self, classes: I) -> Self
+    where
+        S: Into<Symbol>,
+        I: IntoIterator<Item = S>,
+    {
+        self.classes.extend(classes.into_iter().map(Into::into));
+        self
+    }
+
+    /// Matches instances whose name matches `pattern`, in addition to any
+    /// other patterns that were already given. In a pattern, `*` matches any
+    /// number of characters and `?` matches exactly one; every other
+    /// character matches itself.
+    pub fn with_name(mut self, pattern: &str) -> Self {
+        if pattern.contains(['*', '?']) {
+            self.name_patterns.push(NamePattern::new(pattern));
+        } else {
+            self.exact_names.insert(pattern.to_owned());
+        }
+        self
+    }
+
+    /// Only matches instances that have the property `name` and whose value
+    /// for it passes `predicate`.
+    pub fn with_property<K, F>(mut self, name: K, predicate: F) -> Self
+    where
+        K: Into<Symbol>,
+        F: Fn(&Variant) -> bool + Send + Sync + 'static,
+    {
+        self.properties.push((name.into(), Arc::new(predicate)));
+        self
+    }
+
+    /// Only matches instances whose property `name` is equal to `value`.
+    pub fn with_property_value<K, V>(self, name: K, value: V) -> Self
+    where
+        K: Into<Symbol>,
+        V: Into<Variant>,
+    {
+        let value = value.into();
+        self.with_property(name, move |other| *other == value)
+    }
+
+    /// Returns whether `instance` meets every criterion of this matcher.
+    pub fn matches(&self, instance: &Instance) -> bool {
+        if !self.classes.is_empty() && !self.classes.contains(&instance.class) {
+            return false;
+        }
+
+        if !self.matches_name(&instance.name) {
+            return false;
+        }
+
+        self.properties
+            .iter()
+            .all(|(name, predicate)| match instance.properties.get(name) {
+                Some(value) => predicate(value),
+                None => false,
+            })
+    }
+
+    fn matches_name(&self, name: &str) -> bool {
+        if self.exact_names.is_empty() && self.name_patterns.is_empty() {
+            return true;
+        }
+
+        self.exact_names.contains(name)
+            || self
+                .name_patterns
+                .iter()
+                .any(|pattern| pattern.matches(name))
+    }
+}
+
+impl fmt::Debug for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Matcher")
+            .field("classes", &self.classes)
+            .field("exact_names", &self.exact_names)
+            .field("name_patterns", &self.name_patterns)
+            .field(
+                "properties",
+                &self
+                    .properties
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// A name pattern containing wildcards, split on every `*`. The pattern
+/// matches if the first segment is at the start of the name, the last is at
+/// the end, and the rest appear in order in between.
+#[derive(Debug, Clone)]
+struct NamePattern {
+    segments: Vec<Vec<char>>,
+}
+
+impl NamePattern {
+    fn new(pattern: &str) -> Self {
+        Self {
+            segments: pattern
+                .split('*')
+                .map(|segment| segment.chars().collect())
+                .collect(),
+        }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        let (first, rest) = self.segments.split_first().unwrap();
+
+        let mut remaining = match match_segment(first, name) {
+            Some(len) => &name[len..],
+            None => return false,
+        };
+
+        let (last, middle) = match rest.split_last() {
+            Some(split) => split,
+            // There was no `*`, so the whole name has to match.
+            None => return remaining.is_empty(),
+        };
+
+        for segment in middle {
+            match find_segment(segment, remaining) {
+                Some(end) => remaining = &remaining[end..],
+                None => return false,
+            }
+        }
+
+        // The last segment has to be at the very end, so it's checked against
+        // the end of the name rather than searched for.
+        let last_len = last.len();
+        let char_count = remaining.chars().count();
+        if char_count < last_len {
+            return false;
+        }
+
+        let start = remaining
+            .char_indices()
+            .nth(char_count - last_len)
+            .map_or(remaining.len(), |(index, _)| index);
+
+        match_segment(last, &remaining[start..]) == Some(remaining.len() - start)
+    }
+}
+
+/// If `text` starts with `segment`, returns the number of bytes of `text`
+/// that it covers.
+fn match_segment(segment: &[char], text: &str) -> Option<usize> {
+    let mut chars = text.char_indices();
+
+    for expected in segment {
+        let (_, actual) = chars.next()?;
+
+        if *expected != '?' && *expected != actual {
+            return None;
+        }
+    }
+
+    Some(chars.next().map_or(text.len(), |(index, _)| index))
+}
+
+/// Finds the first place `segment` appears in `text`, returning the byte
+/// index just past it.
+fn find_segment(segment: &[char], text: &str) -> Option<usize> {
+    text.char_indices()
+        .map(|(index, _)| index)
+        .chain(std::iter::once(text.len()))
+        .find_map(|start| Some(start + match_segment(segment, &text[start..])?))
+}
+
+impl WeakDom {
+    /// Returns the referents of every instance in the dom that `matcher`
+    /// matches, in no particular order.
+    ///
+    /// If the matcher only matches certain classes and the class index is
+    /// enabled with [`WeakDom::enable_class_index`], only instances of those
+    /// classes are checked.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, Matcher, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Model").with_children([
+    ///     InstanceBuilder::new("Part").with_name("LeftDoor"),
+    ///     InstanceBuilder::new("Part").with_name("RightDoor"),
+    ///     InstanceBuilder::new("Part").with_name("Floor"),
+    /// ]));
+    ///
+    /// let matcher = Matcher::new().with_class("Part").with_name("*Door");
+    /// assert_eq!(dom.find_all(&matcher).len(), 2);
+    /// ```
+    pub fn find_all(&self, matcher: &Matcher) -> Vec<Ref> {
+        if !matcher.classes.is_empty() && self.has_class_index() {
+            return matcher
+                .classes
+                .iter()
+                .flat_map(|class| self.instances_of_class(class))
+                .filter(|referent| matcher.matches(self.get_by_ref(*referent).unwrap()))
+                .collect();
+        }
+
+        self.instances
+            .values()
+            .filter(|instance| matcher.matches(instance))
+            .map(|instance| instance.referent())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn name_patterns() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("Door", "Door", true),
+            ("Door", "Doors", false),
+            ("Door*", "Door", true),
+            ("Door*", "DoorFrame", true),
+            ("Door*", "TrapDoor", false),
+            ("*Door", "TrapDoor", true),
+            ("*Door", "DoorFrame", false),
+            ("*", "", true),
+            ("?oor", "Door", true),
+            ("?oor", "oor", false),
+            ("D*r", "Dr", true),
+            ("D*r", "Door", true),
+            ("D*o*r", "Dr", false),
+            ("a*b*c", "aXbYbZc", true),
+            ("*ab", "aab", true),
+            ("é?*", "éa", true),
+            ("*ö", "Hö", true),
+        ];
+
+        for (pattern, name, expected) in cases {
+            assert_eq!(
+                NamePattern::new(pattern).matches(name),
+                *expected,
+                "{:?} matching {:?}",
+                pattern,
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn find_all() {
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Model").with_children([
+                InstanceBuilder::new("Part")
+                    .with_name("LeftDoor")
+                    .with_property("Anchored", false),
+                InstanceBuilder::new("MeshPart")
+                    .with_name("RightDoor")
+                    .with_property("Anchored", true),
+                InstanceBuilder::new("Part").with_name("Floor"),
+                InstanceBuilder::new("Folder").with_name("Doors"),
+            ]),
+        );
+
+        let named = |dom: &WeakDom, matcher: &Matcher| {
+            let mut names: Vec<String> = dom
+                .find_all(matcher)
+                .into_iter()
+                .map(|referent| dom.get_by_ref(referent).unwrap().name.clone())
+                .collect();
+            names.sort();
+            names
+        };
+
+        let doors = Matcher::new()
+            .with_classes(["Part", "MeshPart"])
+            .with_name("*Door");
+        let unanchored = doors.clone().with_property_value("Anchored", false);
+
+        assert_eq!(named(&dom, &doors), ["LeftDoor", "RightDoor"]);
+        assert_eq!(named(&dom, &unanchored), ["LeftDoor"]);
+        assert_eq!(
+            named(&dom, &Matcher::new().with_name("Floor").with_name("Door?")),
+            ["Doors", "Floor"]
+        );
+        assert_eq!(named(&dom, &Matcher::new()).len(), 5);
+
+        dom.enable_class_index();
+        assert_eq!(named(&dom, &doors), ["LeftDoor", "RightDoor"]);
+        assert_eq!(named(&dom, &unanchored), ["LeftDoor"]);
+    }
+}
@@ -0,0 +1,101 @@
+use std::{collections::BTreeMap, iter::FromIterator};
+
+/// Key-value pairs describing a place or model file as a whole, rather than
+/// any of the instances in it. In binary files these are stored in the `META`
+/// chunk, and in XML files they are stored as `Meta` elements.
+///
+/// Roblox uses metadata to change how some constructs in the file are
+/// interpreted, so it should be preserved when a file is read and written
+/// back out.
+///
+/// ```
+/// use rbx_dom_weak::Metadata;
+///
+/// let mut metadata = Metadata::new();
+/// metadata.set_explicit_auto_joints(true);
+///
+/// assert_eq!(metadata.get("ExplicitAutoJoints"), Some("true"));
+/// assert_eq!(metadata.explicit_auto_joints(), Some(true));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    entries: BTreeMap<String, String>,
+}
+
+impl Metadata {
+    /// Constructs an empty `Metadata`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value of the entry called `key`, if there is one.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// Sets the value of the entry called `key`, returning its old value if it
+    /// had one.
+    pub fn insert<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) -> Option<String> {
+        self.entries.insert(key.into(), value.into())
+    }
+
+    /// Removes the entry called `key`, returning its value if it had one.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.entries.remove(key)
+    }
+
+    /// Returns an iterator over every entry, sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the value of the `ExplicitAutoJoints` entry, which Roblox
+    /// writes to tell itself that joints between parts were created
+    /// explicitly, and should not be recreated when the file is loaded.
+    /// Returns `None` if the entry isn't present or isn't `true` or `false`.
+    pub fn explicit_auto_joints(&self) -> Option<bool> {
+        match self.get("ExplicitAutoJoints")? {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Sets the value of the `ExplicitAutoJoints` entry. See
+    /// [`Metadata::explicit_auto_joints`].
+    pub fn set_explicit_auto_joints(&mut self, value: bool) {
+        self.insert("ExplicitAutoJoints", value.to_string());
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Metadata {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            entries: iter
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> Extend<(K, V)> for Metadata {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.entries.extend(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into())),
+        );
+    }
+}
@@ -0,0 +1,855 @@
+#[cfg(feature = "reflection")]
+use std::convert::TryFrom;
+use std::{collections::BTreeMap, fmt};
+
+#[cfg(feature = "reflection")]
+use rbx_reflection::{DataType, ReflectionDatabase};
+use serde::{de::Error as _, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[cfg(feature = "reflection")]
+use crate::types::{
+    Color3, Content, ContentId, Enum, NumberRange, Tags, UDim, UDim2, VariantType, Vector2, Vector3,
+};
+use crate::{
+    types::{Attributes, Ref, Variant},
+    Instance, InstanceBuilder, Symbol, WeakDom,
+};
+
+/// An instance in Rojo's `*.model.json` format, as it's read.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonModel {
+    name: Option<String>,
+    class_name: String,
+    #[serde(default)]
+    properties: BTreeMap<String, Value>,
+    #[serde(default)]
+    attributes: BTreeMap<String, Value>,
+    #[serde(default)]
+    children: Vec<JsonModel>,
+}
+
+/// An instance in Rojo's `*.model.json` format, as it's written.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonModelRef<'a> {
+    name: &'a str,
+    class_name: &'a str,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    properties: BTreeMap<&'a str, &'a Variant>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<JsonModelRef<'a>>,
+}
+
+/// A node of the `tree` in a Rojo project file. Every key that doesn't start
+/// with `$` is a child.
+#[derive(Deserialize)]
+struct ProjectNode {
+    #[serde(rename = "$className")]
+    class_name: Option<String>,
+    #[serde(rename = "$path")]
+    path: Option<Value>,
+    #[serde(rename = "$properties", default)]
+    properties: BTreeMap<String, Value>,
+    #[serde(rename = "$attributes", default)]
+    attributes: BTreeMap<String, Value>,
+    #[serde(flatten)]
+    children: BTreeMap<String, Value>,
+}
+
+impl InstanceBuilder {
+    /// Reads an instance and its descendants from Rojo's JSON model format,
+    /// which is what `*.model.json` files contain.
+    ///
+    /// Properties can be written with explicit types, like
+    /// `{"Vector3": [1, 2, 3]}`, or implicitly, like `true`. Implicit values
+    /// are read the same way Rojo reads attributes: booleans are `Bool`s,
+    /// numbers are `Float64`s, and strings are `String`s. Other implicit
+    /// values are errors, since telling what they're meant to be needs a
+    /// reflection database; see
+    /// [`InstanceBuilder::from_rojo_model_with_database`] for that. The model's
+    /// `attributes` are always read implicitly and stored in `Attributes`.
+    ///
+    /// Instances without a `name` are named after their class, like with
+    /// [`InstanceBuilder::new`].
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+    ///
+    /// let model = r#"{
+    ///   "className": "Folder",
+    ///   "children": [
+    ///     {
+    ///       "name": "Spawn",
+    ///       "className": "SpawnLocation",
+    ///       "properties": {
+    ///         "Size": { "Vector3": [12, 1, 12] },
+    ///         "Locked": true
+    ///       }
+    ///     }
+    ///   ]
+    /// }"#;
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::from_rojo_model(model)?);
+    /// let spawn = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    /// assert_eq!(spawn.name, "Spawn");
+    /// assert_eq!(spawn.properties.get("Locked"), Some(&Variant::Bool(true)));
+    /// # Ok::<(), rbx_dom_weak::RojoJsonError>(())
+    /// ```
+    pub fn from_rojo_model(json: &str) -> Result<Self, RojoJsonError> {
+        Reader::default().model(json)
+    }
+
+    /// Reads an instance and its descendants from a node of the `tree` in a
+    /// Rojo project file, like `default.project.json`. Project nodes don't
+    /// store their own names, so the name of the instance is given as `name`.
+    ///
+    /// `$className`, `$properties`, and `$attributes` are read like the
+    /// fields of [`InstanceBuilder::from_rojo_model`], and every key that
+    /// doesn't start with `$` is read as a child, in alphabetical order.
+    /// Nodes without a `$className` have the class of their name, which is
+    /// how Rojo handles services. Nodes with a `$path` are errors, since what
+    /// they contain is on the file system.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let project = r#"{
+    ///   "$className": "DataModel",
+    ///   "ReplicatedStorage": {
+    ///     "Shared": { "$className": "Folder" }
+    ///   }
+    /// }"#;
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::from_rojo_project_node("Game", project)?);
+    /// let storage = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    /// assert_eq!(storage.class, "ReplicatedStorage");
+    /// # Ok::<(), rbx_dom_weak::RojoJsonError>(())
+    /// ```
+    pub fn from_rojo_project_node<S: Into<String>>(
+        name: S,
+        json: &str,
+    ) -> Result<Self, RojoJsonError> {
+        Reader::default().project_node(name.into(), json)
+    }
+
+    /// Reads an instance from Rojo's JSON model format like
+    /// [`InstanceBuilder::from_rojo_model`], except that implicit property
+    /// values are read as the type `database` says the property has, like
+    /// Rojo does. Enums can be written as the names of their items, and
+    /// vectors, colors, `UDim`s, `UDim2`s, and `NumberRange`s as arrays of
+    /// numbers. Aliases like `size` are stored under the canonical name of the
+    /// property. Implicit values of properties that aren't in the database are
+    /// read as attributes are.
+    ///
+    /// Requires the `reflection` feature.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::{Enum, Variant}, InstanceBuilder};
+    ///
+    /// let database = rbx_reflection_database::get().unwrap();
+    /// let model = r#"{
+    ///   "className": "Part",
+    ///   "properties": { "Transparency": 0.5, "Material": "Neon" }
+    /// }"#;
+    ///
+    /// let part = InstanceBuilder::from_rojo_model_with_database(model, database)?;
+    /// assert_eq!(part.property("Transparency"), Some(&Variant::Float32(0.5)));
+    /// assert_eq!(part.property("Material"), Some(&Variant::Enum(Enum::from_u32(288))));
+    /// # Ok::<(), rbx_dom_weak::RojoJsonError>(())
+    /// ```
+    #[cfg(feature = "reflection")]
+    pub fn from_rojo_model_with_database(
+        json: &str,
+        database: &ReflectionDatabase,
+    ) -> Result<Self, RojoJsonError> {
+        Reader {
+            database: Some(database),
+        }
+        .model(json)
+    }
+
+    /// Reads an instance from a node of a Rojo project file like
+    /// [`InstanceBuilder::from_rojo_project_node`], reading implicit property
+    /// values like [`InstanceBuilder::from_rojo_model_with_database`].
+    ///
+    /// Requires the `reflection` feature.
+    #[cfg(feature = "reflection")]
+    pub fn from_rojo_project_node_with_database<S: Into<String>>(
+        name: S,
+        json: &str,
+        database: &ReflectionDatabase,
+    ) -> Result<Self, RojoJsonError> {
+        Reader {
+            database: Some(database),
+        }
+        .project_node(name.into(), json)
+    }
+}
+
+impl WeakDom {
+    /// Writes the instance with the given referent and its descendants in
+    /// Rojo's JSON model format, which is what `*.model.json` files contain.
+    ///
+    /// Every property is written with an explicit type, so the model reads
+    /// back the same with [`InstanceBuilder::from_rojo_model`] or in Rojo.
+    /// `Ref` properties are left out, since the referents they hold don't
+    /// mean anything outside of this dom.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Part").with_property("Anchored", true));
+    ///
+    /// let model = dom.to_rojo_model(dom.root_ref());
+    /// assert!(model.contains(r#""Anchored": {"#));
+    ///
+    /// let part = InstanceBuilder::from_rojo_model(&model)?;
+    /// assert_eq!(part.property("Anchored"), Some(&true.into()));
+    /// # Ok::<(), rbx_dom_weak::RojoJsonError>(())
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn to_rojo_model(&self, referent: Ref) -> String {
+        let instance = self
+            .get_by_ref(referent)
+            .unwrap_or_else(|| panic!("cannot write an instance that does not exist"));
+
+        serde_json::to_string_pretty(&self.json_model(instance)).unwrap()
+    }
+
+    /// Writes the instance with the given referent and its descendants as a
+    /// node of the `tree` in a Rojo project file, like `default.project.json`.
+    /// The name of the instance isn't part of the node, and is written by
+    /// whatever contains it instead.
+    ///
+    /// Properties are written like [`WeakDom::to_rojo_model`] writes them.
+    /// Children are keyed by name in project files, so this fails if any
+    /// instance has two children with the same name, or a child with a name
+    /// that starts with `$`.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn to_rojo_project_node(&self, referent: Ref) -> Result<String, RojoJsonError> {
+        let instance = self
+            .get_by_ref(referent)
+            .unwrap_or_else(|| panic!("cannot write an instance that does not exist"));
+
+        let node = self.project_node(instance)?;
+        Ok(serde_json::to_string_pretty(&node).unwrap())
+    }
+
+    fn json_model<'a>(&'a self, instance: &'a Instance) -> JsonModelRef<'a> {
+        JsonModelRef {
+            name: &instance.name,
+            class_name: &instance.class,
+            properties: json_properties(instance),
+            children: instance
+                .children()
+                .iter()
+                .map(|child| self.json_model(&self.instances[child]))
+                .collect(),
+        }
+    }
+
+    fn project_node(&self, instance: &Instance) -> Result<Value, RojoJsonError> {
+        let mut node = Map::new();
+        node.insert(
+            "$className".to_owned(),
+            Value::String(instance.class.to_string()),
+        );
+
+        let properties = json_properties(instance);
+        if !properties.is_empty() {
+            node.insert(
+                "$properties".to_owned(),
+                serde_json::to_value(properties).unwrap(),
+            );
+        }
+
+        for child in instance.children() {
+            let child = &self.instances[child];
+
+            if child.name.starts_with('$') || node.contains_key(&child.name) {
+                return Err(RojoJsonError::InvalidChildName {
+                    name: child.name.clone(),
+                });
+            }
+
+            node.insert(child.name.clone(), self.project_node(child)?);
+        }
+
+        Ok(Value::Object(node))
+    }
+}
+
+fn json_properties(instance: &Instance) -> BTreeMap<&str, &Variant> {
+    instance
+        .sorted_properties_iter()
+        .filter(|(_, value)| !matches!(value, Variant::Ref(_)))
+        .map(|(name, value)| (name.as_str(), value))
+        .collect()
+}
+
+/// Reads Rojo's JSON formats, resolving implicit values with a reflection
+/// database if there is one.
+#[derive(Default)]
+struct Reader<'a> {
+    #[cfg(feature = "reflection")]
+    database: Option<&'a ReflectionDatabase<'a>>,
+    #[cfg(not(feature = "reflection"))]
+    database: std::marker::PhantomData<&'a ()>,
+}
+
+impl Reader<'_> {
+    fn model(&self, json: &str) -> Result<InstanceBuilder, RojoJsonError> {
+        let model: JsonModel = serde_json::from_str(json).map_err(RojoJsonError::InvalidJson)?;
+        self.model_builder(model)
+    }
+
+    fn model_builder(&self, model: JsonModel) -> Result<InstanceBuilder, RojoJsonError> {
+        let mut builder = InstanceBuilder::new(model.class_name);
+        if let Some(name) = model.name {
+            builder.set_name(name);
+        }
+
+        self.add_properties(&mut builder, &model.properties, &model.attributes)?;
+
+        for child in model.children {
+            builder.add_child(self.model_builder(child)?);
+        }
+
+        Ok(builder)
+    }
+
+    fn project_node(&self, name: String, json: &str) -> Result<InstanceBuilder, RojoJsonError> {
+        let node: ProjectNode = serde_json::from_str(json).map_err(RojoJsonError::InvalidJson)?;
+        self.project_node_builder(name, node)
+    }
+
+    fn project_node_builder(
+        &self,
+        name: String,
+        node: ProjectNode,
+    ) -> Result<InstanceBuilder, RojoJsonError> {
+        if node.path.is_some() {
+            return Err(RojoJsonError::PathNotSupported { instance: name });
+        }
+
+        let class = node.class_name.unwrap_or_else(|| name.clone());
+        let mut builder = InstanceBuilder::new(class).with_name(name);
+
+        self.add_properties(&mut builder, &node.properties, &node.attributes)?;
+
+        for (child_name, child) in node.children {
+            if child_name.starts_with('$') {
+                continue;
+            }
+
+            let child = match child {
+                Value::Object(_) => serde_json::from_value(child),
+                _ => Err(serde_json::Error::custom(format!(
+                    "project node {} is not an object",
+                    child_name
+                ))),
+            }
+            .map_err(RojoJsonError::InvalidJson)?;
+
+            builder.add_child(self.project_node_builder(child_name, child)?);
+        }
+
+        Ok(builder)
+    }
+
+    fn add_properties(
+        &self,
+        builder: &mut InstanceBuilder,
+        properties: &BTreeMap<String, Value>,
+        attributes: &BTreeMap<String, Value>,
+    ) -> Result<(), RojoJsonError> {
+        for (name, value) in properties {
+            let (name, value) = self.property(builder, name, value)?;
+            builder.add_property(name, value);
+        }
+
+        if attributes.is_empty() {
+            return Ok(());
+        }
+
+        let mut all_attributes = match builder.remove_property("Attributes") {
+            Some(Variant::Attributes(existing)) => existing,
+            _ => Attributes::new(),
+        };
+
+        for (name, value) in attributes {
+            let value = match explicit_value(builder, name, value)? {
+                Some(value) => value,
+                None => implicit_value(value).ok_or_else(|| RojoJsonError::AmbiguousValue {
+                    instance: builder.name.clone(),
+                    property: name.clone(),
+                })?,
+            };
+
+            all_attributes.insert(name.clone(), value);
+        }
+
+        builder.add_property("Attributes", all_attributes);
+        Ok(())
+    }
+
+    fn property(
+        &self,
+        builder: &InstanceBuilder,
+        name: &str,
+        value: &Value,
+    ) -> Result<(Symbol, Variant), RojoJsonError> {
+        if let Some(value) = explicit_value(builder, name, value)? {
+            return Ok((Symbol::from(name), value));
+        }
+
+        if let Some(resolved) = self.resolve(builder, name, value)? {
+            return Ok(resolved);
+        }
+
+        match implicit_value(value) {
+            Some(value) => Ok((Symbol::from(name), value)),
+            None => Err(RojoJsonError::AmbiguousValue {
+                instance: builder.name.clone(),
+                property: name.to_owned(),
+            }),
+        }
+    }
+
+    #[cfg(not(feature = "reflection"))]
+    fn resolve(
+        &self,
+        _builder: &InstanceBuilder,
+        _name: &str,
+        _value: &Value,
+    ) -> Result<Option<(Symbol, Variant)>, RojoJsonError> {
+        Ok(None)
+    }
+
+    /// Reads an implicit property value as the type the database says the
+    /// property has, or returns `None` if the database doesn't know about the
+    /// property.
+    #[cfg(feature = "reflection")]
+    fn resolve(
+        &self,
+        builder: &InstanceBuilder,
+        name: &str,
+        value: &Value,
+    ) -> Result<Option<(Symbol, Variant)>, RojoJsonError> {
+        let database = match self.database {
+            Some(database) => database,
+            None => return Ok(None),
+        };
+        let descriptors = match database.find_property_descriptors(&builder.class, name) {
+            Some(descriptors) => descriptors,
+            None => return Ok(None),
+        };
+        let canonical = descriptors.canonical;
+
+        let resolved = match &canonical.data_type {
+            DataType::Value(ty) => typed_value(*ty, value),
+            DataType::Enum(enum_name) => match value {
+                Value::String(item) => database
+                    .enums
+                    .get(enum_name)
+                    .and_then(|descriptor| descriptor.items.get(item.as_str()))
+                    .map(|value| Enum::from_u32(*value).into()),
+                Value::Number(number) => number
+                    .as_u64()
+                    .and_then(|value| u32::try_from(value).ok())
+                    .map(|value| Enum::from_u32(value).into()),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match resolved {
+            Some(value) => Ok(Some((Symbol::from(canonical.name.as_ref()), value))),
+            None => Err(RojoJsonError::AmbiguousValue {
+                instance: builder.name.clone(),
+                property: name.to_owned(),
+            }),
+        }
+    }
+}
+
+/// Reads a value written with an explicit type, like `{"Bool": true}`, or
+/// returns `None` if `value` isn't written that way.
+fn explicit_value(
+    builder: &InstanceBuilder,
+    name: &str,
+    value: &Value,
+) -> Result<Option<Variant>, RojoJsonError> {
+    match value {
+        Value::Object(object) if object.len() == 1 => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|error| RojoJsonError::InvalidValue {
+                instance: builder.name.clone(),
+                property: name.to_owned(),
+                error,
+            }),
+        _ => Ok(None),
+    }
+}
+
+/// Reads an implicit value the way Rojo reads attributes.
+fn implicit_value(value: &Value) -> Option<Variant> {
+    match value {
+        Value::Bool(value) => Some(Variant::Bool(*value)),
+        Value::Number(number) => number.as_f64().map(Variant::Float64),
+        Value::String(value) => Some(Variant::String(value.clone())),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "reflection")]
+fn typed_value(ty: VariantType, value: &Value) -> Option<Variant> {
+    fn numbers<const N: usize>(value: &Value) -> Option<[f32; N]> {
+        let array = value.as_array()?;
+        if array.len() != N {
+            return None;
+        }
+
+        let mut numbers = [0.0; N];
+        for (number, value) in numbers.iter_mut().zip(array) {
+            *number = value.as_f64()? as f32;
+        }
+
+        Some(numbers)
+    }
+
+    fn udim(value: &Value) -> Option<UDim> {
+        let [scale, offset] = numbers::<2>(value)?;
+        Some(UDim::new(scale, offset as i32))
+    }
+
+    Some(match (ty, value) {
+        (VariantType::Bool, Value::Bool(value)) => Variant::Bool(*value),
+        (VariantType::Float32, Value::Number(number)) => Variant::Float32(number.as_f64()? as f32),
+        (VariantType::Float64, Value::Number(number)) => Variant::Float64(number.as_f64()?),
+        (VariantType::Int32, Value::Number(number)) => {
+            Variant::Int32(i32::try_from(number.as_i64()?).ok()?)
+        }
+        (VariantType::Int64, Value::Number(number)) => Variant::Int64(number.as_i64()?),
+        (VariantType::String, Value::String(value)) => Variant::String(value.clone()),
+        (VariantType::ContentId, Value::String(value)) => {
+            Variant::ContentId(ContentId::from(value.clone()))
+        }
+        (VariantType::Content, Value::String(value)) => {
+            Variant::Content(Content::from_uri(value.clone()))
+        }
+        (VariantType::Tags, Value::Array(tags)) => {
+            let tags: Option<Vec<String>> = tags
+                .iter()
+                .map(|tag| tag.as_str().map(str::to_owned))
+                .collect();
+            Variant::Tags(Tags::from(tags?))
+        }
+        (VariantType::Vector2, _) => {
+            let [x, y] = numbers(value)?;
+            Variant::Vector2(Vector2::new(x, y))
+        }
+        (VariantType::Vector3, _) => {
+            let [x, y, z] = numbers(value)?;
+            Variant::Vector3(Vector3::new(x, y, z))
+        }
+        (VariantType::Color3, _) => {
+            let [r, g, b] = numbers(value)?;
+            Variant::Color3(Color3::new(r, g, b))
+        }
+        (VariantType::NumberRange, _) => {
+            let [min, max] = numbers(value)?;
+            Variant::NumberRange(NumberRange::new(min, max))
+        }
+        (VariantType::UDim, _) => Variant::UDim(udim(value)?),
+        (VariantType::UDim2, Value::Array(array)) if array.len() == 2 => {
+            Variant::UDim2(UDim2::new(udim(&array[0])?, udim(&array[1])?))
+        }
+        _ => return None,
+    })
+}
+
+/// Returned when an instance can't be read from or written to one of Rojo's
+/// JSON formats.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RojoJsonError {
+    /// The JSON is malformed, or isn't a model or project node.
+    InvalidJson(serde_json::Error),
+
+    /// A property is written with an explicit type, but its value isn't a
+    /// valid value of that type.
+    InvalidValue {
+        /// The name of the instance.
+        instance: String,
+
+        /// The name of the property or attribute.
+        property: String,
+
+        /// Why the value couldn't be read.
+        error: serde_json::Error,
+    },
+
+    /// A property is written without a type, and what type it should be
+    /// can't be told from its value.
+    AmbiguousValue {
+        /// The name of the instance.
+        instance: String,
+
+        /// The name of the property or attribute.
+        property: String,
+    },
+
+    /// A project node has a `$path`, which refers to files this crate
+    /// doesn't read.
+    PathNotSupported {
+        /// The name of the instance.
+        instance: String,
+    },
+
+    /// An instance has a child that can't be written as a project node,
+    /// because another child has the same name or its name starts with `$`.
+    InvalidChildName {
+        /// The name of the child.
+        name: String,
+    },
+}
+
+impl fmt::Display for RojoJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RojoJsonError::InvalidJson(error) => write!(f, "invalid Rojo JSON: {}", error),
+            RojoJsonError::InvalidValue {
+                instance,
+                property,
+                error,
+            } => write!(
+                f,
+                "invalid value for {} of {}: {}",
+                property, instance, error
+            ),
+            RojoJsonError::AmbiguousValue { instance, property } => write!(
+                f,
+                "the type of {} of {} is ambiguous, so it must be written explicitly",
+                property, instance
+            ),
+            RojoJsonError::PathNotSupported { instance } => write!(
+                f,
+                "project node {} has a $path, which can't be read",
+                instance
+            ),
+            RojoJsonError::InvalidChildName { name } => write!(
+                f,
+                "child {} can't be written as a project node because its name is taken or starts with $",
+                name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RojoJsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RojoJsonError::InvalidJson(error) => Some(error),
+            RojoJsonError::InvalidValue { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::types::Vector3;
+
+    #[test]
+    fn rojo_model_round_trip() {
+        let target = InstanceBuilder::new("Part");
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Model")
+                .with_name("Machine")
+                .with_property("Attributes", Attributes::new().with("Speed", 2.0f64))
+                .with_ref_property("PrimaryPart", target.referent())
+                .with_children([
+                    target
+                        .with_name("Base")
+                        .with_property("Size", Vector3::new(4.0, 1.0, 2.0))
+                        .with_property("Transparency", 0.25f32),
+                    InstanceBuilder::new("Folder").with_name("Base"),
+                ]),
+        );
+
+        let model = dom.to_rojo_model(dom.root_ref());
+        let reread = WeakDom::new(InstanceBuilder::from_rojo_model(&model).unwrap());
+        let machine = reread.root();
+
+        assert_eq!(machine.name, "Machine");
+        assert_eq!(machine.class, "Model");
+        assert_eq!(
+            machine
+                .get::<Attributes>("Attributes")
+                .unwrap()
+                .get("Speed"),
+            Some(&Variant::Float64(2.0))
+        );
+        assert!(!machine.properties.contains_key("PrimaryPart"));
+
+        let children: Vec<_> = machine
+            .children()
+            .iter()
+            .map(|child| reread.get_by_ref(*child).unwrap())
+            .collect();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].class, "Part");
+        assert_eq!(
+            children[0].get::<Vector3>("Size"),
+            Some(&Vector3::new(4.0, 1.0, 2.0))
+        );
+        assert_eq!(
+            children[0].properties.get("Transparency"),
+            Some(&Variant::Float32(0.25))
+        );
+        assert_eq!(children[1].class, "Folder");
+
+        // Siblings with the same name can't be keyed by name.
+        assert!(matches!(
+            dom.to_rojo_project_node(dom.root_ref()),
+            Err(RojoJsonError::InvalidChildName { name }) if name == "Base"
+        ));
+    }
+
+    #[test]
+    fn rojo_implicit_values() {
+        let model = r#"{
+            "className": "Configuration",
+            "properties": { "Archivable": false, "Tag": "hello" },
+            "attributes": { "Count": 3, "Label": { "String": "big" } }
+        }"#;
+        let config = InstanceBuilder::from_rojo_model(model).unwrap();
+
+        assert_eq!(config.name(), "Configuration");
+        assert_eq!(config.property("Archivable"), Some(&Variant::Bool(false)));
+        assert_eq!(
+            config.property("Tag"),
+            Some(&Variant::String("hello".into()))
+        );
+        match config.property("Attributes") {
+            Some(Variant::Attributes(attributes)) => {
+                assert_eq!(attributes.get("Count"), Some(&Variant::Float64(3.0)));
+                assert_eq!(
+                    attributes.get("Label"),
+                    Some(&Variant::String("big".into()))
+                );
+            }
+            other => panic!("expected attributes, got {:?}", other),
+        }
+
+        let ambiguous = r#"{ "className": "Part", "properties": { "Size": [1, 2, 3] } }"#;
+        assert!(matches!(
+            InstanceBuilder::from_rojo_model(ambiguous),
+            Err(RojoJsonError::AmbiguousValue { property, .. }) if property == "Size"
+        ));
+
+        let invalid = r#"{ "className": "Part", "properties": { "Size": { "Vector3": [1] } } }"#;
+        assert!(matches!(
+            InstanceBuilder::from_rojo_model(invalid),
+            Err(RojoJsonError::InvalidValue { property, .. }) if property == "Size"
+        ));
+    }
+
+    #[test]
+    fn rojo_project_node() {
+        let project = r#"{
+            "$className": "DataModel",
+            "$ignoreUnknownInstances": true,
+            "Workspace": {
+                "$properties": { "Gravity": { "Float32": 100 } },
+                "Baseplate": { "$className": "Part" }
+            },
+            "Lighting": {}
+        }"#;
+        let dom = WeakDom::new(InstanceBuilder::from_rojo_project_node("Game", project).unwrap());
+
+        assert_eq!(dom.root().name, "Game");
+        assert_eq!(dom.root().class, "DataModel");
+
+        let services: Vec<_> = dom
+            .root()
+            .children()
+            .iter()
+            .map(|child| dom.get_by_ref(*child).unwrap())
+            .collect();
+        assert_eq!(services[0].class, "Lighting");
+        assert_eq!(services[1].class, "Workspace");
+        assert_eq!(
+            services[1].properties.get("Gravity"),
+            Some(&Variant::Float32(100.0))
+        );
+
+        let node = dom.to_rojo_project_node(dom.root_ref()).unwrap();
+        let reread = WeakDom::new(InstanceBuilder::from_rojo_project_node("Game", &node).unwrap());
+        let baseplate = reread
+            .get_by_ref(reread.root().children()[1])
+            .unwrap()
+            .children()[0];
+        assert_eq!(reread.get_by_ref(baseplate).unwrap().name, "Baseplate");
+
+        let with_path = r#"{ "$className": "DataModel", "Src": { "$path": "src" } }"#;
+        assert!(matches!(
+            InstanceBuilder::from_rojo_project_node("Game", with_path),
+            Err(RojoJsonError::PathNotSupported { instance }) if instance == "Src"
+        ));
+    }
+
+    #[cfg(feature = "reflection")]
+    #[test]
+    fn rojo_model_with_database() {
+        use crate::types::VariantType;
+
+        let database = rbx_reflection_database::get().unwrap();
+        let model = r#"{
+            "className": "Part",
+            "properties": {
+                "size": [2, 3, 4],
+                "Color": [1, 0, 0],
+                "Material": "Neon",
+                "Locked": true,
+                "Tags": ["Lava"],
+                "Mystery": 1
+            }
+        }"#;
+        let part = InstanceBuilder::from_rojo_model_with_database(model, database).unwrap();
+
+        assert_eq!(
+            part.property("Size").map(Variant::ty),
+            Some(VariantType::Vector3)
+        );
+        assert_eq!(
+            part.property("Color").map(Variant::ty),
+            Some(VariantType::Color3)
+        );
+        assert_eq!(
+            part.property("Material").map(Variant::ty),
+            Some(VariantType::Enum)
+        );
+        assert_eq!(part.property("Locked"), Some(&Variant::Bool(true)));
+        assert_eq!(
+            part.property("Tags").map(Variant::ty),
+            Some(VariantType::Tags)
+        );
+        assert_eq!(part.property("Mystery"), Some(&Variant::Float64(1.0)));
+
+        let wrong = r#"{ "className": "Part", "properties": { "Material": "NotAMaterial" } }"#;
+        assert!(matches!(
+            InstanceBuilder::from_rojo_model_with_database(wrong, database),
+            Err(RojoJsonError::AmbiguousValue { property, .. }) if property == "Material"
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{instance::Instance, types::Ref, InstanceBuilder, WeakDom};
+
+impl WeakDom {
+    /// Creates a dom with a `DataModel` at its root and each of `roots` as
+    /// one of its children.
+    ///
+    /// Model files can hold any number of instances at the top level, so
+    /// rbx_binary and rbx_xml read them into a dom shaped like this, with a
+    /// `DataModel` that isn't part of the file holding them. Methods like
+    /// [`WeakDom::root_refs`] and [`WeakDom::add_root`] treat the children of
+    /// the root instance as the roots of the dom.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::from_roots([
+    ///     InstanceBuilder::new("Part"),
+    ///     InstanceBuilder::new("Model"),
+    /// ]);
+    /// assert_eq!(dom.root().class, "DataModel");
+    /// assert_eq!(dom.root_refs().len(), 2);
+    ///
+    /// let folder = dom.add_root(InstanceBuilder::new("Folder"));
+    /// dom.remove_root(dom.root_refs()[0]);
+    ///
+    /// let classes: Vec<_> = dom.roots().map(|root| root.class.as_str()).collect();
+    /// assert_eq!(classes, ["Model", "Folder"]);
+    /// assert_eq!(dom.root_refs()[1], folder);
+    ///
+    /// // Converting a dom back into a list of roots keeps any `Ref`
+    /// // properties between them.
+    /// let roots = dom.into_roots(true);
+    /// assert_eq!(roots[1].referent(), folder);
+    /// ```
+    pub fn from_roots<I: IntoIterator<Item = InstanceBuilder>>(roots: I) -> WeakDom {
+        WeakDom::new(InstanceBuilder::new("DataModel").with_children(roots))
+    }
+
+    /// Returns the referents of the roots of the dom, which are the children
+    /// of its root instance.
+    #[inline]
+    pub fn root_refs(&self) -> &[Ref] {
+        self.root().children()
+    }
+
+    /// Returns an iterator over the roots of the dom, which are the children
+    /// of its root instance.
+    pub fn roots(&self) -> impl Iterator<Item = &Instance> {
+        self.root_refs()
+            .iter()
+            .map(move |referent| self.get_by_ref(*referent).unwrap())
+    }
+
+    /// Inserts `builder` after the last root of the dom, returning its
+    /// referent. This is the same as inserting it into the root instance.
+    pub fn add_root(&mut self, builder: InstanceBuilder) -> Ref {
+        self.insert(self.root_ref(), builder)
+    }
+
+    /// Destroys the root of the dom with the given referent, along with all
+    /// of its descendants.
+    ///
+    /// ## Panics
+    /// Panics if `referent` is not one of [`WeakDom::root_refs`].
+    pub fn remove_root(&mut self, referent: Ref) {
+        if !self.root_refs().contains(&referent) {
+            panic!("cannot remove an instance that is not a root of the dom");
+        }
+
+        self.destroy(referent);
+    }
+
+    /// Consumes the dom, converting each of its roots and their descendants
+    /// back into an [`InstanceBuilder`]. The root instance is discarded, so
+    /// this undoes [`WeakDom::from_roots`].
+    ///
+    /// `preserve_referents` works the same way as for
+    /// [`WeakDom::into_builder`], and `Ref` properties between the roots are
+    /// kept.
+    pub fn into_roots(self, preserve_referents: bool) -> Vec<InstanceBuilder> {
+        let root_ref = self.root_ref();
+        self.into_builder(root_ref, preserve_referents).children
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::types::Variant;
+
+    #[test]
+    fn roots() {
+        let part = InstanceBuilder::new("Part");
+        let part_ref = part.referent();
+        let weld = InstanceBuilder::new("WeldConstraint").with_property("Part0", part_ref);
+
+        let mut dom = WeakDom::from_roots([part, weld]);
+        let folder_ref = dom.add_root(InstanceBuilder::new("Folder"));
+        assert_eq!(dom.root_refs().len(), 3);
+        assert_eq!(dom.get_by_ref(folder_ref).unwrap().parent(), dom.root_ref());
+
+        dom.remove_root(folder_ref);
+        assert!(dom.get_by_ref(folder_ref).is_none());
+
+        let roots = dom.into_roots(false);
+        assert_eq!(roots.len(), 2);
+        assert_ne!(roots[0].referent(), part_ref);
+
+        // Refs between roots are rewritten to match their new referents.
+        let dom = WeakDom::from_roots(roots);
+        let weld = dom.roots().nth(1).unwrap();
+        assert_eq!(
+            weld.properties.get("Part0"),
+            Some(&Variant::Ref(dom.root_refs()[0]))
+        );
+    }
+
+    #[test]
+    #[should_panic = "not a root"]
+    fn remove_root_panics_on_descendants() {
+        let child = InstanceBuilder::new("Part");
+        let child_ref = child.referent();
+        let mut dom = WeakDom::from_roots([InstanceBuilder::new("Model").with_child(child)]);
+
+        dom.remove_root(child_ref);
+    }
+}
@@ -0,0 +1,243 @@
+use ahash::AHashSet;
+use rbx_types::{Ref, Variant};
+
+use crate::{instance::Instance, symbol, Symbol, WeakDom};
+
+/// The classes whose instances carry a `Source` property.
+pub const SCRIPT_CLASSES: &[&str] = &["Script", "LocalScript", "ModuleScript"];
+
+/// The source of a script in a [`WeakDom`], as returned by
+/// [`WeakDom::scripts`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptSource {
+    /// The referent of the script.
+    pub referent: Ref,
+
+    /// The class of the script, which is one of [`SCRIPT_CLASSES`].
+    pub class: Symbol,
+
+    /// The names of the script and its ancestors separated by `/`, excluding
+    /// the root of the dom. For example, a script named `Main` inside of
+    /// `ServerScriptService` has the path `ServerScriptService/Main`.
+    ///
+    /// Paths are not guaranteed to be unique, since siblings may share a name.
+    pub path: String,
+
+    /// The contents of the script's `Source` property. Sources that are not
+    /// valid UTF-8 are converted lossily.
+    pub source: String,
+
+    /// The script's `ScriptGuid` property, if it has one.
+    pub guid: Option<String>,
+}
+
+impl WeakDom {
+    /// Returns the source of every script in the dom, in the order they
+    /// appear in the tree.
+    pub fn scripts(&self) -> Vec<ScriptSource> {
+        self.descendants()
+            .filter(|instance| is_script(instance))
+            .map(|instance| ScriptSource {
+                referent: instance.referent(),
+                class: instance.class.clone(),
+                path: self.script_path(instance.referent()),
+                source: script_source(instance),
+                guid: match instance.properties.get("ScriptGuid") {
+                    Some(Variant::String(guid)) if !guid.is_empty() => Some(guid.clone()),
+                    _ => None,
+                },
+            })
+            .collect()
+    }
+
+    /// Returns the first script with the given path, as described by
+    /// [`ScriptSource::path`].
+    pub fn script_by_path(&self, path: &str) -> Option<Ref> {
+        self.descendants()
+            .filter(|instance| is_script(instance))
+            .map(Instance::referent)
+            .find(|&referent| self.script_path(referent) == path)
+    }
+
+    /// Replaces the `Source` property of a script. The script's `ScriptGuid`
+    /// is kept as-is so that Roblox still considers it the same script.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to a script in the DOM.
+    pub fn set_script_source<S: Into<String>>(&mut self, referent: Ref, source: S) {
+        let instance = self
+            .get_by_ref_mut(referent)
+            .unwrap_or_else(|| panic!("cannot set the source of an instance that does not exist"));
+
+        if !is_script(instance) {
+            panic!(
+                "cannot set the source of a {}, which is not a script",
+                instance.class
+            );
+        }
+
+        instance
+            .properties
+            .insert(symbol("Source"), Variant::String(source.into()));
+    }
+
+    /// Gives a new `ScriptGuid` to every script under `referent` (including
+    /// itself) that has no `ScriptGuid`, or whose `ScriptGuid` is shared with
+    /// a script that appears earlier in the dom. This is needed after cloning
+    /// scripts or creating them from scratch. Returns the number of scripts
+    /// that were changed.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn assign_script_guids(&mut self, referent: Ref) -> usize {
+        if self.get_by_ref(referent).is_none() {
+            panic!("cannot assign script guids under an instance that does not exist");
+        }
+
+        let mut seen = AHashSet::new();
+        let mut to_assign = Vec::new();
+
+        // Guids elsewhere in the dom count as taken, so that the scripts being
+        // assigned never collide with them.
+        for instance in self.descendants().filter(|instance| is_script(instance)) {
+            let in_scope = self
+                .ancestors_of(instance.referent())
+                .any(|ancestor| ancestor.referent() == referent);
+
+            match instance.properties.get("ScriptGuid") {
+                Some(Variant::String(guid)) if !guid.is_empty() && seen.insert(guid) => {}
+                _ if in_scope => to_assign.push(instance.referent()),
+                _ => {}
+            }
+        }
+
+        for script in &to_assign {
+            let instance = self.get_by_ref_mut(*script).unwrap();
+            instance
+                .properties
+                .insert(symbol("ScriptGuid"), Variant::String(new_script_guid()));
+        }
+
+        to_assign.len()
+    }
+
+    fn script_path(&self, referent: Ref) -> String {
+        let mut components: Vec<&str> = self
+            .ancestors_of(referent)
+            .filter(|ancestor| ancestor.referent() != self.root_ref())
+            .map(|ancestor| ancestor.name.as_str())
+            .collect();
+        components.reverse();
+        components.join("/")
+    }
+}
+
+pub(crate) fn is_script(instance: &Instance) -> bool {
+    SCRIPT_CLASSES.contains(&instance.class.as_str())
+}
+
+fn script_source(instance: &Instance) -> String {
+    match instance.properties.get("Source") {
+        Some(Variant::String(source)) => source.clone(),
+        Some(Variant::BinaryString(source)) => {
+            String::from_utf8_lossy(source.as_ref()).into_owned()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Generates a random `ScriptGuid` in the format Roblox uses, such as
+/// `{2A3C8B5E-9F01-4D6A-B7C2-0E1F2D3C4B5A}`.
+fn new_script_guid() -> String {
+    let hex = Ref::new().to_string().to_uppercase();
+
+    format!(
+        "{{{}-{}-{}-{}-{}}}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn extract_and_inject() {
+        let main = InstanceBuilder::new("Script")
+            .with_name("Main")
+            .with_property("Source", "print('hi')")
+            .with_property("ScriptGuid", "{00000000-0000-0000-0000-000000000001}");
+        let main_ref = main.referent();
+
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("DataModel").with_child(
+                InstanceBuilder::new("ServerScriptService")
+                    .with_name("ServerScriptService")
+                    .with_child(main)
+                    .with_child(InstanceBuilder::new("Folder").with_name("NotAScript")),
+            ),
+        );
+
+        let scripts = dom.scripts();
+        assert_eq!(
+            scripts,
+            [ScriptSource {
+                referent: main_ref,
+                class: symbol("Script"),
+                path: "ServerScriptService/Main".to_owned(),
+                source: "print('hi')".to_owned(),
+                guid: Some("{00000000-0000-0000-0000-000000000001}".to_owned()),
+            }]
+        );
+
+        let found = dom.script_by_path("ServerScriptService/Main").unwrap();
+        dom.set_script_source(found, "print('bye')");
+
+        let scripts = dom.scripts();
+        assert_eq!(scripts[0].source, "print('bye')");
+        assert_eq!(
+            scripts[0].guid.as_deref(),
+            Some("{00000000-0000-0000-0000-000000000001}")
+        );
+
+        assert_eq!(dom.script_by_path("ServerScriptService/NotAScript"), None);
+    }
+
+    #[test]
+    fn assign_script_guids() {
+        let original = InstanceBuilder::new("ModuleScript")
+            .with_property("ScriptGuid", "{00000000-0000-0000-0000-000000000001}");
+        let original_ref = original.referent();
+
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(original)
+                .with_child(InstanceBuilder::new("LocalScript")),
+        );
+        let root_ref = dom.root_ref();
+        let copy_ref = dom.clone_within(original_ref);
+        dom.transfer_within(copy_ref, root_ref);
+
+        // The untouched original and the new LocalScript and copy.
+        assert_eq!(dom.assign_script_guids(root_ref), 2);
+        assert_eq!(dom.assign_script_guids(root_ref), 0);
+
+        let guids: AHashSet<_> = dom
+            .scripts()
+            .into_iter()
+            .map(|script| script.guid.unwrap())
+            .collect();
+        assert_eq!(guids.len(), 3);
+        assert!(guids.contains("{00000000-0000-0000-0000-000000000001}"));
+
+        for guid in &guids {
+            assert_eq!(guid.len(), 38);
+        }
+    }
+}
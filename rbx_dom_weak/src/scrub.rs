@@ -0,0 +1,231 @@
+use crate::{
+    instance::Instance,
+    scripts::is_script,
+    symbol,
+    types::{Ref, Variant},
+    Symbol, WeakDom,
+};
+
+/// Words that, when they appear in the name of a `StringValue`, mean its value
+/// is probably a secret.
+const SECRET_NAMES: &[&str] = &[
+    "apikey", "api_key", "password", "secret", "token", "webhook",
+];
+
+/// Prefixes of values that are secrets no matter what they're named.
+const SECRET_PREFIXES: &[&str] = &[
+    "https://discord.com/api/webhooks/",
+    "https://discordapp.com/api/webhooks/",
+    "https://hooks.slack.com/",
+    "Bearer ",
+];
+
+/// The length at or above which a value made only of characters that appear
+/// in keys, like letters, digits, and `-`, is considered a key.
+const KEY_LENGTH: usize = 32;
+
+/// What [`WeakDom::scrub`] removes from a dom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Scrub {
+    /// Empties the `Source` of every script and removes every `LinkedSource`
+    /// property, so that no code is left in the dom.
+    Scripts,
+
+    /// Empties the `Value` of every `StringValue` that looks like it holds a
+    /// secret, like an API key, token, or webhook URL. Values are checked by
+    /// the name of the `StringValue` and by the shape of the value itself.
+    Secrets,
+
+    /// Does everything [`Scrub::Scripts`] and [`Scrub::Secrets`] do.
+    ScriptsAndSecrets,
+}
+
+impl Scrub {
+    fn scripts(self) -> bool {
+        matches!(self, Scrub::Scripts | Scrub::ScriptsAndSecrets)
+    }
+
+    fn secrets(self) -> bool {
+        matches!(self, Scrub::Secrets | Scrub::ScriptsAndSecrets)
+    }
+}
+
+/// A property that was changed or removed by [`WeakDom::scrub`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrubbedProperty {
+    /// The referent of the instance that had the property.
+    pub referent: Ref,
+
+    /// The name of the property.
+    pub property: Symbol,
+}
+
+impl WeakDom {
+    /// Removes sensitive data from the dom according to `profile`, returning
+    /// every property that was emptied or removed, in the order the instances
+    /// appear in the tree.
+    ///
+    /// This is meant to be run before sharing a place outside of the team
+    /// that made it. Properties are emptied rather than removed where Roblox
+    /// expects them to exist, so the dom can still be written out as usual.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::Variant, InstanceBuilder, Scrub, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_children([
+    ///     InstanceBuilder::new("Script").with_property("Source", "print('hi')"),
+    ///     InstanceBuilder::new("StringValue")
+    ///         .with_name("ApiKey")
+    ///         .with_property("Value", "hunter2"),
+    /// ]));
+    ///
+    /// let scrubbed = dom.scrub(Scrub::ScriptsAndSecrets);
+    /// assert_eq!(scrubbed.len(), 2);
+    ///
+    /// let script = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    /// assert_eq!(script.properties["Source"], Variant::String(String::new()));
+    /// ```
+    pub fn scrub(&mut self, profile: Scrub) -> Vec<ScrubbedProperty> {
+        let source = symbol("Source");
+        let linked_source = symbol("LinkedSource");
+        let value = symbol("Value");
+
+        // Every change is found first and then made, since the dom can't be
+        // changed while it's being walked. `true` means the property is
+        // removed rather than emptied.
+        let mut changes = Vec::new();
+
+        for instance in self.descendants() {
+            let referent = instance.referent();
+
+            if profile.scripts() && is_script(instance) {
+                if has_contents(instance.properties.get(&source)) {
+                    changes.push((referent, source.clone(), false));
+                }
+
+                if instance.properties.contains_key(&linked_source) {
+                    changes.push((referent, linked_source.clone(), true));
+                }
+            }
+
+            if profile.secrets() && instance.class == "StringValue" && is_secret(instance) {
+                changes.push((referent, value.clone(), false));
+            }
+        }
+
+        changes
+            .into_iter()
+            .map(|(referent, property, remove)| {
+                let instance = self.get_by_ref_mut(referent).unwrap();
+
+                if remove {
+                    instance.properties.remove(&property);
+                } else {
+                    instance
+                        .properties
+                        .insert(property.clone(), Variant::String(String::new()));
+                }
+
+                ScrubbedProperty { referent, property }
+            })
+            .collect()
+    }
+}
+
+fn has_contents(value: Option<&Variant>) -> bool {
+    match value {
+        Some(Variant::String(value)) => !value.is_empty(),
+        Some(Variant::BinaryString(value)) => !AsRef::<[u8]>::as_ref(value).is_empty(),
+        _ => false,
+    }
+}
+
+fn is_secret(instance: &Instance) -> bool {
+    let value = match instance.properties.get("Value") {
+        Some(Variant::String(value)) if !value.is_empty() => value,
+        _ => return false,
+    };
+
+    let name = instance.name.to_ascii_lowercase();
+    if SECRET_NAMES.iter().any(|secret| name.contains(secret)) {
+        return true;
+    }
+
+    if SECRET_PREFIXES
+        .iter()
+        .any(|prefix| value.starts_with(prefix))
+    {
+        return true;
+    }
+
+    value.len() >= KEY_LENGTH
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+' | '/' | '='))
+        && value.chars().any(|c| c.is_ascii_digit())
+        && value.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn scrub() {
+        let string_value = |name: &str, value: &str| {
+            InstanceBuilder::new("StringValue")
+                .with_name(name)
+                .with_property("Value", value)
+        };
+
+        let script = InstanceBuilder::new("ModuleScript")
+            .with_property("Source", "return {}")
+            .with_property("LinkedSource", "rbxassetid://1");
+        let webhook = string_value("Log", "https://discord.com/api/webhooks/1/abc");
+        let key = string_value("Config", "d41d8cd98f00b204e9800998ecf8427e");
+        let token = string_value("AuthToken", "short");
+        let plain = string_value("Greeting", "Hello, welcome to the game!");
+        let long_word = string_value("Title", "abcdefghijklmnopqrstuvwxyzabcdefgh");
+
+        let script_ref = script.referent();
+        let webhook_ref = webhook.referent();
+        let key_ref = key.referent();
+        let token_ref = token.referent();
+
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_children([script, webhook, key, token, plain, long_word]),
+        );
+
+        let source = symbol("Source");
+        let linked_source = symbol("LinkedSource");
+        let value = symbol("Value");
+        let scrubbed = |referent, property| ScrubbedProperty { referent, property };
+
+        assert_eq!(
+            dom.scrub(Scrub::Scripts),
+            [
+                scrubbed(script_ref, source.clone()),
+                scrubbed(script_ref, linked_source.clone()),
+            ]
+        );
+        let script = dom.get_by_ref(script_ref).unwrap();
+        assert_eq!(script.properties[&source], Variant::String(String::new()));
+        assert!(!script.properties.contains_key(&linked_source));
+
+        assert_eq!(
+            dom.scrub(Scrub::Secrets),
+            [
+                scrubbed(webhook_ref, value.clone()),
+                scrubbed(key_ref, value.clone()),
+                scrubbed(token_ref, value),
+            ]
+        );
+
+        // Properties that were already scrubbed aren't reported again.
+        assert!(dom.scrub(Scrub::ScriptsAndSecrets).is_empty());
+    }
+}
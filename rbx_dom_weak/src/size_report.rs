@@ -0,0 +1,688 @@
+use std::cmp::Reverse;
+
+use ahash::{AHashMap, AHashSet, HashMap};
+use serde::Serialize;
+
+use crate::{instance::Instance, types::Ref, Asset, AssetManifest, Symbol, SymbolMap, WeakDom};
+
+/// A ranked summary of where the bytes in a [`WeakDom`] are spent.
+///
+/// Reports are built from per-instance byte sizes, like the ones populated in
+/// [`WeakDom::instance_byte_sizes`] when a dom is read from a binary file.
+/// Because `SizeReport` implements `Serialize`, it can be written out as JSON
+/// or any other format supported by serde.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, SizeReport, WeakDom};
+///
+/// let dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+///
+/// // Keep the 10 largest entries in each ranking.
+/// let report = SizeReport::new(&dom, 10);
+///
+/// for entry in &report.largest_instances {
+///     println!("{}: {} bytes", entry.full_name, entry.bytes);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeReport {
+    /// The sum of the sizes of every instance in the dom.
+    pub total_bytes: usize,
+
+    /// The instances with the largest sizes of their own, largest first.
+    pub largest_instances: Vec<InstanceSize>,
+
+    /// The instances whose subtrees (the instance and all of its descendants)
+    /// are the largest, largest first.
+    pub largest_subtrees: Vec<InstanceSize>,
+
+    /// The total size of every class present in the dom, largest first. This
+    /// list is not truncated.
+    pub by_class: Vec<ClassSize>,
+}
+
+/// An entry in one of the instance rankings of a [`SizeReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceSize {
+    /// The referent of the instance.
+    pub referent: Ref,
+
+    /// The full name of the instance, equivalent to `Instance:GetFullName()`.
+    pub full_name: String,
+
+    /// The class of the instance.
+    pub class: Symbol,
+
+    /// The number of bytes attributed to the instance. For
+    /// [`SizeReport::largest_subtrees`], this includes all of its descendants.
+    pub bytes: usize,
+}
+
+/// The total size of all instances of a class in a [`SizeReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassSize {
+    /// The name of the class.
+    pub class: Symbol,
+
+    /// How many instances of this class are in the dom.
+    pub instance_count: usize,
+
+    /// The number of bytes attributed to instances of this class.
+    pub bytes: usize,
+}
+
+/// Statistics about every instance of one class in a dom, as returned by
+/// [`WeakDom::class_stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassStats {
+    /// The name of the class.
+    pub class: Symbol,
+
+    /// How many instances of this class are in the dom.
+    pub instance_count: usize,
+
+    /// The number of properties set on all instances of this class together.
+    pub property_count: usize,
+
+    /// The number of bytes attributed to instances of this class by
+    /// [`WeakDom::instance_byte_sizes`], or `None` if the dom has no byte
+    /// sizes.
+    pub bytes: Option<usize>,
+}
+
+impl SizeReport {
+    /// Builds a report from the byte sizes stored in
+    /// [`WeakDom::instance_byte_sizes`], keeping at most `limit` entries in
+    /// each instance ranking.
+    ///
+    /// If the dom has no byte sizes, every instance is reported as zero bytes.
+    pub fn new(dom: &WeakDom, limit: usize) -> Self {
+        match &dom.instance_byte_sizes {
+            Some(byte_sizes) => {
+                Self::with_sizes(dom, limit, |instance| instance.byte_size(byte_sizes))
+            }
+            None => Self::with_sizes(dom, limit, |_| 0),
+        }
+    }
+
+    /// Builds a report from a map of byte sizes keyed by referent, like the
+    /// one returned by rbx_binary's serializer, keeping at most `limit`
+    /// entries in each instance ranking.
+    pub fn from_ref_sizes(dom: &WeakDom, byte_sizes: &HashMap<Ref, usize>, limit: usize) -> Self {
+        Self::with_sizes(dom, limit, |instance| {
+            byte_sizes.get(&instance.referent()).copied().unwrap_or(0)
+        })
+    }
+
+    /// Builds a report using `size_of` to find the size of each instance,
+    /// keeping at most `limit` entries in each instance ranking.
+    pub fn with_sizes<F>(dom: &WeakDom, limit: usize, size_of: F) -> Self
+    where
+        F: Fn(&Instance) -> usize,
+    {
+        let instances: Vec<&Instance> = dom.descendants().collect();
+
+        let mut own_sizes = AHashMap::with_capacity(instances.len());
+        let mut by_class: SymbolMap<ClassSize> = SymbolMap::default();
+        let mut total_bytes = 0;
+
+        for instance in &instances {
+            let size = size_of(instance);
+            own_sizes.insert(instance.referent(), size);
+            total_bytes += size;
+
+            let class_size = by_class.entry(instance.class.clone()).or_insert(ClassSize {
+                class: instance.class.clone(),
+                instance_count: 0,
+                bytes: 0,
+            });
+            class_size.instance_count += 1;
+            class_size.bytes += size;
+        }
+
+        // Descendants are yielded top-down, so walking them backwards lets us
+        // fold every subtree into its parent after it has been completed.
+        let mut subtree_sizes = own_sizes.clone();
+        for instance in instances.iter().rev() {
+            let size = subtree_sizes[&instance.referent()];
+            if let Some(parent_size) = subtree_sizes.get_mut(&instance.parent()) {
+                *parent_size += size;
+            }
+        }
+
+        let mut by_class: Vec<ClassSize> = by_class.into_values().collect();
+        by_class.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.class.cmp(&b.class)));
+
+        SizeReport {
+            total_bytes,
+            largest_instances: rank(dom, &instances, &own_sizes, limit),
+            largest_subtrees: rank(dom, &instances, &subtree_sizes, limit),
+            by_class,
+        }
+    }
+}
+
+/// Looks up how large the assets referred to by a dom are, for
+/// [`ContentWeightReport`].
+///
+/// Asset sizes aren't stored in the dom, so it's up to the caller to find
+/// them, like by asking Roblox's asset delivery API or reading a local
+/// cache. Closures that take an [`Asset`] and return its size implement this
+/// trait.
+pub trait AssetSizeResolver {
+    /// Returns the size of `asset` in bytes, or `None` if it isn't known.
+    fn asset_size(&self, asset: &Asset) -> Option<u64>;
+}
+
+impl<F> AssetSizeResolver for F
+where
+    F: Fn(&Asset) -> Option<u64>,
+{
+    fn asset_size(&self, asset: &Asset) -> Option<u64> {
+        self(asset)
+    }
+}
+
+/// A ranked summary of the bytes in a [`WeakDom`] together with the bytes of
+/// the assets its instances refer to, for finding what makes a place heavy
+/// to load.
+///
+/// Each asset is only counted once per subtree, no matter how many instances
+/// in it refer to the asset, since the client only downloads it once.
+///
+/// ```
+/// use rbx_dom_weak::{types::ContentId, Asset, ContentWeightReport, InstanceBuilder, WeakDom};
+///
+/// let dom = WeakDom::new(InstanceBuilder::new("Model").with_children([
+///     InstanceBuilder::new("Sound").with_property("SoundId", ContentId::from("rbxassetid://1")),
+///     InstanceBuilder::new("Sound").with_property("SoundId", ContentId::from("rbxassetid://1")),
+/// ]));
+///
+/// let report = ContentWeightReport::new(&dom, &|asset: &Asset| match asset.id {
+///     Some(1) => Some(50_000),
+///     _ => None,
+/// }, 10);
+///
+/// assert_eq!(report.total_asset_bytes, 50_000);
+/// assert_eq!(report.heaviest_subtrees[0].asset_count, 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentWeightReport {
+    /// The sum of the sizes of every instance in the dom.
+    pub total_instance_bytes: usize,
+
+    /// The sum of the sizes of every distinct asset in the dom whose size
+    /// was found.
+    pub total_asset_bytes: u64,
+
+    /// The normalized URLs of the assets whose sizes weren't found, in the
+    /// order they first appear in the dom. These assets count as zero bytes.
+    pub unresolved_assets: Vec<String>,
+
+    /// The instances whose subtrees have the most instance and asset bytes
+    /// together, heaviest first.
+    pub heaviest_subtrees: Vec<SubtreeWeight>,
+}
+
+/// An entry in [`ContentWeightReport::heaviest_subtrees`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtreeWeight {
+    /// The referent of the instance at the top of the subtree.
+    pub referent: Ref,
+
+    /// The full name of the instance, equivalent to `Instance:GetFullName()`.
+    pub full_name: String,
+
+    /// The class of the instance.
+    pub class: Symbol,
+
+    /// The number of bytes attributed to the instance and its descendants.
+    pub instance_bytes: usize,
+
+    /// The number of bytes of the distinct assets referred to by the
+    /// instance and its descendants.
+    pub asset_bytes: u64,
+
+    /// The number of distinct assets referred to by the instance and its
+    /// descendants, including ones whose sizes weren't found.
+    pub asset_count: usize,
+}
+
+impl SubtreeWeight {
+    /// Returns the instance and asset bytes of the subtree together.
+    pub fn total_bytes(&self) -> u64 {
+        self.instance_bytes as u64 + self.asset_bytes
+    }
+}
+
+impl ContentWeightReport {
+    /// Builds a report from the byte sizes stored in
+    /// [`WeakDom::instance_byte_sizes`] and the asset sizes found by
+    /// `resolver`, keeping at most `limit` subtrees.
+    ///
+    /// If the dom has no byte sizes, every instance is counted as zero bytes.
+    pub fn new<R>(dom: &WeakDom, resolver: &R, limit: usize) -> Self
+    where
+        R: AssetSizeResolver + ?Sized,
+    {
+        match &dom.instance_byte_sizes {
+            Some(byte_sizes) => Self::with_sizes(dom, resolver, limit, |instance| {
+                instance.byte_size(byte_sizes)
+            }),
+            None => Self::with_sizes(dom, resolver, limit, |_| 0),
+        }
+    }
+
+    /// Builds a report using `size_of` to find the size of each instance and
+    /// `resolver` to find the size of each asset, keeping at most `limit`
+    /// subtrees.
+    pub fn with_sizes<R, F>(dom: &WeakDom, resolver: &R, limit: usize, size_of: F) -> Self
+    where
+        R: AssetSizeResolver + ?Sized,
+        F: Fn(&Instance) -> usize,
+    {
+        let instances: Vec<&Instance> = dom.descendants().collect();
+
+        let mut instance_bytes: AHashMap<Ref, usize> = instances
+            .iter()
+            .map(|instance| (instance.referent(), size_of(instance)))
+            .collect();
+        let total_instance_bytes = instance_bytes.values().sum();
+
+        // Descendants are yielded top-down, so walking them backwards lets us
+        // fold every subtree into its parent after it has been completed.
+        for instance in instances.iter().rev() {
+            let size = instance_bytes[&instance.referent()];
+            if let Some(parent_size) = instance_bytes.get_mut(&instance.parent()) {
+                *parent_size += size;
+            }
+        }
+
+        let manifest = AssetManifest::new(dom);
+        let mut asset_weights: AHashMap<Ref, (u64, usize)> = AHashMap::new();
+        let mut total_asset_bytes = 0;
+        let mut unresolved_assets = Vec::new();
+
+        for asset in &manifest.assets {
+            let size = match resolver.asset_size(asset) {
+                Some(size) => size,
+                None => {
+                    unresolved_assets.push(asset.url.clone());
+                    0
+                }
+            };
+            total_asset_bytes += size;
+
+            // Every ancestor of every instance that refers to the asset holds
+            // it, but each one should only count it once.
+            let mut counted = AHashSet::new();
+            for reference in &asset.references {
+                for ancestor in dom.ancestors_of(reference.referent) {
+                    if !counted.insert(ancestor.referent()) {
+                        break;
+                    }
+
+                    let weight = asset_weights.entry(ancestor.referent()).or_default();
+                    weight.0 += size;
+                    weight.1 += 1;
+                }
+            }
+        }
+
+        let mut heaviest_subtrees: Vec<SubtreeWeight> = instances
+            .iter()
+            .map(|instance| {
+                let (asset_bytes, asset_count) = asset_weights
+                    .get(&instance.referent())
+                    .copied()
+                    .unwrap_or_default();
+
+                SubtreeWeight {
+                    referent: instance.referent(),
+                    full_name: String::new(),
+                    class: instance.class.clone(),
+                    instance_bytes: instance_bytes[&instance.referent()],
+                    asset_bytes,
+                    asset_count,
+                }
+            })
+            .collect();
+
+        // This sort is stable, which preserves dom order for equal sizes.
+        heaviest_subtrees.sort_by_key(|subtree| Reverse(subtree.total_bytes()));
+        heaviest_subtrees.truncate(limit);
+
+        for subtree in &mut heaviest_subtrees {
+            subtree.full_name = full_name(dom, dom.get_by_ref(subtree.referent).unwrap());
+        }
+
+        ContentWeightReport {
+            total_instance_bytes,
+            total_asset_bytes,
+            unresolved_assets,
+            heaviest_subtrees,
+        }
+    }
+}
+
+impl WeakDom {
+    /// Returns every instance under the root of the dom along with its byte
+    /// size from [`WeakDom::instance_byte_sizes`], largest first. Instances
+    /// with the same size are in the same order as [`WeakDom::descendants`].
+    ///
+    /// If the dom has no byte sizes, because it wasn't read from a binary
+    /// file, every instance has a size of zero.
+    ///
+    /// ```no_run
+    /// # let dom = rbx_dom_weak::WeakDom::new(rbx_dom_weak::InstanceBuilder::new("DataModel"));
+    /// for (referent, size) in dom.instances_by_size().take(10) {
+    ///     println!("{}: {size} bytes", dom.full_name_of(referent));
+    /// }
+    /// ```
+    pub fn instances_by_size(&self) -> impl Iterator<Item = (Ref, usize)> {
+        let mut sizes: Vec<(Ref, usize)> = self
+            .descendants()
+            .map(|instance| {
+                let size = match &self.instance_byte_sizes {
+                    Some(byte_sizes) => instance.byte_size(byte_sizes),
+                    None => 0,
+                };
+
+                (instance.referent(), size)
+            })
+            .collect();
+
+        // This sort is stable, which preserves dom order for equal sizes.
+        sizes.sort_by_key(|(_, size)| Reverse(*size));
+        sizes.into_iter()
+    }
+}
+
+impl WeakDom {
+    /// Returns statistics about each class of instance in the dom, including
+    /// the root, ordered by how many instances there are of each, most first.
+    /// Classes with the same number of instances are ordered by name.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Model").with_children([
+    ///     InstanceBuilder::new("Part").with_property("Anchored", true),
+    ///     InstanceBuilder::new("Part"),
+    /// ]));
+    ///
+    /// let stats = dom.class_stats();
+    /// assert_eq!(stats[0].class, "Part");
+    /// assert_eq!(stats[0].instance_count, 2);
+    /// assert_eq!(stats[0].property_count, 1);
+    /// assert_eq!(stats[0].bytes, None);
+    /// ```
+    pub fn class_stats(&self) -> Vec<ClassStats> {
+        let mut by_class: SymbolMap<ClassStats> = SymbolMap::default();
+
+        for instance in self.descendants() {
+            let stats = by_class
+                .entry(instance.class.clone())
+                .or_insert(ClassStats {
+                    class: instance.class.clone(),
+                    instance_count: 0,
+                    property_count: 0,
+                    bytes: self.instance_byte_sizes.as_ref().map(|_| 0),
+                });
+
+            stats.instance_count += 1;
+            stats.property_count += instance.properties.len();
+
+            if let (Some(bytes), Some(byte_sizes)) = (&mut stats.bytes, &self.instance_byte_sizes) {
+                *bytes += instance.byte_size(byte_sizes);
+            }
+        }
+
+        let mut stats: Vec<ClassStats> = by_class.into_values().collect();
+        stats.sort_by(|a, b| {
+            b.instance_count
+                .cmp(&a.instance_count)
+                .then_with(|| a.class.as_str().cmp(b.class.as_str()))
+        });
+        stats
+    }
+}
+
+/// Picks the `limit` largest instances according to `sizes`. Ties are broken
+/// by the order the instances appear in the dom so that reports are stable.
+fn rank(
+    dom: &WeakDom,
+    instances: &[&Instance],
+    sizes: &AHashMap<Ref, usize>,
+    limit: usize,
+) -> Vec<InstanceSize> {
+    let mut ranked: Vec<(usize, &Instance)> = instances
+        .iter()
+        .map(|instance| (sizes[&instance.referent()], *instance))
+        .collect();
+
+    // This sort is stable, which preserves dom order for equal sizes.
+    ranked.sort_by_key(|(size, _)| Reverse(*size));
+    ranked.truncate(limit);
+
+    ranked
+        .into_iter()
+        .map(|(bytes, instance)| InstanceSize {
+            referent: instance.referent(),
+            full_name: full_name(dom, instance),
+            class: instance.class.clone(),
+            bytes,
+        })
+        .collect()
+}
+
+fn full_name(dom: &WeakDom, instance: &Instance) -> String {
+    let mut components: Vec<&str> = dom
+        .ancestors_of(instance.referent())
+        .map(|ancestor| ancestor.name.as_str())
+        .collect();
+    components.reverse();
+    components.join(".")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn ranks_instances_subtrees_and_classes() {
+        let big = InstanceBuilder::new("Part").with_name("Big");
+        let small = InstanceBuilder::new("Part").with_name("Small");
+        let folder = InstanceBuilder::new("Folder").with_name("Folder");
+
+        let big_ref = big.referent();
+        let small_ref = small.referent();
+        let folder_ref = folder.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(folder.with_child(small))
+                .with_child(big),
+        );
+
+        let mut sizes = HashMap::default();
+        sizes.insert(big_ref, 100);
+        sizes.insert(small_ref, 60);
+        sizes.insert(folder_ref, 50);
+
+        let report = SizeReport::from_ref_sizes(&dom, &sizes, 2);
+
+        assert_eq!(report.total_bytes, 210);
+
+        let largest: Vec<_> = report
+            .largest_instances
+            .iter()
+            .map(|entry| (entry.full_name.as_str(), entry.bytes))
+            .collect();
+        assert_eq!(
+            largest,
+            [("DataModel.Big", 100), ("DataModel.Folder.Small", 60)]
+        );
+
+        let subtrees: Vec<_> = report
+            .largest_subtrees
+            .iter()
+            .map(|entry| (entry.full_name.as_str(), entry.bytes))
+            .collect();
+        assert_eq!(subtrees, [("DataModel", 210), ("DataModel.Folder", 110)]);
+
+        let classes: Vec<_> = report
+            .by_class
+            .iter()
+            .map(|entry| (entry.class.as_str(), entry.instance_count, entry.bytes))
+            .collect();
+        assert_eq!(
+            classes,
+            [("Part", 2, 160), ("Folder", 1, 50), ("DataModel", 1, 0)]
+        );
+    }
+
+    #[test]
+    fn class_stats() {
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Model").with_children([
+                InstanceBuilder::new("Part")
+                    .with_binary_referent(0)
+                    .with_property("Anchored", true)
+                    .with_property("Locked", true),
+                InstanceBuilder::new("Part")
+                    .with_binary_referent(1)
+                    .with_property("Anchored", false),
+                InstanceBuilder::new("Folder").with_binary_referent(2),
+            ]),
+        );
+
+        let summary = |dom: &WeakDom| -> Vec<_> {
+            dom.class_stats()
+                .into_iter()
+                .map(|stats| {
+                    (
+                        stats.class.as_str().to_owned(),
+                        stats.instance_count,
+                        stats.property_count,
+                        stats.bytes,
+                    )
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            summary(&dom),
+            [
+                ("Part".to_owned(), 2, 3, None),
+                ("Folder".to_owned(), 1, 0, None),
+                ("Model".to_owned(), 1, 0, None),
+            ]
+        );
+
+        dom.instance_byte_sizes = Some([(0, 30), (1, 20), (2, 5)].iter().copied().collect());
+        assert_eq!(
+            summary(&dom),
+            [
+                ("Part".to_owned(), 2, 3, Some(50)),
+                ("Folder".to_owned(), 1, 0, Some(5)),
+                ("Model".to_owned(), 1, 0, Some(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn instances_by_size() {
+        let big = InstanceBuilder::new("Part").with_binary_referent(0);
+        let small = InstanceBuilder::new("Part").with_binary_referent(1);
+        let tied = InstanceBuilder::new("Part").with_binary_referent(2);
+        let (big_ref, small_ref, tied_ref) = (big.referent(), small.referent(), tied.referent());
+
+        let mut dom =
+            WeakDom::new(InstanceBuilder::new("Folder").with_children([small, big, tied]));
+        let root_ref = dom.root_ref();
+        assert!(dom.instances_by_size().all(|(_, size)| size == 0));
+
+        dom.instance_byte_sizes = Some([(0, 100), (1, 40), (2, 40)].iter().copied().collect());
+        let sizes: Vec<(Ref, usize)> = dom.instances_by_size().collect();
+        assert_eq!(
+            sizes,
+            [
+                (big_ref, 100),
+                (small_ref, 40),
+                (tied_ref, 40),
+                (root_ref, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn content_weight_report() {
+        use crate::types::ContentId;
+
+        let sound =
+            |id: &str| InstanceBuilder::new("Sound").with_property("SoundId", ContentId::from(id));
+        let music = InstanceBuilder::new("Folder")
+            .with_name("Music")
+            .with_children([sound("rbxassetid://1"), sound("rbxassetid://1")]);
+        let music_ref = music.referent();
+        let effects = InstanceBuilder::new("Folder")
+            .with_name("Effects")
+            .with_children([sound("rbxassetid://1"), sound("rbxassetid://2")]);
+        let effects_ref = effects.referent();
+        let missing = sound("rbxassetid://3");
+        let missing_ref = missing.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("SoundService").with_children([music, effects, missing]),
+        );
+
+        let mut sizes = HashMap::default();
+        sizes.insert(effects_ref, 10);
+        sizes.insert(missing_ref, 5);
+
+        let resolver = |asset: &Asset| match asset.id {
+            Some(1) => Some(1000),
+            Some(2) => Some(300),
+            _ => None,
+        };
+        let report = ContentWeightReport::with_sizes(&dom, &resolver, 4, |instance| {
+            sizes.get(&instance.referent()).copied().unwrap_or(0)
+        });
+
+        assert_eq!(report.total_instance_bytes, 15);
+        assert_eq!(report.total_asset_bytes, 1300);
+        assert_eq!(report.unresolved_assets, ["rbxassetid://3"]);
+
+        let subtrees: Vec<_> = report
+            .heaviest_subtrees
+            .iter()
+            .map(|subtree| {
+                (
+                    subtree.full_name.as_str(),
+                    subtree.instance_bytes,
+                    subtree.asset_bytes,
+                    subtree.asset_count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            subtrees,
+            [
+                ("SoundService", 15, 1300, 3),
+                ("SoundService.Effects", 10, 1300, 2),
+                ("SoundService.Music", 0, 1000, 1),
+                ("SoundService.Music.Sound", 0, 1000, 1),
+            ]
+        );
+        assert_eq!(report.heaviest_subtrees[2].referent, music_ref);
+    }
+}
@@ -0,0 +1,145 @@
+use std::ops::Index;
+
+use ahash::AHashMap;
+
+use crate::{instance::Instance, types::Ref, WeakDom};
+
+/// A read-only view of every instance in a [`WeakDom`], numbered with dense
+/// indices, returned by [`WeakDom::as_slice`].
+///
+/// Instances are borrowed, so the dom can't be changed while the view exists.
+/// Because the view can be shared between threads, it can be used to split
+/// work over every instance between threads, like with
+/// [rayon](https://docs.rs/rayon)'s `par_iter` on [`DomSlice::as_slice`].
+///
+/// ```
+/// use rbx_dom_weak::{Instance, InstanceBuilder, WeakDom};
+///
+/// fn count_parts(instances: &[&Instance]) -> usize {
+///     instances.iter().filter(|instance| instance.class == "Part").count()
+/// }
+///
+/// let dom = WeakDom::new(
+///     InstanceBuilder::new("Folder")
+///         .with_child(InstanceBuilder::new("Part"))
+///         .with_child(InstanceBuilder::new("Part")),
+/// );
+///
+/// let slice = dom.as_slice();
+/// let (first, second) = slice.as_slice().split_at(slice.len() / 2);
+///
+/// let parts = std::thread::scope(|scope| {
+///     let first = scope.spawn(|| count_parts(first));
+///     count_parts(second) + first.join().unwrap()
+/// });
+/// assert_eq!(parts, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DomSlice<'a> {
+    instances: Vec<&'a Instance>,
+    indices: AHashMap<Ref, usize>,
+}
+
+impl<'a> DomSlice<'a> {
+    /// Returns the number of instances in the view.
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// Returns whether the view has no instances.
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Returns the instance at `index`, if there is one.
+    pub fn get(&self, index: usize) -> Option<&'a Instance> {
+        self.instances.get(index).copied()
+    }
+
+    /// Returns the index of the instance with the given referent, if it's in
+    /// the view.
+    pub fn index_of(&self, referent: Ref) -> Option<usize> {
+        self.indices.get(&referent).copied()
+    }
+
+    /// Returns every instance in the view, ordered by index.
+    pub fn as_slice(&self) -> &[&'a Instance] {
+        &self.instances
+    }
+
+    /// Returns an iterator over every instance in the view, along with its
+    /// index.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &'a Instance)> + '_ {
+        self.instances.iter().copied().enumerate()
+    }
+}
+
+impl<'a> Index<usize> for DomSlice<'a> {
+    type Output = Instance;
+
+    fn index(&self, index: usize) -> &Instance {
+        self.instances[index]
+    }
+}
+
+impl WeakDom {
+    /// Returns a read-only view of every instance under the root of the dom,
+    /// including the root itself, numbered in the same order as
+    /// [`WeakDom::descendants`]. The root always has index `0`.
+    ///
+    /// Building the view visits every instance once, so it should be reused
+    /// rather than built again for each lookup.
+    pub fn as_slice(&self) -> DomSlice<'_> {
+        let instances: Vec<&Instance> = self.descendants().collect();
+        let indices = instances
+            .iter()
+            .enumerate()
+            .map(|(index, instance)| (instance.referent(), index))
+            .collect();
+
+        DomSlice { instances, indices }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn as_slice() {
+        let part = InstanceBuilder::new("Part");
+        let part_ref = part.referent();
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Model")
+                .with_child(InstanceBuilder::new("Folder").with_child(part))
+                .with_child(InstanceBuilder::new("Decal")),
+        );
+        let orphan_ref = dom.insert(Ref::none(), InstanceBuilder::new("Sky"));
+
+        let slice = dom.as_slice();
+        assert_eq!(slice.len(), 4);
+        assert_eq!(slice[0].referent(), dom.root_ref());
+        assert_eq!(slice.index_of(orphan_ref), None);
+
+        let index = slice.index_of(part_ref).unwrap();
+        assert_eq!(slice.get(index).unwrap().referent(), part_ref);
+        assert!(slice.get(slice.len()).is_none());
+
+        let classes: Vec<&str> = slice
+            .iter()
+            .map(|(index, instance)| {
+                assert_eq!(slice.index_of(instance.referent()), Some(index));
+                instance.class.as_str()
+            })
+            .collect();
+        assert_eq!(classes, ["Model", "Folder", "Decal", "Part"]);
+    }
+
+    #[test]
+    fn send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<DomSlice>();
+    }
+}
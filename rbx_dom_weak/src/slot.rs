@@ -0,0 +1,65 @@
+//! How a [`WeakDom`][crate::WeakDom] stores its instances.
+//!
+//! With the `snapshots` feature, every instance is kept behind an `Arc` so
+//! that snapshots and forks can share it, and changing an instance only
+//! copies it if it's shared. Without the feature, instances are stored
+//! directly in the dom, which avoids an allocation and a pointer to follow
+//! for every instance.
+
+use crate::instance::Instance;
+
+/// The storage for a single instance in a dom.
+#[cfg(feature = "snapshots")]
+pub(crate) type Slot = std::sync::Arc<Instance>;
+
+/// The storage for a single instance in a dom.
+#[cfg(not(feature = "snapshots"))]
+pub(crate) type Slot = Instance;
+
+#[inline]
+pub(crate) fn new(instance: Instance) -> Slot {
+    #[cfg(feature = "snapshots")]
+    {
+        std::sync::Arc::new(instance)
+    }
+
+    #[cfg(not(feature = "snapshots"))]
+    {
+        instance
+    }
+}
+
+#[inline]
+pub(crate) fn get(slot: &Slot) -> &Instance {
+    slot
+}
+
+/// Returns a mutable reference to the instance in `slot`, copying it first
+/// if it's shared with another dom.
+#[inline]
+pub(crate) fn make_mut(slot: &mut Slot) -> &mut Instance {
+    #[cfg(feature = "snapshots")]
+    {
+        std::sync::Arc::make_mut(slot)
+    }
+
+    #[cfg(not(feature = "snapshots"))]
+    {
+        slot
+    }
+}
+
+/// Takes the instance out of `slot`, copying it if it's shared with another
+/// dom.
+#[inline]
+pub(crate) fn into_inner(slot: Slot) -> Instance {
+    #[cfg(feature = "snapshots")]
+    {
+        std::sync::Arc::try_unwrap(slot).unwrap_or_else(|slot| (*slot).clone())
+    }
+
+    #[cfg(not(feature = "snapshots"))]
+    {
+        slot
+    }
+}
@@ -0,0 +1,151 @@
+use serde::Serialize;
+
+use crate::{instance::Instance, types::Ref, Symbol, WeakDom, SCRIPT_CLASSES};
+
+/// An instance in a Rojo-style `sourcemap.json`, which editor and language
+/// server integrations use to map instances in a place to the files they
+/// came from.
+///
+/// The whole sourcemap is the node for its root instance. Because
+/// `SourcemapNode` implements `Serialize`, it can be written with
+/// [`SourcemapNode::to_json`] or by any other serde serializer.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, SourcemapNode, WeakDom};
+///
+/// let dom = WeakDom::new(
+///     InstanceBuilder::new("DataModel").with_child(
+///         InstanceBuilder::new("ServerScriptService")
+///             .with_child(InstanceBuilder::new("Script").with_name("Main")),
+///     ),
+/// );
+///
+/// let sourcemap = SourcemapNode::new(&dom, dom.root_ref(), |instance| {
+///     if instance.class == "Script" {
+///         vec![format!("src/server/{}.server.luau", instance.name)]
+///     } else {
+///         Vec::new()
+///     }
+/// });
+///
+/// let service = &sourcemap.children[0];
+/// assert_eq!(service.children[0].file_paths, ["src/server/Main.server.luau"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcemapNode {
+    /// The name of the instance.
+    pub name: String,
+
+    /// The class of the instance.
+    pub class_name: Symbol,
+
+    /// The files the instance came from, which are left out of the JSON if
+    /// there aren't any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub file_paths: Vec<String>,
+
+    /// The instance's children, in order, which are left out of the JSON if
+    /// there aren't any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SourcemapNode>,
+}
+
+impl SourcemapNode {
+    /// Builds the sourcemap of the instance with the given referent and its
+    /// descendants. `file_paths` is called with each instance to find the
+    /// files it came from, which may be none.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn new<F>(dom: &WeakDom, referent: Ref, mut file_paths: F) -> Self
+    where
+        F: FnMut(&Instance) -> Vec<String>,
+    {
+        let instance = dom.get_by_ref(referent).unwrap_or_else(|| {
+            panic!("cannot build the sourcemap of an instance that does not exist")
+        });
+
+        Self::build(dom, instance, &mut file_paths)
+    }
+
+    fn build<F>(dom: &WeakDom, instance: &Instance, file_paths: &mut F) -> Self
+    where
+        F: FnMut(&Instance) -> Vec<String>,
+    {
+        Self {
+            name: instance.name.clone(),
+            class_name: instance.class.clone(),
+            file_paths: file_paths(instance),
+            children: instance
+                .children()
+                .iter()
+                .map(|child| Self::build(dom, &dom.instances[child], file_paths))
+                .collect(),
+        }
+    }
+
+    /// Removes every descendant that isn't a script and has no scripts
+    /// among its own descendants, like Rojo does unless it's asked to include
+    /// non-scripts. Scripts are instances of the [`SCRIPT_CLASSES`].
+    pub fn retain_scripts(&mut self) {
+        self.children.retain_mut(|child| {
+            child.retain_scripts();
+            !child.children.is_empty() || SCRIPT_CLASSES.contains(&child.class_name.as_str())
+        });
+    }
+
+    /// Writes the sourcemap as JSON, in the same form as Rojo's
+    /// `sourcemap.json`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn sourcemap() {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("DataModel").with_children([
+                InstanceBuilder::new("ReplicatedStorage").with_child(
+                    InstanceBuilder::new("Folder")
+                        .with_name("Shared")
+                        .with_child(InstanceBuilder::new("ModuleScript").with_name("Util"))
+                        .with_child(InstanceBuilder::new("Folder").with_name("Assets")),
+                ),
+                InstanceBuilder::new("Lighting"),
+            ]),
+        );
+
+        let mut sourcemap = SourcemapNode::new(&dom, dom.root_ref(), |instance| {
+            match instance.class.as_str() {
+                "DataModel" => vec!["default.project.json".to_owned()],
+                "ModuleScript" => vec![format!("src/shared/{}.luau", instance.name)],
+                _ => Vec::new(),
+            }
+        });
+
+        assert_eq!(
+            sourcemap.to_json(),
+            concat!(
+                r#"{"name":"DataModel","className":"DataModel","filePaths":["default.project.json"],"children":["#,
+                r#"{"name":"ReplicatedStorage","className":"ReplicatedStorage","children":["#,
+                r#"{"name":"Shared","className":"Folder","children":["#,
+                r#"{"name":"Util","className":"ModuleScript","filePaths":["src/shared/Util.luau"]},"#,
+                r#"{"name":"Assets","className":"Folder"}]}]},"#,
+                r#"{"name":"Lighting","className":"Lighting"}]}"#,
+            )
+        );
+
+        sourcemap.retain_scripts();
+        assert_eq!(sourcemap.children.len(), 1);
+        let shared = &sourcemap.children[0].children[0];
+        assert_eq!(shared.children.len(), 1);
+        assert_eq!(shared.children[0].name, "Util");
+    }
+}
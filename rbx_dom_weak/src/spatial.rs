@@ -0,0 +1,379 @@
+use ahash::AHashSet;
+
+use crate::{
+    instance::Instance,
+    types::{Ref, Region3, Variant, Vector3},
+    WeakDom,
+};
+
+/// The classes whose instances take up space in the world, according to
+/// their `CFrame` and `Size` properties.
+pub const PART_CLASSES: &[&str] = &[
+    "Part",
+    "WedgePart",
+    "CornerWedgePart",
+    "TrussPart",
+    "SpawnLocation",
+    "Seat",
+    "VehicleSeat",
+    "SkateboardPlatform",
+    "MeshPart",
+    "PartOperation",
+    "UnionOperation",
+    "NegateOperation",
+    "IntersectOperation",
+];
+
+/// Which instances [`WeakDom::find_in_region`] finds.
+///
+/// ```
+/// use rbx_dom_weak::{types::{Region3, Vector3}, RegionQuery};
+///
+/// let region = Region3::new(Vector3::new(-10.0, 0.0, -10.0), Vector3::new(10.0, 20.0, 10.0));
+/// let query = RegionQuery::new(region).with_contained(true).with_whole_models(true);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegionQuery {
+    region: Region3,
+    contained: bool,
+    whole_models: bool,
+}
+
+impl RegionQuery {
+    /// Creates a `RegionQuery` that finds every part that touches `region`.
+    pub fn new(region: Region3) -> Self {
+        Self {
+            region,
+            contained: false,
+            whole_models: false,
+        }
+    }
+
+    /// Sets whether to only find parts that are entirely inside of the
+    /// region, rather than every part that touches it.
+    pub fn with_contained(mut self, contained: bool) -> Self {
+        self.contained = contained;
+        self
+    }
+
+    /// Sets whether to find the outermost `Model` that each matching part is
+    /// inside of instead of the part itself, so that models are kept whole.
+    /// Parts that aren't inside of a `Model` are still found on their own.
+    pub fn with_whole_models(mut self, whole_models: bool) -> Self {
+        self.whole_models = whole_models;
+        self
+    }
+
+    fn matches(&self, bounds: &Region3) -> bool {
+        let region = &self.region;
+
+        if self.contained {
+            (0..3).all(|axis| {
+                axis_of(region.min, axis) <= axis_of(bounds.min, axis)
+                    && axis_of(bounds.max, axis) <= axis_of(region.max, axis)
+            })
+        } else {
+            (0..3).all(|axis| {
+                axis_of(region.min, axis) <= axis_of(bounds.max, axis)
+                    && axis_of(bounds.min, axis) <= axis_of(region.max, axis)
+            })
+        }
+    }
+}
+
+impl WeakDom {
+    /// Finds every part in the dom that's in the region described by `query`,
+    /// in the order they appear in the tree. A part is any instance of one of
+    /// [`PART_CLASSES`], and its extent is worked out from its `CFrame` and
+    /// `Size` properties. Parts without both are ignored.
+    ///
+    /// The referents that are returned can be passed straight to a
+    /// serializer to write out that slice of the dom.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{
+    ///     types::{CFrame, Matrix3, Region3, Vector3},
+    ///     InstanceBuilder, RegionQuery, WeakDom,
+    /// };
+    ///
+    /// let part = |x| {
+    ///     InstanceBuilder::new("Part")
+    ///         .with_property("CFrame", CFrame::new(Vector3::new(x, 0.0, 0.0), Matrix3::identity()))
+    ///         .with_property("Size", Vector3::new(2.0, 2.0, 2.0))
+    /// };
+    /// let near = part(0.0);
+    /// let near_ref = near.referent();
+    ///
+    /// let dom = WeakDom::new(InstanceBuilder::new("Workspace").with_children([near, part(100.0)]));
+    ///
+    /// let region = Region3::new(Vector3::new(-5.0, -5.0, -5.0), Vector3::new(5.0, 5.0, 5.0));
+    /// assert_eq!(dom.find_in_region(&RegionQuery::new(region)), [near_ref]);
+    /// ```
+    pub fn find_in_region(&self, query: &RegionQuery) -> Vec<Ref> {
+        let mut found = Vec::new();
+        let mut seen = AHashSet::new();
+
+        for instance in self.descendants() {
+            match part_bounds(instance) {
+                Some(bounds) if query.matches(&bounds) => {}
+                _ => continue,
+            }
+
+            let referent = if query.whole_models {
+                self.ancestors_of(instance.referent())
+                    .filter(|ancestor| ancestor.class == "Model")
+                    .last()
+                    .unwrap_or(instance)
+                    .referent()
+            } else {
+                instance.referent()
+            };
+
+            if seen.insert(referent) {
+                found.push(referent);
+            }
+        }
+
+        found
+    }
+
+    /// Returns the smallest world-space box that holds every part in the
+    /// subtree starting at `referent`, including the instance itself. Parts
+    /// are found and measured in the same way as [`WeakDom::find_in_region`].
+    ///
+    /// Returns `None` if there are no parts in the subtree.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{
+    ///     types::{CFrame, Matrix3, Region3, Vector3},
+    ///     InstanceBuilder, WeakDom,
+    /// };
+    ///
+    /// let part = |x| {
+    ///     InstanceBuilder::new("Part")
+    ///         .with_property("CFrame", CFrame::new(Vector3::new(x, 0.0, 0.0), Matrix3::identity()))
+    ///         .with_property("Size", Vector3::new(2.0, 2.0, 2.0))
+    /// };
+    /// let dom = WeakDom::new(InstanceBuilder::new("Model").with_children([part(0.0), part(10.0)]));
+    ///
+    /// assert_eq!(
+    ///     dom.bounding_box(dom.root_ref()),
+    ///     Some(Region3::new(Vector3::new(-1.0, -1.0, -1.0), Vector3::new(11.0, 1.0, 1.0)))
+    /// );
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn bounding_box(&self, referent: Ref) -> Option<Region3> {
+        self.descendants_of(referent)
+            .filter_map(part_bounds)
+            .reduce(|total, bounds| {
+                Region3::new(
+                    Vector3::new(
+                        total.min.x.min(bounds.min.x),
+                        total.min.y.min(bounds.min.y),
+                        total.min.z.min(bounds.min.z),
+                    ),
+                    Vector3::new(
+                        total.max.x.max(bounds.max.x),
+                        total.max.y.max(bounds.max.y),
+                        total.max.z.max(bounds.max.z),
+                    ),
+                )
+            })
+    }
+}
+
+/// Returns the world-space box that a part takes up, if it's a part with both
+/// a `CFrame` and a `Size`.
+fn part_bounds(instance: &Instance) -> Option<Region3> {
+    if !PART_CLASSES.contains(&instance.class.as_str()) {
+        return None;
+    }
+
+    let cframe = match instance.properties.get("CFrame") {
+        Some(Variant::CFrame(cframe)) => cframe,
+        _ => return None,
+    };
+    let size = match instance.properties.get("Size") {
+        Some(Variant::Vector3(size)) => size,
+        _ => return None,
+    };
+
+    // Each row of the orientation says how much each of the part's axes
+    // points along one world axis, so the box's half-size along that axis is
+    // the sum of the part's half-sizes scaled by how much they point along it.
+    let half_extent =
+        |row: Vector3| (row.x.abs() * size.x + row.y.abs() * size.y + row.z.abs() * size.z) / 2.0;
+    let orientation = &cframe.orientation;
+    let half = Vector3::new(
+        half_extent(orientation.x),
+        half_extent(orientation.y),
+        half_extent(orientation.z),
+    );
+    let position = cframe.position;
+
+    Some(Region3::new(
+        Vector3::new(
+            position.x - half.x,
+            position.y - half.y,
+            position.z - half.z,
+        ),
+        Vector3::new(
+            position.x + half.x,
+            position.y + half.y,
+            position.z + half.z,
+        ),
+    ))
+}
+
+fn axis_of(vector: Vector3, axis: usize) -> f32 {
+    match axis {
+        0 => vector.x,
+        1 => vector.y,
+        _ => vector.z,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{
+        types::{CFrame, Matrix3},
+        InstanceBuilder,
+    };
+
+    fn part(
+        class: &str,
+        position: Vector3,
+        orientation: Matrix3,
+        size: Vector3,
+    ) -> InstanceBuilder {
+        InstanceBuilder::new(class)
+            .with_property("CFrame", CFrame::new(position, orientation))
+            .with_property("Size", size)
+    }
+
+    #[test]
+    fn find_in_region() {
+        let cube = Vector3::new(2.0, 2.0, 2.0);
+
+        // A long part turned on its side so that it reaches up into the
+        // region, even though its position is below it.
+        let turned = Matrix3::new(
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+        let pole = part(
+            "Part",
+            Vector3::new(0.0, -6.0, 0.0),
+            turned,
+            Vector3::new(1.0, 1.0, 10.0),
+        );
+        let inside = part(
+            "MeshPart",
+            Vector3::new(0.0, 0.0, 0.0),
+            Matrix3::identity(),
+            cube,
+        );
+        let edge = part(
+            "Part",
+            Vector3::new(5.0, 0.0, 0.0),
+            Matrix3::identity(),
+            cube,
+        );
+        let far = part(
+            "Part",
+            Vector3::new(50.0, 0.0, 0.0),
+            Matrix3::identity(),
+            cube,
+        );
+        let in_model = part(
+            "UnionOperation",
+            Vector3::new(0.0, 2.0, 0.0),
+            Matrix3::identity(),
+            cube,
+        );
+        let not_a_part = part(
+            "Folder",
+            Vector3::new(0.0, 0.0, 0.0),
+            Matrix3::identity(),
+            cube,
+        );
+
+        let pole_ref = pole.referent();
+        let inside_ref = inside.referent();
+        let edge_ref = edge.referent();
+        let in_model_ref = in_model.referent();
+
+        let house = InstanceBuilder::new("Model")
+            .with_child(InstanceBuilder::new("Model").with_child(in_model));
+        let house_ref = house.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Workspace")
+                .with_children([pole, inside, edge, far, not_a_part, house]),
+        );
+
+        let region = Region3::new(Vector3::new(-5.0, -5.0, -5.0), Vector3::new(5.0, 5.0, 5.0));
+        let query = RegionQuery::new(region);
+
+        assert_eq!(
+            dom.find_in_region(&query),
+            [pole_ref, inside_ref, edge_ref, in_model_ref]
+        );
+        assert_eq!(
+            dom.find_in_region(&query.with_contained(true)),
+            [inside_ref, in_model_ref]
+        );
+        assert_eq!(
+            dom.find_in_region(&query.with_whole_models(true)),
+            [pole_ref, inside_ref, edge_ref, house_ref]
+        );
+    }
+
+    #[test]
+    fn bounding_box() {
+        // A part turned 45 degrees around the Y axis takes up more room along
+        // X and Z than it would facing forward.
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+        let spun = part(
+            "WedgePart",
+            Vector3::new(0.0, 0.0, 0.0),
+            Matrix3::new(
+                Vector3::new(diagonal, 0.0, diagonal),
+                Vector3::new(0.0, 1.0, 0.0),
+                Vector3::new(-diagonal, 0.0, diagonal),
+            ),
+            Vector3::new(2.0, 2.0, 2.0),
+        );
+        let tall = part(
+            "Part",
+            Vector3::new(10.0, 5.0, 0.0),
+            Matrix3::identity(),
+            Vector3::new(1.0, 10.0, 1.0),
+        );
+
+        let model = InstanceBuilder::new("Model")
+            .with_child(InstanceBuilder::new("Folder").with_child(spun))
+            .with_child(tall);
+        let model_ref = model.referent();
+        let empty = InstanceBuilder::new("Model");
+        let empty_ref = empty.referent();
+
+        let dom = WeakDom::new(InstanceBuilder::new("Workspace").with_children([model, empty]));
+
+        let bounds = dom.bounding_box(model_ref).unwrap();
+        let close = |a: Vector3, b: Vector3| {
+            (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4 && (a.z - b.z).abs() < 1e-4
+        };
+        let corner = std::f32::consts::SQRT_2;
+        assert!(close(bounds.min, Vector3::new(-corner, -1.0, -corner)));
+        assert!(close(bounds.max, Vector3::new(10.5, 10.0, corner)));
+
+        assert_eq!(dom.bounding_box(dom.root_ref()), Some(bounds));
+        assert_eq!(dom.bounding_box(empty_ref), None);
+    }
+}
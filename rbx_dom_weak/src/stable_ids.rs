@@ -0,0 +1,143 @@
+use std::str;
+
+use crate::{
+    symbol,
+    types::{Attributes, Ref, Variant},
+    WeakDom,
+};
+
+/// The attribute that [`WeakDom::set_stable_id`] stores stable IDs in.
+pub const STABLE_ID_ATTRIBUTE: &str = "RbxStableId";
+
+impl WeakDom {
+    /// Returns the stable ID given to the instance with the given referent by
+    /// [`WeakDom::set_stable_id`], if it has one.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn stable_id_of(&self, referent: Ref) -> Option<&str> {
+        let instance = self.get_by_ref(referent).unwrap_or_else(|| {
+            panic!("cannot get the stable ID of an instance that does not exist")
+        });
+
+        // String attributes are read from files as `BinaryString`s, since
+        // they aren't required to be valid UTF-8.
+        match instance.properties.get("Attributes") {
+            Some(Variant::Attributes(attributes)) => match attributes.get(STABLE_ID_ATTRIBUTE) {
+                Some(Variant::String(id)) => Some(id),
+                Some(Variant::BinaryString(id)) => str::from_utf8(id.as_ref()).ok(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Gives the instance with the given referent an ID, like a UUID, that
+    /// stays the same when the dom is written to a file and read back,
+    /// returning its previous ID. Referents are made up anew every time a file
+    /// is read, so this is how sync tools can match up the instances in a file
+    /// with the ones they came from.
+    ///
+    /// The ID is stored in the instance's [`STABLE_ID_ATTRIBUTE`] attribute,
+    /// so it's kept by rbx_binary, rbx_xml, and Roblox Studio alike.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+    /// let folder = dom.root_ref();
+    ///
+    /// dom.set_stable_id(folder, "2f1c0a88-6f6e-4f7e-9a43-b5f2d1a9c3e0");
+    /// assert_eq!(dom.stable_id_of(folder), Some("2f1c0a88-6f6e-4f7e-9a43-b5f2d1a9c3e0"));
+    /// assert_eq!(
+    ///     dom.find_by_stable_id("2f1c0a88-6f6e-4f7e-9a43-b5f2d1a9c3e0"),
+    ///     Some(folder)
+    /// );
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn set_stable_id<S: Into<String>>(&mut self, referent: Ref, id: S) -> Option<String> {
+        self.replace_stable_id(referent, Some(id.into()))
+    }
+
+    /// Removes the stable ID given to the instance with the given referent by
+    /// [`WeakDom::set_stable_id`], returning it if there was one.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn remove_stable_id(&mut self, referent: Ref) -> Option<String> {
+        self.replace_stable_id(referent, None)
+    }
+
+    /// Returns the first instance in the dom with the given stable ID,
+    /// searching in the order instances appear in the tree.
+    pub fn find_by_stable_id(&self, id: &str) -> Option<Ref> {
+        self.descendants()
+            .map(|instance| instance.referent())
+            .find(|&referent| self.stable_id_of(referent) == Some(id))
+    }
+
+    fn replace_stable_id(&mut self, referent: Ref, id: Option<String>) -> Option<String> {
+        let key = symbol("Attributes");
+        let instance = self.get_by_ref(referent).unwrap_or_else(|| {
+            panic!("cannot set the stable ID of an instance that does not exist")
+        });
+
+        let mut attributes = match instance.properties.get(&key) {
+            Some(Variant::Attributes(attributes)) => attributes.clone(),
+            _ => Attributes::new(),
+        };
+
+        let previous = match id {
+            Some(id) => attributes.insert(STABLE_ID_ATTRIBUTE.to_owned(), Variant::String(id)),
+            None => attributes.remove(STABLE_ID_ATTRIBUTE),
+        };
+
+        // Attributes are changed through `replace_property` so that the change
+        // is journaled like any other.
+        self.replace_property(referent, key, Some(Variant::Attributes(attributes)));
+
+        match previous {
+            Some(Variant::String(id)) => Some(id),
+            Some(Variant::BinaryString(id)) => String::from_utf8(id.into_vec()).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn stable_ids() {
+        let part = InstanceBuilder::new("Part")
+            .with_property("Attributes", Attributes::new().with("Health", 100.0f64));
+        let part_ref = part.referent();
+        let mut dom = WeakDom::new(InstanceBuilder::new("Model").with_child(part));
+        let model_ref = dom.root_ref();
+
+        assert_eq!(dom.stable_id_of(part_ref), None);
+        assert_eq!(dom.set_stable_id(part_ref, "a"), None);
+        assert_eq!(dom.set_stable_id(part_ref, "b"), Some("a".to_owned()));
+        dom.set_stable_id(model_ref, "c");
+
+        assert_eq!(dom.stable_id_of(part_ref), Some("b"));
+        assert_eq!(dom.find_by_stable_id("c"), Some(model_ref));
+        assert_eq!(dom.find_by_stable_id("a"), None);
+
+        // Other attributes are left alone.
+        let part = dom.get_by_ref(part_ref).unwrap();
+        let attributes = match part.properties.get("Attributes") {
+            Some(Variant::Attributes(attributes)) => attributes,
+            other => panic!("expected attributes, got {:?}", other),
+        };
+        assert_eq!(attributes.get("Health"), Some(&Variant::Float64(100.0)));
+
+        assert_eq!(dom.remove_stable_id(part_ref), Some("b".to_owned()));
+        assert_eq!(dom.stable_id_of(part_ref), None);
+    }
+}
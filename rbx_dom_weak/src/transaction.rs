@@ -0,0 +1,245 @@
+use crate::{journal::Journal, WeakDom};
+
+/// The undo and redo stacks of a [`WeakDom`].
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    undo: Vec<Journal>,
+    redo: Vec<Journal>,
+    depth: usize,
+}
+
+impl WeakDom {
+    /// Runs `f` as a single transaction. If `f` returns `Ok`, its changes are
+    /// kept and can later be undone together with [`WeakDom::undo`]. If it
+    /// returns `Err`, every change it made is rolled back before the error is
+    /// returned, leaving the dom as it was before the transaction.
+    ///
+    /// Only changes that a [`Journal`] would record are rolled back or undone,
+    /// so `f` should only change the dom through the methods listed there.
+    /// Transactions can be nested; a nested transaction that commits becomes
+    /// part of the transaction around it. If the dom is journaling, the
+    /// changes of committed transactions are recorded, while rolled back ones
+    /// leave no trace.
+    ///
+    /// If `f` panics, the changes it made before panicking are kept.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+    /// let root_ref = dom.root_ref();
+    ///
+    /// let result: Result<(), &str> = dom.transaction(|tx| {
+    ///     tx.insert(root_ref, InstanceBuilder::new("Part"));
+    ///     Err("changed my mind")
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(dom.root().children().is_empty());
+    ///
+    /// dom.transaction(|tx| {
+    ///     tx.insert(root_ref, InstanceBuilder::new("Part"));
+    ///     tx.set_property(root_ref, "Archivable", false);
+    ///     Ok::<_, ()>(())
+    /// })
+    /// .unwrap();
+    ///
+    /// dom.undo();
+    /// assert!(dom.root().children().is_empty());
+    /// dom.redo();
+    /// assert_eq!(dom.root().children().len(), 1);
+    /// ```
+    pub fn transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut WeakDom) -> Result<T, E>,
+    {
+        let outer = self.journal.replace(Journal::new());
+        self.history.depth += 1;
+
+        let result = f(self);
+
+        self.history.depth -= 1;
+        let journal = std::mem::replace(&mut self.journal, outer).unwrap_or_default();
+
+        match &result {
+            Ok(_) => {
+                // An outer transaction owns the outer journal, so it's the one
+                // that ends up on the undo stack.
+                if let Some(outer) = &mut self.journal {
+                    for entry in journal.entries() {
+                        outer.push(entry.clone());
+                    }
+                }
+
+                if self.history.depth == 0 && !journal.is_empty() {
+                    self.history.undo.push(journal);
+                    self.history.redo.clear();
+                }
+            }
+            Err(_) => {
+                let outer = self.journal.take();
+                self.revert(&journal);
+                self.journal = outer;
+            }
+        }
+
+        result
+    }
+
+    /// Undoes the most recent committed transaction that hasn't been undone,
+    /// returning whether there was one. If the dom is journaling, the changes
+    /// made to undo it are recorded.
+    ///
+    /// ## Panics
+    /// Panics if the dom was changed outside of a transaction in a way that
+    /// removed instances the transaction refers to.
+    pub fn undo(&mut self) -> bool {
+        let journal = match self.history.undo.pop() {
+            Some(journal) => journal,
+            None => return false,
+        };
+
+        self.revert(&journal);
+        self.history.redo.push(journal);
+        true
+    }
+
+    /// Redoes the most recently undone transaction, returning whether there
+    /// was one. Committing a new transaction clears the transactions that can
+    /// be redone.
+    ///
+    /// ## Panics
+    /// Panics if the dom was changed outside of a transaction in a way that
+    /// removed instances the transaction refers to.
+    pub fn redo(&mut self) -> bool {
+        let journal = match self.history.redo.pop() {
+            Some(journal) => journal,
+            None => return false,
+        };
+
+        journal.replay(self);
+        self.history.undo.push(journal);
+        true
+    }
+
+    /// Returns whether there's a transaction that [`WeakDom::undo`] can undo.
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    /// Returns whether there's a transaction that [`WeakDom::redo`] can redo.
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Forgets every transaction that could be undone or redone.
+    pub fn clear_history(&mut self) {
+        self.history.undo.clear();
+        self.history.redo.clear();
+    }
+
+    fn revert(&mut self, journal: &Journal) {
+        for entry in journal.entries().iter().rev() {
+            entry.inverse().apply(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{types::Variant, InstanceBuilder};
+
+    use super::*;
+
+    #[test]
+    fn rollback() {
+        let part = InstanceBuilder::new("Part").with_property("Anchored", true);
+        let part_ref = part.referent();
+        let folder = InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("Decal"));
+        let folder_ref = folder.referent();
+        let mut dom = WeakDom::new(InstanceBuilder::new("Model").with_children([part, folder]));
+        let root_ref = dom.root_ref();
+        let decal_ref = dom.get_by_ref(folder_ref).unwrap().children()[0];
+
+        dom.start_journal();
+        let result: Result<(), &str> = dom.transaction(|tx| {
+            tx.set_name(part_ref, "Renamed");
+            tx.set_class(part_ref, "WedgePart");
+            tx.remove_property(part_ref, "Anchored");
+            tx.transfer_within(part_ref, folder_ref);
+            tx.destroy(decal_ref);
+            let copy = tx.clone_within(folder_ref);
+            tx.transfer_within(copy, root_ref);
+            Err("rolled back")
+        });
+
+        assert_eq!(result, Err("rolled back"));
+        assert!(dom.journal().unwrap().is_empty());
+        assert!(!dom.can_undo());
+
+        assert_eq!(dom.root().children(), [part_ref, folder_ref]);
+        assert_eq!(dom.get_by_ref(folder_ref).unwrap().children(), [decal_ref]);
+        assert_eq!(dom.get_by_ref(part_ref).unwrap().name, "Part");
+        assert_eq!(dom.get_by_ref(part_ref).unwrap().class, "Part");
+        assert_eq!(
+            dom.get_by_ref(part_ref).unwrap().properties.get("Anchored"),
+            Some(&Variant::Bool(true))
+        );
+        assert_eq!(dom.descendants().count(), 4);
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Model"));
+        let root_ref = dom.root_ref();
+        let insert = |dom: &mut WeakDom, name: &str| {
+            dom.transaction(|tx| {
+                let part_ref = tx.insert(root_ref, InstanceBuilder::new("Part").with_name(name));
+
+                // A nested transaction that commits becomes part of the outer
+                // one, while one that fails is rolled back on its own.
+                tx.transaction(|tx| Ok::<_, ()>(tx.set_property(part_ref, "Anchored", true)))?;
+                tx.transaction(|tx| {
+                    tx.destroy(part_ref);
+                    Err::<(), _>(())
+                })
+                .unwrap_err();
+
+                Ok::<_, ()>(part_ref)
+            })
+            .unwrap()
+        };
+        let names = |dom: &WeakDom| -> Vec<String> {
+            dom.root()
+                .children()
+                .iter()
+                .map(|child| dom.get_by_ref(*child).unwrap().name.clone())
+                .collect()
+        };
+
+        let a_ref = insert(&mut dom, "A");
+        insert(&mut dom, "B");
+        assert_eq!(names(&dom), ["A", "B"]);
+
+        assert!(dom.undo());
+        assert_eq!(names(&dom), ["A"]);
+        assert!(dom.can_redo());
+
+        assert!(dom.undo());
+        assert!(names(&dom).is_empty());
+        assert!(!dom.undo());
+
+        assert!(dom.redo());
+        assert_eq!(names(&dom), ["A"]);
+        assert_eq!(
+            dom.get_by_ref(a_ref).unwrap().properties.get("Anchored"),
+            Some(&Variant::Bool(true))
+        );
+
+        insert(&mut dom, "C");
+        assert!(!dom.can_redo());
+        assert_eq!(names(&dom), ["A", "C"]);
+
+        dom.clear_history();
+        assert!(!dom.can_undo());
+    }
+}
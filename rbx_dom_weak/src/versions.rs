@@ -0,0 +1,368 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    journal::{InstanceSnapshot, Journal},
+    WeakDom,
+};
+
+/// One line of a version log.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Record {
+    /// The dom as it was at version 0. Always the first line of a log.
+    Base { root: InstanceSnapshot },
+
+    /// The changes that turn the previous version into `version`.
+    Patch { version: usize, journal: Journal },
+}
+
+/// Writes a dom and the changes made to it over time to an append-only
+/// version log, which [`VersionedDom::read`] can read back to rebuild the
+/// dom at any version.
+///
+/// A log holds the dom once, followed by one [`Journal`] per version, which
+/// takes up much less space than a copy of the dom for every version. Each
+/// record is written as a single line of JSON, so a log can be appended to
+/// while it's being read and a write that's cut off partway only loses the
+/// version being written.
+///
+/// Only the dom's instances are stored, not its [`Metadata`][crate::Metadata]
+/// or anything else that isn't recorded in a journal.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, VersionLogWriter, VersionedDom, WeakDom};
+///
+/// let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+/// let mut log = Vec::new();
+/// let mut writer = VersionLogWriter::create(&mut log, &dom)?;
+///
+/// dom.start_journal();
+/// let root_ref = dom.root_ref();
+/// dom.insert(root_ref, InstanceBuilder::new("Part"));
+/// writer.write_version(&dom.stop_journal().unwrap())?;
+///
+/// let versioned = VersionedDom::read(log.as_slice())?;
+/// assert_eq!(versioned.latest_version(), 1);
+/// assert!(versioned.dom_at(0).unwrap().root().children().is_empty());
+/// assert_eq!(versioned.dom_at(1).unwrap().root().children().len(), 1);
+/// # Ok::<(), rbx_dom_weak::VersionLogError>(())
+/// ```
+pub struct VersionLogWriter<W: Write> {
+    writer: W,
+    version: usize,
+}
+
+impl<W: Write> VersionLogWriter<W> {
+    /// Starts a new version log in `writer`, storing `dom` as version 0.
+    pub fn create(writer: W, dom: &WeakDom) -> Result<Self, VersionLogError> {
+        let mut log = Self { writer, version: 0 };
+        log.write_record(&Record::Base {
+            root: InstanceSnapshot::new(dom, dom.root_ref()),
+        })?;
+
+        Ok(log)
+    }
+
+    /// Continues an existing version log whose latest version is `version`.
+    /// `writer` should append to the end of the log, like a file opened with
+    /// [`OpenOptions::append`][std::fs::OpenOptions::append], and `version`
+    /// is usually [`VersionedDom::latest_version`] of the same log.
+    pub fn append(writer: W, version: usize) -> Self {
+        Self { writer, version }
+    }
+
+    /// Writes the changes recorded in `journal` as a new version, returning
+    /// the number of that version. The log is flushed before this returns.
+    pub fn write_version(&mut self, journal: &Journal) -> Result<usize, VersionLogError> {
+        let version = self.version + 1;
+        self.write_record(&Record::Patch {
+            version,
+            journal: journal.clone(),
+        })?;
+        self.version = version;
+
+        Ok(version)
+    }
+
+    /// Returns the latest version in the log.
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// Returns the writer the log is being written to.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_record(&mut self, record: &Record) -> Result<(), VersionLogError> {
+        let mut line = serde_json::to_vec(record).map_err(VersionLogError::Serialize)?;
+        line.push(b'\n');
+
+        // Writing the whole line at once keeps other writers appending to the
+        // same file from interleaving with it.
+        self.writer.write_all(&line)?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// A dom and every version of it, read from a log written by
+/// [`VersionLogWriter`].
+#[derive(Debug, Clone)]
+pub struct VersionedDom {
+    base: InstanceSnapshot,
+    patches: Vec<Journal>,
+}
+
+impl VersionedDom {
+    /// Reads a version log from `reader`.
+    ///
+    /// If the last line of the log is incomplete, as happens when a write is
+    /// interrupted, it's ignored and the log is read up to the version before
+    /// it. Any other invalid line is an error.
+    pub fn read<R: BufRead>(mut reader: R) -> Result<Self, VersionLogError> {
+        let mut base = None;
+        let mut patches = Vec::new();
+        let mut line = String::new();
+
+        for line_number in 1.. {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+
+            let record = match serde_json::from_str(&line) {
+                Ok(record) => record,
+                Err(_) if !line.ends_with('\n') => break,
+                Err(error) => {
+                    return Err(VersionLogError::InvalidRecord {
+                        line: line_number,
+                        error,
+                    })
+                }
+            };
+
+            match (record, &base) {
+                (Record::Base { root }, None) => base = Some(root),
+                (Record::Patch { version, journal }, Some(_)) => {
+                    if version != patches.len() + 1 {
+                        return Err(VersionLogError::UnexpectedVersion {
+                            line: line_number,
+                            expected: patches.len() + 1,
+                            version,
+                        });
+                    }
+
+                    patches.push(journal);
+                }
+                (Record::Base { .. }, Some(_)) | (Record::Patch { .. }, None) => {
+                    return Err(VersionLogError::MisplacedBase { line: line_number })
+                }
+            }
+        }
+
+        let base = base.ok_or(VersionLogError::MisplacedBase { line: 1 })?;
+        Ok(Self { base, patches })
+    }
+
+    /// Returns the latest version in the log. Version 0 is the dom the log
+    /// was created with.
+    pub fn latest_version(&self) -> usize {
+        self.patches.len()
+    }
+
+    /// Returns the changes that turned the version before `version` into
+    /// `version`, or `None` if there's no such version or `version` is 0.
+    pub fn patch(&self, version: usize) -> Option<&Journal> {
+        version
+            .checked_sub(1)
+            .and_then(|index| self.patches.get(index))
+    }
+
+    /// Rebuilds the dom as it was at `version`, or returns `None` if the log
+    /// doesn't have that version. Instances keep the referents they had when
+    /// the log was written.
+    ///
+    /// ## Panics
+    /// Panics if a patch refers to an instance that isn't in the dom, which
+    /// means the journals written to the log weren't recorded one after
+    /// another from the same dom.
+    pub fn dom_at(&self, version: usize) -> Option<WeakDom> {
+        if version > self.latest_version() {
+            return None;
+        }
+
+        let mut dom = WeakDom::new(self.base.to_builder());
+        for journal in &self.patches[..version] {
+            journal.replay(&mut dom);
+        }
+
+        Some(dom)
+    }
+
+    /// Rebuilds the dom as it was at the latest version.
+    pub fn latest(&self) -> WeakDom {
+        self.dom_at(self.latest_version()).unwrap()
+    }
+}
+
+/// An error that can occur while reading or writing a version log.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum VersionLogError {
+    /// Reading from or writing to the log failed.
+    Io(io::Error),
+
+    /// A record couldn't be turned into JSON.
+    Serialize(serde_json::Error),
+
+    /// A line of the log wasn't a valid record.
+    InvalidRecord {
+        /// The line the record was on, starting from 1.
+        line: usize,
+        /// The error the record failed to parse with.
+        error: serde_json::Error,
+    },
+
+    /// The log didn't start with the dom it was created with, or had it more
+    /// than once.
+    MisplacedBase {
+        /// The line the problem was found on, starting from 1.
+        line: usize,
+    },
+
+    /// A version was missing from the log or out of order.
+    UnexpectedVersion {
+        /// The line the version was on, starting from 1.
+        line: usize,
+        /// The version that should have been on the line.
+        expected: usize,
+        /// The version that was on the line.
+        version: usize,
+    },
+}
+
+impl From<io::Error> for VersionLogError {
+    fn from(error: io::Error) -> Self {
+        VersionLogError::Io(error)
+    }
+}
+
+impl fmt::Display for VersionLogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionLogError::Io(error) => write!(f, "version log I/O error: {}", error),
+            VersionLogError::Serialize(error) => {
+                write!(f, "could not write version log record: {}", error)
+            }
+            VersionLogError::InvalidRecord { line, error } => {
+                write!(f, "invalid version log record on line {}: {}", line, error)
+            }
+            VersionLogError::MisplacedBase { line } => write!(
+                f,
+                "version log must start with exactly one base record (line {})",
+                line
+            ),
+            VersionLogError::UnexpectedVersion {
+                line,
+                expected,
+                version,
+            } => write!(
+                f,
+                "expected version {} on line {} of version log, but found version {}",
+                expected, line, version
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VersionLogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VersionLogError::Io(error) => Some(error),
+            VersionLogError::Serialize(error) => Some(error),
+            VersionLogError::InvalidRecord { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn versions() {
+        let part = InstanceBuilder::new("Part").with_property("Anchored", false);
+        let part_ref = part.referent();
+        let mut dom = WeakDom::new(InstanceBuilder::new("Model").with_child(part));
+
+        let mut log = Vec::new();
+        let mut writer = VersionLogWriter::create(&mut log, &dom).unwrap();
+
+        dom.start_journal();
+        dom.set_property(part_ref, "Anchored", true);
+        assert_eq!(
+            writer.write_version(&dom.stop_journal().unwrap()).unwrap(),
+            1
+        );
+
+        dom.start_journal();
+        dom.set_name(part_ref, "Renamed");
+        let decal_ref = dom.insert(part_ref, InstanceBuilder::new("Decal"));
+        assert_eq!(
+            writer.write_version(&dom.stop_journal().unwrap()).unwrap(),
+            2
+        );
+
+        // Writing can pick up where an earlier writer left off.
+        let mut writer = VersionLogWriter::append(&mut log, 2);
+        dom.start_journal();
+        dom.destroy(decal_ref);
+        assert_eq!(
+            writer.write_version(&dom.stop_journal().unwrap()).unwrap(),
+            3
+        );
+
+        let versioned = VersionedDom::read(log.as_slice()).unwrap();
+        assert_eq!(versioned.latest_version(), 3);
+        assert!(versioned.patch(0).is_none());
+        assert_eq!(versioned.patch(2).unwrap().len(), 2);
+        assert!(versioned.dom_at(4).is_none());
+
+        let part_at = |version| {
+            let dom = versioned.dom_at(version).unwrap();
+            let part = dom.get_by_ref(part_ref).unwrap();
+            (
+                part.name.clone(),
+                part.properties.get("Anchored").cloned(),
+                part.children().len(),
+            )
+        };
+        assert_eq!(part_at(0), ("Part".to_owned(), Some(false.into()), 0));
+        assert_eq!(part_at(1), ("Part".to_owned(), Some(true.into()), 0));
+        assert_eq!(part_at(2), ("Renamed".to_owned(), Some(true.into()), 1));
+        assert_eq!(part_at(3), ("Renamed".to_owned(), Some(true.into()), 0));
+        assert_eq!(versioned.latest().root_ref(), dom.root_ref());
+
+        // A final record that was cut off partway is ignored.
+        let truncated = &log[..log.len() - 10];
+        assert_eq!(VersionedDom::read(truncated).unwrap().latest_version(), 2);
+
+        let mut missing_base = log.clone();
+        let second_line = log.iter().position(|&byte| byte == b'\n').unwrap() + 1;
+        missing_base.drain(..second_line);
+        assert!(matches!(
+            VersionedDom::read(missing_base.as_slice()),
+            Err(VersionLogError::MisplacedBase { line: 1 })
+        ));
+    }
+}
@@ -5,10 +5,9 @@ use std::{
 
 use crate::{
     types::{Ref, Variant},
-    WeakDom,
+    Symbol, WeakDom,
 };
 use serde::{Deserialize, Serialize};
-use ustr::Ustr;
 
 /// Contains state for viewing and redacting nondeterministic portions of
 /// WeakDom objects, making them suitable for usage in snapshot tests.
@@ -111,14 +110,14 @@ impl DomViewer {
                     other => ViewedValue::Other(other.clone()),
                 };
 
-                (*key, new_value)
+                (key.clone(), new_value)
             })
             .collect();
 
         ViewedInstance {
             referent: self.referent_to_id.get(&referent).unwrap().clone(),
             name: instance.name.clone(),
-            class: instance.class,
+            class: instance.class.clone(),
             properties,
             children,
         }
@@ -137,8 +136,8 @@ impl Default for DomViewer {
 pub struct ViewedInstance {
     referent: String,
     name: String,
-    class: Ustr,
-    properties: BTreeMap<Ustr, ViewedValue>,
+    class: Symbol,
+    properties: BTreeMap<Symbol, ViewedValue>,
     children: Vec<ViewedInstance>,
 }
 
@@ -0,0 +1,439 @@
+use std::{
+    io::{self, BufRead, BufReader, Cursor, Read},
+    path::Path,
+};
+
+use rbx_dom_weak::WeakDom;
+use thiserror::Error;
+
+/// The format of a Roblox model or place file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelKind {
+    /// The binary format, used by `.rbxm` and `.rbxl` files.
+    Binary,
+
+    /// The XML format, used by `.rbxmx` and `.rbxlx` files.
+    Xml,
+}
+
+impl ModelKind {
+    /// Determines the kind of a file from the extension of its path, or
+    /// returns `None` if it isn't the extension of a model or place file.
+    pub fn from_path(path: &Path) -> Option<ModelKind> {
+        log::trace!("Resolving type of file for path {}", path.display());
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("rbxm") | Some("rbxl") => Some(ModelKind::Binary),
+            Some("rbxmx") | Some("rbxlx") => Some(ModelKind::Xml),
+
+            _ => None,
+        }
+    }
+
+    /// Determines the kind of a file from its first bytes. Binary files
+    /// always start with `<roblox!`, while XML files start with a `<roblox`
+    /// tag that may be preceded by a byte order mark, whitespace, or an XML
+    /// declaration. Returns `None` if `header` isn't the start of either.
+    ///
+    /// ```
+    /// use rbx_file::ModelKind;
+    ///
+    /// let xml = b"<?xml version=\"1.0\"?><roblox version=\"4\"></roblox>";
+    /// assert_eq!(ModelKind::from_header(xml), Some(ModelKind::Xml));
+    /// assert_eq!(ModelKind::from_header(b"Hello, world!"), None);
+    /// ```
+    pub fn from_header(header: &[u8]) -> Option<ModelKind> {
+        log::trace!("Resolving type of file from {} header bytes", header.len());
+
+        if header.starts_with(b"<roblox!") {
+            return Some(ModelKind::Binary);
+        }
+
+        let header = header.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(header);
+        let start = header
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .unwrap_or(header.len());
+        let header = &header[start..];
+
+        if header.starts_with(b"<roblox") || header.starts_with(b"<?xml") {
+            Some(ModelKind::Xml)
+        } else {
+            None
+        }
+    }
+}
+
+/// A layer of compression that a whole file was wrapped in after it was
+/// written, like a `.rbxl.gz` file. This is separate from the compression of
+/// the chunks inside of binary files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrapper {
+    /// The file was compressed with gzip.
+    Gzip,
+
+    /// The file was compressed with zstd.
+    Zstd,
+}
+
+impl Wrapper {
+    /// Determines what a file is wrapped in from its first bytes, or returns
+    /// `None` if it isn't wrapped in anything rbx_file knows how to
+    /// decompress.
+    pub fn from_header(header: &[u8]) -> Option<Wrapper> {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Some(Wrapper::Gzip)
+        } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Wrapper::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// How many bytes from the start of a file are looked at to work out what's
+/// in it. XML files with more whitespace than this before their first tag
+/// aren't detected.
+const HEADER_LEN: u64 = 512;
+
+/// Reads the first [`HEADER_LEN`] bytes of `reader`, or all of it if it's
+/// shorter, returning a reader that still starts at the beginning of the
+/// file. The header is the first half of the returned [`io::Chain`].
+///
+/// A single `fill_buf` can return as little as one byte, like when reading
+/// from a pipe, so the header is read into its own buffer instead of being
+/// peeked at.
+fn peek_header<R: BufRead>(mut reader: R) -> io::Result<io::Chain<Cursor<Vec<u8>>, R>> {
+    let mut header = Vec::new();
+    (&mut reader).take(HEADER_LEN).read_to_end(&mut header)?;
+
+    Ok(Cursor::new(header).chain(reader))
+}
+
+/// The most layers of compression [`decompress_wrappers`] will remove from a
+/// file. Real files are wrapped at most once, so anything past this is far
+/// more likely to be a decompression bomb than a model.
+pub const MAX_WRAPPERS: usize = 2;
+
+/// Removes the layers of gzip or zstd compression that `reader` is wrapped
+/// in, returning a reader over the file inside of them. Files that aren't
+/// wrapped in anything are returned as they are. Files wrapped in zstd are
+/// only decompressed with the `zstd` feature, which is on by default.
+///
+/// Files wrapped in more than [`MAX_WRAPPERS`] layers are rejected with
+/// [`DecodeError::TooManyWrappers`].
+///
+/// ```
+/// use std::io::{Read, Write};
+///
+/// use flate2::{write::GzEncoder, Compression};
+///
+/// let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+/// encoder.write_all(b"<roblox!")?;
+/// let wrapped = encoder.finish()?;
+///
+/// let mut contents = Vec::new();
+/// rbx_file::decompress_wrappers(wrapped.as_slice())?.read_to_end(&mut contents)?;
+/// assert_eq!(contents, b"<roblox!");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn decompress_wrappers<'a, R: BufRead + 'a>(
+    reader: R,
+) -> Result<Box<dyn BufRead + 'a>, DecodeError> {
+    let mut reader: Box<dyn BufRead + 'a> = Box::new(reader);
+    let mut layers = 0;
+
+    loop {
+        let peeked = peek_header(reader)?;
+        let wrapper = Wrapper::from_header(peeked.get_ref().0.get_ref());
+        reader = Box::new(peeked);
+
+        let wrapper = match wrapper {
+            Some(wrapper) => wrapper,
+            None => break,
+        };
+
+        if layers == MAX_WRAPPERS {
+            return Err(DecodeError::TooManyWrappers {
+                limit: MAX_WRAPPERS,
+            });
+        }
+
+        log::trace!("Decompressing file wrapped in {wrapper:?}");
+        layers += 1;
+
+        reader = match wrapper {
+            Wrapper::Gzip => Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))),
+            #[cfg(feature = "zstd")]
+            Wrapper::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+            #[cfg(not(feature = "zstd"))]
+            Wrapper::Zstd => {
+                return Err(DecodeError::Io(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "files wrapped in zstd need rbx_file's zstd feature",
+                )))
+            }
+        };
+    }
+
+    Ok(reader)
+}
+
+/// Decodes a model or place file in either format, choosing a decoder by
+/// looking at the contents of `reader` rather than a file extension. Files
+/// wrapped in gzip or zstd are decompressed first with
+/// [`decompress_wrappers`].
+///
+/// XML files are read with
+/// [`DecodePropertyBehavior::ReadUnknown`][rbx_xml::DecodePropertyBehavior::ReadUnknown],
+/// so properties that aren't in the reflection database are kept.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+///
+/// let dom = WeakDom::new(InstanceBuilder::new("Folder"));
+/// let mut binary = Vec::new();
+/// rbx_binary::to_writer(&mut binary, &dom, &[dom.root_ref()])?;
+///
+/// let decoded = rbx_file::from_reader_auto(binary.as_slice())?;
+/// assert_eq!(decoded.root().children().len(), 1);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn from_reader_auto<'a, R: BufRead + 'a>(reader: R) -> Result<WeakDom, DecodeError> {
+    let reader = peek_header(decompress_wrappers(reader)?)?;
+
+    match ModelKind::from_header(reader.get_ref().0.get_ref()) {
+        Some(ModelKind::Xml) => {
+            let options = rbx_xml::DecodeOptions::new()
+                .property_behavior(rbx_xml::DecodePropertyBehavior::ReadUnknown);
+
+            Ok(rbx_xml::from_reader(reader, options)?)
+        }
+
+        Some(ModelKind::Binary) => Ok(rbx_binary::from_reader(reader)?),
+
+        None => Err(DecodeError::UnknownFormat),
+    }
+}
+
+/// An error that can occur while reading a file with [`from_reader_auto`]
+/// or [`decompress_wrappers`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// Reading or decompressing the file failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The file was in the binary format, but couldn't be decoded.
+    #[error(transparent)]
+    Binary(#[from] rbx_binary::DecodeError),
+
+    /// The file was in the XML format, but couldn't be decoded.
+    #[error(transparent)]
+    Xml(#[from] rbx_xml::DecodeError),
+
+    /// The file wasn't in either format.
+    #[error("not a Roblox model or place file")]
+    UnknownFormat,
+
+    /// The file was wrapped in more layers of compression than
+    /// [`decompress_wrappers`] will remove.
+    #[error("file is wrapped in more than {limit} layers of compression")]
+    TooManyWrappers {
+        /// The most layers that are removed, which is [`MAX_WRAPPERS`].
+        limit: usize,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::InstanceBuilder;
+
+    #[test]
+    fn detect_format() {
+        let dom = WeakDom::new(InstanceBuilder::new("Folder").with_name("Detected"));
+        let root_ref = dom.root_ref();
+
+        let mut binary = Vec::new();
+        rbx_binary::to_writer(&mut binary, &dom, &[root_ref]).unwrap();
+        let mut xml = Vec::new();
+        rbx_xml::to_writer_default(&mut xml, &dom, &[root_ref]).unwrap();
+
+        // XML files may start with a byte order mark and whitespace.
+        let mut padded_xml = b"\xEF\xBB\xBF\n  ".to_vec();
+        padded_xml.extend_from_slice(&xml);
+
+        for (file, kind) in [
+            (&binary, ModelKind::Binary),
+            (&xml, ModelKind::Xml),
+            (&padded_xml, ModelKind::Xml),
+        ] {
+            assert_eq!(ModelKind::from_header(file), Some(kind));
+
+            let decoded = from_reader_auto(file.as_slice()).unwrap();
+            let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+            assert_eq!(folder.name, "Detected");
+        }
+
+        assert!(matches!(
+            from_reader_auto(&b"not a model"[..]),
+            Err(DecodeError::UnknownFormat)
+        ));
+        assert_eq!(
+            ModelKind::from_path(Path::new("Place.rbxlx")),
+            Some(ModelKind::Xml)
+        );
+        assert_eq!(ModelKind::from_path(Path::new("Place.txt")), None);
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn wrappers() {
+        use std::io::Write;
+
+        use flate2::{write::GzEncoder, Compression};
+
+        let gzip = |data: &[u8]| {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        };
+        let zstd = |data: &[u8]| zstd::encode_all(data, 0).unwrap();
+
+        let dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let mut binary = Vec::new();
+        rbx_binary::to_writer(&mut binary, &dom, &[dom.root_ref()]).unwrap();
+
+        let gzipped = gzip(&binary);
+        assert_eq!(Wrapper::from_header(&gzipped), Some(Wrapper::Gzip));
+
+        let twice = zstd(&gzipped);
+        assert_eq!(Wrapper::from_header(&twice), Some(Wrapper::Zstd));
+
+        for file in [&binary, &gzipped, &twice] {
+            let decoded = from_reader_auto(file.as_slice()).unwrap();
+            assert_eq!(decoded.root().children().len(), 1);
+        }
+
+        // Layers past the limit aren't decompressed, however small they are.
+        let too_many = gzip(&twice);
+        assert!(matches!(
+            from_reader_auto(too_many.as_slice()),
+            Err(DecodeError::TooManyWrappers {
+                limit: MAX_WRAPPERS
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn wrapper_depth() {
+        use std::io::Write;
+
+        use flate2::{write::GzEncoder, Compression};
+
+        fn wrap(data: &[u8], wrapper: Wrapper) -> Vec<u8> {
+            match wrapper {
+                Wrapper::Gzip => {
+                    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                    encoder.write_all(data).unwrap();
+                    encoder.finish().unwrap()
+                }
+                Wrapper::Zstd => zstd::encode_all(data, 0).unwrap(),
+            }
+        }
+
+        let contents = b"<roblox!not really a model".to_vec();
+        let layers = [
+            vec![],
+            vec![Wrapper::Gzip],
+            vec![Wrapper::Zstd],
+            vec![Wrapper::Gzip, Wrapper::Zstd],
+            vec![Wrapper::Zstd, Wrapper::Gzip],
+            vec![Wrapper::Gzip, Wrapper::Gzip],
+            vec![Wrapper::Zstd, Wrapper::Zstd],
+        ];
+
+        for wrappers in layers {
+            assert!(wrappers.len() <= MAX_WRAPPERS);
+
+            // Wrappers are applied innermost first.
+            let wrapped = wrappers
+                .iter()
+                .fold(contents.clone(), |data, &wrapper| wrap(&data, wrapper));
+            let mut unwrapped = Vec::new();
+            decompress_wrappers(wrapped.as_slice())
+                .unwrap()
+                .read_to_end(&mut unwrapped)
+                .unwrap();
+            assert_eq!(unwrapped, contents, "unwrapping {:?}", wrappers);
+
+            // One more layer of either kind goes past the limit.
+            for extra in [Wrapper::Gzip, Wrapper::Zstd] {
+                let too_many = wrap(&wrapped, extra);
+                if wrappers.len() < MAX_WRAPPERS {
+                    assert!(decompress_wrappers(too_many.as_slice()).is_ok());
+                } else {
+                    assert!(
+                        matches!(
+                            decompress_wrappers(too_many.as_slice()),
+                            Err(DecodeError::TooManyWrappers {
+                                limit: MAX_WRAPPERS
+                            })
+                        ),
+                        "{:?} inside of {:?} should be rejected",
+                        wrappers,
+                        extra
+                    );
+                }
+            }
+        }
+
+        // Wrapped files that aren't models are still unwrapped, but can't be
+        // decoded.
+        let text = wrap(b"Hello, world!", Wrapper::Zstd);
+        assert!(matches!(
+            from_reader_auto(text.as_slice()),
+            Err(DecodeError::UnknownFormat)
+        ));
+    }
+
+    /// Returns one byte from every read, like a slow pipe can.
+    struct OneByteAtATime<'a>(&'a [u8]);
+
+    impl Read for OneByteAtATime<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match (self.0.split_first(), buf.is_empty()) {
+                (Some((&byte, rest)), false) => {
+                    buf[0] = byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn short_reads() {
+        let dom = WeakDom::new(InstanceBuilder::new("Folder").with_name("Slow"));
+        let mut binary = Vec::new();
+        rbx_binary::to_writer(&mut binary, &dom, &[dom.root_ref()]).unwrap();
+        let mut xml = b"\xEF\xBB\xBF\n".to_vec();
+        rbx_xml::to_writer_default(&mut xml, &dom, &[dom.root_ref()]).unwrap();
+        #[allow(unused_mut)]
+        let mut files = vec![binary.clone(), xml];
+        #[cfg(feature = "zstd")]
+        files.push(zstd::encode_all(binary.as_slice(), 0).unwrap());
+
+        for file in &files {
+            // A buffer this small means every fill_buf returns a single byte.
+            let reader = BufReader::with_capacity(1, OneByteAtATime(file));
+            let decoded = from_reader_auto(reader).unwrap();
+            let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+            assert_eq!(folder.name, "Slow");
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! rbx_file reads Roblox model and place files without needing to know
+//! which format they're in ahead of time.
+//!
+//! [`from_reader_auto`] reads a file in either the binary or XML format,
+//! working out which one it's in from its contents, and decompresses files
+//! that were wrapped in gzip or zstd with [`decompress_wrappers`].
+//!
+//! ```no_run
+//! use std::{fs::File, io::BufReader};
+//!
+//! let file = BufReader::new(File::open("place.rbxl")?);
+//! let dom = rbx_file::from_reader_auto(file)?;
+//!
+//! println!("{} has {} children", dom.root().name, dom.root().children().len());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## Features
+//! The `zstd` feature, which is on by default, reads files wrapped in zstd
+//! and files with Zstandard-compressed chunks. It builds the C zstd
+//! library, so it needs a C compiler for the target.
+//!
+//! The `wasm` feature adds [`wasm::Model`], which exposes opening,
+//! inspecting, and writing files to JavaScript through `wasm-bindgen`.
+
+#![deny(missing_docs)]
+
+mod detect;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use crate::detect::{
+    decompress_wrappers, from_reader_auto, DecodeError, ModelKind, Wrapper, MAX_WRAPPERS,
+};
@@ -0,0 +1,144 @@
+//! JavaScript bindings for reading and writing files in the browser, built
+//! with `wasm-bindgen` when the `wasm` feature is enabled.
+//!
+//! ```js
+//! import { Model } from "rbx_file";
+//!
+//! const model = Model.open(new Uint8Array(await file.arrayBuffer()));
+//! console.log(model.instanceCount(), JSON.parse(model.inspect()));
+//! const rbxm = model.toBinary();
+//! ```
+//!
+//! For files with Zstandard-compressed chunks to be read on
+//! `wasm32-unknown-unknown`, the `zstd` feature needs a C compiler for that
+//! target. Without it, build with `--no-default-features --features wasm`.
+
+use rbx_dom_weak::WeakDom;
+use serde_json::{json, Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::{from_reader_auto, DecodeError};
+
+/// A model or place file that has been read into memory.
+#[wasm_bindgen]
+pub struct Model {
+    dom: WeakDom,
+}
+
+#[wasm_bindgen]
+impl Model {
+    /// Reads a model or place file in either format from its bytes, like
+    /// [`from_reader_auto`].
+    pub fn open(bytes: &[u8]) -> Result<Model, JsError> {
+        Ok(Self::read(bytes)?)
+    }
+
+    /// Returns the number of instances in the file, not counting the root
+    /// that holds them.
+    #[wasm_bindgen(js_name = instanceCount)]
+    pub fn instance_count(&self) -> usize {
+        self.dom.descendants().count() - 1
+    }
+
+    /// Describes every instance in the file as a JSON array, in the order
+    /// of [`WeakDom::descendants`]. Each instance is an object with its
+    /// `referent`, `parent`, `name`, `class`, and an object mapping the
+    /// name of each of its `properties` to the name of its type.
+    pub fn inspect(&self) -> String {
+        let instances: Vec<Value> = self
+            .dom
+            .descendants()
+            .map(|instance| {
+                let properties: Map<String, Value> = instance
+                    .properties
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), json!(format!("{:?}", value.ty()))))
+                    .collect();
+
+                json!({
+                    "referent": instance.referent().to_string(),
+                    "parent": instance.parent().to_string(),
+                    "name": instance.name,
+                    "class": instance.class.as_str(),
+                    "properties": properties,
+                })
+            })
+            .collect();
+
+        Value::Array(instances).to_string()
+    }
+
+    /// Writes the file in the binary format.
+    #[wasm_bindgen(js_name = toBinary)]
+    pub fn to_binary(&self) -> Result<Vec<u8>, JsError> {
+        Ok(self.write_binary()?)
+    }
+
+    /// Writes the file in the XML format.
+    #[wasm_bindgen(js_name = toXml)]
+    pub fn to_xml(&self) -> Result<Vec<u8>, JsError> {
+        Ok(self.write_xml()?)
+    }
+}
+
+// These are kept apart from the exported methods so that they can be
+// tested natively, where creating a `JsError` panics.
+impl Model {
+    fn read(bytes: &[u8]) -> Result<Model, DecodeError> {
+        Ok(Model {
+            dom: from_reader_auto(bytes)?,
+        })
+    }
+
+    fn write_binary(&self) -> Result<Vec<u8>, rbx_binary::EncodeError> {
+        let mut buffer = Vec::new();
+        rbx_binary::to_writer(&mut buffer, &self.dom, self.dom.root().children())?;
+        Ok(buffer)
+    }
+
+    fn write_xml(&self) -> Result<Vec<u8>, rbx_xml::EncodeError> {
+        let mut buffer = Vec::new();
+        rbx_xml::to_writer_default(&mut buffer, &self.dom, self.dom.root().children())?;
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::InstanceBuilder;
+
+    #[test]
+    fn round_trip() {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("DataModel").with_child(
+                InstanceBuilder::new("Part")
+                    .with_name("Brick")
+                    .with_property("Anchored", true),
+            ),
+        );
+        let mut binary = Vec::new();
+        rbx_binary::to_writer(&mut binary, &dom, dom.root().children()).unwrap();
+
+        let model = Model::read(&binary).unwrap();
+        assert_eq!(model.instance_count(), 1);
+
+        let inspected: Value = serde_json::from_str(&model.inspect()).unwrap();
+        let part = &inspected[1];
+        assert_eq!(part["name"], "Brick");
+        assert_eq!(part["class"], "Part");
+        assert_eq!(part["parent"], inspected[0]["referent"]);
+        assert_eq!(part["properties"]["Anchored"], "Bool");
+
+        for file in [model.write_binary().unwrap(), model.write_xml().unwrap()] {
+            let reread = Model::read(&file).unwrap();
+            assert_eq!(reread.instance_count(), 1);
+        }
+
+        assert!(matches!(
+            Model::read(b"not a model"),
+            Err(DecodeError::UnknownFormat)
+        ));
+    }
+}
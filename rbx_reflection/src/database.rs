@@ -101,6 +101,93 @@ impl<'a> ReflectionDatabase<'a> {
             }
         }
     }
+
+    /// Finds both the canonical and serialized property descriptors for a
+    /// property given the name of a class that contains or inherits it. The
+    /// property may be named by its canonical name, its serialized name, or
+    /// any of its aliases, like `size` for `Size` or `Color3uint8` for `Color`.
+    ///
+    /// Returns `None` if the property is not known.
+    pub fn find_property_descriptors(
+        &'a self,
+        class_name: &str,
+        property_name: &str,
+    ) -> Option<PropertyDescriptors<'a>> {
+        let class = self.classes.get(class_name)?;
+
+        for class in self.superclasses_iter(class) {
+            let Some(descriptor) = class.properties.get(property_name) else {
+                continue;
+            };
+
+            let canonical = match &descriptor.kind {
+                PropertyKind::Canonical { .. } => descriptor,
+                PropertyKind::Alias { alias_for } => class.properties.get(alias_for.as_ref())?,
+            };
+
+            // If one property in the database calls itself an alias of
+            // another property, that property must be canonical.
+            let PropertyKind::Canonical { serialization } = &canonical.kind else {
+                return None;
+            };
+
+            let serialized = match serialization {
+                PropertySerialization::Serializes | PropertySerialization::Migrate(_) => {
+                    Some(canonical)
+                }
+                PropertySerialization::SerializesAs(serialized_name) => {
+                    class.properties.get(serialized_name.as_ref())
+                }
+                PropertySerialization::DoesNotSerialize => None,
+            };
+
+            return Some(PropertyDescriptors {
+                canonical,
+                serialized,
+            });
+        }
+
+        None
+    }
+
+    /// Returns the canonical name of a property, which is the name used by
+    /// rbx_dom_weak and the one that should be used in tools. See
+    /// [`ReflectionDatabase::find_property_descriptors`].
+    pub fn canonical_property_name(
+        &'a self,
+        class_name: &str,
+        property_name: &str,
+    ) -> Option<&'a str> {
+        self.find_property_descriptors(class_name, property_name)
+            .map(|descriptors| descriptors.canonical.name.as_ref())
+    }
+
+    /// Returns the name a property is written under in Roblox's file formats,
+    /// or `None` if the property is unknown or does not serialize. See
+    /// [`ReflectionDatabase::find_property_descriptors`].
+    pub fn serialized_property_name(
+        &'a self,
+        class_name: &str,
+        property_name: &str,
+    ) -> Option<&'a str> {
+        self.find_property_descriptors(class_name, property_name)?
+            .serialized
+            .map(|descriptor| descriptor.name.as_ref())
+    }
+}
+
+/// The canonical and serialized forms of a property, as returned by
+/// [`ReflectionDatabase::find_property_descriptors`]. These might be the same
+/// descriptor!
+#[derive(Debug, Clone, Copy)]
+pub struct PropertyDescriptors<'a> {
+    /// The canonical form of the property, which is what should be exposed to
+    /// users.
+    pub canonical: &'a PropertyDescriptor<'a>,
+
+    /// The form of the property that is written to files, or `None` if the
+    /// property does not serialize.
+    pub serialized: Option<&'a PropertyDescriptor<'a>>,
 }
 
 /// Describes a class of Instance, its properties, and its relation to other
@@ -0,0 +1,147 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use rbx_types::Enum;
+
+use crate::{DataType, ReflectionDatabase};
+
+/// Converts between the values of enums and the names of their items, like
+/// `256` and `Plastic` for the `Material` enum.
+///
+/// Items are looked up in a [`ReflectionDatabase`], but individual items can be
+/// overridden or added with [`EnumResolver::add_override`]. This is useful
+/// when working with files that use enum items that are newer than the
+/// database.
+///
+/// ```
+/// use rbx_reflection::{EnumDescriptor, EnumResolver, ReflectionDatabase};
+///
+/// let mut database = ReflectionDatabase::new();
+/// let mut material = EnumDescriptor::new("Material");
+/// material.items.insert("Plastic".into(), 256);
+/// database.enums.insert("Material".into(), material);
+///
+/// let resolver = EnumResolver::new(&database).with_override("Material", "Future", 2000);
+///
+/// assert_eq!(resolver.item_name("Material", 256), Some("Plastic"));
+/// assert_eq!(resolver.item_value("Material", "Future"), Some(2000));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EnumResolver<'a> {
+    database: &'a ReflectionDatabase<'a>,
+    overrides: HashMap<Cow<'a, str>, HashMap<Cow<'a, str>, u32>>,
+}
+
+impl<'a> EnumResolver<'a> {
+    /// Creates an `EnumResolver` that looks up items in `database`.
+    pub fn new(database: &'a ReflectionDatabase<'a>) -> Self {
+        Self {
+            database,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Adds an override for the item of `enum_name` called `item_name`, then
+    /// returns the resolver. See [`EnumResolver::add_override`].
+    pub fn with_override<E, I>(mut self, enum_name: E, item_name: I, value: u32) -> Self
+    where
+        E: Into<Cow<'a, str>>,
+        I: Into<Cow<'a, str>>,
+    {
+        self.add_override(enum_name, item_name, value);
+        self
+    }
+
+    /// Makes the item of `enum_name` called `item_name` have the given value.
+    /// Overrides take precedence over the database: an overridden item
+    /// replaces any item in the database with the same name or value.
+    pub fn add_override<E, I>(&mut self, enum_name: E, item_name: I, value: u32)
+    where
+        E: Into<Cow<'a, str>>,
+        I: Into<Cow<'a, str>>,
+    {
+        let items = self.overrides.entry(enum_name.into()).or_default();
+        items.retain(|_, existing| *existing != value);
+        items.insert(item_name.into(), value);
+    }
+
+    /// Returns the name of the item of `enum_name` with the given value, or
+    /// `None` if there isn't one.
+    pub fn item_name(&self, enum_name: &str, value: u32) -> Option<&str> {
+        let overrides = self.overrides.get(enum_name);
+
+        if let Some(overrides) = overrides {
+            let found = overrides
+                .iter()
+                .find(|(_, item_value)| **item_value == value);
+
+            if let Some((name, _)) = found {
+                return Some(name.as_ref());
+            }
+        }
+
+        let descriptor = self.database.enums.get(enum_name)?;
+        let (name, _) = descriptor.items.iter().find(|(name, item_value)| {
+            **item_value == value
+                && !overrides.is_some_and(|overrides| overrides.contains_key(name.as_ref()))
+        })?;
+
+        Some(name.as_ref())
+    }
+
+    /// Returns the value of the item of `enum_name` called `item_name`, or
+    /// `None` if there isn't one.
+    pub fn item_value(&self, enum_name: &str, item_name: &str) -> Option<u32> {
+        let overrides = self.overrides.get(enum_name);
+
+        if let Some(value) = overrides.and_then(|overrides| overrides.get(item_name)) {
+            return Some(*value);
+        }
+
+        let value = *self.database.enums.get(enum_name)?.items.get(item_name)?;
+
+        // An override with the same value replaces the database's item.
+        if overrides.is_some_and(|overrides| overrides.values().any(|other| *other == value)) {
+            return None;
+        }
+
+        Some(value)
+    }
+
+    /// Returns the name of the enum used by a property, given the name of a
+    /// class that contains or inherits it. Returns `None` if the property is
+    /// unknown or is not an enum.
+    pub fn property_enum(&self, class_name: &str, property_name: &str) -> Option<&'a str> {
+        let descriptors = self
+            .database
+            .find_property_descriptors(class_name, property_name)?;
+
+        match &descriptors.canonical.data_type {
+            DataType::Enum(enum_name) => Some(enum_name.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the item that the value of an enum property refers
+    /// to, like `Plastic` for a `Part`'s `Material` of `256`.
+    pub fn property_item_name(
+        &self,
+        class_name: &str,
+        property_name: &str,
+        value: Enum,
+    ) -> Option<&str> {
+        let enum_name = self.property_enum(class_name, property_name)?;
+        self.item_name(enum_name, value.to_u32())
+    }
+
+    /// Returns the value of an enum property that refers to the item called
+    /// `item_name`, like `256` for a `Part`'s `Material` of `Plastic`.
+    pub fn property_item_value(
+        &self,
+        class_name: &str,
+        property_name: &str,
+        item_name: &str,
+    ) -> Option<Enum> {
+        let enum_name = self.property_enum(class_name, property_name)?;
+        self.item_value(enum_name, item_name).map(Enum::from_u32)
+    }
+}
@@ -1,10 +1,12 @@
 mod class_tag;
 mod database;
+mod enums;
 mod migration;
 mod property_tag;
 mod serde_util;
 
 pub use class_tag::*;
 pub use database::*;
+pub use enums::*;
 pub use migration::*;
 pub use property_tag::*;
@@ -146,7 +146,7 @@ fn get_local_location_no_var() -> Option<PathBuf> {
 
 #[cfg(test)]
 mod test {
-    use rbx_reflection::ClassDescriptor;
+    use rbx_reflection::{ClassDescriptor, EnumResolver};
 
     use super::*;
 
@@ -236,4 +236,62 @@ mod test {
         assert!(database.has_superclass(part_class_descriptor, instance_class_descriptor));
         assert!(!database.has_superclass(instance_class_descriptor, part_class_descriptor));
     }
+
+    #[test]
+    fn property_names() {
+        let database = get_bundled();
+
+        assert_eq!(
+            database.canonical_property_name("Part", "Size"),
+            Some("Size")
+        );
+        assert_eq!(
+            database.canonical_property_name("Part", "size"),
+            Some("Size")
+        );
+        assert_eq!(
+            database.canonical_property_name("Part", "Color3uint8"),
+            Some("Color")
+        );
+        assert_eq!(
+            database.serialized_property_name("Part", "Color"),
+            Some("Color3uint8")
+        );
+        assert_eq!(
+            database.serialized_property_name("Part", "Name"),
+            Some("Name")
+        );
+        assert_eq!(
+            database.canonical_property_name("Part", "NotAProperty"),
+            None
+        );
+        assert_eq!(database.canonical_property_name("NotAClass", "Name"), None);
+    }
+
+    #[test]
+    fn enum_resolver() {
+        let database = get_bundled();
+        let mut resolver = EnumResolver::new(database);
+
+        assert_eq!(resolver.item_name("Material", 256), Some("Plastic"));
+        assert_eq!(resolver.item_value("Material", "Plastic"), Some(256));
+        assert_eq!(resolver.property_enum("Part", "Material"), Some("Material"));
+        assert_eq!(
+            resolver
+                .property_item_value("Part", "Material", "Plastic")
+                .map(|value| value.to_u32()),
+            Some(256)
+        );
+        assert_eq!(resolver.property_enum("Part", "Size"), None);
+
+        resolver.add_override("Material", "Plasticky", 256);
+        assert_eq!(resolver.item_name("Material", 256), Some("Plasticky"));
+        assert_eq!(resolver.item_value("Material", "Plastic"), None);
+        assert_eq!(
+            resolver
+                .property_item_value("Part", "Material", "Plasticky")
+                .map(|value| value.to_u32()),
+            Some(256)
+        );
+    }
 }
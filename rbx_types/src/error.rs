@@ -1,6 +1,9 @@
 use thiserror::Error;
 
-use crate::{AttributeError, MaterialColorsError, Matrix3Error, UniqueIdError};
+use crate::{
+    AttributeError, MaterialColorsError, Matrix3Error, PhysicalConfigDataError, SmoothGridError,
+    UniqueIdError,
+};
 
 /// Represents an error that occurred when using a fallible method.
 #[derive(Debug, Error)]
@@ -33,6 +36,22 @@ impl From<MaterialColorsError> for Error {
     }
 }
 
+impl From<PhysicalConfigDataError> for Error {
+    fn from(source: PhysicalConfigDataError) -> Self {
+        Self {
+            source: Box::new(source.into()),
+        }
+    }
+}
+
+impl From<SmoothGridError> for Error {
+    fn from(source: SmoothGridError) -> Self {
+        Self {
+            source: Box::new(source.into()),
+        }
+    }
+}
+
 impl From<UniqueIdError> for Error {
     fn from(source: UniqueIdError) -> Self {
         Self {
@@ -52,6 +71,12 @@ enum InnerError {
     #[error(transparent)]
     MaterialColors(#[from] MaterialColorsError),
 
+    #[error(transparent)]
+    PhysicalConfigData(#[from] PhysicalConfigDataError),
+
+    #[error(transparent)]
+    SmoothGrid(#[from] SmoothGridError),
+
     #[error(transparent)]
     UniqueId(#[from] UniqueIdError),
 }
@@ -13,11 +13,14 @@ mod faces;
 mod font;
 mod lister;
 mod material_colors;
+mod physical_config_data;
 mod physical_properties;
 mod referent;
 mod security_capabilities;
+mod sequence;
 mod shared_string;
 mod tags;
+mod terrain;
 mod unique_id;
 mod variant;
 
@@ -31,10 +34,12 @@ pub use error::*;
 pub use faces::*;
 pub use font::*;
 pub use material_colors::*;
+pub use physical_config_data::*;
 pub use physical_properties::*;
 pub use referent::*;
 pub use security_capabilities::*;
 pub use shared_string::*;
 pub use tags::*;
+pub use terrain::*;
 pub use unique_id::*;
 pub use variant::*;
@@ -116,7 +116,7 @@ macro_rules! material_colors {
 
         /// A list of all `TerrainMaterials` in the order they must be read
         /// and written.
-        const MATERIAL_ORDER: [TerrainMaterials; 21] = [$(TerrainMaterials::$name,)*];
+        pub(crate) const MATERIAL_ORDER: [TerrainMaterials; 21] = [$(TerrainMaterials::$name,)*];
 
         /// All materials that are represented by `MaterialColors`.
         #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
@@ -0,0 +1,315 @@
+use std::convert::TryInto;
+
+use thiserror::Error;
+
+use crate::{Error as CrateError, Vector3};
+
+/// The magic string that every non-empty `PhysicalConfigData` blob starts with.
+const MAGIC: &[u8] = b"CSGPHS";
+
+/// The decoded collision geometry stored in the `PhysicalConfigData` property
+/// of unions and `MeshPart` instances.
+///
+/// This type is read-only. It exists so that tooling can inspect the size and
+/// shape of CSG data embedded in a file; the original blob should be kept
+/// when writing the instance back out.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PhysicalConfigData {
+    version: u32,
+    hulls: Vec<ConvexHull>,
+}
+
+/// One of the convex pieces that make up the collision geometry described by
+/// `PhysicalConfigData`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConvexHull {
+    /// The vertices of the hull, relative to the center of the part.
+    pub vertices: Vec<Vector3>,
+    /// The triangles of the hull, as indices into `vertices`.
+    pub triangles: Vec<[u32; 3]>,
+}
+
+/// An axis-aligned box that contains a set of points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// The corner of the box with the smallest coordinates.
+    pub min: Vector3,
+    /// The corner of the box with the largest coordinates.
+    pub max: Vector3,
+}
+
+impl BoundingBox {
+    /// Returns the smallest box containing every point yielded by `points`, or
+    /// `None` if it yields no points.
+    pub fn from_points<'a, I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Vector3>,
+    {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+
+        Some(points.fold(Self::new(first, first), |bounds, point| {
+            Self::new(
+                Vector3::new(
+                    bounds.min.x.min(point.x),
+                    bounds.min.y.min(point.y),
+                    bounds.min.z.min(point.z),
+                ),
+                Vector3::new(
+                    bounds.max.x.max(point.x),
+                    bounds.max.y.max(point.y),
+                    bounds.max.z.max(point.z),
+                ),
+            )
+        }))
+    }
+
+    /// Constructs a new `BoundingBox` from its two corners.
+    #[inline]
+    pub fn new(min: Vector3, max: Vector3) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns the size of the box on each axis.
+    pub fn size(&self) -> Vector3 {
+        Vector3::new(
+            self.max.x - self.min.x,
+            self.max.y - self.min.y,
+            self.max.z - self.min.z,
+        )
+    }
+
+    /// Returns the smallest box that contains both this box and `other`.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox::from_points(&[self.min, self.max, other.min, other.max]).unwrap()
+    }
+}
+
+impl ConvexHull {
+    /// Returns the bounding box of this hull's vertices, or `None` if it has
+    /// no vertices.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_points(&self.vertices)
+    }
+}
+
+impl PhysicalConfigData {
+    /// Returns the version of the format the blob was written with, or `0`
+    /// if the blob was empty.
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the convex pieces that make up the collision geometry.
+    #[inline]
+    pub fn hulls(&self) -> &[ConvexHull] {
+        &self.hulls
+    }
+
+    /// Returns the total number of vertices across every hull.
+    pub fn vertex_count(&self) -> usize {
+        self.hulls.iter().map(|hull| hull.vertices.len()).sum()
+    }
+
+    /// Returns the total number of triangles across every hull.
+    pub fn triangle_count(&self) -> usize {
+        self.hulls.iter().map(|hull| hull.triangles.len()).sum()
+    }
+
+    /// Returns the bounding box of every hull, or `None` if there is no
+    /// geometry.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.hulls
+            .iter()
+            .filter_map(ConvexHull::bounding_box)
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Decodes a `PhysicalConfigData` from a binary blob, usually the
+    /// contents of a `SharedString`. An empty blob decodes to an empty
+    /// `PhysicalConfigData`, which is what Roblox writes for parts that use
+    /// their default collision geometry.
+    pub fn decode(buffer: &[u8]) -> Result<Self, CrateError> {
+        if buffer.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut reader = Reader { buffer };
+
+        if reader.read_bytes(MAGIC.len())? != MAGIC {
+            return Err(PhysicalConfigDataError::BadMagic.into());
+        }
+
+        let version = reader.read_u32()?;
+        let mut hulls = Vec::new();
+
+        while !reader.buffer.is_empty() {
+            let vertex_count = reader.read_len(12)?;
+            let mut vertices = Vec::with_capacity(vertex_count);
+            for _ in 0..vertex_count {
+                vertices.push(Vector3::new(
+                    reader.read_f32()?,
+                    reader.read_f32()?,
+                    reader.read_f32()?,
+                ));
+            }
+
+            let triangle_count = reader.read_len(12)?;
+            let mut triangles = Vec::with_capacity(triangle_count);
+            for _ in 0..triangle_count {
+                let triangle = [reader.read_u32()?, reader.read_u32()?, reader.read_u32()?];
+
+                if let Some(index) = triangle
+                    .iter()
+                    .find(|index| **index as usize >= vertex_count)
+                {
+                    return Err(PhysicalConfigDataError::BadVertexIndex {
+                        index: *index,
+                        vertex_count,
+                    }
+                    .into());
+                }
+
+                triangles.push(triangle);
+            }
+
+            hulls.push(ConvexHull {
+                vertices,
+                triangles,
+            });
+        }
+
+        Ok(Self { version, hulls })
+    }
+}
+
+struct Reader<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], PhysicalConfigDataError> {
+        if self.buffer.len() < len {
+            return Err(PhysicalConfigDataError::UnexpectedEof);
+        }
+
+        let (bytes, rest) = self.buffer.split_at(len);
+        self.buffer = rest;
+
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, PhysicalConfigDataError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn read_f32(&mut self) -> Result<f32, PhysicalConfigDataError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(f32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Reads the length of an array whose elements are `element_size` bytes,
+    /// making sure that the blob is long enough to contain it so that
+    /// malformed lengths can't cause huge allocations.
+    fn read_len(&mut self, element_size: usize) -> Result<usize, PhysicalConfigDataError> {
+        let len = self.read_u32()? as usize;
+
+        if len.saturating_mul(element_size) > self.buffer.len() {
+            return Err(PhysicalConfigDataError::UnexpectedEof);
+        }
+
+        Ok(len)
+    }
+}
+
+/// An error that can occur when deserializing a `PhysicalConfigData`.
+#[derive(Debug, Error)]
+pub(crate) enum PhysicalConfigDataError {
+    #[error("PhysicalConfigData blob did not start with CSGPHS")]
+    BadMagic,
+
+    #[error("PhysicalConfigData blob ended unexpectedly")]
+    UnexpectedEof,
+
+    #[error("PhysicalConfigData triangle used vertex {index}, but its hull only has {vertex_count} vertices")]
+    BadVertexIndex { index: u32, vertex_count: usize },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write_hull(buffer: &mut Vec<u8>, vertices: &[[f32; 3]], triangles: &[[u32; 3]]) {
+        buffer.extend_from_slice(&(vertices.len() as u32).to_le_bytes());
+        for component in vertices.iter().flatten() {
+            buffer.extend_from_slice(&component.to_le_bytes());
+        }
+
+        buffer.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for index in triangles.iter().flatten() {
+            buffer.extend_from_slice(&index.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn decode() {
+        let mut buffer = b"CSGPHS".to_vec();
+        buffer.extend_from_slice(&3u32.to_le_bytes());
+
+        let tetrahedron = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ];
+        write_hull(
+            &mut buffer,
+            &tetrahedron,
+            &[[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]],
+        );
+        write_hull(
+            &mut buffer,
+            &[[-2.0, 0.5, 0.0], [0.0, 3.0, 0.0], [0.0, 0.0, -1.0]],
+            &[[0, 1, 2]],
+        );
+
+        let data = PhysicalConfigData::decode(&buffer).unwrap();
+
+        assert_eq!(data.version(), 3);
+        assert_eq!(data.hulls().len(), 2);
+        assert_eq!(data.vertex_count(), 7);
+        assert_eq!(data.triangle_count(), 5);
+        assert_eq!(
+            data.bounding_box(),
+            Some(BoundingBox::new(
+                Vector3::new(-2.0, 0.0, -1.0),
+                Vector3::new(1.0, 3.0, 1.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn decode_empty() {
+        let data = PhysicalConfigData::decode(&[]).unwrap();
+
+        assert_eq!(data.triangle_count(), 0);
+        assert_eq!(data.bounding_box(), None);
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(PhysicalConfigData::decode(b"CSGMDL\x03\0\0\0").is_err());
+        assert!(PhysicalConfigData::decode(b"CSGPHS\x03\0").is_err());
+
+        let mut buffer = b"CSGPHS\x03\0\0\0".to_vec();
+        write_hull(&mut buffer, &[[0.0; 3]], &[[0, 0, 1]]);
+        assert!(PhysicalConfigData::decode(&buffer).is_err());
+
+        // A hull claiming far more vertices than the blob contains.
+        let mut buffer = b"CSGPHS\x03\0\0\0".to_vec();
+        buffer.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(PhysicalConfigData::decode(&buffer).is_err());
+    }
+}
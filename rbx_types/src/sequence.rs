@@ -0,0 +1,222 @@
+use crate::{
+    Color3, ColorSequence, ColorSequenceKeypoint, NumberRange, NumberSequence,
+    NumberSequenceKeypoint,
+};
+
+impl NumberRange {
+    /// Returns whether `value` is between `min` and `max`, inclusive.
+    pub fn contains(&self, value: f32) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    /// Returns `value` limited to be between `min` and `max`.
+    pub fn clamp(&self, value: f32) -> f32 {
+        value.max(self.min).min(self.max)
+    }
+}
+
+impl ColorSequence {
+    /// Creates a `ColorSequence` that is `color` the whole way through.
+    pub fn from_color(color: Color3) -> Self {
+        Self {
+            keypoints: vec![
+                ColorSequenceKeypoint::new(0.0, color),
+                ColorSequenceKeypoint::new(1.0, color),
+            ],
+        }
+    }
+
+    /// Inserts a keypoint, keeping the keypoints ordered by time. A keypoint
+    /// that already exists at the same time is replaced.
+    ///
+    /// The time of the keypoint is clamped between 0 and 1, since Roblox
+    /// rejects sequences with keypoints outside of that range.
+    pub fn insert_keypoint(&mut self, keypoint: ColorSequenceKeypoint) {
+        let keypoint = ColorSequenceKeypoint {
+            time: keypoint.time.clamp(0.0, 1.0),
+            ..keypoint
+        };
+
+        match self
+            .keypoints
+            .binary_search_by(|other| other.time.total_cmp(&keypoint.time))
+        {
+            Ok(index) => self.keypoints[index] = keypoint,
+            Err(index) => self.keypoints.insert(index, keypoint),
+        }
+    }
+
+    /// Returns the color of the sequence at `time`, blending linearly between
+    /// the keypoints around it like Roblox does. Times before the first
+    /// keypoint or after the last use that keypoint's color.
+    ///
+    /// Returns `None` if the sequence has no keypoints. The keypoints must be
+    /// ordered by time.
+    ///
+    /// ```
+    /// # use rbx_types::{Color3, ColorSequence, ColorSequenceKeypoint};
+    /// let sequence = ColorSequence {
+    ///     keypoints: vec![
+    ///         ColorSequenceKeypoint::new(0.0, Color3::new(0.0, 0.0, 0.0)),
+    ///         ColorSequenceKeypoint::new(1.0, Color3::new(1.0, 0.5, 0.0)),
+    ///     ],
+    /// };
+    ///
+    /// assert_eq!(sequence.sample(0.5), Some(Color3::new(0.5, 0.25, 0.0)));
+    /// ```
+    pub fn sample(&self, time: f32) -> Option<Color3> {
+        let (before, after, alpha) = surrounding(&self.keypoints, time, |keypoint| keypoint.time)?;
+
+        Some(Color3::new(
+            lerp(before.color.r, after.color.r, alpha),
+            lerp(before.color.g, after.color.g, alpha),
+            lerp(before.color.b, after.color.b, alpha),
+        ))
+    }
+}
+
+impl NumberSequence {
+    /// Creates a `NumberSequence` that is `value` the whole way through, with
+    /// no envelope.
+    pub fn from_value(value: f32) -> Self {
+        Self {
+            keypoints: vec![
+                NumberSequenceKeypoint::new(0.0, value, 0.0),
+                NumberSequenceKeypoint::new(1.0, value, 0.0),
+            ],
+        }
+    }
+
+    /// Inserts a keypoint, keeping the keypoints ordered by time. A keypoint
+    /// that already exists at the same time is replaced.
+    ///
+    /// The time of the keypoint is clamped between 0 and 1, since Roblox
+    /// rejects sequences with keypoints outside of that range.
+    pub fn insert_keypoint(&mut self, keypoint: NumberSequenceKeypoint) {
+        let keypoint = NumberSequenceKeypoint {
+            time: keypoint.time.clamp(0.0, 1.0),
+            ..keypoint
+        };
+
+        match self
+            .keypoints
+            .binary_search_by(|other| other.time.total_cmp(&keypoint.time))
+        {
+            Ok(index) => self.keypoints[index] = keypoint,
+            Err(index) => self.keypoints.insert(index, keypoint),
+        }
+    }
+
+    /// Returns the value of the sequence at `time`, blending linearly between
+    /// the keypoints around it like Roblox does. Times before the first
+    /// keypoint or after the last use that keypoint's value. Envelopes are
+    /// ignored.
+    ///
+    /// Returns `None` if the sequence has no keypoints. The keypoints must be
+    /// ordered by time.
+    pub fn sample(&self, time: f32) -> Option<f32> {
+        let (before, after, alpha) = surrounding(&self.keypoints, time, |keypoint| keypoint.time)?;
+
+        Some(lerp(before.value, after.value, alpha))
+    }
+
+    /// Limits the value of every keypoint to be within `range`.
+    pub fn clamp_values(&mut self, range: NumberRange) {
+        for keypoint in &mut self.keypoints {
+            keypoint.value = range.clamp(keypoint.value);
+        }
+    }
+}
+
+/// Finds the keypoints on either side of `time`, along with how far `time` is
+/// between them, from 0 to 1.
+fn surrounding<T>(keypoints: &[T], time: f32, time_of: fn(&T) -> f32) -> Option<(&T, &T, f32)> {
+    let first = keypoints.first()?;
+    let last = keypoints.last()?;
+
+    if time <= time_of(first) {
+        return Some((first, first, 0.0));
+    }
+
+    for pair in keypoints.windows(2) {
+        let (before, after) = (&pair[0], &pair[1]);
+        let (start, end) = (time_of(before), time_of(after));
+
+        if time <= end {
+            let alpha = if end > start {
+                (time - start) / (end - start)
+            } else {
+                1.0
+            };
+
+            return Some((before, after, alpha));
+        }
+    }
+
+    Some((last, last, 0.0))
+}
+
+fn lerp(a: f32, b: f32, alpha: f32) -> f32 {
+    a + (b - a) * alpha
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn number_range() {
+        let range = NumberRange::new(1.0, 2.0);
+
+        assert!(range.contains(1.5));
+        assert!(range.contains(2.0));
+        assert!(!range.contains(2.5));
+        assert_eq!(range.clamp(0.0), 1.0);
+        assert_eq!(range.clamp(3.0), 2.0);
+        assert_eq!(range.clamp(1.25), 1.25);
+    }
+
+    #[test]
+    fn color_sequence() {
+        let red = Color3::new(1.0, 0.0, 0.0);
+        let blue = Color3::new(0.0, 0.0, 1.0);
+        let mut sequence = ColorSequence::from_color(red);
+
+        sequence.insert_keypoint(ColorSequenceKeypoint::new(0.5, blue));
+        sequence.insert_keypoint(ColorSequenceKeypoint::new(2.0, blue));
+
+        let times: Vec<f32> = sequence.keypoints.iter().map(|k| k.time).collect();
+        assert_eq!(times, [0.0, 0.5, 1.0]);
+        assert_eq!(sequence.keypoints[2].color, blue);
+
+        assert_eq!(sequence.sample(-1.0), Some(red));
+        assert_eq!(sequence.sample(0.25), Some(Color3::new(0.5, 0.0, 0.5)));
+        assert_eq!(sequence.sample(0.75), Some(blue));
+        assert_eq!(sequence.sample(5.0), Some(blue));
+
+        assert_eq!(
+            ColorSequence {
+                keypoints: Vec::new()
+            }
+            .sample(0.5),
+            None
+        );
+    }
+
+    #[test]
+    fn number_sequence() {
+        let mut sequence = NumberSequence::from_value(10.0);
+        sequence.insert_keypoint(NumberSequenceKeypoint::new(1.0, 20.0, 0.0));
+        sequence.insert_keypoint(NumberSequenceKeypoint::new(0.5, 0.0, 1.0));
+
+        assert_eq!(sequence.keypoints.len(), 3);
+        assert_eq!(sequence.sample(0.0), Some(10.0));
+        assert_eq!(sequence.sample(0.25), Some(5.0));
+        assert_eq!(sequence.sample(0.75), Some(10.0));
+        assert_eq!(sequence.sample(1.0), Some(20.0));
+
+        sequence.clamp_values(NumberRange::new(5.0, 15.0));
+        let values: Vec<f32> = sequence.keypoints.iter().map(|k| k.value).collect();
+        assert_eq!(values, [10.0, 5.0, 15.0]);
+    }
+}
@@ -8,6 +8,8 @@ use std::{
 
 use blake3::Hash as Blake3Hash;
 
+use crate::BinaryString;
+
 lazy_static::lazy_static! {
     static ref STRING_CACHE: Arc<Mutex<HashMap<Blake3Hash, Weak<Vec<u8>>>>> = {
         Arc::new(Mutex::new(HashMap::new()))
@@ -74,6 +76,77 @@ impl SharedString {
     pub fn hash(&self) -> SharedStringHash {
         SharedStringHash(self.hash)
     }
+
+    /// Returns the `SharedString` with the given hash, if one is still alive
+    /// anywhere in the process.
+    pub fn get(hash: SharedStringHash) -> Option<SharedString> {
+        let cache = STRING_CACHE.lock().unwrap();
+        let data = cache.get(&hash.0)?.upgrade()?;
+
+        Some(SharedString {
+            data: Some(data),
+            hash: hash.0,
+        })
+    }
+}
+
+impl From<Vec<u8>> for SharedString {
+    fn from(data: Vec<u8>) -> Self {
+        SharedString::new(data)
+    }
+}
+
+impl From<&'_ [u8]> for SharedString {
+    fn from(data: &[u8]) -> Self {
+        SharedString::new(data.to_vec())
+    }
+}
+
+impl From<BinaryString> for SharedString {
+    fn from(value: BinaryString) -> Self {
+        SharedString::new(value.into())
+    }
+}
+
+/// A snapshot of the process-wide cache that deduplicates `SharedString`
+/// values.
+///
+/// Every `SharedString` with the same contents shares one buffer, which is
+/// freed once the last of them is dropped. Comparing `bytes` against the total
+/// size of every `SharedString` in a dom shows how much deduplication saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharedStringCacheStats {
+    /// The number of distinct buffers in the cache.
+    pub entries: usize,
+
+    /// The total length of every buffer in the cache.
+    pub bytes: usize,
+}
+
+impl SharedStringCacheStats {
+    /// Returns the current state of the `SharedString` cache.
+    pub fn current() -> Self {
+        let handles: Vec<SharedString> = {
+            let cache = STRING_CACHE.lock().unwrap();
+
+            cache
+                .iter()
+                .filter_map(|(hash, data)| {
+                    Some(SharedString {
+                        data: Some(data.upgrade()?),
+                        hash: *hash,
+                    })
+                })
+                .collect()
+        };
+
+        // The handles are dropped after the cache is unlocked, since dropping
+        // the last handle to a buffer removes it from the cache.
+        Self {
+            entries: handles.len(),
+            bytes: handles.iter().map(|handle| handle.data().len()).sum(),
+        }
+    }
 }
 
 impl Hash for SharedString {
@@ -195,6 +268,39 @@ mod test {
         assert!(Arc::ptr_eq(data_1, data_2));
     }
 
+    #[test]
+    fn get_by_hash() {
+        let handle = SharedString::from(b"get_by_hash".as_ref());
+        let hash = handle.hash();
+
+        let found = SharedString::get(hash).unwrap();
+        assert!(Arc::ptr_eq(
+            handle.data.as_ref().unwrap(),
+            found.data.as_ref().unwrap()
+        ));
+
+        std::mem::drop((handle, found));
+        assert_eq!(SharedString::get(hash), None);
+    }
+
+    #[test]
+    fn cache_stats() {
+        let data = b"cache_stats test buffer".to_vec();
+
+        let handle_1 = SharedString::from(data.clone());
+        let handle_2 = SharedString::from(BinaryString::from(data.clone()));
+        let stats = SharedStringCacheStats::current();
+
+        // Other tests use the cache at the same time, so this can only check
+        // that the buffer both handles share is accounted for.
+        assert!(stats.entries >= 1);
+        assert!(stats.bytes >= data.len());
+        assert!(Arc::ptr_eq(
+            handle_1.data.as_ref().unwrap(),
+            handle_2.data.as_ref().unwrap()
+        ));
+    }
+
     #[test]
     fn drop() {
         {
@@ -0,0 +1,467 @@
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use crate::material_colors::MATERIAL_ORDER;
+use crate::{Error as CrateError, TerrainMaterials};
+
+/// The only version of the `SmoothGrid` format that is understood.
+const VERSION: u8 = 1;
+
+/// The chunk size exponent used by Roblox, giving chunks of 32³ voxels.
+const DEFAULT_CHUNK_EXPONENT: u8 = 5;
+
+/// The largest chunk size exponent that will be decoded. Anything larger
+/// than this would require allocating unreasonably large chunks.
+const MAX_CHUNK_EXPONENT: u8 = 8;
+
+const MATERIAL_MASK: u8 = 0b0011_1111;
+const HAS_OCCUPANCY: u8 = 0b0100_0000;
+const HAS_RUN_LENGTH: u8 = 0b1000_0000;
+
+/// The material of a single terrain voxel.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoxelMaterial {
+    /// The voxel is empty.
+    #[default]
+    Air,
+    /// The voxel is filled with water.
+    Water,
+    /// The voxel is filled with a solid material.
+    Terrain(TerrainMaterials),
+}
+
+impl VoxelMaterial {
+    /// Returns the id used for this material in a `SmoothGrid` blob.
+    pub fn id(self) -> u8 {
+        match self {
+            Self::Air => 0,
+            Self::Water => 1,
+            Self::Terrain(material) => {
+                let index = MATERIAL_ORDER
+                    .iter()
+                    .position(|other| *other == material)
+                    .unwrap();
+
+                index as u8 + 2
+            }
+        }
+    }
+
+    /// Returns the material with the given `SmoothGrid` id, if there is one.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Air),
+            1 => Some(Self::Water),
+            _ => MATERIAL_ORDER
+                .get(id as usize - 2)
+                .map(|material| Self::Terrain(*material)),
+        }
+    }
+}
+
+impl From<TerrainMaterials> for VoxelMaterial {
+    fn from(material: TerrainMaterials) -> Self {
+        Self::Terrain(material)
+    }
+}
+
+/// A single terrain voxel, which is 4 studs wide on each axis.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Voxel {
+    /// The material the voxel is filled with.
+    pub material: VoxelMaterial,
+    /// How full the voxel is, from `0` (empty) to `255` (full). This is
+    /// ignored for `Air` voxels.
+    pub occupancy: u8,
+}
+
+impl Voxel {
+    /// An empty voxel.
+    pub const AIR: Voxel = Voxel {
+        material: VoxelMaterial::Air,
+        occupancy: 0,
+    };
+
+    /// Constructs a new `Voxel` from its material and occupancy.
+    #[inline]
+    pub fn new(material: VoxelMaterial, occupancy: u8) -> Self {
+        Self {
+            material,
+            occupancy,
+        }
+    }
+
+    /// Constructs a completely full `Voxel` of the given material.
+    #[inline]
+    pub fn solid(material: VoxelMaterial) -> Self {
+        Self::new(material, u8::MAX).normalized()
+    }
+
+    /// Returns whether this voxel is empty.
+    #[inline]
+    pub fn is_air(&self) -> bool {
+        self.material == VoxelMaterial::Air
+    }
+
+    fn normalized(self) -> Self {
+        if self.is_air() {
+            Self::AIR
+        } else {
+            self
+        }
+    }
+}
+
+/// The voxel data of a `Terrain` instance, stored in the `Terrain.SmoothGrid`
+/// property.
+///
+/// Voxels are addressed by their position in voxel space, where a voxel at
+/// `[x, y, z]` covers the studs from `[x, y, z] * 4` to `[x + 1, y + 1, z + 1] * 4`.
+/// The colors of terrain materials are stored separately, in
+/// [`MaterialColors`](crate::MaterialColors).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SmoothGrid {
+    chunk_exponent: u8,
+    chunks: BTreeMap<[i32; 3], Vec<Voxel>>,
+}
+
+impl SmoothGrid {
+    /// Constructs a new `SmoothGrid` that contains no terrain.
+    pub fn new() -> Self {
+        Self::with_chunk_exponent(DEFAULT_CHUNK_EXPONENT)
+    }
+
+    fn with_chunk_exponent(chunk_exponent: u8) -> Self {
+        Self {
+            chunk_exponent,
+            chunks: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the width of a chunk of this grid in voxels.
+    #[inline]
+    pub fn chunk_size(&self) -> i32 {
+        1 << self.chunk_exponent
+    }
+
+    /// Returns the voxel at the given position. Positions that contain no
+    /// terrain are `Air`.
+    pub fn get_voxel(&self, position: [i32; 3]) -> Voxel {
+        let (chunk, index) = self.locate(position);
+
+        match self.chunks.get(&chunk) {
+            Some(voxels) => voxels[index],
+            None => Voxel::AIR,
+        }
+    }
+
+    /// Replaces the voxel at the given position, returning the old voxel.
+    pub fn set_voxel(&mut self, position: [i32; 3], voxel: Voxel) -> Voxel {
+        let voxel = voxel.normalized();
+        let (chunk, index) = self.locate(position);
+        let chunk_len = self.chunk_len();
+
+        if voxel.is_air() && !self.chunks.contains_key(&chunk) {
+            return Voxel::AIR;
+        }
+
+        let voxels = self
+            .chunks
+            .entry(chunk)
+            .or_insert_with(|| vec![Voxel::AIR; chunk_len]);
+
+        std::mem::replace(&mut voxels[index], voxel)
+    }
+
+    /// Returns an iterator over the position of every voxel that is not `Air`
+    /// alongside the voxel itself.
+    pub fn voxels(&self) -> impl Iterator<Item = ([i32; 3], Voxel)> + '_ {
+        let size = self.chunk_size();
+
+        self.chunks.iter().flat_map(move |(chunk, voxels)| {
+            voxels
+                .iter()
+                .enumerate()
+                .filter(|(_, voxel)| !voxel.is_air())
+                .map(move |(index, voxel)| {
+                    let index = index as i32;
+                    let local = [index / (size * size), index / size % size, index % size];
+                    let position = [
+                        chunk[0] * size + local[0],
+                        chunk[1] * size + local[1],
+                        chunk[2] * size + local[2],
+                    ];
+
+                    (position, *voxel)
+                })
+        })
+    }
+
+    /// Returns whether this grid contains no terrain.
+    pub fn is_empty(&self) -> bool {
+        self.voxels().next().is_none()
+    }
+
+    /// Encodes the `SmoothGrid` into a binary blob that can be understood
+    /// by Roblox.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = vec![VERSION, self.chunk_exponent];
+        let mut previous = [0; 3];
+
+        for (chunk, voxels) in &self.chunks {
+            for axis in 0..3 {
+                write_varint(&mut buffer, chunk[axis].wrapping_sub(previous[axis]));
+            }
+            previous = *chunk;
+
+            let mut rest = voxels.as_slice();
+            while let Some(voxel) = rest.first() {
+                let run = rest
+                    .iter()
+                    .take(256)
+                    .take_while(|other| *other == voxel)
+                    .count();
+                rest = &rest[run..];
+
+                let mut header = voxel.material.id();
+                let occupancy =
+                    (!voxel.is_air() && voxel.occupancy != u8::MAX).then_some(voxel.occupancy);
+
+                if occupancy.is_some() {
+                    header |= HAS_OCCUPANCY;
+                }
+                if run > 1 {
+                    header |= HAS_RUN_LENGTH;
+                }
+
+                buffer.push(header);
+                buffer.extend(occupancy);
+                if run > 1 {
+                    buffer.push((run - 1) as u8);
+                }
+            }
+        }
+
+        buffer
+    }
+
+    /// Decodes a `SmoothGrid` from a binary blob. The blob must be the same
+    /// format used by `encode` and Roblox.
+    pub fn decode(buffer: &[u8]) -> Result<Self, CrateError> {
+        let mut reader = Reader { buffer };
+
+        let version = reader.read_u8()?;
+        if version != VERSION {
+            return Err(SmoothGridError::UnknownVersion(version).into());
+        }
+
+        let chunk_exponent = reader.read_u8()?;
+        if chunk_exponent > MAX_CHUNK_EXPONENT {
+            return Err(SmoothGridError::ChunkTooLarge(chunk_exponent).into());
+        }
+
+        let mut grid = Self::with_chunk_exponent(chunk_exponent);
+        let chunk_len = grid.chunk_len();
+        let mut previous = [0i32; 3];
+
+        while !reader.buffer.is_empty() {
+            let mut chunk = [0; 3];
+            for axis in 0..3 {
+                chunk[axis] = previous[axis].wrapping_add(reader.read_varint()?);
+            }
+            previous = chunk;
+
+            let mut voxels = Vec::with_capacity(chunk_len);
+            while voxels.len() < chunk_len {
+                let header = reader.read_u8()?;
+                let id = header & MATERIAL_MASK;
+                let material =
+                    VoxelMaterial::from_id(id).ok_or(SmoothGridError::UnknownMaterial(id))?;
+
+                let occupancy = if header & HAS_OCCUPANCY != 0 {
+                    reader.read_u8()?
+                } else {
+                    u8::MAX
+                };
+                let run = if header & HAS_RUN_LENGTH != 0 {
+                    reader.read_u8()? as usize + 1
+                } else {
+                    1
+                };
+
+                if voxels.len() + run > chunk_len {
+                    return Err(SmoothGridError::RunOverflow(chunk).into());
+                }
+
+                let voxel = Voxel::new(material, occupancy).normalized();
+                voxels.extend(std::iter::repeat_n(voxel, run));
+            }
+
+            if grid.chunks.insert(chunk, voxels).is_some() {
+                return Err(SmoothGridError::DuplicateChunk(chunk).into());
+            }
+        }
+
+        Ok(grid)
+    }
+
+    fn chunk_len(&self) -> usize {
+        1 << (self.chunk_exponent * 3)
+    }
+
+    /// Splits a voxel position into the position of its chunk and its index
+    /// within that chunk.
+    fn locate(&self, position: [i32; 3]) -> ([i32; 3], usize) {
+        let exponent = self.chunk_exponent as u32;
+        let mask = self.chunk_size() - 1;
+
+        let chunk = position.map(|axis| axis >> exponent);
+        let [x, y, z] = position.map(|axis| (axis & mask) as usize);
+        let index = (((x << exponent) | y) << exponent) | z;
+
+        (chunk, index)
+    }
+}
+
+impl Default for SmoothGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Reader<'a> {
+    buffer: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn read_u8(&mut self) -> Result<u8, SmoothGridError> {
+        let (first, rest) = self
+            .buffer
+            .split_first()
+            .ok_or(SmoothGridError::UnexpectedEof)?;
+        self.buffer = rest;
+
+        Ok(*first)
+    }
+
+    fn read_varint(&mut self) -> Result<i32, SmoothGridError> {
+        let mut value = 0u32;
+
+        for shift in (0..35).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7F) as u32) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok((value >> 1) as i32 ^ -((value & 1) as i32));
+            }
+        }
+
+        Err(SmoothGridError::VarintTooLong)
+    }
+}
+
+fn write_varint(buffer: &mut Vec<u8>, value: i32) {
+    let mut value = ((value << 1) ^ (value >> 31)) as u32;
+
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// An error that can occur when deserializing a `SmoothGrid`.
+#[derive(Debug, Error)]
+pub(crate) enum SmoothGridError {
+    #[error("SmoothGrid blob has unknown version {0}")]
+    UnknownVersion(u8),
+
+    #[error("SmoothGrid chunk size exponent {0} is too large")]
+    ChunkTooLarge(u8),
+
+    #[error("SmoothGrid blob ended unexpectedly")]
+    UnexpectedEof,
+
+    #[error("SmoothGrid blob contained a chunk position that was too long")]
+    VarintTooLong,
+
+    #[error("SmoothGrid blob contained unknown material id {0}")]
+    UnknownMaterial(u8),
+
+    #[error("SmoothGrid chunk {0:?} contained more voxels than fit in a chunk")]
+    RunOverflow([i32; 3]),
+
+    #[error("SmoothGrid blob contained chunk {0:?} more than once")]
+    DuplicateChunk([i32; 3]),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn material_ids() {
+        for id in 0..23 {
+            let material = VoxelMaterial::from_id(id).unwrap();
+            assert_eq!(material.id(), id);
+        }
+
+        assert_eq!(VoxelMaterial::from_id(23), None);
+        assert_eq!(VoxelMaterial::Terrain(TerrainMaterials::Grass).id(), 2);
+        assert_eq!(VoxelMaterial::Terrain(TerrainMaterials::Pavement).id(), 22);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut grid = SmoothGrid::new();
+        let grass = Voxel::solid(TerrainMaterials::Grass.into());
+        let water = Voxel::new(VoxelMaterial::Water, 100);
+
+        grid.set_voxel([0, 0, 0], grass);
+        grid.set_voxel([0, 0, 1], grass);
+        grid.set_voxel([-1, 40, 3], water);
+
+        let decoded = SmoothGrid::decode(&grid.encode()).unwrap();
+        assert_eq!(decoded, grid);
+
+        assert_eq!(decoded.get_voxel([0, 0, 1]), grass);
+        assert_eq!(decoded.get_voxel([-1, 40, 3]), water);
+        assert_eq!(decoded.get_voxel([5, 5, 5]), Voxel::AIR);
+
+        let voxels: Vec<_> = decoded.voxels().collect();
+        assert_eq!(
+            voxels,
+            [([-1, 40, 3], water), ([0, 0, 0], grass), ([0, 0, 1], grass)]
+        );
+    }
+
+    #[test]
+    fn set_voxel() {
+        let mut grid = SmoothGrid::new();
+        let rock = Voxel::solid(TerrainMaterials::Rock.into());
+
+        assert!(grid.is_empty());
+        assert_eq!(grid.set_voxel([3, 3, 3], Voxel::AIR), Voxel::AIR);
+        assert_eq!(grid.encode(), [1, 5]);
+
+        assert_eq!(grid.set_voxel([3, 3, 3], rock), Voxel::AIR);
+        assert_eq!(grid.set_voxel([3, 3, 3], Voxel::AIR), rock);
+        assert!(grid.is_empty());
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(SmoothGrid::decode(&[]).is_err());
+        assert!(SmoothGrid::decode(&[2, 5]).is_err());
+        assert!(SmoothGrid::decode(&[1, 9]).is_err());
+        // A chunk at [0, 0, 0] whose single voxel uses material id 63.
+        assert!(SmoothGrid::decode(&[1, 0, 0, 0, 0, 63]).is_err());
+        // A chunk with a single voxel that has a run length of 2.
+        assert!(SmoothGrid::decode(&[1, 0, 0, 0, 0, 0x82, 1]).is_err());
+        // A chunk that's missing its voxels.
+        assert!(SmoothGrid::decode(&[1, 5, 0, 0, 0, 2]).is_err());
+    }
+}
@@ -21,6 +21,24 @@ lazy_static! {
     pub static ref EPOCH: SystemTime = UNIX_EPOCH - Duration::from_secs(EPOCH_AS_UNIX);
 }
 
+/// Returns how long it has been since the `UniqueId` epoch, or `None` if the
+/// system clock is set to a time before it.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn since_epoch() -> Option<Duration> {
+    SystemTime::now().duration_since(*EPOCH).ok()
+}
+
+/// Returns how long it has been since the `UniqueId` epoch, or `None` if the
+/// system clock is set to a time before it.
+///
+/// `SystemTime::now` panics on this target, so the time comes from
+/// JavaScript's `Date.now` instead.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn since_epoch() -> Option<Duration> {
+    let since_unix = Duration::from_secs_f64(js_sys::Date::now() / 1000.0);
+    since_unix.checked_sub(Duration::from_secs(EPOCH_AS_UNIX))
+}
+
 /// Represents an error that can occur when constructing a new `UniqueId`.
 #[derive(Debug, Error)]
 pub(crate) enum UniqueIdError {
@@ -70,9 +88,7 @@ impl UniqueId {
 
     /// Returns a new UniqueId.
     pub fn now() -> Result<Self, CrateError> {
-        let time = SystemTime::now()
-            .duration_since(*EPOCH)
-            .map_err(|_| CrateError::from(UniqueIdError::SystemPastTime))?;
+        let time = since_epoch().ok_or(UniqueIdError::SystemPastTime)?;
 
         Ok(Self {
             index: INDEX.fetch_add(1, Ordering::AcqRel),
@@ -84,6 +100,23 @@ impl UniqueId {
         })
     }
 
+    /// Returns a new `UniqueId` in the same format Roblox generates them in,
+    /// like [`UniqueId::now`], but without failing. If the system clock is set
+    /// to a time before the `UniqueId` epoch, the time portion is `0`, and if
+    /// it's too far past the epoch to fit, it's `u32::MAX`. The index and
+    /// random portions still keep the result unique.
+    pub fn generate() -> Self {
+        let time = since_epoch()
+            .map(|time| u32::try_from(time.as_secs()).unwrap_or(u32::MAX))
+            .unwrap_or(0);
+
+        Self {
+            index: INDEX.fetch_add(1, Ordering::AcqRel),
+            time,
+            random: thread_rng().gen_range(0..i64::MAX),
+        }
+    }
+
     /// Returns whether this `UniqueId` is 'nil' or not. That is, whether
     /// every field of the UniqueId is set to `0`.
     pub fn is_nil(&self) -> bool {
@@ -233,6 +266,17 @@ mod test {
         assert_eq!(uid.to_string(), "0badf00dc0ffee4200133700deadbeef");
     }
 
+    #[test]
+    fn generate() {
+        let first = UniqueId::generate();
+        let second = UniqueId::generate();
+
+        assert_ne!(first, second);
+        assert!(!first.is_nil());
+        assert!(first.random() >= 0);
+        assert_eq!(first.to_string().parse::<UniqueId>().unwrap(), first);
+    }
+
     #[test]
     fn from_str() {
         let str = "0badf00dc0ffee4200133700deadbeef";
@@ -52,6 +52,17 @@ macro_rules! make_variant {
                     Self::$variant_name(value)
                 }
             }
+
+            impl FromVariant for $inner_type {
+                const VARIANT_TYPE: VariantType = VariantType::$variant_name;
+
+                fn from_variant(value: &Variant) -> Option<&Self> {
+                    match value {
+                        Variant::$variant_name(inner) => Some(inner),
+                        _ => None,
+                    }
+                }
+            }
         )*
 
         /// Represents any type that can be held in a `Variant`.
@@ -87,6 +98,27 @@ macro_rules! make_variant {
     };
 }
 
+/// Represents any type that can be held in a `Variant`, and borrowed back out
+/// of a `Variant` holding it.
+///
+/// ```
+/// use rbx_types::{FromVariant, Variant, VariantType, Vector3};
+///
+/// let value = Variant::Vector3(Vector3::new(1.0, 2.0, 3.0));
+///
+/// assert_eq!(Vector3::from_variant(&value), Some(&Vector3::new(1.0, 2.0, 3.0)));
+/// assert_eq!(bool::from_variant(&value), None);
+/// assert_eq!(<bool as FromVariant>::VARIANT_TYPE, VariantType::Bool);
+/// ```
+pub trait FromVariant {
+    /// The type of `Variant` that holds this type.
+    const VARIANT_TYPE: VariantType;
+
+    /// Returns the value held by `value`, or `None` if it holds a different
+    /// type.
+    fn from_variant(value: &Variant) -> Option<&Self>;
+}
+
 // IMPORTANT! The order of this enum is very important in order to preserve the
 // discriminant values that Rust assigns for both Variant and VariantType. Any
 // newly-added variants MUST be added to the end!
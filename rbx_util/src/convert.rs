@@ -6,8 +6,9 @@ use std::{
 use anyhow::Context;
 use clap::Parser;
 use fs_err::File;
+use rbx_file::{from_reader_auto, ModelKind};
 
-use crate::ModelKind;
+use crate::model_kind_from_path;
 
 #[derive(Debug, Parser)]
 pub struct ConvertCommand {
@@ -20,24 +21,13 @@ pub struct ConvertCommand {
 
 impl ConvertCommand {
     pub fn run(&self) -> anyhow::Result<()> {
-        let input_kind = ModelKind::from_path(&self.input_path)?;
-        let output_kind = ModelKind::from_path(&self.output_path)?;
+        let output_kind = model_kind_from_path(&self.output_path)?;
 
         let input_file = BufReader::new(File::open(&self.input_path)?);
 
         log::debug!("Reading file into WeakDom");
-        let dom = match input_kind {
-            ModelKind::Xml => {
-                let options = rbx_xml::DecodeOptions::new()
-                    .property_behavior(rbx_xml::DecodePropertyBehavior::ReadUnknown);
-
-                rbx_xml::from_reader(input_file, options)
-                    .with_context(|| format!("Failed to read {}", self.input_path.display()))?
-            }
-
-            ModelKind::Binary => rbx_binary::from_reader(input_file)
-                .with_context(|| format!("Failed to read {}", self.input_path.display()))?,
-        };
+        let dom = from_reader_auto(input_file)
+            .with_context(|| format!("Failed to read {}", self.input_path.display()))?;
 
         let root_ids = dom.root().children();
 
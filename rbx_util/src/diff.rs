@@ -0,0 +1,122 @@
+use std::{
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use clap::Parser;
+use fs_err::File;
+use rbx_file::from_reader_auto;
+
+#[derive(Debug, Parser)]
+pub struct DiffCommand {
+    /// The original file.
+    before: PathBuf,
+    /// The file to compare against the original. It may be in a different
+    /// format from the original.
+    after: PathBuf,
+}
+
+impl DiffCommand {
+    pub fn run(&self) -> anyhow::Result<()> {
+        let stdout = io::stdout();
+        let mut output = BufWriter::new(stdout.lock());
+
+        self.write_differences(&mut output)?;
+        output.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes every difference between the two files to `output`, one per
+    /// line.
+    fn write_differences<W: Write>(&self, output: &mut W) -> anyhow::Result<()> {
+        let before = read_dom(&self.before)?;
+        let after = read_dom(&self.after)?;
+
+        log::debug!("Comparing files");
+        let differences = rbx_binary::compare(&before, &after);
+
+        for difference in &differences {
+            writeln!(output, "{difference}")?;
+        }
+
+        if differences.is_empty() {
+            log::info!("Files are identical");
+        } else {
+            log::info!("Found {} differences", differences.len());
+        }
+
+        Ok(())
+    }
+}
+
+fn read_dom(path: &Path) -> anyhow::Result<rbx_dom_weak::WeakDom> {
+    log::debug!("Reading from file {}", path.display());
+    let input_file = BufReader::new(File::open(path)?);
+
+    from_reader_auto(input_file).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+    fn string_value(value: &str) -> WeakDom {
+        WeakDom::new(
+            InstanceBuilder::new("DataModel").with_child(
+                InstanceBuilder::new("StringValue")
+                    .with_name("Greeting")
+                    .with_property("Value", value),
+            ),
+        )
+    }
+
+    fn diff(before: &WeakDom, after: &WeakDom) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let before_path = dir.path().join("before.rbxm");
+        let after_path = dir.path().join("after.rbxmx");
+
+        rbx_binary::to_writer(
+            File::create(&before_path).unwrap(),
+            before,
+            before.root().children(),
+        )
+        .unwrap();
+        rbx_xml::to_writer_default(
+            File::create(&after_path).unwrap(),
+            after,
+            after.root().children(),
+        )
+        .unwrap();
+
+        let mut output = Vec::new();
+        DiffCommand {
+            before: before_path,
+            after: after_path,
+        }
+        .write_differences(&mut output)
+        .unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn one_property() {
+        let output = diff(&string_value("Hello"), &string_value("Goodbye"));
+
+        assert_eq!(
+            output,
+            "game.Greeting: Value changed from String(\"Hello\") to String(\"Goodbye\")\n"
+        );
+    }
+
+    #[test]
+    fn identical() {
+        let output = diff(&string_value("Hello"), &string_value("Hello"));
+
+        assert_eq!(output, "");
+    }
+}
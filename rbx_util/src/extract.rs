@@ -0,0 +1,189 @@
+use std::{
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::Context;
+use clap::Parser;
+use fs_err::File;
+use rbx_dom_weak::{types::Ref, ExternalRefPolicy, WeakDom};
+use rbx_file::{from_reader_auto, ModelKind};
+
+use crate::model_kind_from_path;
+
+#[derive(Debug, Parser)]
+pub struct ExtractCommand {
+    /// The file to extract an instance from.
+    input: PathBuf,
+    /// The names of the instance and its ancestors separated by slashes,
+    /// starting below the root, like `Workspace/Map/Tree`.
+    path: String,
+    #[clap(long, short)]
+    /// The place to write the instance and its descendants to. The output
+    /// format is determined by the file extension of this path.
+    output: PathBuf,
+    /// What to do with Ref properties that point outside of the instance,
+    /// like the Adornee of a BillboardGui. Valid values are include, which
+    /// extracts the instances they point to as well, and none, which clears
+    /// them.
+    #[clap(long, default_value = "none")]
+    external_refs: ExternalRefs,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ExternalRefs(ExternalRefPolicy);
+
+impl FromStr for ExternalRefs {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "include" => Ok(ExternalRefs(ExternalRefPolicy::Include)),
+            "none" => Ok(ExternalRefs(ExternalRefPolicy::SetToNone)),
+            _ => anyhow::bail!(
+                "Invalid external ref policy '{source}'. Valid values are: include, none"
+            ),
+        }
+    }
+}
+
+impl ExtractCommand {
+    pub fn run(&self) -> anyhow::Result<()> {
+        let output_kind = model_kind_from_path(&self.output)?;
+
+        let input_file = BufReader::new(File::open(&self.input)?);
+
+        log::debug!("Reading from file {}", self.input.display());
+        let dom = from_reader_auto(input_file)
+            .with_context(|| format!("Failed to read {}", self.input.display()))?;
+
+        let referent = find_by_path(&dom, &self.path)
+            .with_context(|| format!("No instance at {} in {}", self.path, self.input.display()))?;
+
+        let extraction = dom.extract(referent, self.external_refs.0);
+        for external in &extraction.external_refs {
+            let exists = dom.get_by_ref(external.target).is_some();
+            let target = if exists {
+                dom.full_name_of(external.target)
+            } else {
+                "an instance that does not exist".to_owned()
+            };
+            let action = match self.external_refs.0 {
+                ExternalRefPolicy::Include if exists => "including it",
+                _ => "clearing it",
+            };
+
+            log::warn!(
+                "{}.{} points outside of {} to {target}, {action}",
+                dom.full_name_of(external.referent),
+                external.property,
+                self.path,
+            );
+        }
+
+        let output_file = BufWriter::new(File::create(&self.output)?);
+
+        match output_kind {
+            ModelKind::Xml => {
+                let options = rbx_xml::EncodeOptions::new()
+                    .property_behavior(rbx_xml::EncodePropertyBehavior::WriteUnknown);
+
+                rbx_xml::to_writer(output_file, &extraction.dom, &extraction.roots, options)
+                    .with_context(|| format!("Failed to write {}", self.output.display()))?;
+            }
+
+            ModelKind::Binary => {
+                rbx_binary::to_writer(output_file, &extraction.dom, &extraction.roots)
+                    .with_context(|| format!("Failed to write {}", self.output.display()))?;
+            }
+        }
+        log::info!(
+            "Wrote {} to {output_kind:?} file {}",
+            dom.full_name_of(referent),
+            self.output.display()
+        );
+
+        Ok(())
+    }
+}
+
+/// Finds the first instance whose name and ancestors' names match `path`.
+fn find_by_path(dom: &WeakDom, path: &str) -> Option<Ref> {
+    let mut current = dom.root();
+
+    for name in path.split('/').filter(|name| !name.is_empty()) {
+        current = current
+            .children()
+            .iter()
+            .map(|child| dom.get_by_ref(*child).unwrap())
+            .find(|child| child.name == name)?;
+    }
+
+    if current.referent() == dom.root_ref() {
+        None
+    } else {
+        Some(current.referent())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::InstanceBuilder;
+
+    fn workspace() -> WeakDom {
+        WeakDom::new(
+            InstanceBuilder::new("DataModel").with_child(
+                InstanceBuilder::new("Workspace")
+                    .with_name("Workspace")
+                    .with_child(
+                        InstanceBuilder::new("Model").with_name("Map").with_child(
+                            InstanceBuilder::new("Part")
+                                .with_name("Tree")
+                                .with_child(InstanceBuilder::new("Part").with_name("Leaf")),
+                        ),
+                    )
+                    .with_child(InstanceBuilder::new("Part").with_name("Baseplate")),
+            ),
+        )
+    }
+
+    #[test]
+    fn single_subtree() {
+        let dom = workspace();
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("place.rbxl");
+        let output = dir.path().join("map.rbxm");
+        rbx_binary::to_writer(File::create(&input).unwrap(), &dom, dom.root().children()).unwrap();
+
+        ExtractCommand {
+            input,
+            path: "Workspace/Map".to_owned(),
+            output: output.clone(),
+            external_refs: ExternalRefs(ExternalRefPolicy::SetToNone),
+        }
+        .run()
+        .unwrap();
+
+        let extracted = rbx_binary::from_reader(File::open(&output).unwrap()).unwrap();
+        let names: Vec<_> = extracted
+            .descendants()
+            .skip(1)
+            .map(|instance| extracted.full_name_of(instance.referent()))
+            .collect();
+        assert_eq!(names, ["game.Map", "game.Map.Tree", "game.Map.Tree.Leaf"]);
+    }
+
+    #[test]
+    fn path_lookup() {
+        let dom = workspace();
+
+        let tree = find_by_path(&dom, "Workspace/Map/Tree").unwrap();
+        assert_eq!(dom.full_name_of(tree), "game.Workspace.Map.Tree");
+
+        assert!(find_by_path(&dom, "Workspace/Map/Rock").is_none());
+        assert!(find_by_path(&dom, "").is_none());
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use clap::Parser;
+use fs_err::File;
+use rbx_dom_weak::{types::Ref, WeakDom};
+use rbx_file::from_reader_auto;
+
+#[derive(Debug, Parser)]
+pub struct InspectCommand {
+    /// The file to inspect.
+    input: PathBuf,
+    /// How many levels of the tree to print. The whole tree is printed if this
+    /// isn't given.
+    #[clap(long, short)]
+    depth: Option<usize>,
+}
+
+impl InspectCommand {
+    pub fn run(&self) -> anyhow::Result<()> {
+        let stdout = io::stdout();
+        let mut output = BufWriter::new(stdout.lock());
+
+        self.write_report(&mut output)?;
+        output.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the tree of the input file, along with the size of each
+    /// instance, to `output`.
+    fn write_report<W: Write>(&self, output: &mut W) -> anyhow::Result<()> {
+        let input_file = BufReader::new(File::open(&self.input)?);
+
+        log::debug!("Reading file into WeakDom");
+        let dom = from_reader_auto(input_file)
+            .with_context(|| format!("Failed to read {}", self.input.display()))?;
+
+        // Sizes are measured by writing the file out as a binary model, so
+        // they're available no matter which format the input was in.
+        log::debug!("Measuring the size of each instance");
+        let sizes = rbx_binary::Serializer::new()
+            .serialize_with_byte_sizes(io::sink(), &dom, dom.root().children())
+            .context("Failed to measure the size of the file")?;
+        let total: usize = sizes.values().sum();
+        let subtree_sizes = subtree_sizes(&dom, |referent| {
+            sizes.get(&referent).copied().unwrap_or_default()
+        });
+
+        for &referent in dom.root().children() {
+            self.print_instance(output, &dom, &subtree_sizes, referent, 0)?;
+        }
+
+        writeln!(
+            output,
+            "{} instances, {}",
+            dom.descendants().count() - 1,
+            format_size(total)
+        )?;
+
+        Ok(())
+    }
+
+    /// Prints an instance and its descendants, down to the depth limit.
+    fn print_instance<W: Write>(
+        &self,
+        output: &mut W,
+        dom: &WeakDom,
+        subtree_sizes: &HashMap<Ref, usize>,
+        referent: Ref,
+        depth: usize,
+    ) -> anyhow::Result<()> {
+        let instance = dom.get_by_ref(referent).unwrap();
+        let size = subtree_sizes[&referent];
+
+        writeln!(
+            output,
+            "{:indent$}{} ({}) - {}",
+            "",
+            instance.name,
+            instance.class,
+            format_size(size),
+            indent = depth * 2
+        )?;
+
+        if self.depth.is_none_or(|max_depth| depth + 1 < max_depth) {
+            for &child in instance.children() {
+                self.print_instance(output, dom, subtree_sizes, child, depth + 1)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds up the size of every instance and all of its descendants.
+fn subtree_sizes(dom: &WeakDom, size_of: impl Fn(Ref) -> usize) -> HashMap<Ref, usize> {
+    let order: Vec<Ref> = dom
+        .descendants()
+        .map(|instance| instance.referent())
+        .collect();
+    let mut subtree_sizes: HashMap<Ref, usize> = order
+        .iter()
+        .map(|&referent| (referent, size_of(referent)))
+        .collect();
+
+    // Descendants are visited parents first, so walking them backwards
+    // finishes every child before its parent.
+    for &referent in order.iter().rev() {
+        let parent = dom.get_by_ref(referent).unwrap().parent();
+
+        if let Some(size) = subtree_sizes.get(&referent).copied() {
+            if let Some(parent_size) = subtree_sizes.get_mut(&parent) {
+                *parent_size += size;
+            }
+        }
+    }
+
+    subtree_sizes
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::InstanceBuilder;
+
+    fn inspect(depth: Option<usize>) -> Vec<String> {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("DataModel").with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("Model")
+                    .with_child(InstanceBuilder::new("Part").with_name("A"))
+                    .with_child(
+                        InstanceBuilder::new("Folder")
+                            .with_name("Sub")
+                            .with_child(InstanceBuilder::new("Part").with_name("B")),
+                    ),
+            ),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("model.rbxm");
+        rbx_binary::to_writer(File::create(&input).unwrap(), &dom, dom.root().children()).unwrap();
+
+        let mut output = Vec::new();
+        InspectCommand { input, depth }
+            .write_report(&mut output)
+            .unwrap();
+
+        // Sizes depend on the details of the binary format, so only the part
+        // of each line before them is checked.
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| line.split(" - ").next().unwrap().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn whole_tree() {
+        let lines = inspect(None);
+
+        assert_eq!(
+            lines[..4],
+            [
+                "Model (Folder)",
+                "  A (Part)",
+                "  Sub (Folder)",
+                "    B (Part)"
+            ]
+        );
+        assert!(lines[4].starts_with("4 instances, "));
+        assert_eq!(lines.len(), 5);
+    }
+
+    #[test]
+    fn depth() {
+        let lines = inspect(Some(2));
+
+        assert_eq!(
+            lines[..3],
+            ["Model (Folder)", "  A (Part)", "  Sub (Folder)"]
+        );
+        assert!(lines[3].starts_with("4 instances, "));
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn subtree_sizes_include_descendants() {
+        let dom =
+            WeakDom::new(InstanceBuilder::new("DataModel").with_child(
+                InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("Part")),
+            ));
+        let folder = dom.root().children()[0];
+        let part = dom.get_by_ref(folder).unwrap().children()[0];
+
+        let sizes = subtree_sizes(&dom, |_| 10);
+        assert_eq!(sizes[&part], 10);
+        assert_eq!(sizes[&folder], 20);
+        assert_eq!(sizes[&dom.root_ref()], 30);
+    }
+}
@@ -1,4 +1,7 @@
 mod convert;
+mod diff;
+mod extract;
+mod inspect;
 mod remove_prop;
 mod view_binary;
 
@@ -6,8 +9,12 @@ use std::process;
 use std::{path::Path, str::FromStr};
 
 use clap::Parser;
+use rbx_file::ModelKind;
 
 use convert::ConvertCommand;
+use diff::DiffCommand;
+use extract::ExtractCommand;
+use inspect::InspectCommand;
 use remove_prop::RemovePropCommand;
 use view_binary::ViewBinaryCommand;
 
@@ -26,6 +33,9 @@ impl Options {
             Subcommand::ViewBinary(command) => command.run(),
             Subcommand::Convert(command) => command.run(),
             Subcommand::RemoveProp(command) => command.run(),
+            Subcommand::Inspect(command) => command.run(),
+            Subcommand::Diff(command) => command.run(),
+            Subcommand::Extract(command) => command.run(),
         }
     }
 }
@@ -38,6 +48,13 @@ enum Subcommand {
     Convert(ConvertCommand),
     /// Removes a specific property from a specific class within a Roblox file.
     RemoveProp(RemovePropCommand),
+    /// Prints the instances in a file as a tree, with how many bytes each
+    /// instance and its descendants take up.
+    Inspect(InspectCommand),
+    /// Prints every difference between two files.
+    Diff(DiffCommand),
+    /// Writes one instance and its descendants from a file to a new file.
+    Extract(ExtractCommand),
 }
 
 #[derive(Debug, Parser, Clone, Copy)]
@@ -50,21 +67,12 @@ struct GlobalOptions {
     color: ColorChoice,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ModelKind {
-    Binary,
-    Xml,
-}
-
-impl ModelKind {
-    pub fn from_path(path: &Path) -> anyhow::Result<ModelKind> {
-        log::trace!("Resolving type of file for path {}", path.display());
-        match path.extension().and_then(|ext| ext.to_str()) {
-            Some("rbxm") | Some("rbxl") => Ok(ModelKind::Binary),
-            Some("rbxmx") | Some("rbxlx") => Ok(ModelKind::Xml),
-
-            _ => anyhow::bail!("not a Roblox model or place file: {}", path.display()),
-        }
+/// Determines the kind of a file from the extension of its path, failing if
+/// it isn't the extension of a model or place file.
+fn model_kind_from_path(path: &Path) -> anyhow::Result<ModelKind> {
+    match ModelKind::from_path(path) {
+        Some(kind) => Ok(kind),
+        None => anyhow::bail!("not a Roblox model or place file: {}", path.display()),
     }
 }
 
@@ -6,8 +6,9 @@ use std::{
 use anyhow::Context as _;
 use clap::Parser;
 use fs_err::File;
+use rbx_file::{from_reader_auto, ModelKind};
 
-use crate::ModelKind;
+use crate::model_kind_from_path;
 
 #[derive(Debug, Parser)]
 pub struct RemovePropCommand {
@@ -24,31 +25,20 @@ pub struct RemovePropCommand {
 
 impl RemovePropCommand {
     pub fn run(&self) -> anyhow::Result<()> {
-        let input_kind = ModelKind::from_path(&self.input)?;
-        let output_kind = ModelKind::from_path(&self.output)?;
+        let output_kind = model_kind_from_path(&self.output)?;
 
         let input_file = BufReader::new(File::open(&self.input)?);
 
-        log::debug!("Reading from {input_kind:?} file {}", self.input.display());
-        let mut dom = match input_kind {
-            ModelKind::Xml => {
-                let options = rbx_xml::DecodeOptions::new()
-                    .property_behavior(rbx_xml::DecodePropertyBehavior::ReadUnknown);
-
-                rbx_xml::from_reader(input_file, options)
-                    .with_context(|| format!("Failed to read {}", self.input.display()))?
-            }
-
-            ModelKind::Binary => rbx_binary::from_reader(input_file)
-                .with_context(|| format!("Failed to read {}", self.input.display()))?,
-        };
+        log::debug!("Reading from file {}", self.input.display());
+        let mut dom = from_reader_auto(input_file)
+            .with_context(|| format!("Failed to read {}", self.input.display()))?;
 
         let mut queue = vec![dom.root_ref()];
         while let Some(referent) = queue.pop() {
             let inst = dom.get_by_ref_mut(referent).unwrap();
             if inst.class == self.class_name {
                 log::trace!("Removed property {}.{}", inst.name, self.prop_name);
-                inst.properties.remove(&self.prop_name.as_str().into());
+                inst.properties.remove(self.prop_name.as_str());
             }
             queue.extend_from_slice(inst.children());
         }
@@ -5,8 +5,9 @@ use std::{
 
 use clap::Parser;
 use fs_err::File;
+use rbx_file::ModelKind;
 
-use crate::ModelKind;
+use crate::model_kind_from_path;
 
 #[derive(Debug, Parser)]
 pub struct ViewBinaryCommand {
@@ -16,7 +17,7 @@ pub struct ViewBinaryCommand {
 
 impl ViewBinaryCommand {
     pub fn run(&self) -> anyhow::Result<()> {
-        let input_kind = ModelKind::from_path(&self.input)?;
+        let input_kind = model_kind_from_path(&self.input)?;
 
         if input_kind != ModelKind::Binary {
             anyhow::bail!("not a binary model or place file: {}", self.input.display());
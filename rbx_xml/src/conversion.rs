@@ -4,39 +4,38 @@
 use std::borrow::{Borrow, Cow};
 use std::convert::TryInto;
 
-use rbx_dom_weak::types::{ContentId, ContentType, Enum};
-use rbx_dom_weak::{
-    types::{Attributes, BrickColor, Color3uint8, MaterialColors, Tags, Variant, VariantType},
-    Ustr,
+use rbx_dom_weak::types::{
+    Attributes, BrickColor, Color3uint8, MaterialColors, Tags, Variant, VariantType,
 };
+use rbx_dom_weak::types::{ContentId, ContentType, Enum};
 
 pub trait ConvertVariant: Clone + Sized {
-    fn try_convert(self, class_name: Ustr, target_type: VariantType) -> Result<Self, String> {
+    fn try_convert(self, class_name: &str, target_type: VariantType) -> Result<Self, String> {
         Self::try_convert_cow(class_name, Cow::Owned(self), target_type)
             .map(|value| value.into_owned())
     }
 
     fn try_convert_ref(
         &self,
-        class_name: Ustr,
+        class_name: &str,
         target_type: VariantType,
     ) -> Result<Cow<'_, Self>, String> {
         Self::try_convert_cow(class_name, Cow::Borrowed(self), target_type)
     }
 
-    fn try_convert_cow(
-        class_name: Ustr,
-        value: Cow<'_, Self>,
+    fn try_convert_cow<'a>(
+        class_name: &str,
+        value: Cow<'a, Self>,
         target_type: VariantType,
-    ) -> Result<Cow<'_, Self>, String>;
+    ) -> Result<Cow<'a, Self>, String>;
 }
 
 impl ConvertVariant for Variant {
-    fn try_convert_cow(
-        class_name: Ustr,
-        value: Cow<'_, Self>,
+    fn try_convert_cow<'a>(
+        class_name: &str,
+        value: Cow<'a, Self>,
         target_type: VariantType,
-    ) -> Result<Cow<'_, Self>, String> {
+    ) -> Result<Cow<'a, Self>, String> {
         match (value.borrow(), target_type) {
             // Older files may not have their number types moved to 64-bit yet,
             // which can cause problems. See issue #301.
@@ -4,7 +4,7 @@ use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use log::trace;
 use rbx_dom_weak::{
     types::{Ref, SharedString, Variant, VariantType},
-    InstanceBuilder, Ustr, WeakDom,
+    InstanceBuilder, Symbol, WeakDom,
 };
 use rbx_reflection::{DataType, PropertyKind, PropertySerialization, ReflectionDatabase};
 
@@ -12,18 +12,39 @@ use crate::{
     conversion::ConvertVariant,
     core::find_canonical_property_descriptor,
     error::{DecodeError, DecodeErrorKind},
+    layout::XmlLayout,
     types::read_value_xml,
 };
 
 use crate::deserializer_core::{XmlEventReader, XmlReadEvent};
 
 pub fn decode_internal<R: Read>(source: R, options: DecodeOptions) -> Result<WeakDom, DecodeError> {
+    decode(source, options, None)
+}
+
+/// Decodes a file like `decode_internal`, also recording the referent string
+/// and property order of each instance in it.
+pub fn decode_with_layout_internal<R: Read>(
+    source: R,
+    options: DecodeOptions,
+) -> Result<(WeakDom, XmlLayout), DecodeError> {
+    let mut layout = XmlLayout::new();
+    let tree = decode(source, options, Some(&mut layout))?;
+
+    Ok((tree, layout))
+}
+
+fn decode<R: Read>(
+    source: R,
+    options: DecodeOptions,
+    layout: Option<&mut XmlLayout>,
+) -> Result<WeakDom, DecodeError> {
     let mut tree = WeakDom::new(InstanceBuilder::new("DataModel"));
 
     let root_id = tree.root_ref();
 
     let mut iterator = XmlEventReader::from_source(source);
-    let mut state = ParseState::new(&mut tree, options);
+    let mut state = ParseState::new(&mut tree, options, layout);
 
     deserialize_root(&mut iterator, &mut state, root_id)?;
     apply_referent_rewrites(&mut state);
@@ -118,11 +139,6 @@ pub struct ParseState<'dom, 'db> {
 
     options: DecodeOptions<'db>,
 
-    /// Metadata deserialized from 'Meta' fields in the file.
-    /// Known fields are:
-    /// - ExplicitAutoJoints
-    metadata: HashMap<String, String>,
-
     /// A map referent strings to IDs. This map is filled up as instances are
     /// deserialized, and referred to when filling out Ref properties.
     ///
@@ -147,31 +163,47 @@ pub struct ParseState<'dom, 'db> {
     /// Contains all of the unknown types that have been found so far. Tracking
     /// them here helps ensure that we only output a warning once per type.
     unknown_type_names: HashSet<String>,
+
+    /// Where to record the referent string and property order of each
+    /// instance, if the caller asked for them.
+    layout: Option<&'dom mut XmlLayout>,
 }
 
 struct ReferentRewrite {
     id: Ref,
-    property_name: Ustr,
+    property_name: Symbol,
     referent_value: String,
 }
 
 struct SharedStringRewrite {
     id: Ref,
-    property_name: Ustr,
+    property_name: Symbol,
     shared_string_hash: String,
 }
 
 impl<'dom, 'db> ParseState<'dom, 'db> {
-    fn new(tree: &'dom mut WeakDom, options: DecodeOptions<'db>) -> ParseState<'dom, 'db> {
+    fn new(
+        tree: &'dom mut WeakDom,
+        options: DecodeOptions<'db>,
+        layout: Option<&'dom mut XmlLayout>,
+    ) -> ParseState<'dom, 'db> {
         ParseState {
             tree,
             options,
-            metadata: HashMap::new(),
             referents_to_ids: HashMap::new(),
             referent_rewrites: Vec::new(),
             known_shared_strings: HashMap::new(),
             shared_string_rewrites: Vec::new(),
             unknown_type_names: HashSet::new(),
+            layout,
+        }
+    }
+
+    /// Records that the instance has the property `name`, which was written
+    /// after the properties recorded for it so far.
+    fn record_property(&mut self, id: Ref, name: Symbol) {
+        if let Some(layout) = &mut self.layout {
+            layout.add_property(id, name);
         }
     }
 
@@ -197,7 +229,7 @@ impl<'dom, 'db> ParseState<'dom, 'db> {
     /// have a complete view of how referents map to Ref values.
     ///
     /// This is used to deserialize non-null Ref values correctly.
-    pub fn add_referent_rewrite(&mut self, id: Ref, property_name: Ustr, referent_value: String) {
+    pub fn add_referent_rewrite(&mut self, id: Ref, property_name: Symbol, referent_value: String) {
         self.referent_rewrites.push(ReferentRewrite {
             id,
             property_name,
@@ -212,7 +244,7 @@ impl<'dom, 'db> ParseState<'dom, 'db> {
     pub fn add_shared_string_rewrite(
         &mut self,
         id: Ref,
-        property_name: Ustr,
+        property_name: Symbol,
         shared_string_hash: String,
     ) {
         self.shared_string_rewrites.push(SharedStringRewrite {
@@ -311,14 +343,9 @@ fn deserialize_root<R: Read>(
                     }
                 }
             }
-            XmlReadEvent::EndElement { name } => {
-                if name.local_name == "roblox" {
-                    reader.expect_next().unwrap();
-                    break;
-                } else {
-                    let event = reader.expect_next().unwrap();
-                    return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
-                }
+            XmlReadEvent::EndElement { name } if name.local_name == "roblox" => {
+                reader.expect_next().unwrap();
+                break;
             }
             XmlReadEvent::EndDocument => break,
             _ => {
@@ -352,7 +379,7 @@ fn deserialize_metadata<R: Read>(
     let value = reader.read_characters()?;
     reader.expect_end_with_name("Meta")?;
 
-    state.metadata.insert(name, value);
+    state.tree.metadata_mut().insert(name, value);
     Ok(())
 }
 
@@ -364,21 +391,11 @@ fn deserialize_shared_string_dict<R: Read>(
 
     loop {
         match reader.expect_peek()? {
-            XmlReadEvent::StartElement { name, .. } => {
-                if name.local_name == "SharedString" {
-                    deserialize_shared_string(reader, state)?;
-                } else {
-                    let event = reader.expect_next().unwrap();
-                    return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
-                }
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "SharedString" => {
+                deserialize_shared_string(reader, state)?;
             }
-            XmlReadEvent::EndElement { name } => {
-                if name.local_name == "SharedStrings" {
-                    break;
-                } else {
-                    let event = reader.expect_next().unwrap();
-                    return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
-                }
+            XmlReadEvent::EndElement { name } if name.local_name == "SharedStrings" => {
+                break;
             }
             _ => {
                 let event = reader.expect_next().unwrap();
@@ -457,10 +474,14 @@ fn deserialize_instance<R: Read>(
     let instance_id = state.tree.insert(parent_id, builder);
 
     if let Some(referent) = referent {
+        if let Some(layout) = &mut state.layout {
+            layout.set_referent(instance_id, referent.clone());
+        }
+
         state.referents_to_ids.insert(referent, instance_id);
     }
 
-    let mut properties: HashMap<Ustr, Variant> = HashMap::new();
+    let mut properties: HashMap<Symbol, Variant> = HashMap::new();
 
     loop {
         match reader.expect_peek()? {
@@ -495,7 +516,7 @@ fn deserialize_instance<R: Read>(
 
     let instance = state.tree.get_by_ref_mut(instance_id).unwrap();
 
-    instance.name = match properties.remove(&"Name".into()) {
+    instance.name = match properties.remove("Name") {
         Some(value) => match value {
             Variant::String(value) => value,
             _ => return Err(reader.error(DecodeErrorKind::NameMustBeString(value.ty()))),
@@ -516,7 +537,7 @@ fn deserialize_properties<R: Read>(
     reader: &mut XmlEventReader<R>,
     state: &mut ParseState,
     instance_id: Ref,
-    props: &mut HashMap<Ustr, Variant>,
+    props: &mut HashMap<Symbol, Variant>,
 ) -> Result<(), DecodeError> {
     reader.expect_start_with_name("Properties")?;
 
@@ -524,7 +545,8 @@ fn deserialize_properties<R: Read>(
         .tree
         .get_by_ref(instance_id)
         .expect("Couldn't find instance to deserialize properties into")
-        .class;
+        .class
+        .clone();
 
     log::trace!(
         "Deserializing properties for instance {instance_id:?}, whose ClassName is {class_name}"
@@ -552,14 +574,9 @@ fn deserialize_properties<R: Read>(
 
                     (name.local_name.to_owned(), xml_property_name)
                 }
-                XmlReadEvent::EndElement { name } => {
-                    if name.local_name == "Properties" {
-                        reader.expect_next()?;
-                        return Ok(());
-                    } else {
-                        let err = DecodeErrorKind::UnexpectedXmlEvent(reader.expect_next()?);
-                        return Err(reader.error(err));
-                    }
+                XmlReadEvent::EndElement { name } if name.local_name == "Properties" => {
+                    reader.expect_next()?;
+                    return Ok(());
                 }
                 _ => {
                     let err = DecodeErrorKind::UnexpectedXmlEvent(reader.expect_next()?);
@@ -583,6 +600,14 @@ fn deserialize_properties<R: Read>(
         };
 
         if let Some(descriptor) = maybe_descriptor {
+            let property_name = match &descriptor.kind {
+                PropertyKind::Canonical {
+                    serialization: PropertySerialization::Migrate(migration),
+                } => migration.new_property_name.as_ref(),
+                _ => descriptor.name.as_ref(),
+            };
+            state.record_property(instance_id, property_name.into());
+
             let value =
                 match read_value_xml(reader, state, &xml_type_name, instance_id, &descriptor.name)?
                 {
@@ -608,7 +633,7 @@ fn deserialize_properties<R: Read>(
             };
             log::trace!("property's read type: {xml_ty:?}, canonical type: {expected_type:?}");
 
-            let value = match value.try_convert(class_name, expected_type) {
+            let value = match value.try_convert(&class_name, expected_type) {
                 Ok(value) => value,
 
                 // The property descriptor disagreed, and there was no
@@ -672,6 +697,8 @@ fn deserialize_properties<R: Read>(
                     // We'll take this value as-is with no conversions on either
                     // the name or value.
 
+                    state.record_property(instance_id, xml_property_name.as_str().into());
+
                     let value = match read_value_xml(
                         reader,
                         state,
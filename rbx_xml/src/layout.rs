@@ -0,0 +1,96 @@
+use ahash::HashMap;
+use rbx_dom_weak::{types::Ref, Symbol};
+
+/// How the instances in an XML file were laid out: the referent string each
+/// `<Item>` had, and the order its properties were written in.
+///
+/// A layout is returned alongside the tree by
+/// [`from_reader_with_layout`][crate::from_reader_with_layout] and
+/// [`from_str_with_layout`][crate::from_str_with_layout]. Passing it to
+/// [`EncodeOptions::layout`][crate::EncodeOptions::layout] makes the
+/// serializer write each instance back out with its original referent and
+/// property order, so that a file that is read and written again without
+/// changes comes out the same, and a file that is changed only differs where
+/// it was changed. Child order is always kept, since it's part of the tree.
+///
+/// ```
+/// let document = r#"<roblox version="4">
+///   <Item class="Folder" referent="RBXA1B2C3">
+///     <Properties>
+///       <string name="Name">Stuff</string>
+///     </Properties>
+///   </Item>
+/// </roblox>"#;
+///
+/// let (dom, layout) = rbx_xml::from_str_with_layout(document, Default::default())?;
+///
+/// let mut output = Vec::new();
+/// let options = rbx_xml::EncodeOptions::new().layout(&layout);
+/// rbx_xml::to_writer(&mut output, &dom, dom.root().children(), options)?;
+///
+/// assert!(String::from_utf8(output)?.contains(r#"referent="RBXA1B2C3""#));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct XmlLayout {
+    instances: HashMap<Ref, InstanceLayout>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct InstanceLayout {
+    referent: Option<String>,
+    properties: Vec<Symbol>,
+}
+
+impl XmlLayout {
+    /// Creates an empty `XmlLayout`, which leaves the serializer to choose
+    /// referents and property order for every instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the referent string the instance with the given referent had
+    /// in the file it was read from, if it had one.
+    pub fn referent_of(&self, referent: Ref) -> Option<&str> {
+        self.instances.get(&referent)?.referent.as_deref()
+    }
+
+    /// Returns the names of the properties the instance with the given
+    /// referent had in the file it was read from, in the order they were
+    /// written. Names are the ones the properties have in the tree, which
+    /// aren't always the ones they have in XML, and include `Name`.
+    pub fn property_order_of(&self, referent: Ref) -> &[Symbol] {
+        match self.instances.get(&referent) {
+            Some(instance) => &instance.properties,
+            None => &[],
+        }
+    }
+
+    pub(crate) fn set_referent(&mut self, id: Ref, referent: String) {
+        self.instances.entry(id).or_default().referent = Some(referent);
+    }
+
+    pub(crate) fn add_property(&mut self, id: Ref, name: Symbol) {
+        let properties = &mut self.instances.entry(id).or_default().properties;
+
+        if !properties.contains(&name) {
+            properties.push(name);
+        }
+    }
+
+    /// Returns where the property `name` of the instance `id` should be
+    /// written relative to its other properties. Properties that aren't in
+    /// the layout come after the ones that are.
+    pub(crate) fn property_position(&self, id: Ref, name: &str) -> usize {
+        self.property_order_of(id)
+            .iter()
+            .position(|property| property.as_str() == name)
+            .unwrap_or(usize::MAX)
+    }
+
+    pub(crate) fn referents(&self) -> impl Iterator<Item = &str> {
+        self.instances
+            .values()
+            .filter_map(|instance| instance.referent.as_deref())
+    }
+}
@@ -14,7 +14,7 @@
 //! pass in custom options.
 //!
 //! ```
-//! use rbx_dom_weak::{ustr, types::Variant};
+//! use rbx_dom_weak::{symbol, types::Variant};
 //!
 //! let model_file = r#"
 //! <roblox version="4">
@@ -34,7 +34,7 @@
 //! let number_value = model.get_by_ref(number_value_ref).unwrap();
 //!
 //! assert_eq!(
-//!     number_value.properties.get(&ustr("Value")),
+//!     number_value.properties.get("Value"),
 //!     Some(&Variant::Float64(12345.0)),
 //! );
 //! # Ok::<(), Box<dyn std::error::Error>>(())
@@ -114,6 +114,7 @@ mod core;
 mod deserializer;
 mod deserializer_core;
 mod error;
+mod layout;
 mod serializer;
 mod serializer_core;
 mod types;
@@ -127,11 +128,15 @@ use std::io::{Read, Write};
 
 use rbx_dom_weak::{types::Ref, WeakDom};
 
-use crate::{deserializer::decode_internal, serializer::encode_internal};
+use crate::{
+    deserializer::{decode_internal, decode_with_layout_internal},
+    serializer::encode_internal,
+};
 
 pub use crate::{
     deserializer::{DecodeOptions, DecodePropertyBehavior},
     error::{DecodeError, EncodeError},
+    layout::XmlLayout,
     serializer::{EncodeOptions, EncodePropertyBehavior},
 };
 
@@ -158,6 +163,30 @@ pub fn from_str_default<S: AsRef<str>>(reader: S) -> Result<WeakDom, DecodeError
     decode_internal(reader.as_ref().as_bytes(), DecodeOptions::default())
 }
 
+/// Decodes an XML-format model or place from something that implements the
+/// `std::io::Read` trait, along with the referent strings and property order
+/// of its instances.
+///
+/// Passing the returned [`XmlLayout`] to [`EncodeOptions::layout`] writes the
+/// instances back out the same way, so that files kept in version control
+/// don't change more than they need to.
+pub fn from_reader_with_layout<R: Read>(
+    reader: R,
+    options: DecodeOptions,
+) -> Result<(WeakDom, XmlLayout), DecodeError> {
+    decode_with_layout_internal(reader, options)
+}
+
+/// Decodes an XML-format model or place from a string, along with the
+/// referent strings and property order of its instances. See
+/// [`from_reader_with_layout`].
+pub fn from_str_with_layout<S: AsRef<str>>(
+    reader: S,
+    options: DecodeOptions,
+) -> Result<(WeakDom, XmlLayout), DecodeError> {
+    decode_with_layout_internal(reader.as_ref().as_bytes(), options)
+}
+
 /// Serializes a subset of the given tree to an XML format model or place,
 /// writing to something that implements the `std::io::Write` trait.
 pub fn to_writer<W: Write>(
@@ -1,9 +1,10 @@
 use std::{borrow::Cow, collections::BTreeMap, io::Write};
 
-use ahash::{HashMap, HashMapExt};
+use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use rbx_dom_weak::{
+    is_identity_property,
     types::{Ref, SharedString, SharedStringHash, Variant, VariantType},
-    WeakDom,
+    Instance, WeakDom,
 };
 use rbx_reflection::{DataType, PropertyKind, PropertySerialization, ReflectionDatabase};
 
@@ -11,6 +12,7 @@ use crate::{
     conversion::ConvertVariant,
     core::find_serialized_property_descriptor,
     error::{EncodeError as NewEncodeError, EncodeErrorKind},
+    layout::XmlLayout,
     types::write_value_xml,
 };
 
@@ -27,6 +29,8 @@ pub fn encode_internal<W: Write>(
 
     writer.write(XmlWriteEvent::start_element("roblox").attr("version", "4"))?;
 
+    serialize_metadata(&mut writer, tree)?;
+
     let mut property_buffer = Vec::new();
     for id in ids {
         serialize_instance(&mut writer, &mut state, tree, *id, &mut property_buffer)?;
@@ -74,6 +78,7 @@ pub enum EncodePropertyBehavior {
 pub struct EncodeOptions<'db> {
     property_behavior: EncodePropertyBehavior,
     database: &'db ReflectionDatabase<'db>,
+    layout: Option<&'db XmlLayout>,
 }
 
 impl<'db> EncodeOptions<'db> {
@@ -83,6 +88,7 @@ impl<'db> EncodeOptions<'db> {
         EncodeOptions {
             property_behavior: EncodePropertyBehavior::IgnoreUnknown,
             database: rbx_reflection_database::get().unwrap(),
+            layout: None,
         }
     }
 
@@ -103,6 +109,24 @@ impl<'db> EncodeOptions<'db> {
         EncodeOptions { database, ..self }
     }
 
+    /// Makes rbx_xml write instances with the referent strings and property
+    /// order they had in the file `layout` was read from. See [`XmlLayout`].
+    ///
+    /// Instances that aren't in the layout, or whose referent string is
+    /// already taken, are given new referents that don't clash with any in
+    /// the layout. Properties that aren't in the layout are written after the
+    /// ones that are, sorted by name.
+    ///
+    /// By default, instances are given new referents and their properties
+    /// are sorted by name, with `Name` first.
+    #[inline]
+    pub fn layout(self, layout: &'db XmlLayout) -> Self {
+        EncodeOptions {
+            layout: Some(layout),
+            ..self
+        }
+    }
+
     pub(crate) fn use_reflection(&self) -> bool {
         self.property_behavior != EncodePropertyBehavior::NoReflection
     }
@@ -119,11 +143,19 @@ pub struct EmitState<'db> {
 
     /// A map of IDs written so far to the generated referent that they use.
     /// This map is used to correctly emit Ref properties.
-    referent_map: HashMap<Ref, u32>,
+    referent_map: HashMap<Ref, String>,
 
-    /// The referent value that will be used for emitting the next instance.
+    /// The referent value that will be used for emitting the next instance
+    /// that doesn't keep its referent from the layout.
     next_referent: u32,
 
+    /// Every referent string in the layout, which generated referents must
+    /// not clash with.
+    reserved_referents: HashSet<String>,
+
+    /// The referent strings from the layout that have been used so far.
+    used_referents: HashSet<String>,
+
     /// A map of all shared strings referenced so far while generating XML. This
     /// map will be written as the file's SharedString dictionary.
     shared_strings_to_emit: BTreeMap<SharedStringHash, SharedString>,
@@ -132,23 +164,46 @@ pub struct EmitState<'db> {
 impl<'db> EmitState<'db> {
     pub fn new(options: EncodeOptions<'db>) -> EmitState<'db> {
         EmitState {
-            options,
             referent_map: HashMap::new(),
             next_referent: 0,
+            reserved_referents: options
+                .layout
+                .map(|layout| layout.referents().map(str::to_owned).collect())
+                .unwrap_or_default(),
+            used_referents: HashSet::new(),
             shared_strings_to_emit: BTreeMap::new(),
+            options,
         }
     }
 
-    pub fn map_id(&mut self, id: Ref) -> u32 {
-        match self.referent_map.get(&id) {
-            Some(&value) => value,
-            None => {
-                let referent = self.next_referent;
-                self.referent_map.insert(id, referent);
-                self.next_referent += 1;
-                referent
-            }
+    pub fn map_id(&mut self, id: Ref) -> String {
+        if let Some(value) = self.referent_map.get(&id) {
+            return value.clone();
         }
+
+        let preserved = self
+            .options
+            .layout
+            .and_then(|layout| layout.referent_of(id))
+            .filter(|referent| !self.used_referents.contains(*referent));
+
+        let referent = match preserved {
+            Some(referent) => {
+                self.used_referents.insert(referent.to_owned());
+                referent.to_owned()
+            }
+            None => loop {
+                let referent = self.next_referent.to_string();
+                self.next_referent += 1;
+
+                if !self.reserved_referents.contains(&referent) {
+                    break referent;
+                }
+            },
+        };
+
+        self.referent_map.insert(id, referent.clone());
+        referent
     }
 
     pub fn add_shared_string(&mut self, value: SharedString) {
@@ -173,24 +228,50 @@ fn serialize_instance<'dom, W: Write>(
     writer.write(
         XmlWriteEvent::start_element("Item")
             .attr("class", &instance.class)
-            .attr("referent", &mapped_id.to_string()),
+            .attr("referent", &mapped_id),
     )?;
 
     writer.write(XmlWriteEvent::start_element("Properties"))?;
 
-    write_value_xml(
-        writer,
-        state,
-        "Name",
-        &Variant::String(instance.name.clone()),
-    )?;
-
     // Move references to our properties into property_buffer so we can sort
-    // them and iterate them in order.
-    property_buffer.extend(instance.properties.iter().map(|(k, v)| (k.as_str(), v)));
-    property_buffer.sort_unstable_by_key(|(key, _)| *key);
+    // them and iterate them in order. Copies of the instance's name and class
+    // exposed as properties are skipped, since the fields are written instead.
+    property_buffer.extend(
+        instance
+            .properties
+            .iter()
+            .filter(|(k, _)| !is_identity_property(k))
+            .map(|(k, v)| (k.as_str(), v)),
+    );
+
+    // With a layout, Name and the other properties are written in the order
+    // they were read in. Name is written first if the layout doesn't say
+    // where it goes.
+    let layout = state.options.layout;
+    let mut name_position = match layout {
+        Some(layout) => {
+            property_buffer
+                .sort_by_cached_key(|(key, _)| (layout.property_position(id, key), *key));
+            Some(layout.property_position(id, "Name")).filter(|&position| position != usize::MAX)
+        }
+        None => {
+            property_buffer.sort_unstable_by_key(|(key, _)| *key);
+            None
+        }
+    };
+
+    if name_position.is_none() {
+        write_name(writer, state, instance)?;
+    }
 
     for (property_name, value) in property_buffer.drain(..) {
+        if let (Some(layout), Some(position)) = (layout, name_position) {
+            if layout.property_position(id, property_name) > position {
+                write_name(writer, state, instance)?;
+                name_position = None;
+            }
+        }
+
         let maybe_serialized_descriptor = if state.options.use_reflection() {
             find_serialized_property_descriptor(
                 &instance.class,
@@ -210,7 +291,7 @@ fn serialize_instance<'dom, W: Write>(
 
             let mut serialized_name = serialized_descriptor.name.as_ref();
 
-            let mut converted_value = match value.try_convert_ref(instance.class, data_type) {
+            let mut converted_value = match value.try_convert_ref(&instance.class, data_type) {
                 Ok(value) => value,
                 Err(message) => {
                     return Err(
@@ -258,6 +339,10 @@ fn serialize_instance<'dom, W: Write>(
         }
     }
 
+    if name_position.is_some() {
+        write_name(writer, state, instance)?;
+    }
+
     writer.write(XmlWriteEvent::end_element())?;
 
     for child_id in instance.children() {
@@ -269,6 +354,32 @@ fn serialize_instance<'dom, W: Write>(
     Ok(())
 }
 
+fn write_name<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    state: &mut EmitState,
+    instance: &Instance,
+) -> Result<(), NewEncodeError> {
+    write_value_xml(
+        writer,
+        state,
+        "Name",
+        &Variant::String(instance.name.clone()),
+    )
+}
+
+fn serialize_metadata<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    tree: &WeakDom,
+) -> Result<(), NewEncodeError> {
+    for (name, value) in tree.metadata().iter() {
+        writer.write(XmlWriteEvent::start_element("Meta").attr("name", name))?;
+        writer.write_string(value)?;
+        writer.end_element()?;
+    }
+
+    Ok(())
+}
+
 fn serialize_shared_strings<W: Write>(
     writer: &mut XmlEventWriter<W>,
     state: &mut EmitState,
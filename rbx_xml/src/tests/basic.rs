@@ -1,12 +1,12 @@
 //! Basic functionality tests
 
 use rbx_dom_weak::types::{
-    Attributes, BinaryString, BrickColor, Color3, Color3uint8, ColorSequence,
-    ColorSequenceKeypoint, Enum, EnumItem, Font, MaterialColors, NumberRange, NumberSequence,
-    NumberSequenceKeypoint, Rect, Tags, TerrainMaterials, UDim, UDim2, UniqueId, Variant,
-    VariantType, Vector2, Vector3,
+    Attributes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence,
+    ColorSequenceKeypoint, Enum, EnumItem, Font, FontStyle, FontWeight, MaterialColors, Matrix3,
+    NumberRange, NumberSequence, NumberSequenceKeypoint, Rect, SecurityCapabilities, Tags,
+    TerrainMaterials, UDim, UDim2, UniqueId, Variant, VariantType, Vector2, Vector3,
 };
-use rbx_dom_weak::{ustr, InstanceBuilder, WeakDom};
+use rbx_dom_weak::{symbol, InstanceBuilder, WeakDom};
 
 #[test]
 fn with_bool() {
@@ -29,10 +29,7 @@ fn with_bool() {
 
     assert_eq!(child.name, "BoolValue");
     assert_eq!(child.class, "BoolValue");
-    assert_eq!(
-        child.properties.get(&"Value".into()),
-        Some(&Variant::Bool(true))
-    );
+    assert_eq!(child.properties.get("Value"), Some(&Variant::Bool(true)));
 }
 
 #[test]
@@ -56,10 +53,7 @@ fn read_tags() {
     tags.push("Hello");
     tags.push("World");
 
-    assert_eq!(
-        folder.properties.get(&"Tags".into()),
-        Some(&Variant::Tags(tags))
-    );
+    assert_eq!(folder.properties.get("Tags"), Some(&Variant::Tags(tags)));
 }
 
 #[test]
@@ -115,8 +109,8 @@ fn read_attributes() {
     let dom = crate::from_str_default(document).unwrap();
     let folder = dom.get_by_ref(dom.root().children()[0]).unwrap();
 
-    assert_eq!(folder.properties.get(&"AttributesSerialize".into()), None);
-    let folder_attributes = match folder.properties.get(&"Attributes".into()) {
+    assert_eq!(folder.properties.get("AttributesSerialize"), None);
+    let folder_attributes = match folder.properties.get("Attributes") {
         Some(Variant::Attributes(attrs)) => attrs,
         Some(other) => panic!(
             "Attributes property was not Attributes, it was: {:?}",
@@ -226,8 +220,7 @@ fn read_material_colors() {
     let dom = crate::from_str_default(document).unwrap();
     let terrain = dom.get_by_ref(dom.root().children()[0]).unwrap();
 
-    if let Some(Variant::MaterialColors(colors)) = terrain.properties.get(&"MaterialColors".into())
-    {
+    if let Some(Variant::MaterialColors(colors)) = terrain.properties.get("MaterialColors") {
         // There are tests to ensure competency in the actual MaterialColors
         // implementation, so these are just basic "are you ok" checks.
         assert_eq!(
@@ -245,7 +238,7 @@ fn read_material_colors() {
     } else {
         panic!(
             "MaterialColors was not Some(Variant::MaterialColors(_)) and was instead {:?}",
-            terrain.properties.get(&"MaterialColors".into())
+            terrain.properties.get("MaterialColors")
         )
     }
 }
@@ -283,7 +276,7 @@ fn read_unique_id() {
     assert_eq!(child.class, "Workspace");
 
     assert_eq!(
-        child.properties.get(&"UniqueId".into()),
+        child.properties.get("UniqueId"),
         Some(&Variant::UniqueId(UniqueId::new(
             0x0048_15fc,
             0x02e9_c68d,
@@ -314,13 +307,13 @@ fn number_widening() {
     let int_value = tree.get_by_ref(tree.root().children()[0]).unwrap();
     assert_eq!(int_value.class, "IntValue");
     assert_eq!(
-        int_value.properties.get(&"Value".into()),
+        int_value.properties.get("Value"),
         Some(&Variant::Int64(194))
     );
     let float_value = tree.get_by_ref(tree.root().children()[1]).unwrap();
     assert_eq!(float_value.class, "NumberValue");
     assert_eq!(
-        float_value.properties.get(&"Value".into()),
+        float_value.properties.get("Value"),
         Some(&Variant::Float64(1337.0))
     );
 }
@@ -371,9 +364,153 @@ fn enum_item_to_enum() {
         .get_by_ref(*decoded.root().children().first().unwrap())
         .unwrap()
         .properties
-        .get(&ustr("Material"))
+        .get("Material")
         .unwrap()
         .ty();
 
     assert_eq!(prop_type, VariantType::Enum);
 }
+
+#[test]
+fn metadata_round_trip() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Meta name="ExplicitAutoJoints">true</Meta>
+            <Item class="Folder" referent="hello" />
+        </roblox>
+    "#;
+
+    let dom = crate::from_str_default(document).unwrap();
+    assert_eq!(dom.metadata().explicit_auto_joints(), Some(true));
+
+    let mut encoded = Vec::new();
+    crate::to_writer_default(&mut encoded, &dom, dom.root().children()).unwrap();
+
+    let decoded = crate::from_reader_default(encoded.as_slice()).unwrap();
+    assert_eq!(decoded.metadata(), dom.metadata());
+}
+
+#[test]
+fn newer_property_types() {
+    let _ = env_logger::try_init();
+
+    let font = Font {
+        family: "rbxasset://fonts/families/GothamSSm.json".to_owned(),
+        weight: FontWeight::Bold,
+        style: FontStyle::Italic,
+        cached_face_id: Some("rbxasset://fonts/GothamSSm-BoldItalic.otf".to_owned()),
+    };
+    let pivot = CFrame::new(
+        Vector3::new(1.0, 2.0, 3.0),
+        Matrix3::new(
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ),
+    );
+    let unique_id = UniqueId::new(0x0048_15fc, 0x02e9_c68d, 0x44b1_88da_ce63_2b47);
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Model")
+            .with_property("WorldPivotData", Variant::OptionalCFrame(Some(pivot)))
+            .with_property("UniqueId", unique_id)
+            .with_property("Capabilities", SecurityCapabilities::from_bits(0b1011))
+            .with_child(
+                InstanceBuilder::new("TextLabel")
+                    .with_property("FontFace", font.clone())
+                    .with_property("UniqueId", UniqueId::nil()),
+            )
+            .with_child(
+                InstanceBuilder::new("Model")
+                    .with_property("WorldPivotData", Variant::OptionalCFrame(None)),
+            ),
+    );
+
+    let mut encoded = Vec::new();
+    crate::to_writer_default(&mut encoded, &tree, &[tree.root_ref()]).unwrap();
+    let decoded = crate::from_reader_default(encoded.as_slice()).unwrap();
+
+    let model = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let label = decoded.get_by_ref(model.children()[0]).unwrap();
+    let empty_model = decoded.get_by_ref(model.children()[1]).unwrap();
+
+    assert_eq!(
+        model.properties.get("WorldPivotData"),
+        Some(&Variant::OptionalCFrame(Some(pivot)))
+    );
+    assert_eq!(
+        model.properties.get("UniqueId"),
+        Some(&Variant::UniqueId(unique_id))
+    );
+    assert_eq!(
+        model.properties.get("Capabilities"),
+        Some(&Variant::SecurityCapabilities(
+            SecurityCapabilities::from_bits(0b1011)
+        ))
+    );
+    assert_eq!(label.properties.get("FontFace"), Some(&Variant::Font(font)));
+    assert_eq!(
+        label.properties.get("UniqueId"),
+        Some(&Variant::UniqueId(UniqueId::nil()))
+    );
+    assert_eq!(
+        empty_model.properties.get("WorldPivotData"),
+        Some(&Variant::OptionalCFrame(None))
+    );
+}
+
+#[test]
+fn layout_round_trip() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="ObjectValue" referent="RBX2">
+                <Properties>
+                    <Ref name="Value">0</Ref>
+                    <string name="Name">Pointer</string>
+                </Properties>
+            </Item>
+            <Item class="Folder" referent="0">
+                <Properties>
+                    <string name="Name">Target</string>
+                </Properties>
+            </Item>
+        </roblox>
+    "#;
+
+    let (mut dom, layout) =
+        crate::from_str_with_layout(document, crate::DecodeOptions::default()).unwrap();
+    let pointer_ref = dom.root().children()[0];
+    let target_ref = dom.root().children()[1];
+
+    assert_eq!(layout.referent_of(target_ref), Some("0"));
+    assert_eq!(
+        layout.property_order_of(pointer_ref),
+        [symbol("Value"), symbol("Name")]
+    );
+
+    let encode = |dom: &WeakDom, layout| {
+        let mut encoded = Vec::new();
+        let options = crate::EncodeOptions::new().layout(layout);
+        crate::to_writer(&mut encoded, dom, dom.root().children(), options).unwrap();
+        String::from_utf8(encoded).unwrap()
+    };
+
+    let encoded = encode(&dom, &layout);
+    assert!(encoded.contains(r#"referent="RBX2""#));
+    assert!(encoded.contains(r#"<Ref name="Value">0</Ref>"#));
+    assert!(encoded.find(r#"name="Value""#).unwrap() < encoded.find(r#"name="Name""#).unwrap());
+
+    // Writing a file read with its layout gives back the same file.
+    let (redecoded, relayout) =
+        crate::from_str_with_layout(&encoded, crate::DecodeOptions::default()).unwrap();
+    assert_eq!(encode(&redecoded, &relayout), encoded);
+
+    // New instances get referents that don't clash with the layout's.
+    dom.insert(target_ref, InstanceBuilder::new("Folder").with_name("New"));
+    let encoded = encode(&dom, &layout);
+    assert!(encoded.contains(r#"<Item class="Folder" referent="1">"#));
+}